@@ -0,0 +1,135 @@
+pub mod server;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
+use tokio::sync::{mpsc, oneshot};
+
+use crate::storage::TradeQuery;
+use crate::strategies::weekly_profiles::WeeklyBias;
+use crate::trading::kill_switch::KillSwitch;
+use crate::trading::paper_trader::Position;
+use crate::trading::trade_record::TradeRecord;
+
+/// Requests that touch the ledger or the exchange; the bot answers them at
+/// the start of its next tick.
+#[derive(Debug)]
+pub enum ApiCommand {
+    /// Close an open position at market; `None` if no such open position
+    ClosePosition {
+        id: u64,
+        reply: oneshot::Sender<Result<Option<Position>, String>>,
+    },
+    Trades {
+        query: TradeQuery,
+        reply: oneshot::Sender<Result<Vec<TradeRecord>, String>>,
+    },
+}
+
+/// One entry scale's alignment, as last logged on the dashboard.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ScaleAlignment {
+    pub scale: String,
+    pub aligned: bool,
+    pub direction: String,
+    /// Trend per alignment timeframe
+    pub trends: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SymbolStatus {
+    pub symbol: String,
+    /// Last 1m close
+    pub price: Option<f64>,
+    pub weekly_bias: Option<WeeklyBias>,
+    pub alignment: Vec<ScaleAlignment>,
+}
+
+/// Body of `GET /status`, published by the bot after every tick.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct BotStatus {
+    pub updated: Option<DateTime<Utc>>,
+    pub account: String,
+    pub paused: bool,
+    pub kill_switch_active: bool,
+    /// Drawdown circuit breaker halting entries
+    pub circuit_breaker_tripped: bool,
+    pub session: String,
+    pub balance: f64,
+    pub daily_pnl: f64,
+    pub realized_pnl: f64,
+    pub total_trades: usize,
+    pub open_positions: Vec<Position>,
+    pub symbols: Vec<SymbolStatus>,
+}
+
+/// State shared between the bot loop and the HTTP handlers.
+pub struct Shared {
+    status: RwLock<BotStatus>,
+    paused: AtomicBool,
+    kill_switch: KillSwitch,
+    commands: mpsc::Sender<ApiCommand>,
+    token: String,
+}
+
+/// The bot's end of the status/control API.
+pub struct BotApi {
+    shared: Arc<Shared>,
+    commands: mpsc::Receiver<ApiCommand>,
+    addr: SocketAddr,
+}
+
+impl BotApi {
+    /// Bind `addr` and serve in the background. With a non-empty `token`
+    /// every request needs `Authorization: Bearer <token>`. `POST /kill` and
+    /// `POST /unkill` engage and release `kill_switch`.
+    pub async fn serve(addr: &str, token: &str, kill_switch: KillSwitch) -> Result<Self> {
+        let (tx, rx) = mpsc::channel(16);
+        let shared = Arc::new(Shared {
+            status: RwLock::new(BotStatus::default()),
+            paused: AtomicBool::new(false),
+            kill_switch,
+            commands: tx,
+            token: token.to_string(),
+        });
+        let listener = tokio::net::TcpListener::bind(addr)
+            .await
+            .with_context(|| format!("binding API to {}", addr))?;
+        let addr = listener.local_addr()?;
+        let app = server::router(shared.clone());
+        tokio::spawn(async move {
+            if let Err(e) = axum::serve(listener, app).await {
+                tracing::error!("API server stopped: {}", e);
+            }
+        });
+        Ok(Self {
+            shared,
+            commands: rx,
+            addr,
+        })
+    }
+
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Set by `POST /pause`: no new entries until `POST /resume`.
+    pub fn is_paused(&self) -> bool {
+        self.shared.paused.load(Ordering::Relaxed)
+    }
+
+    pub fn publish(&self, status: BotStatus) {
+        if let Ok(mut s) = self.shared.status.write() {
+            *s = status;
+        }
+    }
+
+    /// Next pending command, if any.
+    pub fn try_command(&mut self) -> Option<ApiCommand> {
+        self.commands.try_recv().ok()
+    }
+}
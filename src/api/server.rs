@@ -0,0 +1,228 @@
+use axum::extract::{Path, Query, Request, State};
+use axum::http::{header, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde_json::{json, Value};
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::oneshot;
+
+use super::{ApiCommand, BotStatus, Shared};
+use crate::exchange::metrics as exchange_metrics;
+use crate::storage::TradeQuery;
+use crate::trading::paper_trader::Position;
+use crate::trading::trade_record::TradeRecord;
+
+/// How long a handler waits for the bot to pick up a command (a tick can
+/// include slow exchange calls).
+const COMMAND_TIMEOUT: Duration = Duration::from_secs(30);
+
+type ApiResult<T> = Result<Json<T>, Response>;
+
+fn error(status: StatusCode, message: &str) -> Response {
+    (status, Json(json!({ "error": message }))).into_response()
+}
+
+pub fn router(shared: Arc<Shared>) -> Router {
+    Router::new()
+        .route("/status", get(status))
+        .route("/trades", get(trades))
+        .route("/metrics", get(metrics))
+        .route("/positions/{id}/close", post(close_position))
+        .route("/pause", post(pause))
+        .route("/resume", post(resume))
+        .route("/kill", post(kill))
+        .route("/unkill", post(unkill))
+        .layer(middleware::from_fn_with_state(shared.clone(), auth))
+        .with_state(shared)
+}
+
+async fn auth(State(shared): State<Arc<Shared>>, req: Request, next: Next) -> Response {
+    if !shared.token.is_empty() {
+        let bearer = req
+            .headers()
+            .get(header::AUTHORIZATION)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.strip_prefix("Bearer "));
+        if bearer != Some(shared.token.as_str()) {
+            return error(StatusCode::UNAUTHORIZED, "missing or invalid token");
+        }
+    }
+    next.run(req).await
+}
+
+/// Hand a command to the bot loop and wait for its reply.
+async fn ask<T>(
+    shared: &Shared,
+    command: impl FnOnce(oneshot::Sender<Result<T, String>>) -> ApiCommand,
+) -> Result<T, Response> {
+    let (tx, rx) = oneshot::channel();
+    if shared.commands.send(command(tx)).await.is_err() {
+        return Err(error(StatusCode::SERVICE_UNAVAILABLE, "bot is not running"));
+    }
+    match tokio::time::timeout(COMMAND_TIMEOUT, rx).await {
+        Ok(Ok(Ok(value))) => Ok(value),
+        Ok(Ok(Err(e))) => Err(error(StatusCode::BAD_GATEWAY, &e)),
+        Ok(Err(_)) => Err(error(StatusCode::SERVICE_UNAVAILABLE, "bot is not running")),
+        Err(_) => Err(error(StatusCode::GATEWAY_TIMEOUT, "bot did not respond")),
+    }
+}
+
+async fn status(State(shared): State<Arc<Shared>>) -> Json<BotStatus> {
+    let mut status = shared.status.read().map(|s| s.clone()).unwrap_or_default();
+    // The flag flips immediately; the rest waits for the next tick
+    status.paused = shared.paused.load(Ordering::Relaxed);
+    status.kill_switch_active = shared.kill_switch.is_engaged();
+    Json(status)
+}
+
+/// Exchange endpoint latency, error and cache statistics.
+async fn metrics() -> Json<Value> {
+    Json(json!({ "exchange": exchange_metrics::global().snapshot() }))
+}
+
+async fn trades(
+    State(shared): State<Arc<Shared>>,
+    Query(query): Query<TradeQuery>,
+) -> ApiResult<Vec<TradeRecord>> {
+    ask(&shared, |reply| ApiCommand::Trades { query, reply })
+        .await
+        .map(Json)
+}
+
+async fn close_position(
+    State(shared): State<Arc<Shared>>,
+    Path(id): Path<u64>,
+) -> ApiResult<Position> {
+    match ask(&shared, |reply| ApiCommand::ClosePosition { id, reply }).await? {
+        Some(position) => Ok(Json(position)),
+        None => Err(error(
+            StatusCode::NOT_FOUND,
+            &format!("no open position #{}", id),
+        )),
+    }
+}
+
+async fn pause(State(shared): State<Arc<Shared>>) -> Json<Value> {
+    shared.paused.store(true, Ordering::Relaxed);
+    Json(json!({ "paused": true }))
+}
+
+async fn resume(State(shared): State<Arc<Shared>>) -> Json<Value> {
+    shared.paused.store(false, Ordering::Relaxed);
+    Json(json!({ "paused": false }))
+}
+
+/// Engage the kill switch; the bot flattens (if configured) and cancels
+/// resting limits on its next tick.
+async fn kill(State(shared): State<Arc<Shared>>) -> Json<Value> {
+    shared.kill_switch.engage();
+    Json(json!({ "kill_switch": shared.kill_switch.is_engaged() }))
+}
+
+/// Release the API flag. The switch stays engaged while the sentinel file
+/// exists.
+async fn unkill(State(shared): State<Arc<Shared>>) -> Json<Value> {
+    shared.kill_switch.release();
+    Json(json!({ "kill_switch": shared.kill_switch.is_engaged() }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::BotApi;
+    use super::*;
+    use crate::test_helpers::unique_test_config;
+    use crate::trading::kill_switch::KillSwitch;
+
+    #[tokio::test]
+    async fn serves_status_and_relays_commands_to_the_bot() {
+        let kill_switch = KillSwitch::new(&unique_test_config());
+        let mut api = BotApi::serve("127.0.0.1:0", "secret", kill_switch.clone())
+            .await
+            .unwrap();
+        api.publish(BotStatus {
+            account: "main".to_string(),
+            balance: 1000.0,
+            ..Default::default()
+        });
+        let base = format!("http://{}", api.addr());
+        let client = reqwest::Client::new();
+        let get = |path: &str| {
+            client
+                .get(format!("{}{}", base, path))
+                .bearer_auth("secret")
+        };
+        let post = |path: &str| {
+            client
+                .post(format!("{}{}", base, path))
+                .bearer_auth("secret")
+        };
+
+        let resp = client.get(format!("{}/status", base)).send().await.unwrap();
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+        let status: Value = get("/status").send().await.unwrap().json().await.unwrap();
+        assert_eq!(status["account"], "main");
+        assert_eq!(status["balance"], 1000.0);
+        assert_eq!(status["paused"], false);
+
+        crate::exchange::metrics::global().record("ticker", Duration::from_millis(5), true);
+        let metrics: Value = get("/metrics").send().await.unwrap().json().await.unwrap();
+        assert!(metrics["exchange"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|e| e["endpoint"] == "ticker"));
+
+        post("/pause").send().await.unwrap();
+        assert!(api.is_paused());
+        let status: Value = get("/status").send().await.unwrap().json().await.unwrap();
+        assert_eq!(status["paused"], true);
+        post("/resume").send().await.unwrap();
+        assert!(!api.is_paused());
+
+        let body: Value = post("/kill").send().await.unwrap().json().await.unwrap();
+        assert_eq!(body["kill_switch"], true);
+        assert!(kill_switch.is_engaged());
+        let status: Value = get("/status").send().await.unwrap().json().await.unwrap();
+        assert_eq!(status["kill_switch_active"], true);
+        let body: Value = post("/unkill").send().await.unwrap().json().await.unwrap();
+        assert_eq!(body["kill_switch"], false);
+        assert!(!kill_switch.is_engaged());
+
+        // Stand-in for the bot loop
+        tokio::spawn(async move {
+            loop {
+                while let Some(cmd) = api.try_command() {
+                    match cmd {
+                        ApiCommand::ClosePosition { reply, .. } => {
+                            let _ = reply.send(Ok(None));
+                        }
+                        ApiCommand::Trades { query, reply } => {
+                            assert_eq!(query.scale.as_deref(), Some("5m"));
+                            assert!(query.closed_only);
+                            let _ = reply.send(Ok(Vec::new()));
+                        }
+                    }
+                }
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        });
+
+        let resp = post("/positions/7/close").send().await.unwrap();
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+        let body: Value = resp.json().await.unwrap();
+        assert_eq!(body["error"], "no open position #7");
+
+        let resp = get("/trades?scale=5m&closed_only=true")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        let records: Vec<Value> = resp.json().await.unwrap();
+        assert!(records.is_empty());
+    }
+}
@@ -0,0 +1,210 @@
+use anyhow::Result;
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use super::monte_carlo::SplitMix64;
+use super::report::BacktestReport;
+use super::runner::BacktestRunner;
+use crate::config::Config;
+use crate::exchange::HistoricalExchange;
+use crate::trading::strategy_refiner::StrategyRefiner;
+
+/// Two config variants backtested side by side, with a paired bootstrap
+/// over daily PnL differences (B minus A).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AbComparison {
+    pub a: BacktestReport,
+    pub b: BacktestReport,
+    /// B's total PnL minus A's
+    pub pnl_diff: f64,
+    /// Days both equity curves cover
+    pub days: usize,
+    /// 95% bootstrap interval of the PnL difference
+    pub diff_ci_low: f64,
+    pub diff_ci_high: f64,
+    /// Two-sided bootstrap p-value for "no difference"
+    pub p_value: f64,
+    pub bootstrap_runs: usize,
+}
+
+impl AbComparison {
+    pub fn from_reports(a: BacktestReport, b: BacktestReport, runs: usize, seed: u64) -> Self {
+        let (daily_a, daily_b) = (daily_pnl(&a), daily_pnl(&b));
+        let diffs: Vec<f64> = daily_a
+            .iter()
+            .filter_map(|(day, pnl)| Some(daily_b.get(day)? - pnl))
+            .collect();
+
+        let mut sums = Vec::with_capacity(runs);
+        if !diffs.is_empty() {
+            let mut rng = SplitMix64(seed);
+            for _ in 0..runs {
+                let sum: f64 = (0..diffs.len())
+                    .map(|_| diffs[(rng.next() % diffs.len() as u64) as usize])
+                    .sum();
+                sums.push(sum);
+            }
+        }
+        sums.sort_by(f64::total_cmp);
+        let at = |q: f64| {
+            sums.get(((sums.len().max(1) - 1) as f64 * q).round() as usize)
+                .copied()
+                .unwrap_or(0.0)
+        };
+        let p_value = if sums.is_empty() {
+            1.0
+        } else {
+            let n = sums.len() as f64;
+            let below = sums.iter().filter(|s| **s <= 0.0).count() as f64 / n;
+            let above = sums.iter().filter(|s| **s >= 0.0).count() as f64 / n;
+            (2.0 * below.min(above)).min(1.0)
+        };
+
+        Self {
+            pnl_diff: b.total_pnl - a.total_pnl,
+            days: diffs.len(),
+            diff_ci_low: at(0.025),
+            diff_ci_high: at(0.975),
+            p_value,
+            bootstrap_runs: sums.len(),
+            a,
+            b,
+        }
+    }
+
+    pub fn print_summary(&self) {
+        let row = |name: &str, r: &BacktestReport| {
+            println!(
+                "  {:<8} {:>+11.2} {:>7} {:>6.1} {:>6.2} {:>7.2} {:>7.1}",
+                name,
+                r.total_pnl,
+                r.total_trades,
+                r.win_rate,
+                r.profit_factor,
+                r.sharpe_ratio,
+                r.max_drawdown_pct
+            );
+        };
+        println!("\n{}", "=".repeat(70));
+        println!("  A/B COMPARISON");
+        println!("{}", "=".repeat(70));
+        println!(
+            "  {:<8} {:>11} {:>7} {:>6} {:>6} {:>7} {:>7}",
+            "Variant", "PnL", "Trades", "WR%", "PF", "Sharpe", "MaxDD%"
+        );
+        row("A", &self.a);
+        row("B", &self.b);
+        println!();
+        println!(
+            "  B - A:       ${:+.2} (95% CI ${:+.2} to ${:+.2}, {} days)",
+            self.pnl_diff, self.diff_ci_low, self.diff_ci_high, self.days
+        );
+        println!(
+            "  p-value:     {:.4} ({} bootstrap resamples){}",
+            self.p_value,
+            self.bootstrap_runs,
+            if self.p_value < 0.05 {
+                " — significant"
+            } else {
+                ""
+            }
+        );
+        println!("{}", "=".repeat(70));
+    }
+}
+
+/// Backtest variants `a` and `b` over the same data in one pass and
+/// compare them.
+pub async fn ab_test(
+    exchange: HistoricalExchange,
+    a: Config,
+    b: Config,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    step_minutes: i64,
+) -> Result<AbComparison> {
+    let runs = a.ab_bootstrap_runs;
+    // In-memory refiners so the variants neither share nor persist refinements
+    let refiner_a = StrategyRefiner::in_memory(&a);
+    let refiner_b = StrategyRefiner::in_memory(&b);
+    let mut runner_a = BacktestRunner::new(exchange.clone(), a).with_refiner(refiner_a);
+    let mut runner_b = BacktestRunner::new(exchange, b).with_refiner(refiner_b);
+    let (report_a, report_b) = runner_a
+        .run_ab(&mut runner_b, start, end, step_minutes)
+        .await?;
+    Ok(AbComparison::from_reports(report_a, report_b, runs, 1))
+}
+
+/// Balance change per UTC day of the equity curve, from the starting balance.
+fn daily_pnl(report: &BacktestReport) -> BTreeMap<NaiveDate, f64> {
+    let mut closes: BTreeMap<NaiveDate, f64> = BTreeMap::new();
+    for (ts, balance) in &report.equity_curve {
+        closes.insert(ts.date_naive(), *balance);
+    }
+    let mut prev = report.initial_balance;
+    closes
+        .into_iter()
+        .map(|(day, close)| {
+            let pnl = close - prev;
+            prev = close;
+            (day, pnl)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::default_test_config;
+    use crate::trading::paper_trader::PaperTrader;
+    use chrono::{Duration, TimeZone};
+
+    fn report(daily: &[f64]) -> BacktestReport {
+        let cfg = default_test_config();
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let mut balance = cfg.initial_balance;
+        let curve = daily
+            .iter()
+            .enumerate()
+            .map(|(i, pnl)| {
+                balance += pnl;
+                (start + Duration::days(i as i64), balance)
+            })
+            .collect();
+        let mut r = BacktestReport::from_backtest(
+            &PaperTrader::new_fresh(&cfg),
+            &cfg,
+            start,
+            start + Duration::days(daily.len() as i64),
+            curve,
+            0.0,
+            0.0,
+            0,
+            0,
+        );
+        r.total_pnl = daily.iter().sum();
+        r
+    }
+
+    #[test]
+    fn bootstrap_separates_a_consistent_edge_from_noise() {
+        let a: Vec<f64> = (0..30)
+            .map(|i| if i % 2 == 0 { 5.0 } else { -4.0 })
+            .collect();
+        // B makes 2 more every day
+        let b: Vec<f64> = a.iter().map(|p| p + 2.0).collect();
+        let cmp = AbComparison::from_reports(report(&a), report(&b), 1000, 1);
+        assert_eq!(cmp.days, 30);
+        assert!((cmp.pnl_diff - 60.0).abs() < 1e-9);
+        assert!(cmp.p_value < 0.01);
+        assert!(cmp.diff_ci_low > 0.0);
+
+        // Same days shuffled around: no consistent difference
+        let mut noise = a.clone();
+        noise.rotate_left(1);
+        let cmp = AbComparison::from_reports(report(&a), report(&noise), 1000, 1);
+        assert!(cmp.p_value > 0.05, "p = {}", cmp.p_value);
+        assert!(cmp.diff_ci_low < 0.0 && cmp.diff_ci_high > 0.0);
+    }
+}
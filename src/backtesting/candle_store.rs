@@ -0,0 +1,272 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::models::{Candle, Timeframe};
+
+/// A half-open time range `[start, end)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Span {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+}
+
+impl Span {
+    pub fn new(start: DateTime<Utc>, end: DateTime<Utc>) -> Self {
+        Self { start, end }
+    }
+}
+
+/// One symbol/timeframe on disk: the candles plus the ranges already fetched,
+/// so empty stretches (no trades) are not re-requested on every run.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct StoredSeries {
+    covered: Vec<Span>,
+    candles: Vec<Candle>,
+}
+
+/// Disk-backed candle cache under `root/<SYMBOL>/<tf>.json`.
+///
+/// Backtests ask for what they need; only the ranges not yet covered go to
+/// the network, and the results are merged into the same file.
+pub struct CandleStore {
+    root: PathBuf,
+}
+
+impl CandleStore {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    pub fn path(&self, symbol: &str, tf: Timeframe) -> PathBuf {
+        self.root.join(symbol).join(format!("{}.json", tf))
+    }
+
+    fn read(&self, symbol: &str, tf: Timeframe) -> Result<StoredSeries> {
+        let path = self.path(symbol, tf);
+        if !path.exists() {
+            return Ok(StoredSeries::default());
+        }
+        let content =
+            fs::read_to_string(&path).with_context(|| format!("reading {}", path.display()))?;
+        serde_json::from_str(&content).with_context(|| format!("parsing {}", path.display()))
+    }
+
+    fn write(&self, symbol: &str, tf: Timeframe, series: &StoredSeries) -> Result<()> {
+        let path = self.path(symbol, tf);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        // Write-then-rename so an interrupted run never leaves a truncated file
+        let tmp = path.with_extension("json.tmp");
+        fs::write(&tmp, serde_json::to_string(series)?)?;
+        fs::rename(&tmp, &path).with_context(|| format!("writing {}", path.display()))?;
+        Ok(())
+    }
+
+    /// Parts of `[start, end)` not yet fetched.
+    pub fn missing(
+        &self,
+        symbol: &str,
+        tf: Timeframe,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<Span>> {
+        Ok(subtract(
+            Span::new(start, end),
+            &self.read(symbol, tf)?.covered,
+        ))
+    }
+
+    /// Merge fetched candles (newer values win on equal timestamps) and mark
+    /// `spans` as covered.
+    pub fn insert(
+        &self,
+        symbol: &str,
+        tf: Timeframe,
+        spans: &[Span],
+        candles: Vec<Candle>,
+    ) -> Result<()> {
+        let mut series = self.read(symbol, tf)?;
+        let mut merged = candles;
+        merged.append(&mut series.candles);
+        // Stable sort keeps the new candle first; dedup keeps the first
+        merged.sort_by_key(|c| c.timestamp);
+        merged.dedup_by_key(|c| c.timestamp);
+        series.candles = merged;
+        series.covered.extend_from_slice(spans);
+        series.covered = union(&series.covered);
+        self.write(symbol, tf, &series)
+    }
+
+    /// Stored candles with `start <= timestamp < end`.
+    pub fn load_range(
+        &self,
+        symbol: &str,
+        tf: Timeframe,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<Candle>> {
+        let candles = self.read(symbol, tf)?.candles;
+        let from = candles.partition_point(|c| c.timestamp < start);
+        let to = candles.partition_point(|c| c.timestamp < end);
+        Ok(candles[from..to.max(from)].to_vec())
+    }
+
+    /// Import a legacy `SYMBOL_tf_YYYYmmdd_to_YYYYmmdd.json` cache file
+    /// covering `span`. Returns false if there is no such file.
+    pub fn import_legacy(
+        &self,
+        file: &Path,
+        symbol: &str,
+        tf: Timeframe,
+        span: Span,
+    ) -> Result<bool> {
+        if !file.exists() {
+            return Ok(false);
+        }
+        let content = fs::read_to_string(file)?;
+        let candles: Vec<Candle> = serde_json::from_str(&content)?;
+        // Keep anything already stored over the old snapshot
+        let existing = self.read(symbol, tf)?;
+        let mut merged = existing.candles;
+        merged.extend(candles);
+        merged.sort_by_key(|c| c.timestamp);
+        merged.dedup_by_key(|c| c.timestamp);
+        let mut covered = existing.covered;
+        covered.push(span);
+        self.write(
+            symbol,
+            tf,
+            &StoredSeries {
+                covered: union(&covered),
+                candles: merged,
+            },
+        )?;
+        Ok(true)
+    }
+}
+
+/// Holes longer than one bar between consecutive candles.
+pub fn gaps(candles: &[Candle], tf: Timeframe) -> Vec<Span> {
+    let bar = Duration::seconds(tf.as_seconds() as i64);
+    candles
+        .windows(2)
+        .filter(|w| w[1].timestamp - w[0].timestamp > bar)
+        .map(|w| Span::new(w[0].timestamp + bar, w[1].timestamp))
+        .collect()
+}
+
+/// Sorted, merged spans (overlapping or touching spans join).
+fn union(spans: &[Span]) -> Vec<Span> {
+    let mut sorted: Vec<Span> = spans.iter().copied().filter(|s| s.end > s.start).collect();
+    sorted.sort_by_key(|s| s.start);
+    let mut out: Vec<Span> = Vec::new();
+    for s in sorted {
+        match out.last_mut() {
+            Some(last) if s.start <= last.end => last.end = last.end.max(s.end),
+            _ => out.push(s),
+        }
+    }
+    out
+}
+
+/// `want` minus every covered span.
+fn subtract(want: Span, covered: &[Span]) -> Vec<Span> {
+    let mut out = Vec::new();
+    let mut cursor = want.start;
+    for c in union(covered) {
+        if c.end <= cursor || c.start >= want.end {
+            continue;
+        }
+        if c.start > cursor {
+            out.push(Span::new(cursor, c.start));
+        }
+        cursor = cursor.max(c.end);
+    }
+    if cursor < want.end {
+        out.push(Span::new(cursor, want.end));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::make_candles;
+
+    #[test]
+    fn fetches_only_uncovered_ranges_and_merges() {
+        let root = std::env::temp_dir().join(format!("ict_candles_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let store = CandleStore::new(&root);
+
+        // make_candles starts at 12:00 with 1m spacing
+        let all: Vec<Candle> = make_candles(&[(1.0, 2.0, 0.5, 1.5); 10])
+            .into_iter()
+            .collect();
+        let t = |i: usize| all[0].timestamp + Duration::minutes(i as i64);
+
+        assert_eq!(
+            store
+                .missing("ETH-USD", Timeframe::M1, t(0), t(10))
+                .unwrap(),
+            vec![Span::new(t(0), t(10))]
+        );
+
+        // First run fetched 0-4, a later one 6-10 with minute 8 missing
+        store
+            .insert(
+                "ETH-USD",
+                Timeframe::M1,
+                &[Span::new(t(0), t(5))],
+                all[..5].to_vec(),
+            )
+            .unwrap();
+        let mut later = all[6..].to_vec();
+        later.remove(2);
+        store
+            .insert("ETH-USD", Timeframe::M1, &[Span::new(t(6), t(10))], later)
+            .unwrap();
+        assert_eq!(
+            store
+                .missing("ETH-USD", Timeframe::M1, t(0), t(12))
+                .unwrap(),
+            vec![Span::new(t(5), t(6)), Span::new(t(10), t(12))]
+        );
+
+        // Re-fetching an overlap replaces rather than duplicates
+        let mut revised = all[4].clone();
+        revised.close = 9.0;
+        store
+            .insert(
+                "ETH-USD",
+                Timeframe::M1,
+                &[Span::new(t(4), t(6))],
+                vec![revised],
+            )
+            .unwrap();
+        let loaded = store
+            .load_range("ETH-USD", Timeframe::M1, t(0), t(10))
+            .unwrap();
+        assert_eq!(loaded.len(), 8);
+        assert_eq!(loaded[4].close, 9.0);
+        assert!(store
+            .missing("ETH-USD", Timeframe::M1, t(0), t(10))
+            .unwrap()
+            .is_empty());
+
+        // Minutes 5 and 8 were never returned by the exchange
+        assert_eq!(
+            gaps(&loaded, Timeframe::M1),
+            vec![Span::new(t(5), t(6)), Span::new(t(8), t(9))]
+        );
+        assert!(store
+            .load_range("ETH-USD", Timeframe::M5, t(0), t(10))
+            .unwrap()
+            .is_empty());
+        let _ = fs::remove_dir_all(&root);
+    }
+}
@@ -0,0 +1,356 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use std::io::Write;
+use std::path::Path;
+
+use super::ab_test;
+use super::data_fetcher;
+use super::lookahead::{self, LookaheadReport};
+use super::monte_carlo::{MonteCarloReport, MonteCarloSettings};
+use super::optimizer;
+use super::replay;
+use super::report::BacktestReport;
+use super::runner::BacktestRunner;
+use crate::config::Config;
+use crate::exchange::HistoricalExchange;
+use crate::models::Timeframe;
+use crate::trading::journal::Journal;
+
+/// Candle store and report output directory
+pub const DATA_DIR: &str = "data";
+
+/// Timeframes a backtest loads (4H is resampled from 1H)
+pub const TIMEFRAMES: [Timeframe; 6] = [
+    Timeframe::M1,
+    Timeframe::M5,
+    Timeframe::M15,
+    Timeframe::H1,
+    Timeframe::H4,
+    Timeframe::D1,
+];
+
+/// Historical market for `cfg.symbol` over `[start, end)` plus the range a
+/// run can cover (the first day is lookback). `None` when there is too
+/// little data.
+pub async fn load_exchange(
+    cfg: &Config,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Result<Option<(HistoricalExchange, DateTime<Utc>, DateTime<Utc>)>> {
+    let data = data_fetcher::fetch_and_cache(cfg, start, end, DATA_DIR, &TIMEFRAMES).await?;
+
+    let m1_count = data
+        .iter()
+        .find(|(tf, _)| *tf == Timeframe::M1)
+        .map(|(_, c)| c.len())
+        .unwrap_or(0);
+    if m1_count == 0 {
+        println!("ERROR: No 1-minute data available. Cannot backtest.");
+        println!("Make sure your Coinbase API credentials are configured in .env");
+        return Ok(None);
+    }
+
+    println!("Data loaded:");
+    for (tf, candles) in &data {
+        println!("  {}: {} candles", tf, candles.len());
+    }
+    println!();
+
+    let mut exchange = HistoricalExchange::new();
+    for (tf, candles) in data {
+        exchange.load(tf, candles);
+    }
+    if cfg.instrument(&cfg.symbol).pays_funding() {
+        let funding = data_fetcher::load_funding(DATA_DIR, &cfg.symbol)?;
+        if funding.is_empty() {
+            println!(
+                "Perpetual: no {}/funding_{}.csv, charging {:.4}% every {}h",
+                DATA_DIR,
+                cfg.symbol,
+                cfg.funding_rate * 100.0,
+                cfg.funding_interval_hours
+            );
+        } else {
+            println!("Perpetual: {} funding rates loaded", funding.len());
+        }
+        exchange.load_funding(funding);
+    }
+
+    let data_start = exchange.earliest_time().unwrap_or(start);
+    let data_end = exchange.latest_time().unwrap_or(end);
+    let bt_start = data_start + Duration::days(1);
+    if bt_start >= data_end {
+        println!("ERROR: Not enough data for backtesting");
+        return Ok(None);
+    }
+
+    println!(
+        "Backtesting from {} to {}",
+        bt_start.format("%Y-%m-%d %H:%M"),
+        data_end.format("%Y-%m-%d %H:%M")
+    );
+    println!();
+    Ok(Some((exchange, bt_start, data_end)))
+}
+
+/// Fill the candle store for `[start, end)` without running anything.
+pub async fn fetch_data(cfg: &Config, start: DateTime<Utc>, end: DateTime<Utc>) -> Result<()> {
+    let data = data_fetcher::fetch_and_cache(cfg, start, end, DATA_DIR, &TIMEFRAMES).await?;
+    println!(
+        "{} {} to {} cached in {}/:",
+        cfg.symbol,
+        start.format("%Y-%m-%d"),
+        end.format("%Y-%m-%d"),
+        DATA_DIR
+    );
+    for (tf, candles) in &data {
+        println!("  {}: {} candles", tf, candles.len());
+    }
+    Ok(())
+}
+
+/// Run one backtest, print it and save the text, JSON, CSV, HTML,
+/// seasonality, Monte Carlo reports, trade replays and event journal
+/// under `DATA_DIR`.
+pub async fn backtest(
+    exchange: HistoricalExchange,
+    cfg: Config,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    step_minutes: i64,
+) -> Result<BacktestReport> {
+    let journal_file = format!(
+        "{}/backtest_{}_{}_events.ndjson",
+        DATA_DIR,
+        start.format("%Y%m%d"),
+        end.format("%Y%m%d"),
+    );
+    // Each run writes a fresh journal
+    let _ = std::fs::remove_file(&journal_file);
+    let mut runner = BacktestRunner::new(exchange, cfg).with_journal(Journal::at(&journal_file));
+    let report = runner.run(start, end, step_minutes).await?;
+    report.print_summary();
+
+    let report_file = format!(
+        "{}/backtest_{}_{}.txt",
+        DATA_DIR,
+        report.start.format("%Y%m%d"),
+        report.end.format("%Y%m%d"),
+    );
+    save_report_to_file(&report, &report_file)?;
+    println!("\nReport saved to: {}", report_file);
+
+    // Machine-readable copies for plotting / diffing between runs
+    let stem = report_file.trim_end_matches(".txt");
+    for ext in ["json", "csv"] {
+        let path = format!("{}.{}", stem, ext);
+        report.save(Path::new(&path))?;
+        println!("Report saved to: {}", path);
+    }
+    let path = format!("{}.html", stem);
+    report.to_html(Path::new(&path))?;
+    println!("Report saved to: {}", path);
+    let path = format!("{}_seasonality.csv", stem);
+    std::fs::write(&path, report.seasonality.to_csv())?;
+    println!("Seasonality saved to: {}", path);
+
+    let settings = MonteCarloSettings::from_config(&runner.config);
+    if let Some(mc) = MonteCarloReport::from_backtest(&report, settings) {
+        mc.print_summary();
+        let path = format!("{}_monte_carlo.json", stem);
+        std::fs::write(&path, serde_json::to_string_pretty(&mc)?)?;
+        println!("Monte Carlo saved to: {}", path);
+    }
+    let bars = runner.config.replay_bars;
+    if bars > 0 {
+        let history = runner.paper_trader.history();
+        let replays = replay::build(history, &runner.exchange, &runner.config, bars);
+        let path = format!("{}_replays.json", stem);
+        replay::save(&replays, Path::new(&path))?;
+        println!("{} trade replays saved to: {}", replays.len(), path);
+    }
+    if Path::new(&journal_file).exists() {
+        println!("Event journal saved to: {}", journal_file);
+    }
+
+    Ok(report)
+}
+
+/// Backtest `a` against variant `b` in one pass, print the comparison and
+/// save it as JSON under `DATA_DIR`.
+pub async fn ab_test(
+    exchange: HistoricalExchange,
+    a: Config,
+    b: Config,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    step_minutes: i64,
+) -> Result<()> {
+    let cmp = ab_test::ab_test(exchange, a, b, start, end, step_minutes).await?;
+    cmp.print_summary();
+    let path = format!(
+        "{}/ab_test_{}_{}.json",
+        DATA_DIR,
+        cmp.a.start.format("%Y%m%d"),
+        cmp.a.end.format("%Y%m%d"),
+    );
+    std::fs::create_dir_all(DATA_DIR)?;
+    std::fs::write(&path, serde_json::to_string_pretty(&cmp)?)?;
+    println!("Comparison saved to: {}", path);
+    Ok(())
+}
+
+/// Run the look-ahead audit, print it and save it as JSON under `DATA_DIR`.
+pub async fn audit(
+    exchange: HistoricalExchange,
+    cfg: Config,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    step_minutes: i64,
+) -> Result<LookaheadReport> {
+    let report = lookahead::audit(exchange, cfg, start, end, step_minutes).await?;
+    report.print_summary();
+    let path = format!(
+        "{}/lookahead_audit_{}_{}.json",
+        DATA_DIR,
+        start.format("%Y%m%d"),
+        end.format("%Y%m%d"),
+    );
+    std::fs::create_dir_all(DATA_DIR)?;
+    std::fs::write(&path, serde_json::to_string_pretty(&report)?)?;
+    println!("Audit saved to: {}", path);
+    Ok(report)
+}
+
+/// Sweep the `OPT_*` parameter grid (`OPT_PARALLELISM` runs at a time),
+/// print the ranking and save each combination's report.
+pub async fn optimize(
+    exchange: HistoricalExchange,
+    cfg: Config,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    step_minutes: i64,
+) -> Result<()> {
+    let grid = optimizer::ParamGrid::from_config(&cfg);
+    let parallelism = cfg
+        .opt_parallelism
+        .unwrap_or_else(|| std::thread::available_parallelism().map_or(1, |n| n.get()));
+    let results =
+        optimizer::optimize(exchange, cfg, &grid, start, end, step_minutes, parallelism).await?;
+    optimizer::print_ranking(&results);
+    for (i, r) in results.iter().enumerate() {
+        let path = format!("{}/optimize_{:03}.txt", DATA_DIR, i + 1);
+        save_report_to_file(&r.report, &path)?;
+        let mut f = std::fs::OpenOptions::new().append(true).open(&path)?;
+        writeln!(f, "\nParams: {}", r.params.label())?;
+    }
+    println!(
+        "\nPer-combination reports saved to {}/optimize_*.txt",
+        DATA_DIR
+    );
+    Ok(())
+}
+
+/// Print the summary (and Monte Carlo) of a report saved as JSON by an
+/// earlier run.
+pub fn print_saved_report(cfg: &Config, path: &str) -> Result<()> {
+    let raw = std::fs::read_to_string(path).with_context(|| format!("reading {}", path))?;
+    let report: BacktestReport =
+        serde_json::from_str(&raw).with_context(|| format!("{} is not a JSON report", path))?;
+    report.print_summary();
+    if let Some(mc) = MonteCarloReport::from_backtest(&report, MonteCarloSettings::from_config(cfg))
+    {
+        mc.print_summary();
+    }
+    Ok(())
+}
+
+pub fn save_report_to_file(report: &BacktestReport, path: &str) -> Result<()> {
+    if let Some(parent) = Path::new(path).parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut f = std::fs::File::create(path)?;
+
+    writeln!(f, "ICT Trading Bot Backtest Report")?;
+    writeln!(f, "================================")?;
+    writeln!(
+        f,
+        "Period: {} to {} ({:.0} days)",
+        report.start.format("%Y-%m-%d"),
+        report.end.format("%Y-%m-%d"),
+        report.days
+    )?;
+    writeln!(f, "Fills: {}", report.fills_label())?;
+    writeln!(f, "Config: {}", report.config_hash)?;
+    writeln!(f)?;
+    writeln!(f, "Performance:")?;
+    writeln!(f, "  Initial:  ${:.2}", report.initial_balance)?;
+    writeln!(f, "  Final:    ${:.2}", report.final_balance)?;
+    writeln!(f, "  PnL:      ${:+.2}", report.total_pnl)?;
+    writeln!(f, "  Return:   {:+.1}%", report.total_return_pct)?;
+    if report.instrument.pays_funding() {
+        writeln!(f, "  Funding:  ${:+.2} paid", report.funding_paid)?;
+    }
+    writeln!(f)?;
+    writeln!(f, "Trades:")?;
+    writeln!(f, "  Total:       {}", report.total_trades)?;
+    writeln!(
+        f,
+        "  Win/Loss:    {} / {}",
+        report.winning_trades, report.losing_trades
+    )?;
+    writeln!(f, "  Win Rate:    {:.1}%", report.win_rate)?;
+    writeln!(f, "  Avg Win:     ${:+.2}", report.avg_win)?;
+    writeln!(f, "  Avg Loss:    ${:+.2}", report.avg_loss)?;
+    writeln!(f, "  Profit Factor: {:.2}", report.profit_factor)?;
+    if let Some(r) = &report.r_stats {
+        writeln!(f, "  Expectancy:  {}", r.summary())?;
+        for b in &r.histogram {
+            writeln!(f, "    {:+.1}R to {:+.1}R: {}", b.from, b.to, b.count)?;
+        }
+    }
+    if let Some(p) = &report.pyramid {
+        writeln!(f, "  Pyramided:   {}", p.summary())?;
+    }
+    writeln!(f)?;
+    writeln!(f, "Risk:")?;
+    writeln!(
+        f,
+        "  Max DD:    ${:.2} ({:.1}%)",
+        report.max_drawdown, report.max_drawdown_pct
+    )?;
+    writeln!(f, "  Sharpe:    {:.2}", report.sharpe_ratio)?;
+    writeln!(f)?;
+    writeln!(f, "Signals:")?;
+    writeln!(f, "  Generated: {}", report.total_signals)?;
+    writeln!(f, "  Filtered:  {}", report.signals_filtered)?;
+    writeln!(f)?;
+    writeln!(f, "By Scale:")?;
+    for (scale, stats) in &report.scale_stats {
+        writeln!(
+            f,
+            "  {}: {} trades | WR {:.0}% | PnL ${:+.2} | Entry {:+.1}bps",
+            scale, stats.trades, stats.win_rate, stats.total_pnl, stats.avg_entry_improvement_bps
+        )?;
+    }
+    writeln!(f)?;
+    writeln!(f, "By Session:")?;
+    for (session, stats) in &report.session_stats {
+        writeln!(
+            f,
+            "  {}: {} trades | WR {:.0}% | PnL ${:+.2} | Entry {:+.1}bps",
+            session, stats.trades, stats.win_rate, stats.total_pnl, stats.avg_entry_improvement_bps
+        )?;
+    }
+    writeln!(f)?;
+    writeln!(f, "By Exit Reason:")?;
+    for mix in &report.exit_mix {
+        writeln!(
+            f,
+            "  {}: {}W / {}L | PnL ${:+.2} | {:.0}% of winners | {:.0}% of losers",
+            mix.reason, mix.wins, mix.losses, mix.total_pnl, mix.pct_of_winners, mix.pct_of_losers
+        )?;
+    }
+
+    Ok(())
+}
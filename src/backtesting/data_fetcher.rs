@@ -4,14 +4,51 @@ use std::path::Path;
 use std::time::Duration;
 use tracing::{debug, info, warn};
 
+use crate::backtesting::candle_store::{gaps, CandleStore, Span};
 use crate::config::Config;
 use crate::exchange::CoinbaseClient;
-use crate::models::{Candle, CandleSeries, Timeframe};
+use crate::models::{Candle, CandleSeries, FundingRate, Timeframe};
 
 const MAX_CANDLES_PER_REQUEST: u64 = 300;
 const RATE_LIMIT_SLEEP_MS: u64 = 250;
 
-/// Fetch historical data from Coinbase and save to local JSON files.
+/// Funding history from `{data_dir}/funding_{symbol}.csv` (`time,rate`
+/// rows, RFC 3339 times), oldest first. Empty when the file is absent.
+pub fn load_funding(data_dir: &str, symbol: &str) -> Result<Vec<FundingRate>> {
+    let path = Path::new(data_dir).join(format!("funding_{}.csv", symbol));
+    let Ok(content) = std::fs::read_to_string(&path) else {
+        return Ok(Vec::new());
+    };
+    let mut rates = Vec::new();
+    for (n, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || (n == 0 && line.starts_with("time")) {
+            continue;
+        }
+        let parsed = line.split_once(',').and_then(|(time, rate)| {
+            Some(FundingRate {
+                time: DateTime::parse_from_rfc3339(time.trim())
+                    .ok()?
+                    .with_timezone(&Utc),
+                rate: rate.trim().parse().ok()?,
+            })
+        });
+        match parsed {
+            Some(f) => rates.push(f),
+            None => anyhow::bail!(
+                "{}:{}: invalid funding row '{}'",
+                path.display(),
+                n + 1,
+                line
+            ),
+        }
+    }
+    rates.sort_by_key(|f| f.time);
+    Ok(rates)
+}
+
+/// Load historical data for `[start, end)` from the candle store under
+/// `data_dir`, fetching only the ranges it has not seen from Coinbase.
 pub async fn fetch_and_cache(
     cfg: &Config,
     start: DateTime<Utc>,
@@ -19,14 +56,20 @@ pub async fn fetch_and_cache(
     data_dir: &str,
     timeframes: &[Timeframe],
 ) -> Result<Vec<(Timeframe, Vec<Candle>)>> {
-    std::fs::create_dir_all(data_dir)?;
-
+    let store = CandleStore::new(data_dir);
     let mut client = CoinbaseClient::new(cfg);
     let mut results = Vec::new();
 
     for &tf in timeframes {
-        // Round to day boundaries for cache reuse across runs
-        let cache_file = format!(
+        // Skip 4H and the other non-native timeframes — resampled below
+        if let Some(source) = tf.resample_source() {
+            info!("  Skipping {} (will resample from {})", tf, source);
+            results.push((tf, Vec::new()));
+            continue;
+        }
+
+        // Pick up a pre-store cache file for this exact range, if any
+        let legacy = format!(
             "{}/{}_{}_{}_to_{}.json",
             data_dir,
             cfg.symbol,
@@ -34,73 +77,97 @@ pub async fn fetch_and_cache(
             start.format("%Y%m%d"),
             end.format("%Y%m%d")
         );
-
-        // Try loading from cache first
-        if Path::new(&cache_file).exists() {
-            info!("Loading cached {} data from {}", tf, cache_file);
-            let content = std::fs::read_to_string(&cache_file)?;
-            let candles: Vec<Candle> = serde_json::from_str(&content)?;
-            info!("  Loaded {} candles", candles.len());
-            results.push((tf, candles));
-            continue;
+        if store.import_legacy(Path::new(&legacy), &cfg.symbol, tf, Span::new(start, end))? {
+            info!("Imported legacy cache {} into the candle store", legacy);
+            let _ = std::fs::remove_file(&legacy);
         }
 
-        // Skip 4H — we'll resample from H1
-        if tf == Timeframe::H4 {
-            info!("  Skipping 4H (will resample from 1H)");
-            results.push((tf, Vec::new()));
-            continue;
+        // The newest bar may still be forming; leave it uncovered so the
+        // next run refetches it
+        let bar = chrono::Duration::seconds(tf.as_seconds() as i64);
+        let fetch_end = end.min(Utc::now() - bar);
+        let missing = store.missing(&cfg.symbol, tf, start, fetch_end)?;
+        for span in &missing {
+            info!(
+                "Fetching {} data from Coinbase ({} to {})...",
+                tf,
+                span.start.format("%Y-%m-%d %H:%M"),
+                span.end.format("%Y-%m-%d %H:%M")
+            );
+            let (candles, covered) = fetch_range(&mut client, tf, span.start, span.end).await?;
+            info!("  Fetched {} {} candles", candles.len(), tf);
+            store.insert(&cfg.symbol, tf, &covered, candles)?;
+        }
+        if missing.is_empty() {
+            info!("Using stored {} data from {}", tf, store.path(&cfg.symbol, tf).display());
         }
 
-        info!(
-            "Fetching {} data from Coinbase ({} to {})...",
-            tf,
-            start.format("%Y-%m-%d"),
-            end.format("%Y-%m-%d")
-        );
-
-        let candles = fetch_range(&mut client, tf, start, end).await?;
-        info!("  Fetched {} {} candles total", candles.len(), tf);
-
-        // Save to cache
-        let json = serde_json::to_string(&candles)?;
-        std::fs::write(&cache_file, json)?;
-        info!("  Cached to {}", cache_file);
-
+        let candles = store.load_range(&cfg.symbol, tf, start, end)?;
+        let holes = gaps(&candles, tf);
+        if !holes.is_empty() {
+            let bars: i64 = holes
+                .iter()
+                .map(|g| (g.end - g.start).num_seconds() / tf.as_seconds() as i64)
+                .sum();
+            warn!(
+                "  {} {} data has {} gaps ({} bars missing), largest at {}",
+                cfg.symbol,
+                tf,
+                holes.len(),
+                bars,
+                holes
+                    .iter()
+                    .max_by_key(|g| g.end - g.start)
+                    .map(|g| g.start.format("%Y-%m-%d %H:%M").to_string())
+                    .unwrap_or_default()
+            );
+        }
+        info!("  Loaded {} candles", candles.len());
         results.push((tf, candles));
     }
 
-    // Generate 4H from H1 if needed
-    if timeframes.contains(&Timeframe::H4) {
-        let h1_candles = results
+    // Generate 4H from H1 (and the like) if needed
+    for &tf in timeframes {
+        let Some(source) = tf.resample_source() else {
+            continue;
+        };
+        let source_candles = results
             .iter()
-            .find(|(tf, _)| *tf == Timeframe::H1)
+            .find(|(t, _)| *t == source)
             .map(|(_, c)| c.clone())
             .unwrap_or_default();
 
-        if !h1_candles.is_empty() {
-            let h1_series = CandleSeries::new(h1_candles);
-            let h4_series = h1_series.resample(Duration::from_secs(14400));
-            let h4_candles: Vec<Candle> = h4_series.into_iter().collect();
-            info!("Generated {} 4H candles from H1 data", h4_candles.len());
+        if source_candles.is_empty() {
+            warn!("  No {} data loaded to resample {} from", source, tf);
+            continue;
+        }
+        let series = CandleSeries::new(source_candles).resample_to(tf);
+        let candles: Vec<Candle> = series.into_iter().collect();
+        info!(
+            "Generated {} {} candles from {} data",
+            candles.len(),
+            tf,
+            source
+        );
 
-            if let Some(entry) = results.iter_mut().find(|(tf, _)| *tf == Timeframe::H4) {
-                entry.1 = h4_candles;
-            }
+        if let Some(entry) = results.iter_mut().find(|(t, _)| *t == tf) {
+            entry.1 = candles;
         }
     }
 
     Ok(results)
 }
 
-/// Fetch a date range by paginating through the API in chunks.
+/// Fetch a date range by paginating through the API in chunks. Also returns
+/// the sub-ranges that were fetched successfully.
 async fn fetch_range(
     client: &mut CoinbaseClient,
     tf: Timeframe,
     start: DateTime<Utc>,
     end: DateTime<Utc>,
-) -> Result<Vec<Candle>> {
+) -> Result<(Vec<Candle>, Vec<Span>)> {
     let mut all_candles: Vec<Candle> = Vec::new();
+    let mut covered: Vec<Span> = Vec::new();
     let tf_secs = tf.as_seconds();
     let chunk_duration = tf_secs * MAX_CANDLES_PER_REQUEST;
 
@@ -130,6 +197,11 @@ async fn fetch_range(
                 for candle in series {
                     all_candles.push(candle);
                 }
+                let span = Span::new(ts(chunk_start), ts(chunk_end));
+                match covered.last_mut() {
+                    Some(last) if last.end == span.start => last.end = span.end,
+                    _ => covered.push(span),
+                }
             }
             Err(e) => {
                 warn!("  Error fetching {} chunk {}: {}", tf, chunk_num, e);
@@ -148,5 +220,9 @@ async fn fetch_range(
     all_candles.sort_by_key(|c| c.timestamp);
     all_candles.dedup_by_key(|c| c.timestamp);
 
-    Ok(all_candles)
+    Ok((all_candles, covered))
+}
+
+fn ts(secs: u64) -> DateTime<Utc> {
+    DateTime::from_timestamp(secs as i64, 0).unwrap_or_default()
 }
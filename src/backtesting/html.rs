@@ -0,0 +1,387 @@
+use chrono::{DateTime, Datelike, Utc};
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+
+use super::report::BacktestReport;
+
+const CHART_WIDTH: f64 = 860.0;
+const CHART_HEIGHT: f64 = 220.0;
+/// Points kept per chart; longer curves are thinned evenly
+const MAX_POINTS: usize = 1500;
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+const STYLE: &str = "body{font-family:system-ui,sans-serif;margin:24px;color:#222}\
+h1{font-size:22px}h2{font-size:17px;margin-top:28px}\
+table{border-collapse:collapse;font-size:13px}\
+th,td{border:1px solid #ddd;padding:4px 8px;text-align:right}\
+th{background:#f5f5f5}td.l,th.l{text-align:left}\
+.pos{color:#1e8449}.neg{color:#c0392b}svg{background:#fafafa;border:1px solid #ddd}";
+
+/// Standalone HTML page: summary, equity and drawdown charts (inline SVG),
+/// monthly returns heatmap, and scale/session/exit/setup/seasonality tables.
+pub fn render(report: &BacktestReport) -> String {
+    let mut out = String::new();
+    let _ = write!(
+        out,
+        "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\">\
+         <title>Backtest {} to {}</title><style>{}</style></head><body>\n",
+        report.start.format("%Y-%m-%d"),
+        report.end.format("%Y-%m-%d"),
+        STYLE
+    );
+    let _ = writeln!(
+        out,
+        "<h1>Backtest {} to {} ({:.0} days)</h1>",
+        report.start.format("%Y-%m-%d"),
+        report.end.format("%Y-%m-%d"),
+        report.days
+    );
+    out.push_str(&summary_table(report));
+
+    out.push_str("<h2>Equity</h2>\n");
+    let equity: Vec<f64> = report.equity_curve.iter().map(|(_, b)| *b).collect();
+    out.push_str(&line_chart(&report.equity_curve, &equity, "#2471a3", "$"));
+
+    out.push_str("<h2>Drawdown</h2>\n");
+    out.push_str(&line_chart(
+        &report.equity_curve,
+        &drawdown_pct(&equity),
+        "#c0392b",
+        "%",
+    ));
+
+    let monthly = monthly_returns(report.initial_balance, &report.equity_curve);
+    if !monthly.is_empty() {
+        out.push_str("<h2>Monthly returns</h2>\n");
+        out.push_str(&monthly_table(&monthly));
+    }
+
+    if !report.scale_stats.is_empty() {
+        out.push_str("<h2>By scale</h2>\n<table><tr><th class=\"l\">Scale</th><th>Trades</th>");
+        out.push_str("<th>WR</th><th>PnL</th><th>Avg</th><th>Entry bps</th></tr>\n");
+        let mut scales: Vec<_> = report.scale_stats.iter().collect();
+        scales.sort_by_key(|(k, _)| (*k).clone());
+        for (scale, s) in scales {
+            let _ = writeln!(
+                out,
+                "<tr><td class=\"l\">{}</td><td>{}</td><td>{:.0}%</td>{}{}<td>{:+.1}</td></tr>",
+                escape(scale),
+                s.trades,
+                s.win_rate,
+                money_cell(s.total_pnl),
+                money_cell(s.avg_pnl),
+                s.avg_entry_improvement_bps
+            );
+        }
+        out.push_str("</table>\n");
+    }
+
+    if !report.session_stats.is_empty() {
+        out.push_str("<h2>By session</h2>\n<table><tr><th class=\"l\">Session</th>");
+        out.push_str("<th>Trades</th><th>WR</th><th>PnL</th><th>Entry bps</th></tr>\n");
+        let mut sessions: Vec<_> = report.session_stats.iter().collect();
+        sessions.sort_by(|a, b| b.1.total_pnl.total_cmp(&a.1.total_pnl));
+        for (session, s) in sessions {
+            let _ = writeln!(
+                out,
+                "<tr><td class=\"l\">{}</td><td>{}</td><td>{:.0}%</td>{}<td>{:+.1}</td></tr>",
+                escape(session),
+                s.trades,
+                s.win_rate,
+                money_cell(s.total_pnl),
+                s.avg_entry_improvement_bps
+            );
+        }
+        out.push_str("</table>\n");
+    }
+
+    if !report.exit_mix.is_empty() {
+        out.push_str("<h2>By exit reason</h2>\n<table><tr><th class=\"l\">Reason</th>");
+        out.push_str("<th>Wins</th><th>Losses</th><th>PnL</th></tr>\n");
+        for m in &report.exit_mix {
+            let _ = writeln!(
+                out,
+                "<tr><td class=\"l\">{}</td><td>{}</td><td>{}</td>{}</tr>",
+                escape(&m.reason),
+                m.wins,
+                m.losses,
+                money_cell(m.total_pnl)
+            );
+        }
+        out.push_str("</table>\n");
+    }
+
+    if !report.attribution.is_empty() {
+        out.push_str("<h2>By setup</h2>\n<table><tr><th class=\"l\">Dimension</th>");
+        out.push_str("<th class=\"l\">Value</th><th>Trades</th><th>WR</th><th>PnL</th>");
+        out.push_str("<th>Edge</th></tr>\n");
+        for b in &report.attribution {
+            let _ = writeln!(
+                out,
+                "<tr><td class=\"l\">{}</td><td class=\"l\">{}</td><td>{}</td><td>{:.0}%</td>{}{}</tr>",
+                b.dimension,
+                escape(&b.value),
+                b.total,
+                b.win_rate * 100.0,
+                money_cell(b.total_pnl),
+                money_cell(b.edge)
+            );
+        }
+        out.push_str("</table>\n");
+    }
+
+    if report.seasonality.slots().next().is_some() {
+        out.push_str("<h2>Seasonality (ET, by entry)</h2>\n");
+        out.push_str(&report.seasonality.to_html());
+    }
+
+    out.push_str("</body></html>\n");
+    out
+}
+
+fn summary_table(report: &BacktestReport) -> String {
+    let rows = [
+        ("Initial", format!("${:.2}", report.initial_balance)),
+        ("Final", format!("${:.2}", report.final_balance)),
+        ("PnL", format!("${:+.2}", report.total_pnl)),
+        ("Return", format!("{:+.1}%", report.total_return_pct)),
+        ("Trades", report.total_trades.to_string()),
+        ("Win rate", format!("{:.1}%", report.win_rate)),
+        ("Profit factor", format!("{:.2}", report.profit_factor)),
+        (
+            "Max DD",
+            format!(
+                "${:.2} ({:.1}%)",
+                report.max_drawdown, report.max_drawdown_pct
+            ),
+        ),
+        ("Sharpe", format!("{:.2}", report.sharpe_ratio)),
+        ("Sortino", format!("{:.2}", report.sortino_ratio)),
+        (
+            "Calmar",
+            format!("{:.2} (CAGR {:+.1}%)", report.calmar_ratio, report.cagr_pct),
+        ),
+        (
+            "Time in market",
+            format!("{:.1}%", report.time_in_market_pct),
+        ),
+        (
+            "Avg open positions",
+            format!("{:.2}", report.avg_concurrent_positions),
+        ),
+        (
+            "Longest losing streak",
+            report.longest_losing_streak.to_string(),
+        ),
+        ("Fills", report.fills_label()),
+        ("Config", report.config_hash.clone()),
+    ];
+    let mut out = String::from("<table>\n");
+    for (label, value) in rows {
+        let _ = writeln!(
+            out,
+            "<tr><th class=\"l\">{}</th><td>{}</td></tr>",
+            label,
+            escape(&value)
+        );
+    }
+    out.push_str("</table>\n");
+    out
+}
+
+/// Percent below the running peak at each point, as a negative number.
+fn drawdown_pct(equity: &[f64]) -> Vec<f64> {
+    let mut peak = f64::MIN;
+    equity
+        .iter()
+        .map(|&v| {
+            peak = peak.max(v);
+            if peak > 0.0 {
+                (v - peak) / peak * 100.0
+            } else {
+                0.0
+            }
+        })
+        .collect()
+}
+
+/// Return per calendar month (UTC) from the last balance of each month,
+/// the first measured against the initial balance.
+fn monthly_returns(initial: f64, curve: &[(DateTime<Utc>, f64)]) -> BTreeMap<(i32, u32), f64> {
+    let mut closes: BTreeMap<(i32, u32), f64> = BTreeMap::new();
+    for (ts, balance) in curve {
+        closes.insert((ts.year(), ts.month()), *balance);
+    }
+    let mut prev = initial;
+    closes
+        .into_iter()
+        .map(|(month, close)| {
+            let ret = if prev > 0.0 {
+                (close / prev - 1.0) * 100.0
+            } else {
+                0.0
+            };
+            prev = close;
+            (month, ret)
+        })
+        .collect()
+}
+
+/// Year × month grid shaded green/red by return.
+fn monthly_table(monthly: &BTreeMap<(i32, u32), f64>) -> String {
+    let max_abs = monthly.values().map(|r| r.abs()).fold(0.0_f64, f64::max);
+    let mut out = String::from("<table class=\"monthly\">\n<tr><th></th>");
+    for m in MONTHS {
+        let _ = write!(out, "<th>{}</th>", m);
+    }
+    out.push_str("</tr>\n");
+    let mut years: Vec<i32> = monthly.keys().map(|(y, _)| *y).collect();
+    years.dedup();
+    for year in years {
+        let _ = write!(out, "<tr><th>{}</th>", year);
+        for month in 1..=12 {
+            let Some(ret) = monthly.get(&(year, month)) else {
+                out.push_str("<td></td>");
+                continue;
+            };
+            let alpha = if max_abs > 0.0 {
+                ret.abs() / max_abs * 0.8
+            } else {
+                0.0
+            };
+            let rgb = if *ret >= 0.0 {
+                "46,204,113"
+            } else {
+                "231,76,60"
+            };
+            let _ = write!(
+                out,
+                "<td style=\"background:rgba({},{:.2})\">{:+.1}%</td>",
+                rgb, alpha, ret
+            );
+        }
+        out.push_str("</tr>\n");
+    }
+    out.push_str("</table>\n");
+    out
+}
+
+/// `values` (one per curve point) as an SVG polyline with min/max and
+/// first/last date labels.
+fn line_chart(curve: &[(DateTime<Utc>, f64)], values: &[f64], color: &str, unit: &str) -> String {
+    if values.len() < 2 {
+        return "<p>Not enough data</p>\n".to_string();
+    }
+    let stride = values.len().div_ceil(MAX_POINTS);
+    let mut points: Vec<(usize, f64)> =
+        values.iter().copied().enumerate().step_by(stride).collect();
+    if points.last().map(|p| p.0) != Some(values.len() - 1) {
+        points.push((values.len() - 1, values[values.len() - 1]));
+    }
+
+    let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let span = if max > min { max - min } else { 1.0 };
+    let (pad_x, pad_y) = (70.0, 20.0);
+    let plot_w = CHART_WIDTH - pad_x - 10.0;
+    let plot_h = CHART_HEIGHT - 2.0 * pad_y;
+    let last = (values.len() - 1) as f64;
+
+    let mut polyline = String::new();
+    for (i, v) in points {
+        let x = pad_x + i as f64 / last * plot_w;
+        let y = pad_y + (max - v) / span * plot_h;
+        let _ = write!(polyline, "{:.1},{:.1} ", x, y);
+    }
+
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "<svg width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\" xmlns=\"http://www.w3.org/2000/svg\">",
+        w = CHART_WIDTH,
+        h = CHART_HEIGHT
+    );
+    let _ = writeln!(
+        out,
+        "<polyline fill=\"none\" stroke=\"{}\" stroke-width=\"1.5\" points=\"{}\"/>",
+        color,
+        polyline.trim_end()
+    );
+    let _ = writeln!(
+        out,
+        "<text x=\"4\" y=\"{:.0}\" font-size=\"11\">{:.2}{}</text>",
+        pad_y + 4.0,
+        max,
+        unit
+    );
+    let _ = writeln!(
+        out,
+        "<text x=\"4\" y=\"{:.0}\" font-size=\"11\">{:.2}{}</text>",
+        pad_y + plot_h,
+        min,
+        unit
+    );
+    let _ = writeln!(
+        out,
+        "<text x=\"{:.0}\" y=\"{:.0}\" font-size=\"11\">{}</text>",
+        pad_x,
+        CHART_HEIGHT - 4.0,
+        curve[0].0.format("%Y-%m-%d")
+    );
+    let _ = writeln!(
+        out,
+        "<text x=\"{:.0}\" y=\"{:.0}\" font-size=\"11\" text-anchor=\"end\">{}</text>",
+        CHART_WIDTH - 10.0,
+        CHART_HEIGHT - 4.0,
+        curve[curve.len() - 1].0.format("%Y-%m-%d")
+    );
+    out.push_str("</svg>\n");
+    out
+}
+
+fn money_cell(v: f64) -> String {
+    let class = if v >= 0.0 { "pos" } else { "neg" };
+    format!("<td class=\"{}\">${:+.2}</td>", class, v)
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+
+    #[test]
+    fn monthly_returns_chain_month_end_balances() {
+        let jan = Utc.with_ymd_and_hms(2024, 1, 10, 0, 0, 0).unwrap();
+        let curve = vec![
+            (jan, 1_000.0),
+            (jan + Duration::days(15), 1_100.0),
+            (jan + Duration::days(30), 990.0),
+            (jan + Duration::days(60), 990.0),
+        ];
+        let monthly = monthly_returns(1_000.0, &curve);
+        assert_eq!(monthly.len(), 3);
+        assert!((monthly[&(2024, 1)] - 10.0).abs() < 1e-9);
+        assert!((monthly[&(2024, 2)] + 10.0).abs() < 1e-9);
+        assert_eq!(monthly[&(2024, 3)], 0.0);
+
+        let dd = drawdown_pct(&[100.0, 120.0, 90.0, 130.0]);
+        assert_eq!(dd, vec![0.0, 0.0, -25.0, 0.0]);
+
+        let table = monthly_table(&monthly);
+        assert!(table.contains("<th>2024</th>"));
+        assert!(table.contains("+10.0%"));
+
+        let svg = line_chart(&curve, &[1.0, 2.0, 3.0, 2.0], "#000", "$");
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.contains("2024-03-10"));
+    }
+}
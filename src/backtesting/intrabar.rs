@@ -0,0 +1,86 @@
+use crate::models::Candle;
+
+/// Price path through a candle: open, both extremes, close.
+///
+/// Without tick data the order of the extremes is unknown. Up-closing
+/// candles are assumed to dip first (O -> L -> H -> C), down-closing ones
+/// to rally first (O -> H -> L -> C), which is the usual OHLC convention.
+pub fn ohlc_waypoints(c: &Candle) -> [f64; 4] {
+    if c.close >= c.open {
+        [c.open, c.low, c.high, c.close]
+    } else {
+        [c.open, c.high, c.low, c.close]
+    }
+}
+
+/// `points` synthetic ticks along the OHLC path, spaced evenly by distance
+/// travelled so every extreme is visited exactly. Always includes the open
+/// and close; fewer than 4 points returns just the waypoints.
+pub fn synthetic_ticks(c: &Candle, points: usize) -> Vec<f64> {
+    let w = ohlc_waypoints(c);
+    if points <= 4 {
+        return w.to_vec();
+    }
+
+    let legs = [(w[1] - w[0]).abs(), (w[2] - w[1]).abs(), (w[3] - w[2]).abs()];
+    let total: f64 = legs.iter().sum();
+    if total <= 0.0 {
+        return vec![c.open; points];
+    }
+
+    // Split interior ticks across legs by length; each leg ends on its waypoint
+    let interior = points - 4;
+    let mut per_leg = [0usize; 3];
+    let mut assigned = 0;
+    for (i, len) in legs.iter().enumerate() {
+        per_leg[i] = ((len / total) * interior as f64).floor() as usize;
+        assigned += per_leg[i];
+    }
+    // Remainder goes to the longest leg
+    if let Some(longest) = (0..3).max_by(|&a, &b| legs[a].total_cmp(&legs[b])) {
+        per_leg[longest] += interior - assigned;
+    }
+
+    let mut path = Vec::with_capacity(points);
+    path.push(w[0]);
+    for leg in 0..3 {
+        let (from, to) = (w[leg], w[leg + 1]);
+        let n = per_leg[leg] + 1;
+        for k in 1..=n {
+            path.push(from + (to - from) * k as f64 / n as f64);
+        }
+    }
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::make_candles;
+
+    #[test]
+    fn path_visits_extremes_in_order() {
+        let bull = &make_candles(&[(100.0, 110.0, 95.0, 108.0)])[0];
+        let path = synthetic_ticks(bull, 12);
+        assert_eq!(path.len(), 12);
+        assert_eq!(path[0], 100.0);
+        assert_eq!(*path.last().unwrap(), 108.0);
+        let lo = path.iter().position(|&p| p == 95.0).unwrap();
+        let hi = path.iter().position(|&p| p == 110.0).unwrap();
+        assert!(lo < hi, "bullish candle dips before the high");
+
+        let bear = &make_candles(&[(100.0, 104.0, 90.0, 92.0)])[0];
+        let path = synthetic_ticks(bear, 8);
+        let lo = path.iter().position(|&p| p == 90.0).unwrap();
+        let hi = path.iter().position(|&p| p == 104.0).unwrap();
+        assert!(hi < lo, "bearish candle rallies before the low");
+    }
+
+    #[test]
+    fn degenerate_candles() {
+        let flat = &make_candles(&[(100.0, 100.0, 100.0, 100.0)])[0];
+        assert_eq!(synthetic_ticks(flat, 6), vec![100.0; 6]);
+        let c = &make_candles(&[(100.0, 101.0, 99.0, 100.5)])[0];
+        assert_eq!(synthetic_ticks(c, 2), vec![100.0, 99.0, 101.0, 100.5]);
+    }
+}
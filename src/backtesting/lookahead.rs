@@ -0,0 +1,234 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::monte_carlo::SplitMix64;
+use super::runner::BacktestRunner;
+use crate::config::Config;
+use crate::core::pd_arrays::PdArrayDetector;
+use crate::core::structure::MarketStructure;
+use crate::exchange::HistoricalExchange;
+use crate::models::{CandleSeries, Timeframe};
+
+/// Candles an indicator probe sees before the cut (the runner's lookback)
+const PROBE_WINDOW: usize = 200;
+/// Probe outputs compared are those stamped in the last this many candles
+const PROBE_HORIZON: usize = 20;
+/// Most future candles appended to a probe window
+const MAX_APPENDED: u64 = 10;
+/// Truncation points tried per probe and timeframe
+const PROBE_CUTS: usize = 50;
+/// Timeframes the indicator probes run on
+const PROBE_TIMEFRAMES: [Timeframe; 2] = [Timeframe::M5, Timeframe::M15];
+
+/// One look-ahead finding.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Violation {
+    /// `exchange` or the indicator probe's name
+    pub source: String,
+    pub detail: String,
+}
+
+/// Findings of a look-ahead audit: candles served from the future during
+/// a backtest and indicator outputs that changed once later candles were
+/// appended.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LookaheadReport {
+    pub fetches: usize,
+    /// Candles served past the sim time, including those not listed
+    pub future_candles: usize,
+    /// Indicator windows compared before and after appending candles
+    pub probe_runs: usize,
+    pub violations: Vec<Violation>,
+}
+
+impl LookaheadReport {
+    pub fn is_clean(&self) -> bool {
+        self.violations.is_empty()
+    }
+
+    pub fn print_summary(&self) {
+        println!("\n{}", "=".repeat(70));
+        println!("  LOOK-AHEAD AUDIT");
+        println!("{}", "=".repeat(70));
+        println!("  Candle fetches checked:  {}", self.fetches);
+        println!("  Future candles served:   {}", self.future_candles);
+        println!("  Indicator probes run:    {}", self.probe_runs);
+        if self.is_clean() {
+            println!("  No violations");
+        } else {
+            println!("  Violations:              {}", self.violations.len());
+            for v in &self.violations {
+                println!("    [{}] {}", v.source, v.detail);
+            }
+        }
+        println!("{}", "=".repeat(70));
+    }
+}
+
+/// Outputs of an indicator as `(timestamp, identity)` pairs. Identities
+/// leave out state that legitimately changes later (e.g. `broken`).
+pub type Probe = fn(&CandleSeries) -> Vec<(DateTime<Utc>, String)>;
+
+/// The indicator probes the audit fuzzes.
+pub fn probes() -> Vec<(&'static str, Probe)> {
+    vec![("structure", swing_probe), ("pd_arrays", pda_probe)]
+}
+
+fn swing_probe(candles: &CandleSeries) -> Vec<(DateTime<Utc>, String)> {
+    let mut ms = MarketStructure::new();
+    ms.analyze(candles);
+    ms.swing_highs
+        .iter()
+        .chain(ms.swing_lows.iter())
+        .map(|s| (s.timestamp, format!("{} {}", s.swing_type, s.price)))
+        .collect()
+}
+
+fn pda_probe(candles: &CandleSeries) -> Vec<(DateTime<Utc>, String)> {
+    let mut detector = PdArrayDetector::new();
+    let tf = Timeframe::M5;
+    detector
+        .detect_all(candles, tf, 0.0005, 20, 30)
+        .iter()
+        .map(|p| {
+            let id = format!("{} {} {}-{}", p.pda_type, p.direction, p.low, p.high);
+            (p.timestamp, id)
+        })
+        .collect()
+}
+
+/// Run `probe` on windows of `candles` truncated at random points, then
+/// again with up to `MAX_APPENDED` later candles appended. Any output
+/// stamped in the window's last `PROBE_HORIZON` candles that disappears
+/// or changes used data from after the cut.
+pub fn fuzz_probe(
+    name: &str,
+    probe: Probe,
+    candles: &CandleSeries,
+    cuts: usize,
+    seed: u64,
+) -> (usize, Vec<Violation>) {
+    let n = candles.len();
+    if n < PROBE_WINDOW + MAX_APPENDED as usize {
+        return (0, Vec::new());
+    }
+    let mut rng = SplitMix64(seed);
+    let mut violations = Vec::new();
+    let mut runs = 0;
+    for _ in 0..cuts {
+        let span = (n - PROBE_WINDOW - MAX_APPENDED as usize + 1) as u64;
+        let cut = PROBE_WINDOW + (rng.next() % span) as usize;
+        let appended = 1 + (rng.next() % MAX_APPENDED) as usize;
+        let start = cut - PROBE_WINDOW;
+        let since = candles[cut - PROBE_HORIZON].timestamp;
+
+        let before = probe(&candles.slice(start, cut));
+        let after = probe(&candles.slice(start, cut + appended));
+        runs += 1;
+        for (ts, id) in before.iter().filter(|(ts, _)| *ts >= since) {
+            if !after.iter().any(|(t, i)| t == ts && i == id) {
+                violations.push(Violation {
+                    source: name.to_string(),
+                    detail: format!(
+                        "{} at {} changed after {} candle(s) past {}",
+                        id,
+                        ts,
+                        appended,
+                        candles[cut - 1].timestamp
+                    ),
+                });
+            }
+        }
+    }
+    (runs, violations)
+}
+
+/// Backtest `[start, end)` with the exchange checking every candle it
+/// serves against the sim time, then fuzz the indicator probes on the
+/// same data.
+pub async fn audit(
+    exchange: HistoricalExchange,
+    cfg: Config,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    step_minutes: i64,
+) -> Result<LookaheadReport> {
+    let data: Vec<(Timeframe, CandleSeries)> = PROBE_TIMEFRAMES
+        .iter()
+        .map(|tf| (*tf, exchange.series(*tf)))
+        .collect();
+    let mut runner = BacktestRunner::new(exchange.with_audit(), cfg);
+    runner.run(start, end, step_minutes).await?;
+
+    let audit = runner.exchange.audit().cloned().unwrap_or_default();
+    let mut report = LookaheadReport {
+        fetches: audit.fetches,
+        future_candles: audit.future_candles,
+        probe_runs: 0,
+        violations: audit
+            .violations
+            .into_iter()
+            .map(|detail| Violation {
+                source: "exchange".to_string(),
+                detail,
+            })
+            .collect(),
+    };
+    for (tf, candles) in &data {
+        for (i, (name, probe)) in probes().into_iter().enumerate() {
+            let (runs, found) = fuzz_probe(name, probe, candles, PROBE_CUTS, i as u64 + 1);
+            report.probe_runs += runs;
+            report.violations.extend(found.into_iter().map(|mut v| {
+                v.source = format!("{} {}", v.source, tf);
+                v
+            }));
+        }
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exchange::historical::CandleAudit;
+    use crate::test_helpers::make_candles;
+
+    /// Marks the highest close of the window: repaints when a later
+    /// candle closes higher.
+    fn repainting(candles: &CandleSeries) -> Vec<(DateTime<Utc>, String)> {
+        candles
+            .iter()
+            .max_by(|a, b| a.close.total_cmp(&b.close))
+            .map(|c| vec![(c.timestamp, "top".to_string())])
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn appended_candles_expose_repainting_outputs() {
+        let data: Vec<(f64, f64, f64, f64)> = (0..400)
+            .map(|i| {
+                let p = 100.0 + i as f64 * 0.1;
+                (p, p + 0.5, p - 0.5, p + 0.05)
+            })
+            .collect();
+        let candles = make_candles(&data);
+
+        let (runs, found) = fuzz_probe("top", repainting, &candles, 10, 1);
+        assert_eq!(runs, 10);
+        // A rising market makes every new candle the top
+        assert_eq!(found.len(), 10);
+        assert_eq!(found[0].source, "top");
+
+        let (_, found) = fuzz_probe("structure", swing_probe, &candles, 10, 1);
+        assert!(found.is_empty(), "{:?}", found);
+        assert_eq!(fuzz_probe("top", repainting, &candles.head(50), 10, 1).0, 0);
+
+        let mut audit = CandleAudit::default();
+        let now = candles[9].timestamp;
+        audit.check(Timeframe::M1, now, &candles.head(10));
+        audit.check(Timeframe::M1, now, &candles.head(12));
+        assert_eq!((audit.fetches, audit.future_candles), (2, 2));
+        assert_eq!(audit.violations.len(), 2);
+    }
+}
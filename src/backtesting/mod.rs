@@ -1,6 +1,19 @@
+pub mod ab_test;
+pub mod candle_store;
+pub mod commands;
 pub mod data_fetcher;
+pub mod html;
+pub mod intrabar;
+pub mod lookahead;
+pub mod monte_carlo;
+pub mod optimizer;
+pub mod replay;
 pub mod report;
 pub mod runner;
+pub mod seasonality;
+pub mod verify;
+pub mod walk_forward;
 
 pub use report::BacktestReport;
 pub use runner::BacktestRunner;
+pub use seasonality::Seasonality;
@@ -0,0 +1,237 @@
+use serde::{Deserialize, Serialize};
+
+use super::report::BacktestReport;
+use crate::config::Config;
+
+/// How many orderings to try and what counts as ruin (`MC_RUNS`,
+/// `MC_RUIN_PCT` and `MC_SEED`).
+#[derive(Debug, Clone, Copy)]
+pub struct MonteCarloSettings {
+    pub runs: usize,
+    /// Balance this far (%) below the starting balance = ruined
+    pub ruin_pct: f64,
+    pub seed: u64,
+}
+
+impl MonteCarloSettings {
+    pub fn from_config(cfg: &Config) -> Self {
+        Self {
+            runs: cfg.mc_runs,
+            ruin_pct: cfg.mc_ruin_pct,
+            seed: cfg.mc_seed,
+        }
+    }
+}
+
+/// Percentiles of one statistic across the simulated runs.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Distribution {
+    pub mean: f64,
+    pub p5: f64,
+    pub p50: f64,
+    pub p95: f64,
+    pub worst: f64,
+}
+
+impl Distribution {
+    /// `higher_is_worse`: whether `worst` is the maximum (drawdowns) or
+    /// the minimum.
+    fn of(mut values: Vec<f64>, higher_is_worse: bool) -> Self {
+        if values.is_empty() {
+            return Self::default();
+        }
+        values.sort_by(f64::total_cmp);
+        let at = |q: f64| values[((values.len() - 1) as f64 * q).round() as usize];
+        Self {
+            mean: values.iter().sum::<f64>() / values.len() as f64,
+            p5: at(0.05),
+            p50: at(0.5),
+            p95: at(0.95),
+            worst: if higher_is_worse {
+                values[values.len() - 1]
+            } else {
+                values[0]
+            },
+        }
+    }
+}
+
+/// Outcome of replaying a backtest's trades in random orders. Sizing is
+/// a fraction of the running balance (Kelly), so each trade is replayed
+/// as a return on the balance before it rather than a fixed PnL.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MonteCarloReport {
+    pub runs: usize,
+    pub trades: usize,
+    /// Max peak-to-trough drawdown (%)
+    pub max_drawdown_pct: Distribution,
+    /// Longest stretch below a prior peak, in trades
+    pub recovery_trades: Distribution,
+    /// Share of runs (%) that touched the ruin level
+    pub risk_of_ruin_pct: f64,
+    pub ruin_pct: f64,
+    /// The backtest's own drawdown, for comparison
+    pub backtest_max_drawdown_pct: f64,
+    /// Average days per trade in the backtest, to read recovery in days
+    pub days_per_trade: f64,
+}
+
+impl MonteCarloReport {
+    /// Simulate `report`'s trades; `None` when it has fewer than two.
+    pub fn from_backtest(report: &BacktestReport, settings: MonteCarloSettings) -> Option<Self> {
+        let returns = &report.trade_returns;
+        if returns.len() < 2 || settings.runs == 0 {
+            return None;
+        }
+        let mut rng = SplitMix64(settings.seed);
+        let mut order = returns.clone();
+        let mut drawdowns = Vec::with_capacity(settings.runs);
+        let mut recoveries = Vec::with_capacity(settings.runs);
+        let mut ruined = 0;
+        for _ in 0..settings.runs {
+            rng.shuffle(&mut order);
+            let path = replay(&order, settings.ruin_pct);
+            drawdowns.push(path.max_drawdown_pct);
+            recoveries.push(path.longest_underwater as f64);
+            ruined += usize::from(path.ruined);
+        }
+        Some(Self {
+            runs: settings.runs,
+            trades: returns.len(),
+            max_drawdown_pct: Distribution::of(drawdowns, true),
+            recovery_trades: Distribution::of(recoveries, true),
+            risk_of_ruin_pct: ruined as f64 / settings.runs as f64 * 100.0,
+            ruin_pct: settings.ruin_pct,
+            backtest_max_drawdown_pct: report.max_drawdown_pct,
+            days_per_trade: report.days / returns.len() as f64,
+        })
+    }
+
+    pub fn print_summary(&self) {
+        let dd = &self.max_drawdown_pct;
+        let rec = &self.recovery_trades;
+        println!("\n{}", "=".repeat(70));
+        println!(
+            "  MONTE CARLO ({} reorderings of {} trades)",
+            self.runs, self.trades
+        );
+        println!("{}", "=".repeat(70));
+        println!(
+            "  Max DD:      median {:.1}% | p95 {:.1}% | worst {:.1}% (backtest {:.1}%)",
+            dd.p50, dd.p95, dd.worst, self.backtest_max_drawdown_pct
+        );
+        println!(
+            "  Recovery:    median {:.0} trades (~{:.1}d) | p95 {:.0} (~{:.1}d) | worst {:.0}",
+            rec.p50,
+            rec.p50 * self.days_per_trade,
+            rec.p95,
+            rec.p95 * self.days_per_trade,
+            rec.worst
+        );
+        println!(
+            "  Ruin:        {:.2}% of runs fell {:.0}% below the starting balance",
+            self.risk_of_ruin_pct, self.ruin_pct
+        );
+        println!("{}", "=".repeat(70));
+    }
+}
+
+struct Path {
+    max_drawdown_pct: f64,
+    longest_underwater: usize,
+    ruined: bool,
+}
+
+/// Compound `returns` from a balance of 1.0.
+fn replay(returns: &[f64], ruin_pct: f64) -> Path {
+    let ruin_level = 1.0 - ruin_pct / 100.0;
+    let (mut balance, mut peak) = (1.0_f64, 1.0_f64);
+    let mut path = Path {
+        max_drawdown_pct: 0.0,
+        longest_underwater: 0,
+        ruined: false,
+    };
+    let mut underwater = 0;
+    for r in returns {
+        balance *= 1.0 + r;
+        if balance >= peak {
+            peak = balance;
+            underwater = 0;
+        } else {
+            underwater += 1;
+            path.longest_underwater = path.longest_underwater.max(underwater);
+            path.max_drawdown_pct = path.max_drawdown_pct.max((peak - balance) / peak * 100.0);
+        }
+        path.ruined |= balance <= ruin_level;
+    }
+    path
+}
+
+/// Small seeded generator so simulations are reproducible.
+pub(crate) struct SplitMix64(pub(crate) u64);
+
+impl SplitMix64 {
+    pub(crate) fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Fisher-Yates
+    fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            let j = (self.next() % (i as u64 + 1)) as usize;
+            items.swap(i, j);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::default_test_config;
+    use crate::trading::paper_trader::PaperTrader;
+    use chrono::{Duration, TimeZone, Utc};
+
+    #[test]
+    fn reorderings_bound_the_drawdown() {
+        // Two 10% losses then two 10% wins: together they draw down 19%
+        let returns = [-0.1, -0.1, 0.1, 0.1];
+        let path = replay(&returns, 50.0);
+        assert!((path.max_drawdown_pct - 19.0).abs() < 1e-9);
+        assert_eq!(path.longest_underwater, 4);
+        assert!(!path.ruined);
+        assert!(replay(&[-0.3, -0.3], 50.0).ruined);
+
+        let cfg = default_test_config();
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let mut report = BacktestReport::from_backtest(
+            &PaperTrader::new_fresh(&cfg),
+            &cfg,
+            start,
+            start + Duration::days(4),
+            Vec::new(),
+            19.0,
+            19.0,
+            4,
+            0,
+        );
+        report.trade_returns = returns.to_vec();
+        let settings = MonteCarloSettings {
+            runs: 500,
+            ..MonteCarloSettings::from_config(&cfg)
+        };
+        let mc = MonteCarloReport::from_backtest(&report, settings).unwrap();
+        // Alternating wins and losses draw down only 10%
+        assert!((mc.max_drawdown_pct.worst - 19.0).abs() < 1e-9);
+        assert!(mc.max_drawdown_pct.p5 < 19.0);
+        assert_eq!(mc.risk_of_ruin_pct, 0.0);
+        assert_eq!(mc.days_per_trade, 1.0);
+
+        // Same seed, same answer
+        let again = MonteCarloReport::from_backtest(&report, settings).unwrap();
+        assert_eq!(again.max_drawdown_pct, mc.max_drawdown_pct);
+    }
+}
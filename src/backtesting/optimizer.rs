@@ -0,0 +1,233 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+use tracing::info;
+
+use crate::config::{Config, TpAllocMode};
+use crate::exchange::HistoricalExchange;
+use crate::trading::strategy_refiner::StrategyRefiner;
+
+use super::report::BacktestReport;
+use super::runner::BacktestRunner;
+
+/// Values to sweep; an empty list keeps the base config's value.
+#[derive(Debug, Clone, Default)]
+pub struct ParamGrid {
+    /// Applied to every scale's `min_confidence`
+    pub min_confidence: Vec<f64>,
+    pub fvg_min_gap_percent: Vec<f64>,
+    pub ob_lookback: Vec<usize>,
+    pub tp_alloc: Vec<TpAllocMode>,
+}
+
+impl ParamGrid {
+    /// Grids of `OPT_MIN_CONFIDENCE`, `OPT_FVG_MIN_GAP`, `OPT_OB_LOOKBACK`
+    /// and `OPT_TP_ALLOC`.
+    pub fn from_config(cfg: &Config) -> Self {
+        Self {
+            min_confidence: cfg.opt_min_confidence.clone(),
+            fvg_min_gap_percent: cfg.opt_fvg_min_gap.clone(),
+            ob_lookback: cfg.opt_ob_lookback.clone(),
+            tp_alloc: cfg.opt_tp_alloc.clone(),
+        }
+    }
+
+    /// Cartesian product of all non-empty grids.
+    pub fn combinations(&self) -> Vec<ParamSet> {
+        fn axis<T: Copy>(values: &[T]) -> Vec<Option<T>> {
+            if values.is_empty() {
+                vec![None]
+            } else {
+                values.iter().copied().map(Some).collect()
+            }
+        }
+
+        let mut out = Vec::new();
+        for &min_confidence in &axis(&self.min_confidence) {
+            for &fvg_min_gap_percent in &axis(&self.fvg_min_gap_percent) {
+                for &ob_lookback in &axis(&self.ob_lookback) {
+                    for &tp_alloc in &axis(&self.tp_alloc) {
+                        out.push(ParamSet {
+                            min_confidence,
+                            fvg_min_gap_percent,
+                            ob_lookback,
+                            tp_alloc,
+                        });
+                    }
+                }
+            }
+        }
+        out
+    }
+}
+
+/// One point in the grid; `None` = base config value.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct ParamSet {
+    pub min_confidence: Option<f64>,
+    pub fvg_min_gap_percent: Option<f64>,
+    pub ob_lookback: Option<usize>,
+    pub tp_alloc: Option<TpAllocMode>,
+}
+
+impl ParamSet {
+    pub fn apply(&self, base: &Config) -> Config {
+        let mut cfg = base.clone();
+        if let Some(v) = self.min_confidence {
+            for scale in cfg.hft_scales.values_mut() {
+                scale.min_confidence = v;
+            }
+        }
+        if let Some(v) = self.fvg_min_gap_percent {
+            cfg.fvg_min_gap_percent = v;
+        }
+        if let Some(v) = self.ob_lookback {
+            cfg.ob_lookback = v;
+        }
+        if let Some(v) = self.tp_alloc {
+            cfg.tp_alloc_mode = v;
+        }
+        cfg
+    }
+
+    pub fn label(&self) -> String {
+        let mut parts = Vec::new();
+        if let Some(v) = self.min_confidence {
+            parts.push(format!("conf={:.2}", v));
+        }
+        if let Some(v) = self.fvg_min_gap_percent {
+            parts.push(format!("fvg={}", v));
+        }
+        if let Some(v) = self.ob_lookback {
+            parts.push(format!("ob={}", v));
+        }
+        if let Some(v) = self.tp_alloc {
+            parts.push(format!("tp={:?}", v).to_lowercase());
+        }
+        if parts.is_empty() {
+            "base".to_string()
+        } else {
+            parts.join(" ")
+        }
+    }
+}
+
+pub struct OptimizationResult {
+    pub params: ParamSet,
+    pub report: BacktestReport,
+}
+
+/// Run one backtest per grid combination, at most `parallelism` at a time,
+/// and return results ranked by PnL (Sharpe breaks ties).
+pub async fn optimize(
+    exchange: HistoricalExchange,
+    config: Config,
+    grid: &ParamGrid,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    step_minutes: i64,
+    parallelism: usize,
+) -> Result<Vec<OptimizationResult>> {
+    let combos = grid.combinations();
+    info!(
+        "Optimizer: {} combinations, {} at a time",
+        combos.len(),
+        parallelism.max(1)
+    );
+
+    let permits = Arc::new(Semaphore::new(parallelism.max(1)));
+    let exchange = Arc::new(exchange);
+    let mut handles = Vec::new();
+    for params in combos {
+        let permits = permits.clone();
+        let exchange = exchange.clone();
+        let cfg = params.apply(&config);
+        handles.push(tokio::spawn(async move {
+            let _permit = permits.acquire_owned().await?;
+            // In-memory refiner so runs neither share nor persist refinements
+            let refiner = StrategyRefiner::in_memory(&cfg);
+            let mut runner =
+                BacktestRunner::new(exchange.as_ref().clone(), cfg).with_refiner(refiner);
+            let report = runner.run(start, end, step_minutes).await?;
+            info!(
+                "  {} -> PnL ${:+.2} ({} trades)",
+                params.label(),
+                report.total_pnl,
+                report.total_trades
+            );
+            anyhow::Ok(OptimizationResult { params, report })
+        }));
+    }
+
+    let mut results = Vec::new();
+    for handle in handles {
+        results.push(handle.await??);
+    }
+    rank(&mut results);
+    Ok(results)
+}
+
+fn rank(results: &mut [OptimizationResult]) {
+    results.sort_by(|a, b| {
+        b.report
+            .total_pnl
+            .total_cmp(&a.report.total_pnl)
+            .then(b.report.sharpe_ratio.total_cmp(&a.report.sharpe_ratio))
+    });
+}
+
+/// Ranked comparison table.
+pub fn print_ranking(results: &[OptimizationResult]) {
+    println!("\n{}", "=".repeat(100));
+    println!("  OPTIMIZER RESULTS ({} combinations)", results.len());
+    println!("{}", "=".repeat(100));
+    println!(
+        "  {:>3}  {:<40} {:>11} {:>7} {:>6} {:>6} {:>7} {:>7}",
+        "#", "Params", "PnL", "Trades", "WR%", "PF", "Sharpe", "MaxDD%"
+    );
+    for (i, r) in results.iter().enumerate() {
+        println!(
+            "  {:>3}  {:<40} {:>+11.2} {:>7} {:>6.1} {:>6.2} {:>7.2} {:>7.1}",
+            i + 1,
+            r.params.label(),
+            r.report.total_pnl,
+            r.report.total_trades,
+            r.report.win_rate,
+            r.report.profit_factor,
+            r.report.sharpe_ratio,
+            r.report.max_drawdown_pct,
+        );
+    }
+    println!("{}", "=".repeat(100));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::default_test_config;
+
+    #[test]
+    fn grid_expands_to_cartesian_product() {
+        let grid = ParamGrid {
+            min_confidence: vec![0.5, 0.6],
+            ob_lookback: vec![10, 20, 30],
+            tp_alloc: vec![TpAllocMode::Aggressive],
+            ..Default::default()
+        };
+        let combos = grid.combinations();
+        assert_eq!(combos.len(), 6);
+        assert!(combos.iter().all(|c| c.fvg_min_gap_percent.is_none()));
+
+        let base = default_test_config();
+        let cfg = combos[5].apply(&base);
+        assert!(cfg.hft_scales.values().all(|s| s.min_confidence == 0.6));
+        assert_eq!(cfg.ob_lookback, 30);
+        assert_eq!(cfg.tp_alloc_mode, TpAllocMode::Aggressive);
+        assert_eq!(cfg.fvg_min_gap_percent, base.fvg_min_gap_percent);
+        assert_eq!(combos[5].label(), "conf=0.60 ob=30 tp=aggressive");
+
+        assert_eq!(ParamGrid::default().combinations().len(), 1);
+        assert_eq!(ParamSet::default().label(), "base");
+    }
+}
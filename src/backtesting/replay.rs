@@ -0,0 +1,155 @@
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use std::path::Path;
+
+use crate::config::Config;
+use crate::core::pd_arrays::{ImbalanceParams, PdArrayDetector};
+use crate::core::structure::MarketStructure;
+use crate::exchange::HistoricalExchange;
+use crate::models::{CandleSeries, PositionStatus, Timeframe};
+use crate::storage;
+use crate::trading::paper_trader::Position;
+use crate::trading::trade_record::TradeReplay;
+
+/// Replay bundle of every closed trade in `history`: `bars` entry-timeframe
+/// candles either side of the trade, plus the PDAs and swings detected on
+/// the candles that had closed by entry.
+pub fn build(
+    history: &[Position],
+    exchange: &HistoricalExchange,
+    cfg: &Config,
+    bars: usize,
+) -> Vec<TradeReplay> {
+    history
+        .iter()
+        .filter(|p| {
+            !matches!(
+                p.status,
+                PositionStatus::Open | PositionStatus::Pending | PositionStatus::Cancelled
+            )
+        })
+        .filter_map(|p| replay(p, exchange, cfg, bars))
+        .collect()
+}
+
+fn replay(
+    pos: &Position,
+    exchange: &HistoricalExchange,
+    cfg: &Config,
+    bars: usize,
+) -> Option<TradeReplay> {
+    let tf = cfg
+        .hft_scales
+        .get(&pos.scale)
+        .map(|s| s.entry_tf)
+        .or_else(|| Timeframe::from_str_loose(&pos.scale))?;
+    let entry = storage::entry_time(pos)?;
+    let exit = pos
+        .exit_time
+        .as_deref()
+        .and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+        .map_or(entry, |t| t.with_timezone(&Utc));
+    let bar = Duration::from_std(tf.as_duration()).ok()?;
+    let span = bar * bars as i32;
+    let candles = exchange
+        .candles_between(tf, entry - span, exit + span)
+        .to_vec();
+
+    // What the engine could have seen: candles closed by entry
+    let seen = CandleSeries::new(
+        candles
+            .iter()
+            .filter(|c| c.timestamp + bar <= entry)
+            .cloned()
+            .collect(),
+    );
+    let mut detector = PdArrayDetector::new().with_imbalances(ImbalanceParams::from_config(cfg));
+    let pdas = detector
+        .detect_all(
+            &seen,
+            tf,
+            cfg.fvg_min_gap_percent,
+            cfg.ob_lookback,
+            cfg.breaker_lookback,
+        )
+        .to_vec();
+    let mut structure = MarketStructure::with_lookback(cfg.swing_lookback);
+    structure.analyze(&seen);
+
+    let mut replay = TradeReplay::new(pos, tf, candles);
+    replay.pdas = pdas;
+    replay.swing_highs = structure.swing_highs;
+    replay.swing_lows = structure.swing_lows;
+    Some(replay)
+}
+
+/// Write `replays` to `path` as a JSON array.
+pub fn save(replays: &[TradeReplay], path: &Path) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, serde_json::to_string_pretty(replays)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Candle, CloseReason, Direction};
+    use crate::test_helpers::default_test_config;
+
+    #[test]
+    fn replays_closed_trades_with_surrounding_candles() {
+        let cfg = default_test_config();
+        let start = DateTime::parse_from_rfc3339("2024-01-15T12:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let candles: Vec<Candle> = (0..60)
+            .map(|i| {
+                let open = 100.0 + (i % 7) as f64;
+                Candle {
+                    timestamp: start + Duration::minutes(5 * i),
+                    open,
+                    high: open + 2.0,
+                    low: open - 2.0,
+                    close: open + 1.0,
+                    volume: 10.0,
+                }
+            })
+            .collect();
+        let mut exchange = HistoricalExchange::new();
+        exchange.load(Timeframe::M5, candles);
+
+        let trade = |id: u64, status: &str| -> Position {
+            serde_json::from_value(serde_json::json!({
+                "id": id, "symbol": "BTC-USD", "direction": "long", "entry_price": 104.0,
+                "size_usd": 100.0, "size_btc": 1.0, "stop_loss": 101.0,
+                "initial_stop_loss": 100.0, "take_profit": 110.0,
+                "entry_time": "2024-01-15T14:00:00Z", "exit_time": "2024-01-15T14:30:00Z",
+                "exit_price": 101.0, "close_reason": "stop_loss",
+                "reason": "test", "scale": "5m", "status": status, "pnl": -3.0,
+            }))
+            .unwrap()
+        };
+        let history = vec![trade(1, "closed_sl"), trade(2, "open")];
+
+        let replays = build(&history, &exchange, &cfg, 10);
+        assert_eq!(replays.len(), 1);
+        let r = &replays[0];
+        assert_eq!(r.position_id, 1);
+        assert_eq!(r.timeframe, Timeframe::M5);
+        assert_eq!(r.direction, Direction::Long);
+        assert_eq!(r.close_reason, Some(CloseReason::StopLoss));
+        assert_eq!(r.initial_stop_loss, 100.0);
+        // 13:15 to 15:20: ten 5m bars either side of the 14:00-14:30 trade
+        assert_eq!(r.candles.len(), 26);
+        assert_eq!(r.candles[0].timestamp, start + Duration::minutes(75));
+        // Structure only from the 13:15-13:55 candles closed by entry
+        let seen_until = r.candles[8].timestamp;
+        assert!(r.swing_highs.iter().all(|s| s.timestamp <= seen_until));
+        assert!(r.pdas.iter().all(|p| p.timestamp <= seen_until));
+
+        let json = serde_json::to_value(&replays).unwrap();
+        assert_eq!(json[0]["candles"].as_array().unwrap().len(), 26);
+    }
+}
@@ -1,21 +1,43 @@
-use chrono::{DateTime, Utc};
+use anyhow::Result;
+use chrono::{DateTime, Datelike, Timelike, Utc};
+use chrono_tz::US::Eastern;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::path::Path;
 
-use crate::config::Config;
-use crate::trading::paper_trader::PaperTrader;
+use super::seasonality::{Seasonality, WEEKDAYS};
+use crate::config::{Config, FillTiming};
+use crate::core::r_multiple::RStats;
+use crate::models::{Instrument, PositionStatus};
+use crate::storage;
+use crate::trading::paper_trader::{PaperTrader, Position};
+use crate::trading::trade_analyzer::{BucketStats, ExitMix, TradeAnalyzer};
+use crate::trading::trade_record::TradeRecord;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BacktestReport {
     // Period
     pub start: DateTime<Utc>,
     pub end: DateTime<Utc>,
     pub days: f64,
+    /// Entry fill convention the run used
+    pub fill_timing: FillTiming,
+    /// Signal-to-fill delay of `NextBarOpen` fills (seconds after the next bar opens)
+    #[serde(default)]
+    pub fill_latency_secs: i64,
+    pub instrument: Instrument,
+    /// `Config::config_hash` of the run; equal hashes mean equal settings
+    #[serde(default)]
+    pub config_hash: String,
 
     // Performance
     pub initial_balance: f64,
     pub final_balance: f64,
     pub total_pnl: f64,
     pub total_return_pct: f64,
+    /// Net perpetual funding paid (negative = received), included in PnL
+    pub funding_paid: f64,
 
     // Trades
     pub total_trades: usize,
@@ -28,11 +50,38 @@ pub struct BacktestReport {
     pub best_trade: f64,
     pub worst_trade: f64,
     pub avg_trade: f64,
+    /// Results in R (multiples of each trade's entry risk)
+    #[serde(default)]
+    pub r_stats: Option<RStats>,
+    /// Pyramided positions with their tranches folded in
+    #[serde(default)]
+    pub pyramid: Option<PyramidStats>,
+    /// Mean MAE/MFE by outcome (`win`, `loss`)
+    #[serde(default)]
+    pub excursions: HashMap<String, ExcursionStats>,
 
     // Risk
     pub max_drawdown: f64,
     pub max_drawdown_pct: f64,
     pub sharpe_ratio: f64,
+    /// Like Sharpe, but only downside daily returns count as risk
+    #[serde(default)]
+    pub sortino_ratio: f64,
+    /// Compound annual growth rate (%)
+    #[serde(default)]
+    pub cagr_pct: f64,
+    /// CAGR over max drawdown (%)
+    #[serde(default)]
+    pub calmar_ratio: f64,
+    /// Share (%) of the period with at least one position open
+    #[serde(default)]
+    pub time_in_market_pct: f64,
+    /// Time-weighted mean number of open positions
+    #[serde(default)]
+    pub avg_concurrent_positions: f64,
+    /// Most consecutive losing trades, in close order
+    #[serde(default)]
+    pub longest_losing_streak: usize,
 
     // Signals
     pub total_signals: usize,
@@ -44,11 +93,38 @@ pub struct BacktestReport {
     // By session
     pub session_stats: HashMap<String, SessionStats>,
 
+    // By exit cause
+    pub exit_mix: Vec<ExitMix>,
+
+    /// Edge per PDA type, stop mode, CISD status, tag and strategy
+    #[serde(default)]
+    pub attribution: Vec<BucketStats>,
+
+    // By ET weekday × hour of entry
+    pub seasonality: Seasonality,
+
+    /// By ET weekday of entry (`Mon`..`Sun`)
+    #[serde(default)]
+    pub weekday_stats: HashMap<String, TimeStats>,
+
+    /// By ET hour of entry (0-23)
+    #[serde(default)]
+    pub hour_of_day_stats: HashMap<u32, TimeStats>,
+
     // Equity curve
     pub equity_curve: Vec<(DateTime<Utc>, f64)>,
+
+    /// Each closed trade's PnL as a fraction of the balance before it, in
+    /// close order (Monte Carlo input)
+    #[serde(default)]
+    pub trade_returns: Vec<f64>,
+
+    /// Effective settings of the run (`Config::snapshot`)
+    #[serde(default)]
+    pub config: serde_json::Value,
 }
 
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ScaleStats {
     pub trades: usize,
     pub wins: usize,
@@ -56,18 +132,177 @@ pub struct ScaleStats {
     pub win_rate: f64,
     pub total_pnl: f64,
     pub avg_pnl: f64,
+    /// Mean entry fill vs signal price (bps, positive = improvement)
+    pub avg_entry_improvement_bps: f64,
+}
+
+/// Positions that added tranches, each counted once with its adds.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PyramidStats {
+    pub positions: usize,
+    pub adds: usize,
+    /// PnL of the tranches alone
+    pub add_pnl: f64,
+    /// PnL of the positions including their tranches
+    pub total_pnl: f64,
+    /// Share (%) of pyramided positions net positive
+    pub win_rate: f64,
+}
+
+impl PyramidStats {
+    /// `None` when no tranche was added.
+    pub fn from_history(history: &[Position]) -> Option<Self> {
+        let mut logical: HashMap<u64, f64> = HashMap::new();
+        let mut adds = 0;
+        let mut add_pnl = 0.0;
+        for t in history {
+            if let Some(parent) = t.parent_id {
+                adds += 1;
+                add_pnl += t.pnl;
+                *logical.entry(parent).or_default() += t.pnl;
+            }
+        }
+        if adds == 0 {
+            return None;
+        }
+        for t in history {
+            if let Some(pnl) = logical.get_mut(&t.id) {
+                *pnl += t.pnl;
+            }
+        }
+        let wins = logical.values().filter(|&&pnl| pnl > 0.0).count();
+        Some(Self {
+            positions: logical.len(),
+            adds,
+            add_pnl,
+            total_pnl: logical.values().sum(),
+            win_rate: wins as f64 / logical.len() as f64 * 100.0,
+        })
+    }
+
+    /// One-line summary for report output.
+    pub fn summary(&self) -> String {
+        format!(
+            "{} positions +{} adds | WR {:.0}% | PnL ${:+.2} (adds ${:+.2})",
+            self.positions, self.adds, self.win_rate, self.total_pnl, self.add_pnl
+        )
+    }
 }
 
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct SessionStats {
     pub trades: usize,
     pub wins: usize,
     pub losses: usize,
     pub win_rate: f64,
     pub total_pnl: f64,
+    /// Mean entry fill vs signal price (bps, positive = improvement)
+    pub avg_entry_improvement_bps: f64,
+}
+
+/// Mean excursions of the trades with one outcome, in R (distance from
+/// entry over the initial stop distance).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExcursionStats {
+    pub trades: usize,
+    pub avg_mae_r: f64,
+    pub avg_mfe_r: f64,
+}
+
+impl ExcursionStats {
+    fn from_history(history: &[Position]) -> HashMap<String, Self> {
+        let mut out: HashMap<String, Self> = HashMap::new();
+        for p in history {
+            let Some((mae, mfe)) = p.excursion_r() else {
+                continue;
+            };
+            let outcome = if p.pnl > 0.0 { "win" } else { "loss" };
+            let s = out.entry(outcome.to_string()).or_default();
+            s.trades += 1;
+            s.avg_mae_r += mae;
+            s.avg_mfe_r += mfe;
+        }
+        for s in out.values_mut() {
+            s.avg_mae_r /= s.trades as f64;
+            s.avg_mfe_r /= s.trades as f64;
+        }
+        out
+    }
+}
+
+/// Results of the trades entered in one weekday or hour bucket.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TimeStats {
+    pub trades: usize,
+    pub wins: usize,
+    pub win_rate: f64,
+    pub total_pnl: f64,
+    /// Mean R of the trades with a known entry risk
+    pub avg_r: f64,
+    #[serde(skip)]
+    r_trades: usize,
+}
+
+impl TimeStats {
+    fn add(&mut self, pnl: f64, r: Option<f64>) {
+        self.trades += 1;
+        if pnl > 0.0 {
+            self.wins += 1;
+        }
+        self.total_pnl += pnl;
+        if let Some(r) = r {
+            self.avg_r += r;
+            self.r_trades += 1;
+        }
+    }
+
+    fn finish(&mut self) {
+        if self.trades > 0 {
+            self.win_rate = self.wins as f64 / self.trades as f64 * 100.0;
+        }
+        if self.r_trades > 0 {
+            self.avg_r /= self.r_trades as f64;
+        }
+    }
+
+    fn summary(&self) -> String {
+        format!(
+            "{} trades | WR {:.0}% | PnL ${:+.2} | Avg {:+.2}R",
+            self.trades, self.win_rate, self.total_pnl, self.avg_r
+        )
+    }
+}
+
+/// Trade records bucketed by the ET weekday and hour of their position's entry.
+fn time_stats(trader: &PaperTrader) -> (HashMap<String, TimeStats>, HashMap<u32, TimeStats>) {
+    let positions: HashMap<u64, &Position> = trader.history().iter().map(|p| (p.id, p)).collect();
+    let mut weekdays: HashMap<String, TimeStats> = HashMap::new();
+    let mut hours: HashMap<u32, TimeStats> = HashMap::new();
+    for record in trader.records().values() {
+        let Some(pos) = positions.get(&record.position_id) else {
+            continue;
+        };
+        let Some(entry) = storage::entry_time(pos) else {
+            continue;
+        };
+        let et = entry.with_timezone(&Eastern);
+        let day = WEEKDAYS[et.weekday().num_days_from_monday() as usize];
+        weekdays
+            .entry(day.to_string())
+            .or_default()
+            .add(record.pnl, pos.r_multiple);
+        hours
+            .entry(et.hour())
+            .or_default()
+            .add(record.pnl, pos.r_multiple);
+    }
+    weekdays.values_mut().for_each(TimeStats::finish);
+    hours.values_mut().for_each(TimeStats::finish);
+    (weekdays, hours)
 }
 
 impl BacktestReport {
+    #[allow(clippy::too_many_arguments)]
     pub fn from_backtest(
         trader: &PaperTrader,
         cfg: &Config,
@@ -80,11 +315,11 @@ impl BacktestReport {
         signals_filtered: usize,
     ) -> Self {
         let initial = cfg.initial_balance;
-        let final_balance = trader.balance;
+        let final_balance = trader.balance();
         let total_pnl = final_balance - initial;
         let days = (end - start).num_hours() as f64 / 24.0;
 
-        let history = &trader.trade_history;
+        let history = trader.history();
         let total_trades = history.len();
 
         let wins: Vec<f64> = history.iter().filter(|t| t.pnl > 0.0).map(|t| t.pnl).collect();
@@ -133,15 +368,25 @@ impl BacktestReport {
 
         // Sharpe ratio (annualized, using daily returns from equity curve)
         let sharpe_ratio = compute_sharpe(&equity_curve);
+        let sortino_ratio = compute_sortino(&equity_curve);
+        let cagr_pct = compute_cagr(initial, final_balance, days);
+        let calmar_ratio = if max_drawdown_pct > 0.0 {
+            cagr_pct / max_drawdown_pct
+        } else {
+            0.0
+        };
+        let (time_in_market_pct, avg_concurrent_positions) =
+            exposure(&holding_periods(trader, end), start, end);
 
         // Per-scale stats
         let mut scale_stats: HashMap<String, ScaleStats> = HashMap::new();
-        for record in trader.trade_records.values() {
+        for record in trader.records().values() {
             let entry = scale_stats
                 .entry(record.metadata.scale.clone())
                 .or_default();
             entry.trades += 1;
             entry.total_pnl += record.pnl;
+            entry.avg_entry_improvement_bps += record.entry_improvement_bps;
             if record.pnl > 0.0 {
                 entry.wins += 1;
             } else {
@@ -159,16 +404,20 @@ impl BacktestReport {
             } else {
                 0.0
             };
+            if stats.trades > 0 {
+                stats.avg_entry_improvement_bps /= stats.trades as f64;
+            }
         }
 
         // Per-session stats
         let mut session_stats: HashMap<String, SessionStats> = HashMap::new();
-        for record in trader.trade_records.values() {
+        for record in trader.records().values() {
             let entry = session_stats
                 .entry(record.metadata.session.clone())
                 .or_default();
             entry.trades += 1;
             entry.total_pnl += record.pnl;
+            entry.avg_entry_improvement_bps += record.entry_improvement_bps;
             if record.pnl > 0.0 {
                 entry.wins += 1;
             } else {
@@ -181,12 +430,37 @@ impl BacktestReport {
             } else {
                 0.0
             };
+            if stats.trades > 0 {
+                stats.avg_entry_improvement_bps /= stats.trades as f64;
+            }
         }
 
+        let records: Vec<TradeRecord> = trader.records().values().cloned().collect();
+        let exit_mix = TradeAnalyzer::exit_mix(&records);
+        let analyzer = TradeAnalyzer::new(cfg.min_sample_per_bucket);
+        let mut attribution = analyzer.attribution(&records);
+        attribution.extend(analyzer.tag_stats(&records));
+        attribution.extend(analyzer.dimension_stats(&records, "strategy"));
+        let seasonality = Seasonality::from_positions(history);
+        let (weekday_stats, hour_of_day_stats) = time_stats(trader);
+        let mut balance = initial;
+        let trade_returns = history
+            .iter()
+            .map(|t| {
+                let r = if balance > 0.0 { t.pnl / balance } else { 0.0 };
+                balance += t.pnl;
+                r
+            })
+            .collect();
+
         BacktestReport {
             start,
             end,
             days,
+            fill_timing: cfg.fill_timing,
+            fill_latency_secs: cfg.fill_latency_secs,
+            instrument: cfg.instrument(&cfg.symbol),
+            config_hash: cfg.config_hash(),
             initial_balance: initial,
             final_balance,
             total_pnl,
@@ -195,6 +469,7 @@ impl BacktestReport {
             } else {
                 0.0
             },
+            funding_paid: history.iter().map(|p| p.funding).sum(),
             total_trades,
             winning_trades: winning,
             losing_trades: losing,
@@ -205,15 +480,227 @@ impl BacktestReport {
             best_trade: if total_trades > 0 { best_trade } else { 0.0 },
             worst_trade: if total_trades > 0 { worst_trade } else { 0.0 },
             avg_trade,
+            r_stats: RStats::from_multiples(history.iter().filter_map(|t| t.r_multiple)),
+            pyramid: PyramidStats::from_history(history),
+            excursions: ExcursionStats::from_history(history),
             max_drawdown,
             max_drawdown_pct,
             sharpe_ratio,
+            sortino_ratio,
+            cagr_pct,
+            calmar_ratio,
+            time_in_market_pct,
+            avg_concurrent_positions,
+            longest_losing_streak: longest_losing_streak(history.iter().map(|t| t.pnl)),
             total_signals,
             signals_filtered,
             scale_stats,
             session_stats,
+            exit_mix,
+            attribution,
+            seasonality,
+            weekday_stats,
+            hour_of_day_stats,
             equity_curve,
+            trade_returns,
+            config: cfg.snapshot(),
+        }
+    }
+
+    /// Fill convention for display, with any signal-to-fill latency.
+    pub fn fills_label(&self) -> String {
+        if self.fill_timing == FillTiming::NextBarOpen && self.fill_latency_secs > 0 {
+            format!("{} +{}s", self.fill_timing.as_str(), self.fill_latency_secs)
+        } else {
+            self.fill_timing.as_str().to_string()
+        }
+    }
+
+    /// Full report (including the equity curve) as pretty JSON.
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Full report as long-format CSV: `section,key,field,value`.
+    /// Sections are `summary`, `scale`, `session`, `exit_reason`,
+    /// `attribution` (keyed `dimension:value`), `r`
+    /// (expectancy and histogram buckets keyed by their lower bound),
+    /// `pyramid`, `excursion` (by outcome), `weekday` and `hour` (ET entry),
+    /// `seasonality` (non-empty `Mon 09` style ET slots) and `equity`.
+    pub fn to_csv(&self) -> String {
+        let mut out = String::from("section,key,field,value\n");
+        let mut row = |section: &str, key: &str, field: &str, value: String| {
+            let _ = writeln!(out, "{},{},{},{}", section, csv_field(key), field, value);
+        };
+
+        row("summary", "", "start", self.start.to_rfc3339());
+        row("summary", "", "end", self.end.to_rfc3339());
+        row("summary", "", "fill_timing", self.fill_timing.as_str().to_string());
+        row("summary", "", "instrument", self.instrument.as_str().to_string());
+        row("summary", "", "config_hash", self.config_hash.clone());
+        let summary = [
+            ("days", self.days),
+            ("initial_balance", self.initial_balance),
+            ("final_balance", self.final_balance),
+            ("total_pnl", self.total_pnl),
+            ("total_return_pct", self.total_return_pct),
+            ("funding_paid", self.funding_paid),
+            ("total_trades", self.total_trades as f64),
+            ("winning_trades", self.winning_trades as f64),
+            ("losing_trades", self.losing_trades as f64),
+            ("win_rate", self.win_rate),
+            ("avg_win", self.avg_win),
+            ("avg_loss", self.avg_loss),
+            ("profit_factor", self.profit_factor),
+            ("best_trade", self.best_trade),
+            ("worst_trade", self.worst_trade),
+            ("avg_trade", self.avg_trade),
+            ("max_drawdown", self.max_drawdown),
+            ("max_drawdown_pct", self.max_drawdown_pct),
+            ("sharpe_ratio", self.sharpe_ratio),
+            ("sortino_ratio", self.sortino_ratio),
+            ("cagr_pct", self.cagr_pct),
+            ("calmar_ratio", self.calmar_ratio),
+            ("time_in_market_pct", self.time_in_market_pct),
+            ("avg_concurrent_positions", self.avg_concurrent_positions),
+            ("longest_losing_streak", self.longest_losing_streak as f64),
+            ("total_signals", self.total_signals as f64),
+            ("signals_filtered", self.signals_filtered as f64),
+        ];
+        for (field, value) in summary {
+            row("summary", "", field, value.to_string());
+        }
+
+        let mut scales: Vec<_> = self.scale_stats.iter().collect();
+        scales.sort_by_key(|(k, _)| (*k).clone());
+        for (scale, s) in scales {
+            row("scale", scale, "trades", s.trades.to_string());
+            row("scale", scale, "wins", s.wins.to_string());
+            row("scale", scale, "losses", s.losses.to_string());
+            row("scale", scale, "win_rate", s.win_rate.to_string());
+            row("scale", scale, "total_pnl", s.total_pnl.to_string());
+            row("scale", scale, "avg_pnl", s.avg_pnl.to_string());
+            row("scale", scale, "avg_entry_improvement_bps", s.avg_entry_improvement_bps.to_string());
+        }
+
+        let mut sessions: Vec<_> = self.session_stats.iter().collect();
+        sessions.sort_by_key(|(k, _)| (*k).clone());
+        for (session, s) in sessions {
+            row("session", session, "trades", s.trades.to_string());
+            row("session", session, "wins", s.wins.to_string());
+            row("session", session, "losses", s.losses.to_string());
+            row("session", session, "win_rate", s.win_rate.to_string());
+            row("session", session, "total_pnl", s.total_pnl.to_string());
+            row("session", session, "avg_entry_improvement_bps", s.avg_entry_improvement_bps.to_string());
+        }
+
+        for m in &self.exit_mix {
+            row("exit_reason", &m.reason, "wins", m.wins.to_string());
+            row("exit_reason", &m.reason, "losses", m.losses.to_string());
+            row("exit_reason", &m.reason, "total_pnl", m.total_pnl.to_string());
+            row("exit_reason", &m.reason, "pct_of_winners", m.pct_of_winners.to_string());
+            row("exit_reason", &m.reason, "pct_of_losers", m.pct_of_losers.to_string());
+        }
+
+        for b in &self.attribution {
+            let key = format!("{}:{}", b.dimension, b.value);
+            row("attribution", &key, "trades", b.total.to_string());
+            row("attribution", &key, "win_rate", b.win_rate.to_string());
+            row("attribution", &key, "total_pnl", b.total_pnl.to_string());
+            row("attribution", &key, "edge", b.edge.to_string());
+        }
+
+        if let Some(r) = &self.r_stats {
+            row("r", "", "expectancy_r", r.expectancy_r.to_string());
+            row("r", "", "avg_win_r", r.avg_win_r.to_string());
+            row("r", "", "avg_loss_r", r.avg_loss_r.to_string());
+            for b in &r.histogram {
+                row("r", &b.from.to_string(), "count", b.count.to_string());
+            }
+        }
+
+        if let Some(p) = &self.pyramid {
+            row("pyramid", "", "positions", p.positions.to_string());
+            row("pyramid", "", "adds", p.adds.to_string());
+            row("pyramid", "", "add_pnl", p.add_pnl.to_string());
+            row("pyramid", "", "total_pnl", p.total_pnl.to_string());
+            row("pyramid", "", "win_rate", p.win_rate.to_string());
+        }
+
+        for outcome in ["win", "loss"] {
+            if let Some(e) = self.excursions.get(outcome) {
+                row("excursion", outcome, "trades", e.trades.to_string());
+                row("excursion", outcome, "avg_mae_r", e.avg_mae_r.to_string());
+                row("excursion", outcome, "avg_mfe_r", e.avg_mfe_r.to_string());
+            }
+        }
+
+        let mut time_rows = |section: &str, key: &str, s: &TimeStats| {
+            row(section, key, "trades", s.trades.to_string());
+            row(section, key, "wins", s.wins.to_string());
+            row(section, key, "win_rate", s.win_rate.to_string());
+            row(section, key, "total_pnl", s.total_pnl.to_string());
+            row(section, key, "avg_r", s.avg_r.to_string());
+        };
+        for (day, s) in self.weekdays() {
+            time_rows("weekday", day, s);
         }
+        for (hour, s) in self.hours() {
+            time_rows("hour", &hour.to_string(), s);
+        }
+
+        for (d, h, c) in self.seasonality.slots() {
+            let key = format!("{} {:02}", WEEKDAYS[d], h);
+            row("seasonality", &key, "trades", c.trades.to_string());
+            row("seasonality", &key, "wins", c.wins.to_string());
+            row("seasonality", &key, "net_pnl", c.pnl.to_string());
+        }
+
+        for (ts, balance) in &self.equity_curve {
+            row("equity", &ts.to_rfc3339(), "balance", balance.to_string());
+        }
+
+        out
+    }
+
+    /// Write the report to `path`: CSV for a `.csv` extension, JSON otherwise.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let body = match path.extension().and_then(|e| e.to_str()) {
+            Some("csv") => self.to_csv(),
+            _ => self.to_json()?,
+        };
+        std::fs::write(path, body)?;
+        Ok(())
+    }
+
+    /// Weekday buckets in calendar order, Monday first.
+    pub fn weekdays(&self) -> impl Iterator<Item = (&str, &TimeStats)> {
+        WEEKDAYS
+            .iter()
+            .filter_map(|d| self.weekday_stats.get(*d).map(|s| (*d, s)))
+    }
+
+    /// Hour buckets in order.
+    pub fn hours(&self) -> Vec<(u32, &TimeStats)> {
+        let mut hours: Vec<_> = self
+            .hour_of_day_stats
+            .iter()
+            .map(|(h, s)| (*h, s))
+            .collect();
+        hours.sort_by_key(|(h, _)| *h);
+        hours
+    }
+
+    /// Write the standalone HTML report (charts and tables) to `path`.
+    pub fn to_html(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, super::html::render(self))?;
+        Ok(())
     }
 
     pub fn print_summary(&self) {
@@ -226,6 +713,8 @@ impl BacktestReport {
             self.end.format("%Y-%m-%d"),
             self.days
         );
+        println!("  Fills:       {}", self.fills_label());
+        println!("  Config:      {}", self.config_hash);
         println!();
         println!("  PERFORMANCE");
         println!("  ───────────────────────────────────");
@@ -233,6 +722,9 @@ impl BacktestReport {
         println!("  Final:       ${:.2}", self.final_balance);
         println!("  PnL:         ${:+.2}", self.total_pnl);
         println!("  Return:      {:+.1}%", self.total_return_pct);
+        if self.instrument.pays_funding() {
+            println!("  Funding:     ${:+.2} paid", self.funding_paid);
+        }
         println!();
         println!("  TRADES");
         println!("  ───────────────────────────────────");
@@ -248,11 +740,44 @@ impl BacktestReport {
         println!("  Worst:       ${:+.2}", self.worst_trade);
         println!("  Avg Trade:   ${:+.2}", self.avg_trade);
         println!("  Profit Factor: {:.2}", self.profit_factor);
+        if let Some(r) = &self.r_stats {
+            println!("  Expectancy:  {}", r.summary());
+            for b in &r.histogram {
+                println!(
+                    "    {:>+5.1}R to {:>+5.1}R: {:>4} {}",
+                    b.from,
+                    b.to,
+                    b.count,
+                    "#".repeat(b.count * 40 / r.sample_size)
+                );
+            }
+        }
+        if let Some(p) = &self.pyramid {
+            println!("  Pyramided:   {}", p.summary());
+        }
+        for outcome in ["win", "loss"] {
+            if let Some(e) = self.excursions.get(outcome) {
+                println!(
+                    "  {:<4} MAE/MFE: {:.2}R / {:.2}R avg over {} trades",
+                    outcome, e.avg_mae_r, e.avg_mfe_r, e.trades
+                );
+            }
+        }
         println!();
         println!("  RISK");
         println!("  ───────────────────────────────────");
         println!("  Max DD:      ${:.2} ({:.1}%)", self.max_drawdown, self.max_drawdown_pct);
         println!("  Sharpe:      {:.2}", self.sharpe_ratio);
+        println!("  Sortino:     {:.2}", self.sortino_ratio);
+        println!(
+            "  Calmar:      {:.2} (CAGR {:+.1}%)",
+            self.calmar_ratio, self.cagr_pct
+        );
+        println!(
+            "  Exposure:    {:.1}% of time | {:.2} avg open",
+            self.time_in_market_pct, self.avg_concurrent_positions
+        );
+        println!("  Loss streak: {}", self.longest_losing_streak);
         println!();
         println!("  SIGNALS");
         println!("  ───────────────────────────────────");
@@ -272,11 +797,16 @@ impl BacktestReport {
             println!("  BY SCALE");
             println!("  ───────────────────────────────────");
             let mut scales: Vec<_> = self.scale_stats.iter().collect();
-            scales.sort_by_key(|(k, _)| k.clone());
+            scales.sort_by_key(|(k, _)| (*k).clone());
             for (scale, stats) in scales {
                 println!(
-                    "  {:>4}: {} trades | WR {:.0}% | PnL ${:+.2} | Avg ${:+.2}",
-                    scale, stats.trades, stats.win_rate, stats.total_pnl, stats.avg_pnl
+                    "  {:>4}: {} trades | WR {:.0}% | PnL ${:+.2} | Avg ${:+.2} | Entry {:+.1}bps",
+                    scale,
+                    stats.trades,
+                    stats.win_rate,
+                    stats.total_pnl,
+                    stats.avg_pnl,
+                    stats.avg_entry_improvement_bps
                 );
             }
         }
@@ -289,8 +819,84 @@ impl BacktestReport {
             sessions.sort_by(|a, b| b.1.total_pnl.partial_cmp(&a.1.total_pnl).unwrap());
             for (session, stats) in sessions {
                 println!(
-                    "  {:>12}: {} trades | WR {:.0}% | PnL ${:+.2}",
-                    session, stats.trades, stats.win_rate, stats.total_pnl
+                    "  {:>12}: {} trades | WR {:.0}% | PnL ${:+.2} | Entry {:+.1}bps",
+                    session,
+                    stats.trades,
+                    stats.win_rate,
+                    stats.total_pnl,
+                    stats.avg_entry_improvement_bps
+                );
+            }
+        }
+
+        if !self.exit_mix.is_empty() {
+            println!();
+            println!("  BY EXIT REASON");
+            println!("  ───────────────────────────────────");
+            for mix in &self.exit_mix {
+                println!(
+                    "  {:>13}: {}W / {}L | PnL ${:+.2} | {:.0}% of winners | {:.0}% of losers",
+                    mix.reason,
+                    mix.wins,
+                    mix.losses,
+                    mix.total_pnl,
+                    mix.pct_of_winners,
+                    mix.pct_of_losers
+                );
+            }
+        }
+
+        if !self.attribution.is_empty() {
+            println!();
+            println!("  BY SETUP (edge = expected PnL per trade)");
+            println!("  ───────────────────────────────────");
+            for b in &self.attribution {
+                let small = if b.sample_sufficient {
+                    ""
+                } else {
+                    " (small sample)"
+                };
+                println!(
+                    "  {:>11} {:>14}: {} trades | WR {:.0}% | PnL ${:+.2} | Edge ${:+.2}{}",
+                    b.dimension,
+                    b.value,
+                    b.total,
+                    b.win_rate * 100.0,
+                    b.total_pnl,
+                    b.edge,
+                    small
+                );
+            }
+        }
+
+        if !self.weekday_stats.is_empty() {
+            println!();
+            println!("  BY WEEKDAY (ET, by entry)");
+            println!("  ───────────────────────────────────");
+            for (day, stats) in self.weekdays() {
+                println!("  {}: {}", day, stats.summary());
+            }
+        }
+
+        if !self.hour_of_day_stats.is_empty() {
+            println!();
+            println!("  BY HOUR (ET, by entry)");
+            println!("  ───────────────────────────────────");
+            for (hour, stats) in self.hours() {
+                println!("  {:02}:00: {}", hour, stats.summary());
+            }
+        }
+
+        let ranked = self.seasonality.ranked();
+        if !ranked.is_empty() {
+            println!();
+            println!("  BEST / WORST SLOTS (ET, by entry)");
+            println!("  ───────────────────────────────────");
+            let worst = ranked.iter().rev().take(3).filter(|s| s.2.pnl < 0.0);
+            for (d, h, c) in ranked.iter().take(3).filter(|s| s.2.pnl > 0.0).chain(worst) {
+                println!(
+                    "  {} {:02}:00: {} trades | {}W | PnL ${:+.2}",
+                    WEEKDAYS[*d], h, c.trades, c.wins, c.pnl
                 );
             }
         }
@@ -299,12 +905,17 @@ impl BacktestReport {
     }
 }
 
-fn compute_sharpe(equity_curve: &[(DateTime<Utc>, f64)]) -> f64 {
-    if equity_curve.len() < 2 {
-        return 0.0;
+/// Quote a CSV field if it contains a delimiter or quote.
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
     }
+}
 
-    // Compute daily returns (sample once per day)
+/// Returns between the first equity value of each day.
+fn daily_returns(equity_curve: &[(DateTime<Utc>, f64)]) -> Vec<f64> {
     let mut daily_values: Vec<f64> = Vec::new();
     let mut last_day = None;
     for (ts, val) in equity_curve {
@@ -315,14 +926,17 @@ fn compute_sharpe(equity_curve: &[(DateTime<Utc>, f64)]) -> f64 {
         }
     }
 
-    if daily_values.len() < 2 {
-        return 0.0;
-    }
-
-    let returns: Vec<f64> = daily_values
+    daily_values
         .windows(2)
         .map(|w| (w[1] - w[0]) / w[0])
-        .collect();
+        .collect()
+}
+
+fn compute_sharpe(equity_curve: &[(DateTime<Utc>, f64)]) -> f64 {
+    let returns = daily_returns(equity_curve);
+    if returns.is_empty() {
+        return 0.0;
+    }
 
     let n = returns.len() as f64;
     let mean = returns.iter().sum::<f64>() / n;
@@ -336,3 +950,262 @@ fn compute_sharpe(equity_curve: &[(DateTime<Utc>, f64)]) -> f64 {
     // Annualized Sharpe (assuming ~252 trading days)
     mean / std_dev * 252.0_f64.sqrt()
 }
+
+/// Annualized Sortino: mean daily return over the downside deviation
+/// (root mean square of the negative returns).
+fn compute_sortino(equity_curve: &[(DateTime<Utc>, f64)]) -> f64 {
+    let returns = daily_returns(equity_curve);
+    if returns.is_empty() {
+        return 0.0;
+    }
+
+    let n = returns.len() as f64;
+    let mean = returns.iter().sum::<f64>() / n;
+    let downside = (returns.iter().map(|r| r.min(0.0).powi(2)).sum::<f64>() / n).sqrt();
+
+    if downside == 0.0 {
+        return 0.0;
+    }
+    mean / downside * 252.0_f64.sqrt()
+}
+
+/// Compound annual growth rate (%) from `initial` to `final_balance`.
+fn compute_cagr(initial: f64, final_balance: f64, days: f64) -> f64 {
+    if initial <= 0.0 || days <= 0.0 {
+        return 0.0;
+    }
+    if final_balance <= 0.0 {
+        return -100.0;
+    }
+    ((final_balance / initial).powf(365.0 / days) - 1.0) * 100.0
+}
+
+/// Entry to exit of every filled position; still-open ones run to `end`.
+fn holding_periods(
+    trader: &PaperTrader,
+    end: DateTime<Utc>,
+) -> Vec<(DateTime<Utc>, DateTime<Utc>)> {
+    trader
+        .history()
+        .iter()
+        .chain(trader.positions())
+        .filter(|p| {
+            !matches!(
+                p.status,
+                PositionStatus::Pending | PositionStatus::Cancelled
+            )
+        })
+        .filter_map(|p| {
+            let exit = p
+                .exit_time
+                .as_deref()
+                .and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+                .map_or(end, |t| t.with_timezone(&Utc));
+            Some((storage::entry_time(p)?, exit))
+        })
+        .collect()
+}
+
+/// Share (%) of `start..end` with at least one period open, and the
+/// time-weighted mean number of open periods.
+fn exposure(
+    periods: &[(DateTime<Utc>, DateTime<Utc>)],
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> (f64, f64) {
+    let total = (end - start).num_seconds() as f64;
+    if total <= 0.0 {
+        return (0.0, 0.0);
+    }
+
+    // Closes sort before opens at the same instant
+    let mut events: Vec<(DateTime<Utc>, i32)> = Vec::new();
+    for &(from, to) in periods {
+        let (from, to) = (from.max(start), to.min(end));
+        if from < to {
+            events.push((from, 1));
+            events.push((to, -1));
+        }
+    }
+    events.sort();
+
+    let (mut open, mut last) = (0, start);
+    let (mut exposed, mut weighted) = (0.0, 0.0);
+    for (t, delta) in events {
+        let secs = (t - last).num_seconds() as f64;
+        if open > 0 {
+            exposed += secs;
+        }
+        weighted += open as f64 * secs;
+        open += delta;
+        last = t;
+    }
+    (exposed / total * 100.0, weighted / total)
+}
+
+/// Most consecutive non-positive PnLs.
+fn longest_losing_streak(pnls: impl Iterator<Item = f64>) -> usize {
+    let (mut run, mut longest) = (0, 0);
+    for pnl in pnls {
+        run = if pnl <= 0.0 { run + 1 } else { 0 };
+        longest = longest.max(run);
+    }
+    longest
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::default_test_config;
+    use chrono::Duration;
+
+    #[test]
+    fn exports_json_and_csv() {
+        let cfg = default_test_config();
+        let trader = PaperTrader::new_fresh(&cfg);
+        let start = DateTime::parse_from_rfc3339("2024-01-15T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let curve = vec![(start, 10_000.0), (start + Duration::days(1), 10_050.0)];
+        let report = BacktestReport::from_backtest(
+            &trader,
+            &cfg,
+            start,
+            start + Duration::days(1),
+            curve,
+            0.0,
+            0.0,
+            3,
+            1,
+        );
+
+        let json: serde_json::Value = serde_json::from_str(&report.to_json().unwrap()).unwrap();
+        assert_eq!(json["equity_curve"].as_array().unwrap().len(), 2);
+        assert_eq!(json["total_signals"], 3);
+        // `report --file` reads it back
+        let back: BacktestReport = serde_json::from_value(json).unwrap();
+        assert_eq!(back.equity_curve, report.equity_curve);
+
+        let csv = report.to_csv();
+        assert!(csv.starts_with("section,key,field,value\n"));
+        assert!(csv.contains("summary,,total_signals,3\n"));
+        assert!(csv.contains("summary,,fill_timing,signal_close\n"));
+        assert!(csv.contains("summary,,instrument,spot\n"));
+        assert!(csv.contains("equity,2024-01-16T00:00:00+00:00,balance,10050\n"));
+
+        let dir = std::env::temp_dir().join(format!("ict_report_test_{}", std::process::id()));
+        let path = dir.join("report.csv");
+        report.save(&path).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), csv);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn buckets_trades_by_et_weekday_and_hour_of_entry() {
+        let cfg = default_test_config();
+        let mut trader = PaperTrader::new_fresh(&cfg);
+        // 14:30 UTC Monday is 09:30 ET; 03:00 UTC Wednesday is 22:00 ET Tuesday
+        let trades = [
+            (1, "2024-01-15T14:30:00Z", 5.0, Some(1.0)),
+            (2, "2024-01-15T14:50:00Z", -2.0, Some(-1.0)),
+            (3, "2024-01-17T03:00:00Z", -1.5, None),
+        ];
+        for (id, entry, pnl, r) in trades {
+            let pos: Position = serde_json::from_value(serde_json::json!({
+                "id": id, "symbol": "BTC-USD", "direction": "long", "entry_price": 100.0,
+                "size_usd": 100.0, "size_btc": 1.0, "stop_loss": 99.0, "take_profit": 102.0,
+                "entry_time": entry, "reason": "test", "scale": "5m",
+                "status": "closed_tp", "pnl": pnl, "r_multiple": r,
+                "mae_price": 99.5, "mfe_price": 101.0,
+            }))
+            .unwrap();
+            let record: TradeRecord = serde_json::from_value(serde_json::json!({
+                "position_id": id, "pnl": pnl, "outcome": if pnl > 0.0 { "win" } else { "loss" },
+                "metadata": {
+                    "scale": "5m", "direction": "long", "confidence": 0.6,
+                    "session": "ny_forex", "session_weight": 1.0, "cisd_confirmed": true,
+                    "strategy": "silver_bullet"
+                }
+            }))
+            .unwrap();
+            trader.push_closed(pos, record);
+        }
+
+        let start = trader.history()[0].entry_time.parse().unwrap();
+        let report = BacktestReport::from_backtest(
+            &trader,
+            &cfg,
+            start,
+            start + Duration::days(3),
+            Vec::new(),
+            0.0,
+            0.0,
+            3,
+            0,
+        );
+        let mon = &report.weekday_stats["Mon"];
+        assert_eq!((mon.trades, mon.wins), (2, 1));
+        assert_eq!(mon.win_rate, 50.0);
+        assert_eq!(mon.avg_r, 0.0);
+        let tue = &report.weekday_stats["Tue"];
+        assert_eq!((tue.trades, tue.avg_r), (1, 0.0));
+        assert_eq!(report.hour_of_day_stats[&9].total_pnl, 3.0);
+        assert_eq!(report.hour_of_day_stats[&22].trades, 1);
+        let days: Vec<&str> = report.weekdays().map(|(d, _)| d).collect();
+        assert_eq!(days, ["Mon", "Tue"]);
+        assert_eq!(report.hours()[0].0, 9);
+        let by_strategy = report
+            .attribution
+            .iter()
+            .find(|b| b.dimension == "strategy")
+            .unwrap();
+        assert_eq!(
+            (by_strategy.value.as_str(), by_strategy.total),
+            ("silver_bullet", 3)
+        );
+        assert_eq!(report.excursions["loss"].trades, 2);
+        let win = &report.excursions["win"];
+        assert_eq!((win.trades, win.avg_mae_r, win.avg_mfe_r), (1, 0.5, 1.0));
+
+        let csv = report.to_csv();
+        assert!(csv.contains("weekday,Mon,trades,2\n"));
+        assert!(csv.contains("hour,22,total_pnl,-1.5\n"));
+        let json = report.to_json().unwrap();
+        let back: BacktestReport = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.hour_of_day_stats[&9].trades, 2);
+    }
+
+    #[test]
+    fn risk_ratios_match_known_sequences() {
+        let day0 = DateTime::parse_from_rfc3339("2024-01-15T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        // Daily returns +10%, -10%, +10%
+        let curve: Vec<_> = [100.0, 110.0, 99.0, 108.9]
+            .iter()
+            .enumerate()
+            .map(|(i, &v)| (day0 + Duration::days(i as i64), v))
+            .collect();
+        let mean = 0.1 / 3.0;
+        let downside = (0.01_f64 / 3.0).sqrt();
+        let sortino = compute_sortino(&curve);
+        assert!((sortino - mean / downside * 252.0_f64.sqrt()).abs() < 1e-9);
+        assert!(sortino > compute_sharpe(&curve));
+        assert_eq!(compute_sortino(&curve[..2]), 0.0);
+
+        // 100 -> 121 over two years is 10% a year
+        assert!((compute_cagr(100.0, 121.0, 730.0) - 10.0).abs() < 1e-9);
+        assert_eq!(compute_cagr(100.0, 0.0, 30.0), -100.0);
+
+        // Open 0-12h and 6-18h of a day: exposed 18h, 1 position on average
+        let at = |h: i64| day0 + Duration::hours(h);
+        let periods = [(at(0), at(12)), (at(6), at(18)), (at(30), at(40))];
+        let (pct, avg) = exposure(&periods, at(0), at(24));
+        assert!((pct - 75.0).abs() < 1e-9);
+        assert!((avg - 1.0).abs() < 1e-9);
+
+        let pnls = [1.0, -1.0, -2.0, 0.0, 3.0, -1.0];
+        assert_eq!(longest_losing_streak(pnls.into_iter()), 3);
+        assert_eq!(longest_losing_streak(std::iter::empty()), 0);
+    }
+}
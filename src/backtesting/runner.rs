@@ -1,35 +1,111 @@
 use anyhow::Result;
 use chrono::{DateTime, Duration as ChronoDuration, Utc};
-use std::collections::HashMap;
-use tracing::{debug, info};
+use std::collections::{HashMap, HashSet};
+use tracing::{debug, info, warn};
 
-use crate::config::Config;
+use crate::config::{Config, FillTiming};
+use crate::core::calendar::{self, EconCalendar};
+use crate::core::position_sizing;
 use crate::core::sessions::SessionManager;
-use crate::core::stop_loss::StopLossEngine;
 use crate::exchange::{Exchange, HistoricalExchange};
-use crate::models::{CandleSeries, Direction, PositionStatus, Timeframe};
-use crate::strategies::fractal_engine::FractalEngine;
+use crate::models::instrument::funding_times;
+use crate::models::{Candle, CandleSeries, Direction, PositionStatus, Timeframe};
+use crate::strategies::signals::{context_tags, TradeSignal};
+use crate::strategies::strategy::{self, Strategy, StrategyRegistry};
 use crate::strategies::weekly_profiles::{WeeklyBias, WeeklyProfileClassifier};
-use crate::trading::paper_trader::PaperTrader;
+use crate::trading::drawdown_guard::BreakerEvent;
+use crate::trading::journal::{Journal, JournalEvent};
+use crate::trading::paper_trader::{IntrabarOrdering, PaperTrader, Position, StopAdjustReason};
+use crate::trading::risk_regime::RiskRegime;
 use crate::trading::strategy_refiner::StrategyRefiner;
+use crate::trading::stuck_positions::tightened_stop;
 use crate::trading::trade_record::TradeMetadata;
+use crate::trading::trailing::TrailingStops;
 
+use super::intrabar::synthetic_ticks;
 use super::report::BacktestReport;
 
-/// Steps through historical data candle-by-candle, running the full
-/// ICT fractal engine + paper trader pipeline at each step.
+/// Candles refreshed per timeframe unless DATA_LOOKBACK is set
+const DEFAULT_DATA_LOOKBACK: usize = 200;
+/// Scale cooldown after a close unless COOLDOWN_MINUTES is set
+const DEFAULT_COOLDOWN_MINUTES: i64 = 30;
+
+/// A signal waiting for its fill after the signal bar (`FillTiming::NextBarOpen`).
+struct PendingEntry {
+    signal: TradeSignal,
+    metadata: TradeMetadata,
+    signal_time: DateTime<Utc>,
+}
+
+/// `signal` re-priced to fill at `open`, keeping its stop and target.
+/// `None` if the open has already gapped through either level.
+fn reprice_at_open(signal: &TradeSignal, open: f64) -> Option<TradeSignal> {
+    let valid = match signal.direction {
+        Direction::Long => signal.stop_loss < open && open < signal.take_profit,
+        Direction::Short => signal.take_profit < open && open < signal.stop_loss,
+    };
+    valid.then(|| TradeSignal {
+        entry_price: open,
+        ..signal.clone()
+    })
+}
+
+/// Time and price of a fill at `at`: along the synthetic path of the 1m
+/// bar spanning it, else at the open of the first bar after it.
+fn price_at(bars: &[Candle], at: DateTime<Utc>) -> Option<(DateTime<Utc>, f64)> {
+    let later = bars.partition_point(|b| b.timestamp <= at);
+    if let Some(bar) = later.checked_sub(1).map(|i| &bars[i]) {
+        let offset = (at - bar.timestamp).num_seconds();
+        if offset < 60 {
+            let path = synthetic_ticks(bar, 60);
+            return path.get(offset as usize).map(|p| (at, *p));
+        }
+    }
+    bars.get(later).map(|b| (b.timestamp, b.open))
+}
+
+/// Step counter and equity tracking of one run.
+struct RunProgress {
+    step_count: usize,
+    total_steps: usize,
+    log_interval: usize,
+    equity_curve: Vec<(DateTime<Utc>, f64)>,
+    max_equity: f64,
+    max_drawdown: f64,
+    max_drawdown_pct: f64,
+}
+
+/// Steps through historical data candle-by-candle, running the strategies
+/// (the ICT fractal engine by default) + paper trader pipeline at each step.
 pub struct BacktestRunner {
     pub exchange: HistoricalExchange,
     pub config: Config,
     pub paper_trader: PaperTrader,
-    fractal: FractalEngine,
+    /// Evaluated in order; the first signal for a scale takes its slot
+    strategies: Vec<Box<dyn Strategy>>,
     session: SessionManager,
     weekly_classifier: WeeklyProfileClassifier,
     refiner: StrategyRefiner,
     weekly_bias: Option<WeeklyBias>,
     scale_positions: HashMap<String, u64>,
     scale_cooldown: HashMap<String, DateTime<Utc>>,
+    /// Signals by scale awaiting their next-bar fill
+    pending_entries: HashMap<String, PendingEntry>,
     data_cache: HashMap<Timeframe, CandleSeries>,
+    trailing: TrailingStops,
+    /// High-impact releases for news blackouts (ECON_CALENDAR file)
+    calendar: EconCalendar,
+    /// Positions whose stop was already pulled in for the current blackout
+    news_tightened: HashSet<u64>,
+    /// Trade lifecycle events (off unless set with `with_journal`)
+    journal: Journal,
+    /// Seconds per synthetic tick when replaying 1m bars between steps
+    /// (0 = close only; 15 or more evaluates each bar's OHLC extremes)
+    tick_seconds: u64,
+    intrabar_ordering: IntrabarOrdering,
+    last_position_check: Option<DateTime<Utc>>,
+    /// Last step funding was settled up to (perpetuals only)
+    last_funding: Option<DateTime<Utc>>,
 
     // Counters
     total_signals: usize,
@@ -39,29 +115,73 @@ pub struct BacktestRunner {
 
 impl BacktestRunner {
     pub fn new(exchange: HistoricalExchange, config: Config) -> Self {
-        let fractal = FractalEngine::new(&config);
+        let names = strategy::configured(&config, &config.backtest_strategy);
+        let mut strategies = StrategyRegistry::builtin().build_all(&names, &config);
+        if strategies.is_empty() {
+            warn!("No known strategy in {:?}, using fractal", names);
+            strategies.push(
+                strategy::from_name("fractal", &config).expect("fractal is always available"),
+            );
+        }
         let session = SessionManager::new(&config);
         let paper_trader = PaperTrader::new_fresh(&config);
         let refiner = StrategyRefiner::new(&config);
+        let calendar = calendar::load_local(&config).unwrap_or_default();
 
         Self {
             exchange,
             config: config.clone(),
             paper_trader,
-            fractal,
+            strategies,
             session,
             weekly_classifier: WeeklyProfileClassifier::new(),
             refiner,
             weekly_bias: None,
             scale_positions: HashMap::new(),
             scale_cooldown: HashMap::new(),
+            pending_entries: HashMap::new(),
             data_cache: HashMap::new(),
+            trailing: TrailingStops::new(&config),
+            calendar,
+            news_tightened: HashSet::new(),
+            journal: Journal::disabled(),
+            tick_seconds: config.backtest_tick_seconds,
+            intrabar_ordering: config.intrabar_ordering,
+            last_position_check: None,
+            last_funding: None,
             total_signals: 0,
             signals_filtered: 0,
             last_weekly_ts: None,
         }
     }
 
+    /// Replace the refiner (e.g. with frozen walk-forward refinements).
+    pub fn with_refiner(mut self, refiner: StrategyRefiner) -> Self {
+        self.refiner = refiner;
+        self
+    }
+
+    /// Run only `strategy` (e.g. to compare against the fractal engine).
+    pub fn with_strategy(self, strategy: Box<dyn Strategy>) -> Self {
+        self.with_strategies(vec![strategy])
+    }
+
+    /// Run `strategies` side by side, including user-defined ones.
+    pub fn with_strategies(mut self, strategies: Vec<Box<dyn Strategy>>) -> Self {
+        self.strategies = strategies;
+        self
+    }
+
+    /// Record signals, entries, exits and stop moves to `journal`.
+    pub fn with_journal(mut self, journal: Journal) -> Self {
+        self.journal = journal;
+        self
+    }
+
+    pub fn strategy_names(&self) -> Vec<&'static str> {
+        self.strategies.iter().map(|s| s.name()).collect()
+    }
+
     /// Run the full backtest. Returns a report.
     pub async fn run(
         &mut self,
@@ -69,13 +189,44 @@ impl BacktestRunner {
         end: DateTime<Utc>,
         step_minutes: i64,
     ) -> Result<BacktestReport> {
-        let step = ChronoDuration::minutes(step_minutes);
+        let mut progress = self.begin(start, end, step_minutes);
         let mut current = start;
+        while current <= end {
+            self.step(current, &mut progress, None).await;
+            current += ChronoDuration::minutes(step_minutes);
+        }
+        Ok(self.finish(start, end, progress).await)
+    }
+
+    /// Run this runner and `other` (an A/B variant) over the same steps
+    /// in one pass; `other` reuses the candles this runner fetched.
+    pub async fn run_ab(
+        &mut self,
+        other: &mut BacktestRunner,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        step_minutes: i64,
+    ) -> Result<(BacktestReport, BacktestReport)> {
+        let mut progress_a = self.begin(start, end, step_minutes);
+        let mut progress_b = other.begin(start, end, step_minutes);
+        let mut current = start;
+        while current <= end {
+            self.step(current, &mut progress_a, None).await;
+            other
+                .step(current, &mut progress_b, Some(&self.data_cache))
+                .await;
+            current += ChronoDuration::minutes(step_minutes);
+        }
+        let a = self.finish(start, end, progress_a).await;
+        let b = other.finish(start, end, progress_b).await;
+        Ok((a, b))
+    }
+
+    fn begin(&self, start: DateTime<Utc>, end: DateTime<Utc>, step_minutes: i64) -> RunProgress {
         let total_steps = ((end - start).num_minutes() / step_minutes) as usize;
-        let mut step_count = 0usize;
-        let log_interval = total_steps / 20; // Log ~20 progress updates
 
-        info!("=== BACKTEST START ===");
+        let names = self.strategy_names().join("+");
+        info!("=== BACKTEST START ({}) ===", names);
         info!(
             "Period: {} to {} ({} steps of {}m)",
             start.format("%Y-%m-%d"),
@@ -85,121 +236,157 @@ impl BacktestRunner {
         );
         info!("Initial balance: ${:.2}", self.config.initial_balance);
 
-        let initial_balance = self.config.initial_balance;
-
-        // Equity curve tracking
-        let mut equity_curve: Vec<(DateTime<Utc>, f64)> = Vec::new();
-        let mut max_equity = initial_balance;
-        let mut max_drawdown = 0.0f64;
-        let mut max_drawdown_pct = 0.0f64;
-
-        while current <= end {
-            self.exchange.set_time(current);
-            self.paper_trader.sim_time = Some(current);
-            step_count += 1;
-
-            // Progress logging
-            if log_interval > 0 && step_count % log_interval == 0 {
-                let pct = (step_count as f64 / total_steps as f64) * 100.0;
-                info!(
-                    "  Progress: {:.0}% | {} | Balance: ${:.2} | Trades: {} | Signals: {}",
-                    pct,
-                    current.format("%Y-%m-%d %H:%M"),
-                    self.paper_trader.balance,
-                    self.paper_trader.trade_history.len(),
-                    self.total_signals,
-                );
-            }
+        RunProgress {
+            step_count: 0,
+            total_steps,
+            log_interval: total_steps / 20, // Log ~20 progress updates
+            equity_curve: Vec::new(),
+            max_equity: self.config.initial_balance,
+            max_drawdown: 0.0,
+            max_drawdown_pct: 0.0,
+        }
+    }
 
-            // Refresh data cache
-            self.refresh_data().await;
+    /// Advance one step to `current`. With `shared` the candles are
+    /// copied from another runner at the same time instead of fetched.
+    async fn step(
+        &mut self,
+        current: DateTime<Utc>,
+        progress: &mut RunProgress,
+        shared: Option<&HashMap<Timeframe, CandleSeries>>,
+    ) {
+        self.exchange.set_time(current);
+        self.paper_trader.sim_time = Some(current);
+        self.refiner.sim_time = Some(current);
+        progress.step_count += 1;
+
+        // Progress logging
+        if progress.log_interval > 0 && progress.step_count.is_multiple_of(progress.log_interval) {
+            let pct = (progress.step_count as f64 / progress.total_steps as f64) * 100.0;
+            info!(
+                "  Progress: {:.0}% | {} | Balance: ${:.2} | Trades: {} | Signals: {}",
+                pct,
+                current.format("%Y-%m-%d %H:%M"),
+                self.paper_trader.balance(),
+                self.paper_trader.history().len(),
+                self.total_signals,
+            );
+        }
 
-            // Update session (using simulated time)
-            self.session.update(&self.config, Some(current));
+        // Refresh data cache
+        match shared {
+            Some(data) => self.data_cache.clone_from(data),
+            None => self.refresh_data().await,
+        }
+        self.paper_trader.bar_volume = self
+            .data_cache
+            .get(&Timeframe::M1)
+            .and_then(|c| c.last())
+            .map(|c| c.volume);
+
+        // Update session (using simulated time)
+        self.session.update(&self.config, Some(current));
+
+        // Weekly profile analysis (every 4 hours of sim time)
+        let should_analyze_weekly = match self.last_weekly_ts {
+            Some(last) => (current - last).num_hours() >= 4,
+            None => true,
+        };
+        if should_analyze_weekly {
+            self.analyze_weekly();
+            self.last_weekly_ts = Some(current);
+        }
 
-            // Weekly profile analysis (every 4 hours of sim time)
-            let should_analyze_weekly = match self.last_weekly_ts {
-                Some(last) => (current - last).num_hours() >= 4,
-                None => true,
-            };
-            if should_analyze_weekly {
-                self.analyze_weekly();
-                self.last_weekly_ts = Some(current);
-            }
+        // Fill last step's signals at this bar's open
+        self.fill_pending(current);
 
-            // Check positions
-            self.check_positions(current).await;
+        self.settle_funding(current).await;
 
-            // Scan all scales (optionally skip some via SKIP_SCALES env, comma-separated)
-            let skip_scales: Vec<String> = std::env::var("SKIP_SCALES")
-                .unwrap_or_default()
-                .split(',')
-                .map(|s| s.trim().to_string())
-                .filter(|s| !s.is_empty())
-                .collect();
-            let scale_keys: Vec<String> = self.config.hft_scales.keys()
-                .filter(|k| !skip_scales.contains(k))
-                .cloned()
-                .collect();
-            for scale_key in &scale_keys {
-                self.scan_scale(scale_key, current).await;
-            }
+        // Check positions
+        self.check_positions(current).await;
+        self.check_drawdown(current).await;
 
-            // Track equity
-            let equity = self.paper_trader.balance;
-            equity_curve.push((current, equity));
-            if equity > max_equity {
-                max_equity = equity;
-            }
-            let dd = max_equity - equity;
-            if dd > max_drawdown {
-                max_drawdown = dd;
-                max_drawdown_pct = if max_equity > 0.0 {
-                    dd / max_equity * 100.0
-                } else {
-                    0.0
-                };
-            }
+        // Scan all scales (optionally skip some via SKIP_SCALES)
+        let skip_scales = &self.config.skip_scales;
+        // Sorted so the scan order (and therefore fills) is deterministic
+        let mut scale_keys: Vec<String> = self.config.hft_scales.keys()
+            .filter(|k| !skip_scales.contains(k))
+            .cloned()
+            .collect();
+        scale_keys.sort();
+        for scale_key in &scale_keys {
+            self.scan_scale(scale_key, current).await;
+        }
 
-            current = current + step;
+        // Track mark-to-market equity
+        if let Ok(price) = self.exchange.get_current_price().await {
+            self.paper_trader.mark(&self.config.symbol, price);
+        }
+        let equity = self.paper_trader.equity();
+        progress.equity_curve.push((current, equity));
+        if equity > progress.max_equity {
+            progress.max_equity = equity;
+        }
+        let dd = progress.max_equity - equity;
+        if dd > progress.max_drawdown {
+            progress.max_drawdown = dd;
+            progress.max_drawdown_pct = if progress.max_equity > 0.0 {
+                dd / progress.max_equity * 100.0
+            } else {
+                0.0
+            };
         }
+    }
 
+    async fn finish(
+        &mut self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        progress: RunProgress,
+    ) -> BacktestReport {
         // Close any remaining open positions at the last known price
         if let Ok(price) = self.exchange.get_current_price().await {
             let _ = self.paper_trader.check_positions(price);
         }
 
         info!("=== BACKTEST COMPLETE ===");
+        for stats in self.strategies.iter().filter_map(|s| s.pda_cache_stats()) {
+            info!("PDA cache: {}", stats);
+        }
 
-        Ok(BacktestReport::from_backtest(
+        BacktestReport::from_backtest(
             &self.paper_trader,
             &self.config,
             start,
             end,
-            equity_curve,
-            max_drawdown,
-            max_drawdown_pct,
+            progress.equity_curve,
+            progress.max_drawdown,
+            progress.max_drawdown_pct,
             self.total_signals,
             self.signals_filtered,
-        ))
+        )
     }
 
     async fn refresh_data(&mut self) {
-        let lookback: usize = std::env::var("DATA_LOOKBACK")
-            .ok()
-            .and_then(|s| s.parse().ok())
-            .unwrap_or(200);
-        let timeframes = [
+        let lookback = self.config.data_lookback.unwrap_or(DEFAULT_DATA_LOOKBACK);
+        let mut timeframes = vec![
             (Timeframe::M1, lookback),
             (Timeframe::M5, lookback),
             (Timeframe::M15, lookback),
             (Timeframe::H1, lookback),
             (Timeframe::D1, 30),
         ];
+        // 4H comes from get_4h below
+        for tf in self.config.scale_timeframes() {
+            if tf != Timeframe::H4 && !timeframes.iter().any(|(t, _)| *t == tf) {
+                timeframes.push((tf, lookback));
+            }
+        }
 
         for (tf, limit) in timeframes {
             if let Ok(data) = self.exchange.fetch_ohlcv(tf, limit).await {
                 if !data.is_empty() {
+                    let data = self.checked(tf, data);
                     self.data_cache.insert(tf, data);
                 }
             }
@@ -207,11 +394,27 @@ impl BacktestRunner {
 
         if let Ok(data) = self.exchange.get_4h(200).await {
             if !data.is_empty() {
+                let data = self.checked(Timeframe::H4, data);
                 self.data_cache.insert(Timeframe::H4, data);
             }
         }
     }
 
+    /// Log holes and duplicates in refreshed candles and repair them per
+    /// GAP_FILL.
+    fn checked(&self, tf: Timeframe, data: CandleSeries) -> CandleSeries {
+        let (data, diag) = data.repair(tf, self.config.gap_fill);
+        if !diag.is_clean() {
+            debug!(
+                "{} data at {}: {}",
+                tf,
+                self.exchange.current_time(),
+                diag.summary()
+            );
+        }
+        data
+    }
+
     fn analyze_weekly(&mut self) {
         let daily = match self.data_cache.get(&Timeframe::D1) {
             Some(d) if !d.is_empty() => d,
@@ -227,17 +430,36 @@ impl BacktestRunner {
         self.weekly_bias = Some(bias);
     }
 
-    async fn check_positions(&mut self, sim_time: DateTime<Utc>) {
-        let open_pos: Vec<(usize, Direction, f64, String)> = self
-            .paper_trader
-            .positions
-            .iter()
-            .enumerate()
-            .filter(|(_, p)| p.status == PositionStatus::Open)
-            .map(|(i, p)| (i, p.direction, p.stop_loss, p.scale.clone()))
-            .collect();
+    /// Charge funding for every settlement since the last step, at the
+    /// historical rate when loaded, else the configured flat rate.
+    async fn settle_funding(&mut self, now: DateTime<Utc>) {
+        let symbol = self.config.symbol.clone();
+        if !self.config.instrument(&symbol).pays_funding() {
+            return;
+        }
+        let Some(since) = self.last_funding.replace(now) else {
+            return;
+        };
+        let times = funding_times(since, now, self.config.funding_interval_hours);
+        if times.is_empty() || self.paper_trader.open_positions().next().is_none() {
+            return;
+        }
+        let Ok(price) = self.exchange.get_current_price().await else {
+            return;
+        };
+        for t in times {
+            let rate = if self.exchange.has_funding() {
+                self.exchange.funding_rate_at(t).unwrap_or(0.0)
+            } else {
+                self.config.funding_rate
+            };
+            self.paper_trader.apply_funding(&symbol, rate, price);
+        }
+    }
 
-        if open_pos.is_empty() {
+    async fn check_positions(&mut self, sim_time: DateTime<Utc>) {
+        let since = self.last_position_check.replace(sim_time);
+        if self.paper_trader.is_flat() {
             return;
         }
 
@@ -246,46 +468,79 @@ impl BacktestRunner {
             Err(_) => return,
         };
 
-        // Trail stops using scale-appropriate timeframe
-        let trail_tf_env = std::env::var("TRAIL_TF").unwrap_or_default();
-        for &(_, direction, stop_loss, ref scale) in &open_pos {
-            // Use scale's entry TF for trailing, or override via env
-            let trail_tf = if !trail_tf_env.is_empty() {
-                match trail_tf_env.as_str() {
-                    "1m" => Timeframe::M1,
-                    "5m" => Timeframe::M5,
-                    "15m" => Timeframe::M15,
-                    _ => Timeframe::M5,
-                }
-            } else {
-                match scale.as_str() {
-                    "1m" => Timeframe::M1,
-                    "5m" => Timeframe::M5,
-                    "15m" => Timeframe::M15,
-                    _ => Timeframe::M5,
-                }
-            };
-            if let Some(trail_df) = self.data_cache.get(&trail_tf) {
-                let mut trail_engine = StopLossEngine::new();
-                if let Some(new_sl) =
-                    trail_engine.get_trailing_stop(direction, stop_loss, trail_df, None)
-                {
-                    for pos in &mut self.paper_trader.positions {
-                        if pos.status == PositionStatus::Open
-                            && pos.direction == direction
-                            && (pos.stop_loss - stop_loss).abs() < 0.01
-                        {
-                            pos.stop_loss = new_sl.price;
-                            break;
-                        }
+        // Trail each position on its own state, by id
+        let updates = self.trailing.check(
+            &self.paper_trader,
+            &self.config.symbol,
+            current_price,
+            &self.data_cache,
+            self.config.precision(&self.config.symbol),
+        );
+        for update in updates {
+            let id = update.position_id;
+            let reason = StopAdjustReason::Trail;
+            if let Some(old_sl) = self.paper_trader.update_stop(id, update.price, reason) {
+                let event = JournalEvent::trail(id, old_sl, update.price, reason);
+                self.journal.record(sim_time, event);
+            }
+        }
+
+        // Pull stops in once per position ahead of high-impact news
+        let (before, after) = (
+            self.config.news_blackout_before,
+            self.config.news_blackout_after,
+        );
+        match self.calendar.blackout(sim_time, before, after) {
+            Some(_) if self.config.news_tighten_stops => {
+                let positions: Vec<Position> = self
+                    .paper_trader
+                    .open_positions()
+                    .filter(|p| !self.news_tightened.contains(&p.id))
+                    .cloned()
+                    .collect();
+                for pos in positions {
+                    self.news_tightened.insert(pos.id);
+                    let Some(stop) = tightened_stop(&pos, current_price) else {
+                        continue;
+                    };
+                    let reason = StopAdjustReason::News;
+                    if let Some(old_sl) = self.paper_trader.update_stop(pos.id, stop, reason) {
+                        let event = JournalEvent::trail(pos.id, old_sl, stop, reason);
+                        self.journal.record(sim_time, event);
                     }
                 }
             }
+            Some(_) => {}
+            None => self.news_tightened.clear(),
         }
 
-        let closed = self.paper_trader.check_positions(current_price);
+        let closed = if self.tick_seconds > 0 {
+            self.check_positions_intrabar(since, sim_time)
+        } else {
+            self.paper_trader.check_positions(current_price)
+        };
+        for (id, pe) in self.paper_trader.take_unlogged_partials() {
+            let event = JournalEvent::partial(id, &pe);
+            self.journal.record(sim_time, event);
+        }
+
+        // Unfilled limits give their scale's slot back without a cooldown
+        for pos in self.paper_trader.take_limit_updates() {
+            if pos.status == PositionStatus::Cancelled {
+                debug!(
+                    "[BT {}] {} limit @ {:.2} expired",
+                    sim_time.format("%m-%d %H:%M"),
+                    pos.scale,
+                    pos.entry_price
+                );
+                self.scale_positions.retain(|_, id| *id != pos.id);
+            } else {
+                self.journal.record(sim_time, JournalEvent::entry(&pos));
+            }
+        }
 
         for pos in &closed {
+            self.journal.record(sim_time, JournalEvent::close(pos));
             let result = if pos.pnl > 0.0 { "WIN" } else { "LOSS" };
             debug!(
                 "[BT {}] Position #{} {} PnL ${:+.2}",
@@ -304,15 +559,86 @@ impl BacktestRunner {
                 .collect();
             for key in keys_to_remove {
                 self.scale_positions.remove(&key);
-                let cooldown_mins: i64 = std::env::var("COOLDOWN_MINUTES")
-                    .ok()
-                    .and_then(|s| s.parse().ok())
-                    .unwrap_or(30);
+                let cooldown_mins = self.config.cooldown_minutes.unwrap_or(DEFAULT_COOLDOWN_MINUTES);
                 self.scale_cooldown.insert(key, sim_time + ChronoDuration::minutes(cooldown_mins));
             }
         }
     }
 
+    /// Trip or reset the drawdown circuit breaker; a trip closes open
+    /// positions when DRAWDOWN_FLATTEN is set.
+    async fn check_drawdown(&mut self, sim_time: DateTime<Utc>) {
+        match self.paper_trader.update_drawdown_guard() {
+            Some(BreakerEvent::Tripped { drawdown_pct, peak }) => {
+                info!(
+                    "[BT {}] Circuit breaker tripped: {:.1}% below peak ${:.2}",
+                    sim_time.format("%m-%d %H:%M"),
+                    drawdown_pct,
+                    peak
+                );
+                if self.config.drawdown_flatten {
+                    if let Ok(price) = self.exchange.get_current_price().await {
+                        let closed = self.paper_trader.close_all(price);
+                        for pos in &closed {
+                            self.journal.record(sim_time, JournalEvent::close(pos));
+                        }
+                        self.scale_positions
+                            .retain(|_, id| !closed.iter().any(|p| p.id == *id));
+                    }
+                }
+            }
+            Some(BreakerEvent::Reset { .. }) => {
+                info!(
+                    "[BT {}] Circuit breaker reset",
+                    sim_time.format("%m-%d %H:%M")
+                );
+            }
+            None => {}
+        }
+    }
+
+    /// Replay every 1m bar since the last check so SL/TP races inside a step
+    /// resolve in path order rather than at the close. Coarse tick settings
+    /// evaluate each bar's extremes directly using `intrabar_ordering`.
+    fn check_positions_intrabar(
+        &mut self,
+        since: Option<DateTime<Utc>>,
+        until: DateTime<Utc>,
+    ) -> Vec<Position> {
+        let after = since.unwrap_or(until - ChronoDuration::minutes(1));
+        let ticks_per_bar = (60 / self.tick_seconds.clamp(1, 60)) as usize;
+        let mut closed = Vec::new();
+
+        'bars: for bar in self.exchange.candles_between(Timeframe::M1, after, until) {
+            if self.paper_trader.is_flat() {
+                break;
+            }
+            self.paper_trader.bar_volume = Some(bar.volume);
+            if ticks_per_bar <= 4 {
+                self.paper_trader.sim_time = Some(bar.timestamp);
+                closed.extend(
+                    self.paper_trader
+                        .check_positions_with_candle(bar, self.intrabar_ordering),
+                );
+                continue;
+            }
+
+            let path = synthetic_ticks(bar, ticks_per_bar);
+            let spacing_ms = 60_000 / path.len() as i64;
+            for (k, price) in path.into_iter().enumerate() {
+                if self.paper_trader.is_flat() {
+                    break 'bars;
+                }
+                self.paper_trader.sim_time =
+                    Some(bar.timestamp + ChronoDuration::milliseconds(spacing_ms * k as i64));
+                closed.extend(self.paper_trader.check_positions(price));
+            }
+        }
+
+        self.paper_trader.sim_time = Some(until);
+        closed
+    }
+
     async fn scan_scale(&mut self, scale_key: &str, sim_time: DateTime<Utc>) {
         let weekly_bias = match &self.weekly_bias {
             Some(b) => b.clone(),
@@ -329,12 +655,23 @@ impl BacktestRunner {
             return;
         }
 
+        // No entries around high-impact news
+        let (before, after) = (
+            self.config.news_blackout_before,
+            self.config.news_blackout_after,
+        );
+        if self.calendar.blackout(sim_time, before, after).is_some() {
+            return;
+        }
+
         let profile_str = weekly_bias.profile.to_string();
         if !self.session.should_trade_today(&self.config, &profile_str) {
             return;
         }
 
-        if self.scale_positions.contains_key(scale_key) {
+        if self.scale_positions.contains_key(scale_key)
+            || self.pending_entries.contains_key(scale_key)
+        {
             return;
         }
 
@@ -343,9 +680,11 @@ impl BacktestRunner {
             if sim_time < cooldown_until {
                 return;
             }
-            self.scale_cooldown.remove(&scale_key.to_string());
+            self.scale_cooldown.remove(scale_key);
         }
 
+        self.paper_trader
+            .set_regime(RiskRegime::classify(&weekly_bias, &self.config), &self.config);
         if !self.paper_trader.can_open_position(&self.config) {
             return;
         }
@@ -360,31 +699,27 @@ impl BacktestRunner {
 
         let midnight_open = self.exchange.get_midnight_open().await.ok().flatten();
 
-        // Evaluate this scale
-        let scale = match self.fractal.scales.get_mut(scale_key) {
-            Some(s) => s,
-            None => return,
-        };
-
-        let signal = match scale.evaluate(&self.data_cache, midnight_open, &self.session, &self.config)
-        {
-            Some(s) => s,
-            None => return,
+        // Evaluate this scale with each strategy in STRATEGIES order (the
+        // fractal engine adds cross-scale confluence)
+        let ordered = self
+            .strategies
+            .iter_mut()
+            .map(|s| s.as_mut() as &mut dyn Strategy);
+        let found = strategy::first_signal(
+            ordered,
+            scale_key,
+            &self.data_cache,
+            midnight_open,
+            &self.session,
+            &self.config,
+        );
+        let Some((strategy_name, signal)) = found else {
+            return;
         };
 
         self.total_signals += 1;
 
-        // Cross-scale confluence
-        let all_signals =
-            self.fractal
-                .evaluate_all(&self.data_cache, midnight_open, &self.session, &self.config);
-
-        let signal = all_signals
-            .into_iter()
-            .find(|s| s.scale == scale_key)
-            .unwrap_or(signal);
-
-        let min_conf = self.config.hft_scales[scale_key].min_confidence;
+        let min_conf = self.config.min_confidence(scale_key, &signal.session);
         if signal.confidence < min_conf {
             self.signals_filtered += 1;
             return;
@@ -393,27 +728,24 @@ impl BacktestRunner {
         // Minimum TP distance filter: ensure expected profit > round-trip fees
         let tp_dist_pct = (signal.take_profit - signal.entry_price).abs() / signal.entry_price;
         let round_trip_fee = (self.config.fee_rate + self.config.slippage_rate) * 2.0;
-        let min_tp_multiple: f64 = std::env::var("MIN_TP_MULTIPLE")
-            .ok()
-            .and_then(|s| s.parse().ok())
-            .unwrap_or(6.0);
-        if tp_dist_pct < round_trip_fee * min_tp_multiple {
+        if tp_dist_pct < round_trip_fee * self.config.min_tp_multiple {
             self.signals_filtered += 1;
             return;
         }
 
         // Build metadata
         let pda = &signal.pda_engaged;
+        let tags = context_tags(&self.session, &weekly_bias, &self.calendar, sim_time);
         let metadata = TradeMetadata {
             scale: scale_key.to_string(),
-            direction: signal.direction.to_string(),
+            direction: signal.direction,
             confidence: signal.confidence,
             session: signal.session.clone(),
             session_weight: signal.session_weight,
             cisd_confirmed: signal.cisd_confirmed,
             pda_type: pda.pda_type.to_string(),
-            pda_direction: pda.direction.to_string(),
-            pda_zone: pda.zone.to_string(),
+            pda_direction: Some(pda.direction),
+            pda_zone: Some(pda.zone),
             pda_strength: pda.strength,
             stop_mode: signal.stop_mode.clone(),
             tp_label: signal.tp_label.clone(),
@@ -421,16 +753,71 @@ impl BacktestRunner {
             cross_scale_confluence: signal.cross_scale_confluence,
             alignment: signal.alignment.clone(),
             weekly_profile: weekly_bias.profile.to_string(),
-            weekly_direction: weekly_bias.direction.to_string(),
+            weekly_direction: Some(weekly_bias.direction),
             weekly_confidence: weekly_bias.confidence,
             day_of_week: day,
+            market_regime: Some(self.session.market_regime),
             kelly_fraction: 0.0,
+            risk_regime: String::new(),
+            sizing_mode: String::new(),
+            tags: tags.clone(),
+            strategy: strategy_name.to_string(),
+            extra: Default::default(),
         };
 
-        let trade_signal = signal.to_trade_signal();
-        if let Some(pos) = self.paper_trader.open_position(&trade_signal, scale_key, Some(metadata))
-        {
+        let mut trade_signal = signal.to_trade_signal();
+        trade_signal.tags = tags;
+        trade_signal.size_multiplier = self
+            .refiner
+            .size_multiplier(scale_key, &self.session.current_session)
+            * self.config.strategy_risk(strategy_name);
+        trade_signal.atr_percentile = self
+            .config
+            .hft_scales
+            .get(scale_key)
+            .and_then(|s| self.data_cache.get(&s.entry_tf))
+            .and_then(|c| position_sizing::atr_percentile(c, position_sizing::ATR_PERIOD));
+        let event = JournalEvent::signal(&self.config.symbol, scale_key, &trade_signal);
+        self.journal.record(sim_time, event);
+        if let Some(limit) = signal.limit_entry.filter(|_| self.config.ote_entries) {
+            trade_signal.entry_price = limit;
+            let expires = sim_time + ChronoDuration::minutes(self.config.ote_expiry_minutes);
+            let symbol = self.config.symbol.clone();
+            if let Some(pos) = self.paper_trader.place_limit_for(
+                &symbol,
+                &trade_signal,
+                scale_key,
+                Some(metadata),
+                expires,
+            ) {
+                self.scale_positions.insert(scale_key.to_string(), pos.id);
+            }
+            return;
+        }
+        if self.config.fill_timing == FillTiming::NextBarOpen {
+            self.pending_entries.insert(
+                scale_key.to_string(),
+                PendingEntry {
+                    signal: trade_signal,
+                    metadata,
+                    signal_time: sim_time,
+                },
+            );
+            return;
+        }
+        self.open_entry(scale_key, &trade_signal, metadata, sim_time);
+    }
+
+    fn open_entry(
+        &mut self,
+        scale_key: &str,
+        signal: &TradeSignal,
+        metadata: TradeMetadata,
+        sim_time: DateTime<Utc>,
+    ) {
+        if let Some(pos) = self.paper_trader.open_position(signal, scale_key, Some(metadata)) {
             let pos_id = pos.id;
+            self.journal.record(sim_time, JournalEvent::entry(pos));
             self.scale_positions.insert(scale_key.to_string(), pos_id);
 
             debug!(
@@ -443,4 +830,85 @@ impl BacktestRunner {
             );
         }
     }
+
+    /// Open each pending signal `fill_latency_secs` after its signal bar
+    /// closes: at the next 1m bar's open, or further along that bar's
+    /// synthetic path. Entries whose fill already gapped past the stop or
+    /// target are dropped and counted as filtered.
+    fn fill_pending(&mut self, now: DateTime<Utc>) {
+        let latency = ChronoDuration::seconds(60 + self.config.fill_latency_secs.max(0));
+        let mut scales: Vec<String> = self.pending_entries.keys().cloned().collect();
+        scales.sort();
+        for scale_key in scales {
+            let Some(pending) = self.pending_entries.remove(&scale_key) else {
+                continue;
+            };
+            // Not due yet: wait for a later step
+            if pending.signal_time + latency > now {
+                self.pending_entries.insert(scale_key, pending);
+                continue;
+            }
+            let bars = self
+                .exchange
+                .candles_between(Timeframe::M1, pending.signal_time, now);
+            let Some((fill_time, price)) = price_at(bars, pending.signal_time + latency) else {
+                debug!("[BT] No bar after {} signal, entry dropped", scale_key);
+                continue;
+            };
+            let Some(signal) = reprice_at_open(&pending.signal, price) else {
+                self.signals_filtered += 1;
+                continue;
+            };
+            if !self.paper_trader.can_open_position(&self.config) {
+                continue;
+            }
+            self.paper_trader.sim_time = Some(fill_time);
+            self.open_entry(&scale_key, &signal, pending.metadata, fill_time);
+            self.paper_trader.sim_time = Some(now);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::make_signal;
+
+    #[test]
+    fn reprices_to_next_open_unless_it_gapped_through_a_level() {
+        let signal = make_signal(Direction::Long, 100.0, 98.0, 104.0);
+        let filled = reprice_at_open(&signal, 100.4).unwrap();
+        assert_eq!(filled.entry_price, 100.4);
+        assert_eq!((filled.stop_loss, filled.take_profit), (98.0, 104.0));
+        assert!(reprice_at_open(&signal, 97.5).is_none());
+        assert!(reprice_at_open(&signal, 104.0).is_none());
+
+        let short = TradeSignal {
+            direction: Direction::Short,
+            stop_loss: 102.0,
+            take_profit: 96.0,
+            ..signal
+        };
+        assert!(reprice_at_open(&short, 99.0).is_some());
+        assert!(reprice_at_open(&short, 102.5).is_none());
+    }
+
+    #[test]
+    fn latency_fills_along_the_bar_or_at_the_next_open() {
+        let series = crate::test_helpers::make_candles(&[
+            (100.0, 106.0, 100.0, 106.0),
+            (110.0, 111.0, 109.0, 110.0),
+        ]);
+        let bars = series.as_slice();
+        let t0 = bars[0].timestamp;
+        // Open at the bar's start, then along O -> L -> H -> C
+        assert_eq!(price_at(bars, t0), Some((t0, 100.0)));
+        let (at, price) = price_at(bars, t0 + ChronoDuration::seconds(30)).unwrap();
+        assert_eq!(at, t0 + ChronoDuration::seconds(30));
+        assert!(price > 100.0 && price < 106.0);
+        // Before the first bar: its open
+        let before = t0 - ChronoDuration::seconds(5);
+        assert_eq!(price_at(bars, before), Some((t0, 100.0)));
+        assert!(price_at(&bars[..1], t0 + ChronoDuration::minutes(2)).is_none());
+    }
 }
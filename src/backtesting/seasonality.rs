@@ -0,0 +1,187 @@
+use chrono::{Datelike, Timelike};
+use chrono_tz::US::Eastern;
+use serde::{Deserialize, Serialize};
+use std::fmt::Write as _;
+
+use crate::models::PositionStatus;
+use crate::storage;
+use crate::trading::paper_trader::Position;
+
+pub const WEEKDAYS: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct SeasonalityCell {
+    pub trades: usize,
+    pub wins: usize,
+    pub pnl: f64,
+}
+
+/// Closed trades bucketed by ET weekday and hour of entry, for tuning
+/// killzones and day ratings. Works on any trade history, so live and
+/// backtest ledgers can be compared or merged.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Seasonality {
+    /// `cells[weekday][hour]`, Monday first
+    pub cells: Vec<Vec<SeasonalityCell>>,
+}
+
+impl Default for Seasonality {
+    fn default() -> Self {
+        Self {
+            cells: vec![vec![SeasonalityCell::default(); 24]; 7],
+        }
+    }
+}
+
+impl Seasonality {
+    pub fn from_positions<'a>(positions: impl IntoIterator<Item = &'a Position>) -> Self {
+        let mut out = Self::default();
+        for p in positions {
+            if p.status == PositionStatus::Open {
+                continue;
+            }
+            let Some(entry) = storage::entry_time(p) else {
+                continue;
+            };
+            let et = entry.with_timezone(&Eastern);
+            let cell = &mut out.cells[et.weekday().num_days_from_monday() as usize]
+                [et.hour() as usize];
+            cell.trades += 1;
+            if p.pnl > 0.0 {
+                cell.wins += 1;
+            }
+            cell.pnl += p.pnl;
+        }
+        out
+    }
+
+    /// Add `other`'s counts into this matrix (e.g. live + backtest).
+    pub fn merge(&mut self, other: &Seasonality) {
+        for (row, other_row) in self.cells.iter_mut().zip(&other.cells) {
+            for (cell, o) in row.iter_mut().zip(other_row) {
+                cell.trades += o.trades;
+                cell.wins += o.wins;
+                cell.pnl += o.pnl;
+            }
+        }
+    }
+
+    /// Non-empty slots as `(weekday, hour, cell)`.
+    pub fn slots(&self) -> impl Iterator<Item = (usize, usize, &SeasonalityCell)> {
+        self.cells.iter().enumerate().flat_map(|(d, row)| {
+            row.iter()
+                .enumerate()
+                .filter(|(_, c)| c.trades > 0)
+                .map(move |(h, c)| (d, h, c))
+        })
+    }
+
+    /// Non-empty slots sorted by net PnL, best first.
+    pub fn ranked(&self) -> Vec<(usize, usize, SeasonalityCell)> {
+        let mut slots: Vec<_> = self.slots().map(|(d, h, c)| (d, h, *c)).collect();
+        slots.sort_by(|a, b| b.2.pnl.total_cmp(&a.2.pnl));
+        slots
+    }
+
+    /// One row per slot (all 168): `weekday,hour_et,trades,wins,net_pnl`.
+    pub fn to_csv(&self) -> String {
+        let mut out = String::from("weekday,hour_et,trades,wins,net_pnl\n");
+        for (d, row) in self.cells.iter().enumerate() {
+            for (h, c) in row.iter().enumerate() {
+                let _ = writeln!(
+                    out,
+                    "{},{},{},{},{:.2}",
+                    WEEKDAYS[d], h, c.trades, c.wins, c.pnl
+                );
+            }
+        }
+        out
+    }
+
+    /// Hour × weekday table, cells shaded green/red by net PnL.
+    pub fn to_html(&self) -> String {
+        let max_abs = self
+            .slots()
+            .map(|(_, _, c)| c.pnl.abs())
+            .fold(0.0_f64, f64::max);
+        let mut out = String::from("<table class=\"seasonality\">\n<tr><th>ET</th>");
+        for day in WEEKDAYS {
+            let _ = write!(out, "<th>{}</th>", day);
+        }
+        out.push_str("</tr>\n");
+        for h in 0..24 {
+            let _ = write!(out, "<tr><th>{:02}:00</th>", h);
+            for row in &self.cells {
+                let c = &row[h];
+                if c.trades == 0 {
+                    out.push_str("<td></td>");
+                    continue;
+                }
+                let alpha = if max_abs > 0.0 {
+                    c.pnl.abs() / max_abs * 0.8
+                } else {
+                    0.0
+                };
+                let rgb = if c.pnl >= 0.0 { "46,204,113" } else { "231,76,60" };
+                let _ = write!(
+                    out,
+                    "<td style=\"background:rgba({},{:.2})\" title=\"{} trades, {} wins\">{} | {:+.2}</td>",
+                    rgb, alpha, c.trades, c.wins, c.trades, c.pnl
+                );
+            }
+            out.push_str("</tr>\n");
+        }
+        out.push_str("</table>\n");
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trade(entry: &str, pnl: f64, status: &str) -> Position {
+        serde_json::from_value(serde_json::json!({
+            "id": 1, "symbol": "BTC-USD", "direction": "long", "entry_price": 100.0,
+            "size_usd": 100.0, "size_btc": 1.0, "stop_loss": 99.0,
+            "take_profit": 102.0, "entry_time": entry, "reason": "test",
+            "scale": "5m", "status": status, "pnl": pnl,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn buckets_closed_trades_by_et_weekday_and_hour() {
+        // 14:30 UTC on Monday 2024-01-15 is 09:30 ET (EST)
+        let history = vec![
+            trade("2024-01-15T14:30:00Z", 5.0, "closed_tp"),
+            trade("2024-01-15T14:55:00Z", -2.0, "closed_sl"),
+            // 03:00 UTC Wednesday is 22:00 ET Tuesday
+            trade("2024-01-17T03:00:00Z", -1.5, "closed_sl"),
+            trade("2024-01-15T14:40:00Z", 9.0, "open"),
+        ];
+        let mut s = Seasonality::from_positions(&history);
+        assert_eq!(
+            s.cells[0][9],
+            SeasonalityCell {
+                trades: 2,
+                wins: 1,
+                pnl: 3.0
+            }
+        );
+        assert_eq!(s.cells[1][22].trades, 1);
+        assert_eq!(s.slots().count(), 2);
+        assert_eq!(s.ranked()[0].0, 0);
+
+        s.merge(&Seasonality::from_positions(&history[..1]));
+        assert_eq!(s.cells[0][9].trades, 3);
+
+        let csv = s.to_csv();
+        assert_eq!(csv.lines().count(), 1 + 7 * 24);
+        assert!(csv.contains("\nMon,9,3,2,8.00\n"));
+        assert!(csv.contains("\nTue,22,1,0,-1.50\n"));
+        let html = s.to_html();
+        assert!(html.contains("<th>09:00</th>"));
+        assert!(html.contains("3 | +8.00"));
+    }
+}
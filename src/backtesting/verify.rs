@@ -0,0 +1,151 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+
+use crate::config::Config;
+use crate::exchange::HistoricalExchange;
+use crate::trading::paper_trader::Position;
+
+use super::report::BacktestReport;
+use super::runner::BacktestRunner;
+
+/// Result of running the same backtest twice.
+#[derive(Debug, Clone)]
+pub struct VerifyOutcome {
+    pub trades: usize,
+    pub divergences: Vec<String>,
+}
+
+impl VerifyOutcome {
+    pub fn is_deterministic(&self) -> bool {
+        self.divergences.is_empty()
+    }
+}
+
+/// Run the backtest twice on identical inputs and diff trades and stats.
+pub async fn verify(
+    exchange: HistoricalExchange,
+    config: Config,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    step_minutes: i64,
+) -> Result<VerifyOutcome> {
+    let mut first = BacktestRunner::new(exchange.clone(), config.clone());
+    let report_a = first.run(start, end, step_minutes).await?;
+
+    let mut second = BacktestRunner::new(exchange, config);
+    let report_b = second.run(start, end, step_minutes).await?;
+
+    let trades_a = first.paper_trader.history();
+    let trades_b = second.paper_trader.history();
+
+    let mut divergences = diff_trades(trades_a, trades_b);
+    divergences.extend(diff_reports(&report_a, &report_b));
+
+    Ok(VerifyOutcome {
+        trades: trades_a.len(),
+        divergences,
+    })
+}
+
+/// Trade-by-trade comparison; every serialized field must match exactly.
+pub fn diff_trades(a: &[Position], b: &[Position]) -> Vec<String> {
+    let mut out = Vec::new();
+    if a.len() != b.len() {
+        out.push(format!("trade count: {} vs {}", a.len(), b.len()));
+    }
+    for (i, (ta, tb)) in a.iter().zip(b.iter()).enumerate() {
+        let va = serde_json::to_value(ta).unwrap_or_default();
+        let vb = serde_json::to_value(tb).unwrap_or_default();
+        if va == vb {
+            continue;
+        }
+        let fields: Vec<String> = match (va.as_object(), vb.as_object()) {
+            (Some(oa), Some(ob)) => oa
+                .iter()
+                .filter(|(k, v)| ob.get(*k) != Some(*v))
+                .map(|(k, v)| format!("{}={} vs {}", k, v, ob.get(k).cloned().unwrap_or_default()))
+                .collect(),
+            _ => vec!["unserializable".to_string()],
+        };
+        out.push(format!("trade #{} (id {}): {}", i, ta.id, fields.join(", ")));
+    }
+    out
+}
+
+/// Compare headline stats bit-for-bit.
+pub fn diff_reports(a: &BacktestReport, b: &BacktestReport) -> Vec<String> {
+    let mut out = Vec::new();
+    let floats = [
+        ("final_balance", a.final_balance, b.final_balance),
+        ("total_pnl", a.total_pnl, b.total_pnl),
+        ("win_rate", a.win_rate, b.win_rate),
+        ("profit_factor", a.profit_factor, b.profit_factor),
+        ("max_drawdown", a.max_drawdown, b.max_drawdown),
+        ("sharpe_ratio", a.sharpe_ratio, b.sharpe_ratio),
+    ];
+    for (name, x, y) in floats {
+        if x.to_bits() != y.to_bits() {
+            out.push(format!("{}: {} vs {}", name, x, y));
+        }
+    }
+    let counts = [
+        ("total_trades", a.total_trades, b.total_trades),
+        ("total_signals", a.total_signals, b.total_signals),
+        ("signals_filtered", a.signals_filtered, b.signals_filtered),
+        ("equity_points", a.equity_curve.len(), b.equity_curve.len()),
+    ];
+    for (name, x, y) in counts {
+        if x != y {
+            out.push(format!("{}: {} vs {}", name, x, y));
+        }
+    }
+    if let Some((ea, eb)) = a
+        .equity_curve
+        .iter()
+        .zip(b.equity_curve.iter())
+        .find(|(ea, eb)| ea.0 != eb.0 || ea.1.to_bits() != eb.1.to_bits())
+    {
+        out.push(format!(
+            "equity curve first diverges at {}: {} vs {}",
+            ea.0, ea.1, eb.1
+        ));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Direction;
+    use crate::test_helpers::{default_test_config, make_signal};
+    use crate::trading::paper_trader::PaperTrader;
+
+    fn closed_trades(exit: f64) -> Vec<Position> {
+        let cfg = default_test_config();
+        let mut trader = PaperTrader::new_fresh(&cfg);
+        trader.sim_time = Some(Utc::now());
+        let signal = make_signal(Direction::Long, 50000.0, 49500.0, 51000.0);
+        trader.open_position(&signal, "5m", None);
+        trader.close_all(exit);
+        trader.history().to_vec()
+    }
+
+    #[test]
+    fn identical_trades_have_no_divergence() {
+        let a = closed_trades(50200.0);
+        assert!(diff_trades(&a, &a.clone()).is_empty());
+    }
+
+    #[test]
+    fn changed_exit_is_reported() {
+        let a = closed_trades(50200.0);
+        let mut b = a.clone();
+        b[0].exit_price = Some(50300.0);
+        let diffs = diff_trades(&a, &b);
+        assert_eq!(diffs.len(), 1);
+        assert!(diffs[0].contains("exit_price"));
+
+        let diffs = diff_trades(&a, &[]);
+        assert!(diffs[0].contains("trade count"));
+    }
+}
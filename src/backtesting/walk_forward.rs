@@ -0,0 +1,229 @@
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use tracing::info;
+
+use crate::config::Config;
+use crate::exchange::HistoricalExchange;
+use crate::trading::strategy_refiner::{Adjustment, StrategyRefiner};
+use crate::trading::trade_record::TradeRecord;
+
+use super::report::BacktestReport;
+use super::runner::BacktestRunner;
+
+/// One train/test split.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WindowSpan {
+    pub train_start: DateTime<Utc>,
+    pub train_end: DateTime<Utc>,
+    pub test_start: DateTime<Utc>,
+    pub test_end: DateTime<Utc>,
+}
+
+/// Rolling windows over `[start, end]`; test periods are contiguous and
+/// non-overlapping, each preceded by `train` of history.
+pub fn rolling_windows(
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    train: Duration,
+    test: Duration,
+) -> Vec<WindowSpan> {
+    let mut out = Vec::new();
+    if train <= Duration::zero() || test <= Duration::zero() {
+        return out;
+    }
+    let mut train_start = start;
+    while train_start + train + test <= end {
+        let train_end = train_start + train;
+        out.push(WindowSpan {
+            train_start,
+            train_end,
+            test_start: train_end,
+            test_end: train_end + test,
+        });
+        train_start += test;
+    }
+    out
+}
+
+/// Results for one window: refinements learned in-sample and how they
+/// did out-of-sample versus the unrefined baseline.
+pub struct WalkForwardWindow {
+    pub span: WindowSpan,
+    pub adjustments: Vec<Adjustment>,
+    pub skip_combos: Vec<String>,
+    pub in_sample: BacktestReport,
+    pub out_of_sample: BacktestReport,
+    pub baseline: BacktestReport,
+}
+
+pub struct WalkForwardReport {
+    pub windows: Vec<WalkForwardWindow>,
+}
+
+impl WalkForwardReport {
+    pub fn oos_pnl(&self) -> f64 {
+        self.windows.iter().map(|w| w.out_of_sample.total_pnl).sum()
+    }
+
+    pub fn baseline_pnl(&self) -> f64 {
+        self.windows.iter().map(|w| w.baseline.total_pnl).sum()
+    }
+
+    pub fn oos_trades(&self) -> usize {
+        self.windows
+            .iter()
+            .map(|w| w.out_of_sample.total_trades)
+            .sum()
+    }
+
+    pub fn oos_win_rate(&self) -> f64 {
+        let trades = self.oos_trades();
+        if trades == 0 {
+            return 0.0;
+        }
+        let wins: usize = self
+            .windows
+            .iter()
+            .map(|w| w.out_of_sample.winning_trades)
+            .sum();
+        wins as f64 / trades as f64 * 100.0
+    }
+
+    /// Out-of-sample PnL/day over in-sample PnL/day. Near or above 1.0 means
+    /// the refined edge carried over; well below 1.0 suggests overfitting.
+    pub fn efficiency(&self) -> Option<f64> {
+        let per_day = |r: &BacktestReport| {
+            if r.days > 0.0 {
+                r.total_pnl / r.days
+            } else {
+                0.0
+            }
+        };
+        let is: f64 = self.windows.iter().map(|w| per_day(&w.in_sample)).sum();
+        let oos: f64 = self.windows.iter().map(|w| per_day(&w.out_of_sample)).sum();
+        (is > 0.0).then(|| oos / is)
+    }
+
+    pub fn print_summary(&self) {
+        println!("\n{}", "=".repeat(70));
+        println!("  WALK-FORWARD REPORT ({} windows)", self.windows.len());
+        println!("{}", "=".repeat(70));
+        for (i, w) in self.windows.iter().enumerate() {
+            println!(
+                "  #{:<2} test {} to {} | IS ${:+.2} | OOS ${:+.2} ({} trades) | base ${:+.2} | {} adj, {} skips",
+                i + 1,
+                w.span.test_start.format("%Y-%m-%d"),
+                w.span.test_end.format("%Y-%m-%d"),
+                w.in_sample.total_pnl,
+                w.out_of_sample.total_pnl,
+                w.out_of_sample.total_trades,
+                w.baseline.total_pnl,
+                w.adjustments.len(),
+                w.skip_combos.len(),
+            );
+        }
+        println!();
+        println!("  OUT-OF-SAMPLE");
+        println!("  ───────────────────────────────────");
+        println!("  PnL:         ${:+.2}", self.oos_pnl());
+        println!("  Trades:      {}", self.oos_trades());
+        println!("  Win Rate:    {:.1}%", self.oos_win_rate());
+        println!(
+            "  Baseline:    ${:+.2} (no refinements)",
+            self.baseline_pnl()
+        );
+        match self.efficiency() {
+            Some(e) => println!("  Efficiency:  {:.2} (OOS vs IS PnL/day)", e),
+            None => println!("  Efficiency:  n/a (in-sample not profitable)"),
+        }
+        println!("{}", "=".repeat(70));
+    }
+}
+
+/// Train on each window, freeze the refiner's adjustments, then run the
+/// test period with them and with the unrefined config for comparison.
+pub async fn walk_forward(
+    exchange: HistoricalExchange,
+    config: Config,
+    windows: &[WindowSpan],
+    step_minutes: i64,
+) -> Result<WalkForwardReport> {
+    let mut out = Vec::new();
+
+    for (i, span) in windows.iter().enumerate() {
+        info!(
+            "Walk-forward window {}/{}: train {} to {}, test to {}",
+            i + 1,
+            windows.len(),
+            span.train_start.format("%Y-%m-%d"),
+            span.train_end.format("%Y-%m-%d"),
+            span.test_end.format("%Y-%m-%d"),
+        );
+
+        let mut train = BacktestRunner::new(exchange.clone(), config.clone())
+            .with_refiner(StrategyRefiner::in_memory(&config));
+        let in_sample = train
+            .run(span.train_start, span.train_end, step_minutes)
+            .await?;
+        let closed: Vec<TradeRecord> = train
+            .paper_trader
+            .records()
+            .values()
+            .filter(|r| r.outcome == "win" || r.outcome == "loss")
+            .cloned()
+            .collect();
+
+        let mut frozen_cfg = config.clone();
+        let mut refiner = StrategyRefiner::in_memory(&config);
+        refiner.sim_time = Some(span.train_end);
+        let mut adjustments = refiner.refine(&closed, &mut frozen_cfg);
+        adjustments
+            .extend(refiner.refine_tp_allocation(train.paper_trader.history(), &mut frozen_cfg));
+        let mut skip_combos: Vec<String> = refiner.skip_combos.keys().cloned().collect();
+        skip_combos.sort();
+
+        let mut test = BacktestRunner::new(exchange.clone(), frozen_cfg).with_refiner(refiner);
+        let out_of_sample = test
+            .run(span.test_start, span.test_end, step_minutes)
+            .await?;
+
+        let mut base = BacktestRunner::new(exchange.clone(), config.clone())
+            .with_refiner(StrategyRefiner::in_memory(&config));
+        let baseline = base
+            .run(span.test_start, span.test_end, step_minutes)
+            .await?;
+
+        out.push(WalkForwardWindow {
+            span: *span,
+            adjustments,
+            skip_combos,
+            in_sample,
+            out_of_sample,
+            baseline,
+        });
+    }
+
+    Ok(WalkForwardReport { windows: out })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn windows_roll_by_test_length() {
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let end = start + Duration::days(35);
+        let w = rolling_windows(start, end, Duration::days(14), Duration::days(7));
+        assert_eq!(w.len(), 3);
+        assert_eq!(w[0].train_start, start);
+        assert_eq!(w[0].test_start, start + Duration::days(14));
+        assert_eq!(w[1].train_start, start + Duration::days(7));
+        // Test periods tile without overlap
+        assert_eq!(w[1].test_start, w[0].test_end);
+        assert!(w[2].test_end <= end);
+
+        assert!(rolling_windows(start, end, Duration::days(30), Duration::days(7)).is_empty());
+    }
+}
@@ -2,18 +2,19 @@ use anyhow::Result;
 use chrono::{Duration, Utc};
 use tracing_subscriber::{fmt, EnvFilter};
 
-use ict_trading_bot::backtesting::data_fetcher;
-use ict_trading_bot::backtesting::BacktestRunner;
+use ict_trading_bot::backtesting::commands;
+use ict_trading_bot::backtesting::seasonality::WEEKDAYS;
+use ict_trading_bot::backtesting::verify;
+use ict_trading_bot::backtesting::walk_forward;
+use ict_trading_bot::backtesting::{BacktestRunner, Seasonality};
 use ict_trading_bot::config::Config;
-use ict_trading_bot::exchange::HistoricalExchange;
-use ict_trading_bot::models::Timeframe;
+use ict_trading_bot::storage;
+use ict_trading_bot::strategies::strategy;
 
 #[tokio::main]
 async fn main() -> Result<()> {
     dotenvy::dotenv().ok();
 
-    let cfg = Config::from_env();
-
     let filter = EnvFilter::try_from_default_env()
         .unwrap_or_else(|_| EnvFilter::new("info"));
 
@@ -23,8 +24,52 @@ async fn main() -> Result<()> {
         .with_timer(fmt::time::UtcTime::rfc_3339())
         .init();
 
-    // Parse CLI args or use defaults
-    let args: Vec<String> = std::env::args().collect();
+    // [--config profile.toml] [verify|audit|walkforward|optimize|compare|seasonality|abtest variant.toml] [days_back] [step_minutes] [symbol]
+    let mut args: Vec<String> = std::env::args().collect();
+    let mut cfg = Config::from_args(&mut args)?;
+
+    // Live ledger only; no candles needed
+    if args.get(1).is_some_and(|s| s == "seasonality") {
+        let history = storage::open(&cfg)
+            .load_trader()?
+            .map(|state| state.trade_history)
+            .unwrap_or_default();
+        let seasonality = Seasonality::from_positions(&history);
+        let path = "data/seasonality_live.csv";
+        std::fs::create_dir_all("data")?;
+        std::fs::write(path, seasonality.to_csv())?;
+        println!("{} closed trades from {}", history.len(), cfg.log_dir);
+        for (d, h, c) in seasonality.ranked() {
+            println!(
+                "  {} {:02}:00 ET: {} trades | {}W | PnL ${:+.2}",
+                WEEKDAYS[d],
+                h,
+                c.trades,
+                c.wins,
+                c.pnl
+            );
+        }
+        println!("Seasonality saved to: {}", path);
+        return Ok(());
+    }
+
+    let verify_mode = args.get(1).is_some_and(|s| s == "verify");
+    let audit_mode = args.get(1).is_some_and(|s| s == "audit");
+    let walk_forward_mode = args.get(1).is_some_and(|s| s == "walkforward");
+    let optimize_mode = args.get(1).is_some_and(|s| s == "optimize");
+    let compare_mode = args.get(1).is_some_and(|s| s == "compare");
+    if verify_mode || audit_mode || walk_forward_mode || optimize_mode || compare_mode {
+        args.remove(1);
+    }
+    // A/B: variant B's profile follows the mode
+    let mut variant = None;
+    if args.get(1).is_some_and(|s| s == "abtest") {
+        if args.len() < 3 {
+            anyhow::bail!("abtest needs the variant's config file");
+        }
+        let path: Vec<String> = args.drain(1..3).collect();
+        variant = Some(Config::from_file(&path[1])?);
+    }
 
     let days_back: i64 = args
         .get(1)
@@ -36,6 +81,11 @@ async fn main() -> Result<()> {
         .and_then(|s| s.parse().ok())
         .unwrap_or(5);
 
+    if let Some(symbol) = args.get(3) {
+        cfg = cfg.for_symbol(&symbol.to_uppercase());
+        variant = variant.map(|v| v.for_symbol(&symbol.to_uppercase()));
+    }
+
     let end = Utc::now();
     let start = end - Duration::days(days_back);
 
@@ -49,141 +99,96 @@ async fn main() -> Result<()> {
     println!("╚══════════════════════════════════════════════════════════╝");
     println!();
 
-    // Timeframes to fetch
-    let timeframes = [
-        Timeframe::M1,
-        Timeframe::M5,
-        Timeframe::M15,
-        Timeframe::H1,
-        Timeframe::H4,
-        Timeframe::D1,
-    ];
-
-    // Fetch and cache data
-    let data_dir = "data";
-    let data = data_fetcher::fetch_and_cache(&cfg, start, end, data_dir, &timeframes).await?;
-
-    // Check we have enough data
-    let m1_count = data
-        .iter()
-        .find(|(tf, _)| *tf == Timeframe::M1)
-        .map(|(_, c)| c.len())
-        .unwrap_or(0);
-
-    if m1_count == 0 {
-        println!("ERROR: No 1-minute data available. Cannot backtest.");
-        println!("Make sure your Coinbase API credentials are configured in .env");
+    let Some((exchange, bt_start, bt_end)) = commands::load_exchange(&cfg, start, end).await? else {
         return Ok(());
+    };
+
+    if verify_mode {
+        println!("Verifying determinism (running the backtest twice)...");
+        let outcome = verify::verify(exchange, cfg, bt_start, bt_end, step_minutes).await?;
+        if outcome.is_deterministic() {
+            println!("OK: {} trades and all stats identical across runs", outcome.trades);
+            return Ok(());
+        }
+        println!("DIVERGENCE DETECTED:");
+        for d in &outcome.divergences {
+            println!("  {}", d);
+        }
+        anyhow::bail!(
+            "backtest is not deterministic ({} divergences)",
+            outcome.divergences.len()
+        );
     }
 
-    println!("Data loaded:");
-    for (tf, candles) in &data {
-        println!("  {}: {} candles", tf, candles.len());
-    }
-    println!();
-
-    // Build historical exchange
-    let mut exchange = HistoricalExchange::new(&cfg.symbol);
-    for (tf, candles) in data {
-        exchange.load(tf, candles);
+    if audit_mode {
+        println!("Auditing for look-ahead bias...");
+        let report = commands::audit(exchange, cfg, bt_start, bt_end, step_minutes).await?;
+        if !report.is_clean() {
+            anyhow::bail!(
+                "look-ahead audit found {} violations",
+                report.violations.len()
+            );
+        }
+        return Ok(());
     }
 
-    // Determine actual backtest range from available data
-    let data_start = exchange.earliest_time().unwrap_or(start);
-    let data_end = exchange.latest_time().unwrap_or(end);
-
-    // Start after we have enough lookback (at least 1 day of data)
-    let bt_start = data_start + Duration::days(1);
-    let bt_end = data_end;
-
-    if bt_start >= bt_end {
-        println!("ERROR: Not enough data for backtesting");
+    if walk_forward_mode {
+        let train = Duration::days(cfg.wf_train_days);
+        let test = Duration::days(cfg.wf_test_days);
+        let windows = walk_forward::rolling_windows(bt_start, bt_end, train, test);
+        if windows.is_empty() {
+            println!("ERROR: Period too short for one train + test window");
+            return Ok(());
+        }
+        let report = walk_forward::walk_forward(exchange, cfg, &windows, step_minutes).await?;
+        report.print_summary();
         return Ok(());
     }
 
-    println!(
-        "Backtesting from {} to {}",
-        bt_start.format("%Y-%m-%d %H:%M"),
-        bt_end.format("%Y-%m-%d %H:%M")
-    );
-    println!();
-
-    // Run backtest
-    let mut runner = BacktestRunner::new(exchange, cfg);
-    let report = runner.run(bt_start, bt_end, step_minutes).await?;
-
-    // Print report
-    report.print_summary();
-
-    // Save report to file
-    let report_file = format!(
-        "data/backtest_{}_{}.txt",
-        report.start.format("%Y%m%d"),
-        report.end.format("%Y%m%d"),
-    );
-    save_report_to_file(&report, &report_file)?;
-    println!("\nReport saved to: {}", report_file);
-
-    Ok(())
-}
+    if optimize_mode {
+        return commands::optimize(exchange, cfg, bt_start, bt_end, step_minutes).await;
+    }
 
-fn save_report_to_file(
-    report: &ict_trading_bot::backtesting::BacktestReport,
-    path: &str,
-) -> Result<()> {
-    use std::io::Write;
-
-    let mut f = std::fs::File::create(path)?;
-
-    writeln!(f, "ICT Trading Bot Backtest Report")?;
-    writeln!(f, "================================")?;
-    writeln!(
-        f,
-        "Period: {} to {} ({:.0} days)",
-        report.start.format("%Y-%m-%d"),
-        report.end.format("%Y-%m-%d"),
-        report.days
-    )?;
-    writeln!(f)?;
-    writeln!(f, "Performance:")?;
-    writeln!(f, "  Initial:  ${:.2}", report.initial_balance)?;
-    writeln!(f, "  Final:    ${:.2}", report.final_balance)?;
-    writeln!(f, "  PnL:      ${:+.2}", report.total_pnl)?;
-    writeln!(f, "  Return:   {:+.1}%", report.total_return_pct)?;
-    writeln!(f)?;
-    writeln!(f, "Trades:")?;
-    writeln!(f, "  Total:       {}", report.total_trades)?;
-    writeln!(f, "  Win/Loss:    {} / {}", report.winning_trades, report.losing_trades)?;
-    writeln!(f, "  Win Rate:    {:.1}%", report.win_rate)?;
-    writeln!(f, "  Avg Win:     ${:+.2}", report.avg_win)?;
-    writeln!(f, "  Avg Loss:    ${:+.2}", report.avg_loss)?;
-    writeln!(f, "  Profit Factor: {:.2}", report.profit_factor)?;
-    writeln!(f)?;
-    writeln!(f, "Risk:")?;
-    writeln!(f, "  Max DD:    ${:.2} ({:.1}%)", report.max_drawdown, report.max_drawdown_pct)?;
-    writeln!(f, "  Sharpe:    {:.2}", report.sharpe_ratio)?;
-    writeln!(f)?;
-    writeln!(f, "Signals:")?;
-    writeln!(f, "  Generated: {}", report.total_signals)?;
-    writeln!(f, "  Filtered:  {}", report.signals_filtered)?;
-    writeln!(f)?;
-    writeln!(f, "By Scale:")?;
-    for (scale, stats) in &report.scale_stats {
-        writeln!(
-            f,
-            "  {}: {} trades | WR {:.0}% | PnL ${:+.2}",
-            scale, stats.trades, stats.win_rate, stats.total_pnl
-        )?;
+    if let Some(b) = variant {
+        return commands::ab_test(exchange, cfg, b, bt_start, bt_end, step_minutes).await;
     }
-    writeln!(f)?;
-    writeln!(f, "By Session:")?;
-    for (session, stats) in &report.session_stats {
-        writeln!(
-            f,
-            "  {}: {} trades | WR {:.0}% | PnL ${:+.2}",
-            session, stats.trades, stats.win_rate, stats.total_pnl
-        )?;
+
+    if compare_mode {
+        let mut results = Vec::new();
+        for name in strategy::STRATEGY_NAMES {
+            let Some(strat) = strategy::from_name(name, &cfg) else {
+                continue;
+            };
+            let mut runner = BacktestRunner::new(exchange.clone(), cfg.clone()).with_strategy(strat);
+            let report = runner.run(bt_start, bt_end, step_minutes).await?;
+            println!("\n  STRATEGY: {}", name);
+            report.print_summary();
+            results.push((name, report));
+        }
+
+        println!("\n{}", "=".repeat(80));
+        println!("  STRATEGY COMPARISON");
+        println!("{}", "=".repeat(80));
+        println!(
+            "  {:<20} {:>11} {:>7} {:>6} {:>6} {:>7} {:>7}",
+            "Strategy", "PnL", "Trades", "WR%", "PF", "Sharpe", "MaxDD%"
+        );
+        for (name, r) in &results {
+            println!(
+                "  {:<20} {:>+11.2} {:>7} {:>6.1} {:>6.2} {:>7.2} {:>7.1}",
+                name,
+                r.total_pnl,
+                r.total_trades,
+                r.win_rate,
+                r.profit_factor,
+                r.sharpe_ratio,
+                r.max_drawdown_pct,
+            );
+        }
+        println!("{}", "=".repeat(80));
+        return Ok(());
     }
 
+    commands::backtest(exchange, cfg, bt_start, bt_end, step_minutes).await?;
     Ok(())
 }
@@ -0,0 +1,77 @@
+use chrono::{DateTime, Utc};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+/// Where the bot loop reads the time from.
+#[derive(Debug, Clone, Default)]
+pub enum Clock {
+    #[default]
+    System,
+    /// Stepped by hand (see [`super::BotHarness`])
+    Sim(SimClock),
+}
+
+impl Clock {
+    pub fn now(&self) -> DateTime<Utc> {
+        match self {
+            Clock::System => Utc::now(),
+            Clock::Sim(sim) => sim.now(),
+        }
+    }
+
+    /// Monotonic time for the scheduler.
+    pub fn instant(&self) -> Instant {
+        match self {
+            Clock::System => Instant::now(),
+            Clock::Sim(sim) => sim.instant(),
+        }
+    }
+
+    pub fn is_sim(&self) -> bool {
+        matches!(self, Clock::Sim(_))
+    }
+}
+
+#[derive(Debug)]
+struct SimTime {
+    start: DateTime<Utc>,
+    now: DateTime<Utc>,
+    /// Instant standing in for `start`
+    base: Instant,
+}
+
+/// A wall clock that only moves when told to. Clones share the same time,
+/// so the bot and its scripted markets stay in step.
+#[derive(Debug, Clone)]
+pub struct SimClock {
+    time: Arc<Mutex<SimTime>>,
+}
+
+impl SimClock {
+    pub fn new(start: DateTime<Utc>) -> Self {
+        Self {
+            time: Arc::new(Mutex::new(SimTime {
+                start,
+                now: start,
+                base: Instant::now(),
+            })),
+        }
+    }
+
+    pub fn now(&self) -> DateTime<Utc> {
+        self.time.lock().unwrap().now
+    }
+
+    /// `base` plus the simulated time elapsed since the start.
+    pub fn instant(&self) -> Instant {
+        let t = self.time.lock().unwrap();
+        t.base + (t.now - t.start).to_std().unwrap_or_default()
+    }
+
+    /// Move forward by `by`; negative durations are ignored.
+    pub fn advance(&self, by: chrono::Duration) {
+        if by > chrono::Duration::zero() {
+            self.time.lock().unwrap().now += by;
+        }
+    }
+}
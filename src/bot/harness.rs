@@ -0,0 +1,162 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+
+use super::{Clock, IctBot, SimClock};
+use crate::config::{Config, SharedConfig};
+use crate::exchange::{Exchange, HistoricalExchange};
+use crate::models::{CandleSeries, FundingRate, Timeframe};
+use crate::strategies::signals::TradeSignal;
+use crate::trading::paper_trader::PaperTrader;
+
+/// Scripted candles replayed up to the harness clock: each call sees only
+/// what had printed by then.
+pub struct ScriptedExchange {
+    market: HistoricalExchange,
+    clock: SimClock,
+}
+
+impl ScriptedExchange {
+    pub fn new(market: HistoricalExchange, clock: SimClock) -> Self {
+        Self { market, clock }
+    }
+
+    fn sync(&mut self) -> &mut HistoricalExchange {
+        self.market.set_time(self.clock.now());
+        &mut self.market
+    }
+}
+
+#[async_trait]
+impl Exchange for ScriptedExchange {
+    async fn fetch_ohlcv(&mut self, tf: Timeframe, limit: usize) -> Result<CandleSeries> {
+        self.sync().fetch_ohlcv(tf, limit).await
+    }
+
+    async fn get_current_price(&mut self) -> Result<f64> {
+        self.sync().get_current_price().await
+    }
+
+    async fn get_4h(&mut self, limit: usize) -> Result<CandleSeries> {
+        self.sync().get_4h(limit).await
+    }
+
+    async fn get_midnight_open(&mut self) -> Result<Option<f64>> {
+        self.sync().get_midnight_open().await
+    }
+
+    async fn get_funding_rate(&mut self) -> Result<Option<FundingRate>> {
+        self.sync().get_funding_rate().await
+    }
+}
+
+/// The real [`IctBot`] on a simulated clock, ticked deterministically for
+/// end-to-end tests.
+///
+/// Always paper trading with the status API off. The bot loads and saves
+/// its state under `cfg.log_dir`, so give each test its own directory.
+pub struct BotHarness {
+    bot: IctBot,
+    clock: SimClock,
+}
+
+impl BotHarness {
+    /// One scripted market per symbol; the clock starts at `start`.
+    pub async fn new(
+        mut cfg: Config,
+        markets: Vec<(String, HistoricalExchange)>,
+        start: DateTime<Utc>,
+    ) -> Self {
+        cfg.paper_trade = true;
+        cfg.api_bind.clear();
+        let clock = SimClock::new(start);
+        let markets = markets
+            .into_iter()
+            .map(|(symbol, market)| {
+                let scripted: Box<dyn Exchange> =
+                    Box::new(ScriptedExchange::new(market, clock.clone()));
+                (symbol, scripted)
+            })
+            .collect();
+        let bot = IctBot::with_clock(cfg.shared(), markets, Clock::Sim(clock.clone())).await;
+        Self { bot, clock }
+    }
+
+    pub fn now(&self) -> DateTime<Utc> {
+        self.clock.now()
+    }
+
+    /// Run one loop iteration at the current time.
+    pub async fn tick(&mut self) {
+        self.bot.tick().await;
+    }
+
+    /// Advance by `step` and tick, until `duration` has passed. The live
+    /// loop ticks every second; larger steps trade fidelity for speed.
+    pub async fn run_for(&mut self, duration: Duration, step: Duration) {
+        assert!(step > Duration::zero(), "step must be positive");
+        let end = self.clock.now() + duration;
+        while self.clock.now() < end {
+            self.clock.advance(step);
+            self.bot.tick().await;
+        }
+    }
+
+    /// Open a position on `scale` and give it the scale's slot, as a scan
+    /// that found `signal` would.
+    pub fn open_position(
+        &mut self,
+        symbol: &str,
+        scale: &str,
+        signal: &TradeSignal,
+    ) -> Option<u64> {
+        self.bot.paper_trader.sim_time = Some(self.clock.now());
+        let id = self
+            .bot
+            .paper_trader
+            .open_position_for(symbol, signal, scale, None)?
+            .id;
+        let st = self.bot.symbols.iter_mut().find(|s| s.symbol == symbol)?;
+        st.scale_positions.insert(scale.to_string(), id);
+        Some(id)
+    }
+
+    pub fn config(&self) -> SharedConfig {
+        self.bot.config.clone()
+    }
+
+    pub fn trader(&self) -> &PaperTrader {
+        &self.bot.paper_trader
+    }
+
+    pub fn trader_mut(&mut self) -> &mut PaperTrader {
+        &mut self.bot.paper_trader
+    }
+
+    /// Position holding `scale`'s slot on `symbol`.
+    pub fn scale_position(&self, symbol: &str, scale: &str) -> Option<u64> {
+        self.bot
+            .symbols
+            .iter()
+            .find(|s| s.symbol == symbol)?
+            .scale_positions
+            .get(scale)
+            .copied()
+    }
+
+    /// When `scale` on `symbol` may enter again, if cooling down.
+    pub fn cooldown_until(&self, symbol: &str, scale: &str) -> Option<DateTime<Utc>> {
+        self.bot
+            .symbols
+            .iter()
+            .find(|s| s.symbol == symbol)?
+            .scale_cooldown
+            .get(scale)
+            .copied()
+    }
+
+    /// Graceful shutdown: final checkpoint and shutdown report.
+    pub async fn shutdown(&mut self) {
+        self.bot.shutdown().await;
+    }
+}
@@ -0,0 +1,1678 @@
+pub mod clock;
+pub mod harness;
+
+pub use clock::{Clock, SimClock};
+pub use harness::{BotHarness, ScriptedExchange};
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::time::{Duration, Instant};
+use tracing::{debug, error, info, warn};
+
+use crate::api::{ApiCommand, BotApi, BotStatus, ScaleAlignment, SymbolStatus};
+use crate::config::{Config, SharedConfig};
+use crate::config_history::ConfigHistory;
+use crate::core::calendar::{self, EconCalendar};
+use crate::core::freshness::DataFreshness;
+use crate::core::holidays::Market;
+use crate::core::position_sizing;
+use crate::core::sessions::SessionManager;
+use crate::exchange::{metrics, Exchange};
+use crate::models::instrument::funding_times;
+use crate::models::{CandleSeries, PositionStatus, Timeframe};
+use crate::notifications::{self, DailySummary, Notifier, TradeEvent};
+use crate::strategies::fractal_engine::FractalEngine;
+use crate::strategies::signals::context_tags;
+use crate::strategies::strategy::{self, Strategy, StrategyRegistry};
+use crate::strategies::weekly_profiles::{WeeklyBias, WeeklyProfileClassifier};
+use crate::trading::drawdown_guard::BreakerEvent;
+use crate::trading::journal::{Journal, JournalEvent};
+use crate::trading::kill_switch::KillSwitch;
+use crate::trading::live_trader::LiveTrader;
+use crate::trading::paper_trader::{PaperTrader, Position, StopAdjustReason};
+use crate::trading::risk_regime::RiskRegime;
+use crate::trading::runtime_state::{RuntimeState, SymbolRuntime};
+use crate::trading::scheduler::Scheduler;
+use crate::trading::shutdown_report::{self, ShutdownReport};
+use crate::trading::strategy_refiner::StrategyRefiner;
+use crate::trading::stuck_positions::{tightened_stop, StuckDetector};
+use crate::trading::trade_record::TradeMetadata;
+use crate::trading::trailing::TrailingStops;
+
+const WEEKLY_ANALYSIS_INTERVAL: f64 = 3600.0;
+const POSITION_CHECK_INTERVAL: f64 = 10.0;
+const ALIGNMENT_LOG_INTERVAL: f64 = 300.0;
+const DATA_REFRESH_INTERVAL: f64 = 5.0;
+const CALENDAR_REFRESH_INTERVAL: f64 = 21600.0;
+
+/// Candles refreshed per timeframe unless DATA_LOOKBACK is set
+const DEFAULT_DATA_LOOKBACK: usize = 175;
+/// Scale cooldown after a close unless COOLDOWN_MINUTES is set
+const DEFAULT_COOLDOWN_MINUTES: i64 = 15;
+
+/// Send `event` to every notifier; failures are logged, never fatal.
+async fn notify(notifiers: &[Box<dyn Notifier>], event: TradeEvent) {
+    for n in notifiers {
+        if let Err(e) = n.notify(&event).await {
+            warn!("{} notification failed: {:#}", n.name(), e);
+        }
+    }
+}
+
+/// Signal confidence recorded for a position (0 if unknown).
+fn confidence(trader: &PaperTrader, id: u64) -> f64 {
+    trader
+        .records()
+        .get(&id)
+        .map_or(0.0, |r| r.metadata.confidence)
+}
+
+/// Market data, engines and scale bookkeeping for one traded symbol.
+struct SymbolState {
+    symbol: String,
+    market: Box<dyn Exchange>,
+    weekly_classifier: WeeklyProfileClassifier,
+    fractal: FractalEngine,
+    /// Where the fractal engine sits among the configured strategies, if run
+    fractal_slot: Option<usize>,
+    /// Other configured strategies, in STRATEGIES order
+    strategies: Vec<Box<dyn Strategy>>,
+    weekly_bias: Option<WeeklyBias>,
+
+    scale_positions: HashMap<String, u64>,
+    scale_cooldown: HashMap<String, DateTime<Utc>>,
+    data_cache: HashMap<Timeframe, CandleSeries>,
+    freshness: DataFreshness,
+    /// Last alignment dashboard, for the status API
+    alignment: Vec<ScaleAlignment>,
+    /// Funding settled up to here (perpetuals only)
+    last_funding: DateTime<Utc>,
+    /// Positions already reported as stuck
+    stuck: StuckDetector,
+    /// Per-position trailing stop state
+    trailing: TrailingStops,
+}
+
+impl SymbolState {
+    /// Base asset for size logging (BTC-USD -> BTC)
+    fn base_asset(&self) -> &str {
+        self.symbol.split('-').next().unwrap_or(&self.symbol)
+    }
+}
+
+pub struct IctBot {
+    config: SharedConfig,
+    symbols: Vec<SymbolState>,
+    session: SessionManager,
+    paper_trader: PaperTrader,
+    /// Real order execution when paper_trade is off; the paper trader stays the ledger
+    live: Option<LiveTrader>,
+    refiner: StrategyRefiner,
+    /// Audit trail of config changes (refiner, restarts)
+    config_history: ConfigHistory,
+    kill_switch: KillSwitch,
+    kill_switch_active: bool,
+    /// Trade lifecycle messages (Telegram, Discord)
+    notifiers: Vec<Box<dyn Notifier>>,
+    /// Killzone in progress at the last tick; its end triggers the daily summary
+    killzone: Option<String>,
+    /// HTTP status/control API (when API_BIND is set)
+    api: Option<BotApi>,
+    /// Entries paused via the API
+    paused: bool,
+    /// High-impact releases for news blackouts (ECON_CALENDAR)
+    calendar: EconCalendar,
+    /// Positions whose stop was already pulled in for the current blackout
+    news_tightened: HashSet<u64>,
+    /// Trade lifecycle events as NDJSON (`{log_dir}/events.ndjson`)
+    journal: Journal,
+
+    /// Periodic tasks (`weekly`, `data_refresh`, `positions`, `alignment`,
+    /// `analysis`, `SYMBOL/scale` scans) with per-task offsets and timing
+    scheduler: Scheduler,
+    closed_since_analysis: usize,
+    /// Last runtime state written, to skip unchanged checkpoints
+    last_checkpoint: String,
+    /// System time, or the harness's simulated clock
+    clock: Clock,
+}
+
+impl IctBot {
+    /// One exchange client per traded symbol; positions share a single account.
+    pub async fn new(config: SharedConfig, markets: Vec<(String, Box<dyn Exchange>)>) -> Self {
+        Self::with_clock(config, markets, Clock::System).await
+    }
+
+    /// Same as [`IctBot::new`], reading time from `clock`.
+    pub async fn with_clock(
+        config: SharedConfig,
+        markets: Vec<(String, Box<dyn Exchange>)>,
+        clock: Clock,
+    ) -> Self {
+        let cfg = config.read().await;
+
+        info!("{}", "=".repeat(60));
+        info!("ICT HFT Bot starting up");
+        info!(
+            "Mode: {}",
+            if cfg.paper_trade {
+                "PAPER TRADING"
+            } else {
+                "LIVE TRADING"
+            }
+        );
+        let symbol_names: Vec<&str> = markets.iter().map(|(s, _)| s.as_str()).collect();
+        info!("Symbols: {}", symbol_names.join(", "));
+        info!("Entry scales:");
+        for scale_cfg in cfg.hft_scales.values() {
+            let alignment_tfs: Vec<String> =
+                scale_cfg.alignment_tfs.iter().map(|tf| tf.to_string()).collect();
+            info!(
+                "  {}: entry={} aligned={} scan={}s",
+                scale_cfg.name,
+                scale_cfg.entry_tf,
+                alignment_tfs.join("+"),
+                scale_cfg.scan_interval
+            );
+        }
+        let strategy_names = strategy::configured(&cfg, "fractal");
+        info!("Strategies: {}", strategy_names.join(", "));
+        info!("{}", "=".repeat(60));
+
+        let registry = StrategyRegistry::builtin();
+        let mut symbols: Vec<SymbolState> = markets
+            .into_iter()
+            .map(|(symbol, market)| {
+                let symbol_cfg = cfg.for_symbol(&symbol);
+                // The fractal engine also drives alignment and health checks,
+                // so it lives outside the list and is slotted back in to evaluate
+                let mut strategies = registry.build_all(&strategy_names, &symbol_cfg);
+                let fractal_slot = strategies.iter().position(|s| s.name() == "fractal");
+                if let Some(slot) = fractal_slot {
+                    strategies.remove(slot);
+                }
+                SymbolState {
+                    fractal: FractalEngine::new(&symbol_cfg),
+                    fractal_slot,
+                    strategies,
+                    symbol,
+                    market,
+                    weekly_classifier: WeeklyProfileClassifier::new(),
+                    weekly_bias: None,
+                    scale_positions: HashMap::new(),
+                    scale_cooldown: HashMap::new(),
+                    data_cache: HashMap::new(),
+                    freshness: DataFreshness::new(cfg.data_max_stale_bars),
+                    alignment: Vec::new(),
+                    last_funding: clock.now(),
+                    stuck: StuckDetector::new(),
+                    trailing: TrailingStops::new(&cfg),
+                }
+            })
+            .collect();
+
+        let session = SessionManager::new(&cfg);
+        let mut paper_trader = PaperTrader::new(&cfg);
+
+        // Scale slots and cooldowns from the last run, checked against the ledger
+        let mut runtime = RuntimeState::load(&cfg.log_dir).unwrap_or_default();
+        let v = runtime.validate(&paper_trader, clock.now());
+        if let Some(at) = runtime.breaker_tripped_at {
+            paper_trader.restore_drawdown_trip(at);
+            warn!("Drawdown circuit breaker still tripped (since {})", at);
+        }
+        for st in &mut symbols {
+            if let Some(r) = runtime.symbols.remove(&st.symbol) {
+                st.scale_positions = r.scale_positions.into_iter().collect();
+                st.scale_cooldown = r.scale_cooldown.into_iter().collect();
+                st.weekly_bias = r.weekly_bias;
+            }
+            if !st.scale_positions.is_empty() || !st.scale_cooldown.is_empty() {
+                info!(
+                    "Restored {}: slots {:?}, {} cooldown(s){}",
+                    st.symbol,
+                    st.scale_positions,
+                    st.scale_cooldown.len(),
+                    if st.weekly_bias.is_some() { ", weekly bias" } else { "" }
+                );
+            }
+        }
+        if v.stale_slots + v.restored_slots > 0 {
+            warn!(
+                "Runtime state: dropped {} stale slot(s), assigned {} open position(s) to their scale",
+                v.stale_slots, v.restored_slots
+            );
+        }
+        for st in &symbols {
+            if let Some(peer) = cfg.smt_peer(&st.symbol) {
+                if symbols.iter().any(|s| s.symbol == peer) {
+                    info!("SMT: {} vs {}", st.symbol, peer);
+                } else {
+                    warn!("SMT peer {} of {} is not traded — SMT off for it", peer, st.symbol);
+                }
+            }
+        }
+        let live = (!cfg.paper_trade).then(|| LiveTrader::coinbase(&cfg));
+        let refiner = StrategyRefiner::new(&cfg);
+        let kill_switch = KillSwitch::new(&cfg);
+        let config_history = ConfigHistory::new(&cfg);
+        config_history.record_startup(&cfg);
+        let notifiers = notifications::from_config(&cfg);
+        for n in &notifiers {
+            info!("Notifications: {}", n.name());
+        }
+
+        let api = if cfg.api_bind.is_empty() {
+            None
+        } else {
+            match BotApi::serve(&cfg.api_bind, &cfg.api_token, kill_switch.clone()).await {
+                Ok(api) => {
+                    info!("API listening on {}", api.addr());
+                    if cfg.api_token.is_empty() {
+                        warn!("API_TOKEN not set — the API accepts unauthenticated requests");
+                    }
+                    Some(api)
+                }
+                Err(e) => {
+                    error!("API disabled: {:#}", e);
+                    None
+                }
+            }
+        };
+
+        let calendar = calendar::load(&cfg).await.unwrap_or_default();
+        let journal = Journal::new(&cfg);
+
+        let now = clock.instant();
+        let mut scheduler = Scheduler::new(cfg.schedule_jitter_secs);
+        scheduler.schedule("weekly", Duration::from_secs_f64(WEEKLY_ANALYSIS_INTERVAL), now);
+        scheduler.schedule("data_refresh", Duration::from_secs_f64(DATA_REFRESH_INTERVAL), now);
+        scheduler.schedule("positions", Duration::from_secs_f64(POSITION_CHECK_INTERVAL), now);
+        scheduler.schedule("alignment", Duration::from_secs_f64(ALIGNMENT_LOG_INTERVAL), now);
+        scheduler.schedule("analysis", Duration::from_secs(cfg.analysis_interval), now);
+        scheduler.schedule(
+            "calendar",
+            Duration::from_secs_f64(CALENDAR_REFRESH_INTERVAL),
+            now,
+        );
+        for st in &symbols {
+            for (key, scale_cfg) in &cfg.hft_scales {
+                scheduler.schedule(
+                    &format!("{}/{}", st.symbol, key),
+                    Duration::from_secs(scale_cfg.scan_interval),
+                    now,
+                );
+            }
+        }
+
+        drop(cfg);
+
+        Self {
+            config,
+            symbols,
+            session,
+            paper_trader,
+            live,
+            refiner,
+            config_history,
+            kill_switch,
+            kill_switch_active: false,
+            notifiers,
+            killzone: None,
+            api,
+            paused: false,
+            calendar,
+            news_tightened: HashSet::new(),
+            journal,
+            scheduler,
+            closed_since_analysis: 0,
+            last_checkpoint: String::new(),
+            clock,
+        }
+    }
+
+    pub async fn run(&mut self) -> Result<()> {
+        info!("Bot is now running. Press Ctrl+C to stop.");
+        self.print_status().await;
+
+        loop {
+            tokio::select! {
+                _ = tokio::signal::ctrl_c() => {
+                    self.shutdown().await;
+                    return Ok(());
+                }
+                _ = async {
+                    self.tick().await;
+                    tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
+                } => {}
+            }
+        }
+    }
+
+    /// One pass of the loop: run whatever tasks are due at the clock's time.
+    pub async fn tick(&mut self) {
+        let cfg = self.config.read().await.clone();
+        let now = self.clock.now();
+        if self.clock.is_sim() {
+            self.paper_trader.sim_time = Some(now);
+            self.refiner.sim_time = Some(now);
+        }
+        self.session.update(&cfg, Some(now));
+
+        let killzone = self
+            .session
+            .is_killzone()
+            .then(|| self.session.current_session.clone());
+        if let Some(ended) = self.killzone.take().filter(|s| Some(s) != killzone.as_ref()) {
+            notify(&self.notifiers, TradeEvent::DailySummary(self.daily_summary(&ended))).await;
+        }
+        self.killzone = killzone;
+
+        // Kill switch: stop new entries (and optionally flatten) while engaged
+        let engaged = self.kill_switch.is_engaged();
+        if engaged != self.kill_switch_active {
+            if engaged {
+                warn!(
+                    "KILL SWITCH ENGAGED ({}) — no new entries{}",
+                    self.kill_switch.file().display(),
+                    if self.kill_switch.flatten { ", flattening" } else { "" }
+                );
+                if self.kill_switch.flatten {
+                    self.flatten_all().await;
+                } else {
+                    // Resting limits would otherwise still fill
+                    let cancelled = self.paper_trader.cancel_limits(None);
+                    if !cancelled.is_empty() {
+                        warn!("Cancelled {} resting limit entries", cancelled.len());
+                    }
+                    for pos in &cancelled {
+                        for st in &mut self.symbols {
+                            st.scale_positions.retain(|_, id| *id != pos.id);
+                        }
+                    }
+                }
+            } else {
+                info!("Kill switch released — resuming entries");
+            }
+            self.kill_switch_active = engaged;
+        }
+
+        self.handle_api().await;
+
+        // Weekly profile
+        if self.scheduler.poll("weekly", self.clock.instant()) {
+            let started = Instant::now();
+            for idx in 0..self.symbols.len() {
+                self.analyze_weekly(idx, &cfg);
+            }
+            self.scheduler.finished("weekly", started.elapsed());
+        }
+
+        // Economic calendar (keeps the last good copy on failure)
+        if self.scheduler.poll("calendar", self.clock.instant()) {
+            if let Some(calendar) = calendar::load(&cfg).await {
+                self.calendar = calendar;
+            }
+        }
+
+        // Refresh market data
+        if self.scheduler.poll("data_refresh", self.clock.instant()) {
+            let started = Instant::now();
+            for idx in 0..self.symbols.len() {
+                self.refresh_data(idx).await;
+            }
+            self.share_smt_peers(&cfg);
+            self.scheduler.finished("data_refresh", started.elapsed());
+        }
+
+        // Check positions
+        if self.scheduler.poll("positions", self.clock.instant()) {
+            let started = Instant::now();
+            for idx in 0..self.symbols.len() {
+                self.check_positions(idx, &cfg).await;
+            }
+            self.scheduler.finished("positions", started.elapsed());
+            self.check_drawdown(&cfg).await;
+            // Latest state for the crash report
+            shutdown_report::record(&cfg.log_dir, self.shutdown_report(&cfg, "panic"));
+            self.checkpoint(&cfg);
+        }
+
+        // Alignment dashboard
+        if self.scheduler.poll("alignment", self.clock.instant()) {
+            for idx in 0..self.symbols.len() {
+                self.log_alignment(idx, &cfg);
+            }
+        }
+
+        // Scan each entry scale at its own interval, per symbol
+        let scale_keys: Vec<String> = if self.kill_switch_active || self.paused {
+            Vec::new()
+        } else {
+            cfg.hft_scales.keys().cloned().collect()
+        };
+        for idx in 0..self.symbols.len() {
+            for scale_key in &scale_keys {
+                let task = format!("{}/{}", self.symbols[idx].symbol, scale_key);
+                let interval = Duration::from_secs(cfg.hft_scales[scale_key].scan_interval);
+                self.scheduler.schedule(&task, interval, self.clock.instant());
+                if self.scheduler.poll(&task, self.clock.instant()) {
+                    let started = Instant::now();
+                    self.scan_scale(idx, scale_key, &cfg).await;
+                    self.scheduler.finished(&task, started.elapsed());
+                }
+            }
+        }
+
+        // Self-learning analysis
+        if self.scheduler.poll("analysis", self.clock.instant()) || self.closed_since_analysis >= 10 {
+            let started = Instant::now();
+            self.run_analysis().await;
+            self.scheduler.finished("analysis", started.elapsed());
+            self.closed_since_analysis = 0;
+        }
+
+        // Live order problems the operator has to resolve
+        let alerts = self.live.as_mut().map(LiveTrader::take_alerts);
+        for message in alerts.unwrap_or_default() {
+            notify(&self.notifiers, TradeEvent::Alert(message)).await;
+        }
+
+        self.publish_status(&cfg);
+    }
+
+    async fn refresh_data(&mut self, idx: usize) {
+        let cfg = self.config.read().await;
+        let lookback = cfg.data_lookback.unwrap_or(DEFAULT_DATA_LOOKBACK);
+        let gap_fill = cfg.gap_fill;
+        let mut timeframes = vec![
+            (Timeframe::M1, lookback),
+            (Timeframe::M5, lookback),
+            (Timeframe::M15, lookback),
+            (Timeframe::H1, lookback),
+            (Timeframe::D1, 14),
+        ];
+        // 4H comes from get_4h below
+        for tf in cfg.scale_timeframes() {
+            if tf != Timeframe::H4 && !timeframes.iter().any(|(t, _)| *t == tf) {
+                timeframes.push((tf, lookback));
+            }
+        }
+        drop(cfg);
+
+        let st = &mut self.symbols[idx];
+        for (tf, limit) in timeframes {
+            match st.market.fetch_ohlcv(tf, limit).await {
+                Ok(data) => {
+                    let (data, diag) = data.repair(tf, gap_fill);
+                    if !diag.is_clean() {
+                        debug!("{} {} data: {}", st.symbol, tf, diag.summary());
+                    }
+                    st.freshness.record(tf, &data, self.clock.now());
+                    st.data_cache.insert(tf, data);
+                }
+                Err(e) => {
+                    st.freshness.record_failure(tf);
+                    debug!("Data refresh {} {}: {}", st.symbol, tf, e);
+                }
+            }
+        }
+
+        // 4H by resampling
+        match st.market.get_4h(200).await {
+            Ok(data) => {
+                st.freshness.record(Timeframe::H4, &data, self.clock.now());
+                st.data_cache.insert(Timeframe::H4, data);
+            }
+            Err(e) => {
+                st.freshness.record_failure(Timeframe::H4);
+                debug!("Data refresh {} 4h: {}", st.symbol, e);
+            }
+        }
+    }
+
+    /// Hand each paired symbol its correlated peer's candles for SMT.
+    fn share_smt_peers(&mut self, cfg: &Config) {
+        for idx in 0..self.symbols.len() {
+            let peer = cfg.smt_peer(&self.symbols[idx].symbol).and_then(|peer| {
+                let data = self.symbols.iter().find(|s| s.symbol == peer)?.data_cache.clone();
+                Some((peer, data))
+            });
+            self.symbols[idx]
+                .fractal
+                .set_smt_peer(peer.as_ref().map(|(symbol, data)| (*symbol, data)));
+        }
+    }
+
+    fn analyze_weekly(&mut self, idx: usize, cfg: &Config) {
+        let st = &mut self.symbols[idx];
+        info!("--- Weekly Profile Analysis ({}) ---", st.symbol);
+        let daily = match st.data_cache.get(&Timeframe::D1) {
+            Some(d) => d,
+            None => return,
+        };
+        let htf = match st.data_cache.get(&Timeframe::H1) {
+            Some(d) => d,
+            None => return,
+        };
+
+        let day = self.session.get_day_of_week();
+        let bias = st.weekly_classifier.classify(daily, htf, &day, cfg);
+
+        info!(
+            "Profile: {} | Direction: {} | Confidence: {:.1}%",
+            bias.profile,
+            bias.direction,
+            bias.confidence * 100.0
+        );
+        if bias.tgif_active {
+            info!("TGIF ACTIVE");
+        }
+
+        st.weekly_bias = Some(bias);
+    }
+
+    fn log_alignment(&mut self, idx: usize, cfg: &Config) {
+        let st = &mut self.symbols[idx];
+        if st.data_cache.is_empty() {
+            return;
+        }
+
+        let summary = st.fractal.get_alignment_summary(&st.data_cache, cfg);
+
+        info!("--- Alignment Dashboard ({}) ---", st.symbol);
+        st.alignment = summary
+            .values()
+            .map(|state| ScaleAlignment {
+                scale: state.name.clone(),
+                aligned: state.aligned,
+                direction: state.direction.clone(),
+                trends: state
+                    .details
+                    .iter()
+                    .map(|d| (d.tf.clone(), d.trend.clone()))
+                    .collect(),
+            })
+            .collect();
+        st.alignment.sort_by(|a, b| a.scale.cmp(&b.scale));
+        for state in summary.values() {
+            let status = if state.aligned {
+                "ALIGNED"
+            } else {
+                "NOT ALIGNED"
+            };
+            let details: Vec<String> = state
+                .details
+                .iter()
+                .map(|d| format!("{}:{}", d.tf, d.trend))
+                .collect();
+            info!(
+                "  {}: {} ({}) [{}]",
+                state.name,
+                status,
+                state.direction,
+                details.join(" | ")
+            );
+        }
+    }
+
+    async fn scan_scale(&mut self, idx: usize, scale_key: &str, cfg: &Config) {
+        let st = &mut self.symbols[idx];
+        let weekly_bias = match &st.weekly_bias {
+            Some(b) => b,
+            None => return,
+        };
+
+        let day = self.session.get_day_of_week();
+        if day == "Monday" {
+            return;
+        }
+
+        // Only trade during killzones (London, NY Forex, NY Indices)
+        if !self.session.is_killzone() {
+            return;
+        }
+
+        // No entries around high-impact news
+        let (before, after) = (cfg.news_blackout_before, cfg.news_blackout_after);
+        if let Some(event) = self.calendar.blackout(self.clock.now(), before, after) {
+            debug!(
+                "{} {} skipped: {} blackout",
+                st.symbol, scale_key, event.name
+            );
+            return;
+        }
+
+        let profile_str = weekly_bias.profile.to_string();
+        if !self.session.should_trade_today(cfg, &profile_str) {
+            return;
+        }
+
+        if st.scale_positions.contains_key(scale_key) {
+            return;
+        }
+
+        // Cooldown after position close to prevent churning
+        if let Some(&cooldown_until) = st.scale_cooldown.get(scale_key) {
+            if self.clock.now() < cooldown_until {
+                return;
+            }
+            st.scale_cooldown.remove(scale_key);
+        }
+
+        self.paper_trader
+            .set_regime(RiskRegime::classify(weekly_bias, cfg), cfg);
+        if !self.paper_trader.can_open_position(cfg) {
+            return;
+        }
+
+        if st.data_cache.is_empty() {
+            return;
+        }
+
+        // Degraded mode: skip scales whose required data is missing or stale
+        let required = match st.fractal.scales.get(scale_key) {
+            Some(s) => s.required_timeframes(),
+            None => return,
+        };
+        let problems = st.freshness.check(&required, self.clock.now());
+        if st.freshness.set_degraded(scale_key, !problems.is_empty()) {
+            if problems.is_empty() {
+                info!("{} scale {} data recovered — resuming", st.symbol, scale_key);
+            } else {
+                let details: Vec<String> =
+                    problems.iter().map(|(tf, s)| format!("{}: {}", tf, s)).collect();
+                warn!(
+                    "{} scale {} DEGRADED — skipping [{}]",
+                    st.symbol,
+                    scale_key,
+                    details.join(" | ")
+                );
+            }
+        }
+        if !problems.is_empty() {
+            return;
+        }
+
+        if self.refiner.should_skip(scale_key, &self.session.current_session) {
+            return;
+        }
+
+        let midnight_open = st.market.get_midnight_open().await.ok().flatten();
+
+        // Evaluate this scale with each configured strategy in turn (the
+        // fractal engine adds cross-scale confluence)
+        let mut ordered: Vec<&mut dyn Strategy> = st
+            .strategies
+            .iter_mut()
+            .map(|s| s.as_mut() as &mut dyn Strategy)
+            .collect();
+        if let Some(slot) = st.fractal_slot {
+            ordered.insert(slot, &mut st.fractal);
+        }
+        let data = &st.data_cache;
+        let found =
+            strategy::first_signal(ordered, scale_key, data, midnight_open, &self.session, cfg);
+        let Some((strategy_name, signal)) = found else {
+            return;
+        };
+
+        let min_conf = cfg.min_confidence(scale_key, &signal.session);
+        if signal.confidence < min_conf {
+            return;
+        }
+
+        // Minimum TP distance filter: ensure expected profit > round-trip fees
+        let tp_dist_pct = (signal.take_profit - signal.entry_price).abs() / signal.entry_price;
+        let round_trip_fee = (cfg.fee_rate + cfg.slippage_rate) * 2.0;
+        if tp_dist_pct < round_trip_fee * cfg.min_tp_multiple {
+            debug!(
+                "Skipping {} signal: TP dist {:.4}% < min {:.4}%",
+                scale_key,
+                tp_dist_pct * 100.0,
+                round_trip_fee * cfg.min_tp_multiple * 100.0
+            );
+            return;
+        }
+
+        // Log the signal
+        info!("{}", "=".repeat(60));
+        info!("HFT SIGNAL — {} {}", st.symbol, signal.scale_name);
+        info!("  Strategy: {}", strategy_name);
+        info!("  Direction: {}", signal.direction);
+        info!("  Entry: ${:.2}", signal.entry_price);
+        info!("  Stop Loss: ${:.2} [{}]", signal.stop_loss, signal.stop_mode);
+        info!("  {}", signal.stop_reason);
+        info!("  Take Profit: ${:.2} [{}]", signal.take_profit, signal.tp_label);
+        for lvl in &signal.tp_levels {
+            let flag = if lvl.pda_confluence { " *PDA*" } else { "" };
+            info!("    {}: ${:.2}{}", lvl.label, lvl.price, flag);
+        }
+        info!("  Confidence: {:.1}%", signal.confidence * 100.0);
+        info!(
+            "  CISD: {}",
+            if signal.cisd_confirmed {
+                "Confirmed"
+            } else {
+                "No"
+            }
+        );
+        info!("  Cross-Scale: {} scale(s)", signal.cross_scale_confluence);
+        if !signal.alignment.is_empty() {
+            let align_str: Vec<String> = signal
+                .alignment
+                .iter()
+                .map(|a| format!("{}:{}", a.tf, a.trend))
+                .collect();
+            info!("  Alignment: {}", align_str.join(" | "));
+        }
+        info!("  {}", signal.reason);
+
+        // Build metadata
+        let pda = &signal.pda_engaged;
+        let tags = context_tags(&self.session, weekly_bias, &self.calendar, self.clock.now());
+        let metadata = TradeMetadata {
+            scale: scale_key.to_string(),
+            direction: signal.direction,
+            confidence: signal.confidence,
+            session: signal.session.clone(),
+            session_weight: signal.session_weight,
+            cisd_confirmed: signal.cisd_confirmed,
+            pda_type: pda.pda_type.to_string(),
+            pda_direction: Some(pda.direction),
+            pda_zone: Some(pda.zone),
+            pda_strength: pda.strength,
+            stop_mode: signal.stop_mode.clone(),
+            tp_label: signal.tp_label.clone(),
+            tp_levels: signal.tp_levels.clone(),
+            cross_scale_confluence: signal.cross_scale_confluence,
+            alignment: signal.alignment.clone(),
+            weekly_profile: weekly_bias.profile.to_string(),
+            weekly_direction: Some(weekly_bias.direction),
+            weekly_confidence: weekly_bias.confidence,
+            day_of_week: day,
+            market_regime: Some(self.session.market_regime),
+            kelly_fraction: 0.0,
+            risk_regime: String::new(),
+            sizing_mode: String::new(),
+            tags: tags.clone(),
+            strategy: strategy_name.to_string(),
+            extra: Default::default(),
+        };
+
+        let mut trade_signal = signal.to_trade_signal();
+        trade_signal.tags = tags;
+        self.journal.record(
+            self.clock.now(),
+            JournalEvent::signal(&st.symbol, scale_key, &trade_signal),
+        );
+        notify(
+            &self.notifiers,
+            TradeEvent::Signal {
+                symbol: st.symbol.clone(),
+                scale: scale_key.to_string(),
+                signal: trade_signal.clone(),
+                metadata: Box::new(metadata.clone()),
+            },
+        )
+        .await;
+        trade_signal.size_multiplier = self
+            .refiner
+            .size_multiplier(scale_key, &self.session.current_session)
+            * cfg.strategy_risk(strategy_name);
+        trade_signal.atr_percentile = cfg
+            .hft_scales
+            .get(scale_key)
+            .and_then(|s| st.data_cache.get(&s.entry_tf))
+            .and_then(|c| position_sizing::atr_percentile(c, position_sizing::ATR_PERIOD));
+        if trade_signal.size_multiplier < 1.0 {
+            info!(
+                "  Retrying skipped combo {}_{} at {:.0}% size",
+                scale_key,
+                self.session.current_session,
+                trade_signal.size_multiplier * 100.0
+            );
+        }
+        // Paper entries wait at the OTE limit; live enters at market
+        if let Some(limit) = signal
+            .limit_entry
+            .filter(|_| cfg.ote_entries && self.live.is_none())
+        {
+            trade_signal.entry_price = limit;
+            let expires = self.clock.now() + chrono::Duration::minutes(cfg.ote_expiry_minutes);
+            info!(
+                "  OTE limit @ ${:.2} working until {}",
+                limit,
+                expires.format("%H:%M")
+            );
+            if let Some(pos) = self.paper_trader.place_limit_for(
+                &st.symbol,
+                &trade_signal,
+                scale_key,
+                Some(metadata),
+                expires,
+            ) {
+                st.scale_positions.insert(scale_key.to_string(), pos.id);
+            }
+            info!("{}", "=".repeat(60));
+            return;
+        }
+        let opened = match self.live.as_mut() {
+            Some(live) => live
+                .open_position(
+                    &mut self.paper_trader,
+                    &st.symbol,
+                    &trade_signal,
+                    scale_key,
+                    Some(metadata),
+                )
+                .await
+                .unwrap_or_else(|e| {
+                    error!("Live entry failed: {:#}", e);
+                    None
+                }),
+            None => self
+                .paper_trader
+                .open_position_for(&st.symbol, &trade_signal, scale_key, Some(metadata))
+                .cloned(),
+        };
+        if let Some(pos) = opened {
+            let pos_id = pos.id;
+            let size_usd = pos.size_usd;
+            let size_btc = pos.size_btc;
+            st.scale_positions.insert(scale_key.to_string(), pos_id);
+            let event = JournalEvent::entry(&pos);
+            self.journal.record(self.clock.now(), event);
+
+            info!(
+                "  Position #{} opened: ${:.2} ({:.6} {})",
+                pos_id,
+                size_usd,
+                size_btc,
+                st.base_asset()
+            );
+
+            #[cfg(feature = "charts")]
+            Self::render_chart(&self.paper_trader, st, pos_id, scale_key, &signal.pda_engaged, cfg);
+
+            if let Some(kr) = self.paper_trader.last_kelly_result() {
+                let default_str = if kr.using_default {
+                    "default"
+                } else {
+                    "calculated"
+                };
+                info!(
+                    "  Kelly: {:.4} ({}) | Edge: {:+.4} | Sample: {}",
+                    kr.applied_fraction, default_str, kr.edge, kr.sample_size
+                );
+            }
+
+            let event = TradeEvent::Opened {
+                position: pos,
+                confidence: signal.confidence,
+            };
+            notify(&self.notifiers, event).await;
+        }
+        info!("{}", "=".repeat(60));
+    }
+
+    async fn check_positions(&mut self, idx: usize, cfg: &Config) {
+        let st = &mut self.symbols[idx];
+        let has_open = self
+            .paper_trader
+            .open_positions_for(&st.symbol)
+            .next()
+            .is_some();
+
+        let now = self.clock.now();
+        let funding_due = if cfg.instrument(&st.symbol).pays_funding() {
+            funding_times(st.last_funding, now, cfg.funding_interval_hours).len()
+        } else {
+            0
+        };
+        // Funding is owed only on open positions; otherwise the clock
+        // advances once every due payment has been applied
+        if funding_due == 0 || !has_open {
+            st.last_funding = now;
+        }
+
+        let has_limits = self
+            .paper_trader
+            .pending_positions()
+            .any(|p| p.symbol == st.symbol);
+        if !has_open && !has_limits {
+            return;
+        }
+
+        let current_price = match st.market.get_current_price().await {
+            Ok(p) => p,
+            Err(e) => {
+                error!("Position check error ({}): {}", st.symbol, e);
+                return;
+            }
+        };
+
+        if funding_due > 0 {
+            match st.market.get_funding_rate().await {
+                Ok(Some(funding)) => {
+                    for _ in 0..funding_due {
+                        let paid = self
+                            .paper_trader
+                            .apply_funding(&st.symbol, funding.rate, current_price);
+                        info!(
+                            "Funding {} at {:.4}%: ${:+.4} paid",
+                            st.symbol,
+                            funding.rate * 100.0,
+                            paid
+                        );
+                    }
+                    st.last_funding = now;
+                }
+                Ok(None) => st.last_funding = now,
+                // Still due: retried on the next check
+                Err(e) => warn!("Funding rate fetch failed ({}): {:#}", st.symbol, e),
+            }
+        }
+
+        // Trail each position on its own state, by id
+        let updates = st.trailing.check(
+            &self.paper_trader,
+            &st.symbol,
+            current_price,
+            &st.data_cache,
+            cfg.precision(&st.symbol),
+        );
+        for update in updates {
+            let id = update.position_id;
+            let moved = match self.live.as_mut() {
+                Some(live) => live
+                    .update_stop(
+                        &mut self.paper_trader,
+                        &st.symbol,
+                        id,
+                        update.price,
+                        StopAdjustReason::Trail,
+                    )
+                    .await
+                    .unwrap_or_else(|e| {
+                        error!("Live stop update #{} failed: {:#}", id, e);
+                        None
+                    }),
+                None => self
+                    .paper_trader
+                    .update_stop(id, update.price, StopAdjustReason::Trail),
+            };
+            if let Some(old_sl) = moved {
+                info!(
+                    "Position #{} TRAIL: ${:.2} -> ${:.2} ({})",
+                    id, old_sl, update.price, update.reason
+                );
+                let event = JournalEvent::trail(id, old_sl, update.price, StopAdjustReason::Trail);
+                self.journal.record(now, event);
+            }
+        }
+
+        // Pull stops in once per position ahead of high-impact news
+        let (before, after) = (cfg.news_blackout_before, cfg.news_blackout_after);
+        match self.calendar.blackout(now, before, after) {
+            Some(event) if cfg.news_tighten_stops => {
+                let event = event.name.clone();
+                let positions: Vec<Position> = self
+                    .paper_trader
+                    .open_positions_for(&st.symbol)
+                    .filter(|p| !self.news_tightened.contains(&p.id))
+                    .cloned()
+                    .collect();
+                for pos in positions {
+                    self.news_tightened.insert(pos.id);
+                    let Some(stop) = tightened_stop(&pos, current_price) else {
+                        continue;
+                    };
+                    let moved = match self.live.as_mut() {
+                        Some(live) => live
+                            .update_stop(
+                                &mut self.paper_trader,
+                                &st.symbol,
+                                pos.id,
+                                stop,
+                                StopAdjustReason::News,
+                            )
+                            .await
+                            .unwrap_or_else(|e| {
+                                error!("Live stop update #{} failed: {:#}", pos.id, e);
+                                None
+                            }),
+                        None => self
+                            .paper_trader
+                            .update_stop(pos.id, stop, StopAdjustReason::News),
+                    };
+                    if let Some(old_sl) = moved {
+                        info!(
+                            "Position #{} NEWS ({}): stop ${:.2} -> ${:.2}",
+                            pos.id, event, old_sl, stop
+                        );
+                        let event =
+                            JournalEvent::trail(pos.id, old_sl, stop, StopAdjustReason::News);
+                        self.journal.record(now, event);
+                    }
+                }
+            }
+            Some(_) => {}
+            None => self.news_tightened.clear(),
+        }
+
+        // Positions going nowhere long past their scale's typical hold
+        for stuck in st
+            .stuck
+            .check(&self.paper_trader, cfg, &st.symbol, current_price, now)
+        {
+            let id = stuck.position.id;
+            let new_stop = match tightened_stop(&stuck.position, current_price) {
+                Some(stop) if cfg.stuck_tighten_stop => {
+                    let moved = match self.live.as_mut() {
+                        Some(live) => live
+                            .update_stop(
+                                &mut self.paper_trader,
+                                &st.symbol,
+                                id,
+                                stop,
+                                StopAdjustReason::Stuck,
+                            )
+                            .await
+                            .unwrap_or_else(|e| {
+                                error!("Live stop update #{} failed: {:#}", id, e);
+                                None
+                            }),
+                        None => self
+                            .paper_trader
+                            .update_stop(id, stop, StopAdjustReason::Stuck),
+                    };
+                    if let Some(old_sl) = moved {
+                        let event = JournalEvent::trail(id, old_sl, stop, StopAdjustReason::Stuck);
+                        self.journal.record(now, event);
+                    }
+                    moved.map(|_| stop)
+                }
+                _ => None,
+            };
+            warn!(
+                "Position #{} STUCK: held {:.0}m (typical {:.0}m), progress {:+.0}%{}",
+                id,
+                stuck.held_minutes,
+                stuck.typical_minutes,
+                stuck.progress * 100.0,
+                new_stop.map_or_else(String::new, |s| format!(", stop -> ${:.2}", s))
+            );
+            notify(&self.notifiers, TradeEvent::Stuck { stuck, new_stop }).await;
+        }
+
+        // Log partial exits
+        for (id, pe) in self.paper_trader.take_unlogged_partials() {
+            info!(
+                "Position #{} PARTIAL TP ({} SD): {:.6} {} @ ${:.2} PnL ${:+.2}",
+                id,
+                pe.level,
+                pe.size_btc,
+                st.base_asset(),
+                pe.price,
+                pe.pnl
+            );
+            self.journal.record(now, JournalEvent::partial(id, &pe));
+            if let Some(position) = self.paper_trader.position(id).cloned() {
+                let event = TradeEvent::PartialTp {
+                    position,
+                    exit: pe,
+                    confidence: confidence(&self.paper_trader, id),
+                };
+                notify(&self.notifiers, event).await;
+            }
+        }
+
+        let closed = match self.live.as_mut() {
+            Some(live) => live
+                .check_positions(&mut self.paper_trader, &st.symbol, current_price)
+                .await
+                .unwrap_or_else(|e| {
+                    error!("Live position sync failed ({}): {:#}", st.symbol, e);
+                    Vec::new()
+                }),
+            None => self
+                .paper_trader
+                .check_symbol_positions(&st.symbol, current_price),
+        };
+        self.closed_since_analysis += closed.len();
+
+        // Limits price traded through, and those that expired unfilled
+        for pos in self.paper_trader.take_limit_updates() {
+            if pos.status == PositionStatus::Cancelled {
+                info!(
+                    "{} {} limit #{} @ ${:.2} expired unfilled",
+                    st.symbol, pos.scale, pos.id, pos.entry_price
+                );
+                st.scale_positions.retain(|_, id| *id != pos.id);
+                continue;
+            }
+            info!(
+                "Position #{} opened at limit ${:.2}: ${:.2}",
+                pos.id, pos.signal_price, pos.size_usd
+            );
+            self.journal.record(now, JournalEvent::entry(&pos));
+            let event = TradeEvent::Opened {
+                confidence: confidence(&self.paper_trader, pos.id),
+                position: pos,
+            };
+            notify(&self.notifiers, event).await;
+        }
+
+        for pos in &closed {
+            let result = if pos.pnl > 0.0 { "WIN" } else { "LOSS" };
+            let partials = pos.partial_exits.len();
+            let partial_info = if partials > 0 {
+                format!(" ({} partials)", partials)
+            } else {
+                String::new()
+            };
+            let reason = pos
+                .close_reason
+                .map_or_else(|| "unknown".to_string(), |r| r.to_string());
+            info!(
+                "Position #{} CLOSED ({}, {}){}: PnL ${:+.2} | ${:.2} -> ${:.2}",
+                pos.id,
+                result,
+                reason,
+                partial_info,
+                pos.pnl,
+                pos.entry_price,
+                pos.exit_price.unwrap_or(0.0),
+            );
+            self.journal.record(now, JournalEvent::close(pos));
+
+            // Remove from scale_positions and set cooldown
+            let keys_to_remove: Vec<String> = st
+                .scale_positions
+                .iter()
+                .filter(|(_, &pid)| pid == pos.id)
+                .map(|(k, _)| k.clone())
+                .collect();
+            let cooldown_mins = cfg.cooldown_minutes.unwrap_or(DEFAULT_COOLDOWN_MINUTES);
+            for key in keys_to_remove {
+                st.scale_positions.remove(&key);
+                st.scale_cooldown.insert(
+                    key,
+                    now + chrono::Duration::minutes(cooldown_mins),
+                );
+            }
+
+            let event = TradeEvent::Closed {
+                position: pos.clone(),
+                confidence: confidence(&self.paper_trader, pos.id),
+            };
+            notify(&self.notifiers, event).await;
+        }
+    }
+
+    #[cfg(feature = "charts")]
+    fn render_chart(
+        trader: &PaperTrader,
+        st: &SymbolState,
+        pos_id: u64,
+        scale_key: &str,
+        pda: &crate::core::pd_arrays::Pda,
+        cfg: &Config,
+    ) {
+        use crate::trading::chart;
+
+        let entry_tf = cfg.hft_scales[scale_key].entry_tf;
+        let (Some(candles), Some(pos)) =
+            (st.data_cache.get(&entry_tf), trader.position(pos_id))
+        else {
+            return;
+        };
+        let path = chart::chart_path(&cfg.log_dir, pos_id);
+        match chart::render_position(&path, candles, pos, Some(pda)) {
+            Ok(()) => debug!("Chart saved: {}", path.display()),
+            Err(e) => warn!("Chart render failed for #{}: {}", pos_id, e),
+        }
+    }
+
+    /// Trip or reset the drawdown circuit breaker, flattening on a trip
+    /// when DRAWDOWN_FLATTEN is set.
+    async fn check_drawdown(&mut self, cfg: &Config) {
+        match self.paper_trader.update_drawdown_guard() {
+            Some(BreakerEvent::Tripped { drawdown_pct, peak }) => {
+                warn!(
+                    "CIRCUIT BREAKER TRIPPED — {:.1}% below peak ${:.2}, no new entries for {}h{}",
+                    drawdown_pct,
+                    peak,
+                    cfg.drawdown_cool_off_hours,
+                    if cfg.drawdown_flatten {
+                        ", flattening"
+                    } else {
+                        ""
+                    }
+                );
+                if cfg.drawdown_flatten {
+                    self.flatten_all().await;
+                }
+            }
+            Some(BreakerEvent::Reset { equity }) => {
+                info!(
+                    "Circuit breaker reset — resuming entries, peak now ${:.2}",
+                    equity
+                );
+            }
+            None => {}
+        }
+    }
+
+    async fn flatten_all(&mut self) {
+        for st in &mut self.symbols {
+            let current_price = match st.market.get_current_price().await {
+                Ok(p) => p,
+                Err(e) => {
+                    error!("Flatten failed for {}, no price: {}", st.symbol, e);
+                    continue;
+                }
+            };
+
+            let closed = match self.live.as_mut() {
+                Some(live) => live
+                    .close_all(&mut self.paper_trader, &st.symbol)
+                    .await
+                    .unwrap_or_else(|e| {
+                        error!("Live flatten failed ({}): {:#}", st.symbol, e);
+                        Vec::new()
+                    }),
+                None => self.paper_trader.close_symbol(&st.symbol, current_price),
+            };
+            self.closed_since_analysis += closed.len();
+            for pos in &closed {
+                warn!(
+                    "Position #{} {} FLATTENED: PnL ${:+.2} | ${:.2} -> ${:.2}",
+                    pos.id, st.symbol, pos.pnl, pos.entry_price, current_price
+                );
+                let event = JournalEvent::close(pos);
+                self.journal.record(self.clock.now(), event);
+            }
+            st.scale_positions.clear();
+        }
+    }
+
+    /// Pause/resume transitions and pending API commands.
+    async fn handle_api(&mut self) {
+        let Some(api) = self.api.as_mut() else {
+            return;
+        };
+        let paused = api.is_paused();
+        if paused != self.paused {
+            if paused {
+                warn!("Paused via API — no new entries");
+            } else {
+                info!("Resumed via API");
+            }
+            self.paused = paused;
+        }
+
+        let mut commands = Vec::new();
+        while let Some(cmd) = api.try_command() {
+            commands.push(cmd);
+        }
+        for cmd in commands {
+            match cmd {
+                ApiCommand::ClosePosition { id, reply } => {
+                    let _ = reply.send(self.close_by_id(id).await);
+                }
+                ApiCommand::Trades { query, reply } => {
+                    let records = self
+                        .paper_trader
+                        .query_records(&query)
+                        .map_err(|e| format!("{:#}", e));
+                    let _ = reply.send(records);
+                }
+            }
+        }
+    }
+
+    /// Close one position at market on request from the API.
+    async fn close_by_id(&mut self, id: u64) -> Result<Option<Position>, String> {
+        let Some(symbol) = self
+            .paper_trader
+            .position(id)
+            .filter(|p| p.status == PositionStatus::Open)
+            .map(|p| p.symbol.clone())
+        else {
+            return Ok(None);
+        };
+        let Some(st) = self.symbols.iter_mut().find(|s| s.symbol == symbol) else {
+            return Err(format!("{} is not traded by this bot", symbol));
+        };
+        let closed = match self.live.as_mut() {
+            Some(live) => live
+                .close_position(&mut self.paper_trader, &symbol, id)
+                .await
+                .map_err(|e| format!("live close failed: {:#}", e))?,
+            None => {
+                let price = st
+                    .market
+                    .get_current_price()
+                    .await
+                    .map_err(|e| format!("no price for {}: {}", symbol, e))?;
+                self.paper_trader.close_by_id(id, price)
+            }
+        };
+        if let Some(pos) = &closed {
+            warn!(
+                "Position #{} {} CLOSED via API: PnL ${:+.2} | ${:.2} -> ${:.2}",
+                pos.id,
+                symbol,
+                pos.pnl,
+                pos.entry_price,
+                pos.exit_price.unwrap_or(0.0)
+            );
+            st.scale_positions.retain(|_, pid| *pid != id);
+            self.closed_since_analysis += 1;
+            let event = JournalEvent::close(pos);
+            self.journal.record(self.clock.now(), event);
+            let event = TradeEvent::Closed {
+                position: pos.clone(),
+                confidence: confidence(&self.paper_trader, id),
+            };
+            notify(&self.notifiers, event).await;
+        }
+        Ok(closed)
+    }
+
+    /// Refresh the snapshot served by `GET /status`.
+    fn publish_status(&self, cfg: &Config) {
+        let Some(api) = &self.api else {
+            return;
+        };
+        let snapshot = self.paper_trader.snapshot();
+        let symbols = self
+            .symbols
+            .iter()
+            .map(|st| SymbolStatus {
+                symbol: st.symbol.clone(),
+                price: st
+                    .data_cache
+                    .get(&Timeframe::M1)
+                    .and_then(|df| df.last())
+                    .map(|c| c.close),
+                weekly_bias: st.weekly_bias.clone(),
+                alignment: st.alignment.clone(),
+            })
+            .collect();
+        api.publish(BotStatus {
+            updated: Some(self.clock.now()),
+            account: cfg.account.clone(),
+            paused: self.paused,
+            kill_switch_active: self.kill_switch_active,
+            circuit_breaker_tripped: self.paper_trader.drawdown_guard().is_tripped(),
+            session: self.session.current_session.clone(),
+            balance: snapshot.balance,
+            daily_pnl: snapshot.daily_pnl,
+            realized_pnl: snapshot.realized_pnl,
+            total_trades: snapshot.total_trades,
+            open_positions: snapshot.open_positions,
+            symbols,
+        });
+    }
+
+    async fn run_analysis(&mut self) {
+        let records: Vec<_> = self.paper_trader.records().values().cloned().collect();
+        let closed: Vec<_> = records
+            .iter()
+            .filter(|r| r.outcome == "win" || r.outcome == "loss")
+            .cloned()
+            .collect();
+
+        if closed.len() < self.refiner.min_sample {
+            return;
+        }
+
+        let mut cfg = self.config.write().await;
+        let before = cfg.clone();
+        let mut adjustments = self.refiner.refine(&closed, &mut cfg);
+        adjustments.extend(
+            self.refiner
+                .refine_tp_allocation(self.paper_trader.history(), &mut cfg),
+        );
+        self.paper_trader.set_tp_allocations(&cfg);
+        self.config_history.record("refiner", &before, &cfg);
+
+        if !adjustments.is_empty() {
+            info!("--- Strategy Refinement ---");
+            for adj in &adjustments {
+                if adj.parameter.starts_with("WARNING:") {
+                    warn!("  {}", adj.reason);
+                } else {
+                    info!(
+                        "  {}: {:.4} -> {:.4} ({})",
+                        adj.parameter, adj.old_value, adj.new_value, adj.reason
+                    );
+                    let event = JournalEvent::adjustment(adj);
+                    self.journal.record(self.clock.now(), event);
+                }
+            }
+            if !self.refiner.skip_combos.is_empty() {
+                for (combo, entry) in &self.refiner.skip_combos {
+                    info!(
+                        "  Skip combo {}: since {} (edge {:+.4}, n={}){}",
+                        combo,
+                        entry.since.format("%Y-%m-%d"),
+                        entry.edge,
+                        entry.sample_size.map_or("?".to_string(), |n| n.to_string()),
+                        if self.refiner.is_expired(entry) { ", retrying" } else { "" }
+                    );
+                }
+            }
+        } else {
+            debug!("Analysis complete — no adjustments needed");
+        }
+
+        let window = self.config.read().await.expectancy_window;
+        if let Some(forecast) = self.paper_trader.expectancy_forecast(None, window) {
+            info!("Expectancy forecast {}", forecast.summary());
+        }
+    }
+
+    async fn print_status(&mut self) {
+        let cfg = self.config.read().await;
+        let stats = self.paper_trader.get_stats();
+        self.session.update(&cfg, None);
+
+        info!(
+            "Session: {} (weight: {})",
+            self.session.current_session, self.session.session_weight
+        );
+        info!("Day: {}", self.session.get_day_of_week());
+        for market in [Market::Ny, Market::London] {
+            if let Some(kind) = self.session.holiday(market) {
+                info!("Holiday: {:?} {:?}", market, kind);
+            }
+        }
+        info!("Balance: ${:.2}", stats.balance);
+        info!(
+            "Equity: ${:.2} (unrealized ${:+.2})",
+            stats.equity, stats.unrealized_pnl
+        );
+        info!(
+            "Trades: {} | Win Rate: {}%",
+            stats.total_trades, stats.win_rate
+        );
+        info!("PnL: ${:+.2}", stats.total_pnl);
+        if let Some(r) = &stats.r {
+            info!("Expectancy: {}", r.summary());
+        }
+        info!("Open: {}", stats.open_positions);
+        for st in &self.symbols {
+            info!("  {} scale slots: {:?}", st.symbol, st.scale_positions);
+            info!(
+                "  {} PDA cache: {}",
+                st.symbol,
+                st.fractal.pda_cache_stats()
+            );
+            let degraded = st.freshness.degraded_scales();
+            if !degraded.is_empty() {
+                warn!(
+                    "  {} degraded scales (stale data): {}",
+                    st.symbol,
+                    degraded.join(", ")
+                );
+            }
+        }
+
+        let default_str = if stats.kelly_using_default {
+            "default"
+        } else {
+            "calculated"
+        };
+        info!(
+            "Kelly: f={:.4} ({}) | Edge: {:+.4} | Sample: {}",
+            stats.kelly_fraction, default_str, stats.kelly_edge, stats.kelly_sample
+        );
+
+        let scale_kelly = self.paper_trader.get_kelly_by_scale();
+        for (s, kr) in &scale_kelly {
+            if kr.sample_size > 0 {
+                info!(
+                    "  Kelly {}: f={:.4} WR={:.1}% Payoff={:.2} Edge={:+.4} ({} trades)",
+                    s,
+                    kr.applied_fraction,
+                    kr.win_rate * 100.0,
+                    kr.payoff_ratio,
+                    kr.edge,
+                    kr.sample_size
+                );
+            }
+        }
+
+        if let Some(line) = metrics::summary(&metrics::global().snapshot()) {
+            info!("{}", line);
+        }
+
+        for (task, s) in self.scheduler.all_stats().filter(|(_, s)| s.runs > 0) {
+            info!(
+                "  Schedule {}: runs={} late={} missed={} latency avg={:.0}ms max={:.0}ms",
+                task,
+                s.runs,
+                s.late,
+                s.missed,
+                s.avg_latency().as_secs_f64() * 1000.0,
+                s.max_latency.as_secs_f64() * 1000.0
+            );
+        }
+
+        let window = cfg.expectancy_window;
+        if let Some(forecast) = self.paper_trader.expectancy_forecast(None, window) {
+            info!("Expectancy forecast {}", forecast.summary());
+            let mut scales: Vec<&String> = cfg.hft_scales.keys().collect();
+            scales.sort();
+            for s in scales {
+                if let Some(f) = self.paper_trader.expectancy_forecast(Some(s), window) {
+                    info!("  Expectancy {}: {}", s, f.summary());
+                }
+            }
+        }
+    }
+
+    /// Today's (ET) closed trades and PnL, for the end-of-session message.
+    fn daily_summary(&self, session: &str) -> DailySummary {
+        let today = self.clock.now().with_timezone(&chrono_tz::US::Eastern).date_naive();
+        let closed_today: Vec<f64> = self
+            .paper_trader
+            .history()
+            .iter()
+            .filter(|p| {
+                p.exit_time
+                    .as_deref()
+                    .and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+                    .is_some_and(|t| t.with_timezone(&chrono_tz::US::Eastern).date_naive() == today)
+            })
+            .map(|p| p.pnl)
+            .collect();
+        let snapshot = self.paper_trader.snapshot();
+        DailySummary {
+            date: today.to_string(),
+            session: session.to_string(),
+            trades: closed_today.len(),
+            wins: closed_today.iter().filter(|&&pnl| pnl > 0.0).count(),
+            pnl: closed_today.iter().sum(),
+            balance: snapshot.balance,
+            open_positions: snapshot.open_positions.len(),
+        }
+    }
+
+    /// Persist scale slots, cooldowns, weekly bias and any breaker trip if
+    /// they changed.
+    fn checkpoint(&mut self, cfg: &Config) {
+        let symbols = self
+            .symbols
+            .iter()
+            .map(|st| {
+                let runtime = SymbolRuntime {
+                    scale_positions: st.scale_positions.clone().into_iter().collect(),
+                    scale_cooldown: st.scale_cooldown.clone().into_iter().collect(),
+                    weekly_bias: st.weekly_bias.clone(),
+                };
+                (st.symbol.clone(), runtime)
+            })
+            .collect();
+        let mut state = RuntimeState {
+            saved: None,
+            symbols,
+            breaker_tripped_at: self.paper_trader.drawdown_guard().tripped_at(),
+        };
+        let json = serde_json::to_string(&state).unwrap_or_default();
+        if json == self.last_checkpoint {
+            return;
+        }
+        state.saved = Some(self.clock.now());
+        match state.save(&cfg.log_dir) {
+            Ok(()) => self.last_checkpoint = json,
+            Err(e) => error!("Failed to save runtime state: {:#}", e),
+        }
+    }
+
+    /// Snapshot of account state for the shutdown/crash report.
+    fn shutdown_report(&self, cfg: &Config, reason: &str) -> ShutdownReport {
+        let snapshot = self.paper_trader.snapshot();
+        let mut last_prices = BTreeMap::new();
+        let mut cooldowns = BTreeMap::new();
+        for st in &self.symbols {
+            if let Some(c) = st.data_cache.get(&Timeframe::M1).and_then(|df| df.last()) {
+                last_prices.insert(st.symbol.clone(), c.close);
+            }
+            for (scale, until) in &st.scale_cooldown {
+                cooldowns.insert(format!("{}/{}", st.symbol, scale), *until);
+            }
+        }
+        ShutdownReport {
+            time: self.clock.now(),
+            reason: reason.to_string(),
+            account: cfg.account.clone(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            config_revision: cfg.config_hash(),
+            balance: snapshot.balance,
+            daily_pnl: snapshot.daily_pnl,
+            open_positions: snapshot.open_positions,
+            last_prices,
+            cooldowns,
+            kill_switch_active: self.kill_switch_active,
+        }
+    }
+
+    async fn shutdown(&mut self) {
+        info!("Shutting down...");
+        self.print_status().await;
+        let cfg = self.config.read().await.clone();
+        self.last_checkpoint.clear();
+        self.checkpoint(&cfg);
+        match self.shutdown_report(&cfg, "shutdown").write(&cfg.log_dir) {
+            Ok(path) => info!("Shutdown report: {}", path.display()),
+            Err(e) => error!("Failed to write shutdown report: {:#}", e),
+        }
+        info!("Bot stopped.");
+    }
+}
@@ -1,27 +1,416 @@
-use crate::models::Timeframe;
+use crate::core::anchors::Anchor;
+use crate::core::holidays::Holiday;
+use crate::core::position_sizing::SizingMode;
+use crate::core::smt;
+use crate::models::{GapFill, Instrument, Precision, Timeframe};
+use crate::strategies::strategy;
+use crate::trading::execution_model::ExecutionModelKind;
+use crate::trading::paper_trader::IntrabarOrdering;
+use anyhow::{bail, Context, Result};
+use chrono::{NaiveDate, Weekday};
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
 pub type SharedConfig = Arc<RwLock<Config>>;
 
+/// Copy `overlay` into `base`, recursing into tables present in both.
+fn merge(base: &mut Value, overlay: &Value) {
+    match (base, overlay) {
+        (Value::Object(base), Value::Object(overlay)) => {
+            for (key, value) in overlay {
+                match base.get_mut(key) {
+                    Some(slot) => merge(slot, value),
+                    None => {
+                        base.insert(key.clone(), value.clone());
+                    }
+                }
+            }
+        }
+        (base, overlay) => *base = overlay.clone(),
+    }
+}
+
+/// Copy into `base` every value of `changed` that differs from `defaults`.
+fn overlay_changed(base: &mut Value, changed: &Value, defaults: &Value) {
+    match (base, changed, defaults) {
+        (Value::Object(base), Value::Object(changed), Value::Object(defaults)) => {
+            for (key, value) in changed {
+                match (base.get_mut(key), defaults.get(key)) {
+                    (Some(slot), Some(default)) => overlay_changed(slot, value, default),
+                    (None, Some(default)) if value == default => {}
+                    _ => {
+                        base.insert(key.clone(), value.clone());
+                    }
+                }
+            }
+        }
+        (base, changed, defaults) if changed != defaults => *base = changed.clone(),
+        _ => {}
+    }
+}
+
+/// Every day of the week, Monday first.
+pub const ALL_WEEKDAYS: [Weekday; 7] = [
+    Weekday::Mon,
+    Weekday::Tue,
+    Weekday::Wed,
+    Weekday::Thu,
+    Weekday::Fri,
+    Weekday::Sat,
+    Weekday::Sun,
+];
+
+/// Comma-separated weekdays (`mon,tue` or `monday,tuesday`).
+fn parse_weekdays(s: &str) -> Option<Vec<Weekday>> {
+    s.split(',')
+        .map(str::trim)
+        .filter(|d| !d.is_empty())
+        .map(|d| d.parse().ok())
+        .collect()
+}
+
+/// Comma-separated `YYYY-MM-DD` dates.
+fn parse_dates(s: &str) -> Option<Vec<NaiveDate>> {
+    s.split(',')
+        .map(str::trim)
+        .filter(|d| !d.is_empty())
+        .map(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok())
+        .collect()
+}
+
+/// 64-bit FNV-1a: stable across runs and platforms, unlike `DefaultHasher`.
+pub fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &b| {
+        (hash ^ b as u64).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SessionTime {
     pub start: (u32, u32),
     pub end: (u32, u32),
 }
 
+/// A single alignment requirement evaluated against per-timeframe trends.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlignmentRule {
+    /// All listed timeframes trend the same way (none neutral).
+    Agree(Vec<Timeframe>),
+    /// Timeframe is neutral or trends with the aligned direction.
+    NotOpposing(Timeframe),
+}
+
+impl AlignmentRule {
+    /// Parse rules like `agree:4h,1h;not_opposing:15m`.
+    pub fn parse_list(s: &str) -> Option<Vec<AlignmentRule>> {
+        let mut rules = Vec::new();
+        for part in s.split(';').map(str::trim).filter(|p| !p.is_empty()) {
+            let (kind, tfs) = part.split_once(':')?;
+            let tfs: Vec<Timeframe> = tfs
+                .split(',')
+                .map(|t| Timeframe::from_str_loose(t.trim()))
+                .collect::<Option<_>>()?;
+            match kind.trim() {
+                "agree" if !tfs.is_empty() => rules.push(AlignmentRule::Agree(tfs)),
+                "not_opposing" => {
+                    rules.extend(tfs.into_iter().map(AlignmentRule::NotOpposing))
+                }
+                _ => return None,
+            }
+        }
+        Some(rules)
+    }
+
+    pub fn timeframes(&self) -> Vec<Timeframe> {
+        match self {
+            AlignmentRule::Agree(tfs) => tfs.clone(),
+            AlignmentRule::NotOpposing(tf) => vec![*tf],
+        }
+    }
+}
+
+/// Which partial-TP allocation table new positions use.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TpAllocMode {
+    /// Aggressive when CISD confirmed, conservative otherwise
+    #[default]
+    Dynamic,
+    Conservative,
+    Aggressive,
+}
+
+impl TpAllocMode {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "dynamic" => Some(TpAllocMode::Dynamic),
+            "conservative" => Some(TpAllocMode::Conservative),
+            "aggressive" => Some(TpAllocMode::Aggressive),
+            _ => None,
+        }
+    }
+}
+
+/// Share of a position closed at each SD projection level, as
+/// `(level, share)` pairs whose shares sum to 1.0.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TpAllocation(pub Vec<(f64, f64)>);
+
+impl TpAllocation {
+    /// Bank most at the first target (non-CISD entries)
+    pub fn conservative() -> Self {
+        Self(vec![(-1.0, 0.60), (-2.0, 0.20), (-4.0, 0.10), (-4.5, 0.10)])
+    }
+
+    /// Let runners run (CISD-confirmed entries)
+    pub fn aggressive() -> Self {
+        Self(vec![(-1.0, 0.10), (-2.0, 0.15), (-4.0, 0.30), (-4.5, 0.45)])
+    }
+
+    /// Parse `-1:0.6,-2:0.2,-4:0.1,-4.5:0.1`; `None` unless valid.
+    pub fn parse(s: &str) -> Option<Self> {
+        let levels = s
+            .split(',')
+            .map(str::trim)
+            .filter(|p| !p.is_empty())
+            .map(|p| {
+                let (level, share) = p.split_once(':')?;
+                Some((level.trim().parse().ok()?, share.trim().parse().ok()?))
+            })
+            .collect::<Option<Vec<(f64, f64)>>>()?;
+        let alloc = Self(levels);
+        alloc.is_valid().then_some(alloc)
+    }
+
+    /// At least one level, no negative share, shares summing to 1.0.
+    pub fn is_valid(&self) -> bool {
+        let sum: f64 = self.0.iter().map(|&(_, share)| share).sum();
+        !self.0.is_empty()
+            && self.0.iter().all(|&(_, share)| share >= 0.0)
+            && (sum - 1.0).abs() < 1e-6
+    }
+
+    pub fn share(&self, level: f64) -> Option<f64> {
+        self.0
+            .iter()
+            .find(|&&(l, _)| l == level)
+            .map(|&(_, share)| share)
+    }
+
+    /// Move up to `amount` of `from`'s share to `to`, leaving `from` at
+    /// least `floor`. Returns the amount moved.
+    pub fn shift(&mut self, from: f64, to: f64, amount: f64, floor: f64) -> f64 {
+        let (Some(i), Some(j)) = (
+            self.0.iter().position(|&(l, _)| l == from),
+            self.0.iter().position(|&(l, _)| l == to),
+        ) else {
+            return 0.0;
+        };
+        if i == j {
+            return 0.0;
+        }
+        let moved = amount.min(self.0[i].1 - floor).max(0.0);
+        self.0[i].1 -= moved;
+        self.0[j].1 += moved;
+        moved
+    }
+}
+
+/// Order in which `FractalEngine::evaluate_all` returns competing signals.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SignalRanking {
+    #[default]
+    Confidence,
+    /// Confidence x the scale's historical payoff ratio, net of the loss side
+    ExpectedValue,
+}
+
+impl SignalRanking {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "confidence" => Some(SignalRanking::Confidence),
+            "ev" | "expected_value" => Some(SignalRanking::ExpectedValue),
+            _ => None,
+        }
+    }
+}
+
+/// Where trades, records and refinements are persisted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StorageBackend {
+    /// `paper_trades.json`, `trade_records.json`, `refinements.json` in log_dir
+    #[default]
+    Json,
+    /// `trades.db` in log_dir (requires the `sqlite` feature)
+    Sqlite,
+}
+
+impl StorageBackend {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "json" => Some(StorageBackend::Json),
+            "sqlite" => Some(StorageBackend::Sqlite),
+            _ => None,
+        }
+    }
+}
+
+/// When a backtest entry fills relative to the bar that produced its signal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FillTiming {
+    /// The signal's own entry price (the signal bar's close)
+    #[default]
+    SignalClose,
+    /// Open of the following 1m bar, as a live order would, or
+    /// `fill_latency_secs` after it
+    NextBarOpen,
+}
+
+impl FillTiming {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "signal_close" | "close" => Some(FillTiming::SignalClose),
+            "next_bar_open" | "next_open" => Some(FillTiming::NextBarOpen),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FillTiming::SignalClose => "signal_close",
+            FillTiming::NextBarOpen => "next_bar_open",
+        }
+    }
+}
+
+/// Whether a symbol may hold longs and shorts at the same time (e.g. a 1m
+/// short against a running 15m long). Either way stops, trails and PnL
+/// are tracked per position id.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HedgePolicy {
+    /// Opposite-direction entries open as independent positions
+    #[default]
+    Allow,
+    /// Skip entries against an open or resting position on the symbol
+    Block,
+}
+
+impl HedgePolicy {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "allow" => Some(HedgePolicy::Allow),
+            "block" => Some(HedgePolicy::Block),
+            _ => None,
+        }
+    }
+}
+
+/// How entries outside the preferred Q2/Q3 quarters are treated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QuarterMode {
+    /// Quarters are tracked but not acted on
+    #[default]
+    Off,
+    /// Skip entries in Q1 and Q4
+    Gate,
+    /// Scale Q1/Q4 confidence by `quarter_off_weight`
+    Weight,
+}
+
+impl QuarterMode {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "off" => Some(QuarterMode::Off),
+            "gate" => Some(QuarterMode::Gate),
+            "weight" => Some(QuarterMode::Weight),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            QuarterMode::Off => "off",
+            QuarterMode::Gate => "gate",
+            QuarterMode::Weight => "weight",
+        }
+    }
+}
+
+/// Position cap and per-trade risk for one weekly-profile risk regime.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RiskLimits {
+    pub max_open_positions: usize,
+    /// Max risk per trade as a fraction of balance
+    pub max_risk_pct: f64,
+}
+
+/// Per-killzone overrides of the global thresholds, keyed by session name.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct KillzoneProfile {
+    /// Replaces every scale's `min_confidence` in this session
+    #[serde(default)]
+    pub min_confidence: Option<f64>,
+    /// Replaces `tp_alloc_mode` for entries in this session
+    #[serde(default)]
+    pub tp_alloc_mode: Option<TpAllocMode>,
+    /// Replaces the regime's per-trade risk cap in this session
+    #[serde(default)]
+    pub max_risk_pct: Option<f64>,
+}
+
+impl KillzoneProfile {
+    /// Parse `london:conf=0.6:tp=aggressive:risk=0.03,ny_indices:tp=conservative`.
+    pub fn parse_list(s: &str) -> Option<HashMap<String, KillzoneProfile>> {
+        let mut profiles = HashMap::new();
+        for entry in s.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let mut parts = entry.split(':').map(str::trim);
+            let session = parts.next().filter(|n| !n.is_empty())?;
+            let mut profile = KillzoneProfile::default();
+            for part in parts {
+                let (key, value) = part.split_once('=')?;
+                match key.trim() {
+                    "conf" => profile.min_confidence = Some(value.trim().parse().ok()?),
+                    "tp" => profile.tp_alloc_mode = Some(TpAllocMode::parse(value)?),
+                    "risk" => profile.max_risk_pct = Some(value.trim().parse().ok()?),
+                    _ => return None,
+                }
+            }
+            profiles.insert(session.to_string(), profile);
+        }
+        Some(profiles)
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HftScaleConfig {
     pub name: String,
     pub entry_tf: Timeframe,
     pub alignment_tfs: Vec<Timeframe>,
+    /// Empty = all alignment_tfs must agree
+    #[serde(default)]
+    pub alignment_rules: Vec<AlignmentRule>,
     pub structure_tf: Timeframe,
     pub confirm_tf: Timeframe,
     pub scan_interval: u64,
     pub min_confidence: f64,
     pub weight: f64,
+    /// Close at market after this long without a TP hit; `None` = MAX_HOLD_MINUTES
+    #[serde(default)]
+    pub max_hold_minutes: Option<i64>,
+    /// Overrides of the global partial-TP tables for this scale
+    #[serde(default)]
+    pub tp_alloc_conservative: Option<TpAllocation>,
+    #[serde(default)]
+    pub tp_alloc_aggressive: Option<TpAllocation>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -54,9 +443,29 @@ impl DayRatings {
 pub struct Config {
     // Exchange
     pub exchange: String,
+    /// Primary product (first of `symbols`)
     pub symbol: String,
+    /// All products traded concurrently (env SYMBOLS, comma separated)
+    pub symbols: Vec<String>,
+    /// Per-symbol tick/lot size overrides (env PRECISION, `SYMBOL:price_inc:size_inc,...`)
+    pub precision: HashMap<String, Precision>,
+    /// Spot/perp overrides (env INSTRUMENTS, `SYMBOL:perp,...`); otherwise
+    /// inferred from the product id
+    pub instruments: HashMap<String, Instrument>,
+    /// Hours between perpetual funding settlements (env FUNDING_INTERVAL_HOURS)
+    pub funding_interval_hours: i64,
+    /// Funding rate per settlement used when a backtest has no funding
+    /// history (env FUNDING_RATE)
+    pub funding_rate: f64,
     pub coinbase_api_key: String,
     pub coinbase_api_secret: String,
+    /// Stream market data over WebSocket instead of polling REST
+    pub use_websocket: bool,
+    /// Seconds a live market order may take to fill (env LIVE_FILL_TIMEOUT_SECS)
+    pub live_fill_timeout_secs: u64,
+    /// Live stop-limits rest their limit this fraction beyond the trigger
+    /// (env STOP_LIMIT_BUFFER)
+    pub stop_limit_buffer: f64,
 
     // Paper Trading
     pub paper_trade: bool,
@@ -65,14 +474,232 @@ pub struct Config {
     // Risk
     pub max_daily_loss: f64,
     pub max_open_positions: usize,
+    /// Max risk per trade as a fraction of balance (env MAX_RISK_PCT)
+    pub max_risk_pct: f64,
+    /// Weekly bias confidence below which the cautious limits apply
+    /// (env REGIME_LOW_CONFIDENCE); Undetermined weeks are always cautious
+    pub regime_low_confidence: f64,
+    /// Classic Expansion confidence at or above which the expansion limits
+    /// apply (env REGIME_HIGH_CONFIDENCE)
+    pub regime_high_confidence: f64,
+    /// Limits for low-confidence weeks (env CAUTIOUS_MAX_POSITIONS,
+    /// CAUTIOUS_RISK_PCT; default to the normal limits)
+    pub cautious_limits: RiskLimits,
+    /// Limits for high-confidence Classic Expansion weeks (env
+    /// EXPANSION_MAX_POSITIONS, EXPANSION_RISK_PCT; default to the normal limits)
+    pub expansion_limits: RiskLimits,
+    /// Max net long / net short notional across all open positions, as a
+    /// multiple of balance (env MAX_NET_LONG_EXPOSURE,
+    /// MAX_NET_SHORT_EXPOSURE; 0 = no cap)
+    pub max_net_long_exposure: f64,
+    pub max_net_short_exposure: f64,
+    /// Drawdown from peak balance that halts new entries, as a fraction
+    /// (env MAX_DRAWDOWN, 0 disables)
+    pub max_drawdown: f64,
+    /// Hours entries stay halted after the breaker trips (env DRAWDOWN_COOL_OFF_HOURS)
+    pub drawdown_cool_off_hours: f64,
+    /// Close open positions when the breaker trips (env DRAWDOWN_FLATTEN)
+    pub drawdown_flatten: bool,
+    /// Tranches a winning CISD position may add (env PYRAMID_MAX_ADDS, 0 disables)
+    pub pyramid_max_adds: usize,
+    /// Risk of each added tranche relative to a full entry (env PYRAMID_SIZE)
+    pub pyramid_size: f64,
+    /// Opposite-direction entries on one symbol (env HEDGE_POLICY)
+    pub hedge_policy: HedgePolicy,
+    /// How entries are sized (env SIZING_MODE: kelly, fixed, vol_adjusted, kelly_vol)
+    pub sizing_mode: SizingMode,
+    /// Balance fraction risked by the fixed and vol_adjusted modes (env FIXED_RISK_PCT)
+    pub fixed_risk_pct: f64,
+    /// Favourable move, in R of the initial risk, before a position
+    /// starts trailing (env TRAIL_ACTIVATION_R, 0 = at once)
+    pub trail_activation_r: f64,
+    /// Stop kept this fraction behind the best price since activation
+    /// (env TRAIL_DISTANCE_PCT, 0 = protected swings only)
+    pub trail_distance_pct: f64,
+    /// Timeframe every scale trails on (env TRAIL_TF, unset = the scale's own)
+    pub trail_timeframe: Option<Timeframe>,
+
+    // Kill switch: sentinel file checked every tick
+    pub kill_switch_file: String,
+    /// Start with the kill switch engaged (env KILL_SWITCH)
+    pub kill_switch: bool,
+    pub kill_switch_flatten: bool,
+
+    /// Max per-task offset (seconds) so scans and refreshes don't all fall
+    /// due in the same second (env SCHEDULE_JITTER_SECS)
+    pub schedule_jitter_secs: f64,
+
+    // Notifications: Telegram is enabled when both are set
+    pub telegram_bot_token: String,
+    pub telegram_chat_id: String,
+    /// Discord webhook URL (env DISCORD_WEBHOOK_URL); empty = off
+    pub discord_webhook_url: String,
+
+    /// Status/control API address, e.g. 127.0.0.1:8080 (env API_BIND); empty = off
+    pub api_bind: String,
+    /// Bearer token required by the API when set (env API_TOKEN)
+    pub api_token: String,
 
     // Fees & Slippage (as fraction, e.g., 0.001 = 0.1%)
     pub fee_rate: f64,
     pub slippage_rate: f64,
+    /// Slippage on partial and final exits (env EXIT_SLIPPAGE_RATE, defaults to SLIPPAGE_RATE)
+    pub exit_slippage_rate: f64,
+    /// How simulated fills are priced (env EXECUTION_MODEL: flat, maker_taker)
+    pub execution_model: ExecutionModelKind,
+    /// Fee on resting orders under maker_taker (env MAKER_FEE_RATE, defaults to FEE_RATE)
+    pub maker_fee_rate: f64,
+    /// Square-root impact of taker orders by share of bar volume (env
+    /// IMPACT_COEFFICIENT, 0 = off)
+    pub impact_coefficient: f64,
+    /// Largest share of a bar's volume a taker entry fills at one price;
+    /// bigger ones walk further levels (env PARTIAL_FILL_PCT, 0 = off)
+    pub partial_fill_pct: f64,
+    /// Extra adverse move per level walked (env PARTIAL_FILL_STEP)
+    pub partial_fill_step: f64,
+
+    /// Partial TP allocation table (env TP_ALLOC_MODE)
+    pub tp_alloc_mode: TpAllocMode,
+    /// Partial-TP tables; env TP_ALLOC_CONSERVATIVE / TP_ALLOC_AGGRESSIVE
+    /// (`-1:0.6,-2:0.2,...`), per scale with a `_1M`/`_5M`/`_15M` suffix
+    pub tp_alloc_conservative: TpAllocation,
+    pub tp_alloc_aggressive: TpAllocation,
+    /// How competing signals are ordered (env SIGNAL_RANKING)
+    pub signal_ranking: SignalRanking,
+    /// Move the stop to entry (plus fees) once the first partial TP fills
+    pub move_to_breakeven_after_tp1: bool,
+    /// Flag positions held this multiple of their scale's typical hold with
+    /// no progress (env STUCK_HOLD_MULTIPLE, 0 disables)
+    pub stuck_hold_multiple: f64,
+    /// Max |progress| toward TP or SL (fraction) for a position to count as stuck
+    pub stuck_progress_band: f64,
+    /// Move a stuck position's stop halfway to entry
+    pub stuck_tighten_stop: bool,
+    /// Backtest entry fill convention (env FILL_TIMING)
+    pub fill_timing: FillTiming,
+    /// Signal-to-fill delay after the signal bar closes under next_bar_open
+    /// (env FILL_LATENCY_SECS, 0 = the next bar's open)
+    pub fill_latency_secs: i64,
+    /// Strategy the backtest runs (env BACKTEST_STRATEGY)
+    pub backtest_strategy: String,
+    /// Strategies run side by side, first signal per scale wins (env
+    /// STRATEGIES, comma-separated); empty runs BACKTEST_STRATEGY in
+    /// backtests and the fractal engine live
+    pub strategies: Vec<String>,
+    /// Risk multiplier per strategy (env STRATEGY_RISK,
+    /// `silver_bullet:0.5,...`); unlisted strategies risk in full
+    pub strategy_risk: HashMap<String, f64>,
+    /// Simulated seconds between backtest position checks within a bar
+    /// (env BACKTEST_TICK_SECONDS, 0 = whole bars)
+    pub backtest_tick_seconds: u64,
+    /// Backtest resolution of bars spanning both SL and TP (env INTRABAR_ORDERING)
+    pub intrabar_ordering: IntrabarOrdering,
+    /// Entry-timeframe candles each side of a trade in backtest replay
+    /// bundles (env REPLAY_BARS, 0 = no export)
+    pub replay_bars: usize,
+    /// Day resamples of the A/B significance test (env AB_BOOTSTRAP_RUNS)
+    pub ab_bootstrap_runs: usize,
+    /// Trade-order reshuffles of the Monte Carlo simulation (env MC_RUNS)
+    pub mc_runs: usize,
+    /// Drawdown (%) from the starting balance the simulation counts as
+    /// ruin (env MC_RUIN_PCT)
+    pub mc_ruin_pct: f64,
+    /// Seed of the reshuffles (env MC_SEED)
+    pub mc_seed: u64,
+    /// Optimizer grid of every scale's `min_confidence` (env
+    /// OPT_MIN_CONFIDENCE, comma-separated; empty keeps the base value)
+    pub opt_min_confidence: Vec<f64>,
+    /// Optimizer grid of `fvg_min_gap_percent` (env OPT_FVG_MIN_GAP)
+    pub opt_fvg_min_gap: Vec<f64>,
+    /// Optimizer grid of `ob_lookback` (env OPT_OB_LOOKBACK)
+    pub opt_ob_lookback: Vec<usize>,
+    /// Optimizer grid of `tp_alloc_mode` (env OPT_TP_ALLOC)
+    pub opt_tp_alloc: Vec<TpAllocMode>,
+    /// Optimizer backtests run at once (env OPT_PARALLELISM, unset = one
+    /// per CPU)
+    pub opt_parallelism: Option<usize>,
+    /// Walk-forward training window in days (env WF_TRAIN_DAYS)
+    pub wf_train_days: i64,
+    /// Walk-forward test window in days (env WF_TEST_DAYS)
+    pub wf_test_days: i64,
+    /// Scales left out of the scan (env SKIP_SCALES, comma separated)
+    pub skip_scales: Vec<String>,
+    /// Candles refreshed per timeframe (env DATA_LOOKBACK, unset = the
+    /// runner's own default)
+    pub data_lookback: Option<usize>,
+    /// Repair of refreshed candles with holes or duplicates (env GAP_FILL)
+    pub gap_fill: GapFill,
+    /// Bars a series may lag behind the clock before it counts as stale
+    /// (env DATA_MAX_STALE_BARS)
+    pub data_max_stale_bars: i64,
+    /// Minutes a scale waits after its position closes (env
+    /// COOLDOWN_MINUTES, unset = the runner's own default)
+    pub cooldown_minutes: Option<i64>,
+    /// Minimum TP distance as a multiple of the round-trip cost (env MIN_TP_MULTIPLE)
+    pub min_tp_multiple: f64,
+    /// Position notional cap as a multiple of the balance (env MAX_LEVERAGE)
+    pub max_leverage: f64,
+    /// Close positions with no TP hit after this long, unless the scale
+    /// sets its own (env MAX_HOLD_MINUTES, 0 = never)
+    pub max_hold_minutes: i64,
+    /// Close the remainder this long after the last partial TP (env
+    /// POST_TP_STALL_MINUTES, 0 = never)
+    pub post_tp_stall_minutes: i64,
+    /// Skip entries after this many same-direction expansion candles
+    /// (env EXHAUST_CANDLES, 0 = off)
+    pub exhaust_candles: usize,
+    /// Body, in ATRs, a candle needs to count as a liquidity sweep
+    /// (env SWEEP_DISPLACEMENT_ATR)
+    pub sweep_displacement_atr: f64,
+    /// Open the Judas swing / Power of Three is read against (env JUDAS_ANCHOR)
+    pub judas_anchor: Anchor,
+    /// Open premium/discount is split at; `None` uses the dealing-range
+    /// equilibrium (env PD_ANCHOR, "equilibrium")
+    pub pd_anchor: Option<Anchor>,
+    /// Candles each side of a protected swing (env SWING_LOOKBACK)
+    pub swing_lookback: usize,
+    /// Midnight reversion: min distance from the midnight open (env MIDNIGHT_MIN_DISCOUNT)
+    pub midnight_min_discount: f64,
+    /// Midnight reversion: min reward to risk (env MIDNIGHT_MIN_RR)
+    pub midnight_min_rr: f64,
+    /// Silver bullet: min reward to risk (env SILVER_BULLET_MIN_RR)
+    pub silver_bullet_min_rr: f64,
+    /// Rolling trade window for the expectancy forecast (env EXPECTANCY_WINDOW)
+    pub expectancy_window: usize,
 
     // Sessions (stored as minute offsets from midnight ET)
     pub sessions: HashMap<String, SessionTime>,
     pub session_weights: HashMap<String, f64>,
+    /// Extra holidays (env HOLIDAYS, e.g. `2024-12-24:ny:half,2025-01-01:both:closed`)
+    pub holidays: Vec<Holiday>,
+    /// Apply built-in NYSE / UK bank holiday rules
+    pub builtin_holidays: bool,
+    /// Session weight multiplier on half days
+    pub holiday_half_day_weight: f64,
+    /// ET weekdays entries are allowed on (env TRADING_DAYS, default all)
+    pub trading_days: Vec<Weekday>,
+    /// ET dates with no new entries (env BLACKOUT_DATES, e.g. `2024-12-25,2025-01-01`)
+    pub blackout_dates: Vec<NaiveDate>,
+    /// Economic calendar file (CSV/JSON) or http(s) URL (env ECON_CALENDAR,
+    /// empty = no news blackouts)
+    pub econ_calendar: String,
+    /// Minutes before a high-impact release entries stop (env NEWS_BLACKOUT_BEFORE)
+    pub news_blackout_before: i64,
+    /// Minutes after a high-impact release entries resume (env NEWS_BLACKOUT_AFTER)
+    pub news_blackout_after: i64,
+    /// Pull open stops halfway to entry when a blackout starts (env NEWS_TIGHTEN_STOPS)
+    pub news_tighten_stops: bool,
+    /// Currencies whose releases black out entries (env NEWS_CURRENCIES,
+    /// default `USD`, empty = all); events without a currency always count
+    pub news_currencies: Vec<String>,
+    /// Threshold overrides per killzone (env KILLZONE_PROFILES, see
+    /// `KillzoneProfile::parse_list`)
+    pub killzone_profiles: HashMap<String, KillzoneProfile>,
+    /// Entry handling outside the Q2/Q3 quarters (env QUARTER_MODE)
+    pub quarter_mode: QuarterMode,
+    /// Confidence multiplier for Q1/Q4 entries under `QuarterMode::Weight`
+    /// (env QUARTER_OFF_WEIGHT)
+    pub quarter_off_weight: f64,
 
     // HFT Scales
     pub hft_scales: HashMap<String, HftScaleConfig>,
@@ -80,6 +707,24 @@ pub struct Config {
     // Cross-scale confluence
     pub cross_scale_confluence_bonus: f64,
 
+    /// Price bins per session volume profile (env VOLUME_PROFILE_BINS)
+    pub volume_profile_bins: usize,
+    /// Confidence added when the engaged PDA holds a session POC or
+    /// high-volume node (env VOLUME_CONFLUENCE_BONUS)
+    pub volume_confluence_bonus: f64,
+    /// Correlated symbol pairs for SMT divergence (env SMT_PAIRS,
+    /// `BTC-USD:ETH-USD,...`, symmetric)
+    pub smt_pairs: HashMap<String, String>,
+    /// Confidence added when SMT divergence agrees with the signal (env SMT_WEIGHT)
+    pub smt_weight: f64,
+    /// Drop signals of paired symbols without agreeing SMT (env SMT_REQUIRED)
+    pub smt_required: bool,
+    /// Paper entries wait at the OTE limit instead of the current close
+    /// (env OTE_ENTRIES)
+    pub ote_entries: bool,
+    /// Minutes an unfilled OTE limit stays working (env OTE_EXPIRY_MINUTES)
+    pub ote_expiry_minutes: i64,
+
     // Weekly Profile Day Ratings
     pub day_ratings: HashMap<String, DayRatings>,
     pub min_day_rating: f64,
@@ -88,6 +733,16 @@ pub struct Config {
     pub fvg_min_gap_percent: f64,
     pub ob_lookback: usize,
     pub breaker_lookback: usize,
+    /// Smallest body gap kept as a volume imbalance, as a fraction of price
+    /// (env VI_MIN_GAP)
+    pub vi_min_gap_percent: f64,
+    /// Fewest full-bodied candles in a liquidity void (env VOID_MIN_CANDLES)
+    pub void_min_candles: usize,
+    /// Smallest move a liquidity void spans, as a fraction of price
+    /// (env VOID_MIN_MOVE)
+    pub void_min_move_percent: f64,
+    /// Weekly and daily opening gaps kept as PDAs, each (env OPENING_GAPS_KEEP, 0 disables)
+    pub opening_gaps_keep: usize,
 
     // TGIF
     pub tgif_retrace_min: f64,
@@ -97,18 +752,70 @@ pub struct Config {
     pub analysis_interval: u64,
     pub min_sample_per_bucket: usize,
     pub adjustment_step: f64,
+    /// Days before a skipped scale+session combo is retried (0 = never)
+    pub skip_expiry_days: f64,
+    /// Risk multiplier for trades in a combo retried after its skip expired
+    pub skip_retry_size: f64,
+
+    /// Persistence for trader and refiner state (env STORAGE_BACKEND)
+    pub storage_backend: StorageBackend,
 
     // Logging
     pub log_dir: String,
     pub log_level: String,
+
+    // Account name (state lives in log_dir, which is per-account when ACCOUNTS is set)
+    pub account: String,
 }
 
 impl Config {
     pub fn from_env() -> Self {
         dotenvy::dotenv().ok();
+        Self::from_vars(|key| std::env::var(key).ok())
+    }
 
+    /// Config with every setting looked up through `var`; unset = default.
+    fn from_vars(var: impl Fn(&str) -> Option<String>) -> Self {
         let env = |key: &str, default: &str| -> String {
-            std::env::var(key).unwrap_or_else(|_| default.to_string())
+            var(key).unwrap_or_else(|| default.to_string())
+        };
+
+        // e.g. ALIGNMENT_RULES_5M="agree:4h,1h;not_opposing:15m"
+        let alignment_rules = |key: &str| -> Vec<AlignmentRule> {
+            let raw = env(key, "");
+            AlignmentRule::parse_list(&raw).unwrap_or_else(|| {
+                tracing::warn!("Invalid {}='{}', using all-agree alignment", key, raw);
+                Vec::new()
+            })
+        };
+
+        let max_hold = |key: &str| -> Option<i64> { env(key, "").parse().ok() };
+        let tp_alloc = |key: &str| -> Option<TpAllocation> {
+            let raw = env(key, "");
+            if raw.is_empty() {
+                return None;
+            }
+            let alloc = TpAllocation::parse(&raw);
+            if alloc.is_none() {
+                tracing::warn!(
+                    "Invalid {}='{}' (shares must sum to 1.0), ignoring",
+                    key,
+                    raw
+                );
+            }
+            alloc
+        };
+        let fee_rate: f64 = env("FEE_RATE", "0.001").parse().unwrap_or(0.001);
+        let slippage_rate: f64 = env("SLIPPAGE_RATE", "0.0005").parse().unwrap_or(0.0005);
+        let max_open_positions = 3;
+        let max_risk_pct: f64 = env("MAX_RISK_PCT", "0.02").parse().unwrap_or(0.02);
+        let regime_limits = |prefix: &str| RiskLimits {
+            max_open_positions: env(&format!("{}_MAX_POSITIONS", prefix), "")
+                .parse()
+                .unwrap_or(max_open_positions),
+            max_risk_pct: env(&format!("{}_RISK_PCT", prefix), "")
+                .parse()
+                .unwrap_or(max_risk_pct),
         };
 
         let mut sessions = HashMap::new();
@@ -155,11 +862,15 @@ impl Config {
                 name: "1m Scalp".to_string(),
                 entry_tf: Timeframe::M1,
                 alignment_tfs: vec![Timeframe::M5, Timeframe::M15, Timeframe::H1],
+                alignment_rules: alignment_rules("ALIGNMENT_RULES_1M"),
                 structure_tf: Timeframe::M5,
                 confirm_tf: Timeframe::M1,
                 scan_interval: 10,
                 min_confidence: 0.7,
                 weight: 1.0,
+                max_hold_minutes: max_hold("MAX_HOLD_MINUTES_1M"),
+                tp_alloc_conservative: tp_alloc("TP_ALLOC_CONSERVATIVE_1M"),
+                tp_alloc_aggressive: tp_alloc("TP_ALLOC_AGGRESSIVE_1M"),
             },
         );
         hft_scales.insert(
@@ -168,11 +879,15 @@ impl Config {
                 name: "5m Intraday".to_string(),
                 entry_tf: Timeframe::M5,
                 alignment_tfs: vec![Timeframe::M15, Timeframe::H1, Timeframe::H4],
+                alignment_rules: alignment_rules("ALIGNMENT_RULES_5M"),
                 structure_tf: Timeframe::M15,
                 confirm_tf: Timeframe::M5,
                 scan_interval: 30,
                 min_confidence: 0.55,
                 weight: 1.0,
+                max_hold_minutes: max_hold("MAX_HOLD_MINUTES_5M"),
+                tp_alloc_conservative: tp_alloc("TP_ALLOC_CONSERVATIVE_5M"),
+                tp_alloc_aggressive: tp_alloc("TP_ALLOC_AGGRESSIVE_5M"),
             },
         );
         hft_scales.insert(
@@ -181,11 +896,15 @@ impl Config {
                 name: "15m Swing".to_string(),
                 entry_tf: Timeframe::M15,
                 alignment_tfs: vec![Timeframe::H1, Timeframe::H4, Timeframe::D1],
+                alignment_rules: alignment_rules("ALIGNMENT_RULES_15M"),
                 structure_tf: Timeframe::H1,
                 confirm_tf: Timeframe::M15,
                 scan_interval: 60,
                 min_confidence: 0.7,
                 weight: 1.0,
+                max_hold_minutes: max_hold("MAX_HOLD_MINUTES_15M"),
+                tp_alloc_conservative: tp_alloc("TP_ALLOC_CONSERVATIVE_15M"),
+                tp_alloc_aggressive: tp_alloc("TP_ALLOC_AGGRESSIVE_15M"),
             },
         );
 
@@ -239,39 +958,538 @@ impl Config {
             },
         );
 
+        let mut symbols: Vec<String> = env("SYMBOLS", "BTC-USD")
+            .split(',')
+            .map(|s| s.trim().to_uppercase())
+            .filter(|s| !s.is_empty())
+            .collect();
+        if symbols.is_empty() {
+            symbols.push("BTC-USD".to_string());
+        }
+
         Config {
             exchange: "coinbase".to_string(),
-            symbol: "BTC-USD".to_string(),
+            symbol: symbols[0].clone(),
+            symbols,
+            precision: {
+                let raw = env("PRECISION", "");
+                Precision::parse_list(&raw).unwrap_or_else(|| {
+                    tracing::warn!("Invalid PRECISION='{}', using built-in increments", raw);
+                    HashMap::new()
+                })
+            },
+            instruments: {
+                let raw = env("INSTRUMENTS", "");
+                Instrument::parse_list(&raw).unwrap_or_else(|| {
+                    tracing::warn!("Invalid INSTRUMENTS='{}', inferring from symbols", raw);
+                    HashMap::new()
+                })
+            },
+            funding_interval_hours: env("FUNDING_INTERVAL_HOURS", "1").parse().unwrap_or(1),
+            funding_rate: env("FUNDING_RATE", "0.00001").parse().unwrap_or(0.00001),
             coinbase_api_key: env("COINBASE_API_KEY", ""),
             coinbase_api_secret: env("COINBASE_API_SECRET", "").replace("\\n", "\n"),
+            use_websocket: env("USE_WEBSOCKET", "false").to_lowercase() == "true",
+            live_fill_timeout_secs: env("LIVE_FILL_TIMEOUT_SECS", "30").parse().unwrap_or(30),
+            stop_limit_buffer: env("STOP_LIMIT_BUFFER", "0.005").parse().unwrap_or(0.005),
             paper_trade: env("PAPER_TRADE", "true").to_lowercase() == "true",
             initial_balance: env("INITIAL_BALANCE", "200")
                 .parse()
                 .unwrap_or(200.0),
             max_daily_loss: 0.03,
-            max_open_positions: 3,
-            fee_rate: env("FEE_RATE", "0.001").parse().unwrap_or(0.001),         // 0.1% per trade
-            slippage_rate: env("SLIPPAGE_RATE", "0.0005").parse().unwrap_or(0.0005), // 0.05% per trade
+            max_open_positions,
+            max_risk_pct,
+            regime_low_confidence: env("REGIME_LOW_CONFIDENCE", "0.4").parse().unwrap_or(0.4),
+            regime_high_confidence: env("REGIME_HIGH_CONFIDENCE", "0.7").parse().unwrap_or(0.7),
+            cautious_limits: regime_limits("CAUTIOUS"),
+            expansion_limits: regime_limits("EXPANSION"),
+            max_net_long_exposure: env("MAX_NET_LONG_EXPOSURE", "0").parse().unwrap_or(0.0),
+            max_net_short_exposure: env("MAX_NET_SHORT_EXPOSURE", "0").parse().unwrap_or(0.0),
+            max_drawdown: env("MAX_DRAWDOWN", "0").parse().unwrap_or(0.0),
+            drawdown_cool_off_hours: env("DRAWDOWN_COOL_OFF_HOURS", "24").parse().unwrap_or(24.0),
+            drawdown_flatten: env("DRAWDOWN_FLATTEN", "false").to_lowercase() == "true",
+            pyramid_max_adds: env("PYRAMID_MAX_ADDS", "0").parse().unwrap_or(0),
+            pyramid_size: env("PYRAMID_SIZE", "0.5").parse().unwrap_or(0.5),
+            hedge_policy: HedgePolicy::parse(&env("HEDGE_POLICY", "allow")).unwrap_or_default(),
+            sizing_mode: SizingMode::parse(&env("SIZING_MODE", "kelly")).unwrap_or_default(),
+            fixed_risk_pct: env("FIXED_RISK_PCT", "0.01").parse().unwrap_or(0.01),
+            trail_activation_r: env("TRAIL_ACTIVATION_R", "0").parse().unwrap_or(0.0),
+            trail_distance_pct: env("TRAIL_DISTANCE_PCT", "0").parse().unwrap_or(0.0),
+            trail_timeframe: Timeframe::from_str_loose(&env("TRAIL_TF", "")),
+            kill_switch_file: env("KILL_SWITCH_FILE", "KILL_SWITCH"),
+            kill_switch: matches!(
+                env("KILL_SWITCH", "false").to_lowercase().as_str(),
+                "true" | "1"
+            ),
+            kill_switch_flatten: env("KILL_SWITCH_FLATTEN", "false").to_lowercase() == "true",
+            schedule_jitter_secs: env("SCHEDULE_JITTER_SECS", "3").parse().unwrap_or(3.0),
+            telegram_bot_token: env("TELEGRAM_BOT_TOKEN", ""),
+            telegram_chat_id: env("TELEGRAM_CHAT_ID", ""),
+            discord_webhook_url: env("DISCORD_WEBHOOK_URL", ""),
+            api_bind: env("API_BIND", ""),
+            api_token: env("API_TOKEN", ""),
+            fee_rate,      // 0.1% per trade
+            slippage_rate, // 0.05% per trade
+            exit_slippage_rate: env("EXIT_SLIPPAGE_RATE", "").parse().unwrap_or(slippage_rate),
+            execution_model: ExecutionModelKind::parse(&env("EXECUTION_MODEL", "flat"))
+                .unwrap_or_default(),
+            maker_fee_rate: env("MAKER_FEE_RATE", "").parse().unwrap_or(fee_rate),
+            impact_coefficient: env("IMPACT_COEFFICIENT", "0").parse().unwrap_or(0.0),
+            partial_fill_pct: env("PARTIAL_FILL_PCT", "0").parse().unwrap_or(0.0),
+            partial_fill_step: env("PARTIAL_FILL_STEP", "0.0002").parse().unwrap_or(0.0002),
+            tp_alloc_mode: TpAllocMode::parse(&env("TP_ALLOC_MODE", "dynamic")).unwrap_or_default(),
+            tp_alloc_conservative: tp_alloc("TP_ALLOC_CONSERVATIVE")
+                .unwrap_or_else(TpAllocation::conservative),
+            tp_alloc_aggressive: tp_alloc("TP_ALLOC_AGGRESSIVE")
+                .unwrap_or_else(TpAllocation::aggressive),
+            signal_ranking: SignalRanking::parse(&env("SIGNAL_RANKING", "confidence")).unwrap_or_default(),
+            move_to_breakeven_after_tp1: env("MOVE_TO_BREAKEVEN_AFTER_TP1", "false").to_lowercase() == "true",
+            stuck_hold_multiple: env("STUCK_HOLD_MULTIPLE", "3.0").parse().unwrap_or(3.0),
+            stuck_progress_band: env("STUCK_PROGRESS_BAND", "0.2").parse().unwrap_or(0.2),
+            stuck_tighten_stop: env("STUCK_TIGHTEN_STOP", "false").to_lowercase() == "true",
+            fill_timing: FillTiming::parse(&env("FILL_TIMING", "signal_close")).unwrap_or_default(),
+            fill_latency_secs: env("FILL_LATENCY_SECS", "0").parse().unwrap_or(0),
+            backtest_strategy: env("BACKTEST_STRATEGY", "fractal"),
+            strategies: env("STRATEGIES", "")
+                .split(',')
+                .map(|s| s.trim().to_lowercase())
+                .filter(|s| !s.is_empty())
+                .collect(),
+            strategy_risk: {
+                let raw = env("STRATEGY_RISK", "");
+                strategy::parse_risk_budgets(&raw).unwrap_or_else(|| {
+                    tracing::warn!("Invalid STRATEGY_RISK='{}', ignored", raw);
+                    HashMap::new()
+                })
+            },
+            backtest_tick_seconds: env("BACKTEST_TICK_SECONDS", "15").parse().unwrap_or(15),
+            intrabar_ordering: IntrabarOrdering::parse(&env("INTRABAR_ORDERING", "sl_first"))
+                .unwrap_or_default(),
+            replay_bars: env("REPLAY_BARS", "50").parse().unwrap_or(50),
+            ab_bootstrap_runs: env("AB_BOOTSTRAP_RUNS", "5000").parse().unwrap_or(5000),
+            mc_runs: env("MC_RUNS", "5000").parse().unwrap_or(5000),
+            mc_ruin_pct: env("MC_RUIN_PCT", "50").parse().unwrap_or(50.0),
+            mc_seed: env("MC_SEED", "1").parse().unwrap_or(1),
+            opt_min_confidence: env("OPT_MIN_CONFIDENCE", "")
+                .split(',')
+                .filter_map(|s| s.trim().parse().ok())
+                .collect(),
+            opt_fvg_min_gap: env("OPT_FVG_MIN_GAP", "")
+                .split(',')
+                .filter_map(|s| s.trim().parse().ok())
+                .collect(),
+            opt_ob_lookback: env("OPT_OB_LOOKBACK", "")
+                .split(',')
+                .filter_map(|s| s.trim().parse().ok())
+                .collect(),
+            opt_tp_alloc: env("OPT_TP_ALLOC", "")
+                .split(',')
+                .filter_map(TpAllocMode::parse)
+                .collect(),
+            opt_parallelism: env("OPT_PARALLELISM", "").parse().ok(),
+            wf_train_days: env("WF_TRAIN_DAYS", "30").parse().unwrap_or(30),
+            wf_test_days: env("WF_TEST_DAYS", "7").parse().unwrap_or(7),
+            skip_scales: env("SKIP_SCALES", "")
+                .split(',')
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .collect(),
+            data_lookback: env("DATA_LOOKBACK", "").parse().ok(),
+            gap_fill: GapFill::parse(&env("GAP_FILL", "off")).unwrap_or_default(),
+            data_max_stale_bars: env("DATA_MAX_STALE_BARS", "3").parse().unwrap_or(3),
+            cooldown_minutes: env("COOLDOWN_MINUTES", "").parse().ok(),
+            min_tp_multiple: env("MIN_TP_MULTIPLE", "6").parse().unwrap_or(6.0),
+            max_leverage: env("MAX_LEVERAGE", "5").parse().unwrap_or(5.0),
+            max_hold_minutes: env("MAX_HOLD_MINUTES", "180").parse().unwrap_or(180),
+            post_tp_stall_minutes: env("POST_TP_STALL_MINUTES", "120").parse().unwrap_or(120),
+            exhaust_candles: env("EXHAUST_CANDLES", "0").parse().unwrap_or(0),
+            sweep_displacement_atr: env("SWEEP_DISPLACEMENT_ATR", "1.0").parse().unwrap_or(1.0),
+            judas_anchor: Anchor::parse(&env("JUDAS_ANCHOR", "midnight")).unwrap_or_default(),
+            pd_anchor: Anchor::parse(&env("PD_ANCHOR", "equilibrium")),
+            swing_lookback: env("SWING_LOOKBACK", "1").parse().unwrap_or(1),
+            midnight_min_discount: env("MIDNIGHT_MIN_DISCOUNT", "0.001")
+                .parse()
+                .unwrap_or(0.001),
+            midnight_min_rr: env("MIDNIGHT_MIN_RR", "1.5").parse().unwrap_or(1.5),
+            silver_bullet_min_rr: env("SILVER_BULLET_MIN_RR", "2.0").parse().unwrap_or(2.0),
+            expectancy_window: env("EXPECTANCY_WINDOW", "50").parse().unwrap_or(50),
             sessions,
             session_weights,
+            holidays: {
+                let raw = env("HOLIDAYS", "");
+                Holiday::parse_list(&raw).unwrap_or_else(|| {
+                    tracing::warn!("Invalid HOLIDAYS='{}', ignoring", raw);
+                    Vec::new()
+                })
+            },
+            builtin_holidays: env("BUILTIN_HOLIDAYS", "true").to_lowercase() == "true",
+            holiday_half_day_weight: env("HOLIDAY_HALF_DAY_WEIGHT", "0.5").parse().unwrap_or(0.5),
+            trading_days: {
+                let raw = env("TRADING_DAYS", "mon,tue,wed,thu,fri,sat,sun");
+                parse_weekdays(&raw).unwrap_or_else(|| {
+                    tracing::warn!("Invalid TRADING_DAYS='{}', trading every day", raw);
+                    ALL_WEEKDAYS.to_vec()
+                })
+            },
+            blackout_dates: {
+                let raw = env("BLACKOUT_DATES", "");
+                parse_dates(&raw).unwrap_or_else(|| {
+                    tracing::warn!("Invalid BLACKOUT_DATES='{}', ignoring", raw);
+                    Vec::new()
+                })
+            },
+            econ_calendar: env("ECON_CALENDAR", ""),
+            news_blackout_before: env("NEWS_BLACKOUT_BEFORE", "30").parse().unwrap_or(30),
+            news_blackout_after: env("NEWS_BLACKOUT_AFTER", "15").parse().unwrap_or(15),
+            news_tighten_stops: env("NEWS_TIGHTEN_STOPS", "false").to_lowercase() == "true",
+            news_currencies: env("NEWS_CURRENCIES", "USD")
+                .split(',')
+                .map(|c| c.trim().to_uppercase())
+                .filter(|c| !c.is_empty())
+                .collect(),
+            killzone_profiles: {
+                let raw = env("KILLZONE_PROFILES", "");
+                KillzoneProfile::parse_list(&raw).unwrap_or_else(|| {
+                    tracing::warn!("Invalid KILLZONE_PROFILES='{}', ignoring", raw);
+                    HashMap::new()
+                })
+            },
+            quarter_mode: QuarterMode::parse(&env("QUARTER_MODE", "off")).unwrap_or_default(),
+            quarter_off_weight: env("QUARTER_OFF_WEIGHT", "0.8").parse().unwrap_or(0.8),
             hft_scales,
             cross_scale_confluence_bonus: 0.1,
+            volume_profile_bins: env("VOLUME_PROFILE_BINS", "40").parse().unwrap_or(40),
+            volume_confluence_bonus: env("VOLUME_CONFLUENCE_BONUS", "0.05").parse().unwrap_or(0.05),
+            smt_pairs: {
+                let raw = env("SMT_PAIRS", "");
+                smt::parse_pairs(&raw).unwrap_or_else(|| {
+                    tracing::warn!("Invalid SMT_PAIRS='{}', SMT disabled", raw);
+                    HashMap::new()
+                })
+            },
+            smt_weight: env("SMT_WEIGHT", "0.1").parse().unwrap_or(0.1),
+            smt_required: env("SMT_REQUIRED", "false").to_lowercase() == "true",
+            ote_entries: env("OTE_ENTRIES", "false").to_lowercase() == "true",
+            ote_expiry_minutes: env("OTE_EXPIRY_MINUTES", "60").parse().unwrap_or(60),
             day_ratings,
             min_day_rating: 3.0,
             fvg_min_gap_percent: env("FVG_MIN_GAP", "0.0005").parse().unwrap_or(0.0005),
             ob_lookback: env("OB_LOOKBACK", "20").parse().unwrap_or(20),
             breaker_lookback: env("BREAKER_LOOKBACK", "30").parse().unwrap_or(30),
+            vi_min_gap_percent: env("VI_MIN_GAP", "0.0002").parse().unwrap_or(0.0002),
+            void_min_candles: env("VOID_MIN_CANDLES", "3").parse().unwrap_or(3),
+            void_min_move_percent: env("VOID_MIN_MOVE", "0.005").parse().unwrap_or(0.005),
+            opening_gaps_keep: env("OPENING_GAPS_KEEP", "5").parse().unwrap_or(5),
             tgif_retrace_min: 0.20,
             tgif_retrace_max: 0.30,
             analysis_interval: 3600,
             min_sample_per_bucket: 10,
             adjustment_step: 0.02,
+            skip_expiry_days: env("SKIP_EXPIRY_DAYS", "14").parse().unwrap_or(14.0),
+            skip_retry_size: env("SKIP_RETRY_SIZE", "0.5").parse().unwrap_or(0.5),
+            storage_backend: StorageBackend::parse(&env("STORAGE_BACKEND", "json")).unwrap_or_default(),
             log_dir: "logs".to_string(),
             log_level: "INFO".to_string(),
+            account: "default".to_string(),
+        }
+    }
+
+    /// Effective settings as JSON with credentials blanked, for recording
+    /// next to results. Keys are sorted, so equal configs serialize alike.
+    pub fn snapshot(&self) -> Value {
+        let mut cfg = self.clone();
+        for secret in [
+            &mut cfg.coinbase_api_key,
+            &mut cfg.coinbase_api_secret,
+            &mut cfg.telegram_bot_token,
+            &mut cfg.discord_webhook_url,
+            &mut cfg.api_token,
+        ] {
+            secret.clear();
+        }
+        serde_json::to_value(cfg).unwrap_or(Value::Null)
+    }
+
+    /// FNV-1a hash of the snapshot in hex: two runs with the same hash
+    /// used identical settings.
+    pub fn config_hash(&self) -> String {
+        format!("{:016x}", fnv1a(self.snapshot().to_string().as_bytes()))
+    }
+
+    /// Config from a TOML profile: any subset of the fields, tables merged
+    /// key by key over the defaults. Settings given in the environment (or
+    /// `.env`) still take precedence over the file.
+    pub fn from_file(path: &str) -> Result<Self> {
+        let raw = std::fs::read_to_string(path).with_context(|| format!("reading {}", path))?;
+        let file: toml::Table =
+            toml::from_str(&raw).with_context(|| format!("parsing {}", path))?;
+        let file = serde_json::to_value(file)?;
+
+        let defaults = serde_json::to_value(Self::from_vars(|_| None))?;
+        let from_env = serde_json::to_value(Self::from_env())?;
+        if let (Value::Object(keys), Value::Object(known)) = (&file, &defaults) {
+            for key in keys.keys().filter(|k| !known.contains_key(*k)) {
+                tracing::warn!("Unknown setting '{}' in {}, ignoring", key, path);
+            }
+        }
+        let mut merged = defaults.clone();
+        merge(&mut merged, &file);
+        overlay_changed(&mut merged, &from_env, &defaults);
+
+        let mut cfg: Config = serde_json::from_value(merged)
+            .with_context(|| format!("invalid config in {}", path))?;
+        if let Some(first) = cfg.symbols.first() {
+            if !cfg.symbols.contains(&cfg.symbol) {
+                cfg.symbol = first.clone();
+            }
+        }
+        let allocs = [&cfg.tp_alloc_conservative, &cfg.tp_alloc_aggressive]
+            .into_iter()
+            .chain(cfg.hft_scales.values().flat_map(|s| {
+                [&s.tp_alloc_conservative, &s.tp_alloc_aggressive]
+                    .into_iter()
+                    .flatten()
+            }));
+        for alloc in allocs {
+            if !alloc.is_valid() {
+                bail!(
+                    "invalid TP allocation {:?} in {} (shares must sum to 1.0)",
+                    alloc.0,
+                    path
+                );
+            }
+        }
+        Ok(cfg)
+    }
+
+    /// Config from `--config <path>` in `args` (removed from them), else
+    /// from the environment alone.
+    pub fn from_args(args: &mut Vec<String>) -> Result<Self> {
+        let Some(i) = args.iter().position(|a| a == "--config") else {
+            return Ok(Self::from_env());
+        };
+        if i + 1 >= args.len() {
+            bail!("--config needs a file path");
+        }
+        let path: Vec<String> = args.drain(i..i + 2).collect();
+        Self::from_file(&path[1])
+    }
+
+    /// One config per logical account. ACCOUNTS="conservative,aggressive"
+    /// gives each its own state dir under log_dir; unset = this config only.
+    pub fn accounts(&self) -> Vec<Config> {
+        let names: Vec<String> = std::env::var("ACCOUNTS")
+            .unwrap_or_default()
+            .split(',')
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+            .collect();
+        if names.is_empty() {
+            return vec![self.clone()];
         }
+        names.iter().map(|n| self.for_account(n)).collect()
+    }
+
+    /// Derive an account config: isolated log_dir plus ACCOUNT_<NAME>_* overrides
+    /// (INITIAL_BALANCE, MAX_DAILY_LOSS, MAX_OPEN_POSITIONS, MIN_CONFIDENCE, API_BIND).
+    pub fn for_account(&self, name: &str) -> Config {
+        let mut cfg = self.clone();
+        cfg.account = name.to_string();
+        cfg.log_dir = format!("{}/{}", self.log_dir, name);
+
+        let prefix = format!("ACCOUNT_{}_", name.to_uppercase().replace('-', "_"));
+        let var = |key: &str| std::env::var(format!("{}{}", prefix, key)).ok();
+        if let Some(v) = var("INITIAL_BALANCE").and_then(|s| s.parse().ok()) {
+            cfg.initial_balance = v;
+        }
+        if let Some(v) = var("MAX_DAILY_LOSS").and_then(|s| s.parse().ok()) {
+            cfg.max_daily_loss = v;
+        }
+        if let Some(v) = var("MAX_OPEN_POSITIONS").and_then(|s| s.parse().ok()) {
+            cfg.max_open_positions = v;
+        }
+        if let Some(v) = var("MIN_CONFIDENCE").and_then(|s| s.parse::<f64>().ok()) {
+            for scale in cfg.hft_scales.values_mut() {
+                scale.min_confidence = v;
+            }
+        }
+        if let Some(v) = var("API_BIND") {
+            cfg.api_bind = v;
+        }
+        cfg
+    }
+
+    /// Config for a single product; exchange clients are built per symbol.
+    pub fn for_symbol(&self, symbol: &str) -> Config {
+        let mut cfg = self.clone();
+        cfg.symbol = symbol.to_string();
+        cfg
+    }
+
+    /// Risk multiplier for signals of `strategy` (STRATEGY_RISK, else 1.0).
+    pub fn strategy_risk(&self, strategy: &str) -> f64 {
+        self.strategy_risk.get(strategy).copied().unwrap_or(1.0)
+    }
+
+    /// Tick and lot size for `symbol` (PRECISION override or built-in table).
+    pub fn precision(&self, symbol: &str) -> Precision {
+        Precision::resolve(&self.precision, symbol)
+    }
+
+    /// Spot or perp for `symbol` (INSTRUMENTS override or inferred).
+    pub fn instrument(&self, symbol: &str) -> Instrument {
+        Instrument::resolve(&self.instruments, symbol)
+    }
+
+    /// Every timeframe the scales read, so data refreshes can include
+    /// ones (e.g. 3m, 1w) outside the fixed set.
+    pub fn scale_timeframes(&self) -> Vec<Timeframe> {
+        let mut tfs = Vec::new();
+        for scale in self.hft_scales.values() {
+            let rules = scale.alignment_rules.iter().flat_map(|r| r.timeframes());
+            for tf in [scale.entry_tf, scale.structure_tf, scale.confirm_tf]
+                .into_iter()
+                .chain(scale.alignment_tfs.iter().copied())
+                .chain(rules)
+            {
+                if !tfs.contains(&tf) {
+                    tfs.push(tf);
+                }
+            }
+        }
+        tfs
+    }
+
+    /// Minimum confidence for `scale` signals during `session`.
+    pub fn min_confidence(&self, scale: &str, session: &str) -> f64 {
+        self.killzone_profiles
+            .get(session)
+            .and_then(|k| k.min_confidence)
+            .or_else(|| self.hft_scales.get(scale).map(|s| s.min_confidence))
+            .unwrap_or(f64::INFINITY)
+    }
+
+    /// Partial-TP table for a new position on `scale` during `session`:
+    /// aggressive or conservative per `tp_alloc_mode` (the killzone's when
+    /// it overrides it, and CISD when dynamic), the scale's override when
+    /// it has one.
+    pub fn tp_allocation(&self, scale: &str, session: &str, cisd: bool) -> &TpAllocation {
+        let mode = self
+            .killzone_profiles
+            .get(session)
+            .and_then(|k| k.tp_alloc_mode)
+            .unwrap_or(self.tp_alloc_mode);
+        let aggressive = match mode {
+            TpAllocMode::Dynamic => cisd,
+            TpAllocMode::Conservative => false,
+            TpAllocMode::Aggressive => true,
+        };
+        let scale_cfg = self.hft_scales.get(scale);
+        if aggressive {
+            scale_cfg
+                .and_then(|s| s.tp_alloc_aggressive.as_ref())
+                .unwrap_or(&self.tp_alloc_aggressive)
+        } else {
+            scale_cfg
+                .and_then(|s| s.tp_alloc_conservative.as_ref())
+                .unwrap_or(&self.tp_alloc_conservative)
+        }
+    }
+
+    /// Correlated symbol `symbol` is compared against for SMT divergence.
+    pub fn smt_peer(&self, symbol: &str) -> Option<&str> {
+        self.smt_pairs.get(&symbol.to_uppercase()).map(String::as_str)
     }
 
     pub fn shared(self) -> SharedConfig {
         Arc::new(RwLock::new(self))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn file_settings_merge_over_defaults_under_env() {
+        let path = std::env::temp_dir().join(format!("ict_profile_{}.toml", std::process::id()));
+        std::fs::write(
+            &path,
+            r#"
+symbols = ["ETH-USD", "SOL-USD"]
+ote_expiry_minutes = 30
+smt_weight = 0.2
+tp_alloc_conservative = [[-1.0, 0.5], [-2.0, 0.5]]
+opt_ob_lookback = [10, 20]
+
+[sessions.london]
+start = [3, 0]
+end = [5, 30]
+
+[hft_scales.5m]
+min_confidence = 0.65
+
+[day_ratings.classic_expansion]
+wednesday = 4.0
+"#,
+        )
+        .unwrap();
+        std::env::set_var("SMT_WEIGHT", "0.3");
+        let cfg = Config::from_file(path.to_str().unwrap()).unwrap();
+        std::env::remove_var("SMT_WEIGHT");
+
+        assert_eq!(cfg.symbol, "ETH-USD");
+        assert_eq!(cfg.ote_expiry_minutes, 30);
+        assert_eq!(cfg.smt_weight, 0.3);
+        assert_eq!(cfg.tp_alloc_conservative.share(-2.0), Some(0.5));
+        assert_eq!(cfg.opt_ob_lookback, vec![10, 20]);
+        assert_eq!(cfg.sessions["london"].start, (3, 0));
+        assert_eq!(cfg.sessions["asian"].start, (20, 0));
+        assert_eq!(cfg.hft_scales["5m"].min_confidence, 0.65);
+        assert_eq!(cfg.hft_scales["5m"].scan_interval, 30);
+        assert_eq!(cfg.day_ratings["classic_expansion"].get("Wednesday"), 4.0);
+        assert_eq!(cfg.day_ratings["classic_expansion"].get("Thursday"), 4.5);
+
+        std::fs::write(&path, "tp_alloc_aggressive = [[-1.0, 0.5]]\n").unwrap();
+        assert!(Config::from_file(path.to_str().unwrap()).is_err());
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn killzone_profiles_override_thresholds() {
+        let mut cfg = crate::test_helpers::default_test_config();
+        cfg.killzone_profiles =
+            KillzoneProfile::parse_list("london:tp=aggressive:risk=0.03, ny_indices:conf=0.8")
+                .unwrap();
+        assert_eq!(cfg.killzone_profiles["london"].max_risk_pct, Some(0.03));
+        assert!(KillzoneProfile::parse_list("london:size=2").is_none());
+
+        assert_eq!(cfg.min_confidence("5m", "ny_indices"), 0.8);
+        assert_eq!(cfg.min_confidence("5m", "london"), 0.45);
+        assert_eq!(cfg.min_confidence("2h", "london"), f64::INFINITY);
+
+        // London runs the aggressive table even without CISD
+        assert_eq!(
+            cfg.tp_allocation("5m", "london", false),
+            &cfg.tp_alloc_aggressive
+        );
+        assert_eq!(
+            cfg.tp_allocation("5m", "ny_indices", false),
+            &cfg.tp_alloc_conservative
+        );
+    }
+
+    #[test]
+    fn config_hash_tracks_settings_but_not_credentials() {
+        let cfg = Config::from_vars(|_| None);
+        let mut same = cfg.clone();
+        same.coinbase_api_key = "key".to_string();
+        assert_eq!(cfg.config_hash(), same.config_hash());
+        assert_eq!(same.snapshot()["coinbase_api_key"], "");
+
+        let mut other = cfg.clone();
+        other.min_tp_multiple = 4.0;
+        assert_ne!(cfg.config_hash(), other.config_hash());
+        assert_eq!(cfg.config_hash().len(), 16);
+    }
+}
@@ -0,0 +1,231 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use tracing::{info, warn};
+
+use crate::config::Config;
+
+/// Fields never written to logs or history.
+const SECRET_FIELDS: [&str; 5] = [
+    "coinbase_api_key",
+    "coinbase_api_secret",
+    "telegram_bot_token",
+    "discord_webhook_url",
+    "api_token",
+];
+
+/// One changed field: dotted path (e.g. `hft_scales.5m.min_confidence`).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConfigChange {
+    pub field: String,
+    pub old: Value,
+    pub new: Value,
+}
+
+/// A set of changes applied together, and who made them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigChangeEvent {
+    pub time: DateTime<Utc>,
+    /// `refiner`, `restart`, `api`, ...
+    pub source: String,
+    /// `Config::config_hash` after the change
+    pub revision: String,
+    pub changes: Vec<ConfigChange>,
+}
+
+/// Field-level differences between two configs, secrets excluded.
+pub fn diff(old: &Config, new: &Config) -> Vec<ConfigChange> {
+    match serde_json::to_value(old) {
+        Ok(old) => diff_json(&old, new),
+        Err(_) => Vec::new(),
+    }
+}
+
+/// Like `diff`, against a config already serialized (e.g. a snapshot written
+/// by an older build, which may lack newer fields).
+fn diff_json(old: &Value, new: &Config) -> Vec<ConfigChange> {
+    let Ok(new) = serde_json::to_value(new) else {
+        return Vec::new();
+    };
+    let mut out = Vec::new();
+    diff_values("", old, &new, &mut out);
+    out.retain(|c| !SECRET_FIELDS.contains(&c.field.as_str()));
+    out
+}
+
+fn diff_values(path: &str, old: &Value, new: &Value, out: &mut Vec<ConfigChange>) {
+    match (old, new) {
+        (Value::Object(a), Value::Object(b)) => {
+            let mut keys: Vec<&String> = a.keys().chain(b.keys()).collect();
+            keys.sort();
+            keys.dedup();
+            for key in keys {
+                let child = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{}.{}", path, key)
+                };
+                diff_values(
+                    &child,
+                    a.get(key).unwrap_or(&Value::Null),
+                    b.get(key).unwrap_or(&Value::Null),
+                    out,
+                );
+            }
+        }
+        _ if old != new => out.push(ConfigChange {
+            field: path.to_string(),
+            old: old.clone(),
+            new: new.clone(),
+        }),
+        _ => {}
+    }
+}
+
+/// Append-only log of config changes (`config_history.jsonl`) plus a
+/// snapshot of the last effective config, so changes made between restarts
+/// are caught too.
+pub struct ConfigHistory {
+    history_file: PathBuf,
+    snapshot_file: PathBuf,
+}
+
+impl ConfigHistory {
+    pub fn new(cfg: &Config) -> Self {
+        Self {
+            history_file: PathBuf::from(format!("{}/config_history.jsonl", cfg.log_dir)),
+            snapshot_file: PathBuf::from(format!("{}/config_snapshot.json", cfg.log_dir)),
+        }
+    }
+
+    /// Log and persist the difference between `old` and `new`. Returns the
+    /// event, or None when nothing changed.
+    pub fn record(&self, source: &str, old: &Config, new: &Config) -> Option<ConfigChangeEvent> {
+        self.record_changes(source, diff(old, new), new)
+    }
+
+    fn record_changes(
+        &self,
+        source: &str,
+        changes: Vec<ConfigChange>,
+        new: &Config,
+    ) -> Option<ConfigChangeEvent> {
+        if changes.is_empty() {
+            return None;
+        }
+        let event = ConfigChangeEvent {
+            time: Utc::now(),
+            source: source.to_string(),
+            revision: new.config_hash(),
+            changes,
+        };
+        info!(
+            "Config changed by {} ({} fields, revision {})",
+            event.source,
+            event.changes.len(),
+            event.revision
+        );
+        for c in &event.changes {
+            info!("  {}: {} -> {}", c.field, c.old, c.new);
+        }
+        if let Err(e) = self.append(&event) {
+            warn!("Failed to write config history: {:#}", e);
+        }
+        self.save_snapshot(new);
+        Some(event)
+    }
+
+    /// Compare against the config the previous run ended with and record any
+    /// difference as a `restart` change.
+    pub fn record_startup(&self, cfg: &Config) -> Option<ConfigChangeEvent> {
+        let previous = fs::read_to_string(&self.snapshot_file)
+            .ok()
+            .and_then(|s| serde_json::from_str::<Value>(&s).ok());
+        match previous {
+            Some(prev) => self.record_changes("restart", diff_json(&prev, cfg), cfg),
+            None => {
+                self.save_snapshot(cfg);
+                None
+            }
+        }
+    }
+
+    /// All recorded events, oldest first.
+    pub fn load(&self) -> Vec<ConfigChangeEvent> {
+        fs::read_to_string(&self.history_file)
+            .unwrap_or_default()
+            .lines()
+            .filter_map(|l| serde_json::from_str(l).ok())
+            .collect()
+    }
+
+    fn append(&self, event: &ConfigChangeEvent) -> anyhow::Result<()> {
+        if let Some(dir) = self.history_file.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.history_file)?;
+        writeln!(file, "{}", serde_json::to_string(event)?)?;
+        Ok(())
+    }
+
+    fn save_snapshot(&self, cfg: &Config) {
+        let mut redacted = cfg.clone();
+        redacted.coinbase_api_key.clear();
+        redacted.coinbase_api_secret.clear();
+        redacted.telegram_bot_token.clear();
+        redacted.discord_webhook_url.clear();
+        redacted.api_token.clear();
+        if let Some(dir) = self.snapshot_file.parent() {
+            let _ = fs::create_dir_all(dir);
+        }
+        if let Ok(json) = serde_json::to_string_pretty(&redacted) {
+            let _ = fs::write(&self.snapshot_file, json);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::default_test_config;
+
+    #[test]
+    fn records_field_diffs_and_restart_changes() {
+        let mut cfg = default_test_config();
+        let dir = std::env::temp_dir().join(format!("ict_cfg_hist_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        cfg.log_dir = dir.to_string_lossy().to_string();
+        let history = ConfigHistory::new(&cfg);
+
+        // First start only takes a snapshot
+        assert!(history.record_startup(&cfg).is_none());
+
+        let mut refined = cfg.clone();
+        refined.hft_scales.get_mut("5m").unwrap().min_confidence = 0.65;
+        refined.coinbase_api_secret = "secret".to_string();
+        let event = history.record("refiner", &cfg, &refined).unwrap();
+        assert_eq!(event.changes.len(), 1);
+        assert_eq!(event.changes[0].field, "hft_scales.5m.min_confidence");
+        assert_eq!(event.changes[0].new, serde_json::json!(0.65));
+        assert!(history.record("refiner", &refined, &refined).is_none());
+
+        // Restart with a different env: diffed against the last snapshot
+        let mut restarted = refined.clone();
+        restarted.ob_lookback += 5;
+        let event = history.record_startup(&restarted).unwrap();
+        assert_eq!(event.source, "restart");
+        assert_eq!(event.changes[0].field, "ob_lookback");
+
+        let events = history.load();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].source, "refiner");
+        assert_eq!(events[1].revision, restarted.config_hash());
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
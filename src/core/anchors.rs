@@ -0,0 +1,190 @@
+use chrono::{DateTime, NaiveDate, TimeZone, Utc};
+use chrono_tz::US::Eastern;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::models::CandleSeries;
+
+/// Opening prices the day is measured against.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Anchor {
+    /// 00:00 ET, the true day open
+    #[default]
+    Midnight,
+    /// 08:30 ET, NY open / news release
+    NyOpen,
+    /// 09:30 ET, equities open
+    EquitiesOpen,
+}
+
+impl Anchor {
+    pub const ALL: [Anchor; 3] = [Anchor::Midnight, Anchor::NyOpen, Anchor::EquitiesOpen];
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "midnight" | "0000" | "00:00" => Some(Anchor::Midnight),
+            "ny_open" | "0830" | "08:30" => Some(Anchor::NyOpen),
+            "equities_open" | "0930" | "09:30" => Some(Anchor::EquitiesOpen),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Anchor::Midnight => "midnight",
+            Anchor::NyOpen => "ny_open",
+            Anchor::EquitiesOpen => "equities_open",
+        }
+    }
+
+    /// ET (hour, minute) of the anchor.
+    pub fn time_et(&self) -> (u32, u32) {
+        match self {
+            Anchor::Midnight => (0, 0),
+            Anchor::NyOpen => (8, 30),
+            Anchor::EquitiesOpen => (9, 30),
+        }
+    }
+
+    /// The anchor's instant on ET `date`.
+    pub fn at(&self, date: NaiveDate) -> Option<DateTime<Utc>> {
+        let (h, m) = self.time_et();
+        Eastern
+            .from_local_datetime(&date.and_hms_opt(h, m, 0)?)
+            .earliest()
+            .map(|t| t.with_timezone(&Utc))
+    }
+}
+
+impl fmt::Display for Anchor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (h, m) = self.time_et();
+        write!(f, "{:02}:{:02} open", h, m)
+    }
+}
+
+/// One ET day's anchor prices; `None` until a candle opens at the anchor.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DailyAnchors {
+    pub date: NaiveDate,
+    pub midnight: Option<f64>,
+    pub ny_open: Option<f64>,
+    pub equities_open: Option<f64>,
+}
+
+impl DailyAnchors {
+    pub fn new(date: NaiveDate) -> Self {
+        Self {
+            date,
+            midnight: None,
+            ny_open: None,
+            equities_open: None,
+        }
+    }
+
+    pub fn get(&self, anchor: Anchor) -> Option<f64> {
+        match anchor {
+            Anchor::Midnight => self.midnight,
+            Anchor::NyOpen => self.ny_open,
+            Anchor::EquitiesOpen => self.equities_open,
+        }
+    }
+
+    fn slot(&mut self, anchor: Anchor) -> &mut Option<f64> {
+        match anchor {
+            Anchor::Midnight => &mut self.midnight,
+            Anchor::NyOpen => &mut self.ny_open,
+            Anchor::EquitiesOpen => &mut self.equities_open,
+        }
+    }
+}
+
+/// Anchor prices of the current ET day, read from the open of the candle
+/// stamped exactly at each anchor. Found prices are kept until the day
+/// rolls; missing ones are retried as new candles arrive.
+#[derive(Debug, Clone, Default)]
+pub struct AnchorCache {
+    day: Option<DailyAnchors>,
+}
+
+impl AnchorCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Anchors for the last candle's ET day.
+    pub fn update(&mut self, candles: &CandleSeries) -> Option<&DailyAnchors> {
+        let last = candles.last()?.timestamp;
+        let date = last.with_timezone(&Eastern).date_naive();
+        if self.day.as_ref().map(|d| d.date) != Some(date) {
+            self.day = Some(DailyAnchors::new(date));
+        }
+        let day = self.day.as_mut()?;
+        let slice = candles.as_slice();
+        for anchor in Anchor::ALL {
+            if day.get(anchor).is_some() {
+                continue;
+            }
+            let Some(t) = anchor.at(date).filter(|t| *t <= last) else {
+                continue;
+            };
+            let i = slice.partition_point(|c| c.timestamp < t);
+            if let Some(c) = slice.get(i).filter(|c| c.timestamp == t) {
+                *day.slot(anchor) = Some(c.open);
+            }
+        }
+        self.day.as_ref()
+    }
+
+    /// Cached anchors without re-reading candles.
+    pub fn current(&self) -> Option<&DailyAnchors> {
+        self.day.as_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Candle;
+    use chrono::Duration;
+
+    fn candle_at(t: DateTime<Utc>, open: f64) -> Candle {
+        Candle {
+            timestamp: t,
+            open,
+            high: open + 1.0,
+            low: open - 1.0,
+            close: open,
+            volume: 1.0,
+        }
+    }
+
+    #[test]
+    fn anchors_fill_in_as_the_day_reaches_them_and_reset_on_rollover() {
+        let date = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+        let midnight = Anchor::Midnight.at(date).unwrap();
+        let quarter_hour =
+            |i: i64| candle_at(midnight + Duration::minutes(15 * i), 100.0 + i as f64);
+        // 15m candles from 00:00 to 09:00 ET
+        let mut candles: Vec<Candle> = (0..37).map(quarter_hour).collect();
+
+        let mut cache = AnchorCache::new();
+        let day = cache.update(&CandleSeries::new(candles.clone())).unwrap();
+        assert_eq!(day.get(Anchor::Midnight), Some(100.0));
+        // 08:30 is 34 quarter hours in; 09:30 not reached yet
+        assert_eq!(day.get(Anchor::NyOpen), Some(134.0));
+        assert_eq!(day.get(Anchor::EquitiesOpen), None);
+
+        candles.extend((37..40).map(quarter_hour));
+        let day = cache.update(&CandleSeries::new(candles.clone())).unwrap();
+        assert_eq!(day.get(Anchor::EquitiesOpen), Some(138.0));
+
+        // Next ET day starts empty
+        let next = Anchor::Midnight.at(date.succ_opt().unwrap()).unwrap();
+        candles.push(candle_at(next + Duration::minutes(15), 90.0));
+        let day = cache.update(&CandleSeries::new(candles)).unwrap();
+        assert_eq!(day.date, date.succ_opt().unwrap());
+        assert_eq!(day.get(Anchor::Midnight), None);
+    }
+}
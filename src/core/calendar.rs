@@ -0,0 +1,342 @@
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::US::Eastern;
+use reqwest::Client;
+use serde::{Deserialize, Deserializer, Serialize};
+use std::path::PathBuf;
+use tracing::{info, warn};
+
+use crate::config::Config;
+
+/// Expected market impact of a release.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Impact {
+    Low,
+    Medium,
+    High,
+}
+
+impl Impact {
+    /// Accepts names, 1-3 and the red/orange/yellow colour codes.
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "low" | "1" | "yellow" => Some(Impact::Low),
+            "medium" | "med" | "2" | "orange" => Some(Impact::Medium),
+            "high" | "3" | "red" => Some(Impact::High),
+            _ => None,
+        }
+    }
+}
+
+/// One scheduled release (CPI, FOMC, NFP, ...).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EconEvent {
+    #[serde(deserialize_with = "event_time")]
+    pub time: DateTime<Utc>,
+    pub name: String,
+    pub impact: Impact,
+    /// Currency or region affected (e.g. "USD"), when the source gives it
+    #[serde(default)]
+    pub currency: Option<String>,
+}
+
+/// RFC 3339, or `YYYY-MM-DD HH:MM` in ET (how most calendars publish).
+fn parse_time(s: &str) -> Option<DateTime<Utc>> {
+    let s = s.trim();
+    if let Ok(t) = DateTime::parse_from_rfc3339(s) {
+        return Some(t.with_timezone(&Utc));
+    }
+    let naive = NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M").ok()?;
+    Eastern
+        .from_local_datetime(&naive)
+        .earliest()
+        .map(|t| t.with_timezone(&Utc))
+}
+
+fn event_time<'de, D: Deserializer<'de>>(d: D) -> Result<DateTime<Utc>, D::Error> {
+    let s = String::deserialize(d)?;
+    parse_time(&s).ok_or_else(|| serde::de::Error::custom(format!("bad event time '{}'", s)))
+}
+
+/// CSV with a `time,name,impact[,currency]` header; quoted fields may hold commas.
+fn parse_csv(text: &str) -> Result<Vec<EconEvent>> {
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_reader(text.as_bytes());
+    let header: Vec<String> = reader
+        .headers()
+        .context("Invalid calendar CSV header")?
+        .iter()
+        .map(str::to_lowercase)
+        .collect();
+    if header.iter().all(String::is_empty) {
+        bail!("Empty calendar CSV");
+    }
+    let col = |name: &str| header.iter().position(|h| h == name);
+    let (Some(time_col), Some(name_col), Some(impact_col)) =
+        (col("time"), col("name"), col("impact"))
+    else {
+        bail!("Calendar CSV needs time, name and impact columns");
+    };
+    let currency_col = col("currency");
+
+    let mut events = Vec::new();
+    for (i, record) in reader.records().enumerate() {
+        let record = record.with_context(|| format!("Calendar row {}", i + 1))?;
+        if record.iter().all(str::is_empty) {
+            continue;
+        }
+        let field = |c: usize| record.get(c).unwrap_or("");
+        let time = parse_time(field(time_col))
+            .with_context(|| format!("Calendar row {}: bad time", i + 1))?;
+        let impact = Impact::parse(field(impact_col))
+            .with_context(|| format!("Calendar row {}: bad impact", i + 1))?;
+        events.push(EconEvent {
+            time,
+            name: field(name_col).to_string(),
+            impact,
+            currency: currency_col
+                .map(field)
+                .filter(|c| !c.is_empty())
+                .map(str::to_string),
+        });
+    }
+    Ok(events)
+}
+
+/// JSON array of events, else CSV.
+fn parse_events(text: &str) -> Result<Vec<EconEvent>> {
+    if text.trim_start().starts_with('[') {
+        serde_json::from_str(text).context("Invalid calendar JSON")
+    } else {
+        parse_csv(text)
+    }
+}
+
+/// Where economic events come from.
+#[async_trait]
+pub trait CalendarSource: Send + Sync {
+    fn name(&self) -> String;
+    async fn fetch(&self) -> Result<Vec<EconEvent>>;
+}
+
+/// Local CSV or JSON file.
+pub struct FileSource {
+    path: PathBuf,
+}
+
+impl FileSource {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    pub fn read(&self) -> Result<Vec<EconEvent>> {
+        let text = std::fs::read_to_string(&self.path)
+            .with_context(|| format!("Reading calendar {}", self.path.display()))?;
+        parse_events(&text)
+    }
+}
+
+#[async_trait]
+impl CalendarSource for FileSource {
+    fn name(&self) -> String {
+        self.path.display().to_string()
+    }
+
+    async fn fetch(&self) -> Result<Vec<EconEvent>> {
+        self.read()
+    }
+}
+
+/// HTTP endpoint serving the same CSV or JSON.
+pub struct HttpSource {
+    url: String,
+    client: Client,
+}
+
+impl HttpSource {
+    pub fn new(url: &str) -> Self {
+        Self {
+            url: url.to_string(),
+            client: Client::new(),
+        }
+    }
+}
+
+#[async_trait]
+impl CalendarSource for HttpSource {
+    fn name(&self) -> String {
+        self.url.clone()
+    }
+
+    async fn fetch(&self) -> Result<Vec<EconEvent>> {
+        let resp = self.client.get(&self.url).send().await?;
+        if !resp.status().is_success() {
+            bail!("Calendar {}: {}", self.url, resp.status());
+        }
+        parse_events(&resp.text().await?)
+    }
+}
+
+/// Source for ECON_CALENDAR: an http(s) URL or a file path; `None` if empty.
+pub fn source_from(spec: &str) -> Option<Box<dyn CalendarSource>> {
+    let spec = spec.trim();
+    if spec.is_empty() {
+        None
+    } else if is_url(spec) {
+        Some(Box::new(HttpSource::new(spec)))
+    } else {
+        Some(Box::new(FileSource::new(spec)))
+    }
+}
+
+fn is_url(spec: &str) -> bool {
+    spec.starts_with("http://") || spec.starts_with("https://")
+}
+
+/// Calendar from ECON_CALENDAR limited to NEWS_CURRENCIES; `None` when
+/// unset or unreadable (logged).
+pub async fn load(cfg: &Config) -> Option<EconCalendar> {
+    let source = source_from(&cfg.econ_calendar)?;
+    let calendar = loaded(cfg, &*source, source.fetch().await)?;
+    info!(
+        "Economic calendar: {} events from {}",
+        calendar.len(),
+        source.name()
+    );
+    Some(calendar)
+}
+
+/// [`load`] without the network, for backtests: URLs are skipped.
+pub fn load_local(cfg: &Config) -> Option<EconCalendar> {
+    let spec = cfg.econ_calendar.trim();
+    if spec.is_empty() {
+        return None;
+    }
+    if is_url(spec) {
+        warn!("ECON_CALENDAR URL ignored in backtests — use a local file");
+        return None;
+    }
+    let source = FileSource::new(spec);
+    loaded(cfg, &source, source.read())
+}
+
+fn loaded(
+    cfg: &Config,
+    source: &dyn CalendarSource,
+    events: Result<Vec<EconEvent>>,
+) -> Option<EconCalendar> {
+    match events {
+        Ok(events) => Some(EconCalendar::new(events).for_currencies(&cfg.news_currencies)),
+        Err(e) => {
+            warn!("Economic calendar {} unavailable: {:#}", source.name(), e);
+            None
+        }
+    }
+}
+
+/// Loaded events, sorted by time.
+#[derive(Debug, Clone, Default)]
+pub struct EconCalendar {
+    events: Vec<EconEvent>,
+}
+
+impl EconCalendar {
+    pub fn new(mut events: Vec<EconEvent>) -> Self {
+        events.sort_by_key(|e| e.time);
+        Self { events }
+    }
+
+    /// Keeps events for `currencies` (case-insensitive) and those with no
+    /// currency; an empty list keeps everything.
+    pub fn for_currencies(mut self, currencies: &[String]) -> Self {
+        if !currencies.is_empty() {
+            self.events.retain(|e| {
+                e.currency
+                    .as_deref()
+                    .is_none_or(|c| currencies.iter().any(|k| k.eq_ignore_ascii_case(c)))
+            });
+        }
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// High-impact event starting within `minutes` after `now`.
+    pub fn high_impact_within(&self, now: DateTime<Utc>, minutes: i64) -> Option<&EconEvent> {
+        self.blackout(now, minutes, 0)
+    }
+
+    /// High-impact event whose window (`before` minutes ahead of it to
+    /// `after` minutes past it) contains `now`.
+    pub fn blackout(&self, now: DateTime<Utc>, before: i64, after: i64) -> Option<&EconEvent> {
+        let from = now - Duration::minutes(after);
+        let start = self.events.partition_point(|e| e.time < from);
+        self.events[start..]
+            .iter()
+            .take_while(|e| e.time <= now + Duration::minutes(before))
+            .find(|e| e.impact == Impact::High)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn csv_events_open_a_blackout_around_high_impact_only() {
+        let csv = "time,name,impact,currency\n\
+                   2024-03-12 08:30,CPI,high,USD\n\
+                   2024-03-12 10:00,Wholesale Inventories,low,USD\n\
+                   2024-03-20T18:00:00Z,FOMC,3,\n";
+        let calendar = EconCalendar::new(parse_events(csv).unwrap());
+        assert_eq!(calendar.len(), 3);
+
+        // 08:30 ET in March (EDT) is 12:30 UTC
+        let cpi = Utc.with_ymd_and_hms(2024, 3, 12, 12, 30, 0).unwrap();
+        let at = |minutes: i64| cpi + Duration::minutes(minutes);
+        let hit = calendar.blackout(at(-20), 30, 15);
+        assert_eq!(hit.map(|e| e.name.as_str()), Some("CPI"));
+        assert!(calendar.blackout(at(10), 30, 15).is_some());
+        assert!(calendar.blackout(at(20), 30, 15).is_none());
+        assert!(calendar.high_impact_within(at(-45), 30).is_none());
+
+        // The low-impact release never blacks out
+        assert!(calendar.blackout(at(90), 30, 15).is_none());
+
+        let before_fomc = Utc.with_ymd_and_hms(2024, 3, 20, 17, 45, 0).unwrap();
+        let fomc = calendar.high_impact_within(before_fomc, 30).unwrap();
+        assert_eq!(fomc.name, "FOMC");
+        assert_eq!(fomc.currency, None);
+    }
+
+    #[test]
+    fn quoted_names_parse_and_other_currencies_do_not_black_out() {
+        let csv = "time,name,impact,currency\n\
+                   2024-03-12 08:30,\"Fed Chair Powell Speaks, Q&A\",high,USD\n\
+                   2024-03-12 08:35,BoJ Rate Decision,high,jpy\n\
+                   2024-03-12 08:40,\"ECB Press Conference\",high,EUR\n";
+        let events = parse_events(csv).unwrap();
+        assert_eq!(events[0].name, "Fed Chair Powell Speaks, Q&A");
+        assert_eq!(events[0].impact, Impact::High);
+
+        let usd = EconCalendar::new(events.clone()).for_currencies(&["USD".to_string()]);
+        assert_eq!(usd.len(), 1);
+        let after_powell = Utc.with_ymd_and_hms(2024, 3, 12, 12, 50, 0).unwrap();
+        assert!(usd.blackout(after_powell, 30, 15).is_none());
+
+        // Case-insensitive, and an empty list keeps every currency
+        let jpy = EconCalendar::new(events.clone()).for_currencies(&["JPY".to_string()]);
+        assert_eq!(jpy.len(), 1);
+        assert_eq!(EconCalendar::new(events).for_currencies(&[]).len(), 3);
+    }
+}
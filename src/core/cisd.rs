@@ -17,6 +17,12 @@ pub struct CisdDetector {
     pub confirmed_cisds: Vec<CisdConfirmation>,
 }
 
+impl Default for CisdDetector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl CisdDetector {
     pub fn new() -> Self {
         Self {
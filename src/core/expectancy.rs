@@ -0,0 +1,129 @@
+use chrono::{DateTime, Utc};
+
+/// Fewer trades than this and the projection is flagged as low-confidence.
+const MIN_SAMPLE_SIZE: usize = 20;
+/// Frequency is measured over at least one day so a burst of trades
+/// does not extrapolate to an absurd weekly count.
+const MIN_SPAN_DAYS: f64 = 1.0;
+
+/// Forward projection from the rolling edge and trade frequency.
+#[derive(Debug, Clone)]
+pub struct ExpectancyForecast {
+    pub sample_size: usize,
+    pub win_rate: f64,
+    pub payoff_ratio: f64,
+    /// Mean PnL per trade
+    pub expectancy: f64,
+    pub trades_per_week: f64,
+    /// Expected PnL per week at current edge and frequency
+    pub weekly_mean: f64,
+    /// One standard deviation of weekly PnL (trade dispersion plus the
+    /// sampling error of the mean edge)
+    pub weekly_std: f64,
+    pub low_sample: bool,
+}
+
+impl ExpectancyForecast {
+    /// Compute from `(exit_time, pnl)` pairs, using the last `window` trades.
+    pub fn from_trades(trades: &[(DateTime<Utc>, f64)], window: usize) -> Option<Self> {
+        let mut recent: Vec<(DateTime<Utc>, f64)> = trades.to_vec();
+        recent.sort_by_key(|(t, _)| *t);
+        let recent = &recent[recent.len().saturating_sub(window.max(2))..];
+        let n = recent.len();
+        if n < 2 {
+            return None;
+        }
+
+        let pnls: Vec<f64> = recent.iter().map(|(_, p)| *p).collect();
+        let wins: Vec<f64> = pnls.iter().copied().filter(|p| *p > 0.0).collect();
+        let losses: Vec<f64> = pnls.iter().copied().filter(|p| *p <= 0.0).collect();
+        let win_rate = wins.len() as f64 / n as f64;
+        let avg_win = if wins.is_empty() {
+            0.0
+        } else {
+            wins.iter().sum::<f64>() / wins.len() as f64
+        };
+        let avg_loss = if losses.is_empty() {
+            0.0
+        } else {
+            (losses.iter().sum::<f64>() / losses.len() as f64).abs()
+        };
+        let payoff_ratio = if avg_loss > 0.0 {
+            avg_win / avg_loss
+        } else {
+            0.0
+        };
+
+        let expectancy = pnls.iter().sum::<f64>() / n as f64;
+        let variance = pnls.iter().map(|p| (p - expectancy).powi(2)).sum::<f64>() / (n - 1) as f64;
+        let std = variance.sqrt();
+
+        let span_days = (recent[n - 1].0 - recent[0].0).num_seconds() as f64 / 86_400.0;
+        let trades_per_week = n as f64 / span_days.max(MIN_SPAN_DAYS) * 7.0;
+
+        // Sum of f trades: outcome variance f·σ², plus edge uncertainty (f·σ/√n)²
+        let outcome_var = trades_per_week * variance;
+        let edge_var = (trades_per_week * std / (n as f64).sqrt()).powi(2);
+
+        Some(Self {
+            sample_size: n,
+            win_rate,
+            payoff_ratio,
+            expectancy,
+            trades_per_week,
+            weekly_mean: expectancy * trades_per_week,
+            weekly_std: (outcome_var + edge_var).sqrt(),
+            low_sample: n < MIN_SAMPLE_SIZE,
+        })
+    }
+
+    /// One-line summary for status output.
+    pub fn summary(&self) -> String {
+        format!(
+            "at current edge and frequency: ~${:+.2}/week ± {:.2} (1σ; {:.1} trades/wk, WR {:.1}%, payoff {:.2}, {} trades{})",
+            self.weekly_mean,
+            self.weekly_std,
+            self.trades_per_week,
+            self.win_rate * 100.0,
+            self.payoff_ratio,
+            self.sample_size,
+            if self.low_sample { ", LOW SAMPLE" } else { "" }
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+
+    #[test]
+    fn projects_weekly_pnl_from_edge_and_frequency() {
+        let t0 = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        // 28 trades over two weeks, alternating +30 / -10
+        let trades: Vec<_> = (0..28)
+            .map(|i| {
+                let pnl = if i % 2 == 0 { 30.0 } else { -10.0 };
+                (t0 + Duration::hours(12 * i), pnl)
+            })
+            .collect();
+        let f = ExpectancyForecast::from_trades(&trades, 100).unwrap();
+        assert_eq!(f.sample_size, 28);
+        assert!((f.expectancy - 10.0).abs() < 1e-9);
+        assert!((f.win_rate - 0.5).abs() < 1e-9);
+        assert!((f.payoff_ratio - 3.0).abs() < 1e-9);
+        // 28 trades over 13.5 days
+        assert!((f.trades_per_week - 28.0 / 13.5 * 7.0).abs() < 1e-9);
+        assert!((f.weekly_mean - f.expectancy * f.trades_per_week).abs() < 1e-9);
+        assert!(f.weekly_std > 0.0);
+        assert!(!f.low_sample);
+
+        // Window keeps only the most recent trades
+        let f = ExpectancyForecast::from_trades(&trades, 10).unwrap();
+        assert_eq!(f.sample_size, 10);
+        assert!(f.low_sample);
+        assert!(f.summary().contains("LOW SAMPLE"));
+
+        assert!(ExpectancyForecast::from_trades(&trades[..1], 100).is_none());
+    }
+}
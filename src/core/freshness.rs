@@ -0,0 +1,214 @@
+use chrono::{DateTime, Duration, Utc};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use crate::models::{CandleSeries, Timeframe};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TfStatus {
+    Fresh,
+    Missing,
+    Insufficient { len: usize, required: usize },
+    Stale { age_secs: i64, max_secs: i64 },
+}
+
+impl fmt::Display for TfStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TfStatus::Fresh => write!(f, "fresh"),
+            TfStatus::Missing => write!(f, "missing"),
+            TfStatus::Insufficient { len, required } => {
+                write!(f, "insufficient ({}/{} candles)", len, required)
+            }
+            TfStatus::Stale { age_secs, max_secs } => {
+                write!(f, "stale ({}s old, max {}s)", age_secs, max_secs)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct TfState {
+    last_update: DateTime<Utc>,
+    last_candle: DateTime<Utc>,
+    len: usize,
+    failures: u32,
+}
+
+/// Tracks per-timeframe data freshness and which scales are running degraded.
+pub struct DataFreshness {
+    states: HashMap<Timeframe, TfState>,
+    max_stale_bars: i64,
+    degraded_scales: HashSet<String>,
+}
+
+impl DataFreshness {
+    /// `max_stale_bars`: bars a series may lag behind the clock before it
+    /// counts as stale.
+    pub fn new(max_stale_bars: i64) -> Self {
+        Self {
+            states: HashMap::new(),
+            max_stale_bars,
+            degraded_scales: HashSet::new(),
+        }
+    }
+
+    /// Minimum candles needed for structure/PDA analysis on a timeframe.
+    pub fn min_candles(tf: Timeframe) -> usize {
+        match tf {
+            Timeframe::D1 => 5,
+            _ => 20,
+        }
+    }
+
+    pub fn max_staleness(&self, tf: Timeframe) -> Duration {
+        Duration::seconds(tf.as_seconds() as i64 * self.max_stale_bars)
+    }
+
+    /// Record a successful refresh.
+    pub fn record(&mut self, tf: Timeframe, series: &CandleSeries, now: DateTime<Utc>) {
+        let Some(last) = series.last() else {
+            self.record_failure(tf);
+            return;
+        };
+        self.states.insert(
+            tf,
+            TfState {
+                last_update: now,
+                last_candle: last.timestamp,
+                len: series.len(),
+                failures: 0,
+            },
+        );
+    }
+
+    /// Record a failed refresh; the cached series keeps ageing.
+    pub fn record_failure(&mut self, tf: Timeframe) {
+        if let Some(state) = self.states.get_mut(&tf) {
+            state.failures += 1;
+        }
+    }
+
+    pub fn failures(&self, tf: Timeframe) -> u32 {
+        self.states.get(&tf).map_or(0, |s| s.failures)
+    }
+
+    pub fn last_update(&self, tf: Timeframe) -> Option<DateTime<Utc>> {
+        self.states.get(&tf).map(|s| s.last_update)
+    }
+
+    pub fn status(&self, tf: Timeframe, now: DateTime<Utc>) -> TfStatus {
+        let Some(state) = self.states.get(&tf) else {
+            return TfStatus::Missing;
+        };
+        let required = Self::min_candles(tf);
+        if state.len < required {
+            return TfStatus::Insufficient {
+                len: state.len,
+                required,
+            };
+        }
+        // Candle timestamps are bar opens, so allow one extra bar
+        let age = now - state.last_candle - tf_duration(tf);
+        let max = self.max_staleness(tf);
+        if age > max {
+            return TfStatus::Stale {
+                age_secs: age.num_seconds(),
+                max_secs: max.num_seconds(),
+            };
+        }
+        TfStatus::Fresh
+    }
+
+    /// Problems with any of the required timeframes (empty = ready).
+    pub fn check(&self, required: &[Timeframe], now: DateTime<Utc>) -> Vec<(Timeframe, TfStatus)> {
+        let mut problems: Vec<(Timeframe, TfStatus)> = Vec::new();
+        for &tf in required {
+            if problems.iter().any(|(t, _)| *t == tf) {
+                continue;
+            }
+            let status = self.status(tf, now);
+            if status != TfStatus::Fresh {
+                problems.push((tf, status));
+            }
+        }
+        problems
+    }
+
+    /// Update a scale's degraded flag. Returns true when the flag changed,
+    /// so callers log transitions once instead of every scan.
+    pub fn set_degraded(&mut self, scale: &str, degraded: bool) -> bool {
+        if degraded {
+            self.degraded_scales.insert(scale.to_string())
+        } else {
+            self.degraded_scales.remove(scale)
+        }
+    }
+
+    pub fn degraded_scales(&self) -> Vec<String> {
+        let mut scales: Vec<String> = self.degraded_scales.iter().cloned().collect();
+        scales.sort();
+        scales
+    }
+}
+
+fn tf_duration(tf: Timeframe) -> Duration {
+    Duration::seconds(tf.as_seconds() as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::make_bullish_trend;
+
+    #[test]
+    fn missing_and_insufficient() {
+        let mut fresh = DataFreshness::new(3);
+        let now = Utc::now();
+        assert_eq!(fresh.status(Timeframe::M1, now), TfStatus::Missing);
+
+        let short = make_bullish_trend(5, 100.0);
+        let ts = short.last().unwrap().timestamp;
+        fresh.record(Timeframe::M1, &short, ts);
+        assert!(matches!(
+            fresh.status(Timeframe::M1, ts),
+            TfStatus::Insufficient { len: 5, required: 20 }
+        ));
+    }
+
+    #[test]
+    fn goes_stale_as_clock_advances() {
+        let mut fresh = DataFreshness::new(3);
+        let series = make_bullish_trend(30, 100.0);
+        let last = series.last().unwrap().timestamp;
+        fresh.record(Timeframe::M1, &series, last);
+
+        assert_eq!(fresh.status(Timeframe::M1, last + Duration::minutes(2)), TfStatus::Fresh);
+        assert!(matches!(
+            fresh.status(Timeframe::M1, last + Duration::minutes(10)),
+            TfStatus::Stale { .. }
+        ));
+        // Same age is fine for a slower timeframe
+        fresh.record(Timeframe::H1, &series, last);
+        assert_eq!(fresh.status(Timeframe::H1, last + Duration::minutes(10)), TfStatus::Fresh);
+    }
+
+    #[test]
+    fn check_and_degraded_transitions() {
+        let mut fresh = DataFreshness::new(3);
+        let series = make_bullish_trend(30, 100.0);
+        let last = series.last().unwrap().timestamp;
+        fresh.record(Timeframe::M5, &series, last);
+        fresh.record_failure(Timeframe::M5);
+        assert_eq!(fresh.failures(Timeframe::M5), 1);
+
+        let problems = fresh.check(&[Timeframe::M5, Timeframe::M15, Timeframe::M15], last);
+        assert_eq!(problems, vec![(Timeframe::M15, TfStatus::Missing)]);
+
+        assert!(fresh.set_degraded("5m", true));
+        assert!(!fresh.set_degraded("5m", true));
+        assert_eq!(fresh.degraded_scales(), vec!["5m".to_string()]);
+        assert!(fresh.set_degraded("5m", false));
+        assert!(!fresh.set_degraded("5m", false));
+    }
+}
@@ -0,0 +1,218 @@
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+use serde::{Deserialize, Serialize};
+
+/// Equity/FX venue whose hours drive a killzone.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Market {
+    Ny,
+    London,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HolidayKind {
+    Closed,
+    HalfDay,
+}
+
+/// A configured holiday; `market: None` applies to both NY and London.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Holiday {
+    pub date: NaiveDate,
+    pub market: Option<Market>,
+    pub kind: HolidayKind,
+}
+
+impl Holiday {
+    /// Parse entries like `2024-12-24:ny:half,2025-01-01:both:closed`.
+    pub fn parse_list(s: &str) -> Option<Vec<Holiday>> {
+        let mut out = Vec::new();
+        for part in s.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            let mut fields = part.split(':');
+            let date = NaiveDate::parse_from_str(fields.next()?, "%Y-%m-%d").ok()?;
+            let market = match fields.next().unwrap_or("both") {
+                "ny" => Some(Market::Ny),
+                "london" => Some(Market::London),
+                "both" => None,
+                _ => return None,
+            };
+            let kind = match fields.next().unwrap_or("closed") {
+                "closed" => HolidayKind::Closed,
+                "half" => HolidayKind::HalfDay,
+                _ => return None,
+            };
+            out.push(Holiday { date, market, kind });
+        }
+        Some(out)
+    }
+}
+
+/// Holiday status for a date: configured entries first, then (optionally)
+/// the built-in NYSE / UK bank holiday rules.
+pub fn holiday_status(
+    holidays: &[Holiday],
+    builtin: bool,
+    date: NaiveDate,
+    market: Market,
+) -> Option<HolidayKind> {
+    let configured = holidays
+        .iter()
+        .find(|h| h.date == date && h.market.is_none_or(|m| m == market))
+        .map(|h| h.kind);
+    if configured.is_some() || !builtin {
+        return configured;
+    }
+    match market {
+        Market::Ny => nyse_holiday(date),
+        Market::London => uk_holiday(date),
+    }
+}
+
+fn nth_weekday(year: i32, month: u32, weekday: Weekday, n: u8) -> Option<NaiveDate> {
+    NaiveDate::from_weekday_of_month_opt(year, month, weekday, n)
+}
+
+fn last_weekday(year: i32, month: u32, weekday: Weekday) -> Option<NaiveDate> {
+    nth_weekday(year, month, weekday, 5).or_else(|| nth_weekday(year, month, weekday, 4))
+}
+
+/// Gregorian Easter Sunday (anonymous Gregorian algorithm).
+fn easter(year: i32) -> Option<NaiveDate> {
+    let a = year % 19;
+    let b = year / 100;
+    let c = year % 100;
+    let d = b / 4;
+    let e = b % 4;
+    let f = (b + 8) / 25;
+    let g = (b - f + 1) / 3;
+    let h = (19 * a + b - d - g + 15) % 30;
+    let i = c / 4;
+    let k = c % 4;
+    let l = (32 + 2 * e + 2 * i - h - k) % 7;
+    let m = (a + 11 * h + 22 * l) / 451;
+    let month = (h + l - 7 * m + 114) / 31;
+    let day = (h + l - 7 * m + 114) % 31 + 1;
+    NaiveDate::from_ymd_opt(year, month as u32, day as u32)
+}
+
+/// Fixed-date holiday moved to Friday/Monday when it falls on a weekend.
+fn observed(date: NaiveDate) -> NaiveDate {
+    match date.weekday() {
+        Weekday::Sat => date - Duration::days(1),
+        Weekday::Sun => date + Duration::days(1),
+        _ => date,
+    }
+}
+
+fn nyse_holiday(date: NaiveDate) -> Option<HolidayKind> {
+    let y = date.year();
+    let fixed = |m: u32, d: u32| NaiveDate::from_ymd_opt(y, m, d).map(observed);
+    let closed = [
+        fixed(1, 1),
+        nth_weekday(y, 1, Weekday::Mon, 3),       // MLK Day
+        nth_weekday(y, 2, Weekday::Mon, 3),       // Presidents' Day
+        easter(y).map(|e| e - Duration::days(2)), // Good Friday
+        last_weekday(y, 5, Weekday::Mon),         // Memorial Day
+        fixed(6, 19),
+        fixed(7, 4),
+        nth_weekday(y, 9, Weekday::Mon, 1),  // Labor Day
+        nth_weekday(y, 11, Weekday::Thu, 4), // Thanksgiving
+        fixed(12, 25),
+    ];
+    if closed.contains(&Some(date)) {
+        return Some(HolidayKind::Closed);
+    }
+
+    let half = [
+        nth_weekday(y, 11, Weekday::Thu, 4).map(|t| t + Duration::days(1)),
+        NaiveDate::from_ymd_opt(y, 12, 24).filter(|d| d.weekday().num_days_from_monday() < 5),
+        NaiveDate::from_ymd_opt(y, 7, 3).filter(|d| d.weekday().num_days_from_monday() < 5),
+    ];
+    half.contains(&Some(date)).then_some(HolidayKind::HalfDay)
+}
+
+fn uk_holiday(date: NaiveDate) -> Option<HolidayKind> {
+    let y = date.year();
+    let christmas = NaiveDate::from_ymd_opt(y, 12, 25)?;
+    // Boxing Day shifts past a substituted Christmas
+    let boxing = match christmas.weekday() {
+        Weekday::Fri => christmas + Duration::days(3),
+        Weekday::Sat => christmas + Duration::days(3),
+        Weekday::Sun => christmas + Duration::days(2),
+        _ => christmas + Duration::days(1),
+    };
+    let closed = [
+        NaiveDate::from_ymd_opt(y, 1, 1).map(observed_uk),
+        easter(y).map(|e| e - Duration::days(2)), // Good Friday
+        easter(y).map(|e| e + Duration::days(1)), // Easter Monday
+        nth_weekday(y, 5, Weekday::Mon, 1),       // Early May
+        last_weekday(y, 5, Weekday::Mon),         // Spring
+        last_weekday(y, 8, Weekday::Mon),         // Summer
+        Some(observed_uk(christmas)),
+        Some(boxing),
+    ];
+    if closed.contains(&Some(date)) {
+        return Some(HolidayKind::Closed);
+    }
+    let half = [
+        NaiveDate::from_ymd_opt(y, 12, 24).filter(|d| d.weekday().num_days_from_monday() < 5),
+        NaiveDate::from_ymd_opt(y, 12, 31).filter(|d| d.weekday().num_days_from_monday() < 5),
+    ];
+    half.contains(&Some(date)).then_some(HolidayKind::HalfDay)
+}
+
+/// UK substitutes weekend holidays with the following Monday.
+fn observed_uk(date: NaiveDate) -> NaiveDate {
+    match date.weekday() {
+        Weekday::Sat => date + Duration::days(2),
+        Weekday::Sun => date + Duration::days(1),
+        _ => date,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn d(y: i32, m: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, day).unwrap()
+    }
+
+    #[test]
+    fn builtin_us_and_uk_holidays() {
+        // 2024: Good Friday Mar 29, Thanksgiving Nov 28
+        assert_eq!(nyse_holiday(d(2024, 3, 29)), Some(HolidayKind::Closed));
+        assert_eq!(uk_holiday(d(2024, 4, 1)), Some(HolidayKind::Closed)); // Easter Monday
+        assert_eq!(nyse_holiday(d(2024, 4, 1)), None);
+        assert_eq!(nyse_holiday(d(2024, 11, 28)), Some(HolidayKind::Closed));
+        assert_eq!(nyse_holiday(d(2024, 11, 29)), Some(HolidayKind::HalfDay));
+        assert_eq!(nyse_holiday(d(2024, 7, 4)), Some(HolidayKind::Closed));
+        assert_eq!(uk_holiday(d(2024, 7, 4)), None);
+        assert_eq!(uk_holiday(d(2024, 8, 26)), Some(HolidayKind::Closed));
+        assert_eq!(nyse_holiday(d(2024, 1, 16)), None);
+    }
+
+    #[test]
+    fn configured_entries_override_builtin() {
+        let extra = Holiday::parse_list("2024-01-16:ny:half, 2024-07-04:both:half").unwrap();
+        assert_eq!(
+            holiday_status(&extra, true, d(2024, 1, 16), Market::Ny),
+            Some(HolidayKind::HalfDay)
+        );
+        assert_eq!(
+            holiday_status(&extra, true, d(2024, 1, 16), Market::London),
+            None
+        );
+        assert_eq!(
+            holiday_status(&extra, true, d(2024, 7, 4), Market::Ny),
+            Some(HolidayKind::HalfDay)
+        );
+        assert_eq!(
+            holiday_status(&[], false, d(2024, 12, 25), Market::Ny),
+            None
+        );
+        assert!(Holiday::parse_list("2024-13-01").is_none());
+        assert!(Holiday::parse_list("2024-01-01:tokyo").is_none());
+    }
+}
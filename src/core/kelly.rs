@@ -1,6 +1,8 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+use crate::core::position_sizing::VolRegime;
+
 const MIN_SAMPLE_SIZE: usize = 20;
 const DEFAULT_FRACTION: f64 = 0.02;
 const KELLY_MULTIPLIER: f64 = 0.5;
@@ -20,16 +22,31 @@ pub struct KellyResult {
     pub edge: f64,
 }
 
-/// Trait for anything with a PnL and a reason string (for scale filtering).
+/// Trait for anything with a PnL and the scale it traded on.
 pub trait HasPnl {
     fn pnl(&self) -> f64;
-    fn reason(&self) -> &str;
+    /// HFT scale key (e.g. "5m"), matched exactly
+    fn scale(&self) -> &str;
+    /// Killzone the trade was entered in
+    fn session(&self) -> &str {
+        ""
+    }
+    /// Volatility at entry, when it was measured
+    fn vol_regime(&self) -> Option<VolRegime> {
+        None
+    }
 }
 
 pub struct KellyCriterion {
     scale_results: HashMap<String, KellyResult>,
 }
 
+impl Default for KellyCriterion {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl KellyCriterion {
     pub fn new() -> Self {
         Self {
@@ -44,91 +61,42 @@ impl KellyCriterion {
     ) -> KellyResult {
         // Filter by scale if provided
         let trades: Vec<&T> = if let Some(s) = scale {
-            trade_history
-                .iter()
-                .filter(|t| t.reason().contains(s))
-                .collect()
+            trade_history.iter().filter(|t| t.scale() == s).collect()
         } else {
             trade_history.iter().collect()
         };
-
-        // Apply rolling window
-        let trades: Vec<&T> = if trades.len() > ROLLING_WINDOW {
-            trades[trades.len() - ROLLING_WINDOW..].to_vec()
-        } else {
-            trades
-        };
-
-        // Not enough data
-        if trades.len() < MIN_SAMPLE_SIZE {
-            let result = KellyResult {
-                full_kelly: 0.0,
-                applied_fraction: DEFAULT_FRACTION,
-                win_rate: 0.0,
-                loss_rate: 0.0,
-                payoff_ratio: 0.0,
-                sample_size: trades.len(),
-                using_default: true,
-                edge: 0.0,
-            };
-            if let Some(s) = scale {
-                self.scale_results.insert(s.to_string(), result.clone());
-            }
-            return result;
-        }
-
-        let total = trades.len() as f64;
-        let wins: Vec<&&T> = trades.iter().filter(|t| t.pnl() > 0.0).collect();
-        let losses: Vec<&&T> = trades.iter().filter(|t| t.pnl() <= 0.0).collect();
-
-        let p = wins.len() as f64 / total;
-        let q = 1.0 - p;
-
-        let avg_win = if !wins.is_empty() {
-            wins.iter().map(|t| t.pnl()).sum::<f64>() / wins.len() as f64
-        } else {
-            0.0
-        };
-
-        let avg_loss = if !losses.is_empty() {
-            (losses.iter().map(|t| t.pnl()).sum::<f64>() / losses.len() as f64).abs()
-        } else {
-            1.0
-        };
-
-        let b = if avg_loss > 0.0 {
-            avg_win / avg_loss
-        } else {
-            0.0
-        };
-
-        let full_kelly = if b > 0.0 { (b * p - q) / b } else { 0.0 };
-
-        let edge = b * p - q;
-
-        let mut applied = full_kelly * KELLY_MULTIPLIER;
-
-        if full_kelly <= 0.0 {
-            applied = MIN_KELLY_FRACTION;
-        } else {
-            applied = applied.max(MIN_KELLY_FRACTION).min(MAX_KELLY_FRACTION);
-        }
-
-        let result = KellyResult {
-            full_kelly: round6(full_kelly),
-            applied_fraction: round6(applied),
-            win_rate: round4(p),
-            loss_rate: round4(q),
-            payoff_ratio: round4(b),
-            sample_size: trades.len(),
-            using_default: false,
-            edge: round4(edge),
-        };
-
+        let result = estimate(trades);
         if let Some(s) = scale {
             self.scale_results.insert(s.to_string(), result.clone());
         }
+        result
+    }
 
+    /// Estimate from the scale's trades in the same killzone and
+    /// volatility regime, else from all the scale's trades, else from the
+    /// whole history: the narrowest bucket with enough samples.
+    pub fn calculate_for_context<T: HasPnl>(
+        &mut self,
+        trade_history: &[T],
+        scale: &str,
+        session: &str,
+        regime: Option<VolRegime>,
+    ) -> KellyResult {
+        let in_scale: Vec<&T> = trade_history
+            .iter()
+            .filter(|t| t.scale() == scale)
+            .collect();
+        let in_context: Vec<&T> = in_scale
+            .iter()
+            .copied()
+            .filter(|t| t.session() == session && t.vol_regime() == regime)
+            .collect();
+        let result = [in_context, in_scale]
+            .into_iter()
+            .map(estimate)
+            .find(|r| !r.using_default)
+            .unwrap_or_else(|| estimate(trade_history.iter().collect()));
+        self.scale_results.insert(scale.to_string(), result.clone());
         result
     }
 
@@ -148,6 +116,78 @@ impl KellyCriterion {
     }
 }
 
+/// Kelly fraction of `trades` (the last `ROLLING_WINDOW` of them).
+fn estimate<T: HasPnl>(trades: Vec<&T>) -> KellyResult {
+    // Apply rolling window
+    let trades: Vec<&T> = if trades.len() > ROLLING_WINDOW {
+        trades[trades.len() - ROLLING_WINDOW..].to_vec()
+    } else {
+        trades
+    };
+
+    // Not enough data
+    if trades.len() < MIN_SAMPLE_SIZE {
+        return KellyResult {
+            full_kelly: 0.0,
+            applied_fraction: DEFAULT_FRACTION,
+            win_rate: 0.0,
+            loss_rate: 0.0,
+            payoff_ratio: 0.0,
+            sample_size: trades.len(),
+            using_default: true,
+            edge: 0.0,
+        };
+    }
+
+    let total = trades.len() as f64;
+    let wins: Vec<&&T> = trades.iter().filter(|t| t.pnl() > 0.0).collect();
+    let losses: Vec<&&T> = trades.iter().filter(|t| t.pnl() <= 0.0).collect();
+
+    let p = wins.len() as f64 / total;
+    let q = 1.0 - p;
+
+    let avg_win = if !wins.is_empty() {
+        wins.iter().map(|t| t.pnl()).sum::<f64>() / wins.len() as f64
+    } else {
+        0.0
+    };
+
+    let avg_loss = if !losses.is_empty() {
+        (losses.iter().map(|t| t.pnl()).sum::<f64>() / losses.len() as f64).abs()
+    } else {
+        1.0
+    };
+
+    let b = if avg_loss > 0.0 {
+        avg_win / avg_loss
+    } else {
+        0.0
+    };
+
+    let full_kelly = if b > 0.0 { (b * p - q) / b } else { 0.0 };
+
+    let edge = b * p - q;
+
+    let mut applied = full_kelly * KELLY_MULTIPLIER;
+
+    if full_kelly <= 0.0 {
+        applied = MIN_KELLY_FRACTION;
+    } else {
+        applied = applied.clamp(MIN_KELLY_FRACTION, MAX_KELLY_FRACTION);
+    }
+
+    KellyResult {
+        full_kelly: round6(full_kelly),
+        applied_fraction: round6(applied),
+        win_rate: round4(p),
+        loss_rate: round4(q),
+        payoff_ratio: round4(b),
+        sample_size: trades.len(),
+        using_default: false,
+        edge: round4(edge),
+    }
+}
+
 fn round4(x: f64) -> f64 {
     (x * 10000.0).round() / 10000.0
 }
@@ -162,15 +202,23 @@ mod tests {
 
     struct TestTrade {
         pnl_val: f64,
-        reason_str: String,
+        scale: String,
+        session: String,
+        regime: Option<VolRegime>,
     }
 
     impl HasPnl for TestTrade {
         fn pnl(&self) -> f64 {
             self.pnl_val
         }
-        fn reason(&self) -> &str {
-            &self.reason_str
+        fn scale(&self) -> &str {
+            &self.scale
+        }
+        fn session(&self) -> &str {
+            &self.session
+        }
+        fn vol_regime(&self) -> Option<VolRegime> {
+            self.regime
         }
     }
 
@@ -178,7 +226,9 @@ mod tests {
         pnls.iter()
             .map(|&p| TestTrade {
                 pnl_val: p,
-                reason_str: "5m test".to_string(),
+                scale: "5m".to_string(),
+                session: "london".to_string(),
+                regime: None,
             })
             .collect()
     }
@@ -209,7 +259,7 @@ mod tests {
     #[test]
     fn min_clamp_when_negative_edge() {
         // All losses
-        let trades = make_trades(&vec![-1.0; 25]);
+        let trades = make_trades(&[-1.0; 25]);
         let mut kc = KellyCriterion::new();
         let r = kc.calculate(&trades, None);
         assert!((r.applied_fraction - MIN_KELLY_FRACTION).abs() < 1e-6);
@@ -231,11 +281,54 @@ mod tests {
 
     #[test]
     fn get_risk_amount_correct() {
-        let trades = make_trades(&vec![1.0; 5]); // too few, uses default
+        let trades = make_trades(&[1.0; 5]); // too few, uses default
         let mut kc = KellyCriterion::new();
         let (risk, result) = kc.get_risk_amount(1000.0, &trades, None);
         assert!(result.using_default);
         let expected = (1000.0 * DEFAULT_FRACTION * 100.0).round() / 100.0;
         assert!((risk - expected).abs() < 0.01);
     }
+
+    #[test]
+    fn scale_filter_is_exact() {
+        let mut trades = make_trades(&[2.0; 20]);
+        let mut slow = make_trades(&[-1.0; 20]);
+        for t in &mut slow {
+            t.scale = "15m".to_string();
+        }
+        trades.extend(slow);
+        let mut kc = KellyCriterion::new();
+        // "5m" must not pick up the 15m trades
+        let r = kc.calculate(&trades, Some("5m"));
+        assert_eq!((r.sample_size, r.win_rate), (20, 1.0));
+        assert_eq!(kc.calculate(&trades, Some("15m")).win_rate, 0.0);
+    }
+
+    #[test]
+    fn context_bucket_falls_back_to_scale_then_global() {
+        // 20 London wins in quiet markets, 20 NY losses in volatile ones
+        let mut trades = make_trades(&[2.0; 20]);
+        for t in &mut trades {
+            t.regime = Some(VolRegime::Low);
+        }
+        let mut ny = make_trades(&[-1.0; 20]);
+        for t in &mut ny {
+            t.session = "new_york".to_string();
+            t.regime = Some(VolRegime::High);
+        }
+        trades.extend(ny);
+
+        let mut kc = KellyCriterion::new();
+        let london = kc.calculate_for_context(&trades, "5m", "london", Some(VolRegime::Low));
+        assert_eq!(london.sample_size, 20);
+        assert_eq!(london.win_rate, 1.0);
+        let new_york = kc.calculate_for_context(&trades, "5m", "new_york", Some(VolRegime::High));
+        assert_eq!(new_york.win_rate, 0.0);
+        // Too few trades in this bucket: the scale's 40 trades
+        let asia = kc.calculate_for_context(&trades, "5m", "asia", None);
+        assert_eq!((asia.sample_size, asia.win_rate), (40, 0.5));
+        // No 15m trades at all: the global estimate
+        let other = kc.calculate_for_context(&trades, "15m", "london", None);
+        assert_eq!(other.sample_size, 40);
+    }
 }
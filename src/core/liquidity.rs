@@ -1,12 +1,18 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::fmt;
 
-use crate::models::{CandleSeries, Direction};
+use crate::core::pd_arrays::Pda;
+use crate::core::structure::DealingRange;
+use crate::models::{CandleSeries, Direction, PdaType, Precision};
 
 /// Tolerance for detecting "equal" highs/lows as a fraction of price
 const EQUAL_LEVEL_TOLERANCE: f64 = 0.0005; // 0.05% — tight for BTC
 /// Minimum number of touches to qualify as a liquidity pool
 const MIN_TOUCHES: usize = 2;
+/// Levels within this share of the range height from an extreme count as
+/// external (the extremes themselves are the range's swing high/low)
+const RANGE_EDGE_TOLERANCE: f64 = 0.05;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum LiquidityType {
@@ -27,13 +33,78 @@ pub struct LiquidityPool {
     pub strength: f64,
 }
 
+/// Where liquidity rests relative to the dealing range.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RangeLiquidity {
+    /// Internal Range Liquidity — FVGs/OBs inside the dealing range
+    IRL,
+    /// External Range Liquidity — highs/lows at or beyond the range extremes
+    ERL,
+}
+
+impl fmt::Display for RangeLiquidity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RangeLiquidity::IRL => write!(f, "IRL"),
+            RangeLiquidity::ERL => write!(f, "ERL"),
+        }
+    }
+}
+
+impl RangeLiquidity {
+    /// Classify a price against the dealing range.
+    pub fn classify(price: f64, dr: &DealingRange) -> Self {
+        let edge = (dr.high - dr.low) * RANGE_EDGE_TOLERANCE;
+        if dr.high > dr.low && price < dr.high - edge && price > dr.low + edge {
+            RangeLiquidity::IRL
+        } else {
+            RangeLiquidity::ERL
+        }
+    }
+
+    /// The side of the range price is drawn to from here.
+    pub fn opposite(self) -> Self {
+        match self {
+            RangeLiquidity::IRL => RangeLiquidity::ERL,
+            RangeLiquidity::ERL => RangeLiquidity::IRL,
+        }
+    }
+}
+
+/// Draw on liquidity picked by the IRL→ERL framework.
+#[derive(Debug, Clone)]
+pub struct LiquidityTarget {
+    pub class: RangeLiquidity,
+    pub price: f64,
+    /// Pool touches; 0 for PD arrays
+    pub touches: usize,
+    pub strength: f64,
+    pub label: String,
+}
+
 pub struct LiquidityDetector {
     swing_lookback: usize,
+    precision: Precision,
+}
+
+impl Default for LiquidityDetector {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl LiquidityDetector {
     pub fn new() -> Self {
-        Self { swing_lookback: 5 }
+        Self {
+            swing_lookback: 5,
+            precision: Precision::default(),
+        }
+    }
+
+    /// Round pool prices to this tick size.
+    pub fn with_precision(mut self, precision: Precision) -> Self {
+        self.precision = precision;
+        self
     }
 
     /// Detect all liquidity pools (BSL and SSL) from candle data
@@ -128,6 +199,57 @@ impl LiquidityDetector {
         }
     }
 
+    /// IRL→ERL framework: an entry at internal liquidity (an FVG/OB inside
+    /// the range) targets the nearest unswept external pool, and an entry at
+    /// external liquidity targets the nearest internal FVG/OB.
+    pub fn framework_target(
+        &self,
+        entry: RangeLiquidity,
+        pools: &[LiquidityPool],
+        pdas: &[Pda],
+        dr: &DealingRange,
+        current_price: f64,
+        direction: Direction,
+    ) -> Option<LiquidityTarget> {
+        match entry.opposite() {
+            RangeLiquidity::ERL => {
+                let external: Vec<LiquidityPool> = pools
+                    .iter()
+                    .filter(|p| RangeLiquidity::classify(p.price, dr) == RangeLiquidity::ERL)
+                    .cloned()
+                    .collect();
+                self.nearest_erl_target(&external, current_price, direction)
+                    .map(|p| LiquidityTarget {
+                        class: RangeLiquidity::ERL,
+                        price: p.price,
+                        touches: p.touches,
+                        strength: p.strength,
+                        label: format!("ERL {}x touches ({:.0})", p.touches, p.price),
+                    })
+            }
+            RangeLiquidity::IRL => pdas
+                .iter()
+                .filter(|p| matches!(p.pda_type, PdaType::FVG | PdaType::OB | PdaType::PB))
+                .filter(|p| RangeLiquidity::classify(p.midpoint, dr) == RangeLiquidity::IRL)
+                // The near edge is where the array starts to be rebalanced
+                .filter_map(|p| match direction {
+                    Direction::Long => (p.low > current_price).then_some((p, p.low)),
+                    Direction::Short => (p.high < current_price).then_some((p, p.high)),
+                })
+                .min_by(|a, b| {
+                    let da = (a.1 - current_price).abs();
+                    da.total_cmp(&(b.1 - current_price).abs())
+                })
+                .map(|(p, price)| LiquidityTarget {
+                    class: RangeLiquidity::IRL,
+                    price,
+                    touches: 0,
+                    strength: p.strength,
+                    label: format!("IRL {} ({:.0})", p.pda_type, price),
+                }),
+        }
+    }
+
     fn find_swing_highs(&self, candles: &CandleSeries) -> Vec<(f64, DateTime<Utc>)> {
         let lb = self.swing_lookback;
         let len = candles.len();
@@ -211,7 +333,7 @@ impl LiquidityDetector {
 
                 pools.push(LiquidityPool {
                     pool_type: pool_type.clone(),
-                    price: round2(avg_price),
+                    price: self.precision.round_price(avg_price),
                     touches,
                     first_touch: first,
                     last_touch: last,
@@ -247,10 +369,6 @@ impl LiquidityDetector {
     }
 }
 
-fn round2(x: f64) -> f64 {
-    (x * 100.0).round() / 100.0
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -360,4 +478,56 @@ mod tests {
         let target = detector.nearest_erl_target(&pools, 100.0, Direction::Long);
         assert!(target.is_none());
     }
+
+    #[test]
+    fn irl_entries_target_erl_and_vice_versa() {
+        use crate::models::{Timeframe, Trend, Zone};
+
+        let dr = DealingRange {
+            high: 110.0,
+            low: 90.0,
+            equilibrium: 100.0,
+            premium_zone: 105.0,
+            discount_zone: 95.0,
+        };
+        let pool = |price: f64| LiquidityPool {
+            pool_type: LiquidityType::BSL,
+            price,
+            touches: 2,
+            first_touch: Utc::now(),
+            last_touch: Utc::now(),
+            swept: false,
+            strength: 0.65,
+        };
+        // 104 rests inside the range, 110.2 at its high
+        let pools = vec![pool(104.0), pool(110.2)];
+        let fvg = |low: f64, high: f64| Pda {
+            pda_type: PdaType::FVG,
+            direction: Trend::Bearish,
+            zone: Zone::Premium,
+            high,
+            low,
+            midpoint: (low + high) / 2.0,
+            timestamp: Utc::now(),
+            timeframe: Timeframe::M15,
+            strength: 0.7,
+        };
+        let pdas = vec![fvg(96.0, 97.0), fvg(101.0, 102.0), fvg(111.0, 112.0)];
+
+        assert_eq!(RangeLiquidity::classify(100.0, &dr), RangeLiquidity::IRL);
+        assert_eq!(RangeLiquidity::classify(89.5, &dr), RangeLiquidity::ERL);
+        assert_eq!(RangeLiquidity::classify(109.5, &dr), RangeLiquidity::ERL);
+
+        let detector = LiquidityDetector::new();
+        let long = |entry| {
+            detector
+                .framework_target(entry, &pools, &pdas, &dr, 98.0, Direction::Long)
+                .unwrap()
+        };
+        let t = long(RangeLiquidity::IRL);
+        assert_eq!((t.class, t.price), (RangeLiquidity::ERL, 110.2));
+        let t = long(RangeLiquidity::ERL);
+        assert_eq!((t.class, t.price), (RangeLiquidity::IRL, 101.0));
+        assert_eq!(t.label, "IRL FVG (101)");
+    }
 }
@@ -1,8 +1,22 @@
+pub mod anchors;
+pub mod calendar;
 pub mod cisd;
+pub mod expectancy;
+pub mod freshness;
+pub mod holidays;
 pub mod kelly;
 pub mod liquidity;
+pub mod opening_gaps;
+pub mod ote;
 pub mod pd_arrays;
+pub mod pda_registry;
+pub mod position_sizing;
+pub mod power_of_three;
+pub mod r_multiple;
 pub mod sessions;
+pub mod smt;
 pub mod stddev_projections;
 pub mod stop_loss;
 pub mod structure;
+pub mod sweeps;
+pub mod volume_profile;
@@ -0,0 +1,167 @@
+use chrono::{DateTime, Datelike, NaiveDate, Timelike, Utc, Weekday};
+use chrono_tz::US::Eastern;
+use serde::{Deserialize, Serialize};
+
+use crate::core::pd_arrays::Pda;
+use crate::models::{CandleSeries, PdaType, Timeframe, Trend, Zone};
+
+/// Daily settlement close, ET hour
+const CLOSE_HOUR: u32 = 17;
+/// Next session's open, ET hour
+const OPEN_HOUR: u32 = 18;
+const NWOG_STRENGTH: f64 = 0.8;
+const NDOG_STRENGTH: f64 = 0.6;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GapKind {
+    /// New Week Opening Gap: Friday 17:00 ET close to Sunday 18:00 ET open
+    Week,
+    /// New Day Opening Gap: 17:00 ET close to 18:00 ET open, Monday-Thursday
+    Day,
+}
+
+/// Range between a settlement close and the next session's open.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpeningGap {
+    pub kind: GapKind,
+    pub close: f64,
+    pub open: f64,
+    /// Time of the opening candle
+    pub timestamp: DateTime<Utc>,
+}
+
+impl OpeningGap {
+    pub fn high(&self) -> f64 {
+        self.close.max(self.open)
+    }
+
+    pub fn low(&self) -> f64 {
+        self.close.min(self.open)
+    }
+
+    /// As a PDA: bullish when price opened above the close.
+    pub fn to_pda(&self, eq: f64, tf: Timeframe) -> Pda {
+        let midpoint = (self.high() + self.low()) / 2.0;
+        let (pda_type, strength) = match self.kind {
+            GapKind::Week => (PdaType::NWOG, NWOG_STRENGTH),
+            GapKind::Day => (PdaType::NDOG, NDOG_STRENGTH),
+        };
+        Pda {
+            pda_type,
+            direction: if self.open > self.close {
+                Trend::Bullish
+            } else {
+                Trend::Bearish
+            },
+            zone: if midpoint > eq {
+                Zone::Premium
+            } else {
+                Zone::Discount
+            },
+            high: self.high(),
+            low: self.low(),
+            midpoint,
+            timestamp: self.timestamp,
+            timeframe: tf,
+            strength,
+        }
+    }
+}
+
+/// The last `keep` weekly and `keep` daily opening gaps in `candles`,
+/// oldest first. Needs candles no wider than an hour to see the 17:00 close.
+pub fn detect(candles: &CandleSeries, keep: usize) -> Vec<OpeningGap> {
+    let mut weekly = Vec::new();
+    let mut daily = Vec::new();
+    let mut last_close: Option<f64> = None;
+    let mut last_open: Option<NaiveDate> = None;
+    for c in candles.iter() {
+        let et = c.timestamp.with_timezone(&Eastern);
+        let weekday = et.weekday();
+        let weekend = matches!(weekday, Weekday::Sat | Weekday::Sun);
+        if !weekend && et.hour() < CLOSE_HOUR {
+            last_close = Some(c.close);
+            continue;
+        }
+        let opens = et.hour() >= OPEN_HOUR && !matches!(weekday, Weekday::Fri | Weekday::Sat);
+        if !opens || last_open == Some(et.date_naive()) {
+            continue;
+        }
+        last_open = Some(et.date_naive());
+        let Some(close) = last_close.take() else {
+            continue;
+        };
+        if close == c.open {
+            continue;
+        }
+        let gap = OpeningGap {
+            kind: if weekday == Weekday::Sun {
+                GapKind::Week
+            } else {
+                GapKind::Day
+            },
+            close,
+            open: c.open,
+            timestamp: c.timestamp,
+        };
+        match gap.kind {
+            GapKind::Week => weekly.push(gap),
+            GapKind::Day => daily.push(gap),
+        }
+    }
+    let mut gaps: Vec<OpeningGap> = weekly[weekly.len().saturating_sub(keep)..]
+        .iter()
+        .chain(&daily[daily.len().saturating_sub(keep)..])
+        .cloned()
+        .collect();
+    gaps.sort_by_key(|g| g.timestamp);
+    gaps
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Candle;
+    use chrono::Duration;
+
+    #[test]
+    fn finds_weekly_and_daily_gaps() {
+        // Hourly from Friday 2024-01-12 12:00 ET (17:00 UTC) to Tuesday;
+        // price steps up at each 18:00 ET open
+        let start = DateTime::parse_from_rfc3339("2024-01-12T17:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let mut price = 100.0;
+        let candles: Vec<Candle> = (0..100)
+            .map(|i| {
+                let t = start + Duration::hours(i);
+                if t.with_timezone(&Eastern).hour() == OPEN_HOUR {
+                    price += 2.0;
+                }
+                Candle {
+                    timestamp: t,
+                    open: price,
+                    high: price + 0.5,
+                    low: price - 0.5,
+                    close: price,
+                    volume: 100.0,
+                }
+            })
+            .collect();
+        let gaps = detect(&CandleSeries::new(candles), 5);
+
+        // Sunday open (Fri 18:00 and Sat 18:00 bumps land in the weekly gap),
+        // then Monday's daily gap
+        assert_eq!(gaps.len(), 2);
+        assert_eq!(gaps[0].kind, GapKind::Week);
+        assert_eq!((gaps[0].low(), gaps[0].high()), (100.0, 106.0));
+        assert_eq!(gaps[1].kind, GapKind::Day);
+        assert_eq!((gaps[1].low(), gaps[1].high()), (106.0, 108.0));
+
+        let pda = gaps[0].to_pda(110.0, Timeframe::H1);
+        assert_eq!(pda.pda_type, PdaType::NWOG);
+        assert_eq!(pda.direction, Trend::Bullish);
+        assert_eq!(pda.zone, Zone::Discount);
+    }
+}
@@ -0,0 +1,124 @@
+use serde::{Deserialize, Serialize};
+
+use crate::core::power_of_three::PowerOfThree;
+use crate::models::{CandleSeries, Direction, Trend};
+
+/// Shallow edge of the OTE zone (retracement of the leg)
+pub const OTE_SHALLOW: f64 = 0.62;
+/// Deep edge of the OTE zone
+pub const OTE_DEEP: f64 = 0.79;
+/// Preferred entry inside the zone
+pub const OTE_SWEET_SPOT: f64 = 0.705;
+
+/// ICT Optimal Trade Entry: the 62-79% retracement of an impulse leg.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OteZone {
+    pub direction: Direction,
+    pub leg_high: f64,
+    pub leg_low: f64,
+    /// Zone bounds
+    pub high: f64,
+    pub low: f64,
+    /// 70.5% retracement
+    pub sweet_spot: f64,
+}
+
+impl OteZone {
+    /// Zone of a leg from `leg_low` up to `leg_high` (longs retrace down
+    /// into it) or the reverse for shorts. `None` for an empty leg.
+    pub fn from_leg(direction: Direction, leg_high: f64, leg_low: f64) -> Option<Self> {
+        let range = leg_high - leg_low;
+        if range <= 0.0 {
+            return None;
+        }
+        let level = |r: f64| match direction {
+            Direction::Long => leg_high - range * r,
+            Direction::Short => leg_low + range * r,
+        };
+        let (shallow, deep) = (level(OTE_SHALLOW), level(OTE_DEEP));
+        Some(Self {
+            direction,
+            leg_high,
+            leg_low,
+            high: shallow.max(deep),
+            low: shallow.min(deep),
+            sweet_spot: level(OTE_SWEET_SPOT),
+        })
+    }
+
+    /// Zone of the leg from the day's manipulation extreme to the furthest
+    /// price since, in the direction of `amd.bias`.
+    pub fn from_manipulation(candles: &CandleSeries, amd: &PowerOfThree) -> Option<Self> {
+        let slice = candles.as_slice();
+        match amd.bias {
+            Trend::Bullish => {
+                let low = amd.manipulation_low?;
+                let i = slice.iter().rposition(|c| c.low == low)?;
+                let high = slice[i..]
+                    .iter()
+                    .map(|c| c.high)
+                    .fold(f64::NEG_INFINITY, f64::max);
+                Self::from_leg(Direction::Long, high, low)
+            }
+            Trend::Bearish => {
+                let high = amd.manipulation_high?;
+                let i = slice.iter().rposition(|c| c.high == high)?;
+                let low = slice[i..]
+                    .iter()
+                    .map(|c| c.low)
+                    .fold(f64::INFINITY, f64::min);
+                Self::from_leg(Direction::Short, high, low)
+            }
+            Trend::Neutral => None,
+        }
+    }
+
+    pub fn contains(&self, price: f64) -> bool {
+        self.low <= price && price <= self.high
+    }
+
+    /// Limit price to wait at, if `current` has not retraced into (or
+    /// through) the zone yet.
+    pub fn limit_for(&self, current: f64) -> Option<f64> {
+        let above = match self.direction {
+            Direction::Long => current > self.high,
+            Direction::Short => current < self.low,
+        };
+        above.then_some(self.sweet_spot)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::make_candles;
+
+    #[test]
+    fn zone_spans_the_62_to_79_retracement() {
+        let zone = OteZone::from_leg(Direction::Long, 110.0, 100.0).unwrap();
+        assert!((zone.high - 103.8).abs() < 1e-9);
+        assert!((zone.low - 102.1).abs() < 1e-9);
+        assert!((zone.sweet_spot - 102.95).abs() < 1e-9);
+        assert!(zone.contains(103.0) && !zone.contains(104.0));
+        assert_eq!(zone.limit_for(108.0), Some(zone.sweet_spot));
+        assert_eq!(zone.limit_for(103.0), None);
+
+        let short = OteZone::from_leg(Direction::Short, 110.0, 100.0).unwrap();
+        assert!((short.low - 106.2).abs() < 1e-9);
+        assert!((short.high - 107.9).abs() < 1e-9);
+        assert!(OteZone::from_leg(Direction::Long, 100.0, 100.0).is_none());
+
+        // Judas run to 99.2 below a 100 open, then expansion to 101
+        let candles = make_candles(&[
+            (100.0, 100.02, 99.98, 100.0),
+            (100.0, 100.0, 99.5, 99.6),
+            (99.6, 99.7, 99.2, 99.4),
+            (99.4, 100.4, 99.3, 100.3),
+            (100.3, 101.0, 100.2, 100.9),
+        ]);
+        let amd = PowerOfThree::classify(candles.as_slice(), 100.0);
+        let zone = OteZone::from_manipulation(&candles, &amd).unwrap();
+        assert_eq!((zone.leg_low, zone.leg_high), (99.2, 101.0));
+        assert_eq!(zone.direction, Direction::Long);
+    }
+}
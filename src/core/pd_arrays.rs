@@ -1,7 +1,20 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-
-use crate::models::{CandleSeries, PdaType, Timeframe, Trend, Zone};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+use crate::config::Config;
+use crate::models::{CandleSeries, Direction, PdaType, Timeframe, Trend, Zone};
+
+/// Candles before an OB whose extreme a mitigation block's failure must
+/// not take out (taking it would make the block a breaker)
+const MB_SWING_WINDOW: usize = 5;
+/// Mitigation blocks fail without a sweep, so rank below breakers
+const MB_STRENGTH: f64 = 0.6;
+/// Volume imbalances are thin single-candle gaps
+const VI_STRENGTH: f64 = 0.4;
+/// Body share of range each liquidity void candle needs
+const VOID_MIN_BODY_RATIO: f64 = 0.6;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Pda {
@@ -16,17 +29,59 @@ pub struct Pda {
     pub strength: f64,
 }
 
+/// Minimums for volume imbalances and liquidity voids.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ImbalanceParams {
+    pub vi_min_gap_percent: f64,
+    pub void_min_candles: usize,
+    pub void_min_move_percent: f64,
+}
+
+impl Default for ImbalanceParams {
+    fn default() -> Self {
+        Self {
+            vi_min_gap_percent: 0.0002,
+            void_min_candles: 3,
+            void_min_move_percent: 0.005,
+        }
+    }
+}
+
+impl ImbalanceParams {
+    pub fn from_config(cfg: &Config) -> Self {
+        Self {
+            vi_min_gap_percent: cfg.vi_min_gap_percent,
+            void_min_candles: cfg.void_min_candles,
+            void_min_move_percent: cfg.void_min_move_percent,
+        }
+    }
+}
+
 pub struct PdArrayDetector {
     pub detected: Vec<Pda>,
+    imbalances: ImbalanceParams,
+}
+
+impl Default for PdArrayDetector {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl PdArrayDetector {
     pub fn new() -> Self {
         Self {
             detected: Vec::new(),
+            imbalances: ImbalanceParams::default(),
         }
     }
 
+    /// Volume imbalance and liquidity void minimums.
+    pub fn with_imbalances(mut self, params: ImbalanceParams) -> Self {
+        self.imbalances = params;
+        self
+    }
+
     pub fn detect_all(
         &mut self,
         candles: &CandleSeries,
@@ -39,9 +94,14 @@ impl PdArrayDetector {
         let eq = Self::equilibrium(candles);
 
         self.detect_order_blocks(candles, timeframe, eq, ob_lookback);
+        self.detect_propulsion_blocks(timeframe);
         self.detect_fvg(candles, timeframe, eq, fvg_min_gap_percent);
+        self.detect_inversions(candles);
         self.detect_breaker_blocks(candles, timeframe, eq, breaker_lookback);
+        self.detect_mitigation_blocks(candles, timeframe, eq, breaker_lookback);
         self.detect_rejection_blocks(candles, timeframe, eq);
+        self.detect_volume_imbalances(candles, timeframe, eq);
+        self.detect_liquidity_voids(candles, timeframe, eq);
 
         &self.detected
     }
@@ -173,6 +233,37 @@ impl PdArrayDetector {
         }
     }
 
+    /// Order blocks that traded into an earlier same-direction OB and held
+    /// its 50% level. Runs on the OBs already detected.
+    fn detect_propulsion_blocks(&mut self, tf: Timeframe) {
+        let obs: Vec<&Pda> = self
+            .detected
+            .iter()
+            .filter(|p| p.pda_type == PdaType::OB)
+            .collect();
+        let mut found = Vec::new();
+        for inner in &obs {
+            let host = obs.iter().find(|outer| {
+                outer.direction == inner.direction
+                    && outer.timestamp < inner.timestamp
+                    && match inner.direction {
+                        Trend::Bullish => inner.low <= outer.high && inner.low >= outer.midpoint,
+                        _ => inner.high >= outer.low && inner.high <= outer.midpoint,
+                    }
+            });
+            if let Some(host) = host {
+                found.push(Pda {
+                    pda_type: PdaType::PB,
+                    timeframe: tf,
+                    // Stacked on a defended OB: stronger than either alone
+                    strength: (0.75 + 0.25 * (host.strength + inner.strength)).min(1.0),
+                    ..(*inner).clone()
+                });
+            }
+        }
+        self.detected.extend(found);
+    }
+
     fn detect_fvg(
         &mut self,
         candles: &CandleSeries,
@@ -226,6 +317,40 @@ impl PdArrayDetector {
         }
     }
 
+    /// FVGs a later candle closed through become IFVGs: same gap, opposite
+    /// direction and zone, stamped at the inverting candle. The violated FVG
+    /// stays listed (its registry state is invalidated).
+    fn detect_inversions(&mut self, candles: &CandleSeries) {
+        let mut found = Vec::new();
+        for fvg in self.detected.iter().filter(|p| p.pda_type == PdaType::FVG) {
+            let after = candles
+                .as_slice()
+                .partition_point(|c| c.timestamp <= fvg.timestamp);
+            let inverted = candles.as_slice()[after..]
+                .iter()
+                .find(|c| match fvg.direction {
+                    Trend::Bullish => c.close < fvg.low,
+                    _ => c.close > fvg.high,
+                });
+            if let Some(c) = inverted {
+                found.push(Pda {
+                    pda_type: PdaType::IFVG,
+                    direction: match fvg.direction {
+                        Trend::Bullish => Trend::Bearish,
+                        _ => Trend::Bullish,
+                    },
+                    zone: match fvg.zone {
+                        Zone::Premium => Zone::Discount,
+                        Zone::Discount => Zone::Premium,
+                    },
+                    timestamp: c.timestamp,
+                    ..fvg.clone()
+                });
+            }
+        }
+        self.detected.extend(found);
+    }
+
     fn detect_breaker_blocks(
         &mut self,
         candles: &CandleSeries,
@@ -291,6 +416,64 @@ impl PdArrayDetector {
         }
     }
 
+    /// OBs that failed (price closed back through them) without the move
+    /// before the failure sweeping the extreme preceding the OB.
+    fn detect_mitigation_blocks(
+        &mut self,
+        candles: &CandleSeries,
+        tf: Timeframe,
+        eq: f64,
+        lookback: usize,
+    ) {
+        let len = candles.len();
+        let first = len.saturating_sub(lookback + 2).max(MB_SWING_WINDOW);
+        for idx in first..len.saturating_sub(2) {
+            let c = &candles[idx];
+            let next = &candles[idx + 1];
+
+            // Bearish OB (up candle, then a close below it) that price
+            // reclaims while holding a higher low, or the bullish mirror
+            let direction = if c.close > c.open && next.close < c.low {
+                Trend::Bullish
+            } else if c.close < c.open && next.close > c.high {
+                Trend::Bearish
+            } else {
+                continue;
+            };
+            let failure = candles
+                .slice(idx + 2, len)
+                .iter()
+                .position(|a| match direction {
+                    Trend::Bullish => a.close > c.high,
+                    _ => a.close < c.low,
+                });
+            let Some(failure) = failure else {
+                continue;
+            };
+            let before = candles.slice(idx - MB_SWING_WINDOW, idx);
+            let leg = candles.slice(idx + 1, idx + 2 + failure);
+            let swept = match direction {
+                Trend::Bullish => leg.lows_min() < before.lows_min(),
+                _ => leg.highs_max() > before.highs_max(),
+            };
+            if swept {
+                continue;
+            }
+            let mid = (c.high + c.low) / 2.0;
+            self.detected.push(Pda {
+                pda_type: PdaType::MB,
+                direction,
+                zone: Self::classify_zone(mid, eq),
+                high: c.high,
+                low: c.low,
+                midpoint: mid,
+                timestamp: c.timestamp,
+                timeframe: tf,
+                strength: MB_STRENGTH,
+            });
+        }
+    }
+
     fn detect_rejection_blocks(&mut self, candles: &CandleSeries, tf: Timeframe, eq: f64) {
         for i in 0..candles.len() {
             let c = &candles[i];
@@ -340,6 +523,216 @@ impl PdArrayDetector {
             }
         }
     }
+
+    /// Consecutive same-direction candles whose bodies leave a gap.
+    fn detect_volume_imbalances(&mut self, candles: &CandleSeries, tf: Timeframe, eq: f64) {
+        let min_gap = self.imbalances.vi_min_gap_percent;
+        for i in 1..candles.len() {
+            let (c1, c2) = (&candles[i - 1], &candles[i]);
+            let (direction, low, high) = if c1.is_bullish() && c2.is_bullish() {
+                (Trend::Bullish, c1.close, c2.open)
+            } else if c1.is_bearish() && c2.is_bearish() {
+                (Trend::Bearish, c2.open, c1.close)
+            } else {
+                continue;
+            };
+            if high <= low || (high - low) / low < min_gap {
+                continue;
+            }
+            let mid = (high + low) / 2.0;
+            self.detected.push(Pda {
+                pda_type: PdaType::VI,
+                direction,
+                zone: Self::classify_zone(mid, eq),
+                high,
+                low,
+                midpoint: mid,
+                timestamp: c2.timestamp,
+                timeframe: tf,
+                strength: VI_STRENGTH,
+            });
+        }
+    }
+
+    /// Completed runs of full-bodied same-direction candles spanning at
+    /// least the minimum move. A run still going at the last candle is left
+    /// until it ends, so the void's extent never changes once listed.
+    fn detect_liquidity_voids(&mut self, candles: &CandleSeries, tf: Timeframe, eq: f64) {
+        let params = self.imbalances;
+        let full_bodied = |i: usize, bullish: bool| {
+            let c = &candles[i];
+            c.is_bullish() == bullish
+                && c.total_range() > 0.0
+                && c.body() / c.total_range() >= VOID_MIN_BODY_RATIO
+        };
+        let mut i = 0;
+        while i < candles.len() {
+            let bullish = candles[i].is_bullish();
+            let mut end = i;
+            while end < candles.len() && full_bodied(end, bullish) {
+                end += 1;
+            }
+            if end == i {
+                i += 1;
+                continue;
+            }
+            let (first, last) = (&candles[i], &candles[end - 1]);
+            let (direction, low, high) = if bullish {
+                (Trend::Bullish, first.open, last.close)
+            } else {
+                (Trend::Bearish, last.close, first.open)
+            };
+            if end < candles.len()
+                && end - i >= params.void_min_candles.max(1)
+                && (high - low) / low >= params.void_min_move_percent
+            {
+                let mid = (high + low) / 2.0;
+                self.detected.push(Pda {
+                    pda_type: PdaType::LV,
+                    direction,
+                    zone: Self::classify_zone(mid, eq),
+                    high,
+                    low,
+                    midpoint: mid,
+                    timestamp: first.timestamp,
+                    timeframe: tf,
+                    strength: ((high - low) / low * 100.0).min(1.0),
+                });
+            }
+            i = end;
+        }
+    }
+}
+
+/// Price has traded through the whole of `pda` since it formed: down to
+/// the low of a bullish array or up to the high of a bearish one.
+pub fn is_filled(pda: &Pda, candles: &CandleSeries) -> bool {
+    candles
+        .iter()
+        .filter(|c| c.timestamp > pda.timestamp)
+        .any(|c| match pda.direction {
+            Trend::Bullish => c.low <= pda.low,
+            _ => c.high >= pda.high,
+        })
+}
+
+/// Fill point of the unfilled volume imbalance or liquidity void `price`
+/// sits inside: its far side in `direction`. Price runs through voids, so
+/// a target inside one is better placed at its end.
+pub fn void_fill(
+    pdas: &[Pda],
+    candles: &CandleSeries,
+    price: f64,
+    direction: Direction,
+) -> Option<(f64, PdaType)> {
+    pdas.iter()
+        .filter(|p| matches!(p.pda_type, PdaType::VI | PdaType::LV))
+        .filter(|p| p.low < price && price < p.high)
+        .filter(|p| !is_filled(p, candles))
+        .map(|p| match direction {
+            Direction::Long => (p.high, p.pda_type),
+            Direction::Short => (p.low, p.pda_type),
+        })
+        .max_by(|a, b| match direction {
+            Direction::Long => a.0.total_cmp(&b.0),
+            Direction::Short => b.0.total_cmp(&a.0),
+        })
+}
+
+/// Lookups served from a cache versus recomputed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+}
+
+impl CacheStats {
+    pub fn hit_rate(&self) -> f64 {
+        let total = self.hits + self.misses;
+        if total == 0 {
+            0.0
+        } else {
+            self.hits as f64 / total as f64
+        }
+    }
+
+    pub fn merge(&mut self, other: CacheStats) {
+        self.hits += other.hits;
+        self.misses += other.misses;
+    }
+}
+
+impl std::fmt::Display for CacheStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} hits / {} lookups ({:.0}%)",
+            self.hits,
+            self.hits + self.misses,
+            self.hit_rate() * 100.0
+        )
+    }
+}
+
+/// `PdArrayDetector` that skips re-detection while the series and params
+/// are unchanged. The key is the series length, first timestamp and full
+/// last candle (it may still be forming).
+#[derive(Default)]
+pub struct PdaCache {
+    detector: PdArrayDetector,
+    key: Option<u64>,
+    pub stats: CacheStats,
+}
+
+impl PdaCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Volume imbalance and liquidity void minimums.
+    pub fn with_imbalances(mut self, params: ImbalanceParams) -> Self {
+        self.detector = self.detector.with_imbalances(params);
+        self
+    }
+
+    pub fn detect_all(
+        &mut self,
+        candles: &CandleSeries,
+        timeframe: Timeframe,
+        fvg_min_gap_percent: f64,
+        ob_lookback: usize,
+        breaker_lookback: usize,
+    ) -> &[Pda] {
+        let mut h = DefaultHasher::new();
+        candles.len().hash(&mut h);
+        candles.first().map(|c| c.timestamp).hash(&mut h);
+        if let Some(c) = candles.last() {
+            c.timestamp.hash(&mut h);
+            for v in [c.open, c.high, c.low, c.close] {
+                v.to_bits().hash(&mut h);
+            }
+        }
+        timeframe.hash(&mut h);
+        fvg_min_gap_percent.to_bits().hash(&mut h);
+        ob_lookback.hash(&mut h);
+        breaker_lookback.hash(&mut h);
+        let key = h.finish();
+
+        if self.key == Some(key) {
+            self.stats.hits += 1;
+        } else {
+            self.stats.misses += 1;
+            self.detector.detect_all(
+                candles,
+                timeframe,
+                fvg_min_gap_percent,
+                ob_lookback,
+                breaker_lookback,
+            );
+            self.key = Some(key);
+        }
+        &self.detector.detected
+    }
 }
 
 #[cfg(test)]
@@ -454,4 +847,131 @@ mod tests {
         let rbs: Vec<&Pda> = pdas.iter().filter(|p| p.pda_type == PdaType::RB && p.direction == Trend::Bearish).collect();
         assert!(!rbs.is_empty(), "Expected bearish RB, got: {:?}", pdas);
     }
+
+    #[test]
+    fn ob_holding_earlier_obs_midpoint_is_propulsion_block() {
+        let mut data = vec![(100.0, 101.0, 99.0, 100.0); 5];
+        // OB 98-106 (50% at 102)
+        data.push((105.0, 106.0, 98.0, 99.0));
+        data.push((99.0, 115.0, 98.0, 113.0));
+        // Retrace into it, holding 103, then another OB
+        data.push((112.0, 113.0, 103.0, 104.0));
+        data.push((104.0, 120.0, 103.5, 118.0));
+        data.push((118.0, 119.0, 117.0, 118.5));
+        let pbs: Vec<Pda> = detect(&data)
+            .into_iter()
+            .filter(|p| p.pda_type == PdaType::PB)
+            .collect();
+        assert_eq!(pbs.len(), 1, "{:?}", pbs);
+        assert_eq!((pbs[0].low, pbs[0].high), (103.0, 113.0));
+        assert!(pbs[0].strength > 0.75);
+
+        // Retracing through the 50% leaves just two OBs
+        data[7] = (112.0, 113.0, 101.0, 104.0);
+        assert!(detect(&data).iter().all(|p| p.pda_type != PdaType::PB));
+    }
+
+    #[test]
+    fn failed_ob_without_sweep_is_mitigation_block() {
+        let mut data = vec![(100.0, 101.0, 95.0, 100.0); 6];
+        // Up candle, then a close below it: bearish OB
+        data.push((100.0, 102.0, 99.5, 101.5));
+        data.push((101.0, 101.2, 97.0, 97.5));
+        // Higher low above 95, then a close back above the OB
+        data.push((97.5, 98.0, 96.0, 97.0));
+        data.push((97.0, 103.5, 96.8, 103.0));
+        data.push((103.0, 104.0, 102.5, 103.5));
+        let mbs = |data: &[(f64, f64, f64, f64)]| -> Vec<Pda> {
+            detect(data)
+                .into_iter()
+                .filter(|p| p.pda_type == PdaType::MB)
+                .collect()
+        };
+        let found = mbs(&data);
+        assert_eq!(found.len(), 1, "{:?}", found);
+        assert_eq!(found[0].direction, Trend::Bullish);
+        assert_eq!((found[0].low, found[0].high), (99.5, 102.0));
+
+        // Sweeping the 95 low first makes it a breaker, not an MB
+        data[8] = (97.5, 98.0, 94.0, 97.0);
+        assert!(mbs(&data).is_empty());
+    }
+
+    #[test]
+    fn fvg_closed_through_inverts() {
+        let mut data = vec![(100.0, 100.5, 99.5, 100.0); 3];
+        // Bullish FVG 100.5-102
+        data.push((100.0, 104.0, 100.0, 103.5));
+        data.push((103.5, 105.0, 102.0, 104.5));
+        data.push((104.5, 105.0, 101.0, 101.5));
+        // Close below the gap
+        data.push((101.5, 101.8, 99.0, 99.5));
+        let pdas = detect(&data);
+        let ifvg: Vec<&Pda> = pdas
+            .iter()
+            .filter(|p| p.pda_type == PdaType::IFVG)
+            .collect();
+        assert_eq!(ifvg.len(), 1, "{:?}", pdas);
+        assert_eq!(ifvg[0].direction, Trend::Bearish);
+        assert_eq!((ifvg[0].low, ifvg[0].high), (100.5, 102.0));
+        assert_eq!(ifvg[0].timestamp, make_candles(&data)[6].timestamp);
+        assert!(pdas
+            .iter()
+            .any(|p| p.pda_type == PdaType::FVG && p.direction == Trend::Bullish));
+    }
+
+    #[test]
+    fn detects_volume_imbalances_and_completed_voids() {
+        let mut data = vec![(100.0, 100.5, 99.5, 100.0); 3];
+        // Body gap 100.4 -> 100.6 with overlapping wicks
+        data.push((100.0, 100.5, 99.9, 100.4));
+        data.push((100.6, 101.0, 100.3, 100.9));
+        // Three full-bodied down candles: 101 -> 98
+        data.push((101.0, 101.1, 99.9, 100.0));
+        data.push((100.0, 100.1, 98.9, 99.0));
+        data.push((99.0, 99.1, 97.9, 98.0));
+        let of = |pdas: Vec<Pda>, t: PdaType| -> Vec<Pda> {
+            pdas.into_iter().filter(|p| p.pda_type == t).collect()
+        };
+
+        let vi = of(detect(&data), PdaType::VI);
+        assert_eq!(vi.len(), 1, "{:?}", vi);
+        assert_eq!((vi[0].low, vi[0].high), (100.4, 100.6));
+        // The run is still going at the last candle
+        assert!(of(detect(&data), PdaType::LV).is_empty());
+
+        data.push((98.0, 98.6, 97.5, 98.4));
+        let lv = of(detect(&data), PdaType::LV);
+        assert_eq!(lv.len(), 1, "{:?}", lv);
+        assert_eq!(lv[0].direction, Trend::Bearish);
+        assert_eq!((lv[0].low, lv[0].high), (98.0, 101.0));
+
+        // A short targeting 99.5 inside the void runs on to its low
+        let candles = make_candles(&data);
+        let fill = void_fill(&lv, &candles, 99.5, Direction::Short);
+        assert_eq!(fill, Some((98.0, PdaType::LV)));
+        assert_eq!(void_fill(&lv, &candles, 102.0, Direction::Short), None);
+        data.push((98.4, 101.2, 98.3, 101.0));
+        assert!(is_filled(&lv[0], &make_candles(&data)));
+    }
+
+    #[test]
+    fn cache_reuses_detection_until_series_changes() {
+        let mut data = vec![(100.0, 101.0, 99.0, 100.0); 5];
+        data.push((105.0, 106.0, 98.0, 99.0));
+        data.push((99.0, 115.0, 98.0, 113.0));
+        let mut cache = PdaCache::new();
+        let candles = make_candles(&data);
+        let tf = Timeframe::M1;
+        let first = cache.detect_all(&candles, tf, 0.0005, 20, 30).len();
+        let again = cache.detect_all(&candles, tf, 0.0005, 20, 30).len();
+        assert_eq!(first, again);
+        assert_eq!((cache.stats.hits, cache.stats.misses), (1, 1));
+
+        // Forming candle ticks
+        data[6].3 = 114.0;
+        cache.detect_all(&make_candles(&data), tf, 0.0005, 20, 30);
+        assert_eq!((cache.stats.hits, cache.stats.misses), (1, 2));
+        assert_eq!(cache.stats.to_string(), "1 hits / 3 lookups (33%)");
+    }
 }
@@ -0,0 +1,159 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::core::pd_arrays::Pda;
+use crate::models::{Candle, CandleSeries, Trend};
+
+/// Lifecycle of a PD array as price returns to it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PdaState {
+    /// Not yet traded through
+    Fresh,
+    /// Wicked through the far side, but no close beyond it
+    Mitigated,
+    /// A candle closed beyond the far side
+    Invalidated,
+}
+
+impl fmt::Display for PdaState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PdaState::Fresh => write!(f, "fresh"),
+            PdaState::Mitigated => write!(f, "mitigated"),
+            PdaState::Invalidated => write!(f, "invalidated"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Tracked {
+    pda: Pda,
+    state: PdaState,
+    /// Last closed candle applied to `state`
+    checked_until: DateTime<Utc>,
+}
+
+/// States of the PD arrays detected on one timeframe, carried across
+/// evaluations and advanced only by candles not yet seen.
+#[derive(Debug, Clone, Default)]
+pub struct PdaRegistry {
+    tracked: Vec<Tracked>,
+}
+
+impl PdaRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Track `detected` (dropping arrays no longer detected) and apply the
+    /// closed candles of `candles` each has not seen. The last candle is
+    /// taken as still forming.
+    pub fn update(&mut self, detected: &[Pda], candles: &CandleSeries) {
+        let closed = &candles.as_slice()[..candles.len().saturating_sub(1)];
+        let mut tracked = Vec::with_capacity(detected.len());
+        for pda in detected {
+            let mut t = match self.tracked.iter().position(|t| same(&t.pda, pda)) {
+                Some(i) => self.tracked.swap_remove(i),
+                None => Tracked {
+                    pda: pda.clone(),
+                    state: PdaState::Fresh,
+                    // Skip the candles that formed it
+                    checked_until: pda.timestamp
+                        + chrono::Duration::from_std(pda.timeframe.as_duration())
+                            .unwrap_or_default(),
+                },
+            };
+            let start = closed.partition_point(|c| c.timestamp <= t.checked_until);
+            for c in &closed[start..] {
+                t.state = t.state.max(state_after(&t.pda, c));
+            }
+            if let Some(c) = closed.last() {
+                t.checked_until = t.checked_until.max(c.timestamp);
+            }
+            tracked.push(t);
+        }
+        self.tracked = tracked;
+    }
+
+    /// State of `pda`; arrays not tracked count as fresh.
+    pub fn state(&self, pda: &Pda) -> PdaState {
+        self.tracked
+            .iter()
+            .find(|t| same(&t.pda, pda))
+            .map_or(PdaState::Fresh, |t| t.state)
+    }
+}
+
+fn same(a: &Pda, b: &Pda) -> bool {
+    a.pda_type == b.pda_type
+        && a.timeframe == b.timeframe
+        && a.timestamp == b.timestamp
+        && a.direction == b.direction
+}
+
+/// State `c` alone leaves `pda` in.
+fn state_after(pda: &Pda, c: &Candle) -> PdaState {
+    let (closed_beyond, wicked_beyond) = match pda.direction {
+        Trend::Bullish => (c.close < pda.low, c.low < pda.low),
+        Trend::Bearish => (c.close > pda.high, c.high > pda.high),
+        Trend::Neutral => (false, false),
+    };
+    if closed_beyond {
+        PdaState::Invalidated
+    } else if wicked_beyond {
+        PdaState::Mitigated
+    } else {
+        PdaState::Fresh
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{PdaType, Timeframe, Zone};
+    use crate::test_helpers::make_candles;
+
+    #[test]
+    fn advances_states_as_candles_arrive() {
+        let mut data = vec![(100.0, 100.5, 99.5, 100.0); 3];
+        let candles = make_candles(&data);
+        let pda = |pda_type: PdaType, low: f64| Pda {
+            pda_type,
+            direction: Trend::Bullish,
+            zone: Zone::Discount,
+            high: 99.8,
+            low,
+            midpoint: (99.8 + low) / 2.0,
+            timestamp: candles[0].timestamp,
+            timeframe: Timeframe::M1,
+            strength: 0.5,
+        };
+        let (shallow, deep) = (pda(PdaType::BRK, 99.0), pda(PdaType::OB, 98.0));
+        let mut registry = PdaRegistry::new();
+        registry.update(&[shallow.clone(), deep.clone()], &candles);
+        assert_eq!(registry.state(&shallow), PdaState::Fresh);
+
+        // Wick to 98.9 through the shallow array, close back inside
+        data.push((100.0, 100.0, 98.9, 99.5));
+        data.push((99.5, 99.6, 99.4, 99.5));
+        registry.update(&[shallow.clone(), deep.clone()], &make_candles(&data));
+        assert_eq!(registry.state(&shallow), PdaState::Mitigated);
+        assert_eq!(registry.state(&deep), PdaState::Fresh);
+
+        // Close below both; the still-forming last candle does not count
+        data.push((99.5, 99.5, 97.5, 97.8));
+        let candles = make_candles(&data);
+        registry.update(&[shallow.clone(), deep.clone()], &candles);
+        assert_eq!(registry.state(&deep), PdaState::Fresh);
+        data.push((97.8, 98.5, 97.6, 98.4));
+        registry.update(&[shallow.clone(), deep.clone()], &make_candles(&data));
+        assert_eq!(registry.state(&shallow), PdaState::Invalidated);
+        assert_eq!(registry.state(&deep), PdaState::Invalidated);
+
+        // Dropped once no longer detected
+        registry.update(std::slice::from_ref(&deep), &make_candles(&data));
+        assert_eq!(registry.state(&shallow), PdaState::Fresh);
+    }
+}
@@ -0,0 +1,171 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::models::CandleSeries;
+
+/// ATR period the volatility percentile is measured with
+pub const ATR_PERIOD: usize = 14;
+/// ATR readings the current one is ranked against
+const PERCENTILE_LOOKBACK: usize = 100;
+/// Readings needed before the percentile is trusted
+const MIN_READINGS: usize = 20;
+/// Risk multiplier bounds for the quietest and most volatile markets
+const MAX_VOL_MULTIPLIER: f64 = 1.5;
+const MIN_VOL_MULTIPLIER: f64 = 0.5;
+
+/// How the risk of each entry is sized (env SIZING_MODE).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SizingMode {
+    /// Half-Kelly fraction of the scale's recent trades
+    #[default]
+    Kelly,
+    /// Constant fraction of the balance (`fixed_risk_pct`)
+    Fixed,
+    /// Fixed fraction scaled inversely with the ATR percentile
+    VolAdjusted,
+    /// Kelly fraction scaled inversely with the ATR percentile
+    KellyVol,
+}
+
+impl SizingMode {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "kelly" => Some(SizingMode::Kelly),
+            "fixed" => Some(SizingMode::Fixed),
+            "vol_adjusted" | "vol" => Some(SizingMode::VolAdjusted),
+            "kelly_vol" => Some(SizingMode::KellyVol),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SizingMode::Kelly => "kelly",
+            SizingMode::Fixed => "fixed",
+            SizingMode::VolAdjusted => "vol_adjusted",
+            SizingMode::KellyVol => "kelly_vol",
+        }
+    }
+
+    /// Fraction of the balance to risk. Without an ATR percentile the
+    /// volatility modes fall back to their unscaled base.
+    pub fn risk_fraction(
+        &self,
+        kelly_fraction: f64,
+        fixed_fraction: f64,
+        atr_percentile: Option<f64>,
+    ) -> f64 {
+        let vol = atr_percentile.map_or(1.0, vol_multiplier);
+        match self {
+            SizingMode::Kelly => kelly_fraction,
+            SizingMode::Fixed => fixed_fraction,
+            SizingMode::VolAdjusted => fixed_fraction * vol,
+            SizingMode::KellyVol => kelly_fraction * vol,
+        }
+    }
+}
+
+impl fmt::Display for SizingMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Volatility bucket of an ATR percentile, for context-specific Kelly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VolRegime {
+    Low,
+    Normal,
+    High,
+}
+
+impl VolRegime {
+    /// Bottom, middle or top third of the ATR percentile.
+    pub fn from_percentile(percentile: f64) -> Self {
+        if percentile < 1.0 / 3.0 {
+            VolRegime::Low
+        } else if percentile > 2.0 / 3.0 {
+            VolRegime::High
+        } else {
+            VolRegime::Normal
+        }
+    }
+}
+
+impl fmt::Display for VolRegime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VolRegime::Low => write!(f, "low"),
+            VolRegime::Normal => write!(f, "normal"),
+            VolRegime::High => write!(f, "high"),
+        }
+    }
+}
+
+/// Rank (0-1) of the latest `period` ATR among the readings of the last
+/// `PERCENTILE_LOOKBACK` candles. `None` with too little history.
+pub fn atr_percentile(candles: &CandleSeries, period: usize) -> Option<f64> {
+    let slice = candles.as_slice();
+    let start = slice.len().saturating_sub(PERCENTILE_LOOKBACK + period);
+    let slice = &slice[start..];
+    if period == 0 || slice.len() < period + MIN_READINGS {
+        return None;
+    }
+    let trs: Vec<f64> = slice
+        .windows(2)
+        .map(|w| {
+            let (prev, c) = (&w[0], &w[1]);
+            (c.high - c.low)
+                .max((c.high - prev.close).abs())
+                .max((c.low - prev.close).abs())
+        })
+        .collect();
+    let atrs: Vec<f64> = trs
+        .windows(period)
+        .map(|w| w.iter().sum::<f64>() / period as f64)
+        .collect();
+    let (current, history) = atrs.split_last()?;
+    if history.is_empty() {
+        return None;
+    }
+    let below = history.iter().filter(|a| *a <= current).count();
+    Some(below as f64 / history.len() as f64)
+}
+
+/// Risk multiplier for an ATR percentile: 1.0 at the median, larger in
+/// quiet markets and smaller in volatile ones.
+pub fn vol_multiplier(percentile: f64) -> f64 {
+    (MAX_VOL_MULTIPLIER - percentile.clamp(0.0, 1.0)).clamp(MIN_VOL_MULTIPLIER, MAX_VOL_MULTIPLIER)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::make_candles;
+
+    #[test]
+    fn risk_shrinks_as_volatility_rises() {
+        // 40 quiet bars, then one wide one
+        let mut data = vec![(100.0, 100.5, 99.5, 100.0); 40];
+        let quiet = make_candles(&data);
+        data.push((100.0, 105.0, 95.0, 100.0));
+        let wild = make_candles(&data);
+
+        let calm = atr_percentile(&quiet, ATR_PERIOD).unwrap();
+        let hot = atr_percentile(&wild, ATR_PERIOD).unwrap();
+        assert_eq!(hot, 1.0);
+        assert!(calm <= hot);
+        assert!(atr_percentile(&make_candles(&data[..10]), ATR_PERIOD).is_none());
+
+        assert_eq!(vol_multiplier(0.5), 1.0);
+        assert_eq!(vol_multiplier(1.0), 0.5);
+        let mode = SizingMode::parse("kelly_vol").unwrap();
+        assert_eq!(mode.risk_fraction(0.02, 0.01, Some(hot)), 0.01);
+        assert_eq!(mode.risk_fraction(0.02, 0.01, None), 0.02);
+        assert_eq!(SizingMode::Fixed.risk_fraction(0.02, 0.01, Some(hot)), 0.01);
+        let quiet_fraction = SizingMode::VolAdjusted.risk_fraction(0.02, 0.01, Some(0.0));
+        assert!((quiet_fraction - 0.015).abs() < 1e-12);
+    }
+}
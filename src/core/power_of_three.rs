@@ -0,0 +1,140 @@
+use chrono_tz::US::Eastern;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::models::{Candle, CandleSeries, Trend};
+
+/// How far (fraction of the open) price must trade away from the open
+/// before the day leaves accumulation
+const ACCUMULATION_BAND: f64 = 0.0005;
+
+/// ICT Power of Three (AMD) phase of the trading day.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Phase {
+    /// Price still holding around the open
+    Accumulation,
+    /// A run away from the open against the day's real direction (Judas swing)
+    Manipulation,
+    /// Price back through the open, expanding the other way
+    Distribution,
+}
+
+impl fmt::Display for Phase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Phase::Accumulation => write!(f, "accumulation"),
+            Phase::Manipulation => write!(f, "manipulation"),
+            Phase::Distribution => write!(f, "distribution"),
+        }
+    }
+}
+
+/// Where the day stands relative to its (midnight) open.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PowerOfThree {
+    pub phase: Phase,
+    pub open: f64,
+    /// Expected distribution direction: opposite the manipulation leg
+    pub bias: Trend,
+    /// Extremes of the manipulation leg (both `None` in accumulation)
+    pub manipulation_high: Option<f64>,
+    pub manipulation_low: Option<f64>,
+}
+
+impl PowerOfThree {
+    /// Classify `candles` (oldest first) against `open`.
+    pub fn classify(candles: &[Candle], open: f64) -> Self {
+        let mut amd = Self {
+            phase: Phase::Accumulation,
+            open,
+            bias: Trend::Neutral,
+            manipulation_high: None,
+            manipulation_low: None,
+        };
+        let (upper, lower) = (
+            open * (1.0 + ACCUMULATION_BAND),
+            open * (1.0 - ACCUMULATION_BAND),
+        );
+        for c in candles {
+            match amd.phase {
+                Phase::Accumulation => {
+                    // A candle through both sides counts as the leg its close rejected
+                    let down = c.low < lower && (c.high <= upper || c.close > open);
+                    if down {
+                        amd.phase = Phase::Manipulation;
+                        amd.bias = Trend::Bullish;
+                        amd.manipulation_low = Some(c.low);
+                    } else if c.high > upper {
+                        amd.phase = Phase::Manipulation;
+                        amd.bias = Trend::Bearish;
+                        amd.manipulation_high = Some(c.high);
+                    } else {
+                        continue;
+                    }
+                }
+                Phase::Manipulation => {
+                    if amd.bias == Trend::Bullish {
+                        amd.manipulation_low = amd.manipulation_low.map(|l| l.min(c.low));
+                    } else {
+                        amd.manipulation_high = amd.manipulation_high.map(|h| h.max(c.high));
+                    }
+                }
+                Phase::Distribution => break,
+            }
+            let reclaimed = match amd.bias {
+                Trend::Bullish => c.close > open,
+                Trend::Bearish => c.close < open,
+                Trend::Neutral => false,
+            };
+            if reclaimed {
+                amd.phase = Phase::Distribution;
+            }
+        }
+        amd
+    }
+
+    /// Classify the candles of the last candle's ET day.
+    pub fn today(candles: &CandleSeries, open: f64) -> Self {
+        let slice = candles.as_slice();
+        let day = slice
+            .last()
+            .map(|c| c.timestamp.with_timezone(&Eastern).date_naive());
+        let start =
+            slice.partition_point(|c| Some(c.timestamp.with_timezone(&Eastern).date_naive()) < day);
+        Self::classify(&slice[start..], open)
+    }
+
+    /// Manipulation done and price distributing in `direction`.
+    pub fn distributing(&self, direction: Trend) -> bool {
+        self.phase == Phase::Distribution && self.bias == direction
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::make_candles;
+
+    #[test]
+    fn walks_accumulation_manipulation_distribution() {
+        let mut data = vec![(100.0, 100.02, 99.98, 100.0); 3];
+        let amd = PowerOfThree::classify(make_candles(&data).as_slice(), 100.0);
+        assert_eq!(amd.phase, Phase::Accumulation);
+
+        // Judas run below the open
+        data.push((100.0, 100.0, 99.5, 99.6));
+        data.push((99.6, 99.7, 99.2, 99.4));
+        let amd = PowerOfThree::classify(make_candles(&data).as_slice(), 100.0);
+        assert_eq!(amd.phase, Phase::Manipulation);
+        assert_eq!(amd.bias, Trend::Bullish);
+        assert_eq!(amd.manipulation_low, Some(99.2));
+
+        // Back above the open
+        data.push((99.4, 100.4, 99.3, 100.3));
+        let amd = PowerOfThree::classify(make_candles(&data).as_slice(), 100.0);
+        assert!(amd.distributing(Trend::Bullish));
+        assert_eq!(amd.manipulation_low, Some(99.2));
+        assert_eq!(amd.manipulation_high, None);
+    }
+}
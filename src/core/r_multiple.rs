@@ -0,0 +1,99 @@
+use serde::{Deserialize, Serialize};
+
+/// Width of an R histogram bucket
+const BUCKET_R: f64 = 0.5;
+/// Results beyond +/- this many R land in the outermost buckets
+const HISTOGRAM_LIMIT_R: f64 = 5.0;
+
+/// Trades whose result fell in `[from, to)` R.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RBucket {
+    pub from: f64,
+    pub to: f64,
+    pub count: usize,
+}
+
+/// Risk-normalized performance: results in multiples of the dollar risk
+/// each trade took at entry.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RStats {
+    pub sample_size: usize,
+    /// Mean R per trade
+    pub expectancy_r: f64,
+    pub avg_win_r: f64,
+    /// Negative
+    pub avg_loss_r: f64,
+    /// Non-empty buckets, lowest first
+    pub histogram: Vec<RBucket>,
+}
+
+impl RStats {
+    /// `None` without any trades.
+    pub fn from_multiples(multiples: impl IntoIterator<Item = f64>) -> Option<Self> {
+        let rs: Vec<f64> = multiples.into_iter().filter(|r| r.is_finite()).collect();
+        if rs.is_empty() {
+            return None;
+        }
+        let mean = |v: &[f64]| {
+            if v.is_empty() {
+                0.0
+            } else {
+                v.iter().sum::<f64>() / v.len() as f64
+            }
+        };
+        let wins: Vec<f64> = rs.iter().copied().filter(|r| *r > 0.0).collect();
+        let losses: Vec<f64> = rs.iter().copied().filter(|r| *r <= 0.0).collect();
+
+        let lowest = -HISTOGRAM_LIMIT_R / BUCKET_R;
+        let highest = HISTOGRAM_LIMIT_R / BUCKET_R - 1.0;
+        let mut histogram: Vec<RBucket> = Vec::new();
+        for r in &rs {
+            let i = (r / BUCKET_R).floor().clamp(lowest, highest);
+            let from = i * BUCKET_R;
+            match histogram.iter_mut().find(|b| b.from == from) {
+                Some(b) => b.count += 1,
+                None => histogram.push(RBucket {
+                    from,
+                    to: from + BUCKET_R,
+                    count: 1,
+                }),
+            }
+        }
+        histogram.sort_by(|a, b| a.from.total_cmp(&b.from));
+
+        Some(Self {
+            sample_size: rs.len(),
+            expectancy_r: mean(&rs),
+            avg_win_r: mean(&wins),
+            avg_loss_r: mean(&losses),
+            histogram,
+        })
+    }
+
+    /// One-line summary for status output.
+    pub fn summary(&self) -> String {
+        format!(
+            "{:+.2}R/trade | avg win {:+.2}R | avg loss {:+.2}R (n={})",
+            self.expectancy_r, self.avg_win_r, self.avg_loss_r, self.sample_size
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expectancy_and_histogram_in_r() {
+        let stats = RStats::from_multiples([2.0, 2.2, -1.0, -1.0, 9.0]).unwrap();
+        assert!((stats.expectancy_r - 2.24).abs() < 1e-9);
+        assert!((stats.avg_win_r - 4.4).abs() < 1e-9);
+        assert_eq!(stats.avg_loss_r, -1.0);
+
+        let buckets: Vec<(f64, usize)> =
+            stats.histogram.iter().map(|b| (b.from, b.count)).collect();
+        // 9R is folded into the top bucket
+        assert_eq!(buckets, vec![(-1.0, 2), (2.0, 2), (4.5, 1)]);
+        assert!(RStats::from_multiples([]).is_none());
+    }
+}
@@ -1,12 +1,213 @@
-use chrono::{DateTime, Timelike, Utc};
+use chrono::{DateTime, Datelike, NaiveDate, Timelike, Utc, Weekday};
 use chrono_tz::US::Eastern;
+use serde::{Deserialize, Serialize};
+use std::fmt;
 
-use crate::config::Config;
+use crate::config::{Config, QuarterMode, SessionTime};
+use crate::core::holidays::{holiday_status, HolidayKind, Market};
+use crate::models::{CandleSeries, Trend};
 
 pub struct SessionManager {
     pub current_session: String,
     pub session_weight: f64,
+    /// Quarter of the current 6-hour quarterly-theory session
+    pub quarter: Quarter,
+    /// Weekday, weekend or holiday, for trade metadata
+    pub market_regime: MarketRegime,
+    /// Today is an enabled weekday and not a blackout date
+    trading_day: bool,
     last_update_time: DateTime<Utc>,
+    ny_holiday: Option<HolidayKind>,
+    london_holiday: Option<HolidayKind>,
+}
+
+/// Quarterly theory start of the day (ET); its four 6-hour sessions (Asia,
+/// London, NY AM, NY PM) each split into four 90-minute quarters
+const QUARTER_DAY_START_MIN: u32 = 18 * 60;
+const QUARTER_MINUTES: u32 = 90;
+
+/// 90-minute quarter of a quarterly-theory session.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+pub enum Quarter {
+    /// Accumulation
+    Q1,
+    /// Manipulation
+    Q2,
+    /// Distribution
+    Q3,
+    /// Continuation or reversal
+    Q4,
+}
+
+impl fmt::Display for Quarter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Quarter::Q1 => write!(f, "Q1"),
+            Quarter::Q2 => write!(f, "Q2"),
+            Quarter::Q3 => write!(f, "Q3"),
+            Quarter::Q4 => write!(f, "Q4"),
+        }
+    }
+}
+
+impl Quarter {
+    /// Quarter containing `t`.
+    pub fn at(t: DateTime<Utc>) -> Self {
+        let et = t.with_timezone(&Eastern);
+        let minute = et.hour() * 60 + et.minute();
+        let since_start = (minute + 24 * 60 - QUARTER_DAY_START_MIN) % (24 * 60);
+        match (since_start % (4 * QUARTER_MINUTES)) / QUARTER_MINUTES {
+            0 => Quarter::Q1,
+            1 => Quarter::Q2,
+            2 => Quarter::Q3,
+            _ => Quarter::Q4,
+        }
+    }
+
+    /// Q2 and Q3, the manipulation and distribution windows.
+    pub fn is_preferred(&self) -> bool {
+        matches!(self, Quarter::Q2 | Quarter::Q3)
+    }
+}
+
+/// What the traditional markets are doing today.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MarketRegime {
+    Weekday,
+    /// Saturday or Sunday: FX and equities closed, thin liquidity
+    Weekend,
+    /// NY or London closed or on a half day
+    Holiday,
+}
+
+impl fmt::Display for MarketRegime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MarketRegime::Weekday => write!(f, "weekday"),
+            MarketRegime::Weekend => write!(f, "weekend"),
+            MarketRegime::Holiday => write!(f, "holiday"),
+        }
+    }
+}
+
+/// Venue whose hours a session belongs to.
+fn session_market(name: &str) -> Option<Market> {
+    match name {
+        "london" => Some(Market::London),
+        "ny_forex" | "ny_indices" => Some(Market::Ny),
+        _ => None,
+    }
+}
+
+/// ET date of the session window of `times` containing `t`, if any. The
+/// after-midnight part of a window that wraps midnight belongs to the day
+/// it started on.
+fn session_day(times: &SessionTime, t: DateTime<Utc>) -> Option<NaiveDate> {
+    let et = t.with_timezone(&Eastern);
+    let current_time = et.hour() * 60 + et.minute();
+    let start_min = times.start.0 * 60 + times.start.1;
+    let end_min = times.end.0 * 60 + times.end.1;
+
+    if start_min < end_min {
+        (current_time >= start_min && current_time < end_min).then(|| et.date_naive())
+    } else if current_time >= start_min {
+        // Wraps midnight (e.g. Asian session 20:00 - 00:00)
+        Some(et.date_naive())
+    } else if current_time < end_min {
+        et.date_naive().pred_opt()
+    } else {
+        None
+    }
+}
+
+/// Configured session containing `t`, if any.
+pub fn session_at(cfg: &Config, t: DateTime<Utc>) -> Option<&str> {
+    cfg.sessions
+        .iter()
+        .find_map(|(name, times)| session_day(times, t).map(|_| name.as_str()))
+}
+
+/// High and low of one day's session window.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SessionRange {
+    pub session: String,
+    /// ET date the session opened on
+    pub day: NaiveDate,
+    pub high: f64,
+    pub low: f64,
+    /// First and last candle inside the window
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    /// Candles after the window exist, so the range can no longer grow
+    pub complete: bool,
+}
+
+impl SessionRange {
+    pub fn equilibrium(&self) -> f64 {
+        (self.high + self.low) / 2.0
+    }
+
+    /// Candles after the window traded below the low (`Bullish`) or above
+    /// the high (`Bearish`) and the last one closed back inside.
+    pub fn swept(&self, candles: &CandleSeries, side: Trend) -> bool {
+        let after: Vec<_> = candles.iter().filter(|c| c.timestamp > self.end).collect();
+        let Some(last) = after.last() else {
+            return false;
+        };
+        match side {
+            Trend::Bullish => after.iter().any(|c| c.low < self.low) && last.close > self.low,
+            Trend::Bearish => after.iter().any(|c| c.high > self.high) && last.close < self.high,
+            Trend::Neutral => false,
+        }
+    }
+}
+
+/// Per-day ranges of session `name` (a `cfg.sessions` key) over `candles`,
+/// oldest first. Empty for an unknown session.
+pub fn session_ranges(cfg: &Config, name: &str, candles: &CandleSeries) -> Vec<SessionRange> {
+    let Some(times) = cfg.sessions.get(name) else {
+        return Vec::new();
+    };
+    let mut ranges: Vec<SessionRange> = Vec::new();
+    for c in candles.iter() {
+        match session_day(times, c.timestamp) {
+            Some(day) => match ranges.last_mut() {
+                Some(r) if r.day == day && !r.complete => {
+                    r.high = r.high.max(c.high);
+                    r.low = r.low.min(c.low);
+                    r.end = c.timestamp;
+                }
+                _ => ranges.push(SessionRange {
+                    session: name.to_string(),
+                    day,
+                    high: c.high,
+                    low: c.low,
+                    start: c.timestamp,
+                    end: c.timestamp,
+                    complete: false,
+                }),
+            },
+            None => {
+                if let Some(r) = ranges.last_mut() {
+                    r.complete = true;
+                }
+            }
+        }
+    }
+    ranges
+}
+
+/// Most recent finished range of session `name`.
+pub fn last_completed_range(
+    cfg: &Config,
+    name: &str,
+    candles: &CandleSeries,
+) -> Option<SessionRange> {
+    session_ranges(cfg, name, candles)
+        .into_iter()
+        .rev()
+        .find(|r| r.complete)
 }
 
 impl SessionManager {
@@ -17,15 +218,34 @@ impl SessionManager {
                 .session_weights
                 .get("off_session")
                 .unwrap_or(&0.5),
+            quarter: Quarter::at(Utc::now()),
+            market_regime: MarketRegime::Weekday,
+            trading_day: true,
             last_update_time: Utc::now(),
+            ny_holiday: None,
+            london_holiday: None,
         }
     }
 
     pub fn update(&mut self, cfg: &Config, utc_now: Option<DateTime<Utc>>) {
         let utc_now = utc_now.unwrap_or_else(Utc::now);
         self.last_update_time = utc_now;
+        self.quarter = Quarter::at(utc_now);
         let et_now = utc_now.with_timezone(&Eastern);
-        let current_time = et_now.hour() * 60 + et_now.minute();
+        let today = et_now.date_naive();
+        self.ny_holiday = holiday_status(&cfg.holidays, cfg.builtin_holidays, today, Market::Ny);
+        self.london_holiday =
+            holiday_status(&cfg.holidays, cfg.builtin_holidays, today, Market::London);
+        let weekday = today.weekday();
+        self.market_regime = if matches!(weekday, Weekday::Sat | Weekday::Sun) {
+            MarketRegime::Weekend
+        } else if self.ny_holiday.is_some() || self.london_holiday.is_some() {
+            MarketRegime::Holiday
+        } else {
+            MarketRegime::Weekday
+        };
+        self.trading_day =
+            cfg.trading_days.contains(&weekday) && !cfg.blackout_dates.contains(&today);
 
         self.current_session = "off_session".to_string();
         self.session_weight = *cfg
@@ -33,25 +253,26 @@ impl SessionManager {
             .get("off_session")
             .unwrap_or(&0.5);
 
-        for (name, times) in &cfg.sessions {
-            let start_min = times.start.0 * 60 + times.start.1;
-            let end_min = times.end.0 * 60 + times.end.1;
-
-            let in_session = if start_min < end_min {
-                current_time >= start_min && current_time < end_min
-            } else {
-                // Wraps midnight (e.g. Asian session 20:00 - 00:00)
-                current_time >= start_min || current_time < end_min
+        if let Some(name) = session_at(cfg, utc_now) {
+            self.current_session = name.to_string();
+            self.session_weight = match session_market(name).and_then(|m| self.holiday(m)) {
+                Some(HolidayKind::Closed) => *cfg
+                    .session_weights
+                    .get("off_session")
+                    .unwrap_or(&0.5),
+                Some(HolidayKind::HalfDay) => {
+                    cfg.session_weights.get(name).unwrap_or(&0.5) * cfg.holiday_half_day_weight
+                }
+                None => *cfg.session_weights.get(name).unwrap_or(&0.5),
             };
+        }
+    }
 
-            if in_session {
-                self.current_session = name.clone();
-                self.session_weight = *cfg
-                    .session_weights
-                    .get(name)
-                    .unwrap_or(&0.5);
-                break;
-            }
+    /// Holiday status of a venue for the current (ET) day.
+    pub fn holiday(&self, market: Market) -> Option<HolidayKind> {
+        match market {
+            Market::Ny => self.ny_holiday,
+            Market::London => self.london_holiday,
         }
     }
 
@@ -63,11 +284,21 @@ impl SessionManager {
         self.current_session == "ny_forex" || self.current_session == "ny_indices"
     }
 
+    /// Entry multiplier for the current quarter under `cfg.quarter_mode`;
+    /// `None` when the quarter is gated out.
+    pub fn quarter_multiplier(&self, cfg: &Config) -> Option<f64> {
+        match cfg.quarter_mode {
+            QuarterMode::Off => Some(1.0),
+            _ if self.quarter.is_preferred() => Some(1.0),
+            QuarterMode::Gate => None,
+            QuarterMode::Weight => Some(cfg.quarter_off_weight),
+        }
+    }
+
+    /// In a London / NY killzone whose venue is open today.
     pub fn is_killzone(&self) -> bool {
-        matches!(
-            self.current_session.as_str(),
-            "london" | "ny_forex" | "ny_indices"
-        )
+        session_market(&self.current_session)
+            .is_some_and(|m| self.holiday(m) != Some(HolidayKind::Closed))
     }
 
     pub fn get_day_of_week(&self) -> String {
@@ -82,8 +313,15 @@ impl SessionManager {
             .map_or(0.0, |ratings| ratings.get(&day))
     }
 
+    /// Today's weekday is in TRADING_DAYS and the date is not in BLACKOUT_DATES.
+    pub fn is_trading_day(&self) -> bool {
+        self.trading_day
+    }
+
     pub fn should_trade_today(&self, cfg: &Config, profile: &str) -> bool {
-        self.get_day_rating(cfg, profile) >= cfg.min_day_rating
+        let full_holiday = self.ny_holiday == Some(HolidayKind::Closed)
+            && self.london_holiday == Some(HolidayKind::Closed);
+        self.trading_day && !full_holiday && self.get_day_rating(cfg, profile) >= cfg.min_day_rating
     }
 
     /// Check if current time is in the AM Silver Bullet window (10:00-11:00 ET)
@@ -91,7 +329,7 @@ impl SessionManager {
         let et_now = self.last_update_time.with_timezone(&Eastern);
         let current_min = et_now.hour() * 60 + et_now.minute();
         // 10:00 AM - 11:00 AM ET
-        current_min >= 600 && current_min < 660
+        (600..660).contains(&current_min)
     }
 
     /// Get Silver Bullet multiplier (1.0 = no boost, >1.0 = boosted)
@@ -107,6 +345,7 @@ impl SessionManager {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::ALL_WEEKDAYS;
     use crate::test_helpers::default_test_config;
     use chrono::TimeZone;
 
@@ -163,6 +402,35 @@ mod tests {
         assert!(!sm.is_killzone());
     }
 
+    #[test]
+    fn quarters_split_each_six_hour_session() {
+        let mut cfg = default_test_config();
+        let mut sm = SessionManager::new(&cfg);
+        // London 00:00-06:00 ET: Q1 00:00, Q2 01:30, Q3 03:00, Q4 04:30
+        for (h, m, q) in [
+            (0, 0, Quarter::Q1),
+            (1, 29, Quarter::Q1),
+            (1, 30, Quarter::Q2),
+            (3, 0, Quarter::Q3),
+            (5, 59, Quarter::Q4),
+            (9, 30, Quarter::Q3),
+            (18, 0, Quarter::Q1),
+            (23, 59, Quarter::Q4),
+        ] {
+            sm.update(&cfg, Some(make_utc_for_et_hour(h, m)));
+            assert_eq!(sm.quarter, q, "{:02}:{:02}", h, m);
+        }
+
+        sm.update(&cfg, Some(make_utc_for_et_hour(0, 15)));
+        assert_eq!(sm.quarter_multiplier(&cfg), Some(1.0));
+        cfg.quarter_mode = QuarterMode::Gate;
+        assert_eq!(sm.quarter_multiplier(&cfg), None);
+        cfg.quarter_mode = QuarterMode::Weight;
+        assert_eq!(sm.quarter_multiplier(&cfg), Some(0.8));
+        sm.update(&cfg, Some(make_utc_for_et_hour(2, 0)));
+        assert_eq!(sm.quarter_multiplier(&cfg), Some(1.0));
+    }
+
     #[test]
     fn killzone_false_for_asian() {
         let cfg = default_test_config();
@@ -171,4 +439,109 @@ mod tests {
         assert_eq!(sm.current_session, "asian");
         assert!(!sm.is_killzone());
     }
+
+    #[test]
+    fn holidays_close_killzones() {
+        let mut cfg = default_test_config();
+        cfg.builtin_holidays = true;
+        let mut sm = SessionManager::new(&cfg);
+
+        // 2024-01-15 is MLK Day: NY closed, London open
+        sm.update(&cfg, Some(make_utc_for_et_hour(8, 0)));
+        assert_eq!(sm.current_session, "ny_forex");
+        assert!(!sm.is_killzone());
+        sm.update(&cfg, Some(make_utc_for_et_hour(3, 0)));
+        assert!(sm.is_killzone());
+        assert!((sm.session_weight - 1.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn asia_range_is_anchored_to_its_day_and_swept() {
+        let cfg = default_test_config();
+        // Hourly from 19:00 ET on Jan 15: Asia is 20:00-00:00 ET
+        let base = make_utc_for_et_hour(19, 0);
+        let bars = [
+            (100.0, 100.5, 99.5),
+            (100.0, 101.0, 99.0),
+            (100.0, 101.5, 99.2),
+            (100.0, 100.8, 99.4),
+            (100.0, 100.6, 99.6),
+            // Midnight ET: the range is complete
+            (100.0, 100.2, 99.8),
+            (99.8, 99.9, 98.5),
+            (98.6, 99.8, 98.6),
+        ];
+        let candles = CandleSeries::new(
+            bars.iter()
+                .enumerate()
+                .map(|(i, &(open, high, low))| crate::models::Candle {
+                    timestamp: base + chrono::Duration::hours(i as i64),
+                    open,
+                    high,
+                    low,
+                    close: if i == bars.len() - 1 { 99.5 } else { open },
+                    volume: 100.0,
+                })
+                .collect(),
+        );
+
+        let ranges = session_ranges(&cfg, "asian", &candles);
+        assert_eq!(ranges.len(), 1);
+        let asia = &ranges[0];
+        assert_eq!(asia.day, NaiveDate::from_ymd_opt(2024, 1, 15).unwrap());
+        assert_eq!((asia.high, asia.low), (101.5, 99.0));
+        assert!(asia.complete);
+        assert_eq!(
+            last_completed_range(&cfg, "asian", &candles).as_ref(),
+            Some(asia)
+        );
+
+        // Ran under 99.0 after the session and closed back above it
+        assert!(asia.swept(&candles, Trend::Bullish));
+        assert!(!asia.swept(&candles, Trend::Bearish));
+        // London opened on the last candle and is still forming
+        assert!(last_completed_range(&cfg, "london", &candles).is_none());
+    }
+
+    #[test]
+    fn half_day_scales_weight_and_full_holiday_blocks_trading() {
+        let mut cfg = default_test_config();
+        cfg.holidays =
+            crate::core::holidays::Holiday::parse_list("2024-01-15:london:half,2024-01-16:both")
+                .unwrap();
+        let mut sm = SessionManager::new(&cfg);
+
+        sm.update(&cfg, Some(make_utc_for_et_hour(3, 0)));
+        assert!(sm.is_killzone());
+        assert!((sm.session_weight - 0.75).abs() < 1e-9);
+
+        // Next day both venues are closed
+        sm.update(&cfg, Some(make_utc_for_et_hour(3, 0) + chrono::Duration::days(1)));
+        assert!(!sm.is_killzone());
+        assert!(!sm.should_trade_today(&cfg, "undetermined"));
+    }
+
+    #[test]
+    fn weekends_and_disabled_days_are_not_trading_days() {
+        let mut cfg = default_test_config();
+        cfg.trading_days = ALL_WEEKDAYS[..5].to_vec();
+        let mut sm = SessionManager::new(&cfg);
+        let monday = make_utc_for_et_hour(3, 0);
+
+        // Wednesday: an ordinary trading day
+        sm.update(&cfg, Some(monday + chrono::Duration::days(2)));
+        assert_eq!(sm.market_regime, MarketRegime::Weekday);
+        assert!(sm.should_trade_today(&cfg, "undetermined"));
+
+        // Saturday is disabled by TRADING_DAYS
+        sm.update(&cfg, Some(monday + chrono::Duration::days(5)));
+        assert_eq!(sm.market_regime, MarketRegime::Weekend);
+        assert!(!sm.is_trading_day());
+        assert!(!sm.should_trade_today(&cfg, "undetermined"));
+
+        // Wednesday again, now a blackout date
+        cfg.blackout_dates = vec![NaiveDate::from_ymd_opt(2024, 1, 17).unwrap()];
+        sm.update(&cfg, Some(monday + chrono::Duration::days(2)));
+        assert!(!sm.is_trading_day());
+    }
 }
@@ -0,0 +1,151 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::core::structure::{MarketStructure, SwingPoint};
+use crate::models::{CandleSeries, Direction};
+
+/// Candles either side of a swing searched for the peer's matching extreme
+const MATCH_WINDOW: usize = 2;
+/// A divergence older than this many candles no longer confirms anything
+const MAX_AGE_BARS: usize = 20;
+
+/// Smart Money Technique divergence: at the last two swing highs (or lows)
+/// one symbol made a new extreme and its correlated peer did not.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SmtDivergence {
+    /// Long on diverging lows, short on diverging highs
+    pub direction: Direction,
+    /// Time of the later swing
+    pub timestamp: DateTime<Utc>,
+    /// Whether this symbol made the new extreme (the peer failed to confirm)
+    pub primary_led: bool,
+}
+
+impl SmtDivergence {
+    pub fn describe(&self, peer: &str) -> String {
+        let (side, extreme) = match self.direction {
+            Direction::Long => ("bullish", "low"),
+            Direction::Short => ("bearish", "high"),
+        };
+        if self.primary_led {
+            format!(
+                "{} SMT, {} failed to confirm the new {}",
+                side, peer, extreme
+            )
+        } else {
+            format!("{} SMT, new {} on {} only", side, extreme, peer)
+        }
+    }
+}
+
+/// Latest recent divergence of `primary` against `peer` (same timeframe).
+pub fn detect(primary: &CandleSeries, peer: &CandleSeries) -> Option<SmtDivergence> {
+    let mut structure = MarketStructure::new();
+    structure.analyze(primary);
+    let oldest = primary
+        .get(primary.len().checked_sub(MAX_AGE_BARS)?)?
+        .timestamp;
+    [
+        divergence_at(&structure.swing_highs, peer, Direction::Short),
+        divergence_at(&structure.swing_lows, peer, Direction::Long),
+    ]
+    .into_iter()
+    .flatten()
+    .filter(|d| d.timestamp >= oldest)
+    .max_by_key(|d| d.timestamp)
+}
+
+fn divergence_at(
+    swings: &[SwingPoint],
+    peer: &CandleSeries,
+    direction: Direction,
+) -> Option<SmtDivergence> {
+    let [prev, last] = swings.last_chunk::<2>()?;
+    let highs = direction == Direction::Short;
+    let beyond = |a: f64, b: f64| if highs { a > b } else { a < b };
+    let primary_new = beyond(last.price, prev.price);
+    let peer_new = beyond(
+        peer_extreme(peer, last.timestamp, highs)?,
+        peer_extreme(peer, prev.timestamp, highs)?,
+    );
+    (primary_new != peer_new).then_some(SmtDivergence {
+        direction,
+        timestamp: last.timestamp,
+        primary_led: primary_new,
+    })
+}
+
+/// Peer's high (or low) within `MATCH_WINDOW` candles of `t`.
+fn peer_extreme(peer: &CandleSeries, t: DateTime<Utc>, highs: bool) -> Option<f64> {
+    let candles = peer.as_slice();
+    let i = candles.partition_point(|c| c.timestamp < t);
+    if i == candles.len() {
+        return None;
+    }
+    let window =
+        &candles[i.saturating_sub(MATCH_WINDOW)..(i + MATCH_WINDOW + 1).min(candles.len())];
+    let extreme = if highs {
+        window
+            .iter()
+            .map(|c| c.high)
+            .fold(f64::NEG_INFINITY, f64::max)
+    } else {
+        window.iter().map(|c| c.low).fold(f64::INFINITY, f64::min)
+    };
+    Some(extreme)
+}
+
+/// Parse `BTC-USD:ETH-USD,...` into a symmetric symbol -> peer map.
+pub fn parse_pairs(s: &str) -> Option<HashMap<String, String>> {
+    let mut out = HashMap::new();
+    for entry in s.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+        let (a, b) = entry.split_once(':')?;
+        let (a, b) = (a.trim().to_uppercase(), b.trim().to_uppercase());
+        if a.is_empty() || b.is_empty() || a == b {
+            return None;
+        }
+        out.insert(b.clone(), a.clone());
+        out.insert(a, b);
+    }
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::make_candles;
+
+    /// Up to a 110 high, down to 104, up to `second`, down again.
+    fn two_peaks(second: f64) -> CandleSeries {
+        let mut path: Vec<f64> = (0..=10).map(|i| 100.0 + i as f64).collect();
+        path.extend((1..=6).map(|i| 110.0 - i as f64));
+        path.extend((1..=8).map(|i| 104.0 + (second - 104.0) * i as f64 / 8.0));
+        path.extend((1..=15).map(|i| second - i as f64 * 0.5));
+        let data: Vec<_> = path.iter().map(|&p| (p, p + 0.2, p - 0.2, p)).collect();
+        make_candles(&data)
+    }
+
+    #[test]
+    fn detects_bearish_divergence_at_swing_highs() {
+        let btc = two_peaks(112.0);
+        let eth = two_peaks(108.0);
+
+        let smt = detect(&btc, &eth).unwrap();
+        assert_eq!(smt.direction, Direction::Short);
+        assert!(smt.primary_led);
+        assert!(smt
+            .describe("ETH-USD")
+            .contains("ETH-USD failed to confirm"));
+        let smt = detect(&eth, &btc).unwrap();
+        assert_eq!(smt.direction, Direction::Short);
+        assert!(!smt.primary_led);
+
+        // Both confirm the higher high: no divergence
+        assert!(detect(&btc, &two_peaks(113.0)).is_none());
+
+        let pairs = parse_pairs("BTC-USD:eth-usd").unwrap();
+        assert_eq!(pairs["ETH-USD"], "BTC-USD");
+        assert!(parse_pairs("BTC-USD").is_none());
+    }
+}
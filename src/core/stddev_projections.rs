@@ -1,7 +1,7 @@
 use serde::{Deserialize, Serialize};
 
 use crate::core::pd_arrays::Pda;
-use crate::models::{CandleSeries, Trend};
+use crate::models::{CandleSeries, Precision, Trend};
 
 const DEVIATION_LEVELS: &[f64] = &[-1.0, -2.0, -4.0, -4.5];
 const PDA_CONFLUENCE_TOLERANCE: f64 = 0.15;
@@ -43,15 +43,29 @@ impl SdProjection {
 
 pub struct StdDevProjector {
     pub projections: Vec<SdProjection>,
+    /// Level prices are rounded to this tick size
+    pub precision: Precision,
+}
+
+impl Default for StdDevProjector {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl StdDevProjector {
     pub fn new() -> Self {
         Self {
             projections: Vec::new(),
+            precision: Precision::default(),
         }
     }
 
+    pub fn with_precision(mut self, precision: Precision) -> Self {
+        self.precision = precision;
+        self
+    }
+
     pub fn project(
         &mut self,
         candles: &CandleSeries,
@@ -81,16 +95,16 @@ impl StdDevProjector {
                 };
 
                 let label = match dev {
-                    x if x == -1.0 => "TP1 (-1 SD) 50%".to_string(),
-                    x if x == -2.0 => "TP2 (-2 SD) 16.7%".to_string(),
-                    x if x == -4.0 => "TP3 (-4 SD) 16.7%".to_string(),
-                    x if x == -4.5 => "TP4 (-4.5 SD) 16.7%".to_string(),
+                    -1.0 => "TP1 (-1 SD) 50%".to_string(),
+                    -2.0 => "TP2 (-2 SD) 16.7%".to_string(),
+                    -4.0 => "TP3 (-4 SD) 16.7%".to_string(),
+                    -4.5 => "TP4 (-4.5 SD) 16.7%".to_string(),
                     _ => format!("SD {}", dev),
                 };
 
                 DeviationLevel {
                     level: dev,
-                    price: round2(price),
+                    price: self.precision.round_price(price),
                     label,
                     has_pda_confluence: false,
                     confluence_pda: None,
@@ -118,7 +132,7 @@ impl StdDevProjector {
             direction,
             anchor_high: manip_high,
             anchor_low: manip_low,
-            range_size: round2(range_size),
+            range_size: self.precision.round_price(range_size),
             recommended_tp: recommended.price,
             recommended_label: recommended.label.clone(),
             levels,
@@ -158,7 +172,7 @@ impl StdDevProjector {
                     "moderate"
                 };
                 zones.push(ConfluenceZone {
-                    price: round2((a.price + b.price) / 2.0),
+                    price: self.precision.round_price((a.price + b.price) / 2.0),
                     levels: vec![a.level, b.level],
                     strength: strength.to_string(),
                 });
@@ -229,7 +243,7 @@ impl StdDevProjector {
         let sd_4 = levels.iter().find(|l| l.level == -4.0);
         let sd_45 = levels.iter().find(|l| l.level == -4.5);
 
-        let already_past_2 = sd_2.map_or(false, |l| match direction {
+        let already_past_2 = sd_2.is_some_and(|l| match direction {
             Trend::Bullish => current > l.price,
             Trend::Bearish => current < l.price,
             Trend::Neutral => false,
@@ -268,10 +282,6 @@ struct LevelInfo {
     range_size: f64,
 }
 
-fn round2(x: f64) -> f64 {
-    (x * 100.0).round() / 100.0
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -2,7 +2,7 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
 use crate::core::pd_arrays::Pda;
-use crate::models::{CandleSeries, Direction, StopMode, SwingType, Trend};
+use crate::models::{CandleSeries, Direction, Precision, StopMode, SwingType, Trend};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProtectedSwing {
@@ -28,28 +28,40 @@ pub struct StopLossLevel {
 
 const MAX_WICK_RATIO_FOR_BODY: f64 = 0.4;
 const MIN_RR_THRESHOLD: f64 = 1.5;
+/// Candles each side of a protected swing when SWING_LOOKBACK is unset
+const DEFAULT_SWING_LOOKBACK: usize = 1;
 
 pub struct StopLossEngine {
     pub swing_lookback: usize,
     pub protected_swings: Vec<ProtectedSwing>,
+    /// Stop prices are rounded to this tick size
+    pub precision: Precision,
+}
+
+impl Default for StopLossEngine {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl StopLossEngine {
     pub fn new() -> Self {
-        let lb: usize = std::env::var("SWING_LOOKBACK")
-            .ok()
-            .and_then(|s| s.parse().ok())
-            .unwrap_or(1);
-        Self::with_lookback(lb)
+        Self::with_lookback(DEFAULT_SWING_LOOKBACK)
     }
 
     pub fn with_lookback(lookback: usize) -> Self {
         Self {
             swing_lookback: lookback,
             protected_swings: Vec::new(),
+            precision: Precision::default(),
         }
     }
 
+    pub fn with_precision(mut self, precision: Precision) -> Self {
+        self.precision = precision;
+        self
+    }
+
     pub fn find_protected_swings(
         &mut self,
         candles: &CandleSeries,
@@ -75,7 +87,7 @@ impl StopLossEngine {
         }
 
         self.protected_swings
-            .sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+            .sort_by_key(|s| std::cmp::Reverse(s.timestamp));
 
         &self.protected_swings
     }
@@ -101,6 +113,7 @@ impl StopLossEngine {
 
         let reward_distance = (take_profit - entry_price).abs();
         let swing_type = swing.swing_type;
+        let dp = self.precision.price_decimals();
 
         // Mode 1: WICK
         let wick_stop = swing.extreme;
@@ -113,14 +126,14 @@ impl StopLossEngine {
 
         if wick_rr >= MIN_RR_THRESHOLD {
             let reason = format!(
-                "Protected swing {} (wick) @ {:.2} | R:R {:.1}",
-                swing_type, wick_stop, wick_rr
+                "Protected swing {} (wick) @ {:.*} | R:R {:.1}",
+                swing_type, dp, wick_stop, wick_rr
             );
             return StopLossLevel {
-                price: round2(wick_stop),
+                price: self.precision.round_price(wick_stop),
                 mode: StopMode::Wick,
                 protected_swing: swing,
-                risk_distance: round2(wick_distance),
+                risk_distance: self.precision.round_price(wick_distance),
                 risk_percent: round3(wick_distance / entry_price * 100.0),
                 reason,
             };
@@ -138,14 +151,14 @@ impl StopLossEngine {
 
         if body_safe && body_rr >= MIN_RR_THRESHOLD {
             let reason = format!(
-                "Protected swing {} (body) @ {:.2} | R:R {:.1}",
-                swing_type, body_stop, body_rr
+                "Protected swing {} (body) @ {:.*} | R:R {:.1}",
+                swing_type, dp, body_stop, body_rr
             );
             return StopLossLevel {
-                price: round2(body_stop),
+                price: self.precision.round_price(body_stop),
                 mode: StopMode::Body,
                 protected_swing: swing,
-                risk_distance: round2(body_distance),
+                risk_distance: self.precision.round_price(body_distance),
                 risk_percent: round3(body_distance / entry_price * 100.0),
                 reason,
             };
@@ -163,14 +176,14 @@ impl StopLossEngine {
 
             if cont_rr >= MIN_RR_THRESHOLD {
                 let reason = format!(
-                    "Continuation swing {} @ {:.2} | R:R {:.1} (tighter than original {:.2})",
-                    swing_type, cont_stop, cont_rr, wick_stop
+                    "Continuation swing {} @ {:.*} | R:R {:.1} (tighter than original {:.*})",
+                    swing_type, dp, cont_stop, cont_rr, dp, wick_stop
                 );
                 return StopLossLevel {
-                    price: round2(cont_stop),
+                    price: self.precision.round_price(cont_stop),
                     mode: StopMode::Continuation,
                     protected_swing: continuation,
-                    risk_distance: round2(cont_distance),
+                    risk_distance: self.precision.round_price(cont_distance),
                     risk_percent: round3(cont_distance / entry_price * 100.0),
                     reason,
                 };
@@ -179,14 +192,14 @@ impl StopLossEngine {
 
         // Fallback to wick
         let reason = format!(
-            "Protected swing {} (wick, low R:R {:.1}) @ {:.2}",
-            swing_type, wick_rr, wick_stop
+            "Protected swing {} (wick, low R:R {:.1}) @ {:.*}",
+            swing_type, wick_rr, dp, wick_stop
         );
         StopLossLevel {
-            price: round2(wick_stop),
+            price: self.precision.round_price(wick_stop),
             mode: StopMode::Wick,
             protected_swing: swing,
-            risk_distance: round2(wick_distance),
+            risk_distance: self.precision.round_price(wick_distance),
             risk_percent: round3(wick_distance / entry_price * 100.0),
             reason,
         }
@@ -212,12 +225,16 @@ impl StopLossEngine {
                     .into_iter()
                     .max_by(|a, b| a.extreme.partial_cmp(&b.extreme).unwrap())?;
                 Some(StopLossLevel {
-                    price: round2(best.extreme),
+                    price: self.precision.round_price(best.extreme),
                     mode: StopMode::Wick,
                     protected_swing: best.clone(),
                     risk_distance: 0.0,
                     risk_percent: 0.0,
-                    reason: format!("Trailing stop: new protected low @ {:.2}", best.extreme),
+                    reason: format!(
+                        "Trailing stop: new protected low @ {:.*}",
+                        self.precision.price_decimals(),
+                        best.extreme
+                    ),
                 })
             }
             Direction::Short => {
@@ -230,12 +247,16 @@ impl StopLossEngine {
                     .into_iter()
                     .min_by(|a, b| a.extreme.partial_cmp(&b.extreme).unwrap())?;
                 Some(StopLossLevel {
-                    price: round2(best.extreme),
+                    price: self.precision.round_price(best.extreme),
                     mode: StopMode::Wick,
                     protected_swing: best.clone(),
                     risk_distance: 0.0,
                     risk_percent: 0.0,
-                    reason: format!("Trailing stop: new protected high @ {:.2}", best.extreme),
+                    reason: format!(
+                        "Trailing stop: new protected high @ {:.*}",
+                        self.precision.price_decimals(),
+                        best.extreme
+                    ),
                 })
             }
         }
@@ -507,7 +528,7 @@ impl StopLossEngine {
         };
 
         StopLossLevel {
-            price: round2(stop),
+            price: self.precision.round_price(stop),
             mode: StopMode::Wick,
             protected_swing: ProtectedSwing {
                 swing_type,
@@ -519,9 +540,13 @@ impl StopLossEngine {
                 strength: 0.1,
                 candle_count: 0,
             },
-            risk_distance: round2((entry - stop).abs()),
+            risk_distance: self.precision.round_price((entry - stop).abs()),
             risk_percent: round3((entry - stop).abs() / entry * 100.0),
-            reason: format!("FALLBACK: ATR-based stop (no protected swing found) @ {:.2}", stop),
+            reason: format!(
+                "FALLBACK: ATR-based stop (no protected swing found) @ {:.*}",
+                self.precision.price_decimals(),
+                stop
+            ),
         }
     }
 }
@@ -556,10 +581,6 @@ pub fn calc_atr(candles: &CandleSeries, period: usize) -> f64 {
     slice.iter().sum::<f64>() / slice.len() as f64
 }
 
-fn round2(x: f64) -> f64 {
-    (x * 100.0).round() / 100.0
-}
-
 fn round3(x: f64) -> f64 {
     (x * 1000.0).round() / 1000.0
 }
@@ -567,7 +588,7 @@ fn round3(x: f64) -> f64 {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::test_helpers::{make_bullish_trend, make_bearish_trend, make_candles};
+    use crate::test_helpers::{make_bullish_trend, make_bearish_trend, make_candles, scale_prices};
 
     #[test]
     fn wick_mode_when_good_rr() {
@@ -583,6 +604,20 @@ mod tests {
         assert!(sl.risk_distance > 0.0);
     }
 
+    #[test]
+    fn stop_keeps_tick_precision_for_low_priced_asset() {
+        // The BTC-scale trend at DOGE prices (~0.1 - 0.4)
+        let candles = scale_prices(&make_bullish_trend(30, 100.0), 0.001);
+        let doge = Precision::for_symbol("DOGE-USD");
+        let mut engine = StopLossEngine::new().with_precision(doge);
+        let sl = engine.get_stop_loss(0.38, Direction::Long, 0.5, &candles, None);
+        assert!(sl.price > 0.0 && sl.price < 0.38);
+        assert_eq!(sl.price, doge.round_price(sl.price));
+        // Cent rounding would have collapsed the stop onto a coarser grid
+        assert_ne!(sl.price, (sl.price * 100.0).round() / 100.0);
+        assert!(sl.reason.contains(&format!("{:.5}", sl.protected_swing.extreme)));
+    }
+
     #[test]
     fn fallback_stop_when_no_swings() {
         // Very few candles => no protected swings found
@@ -1,7 +1,7 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
-use crate::models::{BosType, CandleSeries, SwingType, Trend};
+use crate::models::{BosType, Candle, CandleSeries, SwingType, Trend};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SwingPoint {
@@ -45,12 +45,30 @@ pub struct LiquidityLevels {
     pub ssl: Vec<f64>,
 }
 
+/// Swings, BOS events and trend of a candle window. `analyze` rebuilds
+/// them from scratch; `update` and `push_candle` carry them forward as the
+/// window slides, with the same result.
 pub struct MarketStructure {
     pub swing_lookback: usize,
     pub swing_highs: Vec<SwingPoint>,
     pub swing_lows: Vec<SwingPoint>,
     pub trend: Trend,
     pub bos_events: Vec<BosEvent>,
+    /// Candles analyzed so far
+    window: Vec<Candle>,
+    /// Swing and BOS state through `window[..settled]`, which no later
+    /// candle can change; the public fields add the candles after it
+    settled: usize,
+    settled_highs: Vec<SwingPoint>,
+    settled_lows: Vec<SwingPoint>,
+    /// Each event with the timestamp of the swing it broke
+    settled_bos: Vec<(BosEvent, DateTime<Utc>)>,
+}
+
+impl Default for MarketStructure {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl MarketStructure {
@@ -65,16 +83,108 @@ impl MarketStructure {
             swing_lows: Vec::new(),
             trend: Trend::Neutral,
             bos_events: Vec::new(),
+            window: Vec::new(),
+            settled: 1,
+            settled_highs: Vec::new(),
+            settled_lows: Vec::new(),
+            settled_bos: Vec::new(),
         }
     }
 
     pub fn analyze(&mut self, candles: &CandleSeries) -> Trend {
-        self.swing_highs.clear();
-        self.swing_lows.clear();
-        self.bos_events.clear();
+        self.window = candles.as_slice().to_vec();
+        self.settled = 1;
+        self.settled_bos.clear();
+        self.find_swings();
+        self.settle_and_publish()
+    }
+
+    /// Same as `analyze(candles)`, reusing the previous window's work when
+    /// `candles` is it slid forward (its last candle unchanged and found in
+    /// `candles`, later candles appended, older ones dropped).
+    pub fn update(&mut self, candles: &CandleSeries) -> Trend {
+        let (Some(last), Some(first)) = (self.window.last(), candles.first()) else {
+            return self.analyze(candles);
+        };
+        let slice = candles.as_slice();
+        let at = slice.partition_point(|c| c.timestamp < last.timestamp);
+        let unchanged = slice.get(at).is_some_and(|c| {
+            c.timestamp == last.timestamp
+                && c.open == last.open
+                && c.high == last.high
+                && c.low == last.low
+                && c.close == last.close
+        });
+        // Same overlap length, so nothing was inserted or removed in between
+        let dropped = self
+            .window
+            .partition_point(|c| c.timestamp < first.timestamp);
+        let overlap = self.window.len() - dropped;
+        if !unchanged || first.timestamp < self.window[0].timestamp || overlap != at + 1 {
+            return self.analyze(candles);
+        }
+        if at + 1 == slice.len() && first.timestamp == self.window[0].timestamp {
+            return self.trend;
+        }
+        for candle in &slice[at + 1..] {
+            self.push(candle.clone());
+        }
+        self.trim_before(first.timestamp);
+        self.settle_and_publish()
+    }
+
+    /// Append one candle (newer than the last) and return the new trend.
+    pub fn push_candle(&mut self, candle: &Candle) -> Trend {
+        self.push(candle.clone());
+        self.settle_and_publish()
+    }
+
+    fn push(&mut self, candle: Candle) {
+        self.window.push(candle);
+        // The candle `lb` back now has its full window
+        let lb = self.swing_lookback;
+        let len = self.window.len();
+        if len > lb * 2 {
+            self.check_swing(len - 1 - lb);
+        }
+    }
+
+    /// Drop candles before `start`, with the swings too close to the new
+    /// window's start to be detected in it and the breaks of those swings.
+    fn trim_before(&mut self, start: DateTime<Utc>) {
+        let n = self.window.partition_point(|c| c.timestamp < start);
+        if n == 0 {
+            return;
+        }
+        self.window.drain(..n);
+        self.settled = self.settled.saturating_sub(n).max(1);
+        let keep_from = self.window.get(self.swing_lookback).map(|c| c.timestamp);
+        let kept = |s: &SwingPoint| keep_from.is_some_and(|t| s.timestamp >= t);
+        self.settled_highs.retain(kept);
+        self.settled_lows.retain(kept);
+        self.settled_bos
+            .retain(|(_, swing)| keep_from.is_some_and(|t| *swing >= t));
+    }
+
+    /// Settle BOS for every candle whose earlier swings are all known,
+    /// then publish the settled state plus the remaining candles.
+    fn settle_and_publish(&mut self) -> Trend {
+        // Swings up to `len - 1 - lb` are known, so candles up to `len - lb`
+        let limit = self.window.len().saturating_sub(self.swing_lookback);
+        while self.settled < self.window.len().min(limit + 1) {
+            let candle = &self.window[self.settled];
+            let events = apply_bos(&mut self.settled_highs, &mut self.settled_lows, candle);
+            self.settled_bos.extend(events);
+            self.settled += 1;
+        }
 
-        self.find_swings(candles);
-        self.detect_bos(candles);
+        self.swing_highs = self.settled_highs.clone();
+        self.swing_lows = self.settled_lows.clone();
+        self.bos_events = self.settled_bos.iter().map(|(e, _)| e.clone()).collect();
+        for candle in self.window.iter().skip(self.settled) {
+            let events = apply_bos(&mut self.swing_highs, &mut self.swing_lows, candle);
+            self.bos_events.extend(events.into_iter().map(|(e, _)| e));
+        }
         self.determine_trend();
 
         self.trend
@@ -139,94 +249,49 @@ impl MarketStructure {
         LiquidityLevels { bsl, ssl }
     }
 
-    fn find_swings(&mut self, candles: &CandleSeries) {
+    fn find_swings(&mut self) {
+        self.settled_highs.clear();
+        self.settled_lows.clear();
         let lb = self.swing_lookback;
-        let len = candles.len();
+        let len = self.window.len();
         if len <= lb * 2 {
             return;
         }
 
         for i in lb..(len - lb) {
-            // Swing high: highest high in window
-            let mut is_swing_high = true;
-            let current_high = candles[i].high;
-            for j in (i.saturating_sub(lb))..=(i + lb).min(len - 1) {
-                if candles[j].high > current_high {
-                    is_swing_high = false;
-                    break;
-                }
-            }
-            if is_swing_high {
-                self.swing_highs.push(SwingPoint {
-                    swing_type: SwingType::High,
-                    price: current_high,
-                    timestamp: candles[i].timestamp,
-                    broken: false,
-                });
-            }
-
-            // Swing low: lowest low in window
-            let mut is_swing_low = true;
-            let current_low = candles[i].low;
-            for j in (i.saturating_sub(lb))..=(i + lb).min(len - 1) {
-                if candles[j].low < current_low {
-                    is_swing_low = false;
-                    break;
-                }
-            }
-            if is_swing_low {
-                self.swing_lows.push(SwingPoint {
-                    swing_type: SwingType::Low,
-                    price: current_low,
-                    timestamp: candles[i].timestamp,
-                    broken: false,
-                });
-            }
+            self.check_swing(i);
         }
     }
 
-    fn detect_bos(&mut self, candles: &CandleSeries) {
-        for i in 1..candles.len() {
-            let curr_close = candles[i].close;
-            let curr_ts = candles[i].timestamp;
-
-            // Bullish BOS: close above most recent unbroken swing high
-            let latest_sh = self
-                .swing_highs
-                .iter_mut()
-                .filter(|s| s.timestamp < curr_ts && !s.broken)
-                .max_by(|a, b| a.timestamp.cmp(&b.timestamp));
-
-            if let Some(sh) = latest_sh {
-                if curr_close > sh.price {
-                    let level = sh.price;
-                    sh.broken = true;
-                    self.bos_events.push(BosEvent {
-                        bos_type: BosType::BullishBos,
-                        level,
-                        timestamp: curr_ts,
-                    });
-                }
-            }
+    /// Record `window[i]` as a swing high and/or low if it is the extreme
+    /// of the `lb` candles either side.
+    fn check_swing(&mut self, i: usize) {
+        let lb = self.swing_lookback;
+        let candles = &self.window;
+        let len = candles.len();
+        let range = (i.saturating_sub(lb))..=(i + lb).min(len - 1);
+
+        // Swing high: highest high in window
+        let current_high = candles[i].high;
+        let window = &candles[range];
+        if window.iter().all(|c| c.high <= current_high) {
+            self.settled_highs.push(SwingPoint {
+                swing_type: SwingType::High,
+                price: current_high,
+                timestamp: candles[i].timestamp,
+                broken: false,
+            });
+        }
 
-            // Bearish BOS: close below most recent unbroken swing low
-            let latest_sl = self
-                .swing_lows
-                .iter_mut()
-                .filter(|s| s.timestamp < curr_ts && !s.broken)
-                .max_by(|a, b| a.timestamp.cmp(&b.timestamp));
-
-            if let Some(sl) = latest_sl {
-                if curr_close < sl.price {
-                    let level = sl.price;
-                    sl.broken = true;
-                    self.bos_events.push(BosEvent {
-                        bos_type: BosType::BearishBos,
-                        level,
-                        timestamp: curr_ts,
-                    });
-                }
-            }
+        // Swing low: lowest low in window
+        let current_low = candles[i].low;
+        if window.iter().all(|c| c.low >= current_low) {
+            self.settled_lows.push(SwingPoint {
+                swing_type: SwingType::Low,
+                price: current_low,
+                timestamp: candles[i].timestamp,
+                broken: false,
+            });
         }
     }
 
@@ -258,6 +323,56 @@ impl MarketStructure {
     }
 }
 
+/// Break the most recent unbroken swing high (low) before `candle` when
+/// it closes above (below) it; returns each BOS with the broken swing's
+/// timestamp.
+fn apply_bos(
+    highs: &mut [SwingPoint],
+    lows: &mut [SwingPoint],
+    candle: &Candle,
+) -> Vec<(BosEvent, DateTime<Utc>)> {
+    let mut events = Vec::new();
+    let curr_close = candle.close;
+    let curr_ts = candle.timestamp;
+
+    // Bullish BOS: close above most recent unbroken swing high
+    let latest_sh = highs
+        .iter_mut()
+        .filter(|s| s.timestamp < curr_ts && !s.broken)
+        .max_by(|a, b| a.timestamp.cmp(&b.timestamp));
+
+    if let Some(sh) = latest_sh {
+        if curr_close > sh.price {
+            sh.broken = true;
+            let event = BosEvent {
+                bos_type: BosType::BullishBos,
+                level: sh.price,
+                timestamp: curr_ts,
+            };
+            events.push((event, sh.timestamp));
+        }
+    }
+
+    // Bearish BOS: close below most recent unbroken swing low
+    let latest_sl = lows
+        .iter_mut()
+        .filter(|s| s.timestamp < curr_ts && !s.broken)
+        .max_by(|a, b| a.timestamp.cmp(&b.timestamp));
+
+    if let Some(sl) = latest_sl {
+        if curr_close < sl.price {
+            sl.broken = true;
+            let event = BosEvent {
+                bos_type: BosType::BearishBos,
+                level: sl.price,
+                timestamp: curr_ts,
+            };
+            events.push((event, sl.timestamp));
+        }
+    }
+    events
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -380,6 +495,58 @@ mod tests {
         assert!(max_sh > 150.0);
     }
 
+    #[test]
+    fn incremental_updates_match_full_analysis() {
+        // Random walk so swings and breaks come in every shape
+        let mut seed = 7u64;
+        let mut price = 100.0;
+        let data: Vec<(f64, f64, f64, f64)> = (0..400)
+            .map(|_| {
+                seed = seed
+                    .wrapping_mul(6364136223846793005)
+                    .wrapping_add(1442695040888963407);
+                let step = ((seed >> 33) % 200) as f64 / 100.0 - 1.0;
+                let open = price;
+                price += step;
+                (open, open.max(price) + 0.3, open.min(price) - 0.3, price)
+            })
+            .collect();
+        let candles = make_candles(&data);
+        let state = |ms: &MarketStructure| {
+            let swings: Vec<_> = ms
+                .swing_highs
+                .iter()
+                .chain(&ms.swing_lows)
+                .map(|s| (s.timestamp, s.price, s.broken))
+                .collect();
+            let bos: Vec<_> = ms
+                .bos_events
+                .iter()
+                .map(|e| (e.timestamp, e.level, e.bos_type == BosType::BullishBos))
+                .collect();
+            (swings, bos, ms.trend)
+        };
+
+        let mut sliding = MarketStructure::new();
+        let mut growing = MarketStructure::new();
+        growing.analyze(&candles.head(3));
+        for end in 4..=candles.len() {
+            // Windows of 120 sliding 1-3 candles at a time
+            let window = candles.slice(end.saturating_sub(120), end);
+            if end % 3 == 0 || end == candles.len() {
+                sliding.update(&window);
+                let mut full = MarketStructure::new();
+                full.analyze(&window);
+                assert_eq!(state(&sliding), state(&full), "window ending {}", end);
+            }
+            growing.push_candle(&candles[end - 1]);
+        }
+        let mut full = MarketStructure::new();
+        full.analyze(&candles);
+        assert_eq!(state(&growing), state(&full));
+        assert!(full.bos_events.len() > 10);
+    }
+
     #[test]
     fn dealing_range_equilibrium() {
         let candles = make_bullish_trend(30, 100.0);
@@ -0,0 +1,195 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::core::liquidity::{LiquidityPool, LiquidityType};
+use crate::core::sessions::SessionRange;
+use crate::models::{CandleSeries, Trend};
+
+/// Candles averaged for the ATR a sweep's body is measured against
+const ATR_PERIOD: usize = 14;
+
+/// A candle that wicked through a liquidity pool and closed back on the
+/// near side with a displacement body.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SweepEvent {
+    pub timestamp: DateTime<Utc>,
+    pub pool: LiquidityPool,
+    /// Furthest price traded beyond the pool
+    pub extreme: f64,
+    pub close: f64,
+    /// Candle body in ATRs
+    pub displacement: f64,
+}
+
+impl SweepEvent {
+    /// Direction the sweep sets up: taking sell-side liquidity is bullish.
+    pub fn direction(&self) -> Trend {
+        match self.pool.pool_type {
+            LiquidityType::SSL => Trend::Bullish,
+            LiquidityType::BSL => Trend::Bearish,
+        }
+    }
+}
+
+pub struct SweepDetector {
+    /// Body a sweep candle needs, in ATRs
+    displacement_atr: f64,
+}
+
+impl SweepDetector {
+    pub fn new(displacement_atr: f64) -> Self {
+        Self { displacement_atr }
+    }
+
+    /// First sweep of each pool after its last touch, oldest first. Candles
+    /// without a full ATR window behind them are skipped.
+    pub fn detect(&self, candles: &CandleSeries, pools: &[LiquidityPool]) -> Vec<SweepEvent> {
+        let atrs = rolling_atr(candles);
+        let mut events: Vec<SweepEvent> = pools
+            .iter()
+            .filter_map(|pool| {
+                (ATR_PERIOD..candles.len()).find_map(|i| {
+                    let c = &candles[i];
+                    if c.timestamp <= pool.last_touch || atrs[i] <= 0.0 {
+                        return None;
+                    }
+                    let (through, back_inside, extreme) = match pool.pool_type {
+                        LiquidityType::BSL => (
+                            c.high > pool.price,
+                            c.close < pool.price && c.close < c.open,
+                            c.high,
+                        ),
+                        LiquidityType::SSL => (
+                            c.low < pool.price,
+                            c.close > pool.price && c.close > c.open,
+                            c.low,
+                        ),
+                    };
+                    let displacement = (c.close - c.open).abs() / atrs[i];
+                    (through && back_inside && displacement >= self.displacement_atr).then(|| {
+                        SweepEvent {
+                            timestamp: c.timestamp,
+                            pool: pool.clone(),
+                            extreme,
+                            close: c.close,
+                            displacement,
+                        }
+                    })
+                })
+            })
+            .collect();
+        events.sort_by_key(|e| e.timestamp);
+        events
+    }
+
+    /// Sweeps at or after `since`, oldest first.
+    pub fn recent(
+        &self,
+        candles: &CandleSeries,
+        pools: &[LiquidityPool],
+        since: DateTime<Utc>,
+    ) -> Vec<SweepEvent> {
+        let mut events = self.detect(candles, pools);
+        events.retain(|e| e.timestamp >= since);
+        events
+    }
+
+    /// Sweep of a session's low (`Bullish`) or high (`Bearish`) after it
+    /// closed, provided price has not since closed back beyond it.
+    pub fn session_sweep(
+        &self,
+        candles: &CandleSeries,
+        range: &SessionRange,
+        side: Trend,
+    ) -> Option<SweepEvent> {
+        let (pool_type, price) = match side {
+            Trend::Bullish => (LiquidityType::SSL, range.low),
+            Trend::Bearish => (LiquidityType::BSL, range.high),
+            Trend::Neutral => return None,
+        };
+        let pool = LiquidityPool {
+            pool_type,
+            price,
+            touches: 1,
+            first_touch: range.start,
+            last_touch: range.end,
+            swept: false,
+            strength: 0.5,
+        };
+        let last = candles.last()?.close;
+        let holding = match side {
+            Trend::Bullish => last > price,
+            _ => last < price,
+        };
+        if !holding {
+            return None;
+        }
+        self.detect(candles, std::slice::from_ref(&pool)).pop()
+    }
+}
+
+/// ATR of the `ATR_PERIOD` candles before each index (0 until enough).
+fn rolling_atr(candles: &CandleSeries) -> Vec<f64> {
+    let trs: Vec<f64> = (0..candles.len())
+        .map(|i| {
+            let c = &candles[i];
+            match i.checked_sub(1).map(|p| candles[p].close) {
+                Some(prev) => (c.high - c.low)
+                    .max((c.high - prev).abs())
+                    .max((c.low - prev).abs()),
+                None => c.high - c.low,
+            }
+        })
+        .collect();
+    (0..candles.len())
+        .map(|i| {
+            if i < ATR_PERIOD {
+                0.0
+            } else {
+                trs[i - ATR_PERIOD..i].iter().sum::<f64>() / ATR_PERIOD as f64
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::make_candles;
+
+    #[test]
+    fn sweep_needs_wick_through_close_back_and_displacement() {
+        // Quiet 1-point candles around 100, pool at 98 touched at index 2
+        let mut data = vec![(100.0, 100.5, 99.5, 100.2); 20];
+        data[2] = (99.0, 99.5, 98.0, 99.2);
+        // Wicks through but closes below the pool: not a sweep
+        data.push((99.0, 99.2, 97.5, 97.8));
+        // Wicks through, closes back above, but small body
+        data.push((98.4, 98.8, 97.6, 98.6));
+        // Wicks through, closes back above with displacement
+        data.push((98.3, 100.8, 97.4, 100.6));
+        let candles = make_candles(&data);
+
+        let pools = [LiquidityPool {
+            pool_type: LiquidityType::SSL,
+            price: 98.0,
+            touches: 1,
+            first_touch: candles[2].timestamp,
+            last_touch: candles[2].timestamp,
+            swept: false,
+            strength: 0.3,
+        }];
+        let detector = SweepDetector::new(1.0);
+        let events = detector.detect(&candles, &pools);
+        assert_eq!(events.len(), 1);
+        let e = &events[0];
+        assert_eq!(e.timestamp, candles[22].timestamp);
+        assert_eq!((e.extreme, e.direction()), (97.4, Trend::Bullish));
+        assert!(e.displacement > 1.0);
+
+        let after = candles[22].timestamp + chrono::Duration::minutes(1);
+        assert!(detector.recent(&candles, &pools, after).is_empty());
+        // A stricter threshold rejects the same candle
+        assert!(SweepDetector::new(5.0).detect(&candles, &pools).is_empty());
+    }
+}
@@ -0,0 +1,168 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+use crate::core::sessions::session_at;
+use crate::models::{Candle, CandleSeries};
+
+/// Share of session volume inside the value area
+const VALUE_AREA: f64 = 0.70;
+/// A bin is a high-volume node when it holds this multiple of the mean bin
+const HVN_FACTOR: f64 = 1.5;
+/// Price tolerance (fraction) for a level to count as "at" a price
+const LEVEL_TOLERANCE: f64 = 0.0005;
+
+/// Price-by-volume histogram of one session.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VolumeProfile {
+    pub session: String,
+    pub start: DateTime<Utc>,
+    /// Point of control: midpoint of the busiest bin
+    pub poc: f64,
+    pub value_area_high: f64,
+    pub value_area_low: f64,
+    /// Midpoints of local-maximum bins well above the mean volume
+    pub high_volume_nodes: Vec<f64>,
+}
+
+impl VolumeProfile {
+    /// Each candle's volume is spread evenly over the bins its range covers.
+    /// `None` without volume or price range.
+    pub fn from_candles(session: &str, candles: &[Candle], bins: usize) -> Option<Self> {
+        let low = candles.iter().map(|c| c.low).fold(f64::INFINITY, f64::min);
+        let high = candles
+            .iter()
+            .map(|c| c.high)
+            .fold(f64::NEG_INFINITY, f64::max);
+        if bins == 0 || candles.is_empty() || high <= low {
+            return None;
+        }
+        let size = (high - low) / bins as f64;
+        let bin_of = |price: f64| (((price - low) / size) as usize).min(bins - 1);
+        let mid = |i: usize| low + (i as f64 + 0.5) * size;
+
+        let mut volume = vec![0.0; bins];
+        for c in candles {
+            let (first, last) = (bin_of(c.low), bin_of(c.high));
+            let share = c.volume / (last - first + 1) as f64;
+            for v in &mut volume[first..=last] {
+                *v += share;
+            }
+        }
+        let total: f64 = volume.iter().sum();
+        if total <= 0.0 {
+            return None;
+        }
+
+        let poc = (0..bins)
+            .max_by(|&a, &b| volume[a].total_cmp(&volume[b]))
+            .unwrap_or(0);
+
+        // Grow the value area from the POC toward the busier neighbour
+        let (mut lo, mut hi) = (poc, poc);
+        let mut covered = volume[poc];
+        while covered < total * VALUE_AREA && (lo > 0 || hi < bins - 1) {
+            let below = if lo > 0 { volume[lo - 1] } else { -1.0 };
+            let above = if hi < bins - 1 { volume[hi + 1] } else { -1.0 };
+            if above >= below {
+                hi += 1;
+                covered += above;
+            } else {
+                lo -= 1;
+                covered += below;
+            }
+        }
+
+        let mean = total / bins as f64;
+        let high_volume_nodes = (0..bins)
+            .filter(|&i| {
+                volume[i] >= mean * HVN_FACTOR
+                    && (i == 0 || volume[i] >= volume[i - 1])
+                    && (i == bins - 1 || volume[i] >= volume[i + 1])
+            })
+            .map(mid)
+            .collect();
+
+        Some(Self {
+            session: session.to_string(),
+            start: candles[0].timestamp,
+            poc: mid(poc),
+            value_area_high: low + (hi + 1) as f64 * size,
+            value_area_low: low + lo as f64 * size,
+            high_volume_nodes,
+        })
+    }
+
+    /// POC, value area edges and high-volume nodes.
+    pub fn levels(&self) -> Vec<f64> {
+        let mut levels = vec![self.poc, self.value_area_high, self.value_area_low];
+        levels.extend(&self.high_volume_nodes);
+        levels
+    }
+
+    /// Whether the POC or a high-volume node lies in `[low, high]`.
+    pub fn high_volume_in(&self, low: f64, high: f64) -> bool {
+        let tol = (low + high) / 2.0 * LEVEL_TOLERANCE;
+        std::iter::once(&self.poc)
+            .chain(&self.high_volume_nodes)
+            .any(|&p| p >= low - tol && p <= high + tol)
+    }
+
+    /// Whether any level sits at `price`.
+    pub fn level_at(&self, price: f64) -> bool {
+        self.levels()
+            .iter()
+            .any(|l| (l - price).abs() <= price * LEVEL_TOLERANCE)
+    }
+}
+
+/// One profile per run of consecutive candles in the same configured
+/// session (off-session candles are skipped), oldest first.
+pub fn session_profiles(series: &CandleSeries, cfg: &Config, bins: usize) -> Vec<VolumeProfile> {
+    let mut profiles = Vec::new();
+    let mut run: Vec<Candle> = Vec::new();
+    let mut run_session: Option<&str> = None;
+    for c in series.iter() {
+        let session = session_at(cfg, c.timestamp);
+        if session != run_session && !run.is_empty() {
+            if let Some(p) = run_session.and_then(|s| VolumeProfile::from_candles(s, &run, bins)) {
+                profiles.push(p);
+            }
+            run.clear();
+        }
+        run_session = session;
+        if session.is_some() {
+            run.push(c.clone());
+        }
+    }
+    if let Some(p) = run_session.and_then(|s| VolumeProfile::from_candles(s, &run, bins)) {
+        profiles.push(p);
+    }
+    profiles
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::{default_test_config, make_candles};
+
+    #[test]
+    fn finds_poc_value_area_and_session_runs() {
+        // Most of the volume trades around 105
+        let mut data = vec![(100.0, 101.0, 100.0, 101.0), (109.0, 110.0, 109.0, 110.0)];
+        data.extend(std::iter::repeat_n((105.0, 105.4, 104.6, 105.0), 8));
+        let series = make_candles(&data);
+        let p = VolumeProfile::from_candles("london", series.as_slice(), 10).unwrap();
+        assert!((p.poc - 104.5).abs() < 1e-9 || (p.poc - 105.5).abs() < 1e-9);
+        assert!(p.value_area_low >= 104.0 && p.value_area_high <= 106.0);
+        assert!(p.high_volume_in(104.0, 106.0));
+        assert!(!p.high_volume_in(100.0, 101.0));
+        assert!(p.level_at(p.value_area_high));
+
+        // make_candles starts 12:00 UTC (07:00 ET): ny_forex runs 07:00-10:00
+        let cfg = default_test_config();
+        let profiles = session_profiles(&series, &cfg, 10);
+        assert_eq!(profiles.len(), 1);
+        assert_eq!(profiles[0].session, "ny_forex");
+    }
+}
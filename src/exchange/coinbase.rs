@@ -3,17 +3,21 @@ use async_trait::async_trait;
 use chrono::{DateTime, Timelike, Utc};
 use chrono_tz::US::Eastern;
 use jsonwebtoken::{encode, Algorithm, EncodingKey, Header};
-use reqwest::Client;
+use reqwest::{Client, RequestBuilder, Response};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 use crate::config::Config;
-use crate::exchange::Exchange;
-use crate::models::{Candle, CandleSeries, Timeframe};
+use crate::exchange::metrics;
+use crate::exchange::{Exchange, OrderApi, OrderRejected, OrderSide, OrderState, OrderStatus};
+use crate::models::{Candle, CandleSeries, FundingRate, Instrument, Precision, Timeframe};
 
 const BASE_URL: &str = "https://api.coinbase.com";
 const MIN_REQUEST_INTERVAL: Duration = Duration::from_millis(100);
+/// Most native candles fetched to resample a non-native timeframe from
+const MAX_RESAMPLE_SOURCE: usize = 340;
 
 #[derive(Debug, Serialize)]
 struct JwtClaims {
@@ -49,14 +53,94 @@ struct TickerTrade {
     price: String,
 }
 
+#[derive(Debug, Deserialize)]
+struct ProductResponse {
+    #[serde(default)]
+    future_product_details: Option<FutureProductDetails>,
+}
+
+#[derive(Debug, Deserialize)]
+struct FutureProductDetails {
+    #[serde(default)]
+    perpetual_details: Option<PerpetualDetails>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PerpetualDetails {
+    funding_rate: String,
+    funding_time: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct CreateOrderResponse {
+    success: bool,
+    #[serde(default)]
+    success_response: Option<CreateOrderSuccess>,
+    #[serde(default)]
+    error_response: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CreateOrderSuccess {
+    order_id: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct OrderResponse {
+    order: RawOrder,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawOrder {
+    status: String,
+    #[serde(default)]
+    filled_size: String,
+    #[serde(default)]
+    average_filled_price: String,
+}
+
+static ORDER_SEQ: AtomicU64 = AtomicU64::new(0);
+
+/// Unique client order id (idempotency key for order creation)
+fn client_order_id() -> String {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or(0);
+    format!("ict-{}-{}", nanos, ORDER_SEQ.fetch_add(1, Ordering::Relaxed))
+}
+
+/// Send `request`, recording its latency and outcome under `endpoint`.
+async fn send(endpoint: &str, request: RequestBuilder) -> reqwest::Result<Response> {
+    let started = Instant::now();
+    let resp = request.send().await;
+    let ok = resp.as_ref().is_ok_and(|r| r.status().is_success());
+    metrics::global().record(endpoint, started.elapsed(), ok);
+    resp
+}
+
+/// Size floored to the product's lot size.
+fn fmt_size(x: f64, p: &Precision) -> String {
+    format!("{:.*}", p.size_decimals(), p.round_size(x))
+}
+
+/// Price rounded to the product's tick size.
+fn fmt_price(x: f64, p: &Precision) -> String {
+    format!("{:.*}", p.price_decimals(), p.round_price(x))
+}
+
 pub struct CoinbaseClient {
     client: Client,
     api_key: String,
     api_secret: String,
     symbol: String,
+    precision: Precision,
+    instrument: Instrument,
     last_request: Option<Instant>,
     cache: HashMap<String, (Instant, CandleSeries)>,
     cache_ttl: Duration,
+    /// Stop-limit offset beyond the trigger (fraction)
+    stop_limit_buffer: f64,
 }
 
 impl CoinbaseClient {
@@ -66,9 +150,12 @@ impl CoinbaseClient {
             api_key: cfg.coinbase_api_key.clone(),
             api_secret: cfg.coinbase_api_secret.clone(),
             symbol: cfg.symbol.clone(),
+            precision: cfg.precision(&cfg.symbol),
+            instrument: cfg.instrument(&cfg.symbol),
             last_request: None,
             cache: HashMap::new(),
             cache_ttl: Duration::from_secs(5),
+            stop_limit_buffer: cfg.stop_limit_buffer,
         }
     }
 
@@ -109,18 +196,33 @@ impl CoinbaseClient {
         self.last_request = Some(Instant::now());
     }
 
+    /// Latest `limit` candles; timeframes without a Coinbase granularity
+    /// are resampled from their `resample_source`.
     pub async fn fetch_ohlcv(
         &mut self,
         timeframe: Timeframe,
         limit: usize,
     ) -> Result<CandleSeries> {
+        let Some(source) = timeframe.resample_source() else {
+            return self.fetch_native(timeframe, limit).await;
+        };
+        let ratio = (timeframe.as_seconds() / source.as_seconds()) as usize;
+        let base = self
+            .fetch_native(source, (limit * ratio).min(MAX_RESAMPLE_SOURCE))
+            .await?;
+        Ok(base.resample_to(timeframe).tail(limit))
+    }
+
+    async fn fetch_native(&mut self, timeframe: Timeframe, limit: usize) -> Result<CandleSeries> {
         // Check cache
         let cache_key = format!("{}_{}_{}", self.symbol, timeframe, limit);
         if let Some((cached_at, series)) = self.cache.get(&cache_key) {
             if cached_at.elapsed() < self.cache_ttl {
+                metrics::global().record_cache("candles", true);
                 return Ok(series.clone());
             }
         }
+        metrics::global().record_cache("candles", false);
 
         self.rate_limit().await;
 
@@ -136,7 +238,7 @@ impl CoinbaseClient {
 
         let jwt = self.generate_jwt("GET", &path)?;
 
-        let resp = self
+        let request = self
             .client
             .get(format!("{}{}", BASE_URL, path))
             .query(&[
@@ -145,8 +247,8 @@ impl CoinbaseClient {
                 ("granularity", timeframe.coinbase_granularity().to_string()),
                 ("limit", limit.to_string()),
             ])
-            .header("Authorization", format!("Bearer {}", jwt))
-            .send()
+            .header("Authorization", format!("Bearer {}", jwt));
+        let resp = send("candles", request)
             .await
             .context("Failed to fetch candles")?;
 
@@ -204,7 +306,7 @@ impl CoinbaseClient {
         let limit = ((end_ts - start_ts) / timeframe.as_seconds()).min(300);
         let jwt = self.generate_jwt("GET", &path)?;
 
-        let resp = self
+        let request = self
             .client
             .get(format!("{}{}", BASE_URL, path))
             .query(&[
@@ -213,8 +315,8 @@ impl CoinbaseClient {
                 ("granularity", timeframe.coinbase_granularity().to_string()),
                 ("limit", limit.to_string()),
             ])
-            .header("Authorization", format!("Bearer {}", jwt))
-            .send()
+            .header("Authorization", format!("Bearer {}", jwt));
+        let resp = send("candles", request)
             .await
             .context("Failed to fetch candles (range)")?;
 
@@ -257,12 +359,12 @@ impl CoinbaseClient {
 
         let jwt = self.generate_jwt("GET", &path)?;
 
-        let resp = self
+        let request = self
             .client
             .get(format!("{}{}", BASE_URL, path))
             .query(&[("limit", "1")])
-            .header("Authorization", format!("Bearer {}", jwt))
-            .send()
+            .header("Authorization", format!("Bearer {}", jwt));
+        let resp = send("ticker", request)
             .await
             .context("Failed to fetch ticker")?;
 
@@ -280,6 +382,92 @@ impl CoinbaseClient {
             .context("No price in ticker response")
     }
 
+    /// Funding rate of a perpetual product; `None` for spot.
+    pub async fn get_funding_rate(&mut self) -> Result<Option<FundingRate>> {
+        if !self.instrument.pays_funding() {
+            return Ok(None);
+        }
+        self.rate_limit().await;
+
+        let path = format!("/api/v3/brokerage/products/{}", self.symbol);
+        let jwt = self.generate_jwt("GET", &path)?;
+        let request = self
+            .client
+            .get(format!("{}{}", BASE_URL, path))
+            .header("Authorization", format!("Bearer {}", jwt));
+        let resp = send("product", request)
+            .await
+            .context("Failed to fetch product")?;
+
+        let status = resp.status();
+        if !status.is_success() {
+            let body = resp.text().await.unwrap_or_default();
+            anyhow::bail!("Coinbase product error {}: {}", status, body);
+        }
+
+        let data: ProductResponse = resp.json().await.context("Failed to parse product")?;
+        let Some(perp) = data
+            .future_product_details
+            .and_then(|d| d.perpetual_details)
+        else {
+            return Ok(None);
+        };
+        let rate = perp
+            .funding_rate
+            .parse()
+            .context("Invalid funding rate")?;
+        let time = DateTime::parse_from_rfc3339(&perp.funding_time)
+            .map(|t| t.with_timezone(&Utc))
+            .unwrap_or_else(|_| Utc::now());
+        Ok(Some(FundingRate { time, rate }))
+    }
+
+    async fn create_order(
+        &mut self,
+        side: OrderSide,
+        configuration: serde_json::Value,
+    ) -> Result<String> {
+        self.rate_limit().await;
+
+        let path = "/api/v3/brokerage/orders";
+        let jwt = self.generate_jwt("POST", path)?;
+        let body = serde_json::json!({
+            "client_order_id": client_order_id(),
+            "product_id": self.symbol,
+            "side": side.as_str(),
+            "order_configuration": configuration,
+        });
+
+        let request = self
+            .client
+            .post(format!("{}{}", BASE_URL, path))
+            .header("Authorization", format!("Bearer {}", jwt))
+            .json(&body);
+        let resp = send("create_order", request)
+            .await
+            .context("Failed to create order")?;
+
+        let status = resp.status();
+        if !status.is_success() {
+            let body = resp.text().await.unwrap_or_default();
+            if status.is_client_error() {
+                return Err(OrderRejected(format!("Coinbase {}: {}", status, body)).into());
+            }
+            anyhow::bail!("Coinbase order error {}: {}", status, body);
+        }
+
+        let data: CreateOrderResponse =
+            resp.json().await.context("Failed to parse order response")?;
+        match data.success_response {
+            Some(ok) if data.success => Ok(ok.order_id),
+            _ => Err(OrderRejected(format!(
+                "Coinbase: {}",
+                data.error_response.unwrap_or_default()
+            ))
+            .into()),
+        }
+    }
+
     /// Fetch 4H candles by resampling from 1H
     pub async fn get_4h(&mut self, limit: usize) -> Result<CandleSeries> {
         let hours_needed = (limit * 4).min(340);
@@ -332,4 +520,112 @@ impl Exchange for CoinbaseClient {
     async fn get_midnight_open(&mut self) -> Result<Option<f64>> {
         self.get_midnight_open().await
     }
+
+    async fn get_funding_rate(&mut self) -> Result<Option<FundingRate>> {
+        self.get_funding_rate().await
+    }
+}
+
+#[async_trait]
+impl OrderApi for CoinbaseClient {
+    async fn market_order(&mut self, side: OrderSide, base_size: f64) -> Result<String> {
+        let cfg = serde_json::json!({
+            "market_market_ioc": { "base_size": fmt_size(base_size, &self.precision) }
+        });
+        self.create_order(side, cfg).await
+    }
+
+    async fn limit_order(
+        &mut self,
+        side: OrderSide,
+        base_size: f64,
+        limit_price: f64,
+    ) -> Result<String> {
+        let cfg = serde_json::json!({
+            "limit_limit_gtc": {
+                "base_size": fmt_size(base_size, &self.precision),
+                "limit_price": fmt_price(limit_price, &self.precision),
+                "post_only": false,
+            }
+        });
+        self.create_order(side, cfg).await
+    }
+
+    /// Stop-limit with the limit set `stop_limit_buffer` beyond the trigger so it fills.
+    async fn stop_order(
+        &mut self,
+        side: OrderSide,
+        base_size: f64,
+        stop_price: f64,
+    ) -> Result<String> {
+        let buffer = self.stop_limit_buffer;
+        let (limit_price, stop_direction) = match side {
+            OrderSide::Sell => (stop_price * (1.0 - buffer), "STOP_DIRECTION_STOP_DOWN"),
+            OrderSide::Buy => (stop_price * (1.0 + buffer), "STOP_DIRECTION_STOP_UP"),
+        };
+        let cfg = serde_json::json!({
+            "stop_limit_stop_limit_gtc": {
+                "base_size": fmt_size(base_size, &self.precision),
+                "limit_price": fmt_price(limit_price, &self.precision),
+                "stop_price": fmt_price(stop_price, &self.precision),
+                "stop_direction": stop_direction,
+            }
+        });
+        self.create_order(side, cfg).await
+    }
+
+    async fn cancel_order(&mut self, order_id: &str) -> Result<()> {
+        self.rate_limit().await;
+
+        let path = "/api/v3/brokerage/orders/batch_cancel";
+        let jwt = self.generate_jwt("POST", path)?;
+        let request = self
+            .client
+            .post(format!("{}{}", BASE_URL, path))
+            .header("Authorization", format!("Bearer {}", jwt))
+            .json(&serde_json::json!({ "order_ids": [order_id] }));
+        let resp = send("cancel_order", request)
+            .await
+            .context("Failed to cancel order")?;
+
+        let status = resp.status();
+        if !status.is_success() {
+            let body = resp.text().await.unwrap_or_default();
+            anyhow::bail!("Coinbase cancel error {}: {}", status, body);
+        }
+        Ok(())
+    }
+
+    async fn order_status(&mut self, order_id: &str) -> Result<OrderStatus> {
+        self.rate_limit().await;
+
+        let path = format!("/api/v3/brokerage/orders/historical/{}", order_id);
+        let jwt = self.generate_jwt("GET", &path)?;
+        let request = self
+            .client
+            .get(format!("{}{}", BASE_URL, path))
+            .header("Authorization", format!("Bearer {}", jwt));
+        let resp = send("order_status", request)
+            .await
+            .context("Failed to fetch order")?;
+
+        let status = resp.status();
+        if !status.is_success() {
+            let body = resp.text().await.unwrap_or_default();
+            anyhow::bail!("Coinbase order status error {}: {}", status, body);
+        }
+
+        let data: OrderResponse = resp.json().await.context("Failed to parse order")?;
+        let state = match data.order.status.as_str() {
+            "FILLED" => OrderState::Filled,
+            "CANCELLED" | "EXPIRED" => OrderState::Cancelled,
+            "FAILED" => OrderState::Failed,
+            _ => OrderState::Open,
+        };
+        Ok(OrderStatus {
+            state,
+            filled_size: data.order.filled_size.parse().unwrap_or(0.0),
+            avg_price: data.order.average_filled_price.parse().unwrap_or(0.0),
+        })
+    }
 }
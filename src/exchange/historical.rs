@@ -6,32 +6,100 @@ use std::collections::HashMap;
 use std::time::Duration;
 
 use crate::exchange::Exchange;
-use crate::models::{Candle, CandleSeries, Timeframe};
+use crate::models::{Candle, CandleSeries, FundingRate, Timeframe};
+
+/// Future candles an audit keeps the details of; the rest are only counted
+const MAX_AUDIT_DETAILS: usize = 100;
+
+/// Record of the candles a `HistoricalExchange` served, checked against
+/// its clock (see `with_audit`).
+#[derive(Debug, Clone, Default)]
+pub struct CandleAudit {
+    pub fetches: usize,
+    /// Candles stamped after the sim time they were served at
+    pub future_candles: usize,
+    pub violations: Vec<String>,
+}
+
+impl CandleAudit {
+    /// Count one fetch of `tf` at `now` and record any candle from after it.
+    pub fn check(&mut self, tf: Timeframe, now: DateTime<Utc>, candles: &CandleSeries) {
+        self.fetches += 1;
+        for c in candles.iter().filter(|c| c.timestamp > now) {
+            self.future_candles += 1;
+            if self.violations.len() < MAX_AUDIT_DETAILS {
+                self.violations
+                    .push(format!("{} candle {} served at {}", tf, c.timestamp, now));
+            }
+        }
+    }
+}
 
 /// An Exchange implementation that replays pre-loaded historical data.
 /// A cursor (`now`) controls which candles are visible — only candles
 /// with timestamp <= now are returned, simulating a forward walk.
+#[derive(Clone)]
 pub struct HistoricalExchange {
     data: HashMap<Timeframe, Vec<Candle>>,
+    /// Funding history, oldest first; empty for spot
+    funding: Vec<FundingRate>,
     now: DateTime<Utc>,
-    symbol: String,
+    audit: Option<CandleAudit>,
+}
+
+impl Default for HistoricalExchange {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl HistoricalExchange {
-    pub fn new(symbol: &str) -> Self {
+    pub fn new() -> Self {
         Self {
             data: HashMap::new(),
+            funding: Vec::new(),
             now: Utc::now(),
-            symbol: symbol.to_string(),
+            audit: None,
         }
     }
 
+    /// Check every series `fetch_ohlcv` and `get_4h` return for candles
+    /// past the sim time (look-ahead audit).
+    pub fn with_audit(mut self) -> Self {
+        self.audit = Some(CandleAudit::default());
+        self
+    }
+
+    pub fn audit(&self) -> Option<&CandleAudit> {
+        self.audit.as_ref()
+    }
+
+    /// Every loaded candle of `tf`, regardless of the clock.
+    pub fn series(&self, tf: Timeframe) -> CandleSeries {
+        CandleSeries::new(self.data.get(&tf).cloned().unwrap_or_default())
+    }
+
     /// Load candles for a specific timeframe.
     /// Candles must be sorted oldest-first.
     pub fn load(&mut self, tf: Timeframe, candles: Vec<Candle>) {
         self.data.insert(tf, candles);
     }
 
+    /// Load perpetual funding history (sorted oldest-first).
+    pub fn load_funding(&mut self, rates: Vec<FundingRate>) {
+        self.funding = rates;
+    }
+
+    pub fn has_funding(&self) -> bool {
+        !self.funding.is_empty()
+    }
+
+    /// Latest funding rate published at or before `t`.
+    pub fn funding_rate_at(&self, t: DateTime<Utc>) -> Option<f64> {
+        let end = self.funding.partition_point(|f| f.time <= t);
+        end.checked_sub(1).map(|i| self.funding[i].rate)
+    }
+
     /// Advance the simulation clock.
     pub fn set_time(&mut self, t: DateTime<Utc>) {
         self.now = t;
@@ -57,6 +125,22 @@ impl HistoricalExchange {
             .max()
     }
 
+    /// Candles with `after < timestamp <= until` (oldest first).
+    pub fn candles_between(
+        &self,
+        tf: Timeframe,
+        after: DateTime<Utc>,
+        until: DateTime<Utc>,
+    ) -> &[Candle] {
+        let all = match self.data.get(&tf) {
+            Some(v) => v,
+            None => return &[],
+        };
+        let start = all.partition_point(|c| c.timestamp <= after);
+        let end = all.partition_point(|c| c.timestamp <= until);
+        &all[start..end.max(start)]
+    }
+
     /// Return candles up to `self.now`, capped at `limit`.
     fn visible_candles(&self, tf: Timeframe, limit: usize) -> CandleSeries {
         let empty = Vec::new();
@@ -76,7 +160,20 @@ impl HistoricalExchange {
 #[async_trait]
 impl Exchange for HistoricalExchange {
     async fn fetch_ohlcv(&mut self, tf: Timeframe, limit: usize) -> Result<CandleSeries> {
-        Ok(self.visible_candles(tf, limit))
+        // Timeframes not loaded are resampled from their source, as live
+        let series = match tf.resample_source() {
+            Some(source) if !self.data.contains_key(&tf) => {
+                let ratio = (tf.as_seconds() / source.as_seconds()) as usize;
+                self.visible_candles(source, limit * ratio)
+                    .resample_to(tf)
+                    .tail(limit)
+            }
+            _ => self.visible_candles(tf, limit),
+        };
+        if let Some(audit) = &mut self.audit {
+            audit.check(tf, self.now, &series);
+        }
+        Ok(series)
     }
 
     async fn get_current_price(&mut self) -> Result<f64> {
@@ -92,7 +189,11 @@ impl Exchange for HistoricalExchange {
         // Resample from H1 data
         let hours_needed = (limit * 4).min(340);
         let h1 = self.visible_candles(Timeframe::H1, hours_needed);
-        Ok(h1.resample(Duration::from_secs(14400)))
+        let series = h1.resample(Duration::from_secs(14400));
+        if let Some(audit) = &mut self.audit {
+            audit.check(Timeframe::H4, self.now, &series);
+        }
+        Ok(series)
     }
 
     async fn get_midnight_open(&mut self) -> Result<Option<f64>> {
@@ -120,4 +221,9 @@ impl Exchange for HistoricalExchange {
 
         Ok(None)
     }
+
+    async fn get_funding_rate(&mut self) -> Result<Option<FundingRate>> {
+        let end = self.funding.partition_point(|f| f.time <= self.now);
+        Ok(end.checked_sub(1).map(|i| self.funding[i]))
+    }
 }
@@ -0,0 +1,199 @@
+use serde::Serialize;
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+/// Latencies kept per endpoint for the percentiles.
+const WINDOW: usize = 500;
+
+#[derive(Debug, Default)]
+struct Endpoint {
+    requests: u64,
+    errors: u64,
+    cache_hits: u64,
+    cache_misses: u64,
+    recent: VecDeque<Duration>,
+}
+
+/// Health of one exchange endpoint. Percentiles cover the last `WINDOW`
+/// requests; counts cover the whole run.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct EndpointHealth {
+    pub endpoint: String,
+    pub requests: u64,
+    /// Transport failures and non-2xx responses
+    pub errors: u64,
+    pub error_rate: f64,
+    pub p50_ms: f64,
+    pub p95_ms: f64,
+    pub p99_ms: f64,
+    pub max_ms: f64,
+    pub cache_hits: u64,
+    pub cache_misses: u64,
+    /// `None` for endpoints without a cache
+    pub cache_hit_rate: Option<f64>,
+}
+
+/// Request latency, error and cache counters keyed by endpoint name.
+#[derive(Debug, Default)]
+pub struct ApiMetrics {
+    endpoints: Mutex<BTreeMap<String, Endpoint>>,
+}
+
+/// Nearest-rank percentile of sorted latencies, in ms.
+fn percentile(sorted: &[Duration], p: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let rank = ((p * sorted.len() as f64).ceil() as usize).clamp(1, sorted.len());
+    sorted[rank - 1].as_secs_f64() * 1000.0
+}
+
+impl ApiMetrics {
+    pub fn record(&self, endpoint: &str, latency: Duration, ok: bool) {
+        let Ok(mut endpoints) = self.endpoints.lock() else {
+            return;
+        };
+        let e = endpoints.entry(endpoint.to_string()).or_default();
+        e.requests += 1;
+        if !ok {
+            e.errors += 1;
+        }
+        if e.recent.len() == WINDOW {
+            e.recent.pop_front();
+        }
+        e.recent.push_back(latency);
+    }
+
+    /// A lookup that was (`hit`) or wasn't served without a request.
+    pub fn record_cache(&self, endpoint: &str, hit: bool) {
+        let Ok(mut endpoints) = self.endpoints.lock() else {
+            return;
+        };
+        let e = endpoints.entry(endpoint.to_string()).or_default();
+        if hit {
+            e.cache_hits += 1;
+        } else {
+            e.cache_misses += 1;
+        }
+    }
+
+    pub fn snapshot(&self) -> Vec<EndpointHealth> {
+        let Ok(endpoints) = self.endpoints.lock() else {
+            return Vec::new();
+        };
+        endpoints
+            .iter()
+            .map(|(name, e)| {
+                let mut sorted: Vec<Duration> = e.recent.iter().copied().collect();
+                sorted.sort();
+                let lookups = e.cache_hits + e.cache_misses;
+                EndpointHealth {
+                    endpoint: name.clone(),
+                    requests: e.requests,
+                    errors: e.errors,
+                    error_rate: if e.requests > 0 {
+                        e.errors as f64 / e.requests as f64
+                    } else {
+                        0.0
+                    },
+                    p50_ms: percentile(&sorted, 0.50),
+                    p95_ms: percentile(&sorted, 0.95),
+                    p99_ms: percentile(&sorted, 0.99),
+                    max_ms: sorted.last().map_or(0.0, |d| d.as_secs_f64() * 1000.0),
+                    cache_hits: e.cache_hits,
+                    cache_misses: e.cache_misses,
+                    cache_hit_rate: (lookups > 0).then(|| e.cache_hits as f64 / lookups as f64),
+                }
+            })
+            .collect()
+    }
+}
+
+/// Process-wide metrics shared by every exchange client.
+pub fn global() -> &'static ApiMetrics {
+    static METRICS: OnceLock<ApiMetrics> = OnceLock::new();
+    METRICS.get_or_init(ApiMetrics::default)
+}
+
+/// One-line health summary: totals, the slowest endpoint's p95 and the
+/// overall cache hit rate. `None` before any traffic.
+pub fn summary(health: &[EndpointHealth]) -> Option<String> {
+    let requests: u64 = health.iter().map(|h| h.requests).sum();
+    let hits: u64 = health.iter().map(|h| h.cache_hits).sum();
+    let lookups: u64 = health.iter().map(|h| h.cache_hits + h.cache_misses).sum();
+    if requests == 0 && lookups == 0 {
+        return None;
+    }
+    let errors: u64 = health.iter().map(|h| h.errors).sum();
+    let mut line = format!(
+        "API health: {} req | {:.1}% errors",
+        requests,
+        if requests > 0 {
+            errors as f64 / requests as f64 * 100.0
+        } else {
+            0.0
+        }
+    );
+    if let Some(slowest) = health
+        .iter()
+        .filter(|h| h.requests > 0)
+        .max_by(|a, b| a.p95_ms.total_cmp(&b.p95_ms))
+    {
+        line.push_str(&format!(
+            " | p95 {:.0}ms ({})",
+            slowest.p95_ms, slowest.endpoint
+        ));
+    }
+    if lookups > 0 {
+        line.push_str(&format!(
+            " | cache {:.0}% hit",
+            hits as f64 / lookups as f64 * 100.0
+        ));
+    }
+    Some(line)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tracks_percentiles_errors_and_cache_hits() {
+        let metrics = ApiMetrics::default();
+        assert!(summary(&metrics.snapshot()).is_none());
+
+        for ms in 1..=100 {
+            metrics.record("ticker", Duration::from_millis(ms), ms % 20 != 0);
+        }
+        metrics.record("candles", Duration::from_millis(400), true);
+        for hit in [true, true, true, false] {
+            metrics.record_cache("candles", hit);
+        }
+
+        let health = metrics.snapshot();
+        let candles = &health[0];
+        assert_eq!(candles.endpoint, "candles");
+        assert_eq!(candles.cache_hit_rate, Some(0.75));
+        let ticker = &health[1];
+        assert_eq!(ticker.requests, 100);
+        assert_eq!(ticker.errors, 5);
+        assert!((ticker.error_rate - 0.05).abs() < 1e-12);
+        assert_eq!(ticker.p50_ms, 50.0);
+        assert_eq!(ticker.p95_ms, 95.0);
+        assert_eq!(ticker.max_ms, 100.0);
+        assert_eq!(ticker.cache_hit_rate, None);
+
+        assert_eq!(
+            summary(&health).unwrap(),
+            "API health: 101 req | 5.0% errors | p95 400ms (candles) | cache 75% hit"
+        );
+
+        for _ in 0..WINDOW {
+            metrics.record("ticker", Duration::from_millis(1), true);
+        }
+        let ticker = &metrics.snapshot()[1];
+        assert_eq!(ticker.requests, 100 + WINDOW as u64);
+        assert_eq!(ticker.max_ms, 1.0);
+    }
+}
@@ -1,13 +1,18 @@
 pub mod coinbase;
 pub mod historical;
+pub mod metrics;
+pub mod orders;
+pub mod ws;
 
 pub use coinbase::CoinbaseClient;
 pub use historical::HistoricalExchange;
+pub use orders::{OrderApi, OrderRejected, OrderSide, OrderState, OrderStatus};
+pub use ws::StreamingExchange;
 
 use anyhow::Result;
 use async_trait::async_trait;
 
-use crate::models::{CandleSeries, Timeframe};
+use crate::models::{CandleSeries, FundingRate, Timeframe};
 
 #[async_trait]
 pub trait Exchange: Send + Sync {
@@ -15,4 +20,9 @@ pub trait Exchange: Send + Sync {
     async fn get_current_price(&mut self) -> Result<f64>;
     async fn get_4h(&mut self, limit: usize) -> Result<CandleSeries>;
     async fn get_midnight_open(&mut self) -> Result<Option<f64>>;
+
+    /// Current perpetual funding rate; `None` for spot products.
+    async fn get_funding_rate(&mut self) -> Result<Option<FundingRate>> {
+        Ok(None)
+    }
 }
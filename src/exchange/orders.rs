@@ -0,0 +1,76 @@
+use anyhow::Result;
+use async_trait::async_trait;
+
+use crate::models::Direction;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrderSide {
+    Buy,
+    Sell,
+}
+
+impl OrderSide {
+    /// Side that opens a position in `direction`.
+    pub fn entry(direction: Direction) -> Self {
+        match direction {
+            Direction::Long => OrderSide::Buy,
+            Direction::Short => OrderSide::Sell,
+        }
+    }
+
+    /// Side that reduces / closes a position in `direction`.
+    pub fn exit(direction: Direction) -> Self {
+        match direction {
+            Direction::Long => OrderSide::Sell,
+            Direction::Short => OrderSide::Buy,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OrderSide::Buy => "BUY",
+            OrderSide::Sell => "SELL",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrderState {
+    Open,
+    Filled,
+    Cancelled,
+    Failed,
+}
+
+#[derive(Debug, Clone)]
+pub struct OrderStatus {
+    pub state: OrderState,
+    pub filled_size: f64,
+    pub avg_price: f64,
+}
+
+/// The venue refused an order outright, so none of it can have filled.
+/// Any other placement error leaves the order's fate unknown.
+#[derive(Debug, thiserror::Error)]
+#[error("order rejected: {0}")]
+pub struct OrderRejected(pub String);
+
+/// Order placement for one product. Sizes are in base currency.
+#[async_trait]
+pub trait OrderApi: Send + Sync {
+    async fn market_order(&mut self, side: OrderSide, base_size: f64) -> Result<String>;
+    async fn limit_order(
+        &mut self,
+        side: OrderSide,
+        base_size: f64,
+        limit_price: f64,
+    ) -> Result<String>;
+    async fn stop_order(
+        &mut self,
+        side: OrderSide,
+        base_size: f64,
+        stop_price: f64,
+    ) -> Result<String>;
+    async fn cancel_order(&mut self, order_id: &str) -> Result<()>;
+    async fn order_status(&mut self, order_id: &str) -> Result<OrderStatus>;
+}
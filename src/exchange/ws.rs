@@ -0,0 +1,377 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use futures_util::{SinkExt, StreamExt};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::task::JoinHandle;
+use tokio_tungstenite::{connect_async, tungstenite::Message};
+use tracing::{debug, info, warn};
+
+use crate::config::Config;
+use crate::exchange::{metrics, CoinbaseClient, Exchange};
+use crate::models::{Candle, CandleSeries, FundingRate, Timeframe};
+
+const WS_URL: &str = "wss://advanced-trade-ws.coinbase.com";
+/// Stream is considered dead if silent this long (heartbeats arrive every second)
+const STALE_AFTER: Duration = Duration::from_secs(30);
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(60);
+const MAX_LIVE_CANDLES: usize = 1000;
+/// Timeframe of the candles channel. Only this one is served from the live
+/// series; tick-built bars on other timeframes would carry no volume.
+const STREAMED_TF: Timeframe = Timeframe::M5;
+
+#[derive(Debug, Deserialize)]
+struct WsEnvelope {
+    channel: String,
+    #[serde(default)]
+    timestamp: Option<String>,
+    #[serde(default)]
+    events: Vec<WsEvent>,
+}
+
+#[derive(Debug, Deserialize)]
+struct WsEvent {
+    #[serde(default)]
+    tickers: Vec<WsTicker>,
+    #[serde(default)]
+    candles: Vec<WsCandle>,
+}
+
+#[derive(Debug, Deserialize)]
+struct WsTicker {
+    product_id: String,
+    price: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct WsCandle {
+    product_id: String,
+    start: String,
+    open: String,
+    high: String,
+    low: String,
+    close: String,
+    volume: String,
+}
+
+/// In-memory market state maintained by the stream.
+#[derive(Default)]
+struct LiveState {
+    price: Option<f64>,
+    last_message: Option<Instant>,
+    series: HashMap<Timeframe, CandleSeries>,
+}
+
+impl LiveState {
+    fn healthy(&self) -> bool {
+        self.last_message.is_some_and(|t| t.elapsed() < STALE_AFTER)
+    }
+
+    /// Fold a trade price into the forming candle of every tracked timeframe.
+    fn apply_tick(&mut self, ts: DateTime<Utc>, price: f64) {
+        self.price = Some(price);
+        for (tf, series) in self.series.iter_mut() {
+            let bucket_ts = tf.bucket_start(ts);
+            match series.last_mut() {
+                Some(last) if last.timestamp == bucket_ts => {
+                    last.high = last.high.max(price);
+                    last.low = last.low.min(price);
+                    last.close = price;
+                }
+                // Late tick for an already-closed bar
+                Some(last) if last.timestamp > bucket_ts => {}
+                _ => {
+                    series.push(Candle {
+                        timestamp: bucket_ts,
+                        open: price,
+                        high: price,
+                        low: price,
+                        close: price,
+                        volume: 0.0,
+                    });
+                    series.truncate_front(MAX_LIVE_CANDLES);
+                }
+            }
+        }
+    }
+
+    /// Last `limit` streamed candles, if the stream is healthy and covers them.
+    fn live_series(&self, tf: Timeframe, limit: usize) -> Option<CandleSeries> {
+        if tf != STREAMED_TF || !self.healthy() {
+            return None;
+        }
+        let series = self.series.get(&tf).filter(|s| s.len() >= limit)?;
+        Some(series.tail(limit))
+    }
+
+    /// Seed the streamed timeframe with REST candles.
+    fn backfill(&mut self, tf: Timeframe, rest: &CandleSeries) {
+        if tf != STREAMED_TF {
+            return;
+        }
+        let live = self.series.entry(tf).or_default();
+        for c in rest.iter() {
+            live.upsert(c.clone());
+        }
+        live.truncate_front(MAX_LIVE_CANDLES);
+    }
+
+    fn apply_candle(&mut self, tf: Timeframe, candle: Candle) {
+        if let Some(series) = self.series.get_mut(&tf) {
+            series.upsert(candle);
+            series.truncate_front(MAX_LIVE_CANDLES);
+        }
+    }
+
+    fn handle_message(&mut self, symbol: &str, text: &str) {
+        let env: WsEnvelope = match serde_json::from_str(text) {
+            Ok(e) => e,
+            Err(e) => {
+                debug!("WS parse error: {}", e);
+                return;
+            }
+        };
+        self.last_message = Some(Instant::now());
+
+        let ts = env
+            .timestamp
+            .as_deref()
+            .and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+            .map(|t| t.with_timezone(&Utc))
+            .unwrap_or_else(Utc::now);
+
+        for event in env.events {
+            if env.channel == "ticker" {
+                for t in event.tickers.iter().filter(|t| t.product_id == symbol) {
+                    if let Ok(price) = t.price.parse::<f64>() {
+                        self.apply_tick(ts, price);
+                    }
+                }
+            }
+            if env.channel == "candles" {
+                for c in event.candles.iter().filter(|c| c.product_id == symbol) {
+                    if let Some(candle) = parse_candle(c) {
+                        self.apply_candle(STREAMED_TF, candle);
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn parse_candle(c: &WsCandle) -> Option<Candle> {
+    Some(Candle {
+        timestamp: DateTime::from_timestamp(c.start.parse().ok()?, 0)?,
+        open: c.open.parse().ok()?,
+        high: c.high.parse().ok()?,
+        low: c.low.parse().ok()?,
+        close: c.close.parse().ok()?,
+        volume: c.volume.parse().ok()?,
+    })
+}
+
+/// Coinbase client fed by the Advanced Trade WebSocket (ticker + candles).
+/// The streamed 5m series is backfilled over REST on first use, then kept
+/// live by the stream; other timeframes, and everything while the stream
+/// is quiet, come from REST.
+pub struct StreamingExchange {
+    rest: CoinbaseClient,
+    state: Arc<Mutex<LiveState>>,
+    task: JoinHandle<()>,
+}
+
+impl StreamingExchange {
+    pub fn connect(cfg: &Config) -> Self {
+        let state = Arc::new(Mutex::new(LiveState::default()));
+        let task = tokio::spawn(run_stream(cfg.symbol.clone(), state.clone()));
+        Self {
+            rest: CoinbaseClient::new(cfg),
+            state,
+            task,
+        }
+    }
+
+    pub fn is_live(&self) -> bool {
+        self.state.lock().map(|s| s.healthy()).unwrap_or(false)
+    }
+}
+
+impl Drop for StreamingExchange {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+async fn run_stream(symbol: String, state: Arc<Mutex<LiveState>>) {
+    let mut delay = Duration::from_secs(1);
+    loop {
+        match stream_once(&symbol, &state, &mut delay).await {
+            Ok(()) => warn!("WebSocket closed, reconnecting in {:?}", delay),
+            Err(e) => warn!("WebSocket error: {} (reconnecting in {:?})", e, delay),
+        }
+        tokio::time::sleep(delay).await;
+        delay = (delay * 2).min(MAX_RECONNECT_DELAY);
+    }
+}
+
+async fn stream_once(
+    symbol: &str,
+    state: &Arc<Mutex<LiveState>>,
+    delay: &mut Duration,
+) -> Result<()> {
+    let (mut ws, _) = connect_async(WS_URL).await?;
+    for channel in ["ticker", "candles", "heartbeats"] {
+        let sub = serde_json::json!({
+            "type": "subscribe",
+            "product_ids": [symbol],
+            "channel": channel,
+        });
+        ws.send(Message::text(sub.to_string())).await?;
+    }
+    info!("WebSocket connected ({})", symbol);
+    *delay = Duration::from_secs(1);
+
+    // Anything missed while disconnected is re-backfilled over REST
+    if let Ok(mut st) = state.lock() {
+        st.series.clear();
+    }
+
+    while let Some(msg) = ws.next().await {
+        match msg? {
+            Message::Text(text) => {
+                if let Ok(mut st) = state.lock() {
+                    st.handle_message(symbol, text.as_str());
+                }
+            }
+            Message::Ping(p) => ws.send(Message::Pong(p)).await?,
+            Message::Close(_) => break,
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+#[async_trait]
+impl Exchange for StreamingExchange {
+    async fn fetch_ohlcv(&mut self, tf: Timeframe, limit: usize) -> Result<CandleSeries> {
+        let live = self
+            .state
+            .lock()
+            .ok()
+            .and_then(|st| st.live_series(tf, limit));
+        metrics::global().record_cache("ws_candles", live.is_some());
+        if let Some(series) = live {
+            return Ok(series);
+        }
+
+        let series = self.rest.fetch_ohlcv(tf, limit).await?;
+        if let Ok(mut st) = self.state.lock() {
+            st.backfill(tf, &series);
+        }
+        Ok(series)
+    }
+
+    async fn get_current_price(&mut self) -> Result<f64> {
+        let live = self
+            .state
+            .lock()
+            .ok()
+            .filter(|st| st.healthy())
+            .and_then(|st| st.price);
+        metrics::global().record_cache("ws_ticker", live.is_some());
+        match live {
+            Some(p) => Ok(p),
+            None => self.rest.get_current_price().await,
+        }
+    }
+
+    async fn get_4h(&mut self, limit: usize) -> Result<CandleSeries> {
+        let hours_needed = (limit * 4).min(340);
+        let h1 = Exchange::fetch_ohlcv(self, Timeframe::H1, hours_needed).await?;
+        Ok(h1.resample(Duration::from_secs(14400)))
+    }
+
+    async fn get_midnight_open(&mut self) -> Result<Option<f64>> {
+        self.rest.get_midnight_open().await
+    }
+
+    async fn get_funding_rate(&mut self) -> Result<Option<FundingRate>> {
+        self.rest.get_funding_rate().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::make_candles;
+
+    fn ts(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn ticks_update_and_roll_candles() {
+        let mut st = LiveState::default();
+        // make_candles starts at 2024-01-15T12:00:00Z, 1m apart
+        st.series
+            .insert(Timeframe::M1, make_candles(&[(100.0, 101.0, 99.0, 100.5)]));
+
+        st.apply_tick(ts("2024-01-15T12:00:30Z"), 102.0);
+        let s = &st.series[&Timeframe::M1];
+        assert_eq!(s.len(), 1);
+        assert_eq!(s[0].high, 102.0);
+        assert_eq!(s[0].close, 102.0);
+
+        st.apply_tick(ts("2024-01-15T12:01:05Z"), 98.0);
+        let s = &st.series[&Timeframe::M1];
+        assert_eq!(s.len(), 2);
+        assert_eq!(s[1].open, 98.0);
+        assert_eq!(st.price, Some(98.0));
+
+        // Late tick for the closed bar is ignored
+        st.apply_tick(ts("2024-01-15T12:00:59Z"), 500.0);
+        assert_eq!(st.series[&Timeframe::M1][0].high, 102.0);
+    }
+
+    #[test]
+    fn parses_ticker_and_candle_messages() {
+        let mut st = LiveState::default();
+        st.series.insert(Timeframe::M5, CandleSeries::default());
+        assert!(!st.healthy());
+
+        let ticker = r#"{"channel":"ticker","timestamp":"2024-01-15T12:03:00Z","events":[{"type":"update","tickers":[{"type":"ticker","product_id":"BTC-USD","price":"42000.5"},{"type":"ticker","product_id":"ETH-USD","price":"2500"}]}]}"#;
+        st.handle_message("BTC-USD", ticker);
+        assert!(st.healthy());
+        assert_eq!(st.price, Some(42000.5));
+
+        let candles = r#"{"channel":"candles","timestamp":"2024-01-15T12:03:01Z","events":[{"type":"update","candles":[{"start":"1705320000","high":"42100","low":"41900","open":"41950","close":"42050","volume":"12.5","product_id":"BTC-USD"}]}]}"#;
+        st.handle_message("BTC-USD", candles);
+        let m5 = &st.series[&Timeframe::M5];
+        assert_eq!(m5.len(), 1);
+        assert_eq!(m5[0].volume, 12.5);
+        assert_eq!(m5[0].close, 42050.0);
+
+        st.handle_message("BTC-USD", "not json");
+        assert_eq!(st.price, Some(42000.5));
+    }
+
+    #[test]
+    fn serves_only_the_streamed_timeframe_live() {
+        let mut st = LiveState::default();
+        let rest = make_candles(&[(100.0, 101.0, 99.0, 100.5); 3]);
+        st.backfill(Timeframe::M1, &rest);
+        st.backfill(STREAMED_TF, &rest);
+        assert!(!st.series.contains_key(&Timeframe::M1));
+        assert!(st.live_series(STREAMED_TF, 3).is_none());
+
+        st.last_message = Some(Instant::now());
+        st.apply_tick(ts("2024-01-15T12:10:00Z"), 102.0);
+        // Tick bars never reach callers on timeframes without a candles feed
+        assert!(st.live_series(Timeframe::M1, 1).is_none());
+        let live = st.live_series(STREAMED_TF, 3).unwrap();
+        assert_eq!(live.last().unwrap().close, 102.0);
+    }
+}
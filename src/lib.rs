@@ -1,8 +1,13 @@
+pub mod api;
 pub mod backtesting;
+pub mod bot;
 pub mod config;
+pub mod config_history;
 pub mod core;
 pub mod exchange;
 pub mod models;
+pub mod notifications;
+pub mod storage;
 pub mod strategies;
 #[cfg(test)]
 pub mod test_helpers;
@@ -1,16 +1,94 @@
-mod bot;
-
 use anyhow::Result;
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use clap::{Args, Parser, Subcommand};
+use tracing::Instrument;
 use tracing_subscriber::{fmt, EnvFilter};
 
+use ict_trading_bot::backtesting::commands;
+use ict_trading_bot::bot::IctBot;
 use ict_trading_bot::config::Config;
-use ict_trading_bot::exchange::CoinbaseClient;
+use ict_trading_bot::exchange::{CoinbaseClient, Exchange, StreamingExchange};
+use ict_trading_bot::trading::{accounts, shutdown_report};
+
+/// ICT trading bot: live or paper trading, plus the backtesting tools.
+#[derive(Parser)]
+#[command(version, about)]
+struct Cli {
+    /// TOML config profile; env vars override its settings
+    #[arg(long, global = true)]
+    config: Option<String>,
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Trade live or on paper (PAPER_TRADE); the default
+    Run,
+    /// Backtest a date range and save the reports under data/
+    Backtest {
+        #[command(flatten)]
+        range: Range,
+        /// Minutes between evaluations
+        #[arg(long, default_value_t = 5)]
+        step: i64,
+    },
+    /// Fill the candle store for a date range
+    FetchData {
+        #[command(flatten)]
+        range: Range,
+    },
+    /// Print the summary of a JSON report saved by `backtest`
+    Report {
+        #[arg(long)]
+        file: String,
+    },
+    /// Sweep the OPT_* parameter grid over a date range
+    Optimize {
+        #[command(flatten)]
+        range: Range,
+        /// Minutes between evaluations
+        #[arg(long, default_value_t = 5)]
+        step: i64,
+    },
+}
+
+#[derive(Args)]
+struct Range {
+    /// First day, YYYY-MM-DD (UTC); default 365 days before the end
+    #[arg(long)]
+    start: Option<NaiveDate>,
+    /// Day to stop at, YYYY-MM-DD (UTC); default now
+    #[arg(long)]
+    end: Option<NaiveDate>,
+    /// Product to use instead of the config's first symbol
+    #[arg(long)]
+    symbol: Option<String>,
+}
 
-use crate::bot::IctBot;
+impl Range {
+    fn bounds(&self) -> (DateTime<Utc>, DateTime<Utc>) {
+        let day = |d: NaiveDate| d.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc();
+        let end = self.end.map_or_else(Utc::now, day);
+        let start = self.start.map_or(end - Duration::days(365), day);
+        (start, end)
+    }
+
+    fn config(&self, cfg: Config) -> Config {
+        match &self.symbol {
+            Some(symbol) => cfg.for_symbol(&symbol.to_uppercase()),
+            None => cfg,
+        }
+    }
+}
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    let cfg = Config::from_env();
+    let cli = Cli::parse();
+    let cfg = match &cli.config {
+        Some(path) => Config::from_file(path)?,
+        None => Config::from_env(),
+    };
 
     // Initialize tracing
     let filter = EnvFilter::try_from_default_env()
@@ -22,11 +100,80 @@ async fn main() -> Result<()> {
         .with_timer(fmt::time::UtcTime::rfc_3339())
         .init();
 
-    let market = Box::new(CoinbaseClient::new(&cfg));
-    let shared_config = cfg.shared();
+    match cli.command.unwrap_or(Command::Run) {
+        Command::Run => run(cfg).await,
+        Command::Backtest { range, step } => {
+            let (start, end) = range.bounds();
+            let cfg = range.config(cfg);
+            // The day before the range is loaded as lookback
+            let Some((exchange, start, end)) =
+                commands::load_exchange(&cfg, start - Duration::days(1), end).await?
+            else {
+                return Ok(());
+            };
+            commands::backtest(exchange, cfg, start, end, step).await?;
+            Ok(())
+        }
+        Command::FetchData { range } => {
+            let (start, end) = range.bounds();
+            commands::fetch_data(&range.config(cfg), start, end).await
+        }
+        Command::Report { file } => commands::print_saved_report(&cfg, &file),
+        Command::Optimize { range, step } => {
+            let (start, end) = range.bounds();
+            let cfg = range.config(cfg);
+            let Some((exchange, start, end)) =
+                commands::load_exchange(&cfg, start - Duration::days(1), end).await?
+            else {
+                return Ok(());
+            };
+            commands::optimize(exchange, cfg, start, end, step).await
+        }
+    }
+}
+
+/// One bot per account, each trading all of its symbols.
+async fn run(cfg: Config) -> Result<()> {
+    // Write each account's last recorded state if anything panics
+    shutdown_report::install_panic_hook();
+
+    // One bot per account, each with its own state directory
+    let account_cfgs = cfg.accounts();
+    if account_cfgs.len() > 1 {
+        accounts::log_overview(&account_cfgs);
+    }
+
+    let mut handles = Vec::new();
+    for acct in account_cfgs.clone() {
+        let span = tracing::info_span!("account", name = %acct.account);
+        handles.push(tokio::spawn(
+            async move {
+                let markets: Vec<(String, Box<dyn Exchange>)> = acct
+                    .symbols
+                    .iter()
+                    .map(|symbol| {
+                        let sym_cfg = acct.for_symbol(symbol);
+                        let market: Box<dyn Exchange> = if acct.use_websocket {
+                            Box::new(StreamingExchange::connect(&sym_cfg))
+                        } else {
+                            Box::new(CoinbaseClient::new(&sym_cfg))
+                        };
+                        (symbol.clone(), market)
+                    })
+                    .collect();
+                let mut bot = IctBot::new(acct.shared(), markets).await;
+                bot.run().await
+            }
+            .instrument(span),
+        ));
+    }
+    for handle in handles {
+        handle.await??;
+    }
 
-    let mut bot = IctBot::new(shared_config, market).await;
-    bot.run().await?;
+    if account_cfgs.len() > 1 {
+        accounts::log_overview(&account_cfgs);
+    }
 
     Ok(())
 }
@@ -1,7 +1,9 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Datelike, Utc};
 use serde::{Deserialize, Serialize};
 use std::time::Duration;
 
+use super::Timeframe;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Candle {
     pub timestamp: DateTime<Utc>,
@@ -194,17 +196,43 @@ impl CandleSeries {
 
     /// Resample to a larger timeframe bucket
     pub fn resample(&self, bucket: Duration) -> CandleSeries {
+        let bucket_secs = bucket.as_secs() as i64;
+        self.resample_by(|t| {
+            let ts = t.timestamp();
+            DateTime::from_timestamp(ts - (ts % bucket_secs), 0).unwrap_or(t)
+        })
+    }
+
+    /// Resample into `tf` bars, weeks opening Monday 00:00 UTC.
+    pub fn resample_to(&self, tf: Timeframe) -> CandleSeries {
+        self.resample_by(|t| tf.bucket_start(t))
+    }
+
+    /// Weekly bars opening Monday 00:00 UTC.
+    pub fn resample_weekly(&self) -> CandleSeries {
+        self.resample_to(Timeframe::W1)
+    }
+
+    /// Monthly bars opening on the 1st at 00:00 UTC.
+    pub fn resample_monthly(&self) -> CandleSeries {
+        self.resample_by(|t| {
+            t.date_naive()
+                .with_day(1)
+                .and_then(|d| d.and_hms_opt(0, 0, 0))
+                .map_or(t, |d| d.and_utc())
+        })
+    }
+
+    /// Merge consecutive candles sharing the bucket `bucket_of` maps their
+    /// timestamps to.
+    fn resample_by(&self, bucket_of: impl Fn(DateTime<Utc>) -> DateTime<Utc>) -> CandleSeries {
         if self.candles.is_empty() {
             return CandleSeries::default();
         }
-        let bucket_secs = bucket.as_secs() as i64;
         let mut result: Vec<Candle> = Vec::new();
 
         for candle in &self.candles {
-            let ts = candle.timestamp.timestamp();
-            let bucket_start = ts - (ts % bucket_secs);
-            let bucket_ts =
-                DateTime::from_timestamp(bucket_start, 0).unwrap_or(candle.timestamp);
+            let bucket_ts = bucket_of(candle.timestamp);
 
             if let Some(last) = result.last_mut() {
                 if last.timestamp == bucket_ts {
@@ -254,6 +282,154 @@ impl CandleSeries {
     pub fn push(&mut self, candle: Candle) {
         self.candles.push(candle);
     }
+
+    pub fn last_mut(&mut self) -> Option<&mut Candle> {
+        self.candles.last_mut()
+    }
+
+    /// Replace the candle with the same timestamp, or insert keeping time order.
+    pub fn upsert(&mut self, candle: Candle) {
+        match self
+            .candles
+            .binary_search_by_key(&candle.timestamp, |c| c.timestamp)
+        {
+            Ok(i) => self.candles[i] = candle,
+            Err(i) => self.candles.insert(i, candle),
+        }
+    }
+
+    /// Drop the oldest candles so at most `max_len` remain.
+    pub fn truncate_front(&mut self, max_len: usize) {
+        if self.candles.len() > max_len {
+            self.candles.drain(..self.candles.len() - max_len);
+        }
+    }
+
+    /// Missing bars, repeated timestamps and candles older than their
+    /// predecessor, for `tf` bars.
+    pub fn validate(&self, tf: Timeframe) -> SeriesDiagnostics {
+        let bar = chrono::Duration::seconds(tf.as_seconds() as i64);
+        let mut diag = SeriesDiagnostics::default();
+        for w in self.candles.windows(2) {
+            let (prev, next) = (w[0].timestamp, w[1].timestamp);
+            if next == prev {
+                diag.duplicates.push(next);
+            } else if next < prev {
+                diag.out_of_order.push(next);
+            } else if next - prev > bar {
+                diag.missing_bars += ((next - prev).num_seconds() / bar.num_seconds() - 1) as usize;
+                diag.gaps.push((prev + bar, next));
+            }
+        }
+        diag
+    }
+
+    /// `validate` the series, then repair it with `policy`. The diagnostics
+    /// describe the data as given.
+    pub fn repair(self, tf: Timeframe, policy: GapFill) -> (CandleSeries, SeriesDiagnostics) {
+        let diag = self.validate(tf);
+        if diag.is_clean() || policy == GapFill::Off {
+            return (self, diag);
+        }
+        (self.fill_gaps(tf, policy), diag)
+    }
+
+    /// Sorted copy with duplicates dropped (the later one kept) and, with
+    /// `GapFill::ForwardFill`, every missing bar filled flat at the previous
+    /// close with no volume. `GapFill::Off` returns the series unchanged.
+    pub fn fill_gaps(&self, tf: Timeframe, policy: GapFill) -> CandleSeries {
+        if policy == GapFill::Off {
+            return self.clone();
+        }
+        let mut sorted = self.candles.clone();
+        sorted.sort_by_key(|c| c.timestamp);
+        let bar = chrono::Duration::seconds(tf.as_seconds() as i64);
+        let mut out: Vec<Candle> = Vec::with_capacity(sorted.len());
+        for candle in sorted {
+            match out.last_mut() {
+                Some(last) if last.timestamp == candle.timestamp => {
+                    *last = candle;
+                    continue;
+                }
+                Some(last) if policy == GapFill::ForwardFill => {
+                    let close = last.close;
+                    let mut ts = last.timestamp + bar;
+                    while ts < candle.timestamp {
+                        out.push(Candle {
+                            timestamp: ts,
+                            open: close,
+                            high: close,
+                            low: close,
+                            close,
+                            volume: 0.0,
+                        });
+                        ts += bar;
+                    }
+                }
+                _ => {}
+            }
+            out.push(candle);
+        }
+        CandleSeries::new(out)
+    }
+}
+
+/// Problems `CandleSeries::validate` found.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SeriesDiagnostics {
+    /// `[first missing bar, next candle)` of each hole
+    pub gaps: Vec<(DateTime<Utc>, DateTime<Utc>)>,
+    pub missing_bars: usize,
+    pub duplicates: Vec<DateTime<Utc>>,
+    pub out_of_order: Vec<DateTime<Utc>>,
+}
+
+impl SeriesDiagnostics {
+    pub fn is_clean(&self) -> bool {
+        self.gaps.is_empty() && self.duplicates.is_empty() && self.out_of_order.is_empty()
+    }
+
+    pub fn summary(&self) -> String {
+        format!(
+            "{} gaps ({} bars missing), {} duplicates, {} out of order",
+            self.gaps.len(),
+            self.missing_bars,
+            self.duplicates.len(),
+            self.out_of_order.len()
+        )
+    }
+}
+
+/// How refreshed candles with holes are repaired (env GAP_FILL).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GapFill {
+    /// Log problems but leave the data as fetched
+    #[default]
+    Off,
+    /// Sort and drop duplicates, leaving holes
+    Skip,
+    /// Sort, drop duplicates and fill holes at the previous close
+    ForwardFill,
+}
+
+impl GapFill {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "off" | "" => Some(GapFill::Off),
+            "skip" => Some(GapFill::Skip),
+            "forward_fill" | "ffill" => Some(GapFill::ForwardFill),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            GapFill::Off => "off",
+            GapFill::Skip => "skip",
+            GapFill::ForwardFill => "forward_fill",
+        }
+    }
 }
 
 impl std::ops::Index<usize> for CandleSeries {
@@ -392,6 +568,85 @@ mod tests {
         assert!((resampled[0].close - 105.0).abs() < 1e-9);
     }
 
+    #[test]
+    fn series_resample_weekly_and_monthly_on_calendar_boundaries() {
+        // Daily candles Thu 2024-01-25 .. Fri 2024-02-09
+        let start = DateTime::parse_from_rfc3339("2024-01-25T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let candles = (0..16)
+            .map(|i| Candle {
+                timestamp: start + chrono::Duration::days(i),
+                open: 100.0 + i as f64,
+                high: 110.0 + i as f64,
+                low: 90.0,
+                close: 101.0 + i as f64,
+                volume: 1.0,
+            })
+            .collect();
+        let s = CandleSeries::new(candles);
+
+        let weeks = s.resample_weekly();
+        let opens: Vec<String> = weeks.iter().map(|c| c.timestamp.to_rfc3339()).collect();
+        assert_eq!(
+            opens,
+            [
+                "2024-01-22T00:00:00+00:00",
+                "2024-01-29T00:00:00+00:00",
+                "2024-02-05T00:00:00+00:00"
+            ]
+        );
+        // Mon 29th .. Sun 4th
+        assert_eq!(weeks[1].volume, 7.0);
+        assert!((weeks[1].open - 104.0).abs() < 1e-9);
+        assert!((weeks[1].close - 111.0).abs() < 1e-9);
+
+        let months = s.resample_monthly();
+        assert_eq!(months.len(), 2);
+        assert_eq!(
+            months[1].timestamp.to_rfc3339(),
+            "2024-02-01T00:00:00+00:00"
+        );
+        assert_eq!(months[0].volume, 7.0);
+        // A fixed 7-day bucket starts on the epoch's Thursday instead
+        let fixed = s.resample(std::time::Duration::from_secs(7 * 86400));
+        assert_eq!(fixed[0].timestamp, start);
+    }
+
+    #[test]
+    fn validate_and_fill_gaps() {
+        let data: Vec<(f64, f64, f64, f64)> = (0..6)
+            .map(|i| {
+                let v = 100.0 + i as f64;
+                (v, v + 1.0, v - 1.0, v + 0.5)
+            })
+            .collect();
+        let all = make_candles(&data);
+        // 12:00, 12:01, 12:04, 12:03, 12:03, 12:05: 12:02 never arrives,
+        // 12:03 arrives late and twice
+        let s = CandleSeries::new([0, 1, 4, 3, 3, 5].iter().map(|&i| all[i].clone()).collect());
+
+        let diag = s.validate(Timeframe::M1);
+        assert!(!diag.is_clean());
+        assert_eq!((diag.gaps.len(), diag.missing_bars), (2, 3));
+        assert_eq!(diag.duplicates.len(), 1);
+        assert_eq!(diag.out_of_order.len(), 1);
+
+        let skipped = s.fill_gaps(Timeframe::M1, GapFill::Skip);
+        assert_eq!(skipped.len(), 5);
+        let diag = skipped.validate(Timeframe::M1);
+        assert_eq!((diag.missing_bars, diag.duplicates.len()), (1, 0));
+
+        let (filled, diag) = s.repair(Timeframe::M1, GapFill::ForwardFill);
+        assert_eq!(diag.missing_bars, 3);
+        assert_eq!(filled.len(), 6);
+        assert!(filled.validate(Timeframe::M1).is_clean());
+        // 12:02 filled flat at 12:01's close
+        assert_eq!(filled[2].volume, 0.0);
+        assert!((filled[2].open - 101.5).abs() < 1e-9);
+        assert_eq!(GapFill::parse("ffill"), Some(GapFill::ForwardFill));
+    }
+
     #[test]
     fn series_filter_by_date() {
         let base = DateTime::parse_from_rfc3339("2024-03-10T10:00:00Z")
@@ -420,4 +675,33 @@ mod tests {
         assert_eq!(filtered.len(), 1);
         assert!((filtered[0].open - 100.0).abs() < 1e-9);
     }
+
+    #[test]
+    fn upsert_replaces_or_inserts_in_order() {
+        let mut series = make_candles(&[
+            (100.0, 101.0, 99.0, 100.5),
+            (100.5, 102.0, 100.0, 101.5),
+        ]);
+        let t0 = series[0].timestamp;
+        let t1 = series[1].timestamp;
+
+        let mut replaced = series[1].clone();
+        replaced.close = 110.0;
+        series.upsert(replaced);
+        assert_eq!(series.len(), 2);
+        assert_eq!(series[1].close, 110.0);
+
+        let mut newer = series[1].clone();
+        newer.timestamp = t1 + chrono::Duration::minutes(1);
+        series.upsert(newer);
+        let mut older = series[0].clone();
+        older.timestamp = t0 - chrono::Duration::minutes(1);
+        series.upsert(older);
+        assert_eq!(series.len(), 4);
+        assert!(series.as_slice().windows(2).all(|w| w[0].timestamp < w[1].timestamp));
+
+        series.truncate_front(2);
+        assert_eq!(series.len(), 2);
+        assert_eq!(series[0].timestamp, t1);
+    }
 }
@@ -76,6 +76,20 @@ pub enum PdaType {
     FVG,
     BRK,
     RB,
+    /// Propulsion Block: an OB forming inside an earlier OB that holds its 50%
+    PB,
+    /// Mitigation Block: an OB that failed without sweeping liquidity first
+    MB,
+    /// Inversion FVG: an FVG price closed through, now acting from the other side
+    IFVG,
+    /// Volume Imbalance: gap between consecutive same-direction candle bodies
+    VI,
+    /// Liquidity Void: a run of full-bodied candles leaving one-sided trade
+    LV,
+    /// New Week Opening Gap
+    NWOG,
+    /// New Day Opening Gap
+    NDOG,
 }
 
 impl fmt::Display for PdaType {
@@ -85,6 +99,13 @@ impl fmt::Display for PdaType {
             PdaType::FVG => write!(f, "FVG"),
             PdaType::BRK => write!(f, "BRK"),
             PdaType::RB => write!(f, "RB"),
+            PdaType::PB => write!(f, "PB"),
+            PdaType::MB => write!(f, "MB"),
+            PdaType::IFVG => write!(f, "IFVG"),
+            PdaType::VI => write!(f, "VI"),
+            PdaType::LV => write!(f, "LV"),
+            PdaType::NWOG => write!(f, "NWOG"),
+            PdaType::NDOG => write!(f, "NDOG"),
         }
     }
 }
@@ -126,19 +147,94 @@ impl fmt::Display for StopMode {
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum PositionStatus {
+    /// Limit entry resting until price trades through it
+    Pending,
     Open,
     ClosedTp,
     ClosedSl,
     ClosedManual,
+    ClosedExpired,
+    /// Limit entry that expired unfilled
+    Cancelled,
 }
 
 impl fmt::Display for PositionStatus {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
+            PositionStatus::Pending => write!(f, "pending"),
             PositionStatus::Open => write!(f, "open"),
             PositionStatus::ClosedTp => write!(f, "closed_tp"),
             PositionStatus::ClosedSl => write!(f, "closed_sl"),
             PositionStatus::ClosedManual => write!(f, "closed_manual"),
+            PositionStatus::ClosedExpired => write!(f, "closed_expired"),
+            PositionStatus::Cancelled => write!(f, "cancelled"),
+        }
+    }
+}
+
+/// Why a position was closed; finer-grained than `PositionStatus`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CloseReason {
+    /// Original stop hit
+    StopLoss,
+    /// Stop hit after being trailed from its initial level
+    TrailingStop,
+    /// Stop hit after moving to break-even following a partial TP
+    BreakEven,
+    /// Final take-profit target reached
+    TakeProfit,
+    /// Max hold time elapsed with no target hit
+    Expiry,
+    /// Runner stalled after a partial take-profit
+    Stall,
+    /// Flattened by the operator / kill switch / shutdown
+    Manual,
+    /// Emergency flatten (e.g. the protective stop could not be placed)
+    DisasterStop,
+    /// Flattened ahead of the weekend
+    EndOfWeek,
+}
+
+impl CloseReason {
+    pub const ALL: [CloseReason; 9] = [
+        CloseReason::StopLoss,
+        CloseReason::TrailingStop,
+        CloseReason::BreakEven,
+        CloseReason::TakeProfit,
+        CloseReason::Expiry,
+        CloseReason::Stall,
+        CloseReason::Manual,
+        CloseReason::DisasterStop,
+        CloseReason::EndOfWeek,
+    ];
+
+    /// Coarse status recorded alongside the reason.
+    pub fn status(&self) -> PositionStatus {
+        match self {
+            CloseReason::StopLoss
+            | CloseReason::TrailingStop
+            | CloseReason::BreakEven
+            | CloseReason::DisasterStop => PositionStatus::ClosedSl,
+            CloseReason::Expiry => PositionStatus::ClosedExpired,
+            CloseReason::TakeProfit | CloseReason::Stall => PositionStatus::ClosedTp,
+            CloseReason::Manual | CloseReason::EndOfWeek => PositionStatus::ClosedManual,
+        }
+    }
+}
+
+impl fmt::Display for CloseReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CloseReason::StopLoss => write!(f, "stop_loss"),
+            CloseReason::TrailingStop => write!(f, "trailing_stop"),
+            CloseReason::BreakEven => write!(f, "break_even"),
+            CloseReason::TakeProfit => write!(f, "take_profit"),
+            CloseReason::Expiry => write!(f, "expiry"),
+            CloseReason::Stall => write!(f, "stall"),
+            CloseReason::Manual => write!(f, "manual"),
+            CloseReason::DisasterStop => write!(f, "disaster_stop"),
+            CloseReason::EndOfWeek => write!(f, "end_of_week"),
         }
     }
 }
@@ -0,0 +1,113 @@
+use chrono::{DateTime, Duration, DurationRound, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// How a product is traded: spot, or a perpetual future that pays funding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Instrument {
+    #[default]
+    Spot,
+    Perpetual,
+}
+
+impl Instrument {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "spot" => Some(Instrument::Spot),
+            "perp" | "perpetual" => Some(Instrument::Perpetual),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Instrument::Spot => "spot",
+            Instrument::Perpetual => "perp",
+        }
+    }
+
+    /// Inferred from the product id (e.g. BTC-PERP-INTX is a perpetual).
+    pub fn for_symbol(symbol: &str) -> Self {
+        if symbol.to_uppercase().split('-').any(|part| part == "PERP") {
+            Instrument::Perpetual
+        } else {
+            Instrument::Spot
+        }
+    }
+
+    /// Override if present, else inferred from the symbol.
+    pub fn resolve(overrides: &HashMap<String, Instrument>, symbol: &str) -> Self {
+        overrides
+            .get(&symbol.to_uppercase())
+            .copied()
+            .unwrap_or_else(|| Self::for_symbol(symbol))
+    }
+
+    /// Parse `SYMBOL:spot|perp` entries, comma separated.
+    pub fn parse_list(s: &str) -> Option<HashMap<String, Instrument>> {
+        let mut out = HashMap::new();
+        for entry in s.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let (symbol, kind) = entry.split_once(':')?;
+            out.insert(symbol.trim().to_uppercase(), Self::parse(kind)?);
+        }
+        Some(out)
+    }
+
+    pub fn pays_funding(&self) -> bool {
+        *self == Instrument::Perpetual
+    }
+}
+
+/// A perpetual funding rate: the fraction of notional longs pay shorts at
+/// `time` (negative when shorts pay longs).
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct FundingRate {
+    pub time: DateTime<Utc>,
+    pub rate: f64,
+}
+
+/// Funding settlements in `(after, until]`, every `interval_hours` from
+/// midnight UTC.
+pub fn funding_times(
+    after: DateTime<Utc>,
+    until: DateTime<Utc>,
+    interval_hours: i64,
+) -> Vec<DateTime<Utc>> {
+    let step = Duration::hours(interval_hours.max(1));
+    let Ok(mut t) = after.duration_trunc(step) else {
+        return Vec::new();
+    };
+    let mut out = Vec::new();
+    loop {
+        t += step;
+        if t > until {
+            return out;
+        }
+        out.push(t);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_perps_and_lists_funding_times() {
+        assert_eq!(
+            Instrument::for_symbol("BTC-PERP-INTX"),
+            Instrument::Perpetual
+        );
+        assert_eq!(Instrument::for_symbol("BTC-USD"), Instrument::Spot);
+        let overrides = Instrument::parse_list("btc-usd:perp").unwrap();
+        assert!(Instrument::resolve(&overrides, "BTC-USD").pays_funding());
+        assert!(Instrument::parse_list("BTC-USD:future").is_none());
+
+        let t = |s: &str| DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc);
+        assert_eq!(
+            funding_times(t("2024-01-15T07:30:00Z"), t("2024-01-15T16:00:00Z"), 8),
+            vec![t("2024-01-15T08:00:00Z"), t("2024-01-15T16:00:00Z")]
+        );
+        assert!(funding_times(t("2024-01-15T08:00:00Z"), t("2024-01-15T08:59:00Z"), 1).is_empty());
+    }
+}
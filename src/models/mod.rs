@@ -1,7 +1,11 @@
 pub mod candle;
 pub mod direction;
+pub mod instrument;
+pub mod precision;
 pub mod timeframe;
 
-pub use candle::{Candle, CandleSeries};
+pub use candle::{Candle, CandleSeries, GapFill, SeriesDiagnostics};
 pub use direction::*;
+pub use instrument::{FundingRate, Instrument};
+pub use precision::Precision;
 pub use timeframe::Timeframe;
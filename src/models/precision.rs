@@ -0,0 +1,140 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Venue tick and lot size for one product.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Precision {
+    /// Smallest price step (quote currency)
+    pub price_increment: f64,
+    /// Smallest order size step (base currency)
+    pub size_increment: f64,
+}
+
+impl Default for Precision {
+    /// BTC-USD on Coinbase: cents and satoshis.
+    fn default() -> Self {
+        Self::new(0.01, 0.000_000_01)
+    }
+}
+
+impl Precision {
+    pub fn new(price_increment: f64, size_increment: f64) -> Self {
+        Self {
+            price_increment,
+            size_increment,
+        }
+    }
+
+    /// Built-in increments for common products; unknown symbols use the default.
+    pub fn for_symbol(symbol: &str) -> Self {
+        match symbol.to_uppercase().as_str() {
+            "ETH-USD" => Self::new(0.01, 0.000_000_01),
+            "SOL-USD" => Self::new(0.01, 0.000_000_01),
+            "XRP-USD" => Self::new(0.0001, 0.000_001),
+            "DOGE-USD" => Self::new(0.000_01, 0.1),
+            "EUR-USD" | "GBP-USD" => Self::new(0.000_01, 0.01),
+            _ => Self::default(),
+        }
+    }
+
+    /// Override if present, else the built-in table.
+    pub fn resolve(overrides: &HashMap<String, Precision>, symbol: &str) -> Self {
+        overrides
+            .get(&symbol.to_uppercase())
+            .copied()
+            .unwrap_or_else(|| Self::for_symbol(symbol))
+    }
+
+    /// Parse `SYMBOL:price_inc:size_inc` entries, comma separated
+    /// (e.g. `DOGE-USD:0.00001:1,ETH-USD:0.01:0.0001`).
+    pub fn parse_list(s: &str) -> Option<HashMap<String, Precision>> {
+        let mut out = HashMap::new();
+        for entry in s.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let parts: Vec<&str> = entry.split(':').map(str::trim).collect();
+            let [symbol, price, size] = parts.as_slice() else {
+                return None;
+            };
+            let price: f64 = price.parse().ok().filter(|v: &f64| *v > 0.0)?;
+            let size: f64 = size.parse().ok().filter(|v: &f64| *v > 0.0)?;
+            out.insert(symbol.to_uppercase(), Self::new(price, size));
+        }
+        Some(out)
+    }
+
+    /// Nearest valid price.
+    pub fn round_price(&self, price: f64) -> f64 {
+        let steps = (price / self.price_increment).round();
+        clean(steps * self.price_increment, self.price_decimals())
+    }
+
+    /// Largest valid size not above `size`, so orders never exceed the
+    /// risk they were sized for.
+    pub fn round_size(&self, size: f64) -> f64 {
+        // Snap float noise first so 0.3 / 0.1 = 2.9999999999999996 floors to 3
+        let steps = clean(size / self.size_increment, 6).floor().max(0.0);
+        clean(steps * self.size_increment, self.size_decimals())
+    }
+
+    /// Decimal places implied by the price increment.
+    pub fn price_decimals(&self) -> usize {
+        decimals(self.price_increment)
+    }
+
+    /// Decimal places implied by the size increment.
+    pub fn size_decimals(&self) -> usize {
+        decimals(self.size_increment)
+    }
+}
+
+fn decimals(increment: f64) -> usize {
+    (0..12)
+        .find(|&d| {
+            let scaled = increment * 10f64.powi(d as i32);
+            (scaled - scaled.round()).abs() < 1e-9
+        })
+        .unwrap_or(12)
+}
+
+/// Strip float noise left by multiplying by the increment.
+fn clean(x: f64, decimals: usize) -> f64 {
+    let f = 10f64.powi(decimals as i32);
+    (x * f).round() / f
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rounds_prices_and_sizes_to_venue_increments() {
+        let btc = Precision::for_symbol("BTC-USD");
+        assert_eq!(btc.round_price(50_123.456), 50_123.46);
+        assert_eq!(btc.round_size(0.123_456_789), 0.123_456_78);
+        assert_eq!(btc.price_decimals(), 2);
+
+        let doge = Precision::for_symbol("doge-usd");
+        assert_eq!(doge.round_price(0.081_236_7), 0.081_24);
+        assert_eq!(doge.price_decimals(), 5);
+        assert_eq!(doge.size_decimals(), 1);
+        // Sizes floor to the lot size
+        assert_eq!(doge.round_size(1234.56), 1234.5);
+        assert_eq!(doge.round_size(0.3), 0.3);
+        assert_eq!(doge.round_size(0.05), 0.0);
+
+        let quarter = Precision::new(0.25, 1.0);
+        assert_eq!(quarter.round_price(100.13), 100.25);
+        assert_eq!(quarter.price_decimals(), 2);
+
+        let overrides = Precision::parse_list("DOGE-USD:0.0001:1, ETH-USD:0.05:0.001").unwrap();
+        assert_eq!(
+            Precision::resolve(&overrides, "DOGE-USD"),
+            Precision::new(0.0001, 1.0)
+        );
+        assert_eq!(
+            Precision::resolve(&overrides, "SOL-USD"),
+            Precision::default()
+        );
+        assert!(Precision::parse_list("DOGE-USD:0").is_none());
+        assert!(Precision::parse_list("").unwrap().is_empty());
+    }
+}
@@ -1,3 +1,4 @@
+use chrono::{DateTime, Datelike, Utc};
 use serde::{Deserialize, Serialize};
 use std::fmt;
 use std::time::Duration;
@@ -6,38 +7,62 @@ use std::time::Duration;
 pub enum Timeframe {
     #[serde(rename = "1m")]
     M1,
+    #[serde(rename = "3m")]
+    M3,
     #[serde(rename = "5m")]
     M5,
+    #[serde(rename = "10m")]
+    M10,
     #[serde(rename = "15m")]
     M15,
+    #[serde(rename = "30m")]
+    M30,
     #[serde(rename = "1h")]
     H1,
+    #[serde(rename = "2h")]
+    H2,
     #[serde(rename = "4h")]
     H4,
+    #[serde(rename = "8h")]
+    H8,
     #[serde(rename = "1d")]
     D1,
+    #[serde(rename = "1w")]
+    W1,
 }
 
 impl Timeframe {
     pub fn as_str(&self) -> &'static str {
         match self {
             Timeframe::M1 => "1m",
+            Timeframe::M3 => "3m",
             Timeframe::M5 => "5m",
+            Timeframe::M10 => "10m",
             Timeframe::M15 => "15m",
+            Timeframe::M30 => "30m",
             Timeframe::H1 => "1h",
+            Timeframe::H2 => "2h",
             Timeframe::H4 => "4h",
+            Timeframe::H8 => "8h",
             Timeframe::D1 => "1d",
+            Timeframe::W1 => "1w",
         }
     }
 
     pub fn as_duration(&self) -> Duration {
         match self {
             Timeframe::M1 => Duration::from_secs(60),
+            Timeframe::M3 => Duration::from_secs(180),
             Timeframe::M5 => Duration::from_secs(300),
+            Timeframe::M10 => Duration::from_secs(600),
             Timeframe::M15 => Duration::from_secs(900),
+            Timeframe::M30 => Duration::from_secs(1800),
             Timeframe::H1 => Duration::from_secs(3600),
+            Timeframe::H2 => Duration::from_secs(7200),
             Timeframe::H4 => Duration::from_secs(14400),
+            Timeframe::H8 => Duration::from_secs(28800),
             Timeframe::D1 => Duration::from_secs(86400),
+            Timeframe::W1 => Duration::from_secs(604800),
         }
     }
 
@@ -45,22 +70,46 @@ impl Timeframe {
     pub fn coinbase_granularity(&self) -> &'static str {
         match self {
             Timeframe::M1 => "ONE_MINUTE",
+            Timeframe::M3 => "ONE_MINUTE", // resample from 1m
             Timeframe::M5 => "FIVE_MINUTE",
+            Timeframe::M10 => "FIVE_MINUTE", // resample from 5m
             Timeframe::M15 => "FIFTEEN_MINUTE",
+            Timeframe::M30 => "THIRTY_MINUTE",
             Timeframe::H1 => "ONE_HOUR",
+            Timeframe::H2 => "TWO_HOUR",
             Timeframe::H4 => "ONE_HOUR", // resample from 1h
+            Timeframe::H8 => "ONE_HOUR", // resample from 1h
             Timeframe::D1 => "ONE_DAY",
+            Timeframe::W1 => "ONE_DAY", // resample from 1d
+        }
+    }
+
+    /// Timeframe this one is built from when Coinbase has no granularity
+    /// for it; `None` for native timeframes.
+    pub fn resample_source(&self) -> Option<Timeframe> {
+        match self {
+            Timeframe::M3 => Some(Timeframe::M1),
+            Timeframe::M10 => Some(Timeframe::M5),
+            Timeframe::H4 | Timeframe::H8 => Some(Timeframe::H1),
+            Timeframe::W1 => Some(Timeframe::D1),
+            _ => None,
         }
     }
 
     pub fn from_str_loose(s: &str) -> Option<Timeframe> {
         match s {
             "1m" => Some(Timeframe::M1),
+            "3m" => Some(Timeframe::M3),
             "5m" => Some(Timeframe::M5),
+            "10m" => Some(Timeframe::M10),
             "15m" => Some(Timeframe::M15),
+            "30m" => Some(Timeframe::M30),
             "1h" => Some(Timeframe::H1),
+            "2h" => Some(Timeframe::H2),
             "4h" => Some(Timeframe::H4),
+            "8h" => Some(Timeframe::H8),
             "1d" => Some(Timeframe::D1),
+            "1w" => Some(Timeframe::W1),
             _ => None,
         }
     }
@@ -68,6 +117,19 @@ impl Timeframe {
     pub fn as_seconds(&self) -> u64 {
         self.as_duration().as_secs()
     }
+
+    /// Open time of the bar containing `ts`: Monday 00:00 UTC for weeks,
+    /// a multiple of the bar length since the epoch otherwise.
+    pub fn bucket_start(&self, ts: DateTime<Utc>) -> DateTime<Utc> {
+        let day = ts.date_naive();
+        if *self == Timeframe::W1 {
+            let monday = day - chrono::Days::new(day.weekday().num_days_from_monday() as u64);
+            return monday.and_hms_opt(0, 0, 0).map_or(ts, |t| t.and_utc());
+        }
+        let secs = self.as_seconds() as i64;
+        let bucket = ts.timestamp() - ts.timestamp().rem_euclid(secs);
+        DateTime::from_timestamp(bucket, 0).unwrap_or(ts)
+    }
 }
 
 impl fmt::Display for Timeframe {
@@ -75,3 +137,52 @@ impl fmt::Display for Timeframe {
         write!(f, "{}", self.as_str())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_round_trip_and_sources_divide_evenly() {
+        let all = [
+            Timeframe::M1,
+            Timeframe::M3,
+            Timeframe::M5,
+            Timeframe::M10,
+            Timeframe::M15,
+            Timeframe::M30,
+            Timeframe::H1,
+            Timeframe::H2,
+            Timeframe::H4,
+            Timeframe::H8,
+            Timeframe::D1,
+            Timeframe::W1,
+        ];
+        for tf in all {
+            assert_eq!(Timeframe::from_str_loose(tf.as_str()), Some(tf));
+            let json = serde_json::to_string(&tf).unwrap();
+            assert_eq!(json, format!("\"{}\"", tf));
+            if let Some(source) = tf.resample_source() {
+                assert!(source.resample_source().is_none());
+                assert_eq!(tf.as_seconds() % source.as_seconds(), 0);
+                assert_eq!(tf.coinbase_granularity(), source.coinbase_granularity());
+            }
+        }
+        assert_eq!(Timeframe::M3.as_seconds(), 180);
+        assert_eq!(Timeframe::W1.resample_source(), Some(Timeframe::D1));
+        assert_eq!(Timeframe::M30.coinbase_granularity(), "THIRTY_MINUTE");
+
+        // Thursday 2024-01-18 15:20: its week opened Monday the 15th
+        let ts = DateTime::parse_from_rfc3339("2024-01-18T15:20:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        assert_eq!(
+            Timeframe::W1.bucket_start(ts).to_rfc3339(),
+            "2024-01-15T00:00:00+00:00"
+        );
+        assert_eq!(
+            Timeframe::H4.bucket_start(ts).to_rfc3339(),
+            "2024-01-18T12:00:00+00:00"
+        );
+    }
+}
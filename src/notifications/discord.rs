@@ -0,0 +1,284 @@
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+use chrono::Utc;
+use reqwest::Client;
+use serde_json::{json, Value};
+use std::time::Duration;
+
+use super::{price, Notifier, TradeEvent};
+use crate::models::Direction;
+use crate::trading::trade_record::label;
+
+const GREEN: u32 = 0x2ecc71;
+const RED: u32 = 0xe74c3c;
+const BLUE: u32 = 0x3498db;
+const ORANGE: u32 = 0xe67e22;
+
+/// Posts each event as a rich embed to a Discord webhook.
+pub struct DiscordNotifier {
+    client: Client,
+    webhook_url: String,
+}
+
+impl DiscordNotifier {
+    pub fn new(webhook_url: &str) -> Self {
+        Self {
+            client: Client::builder()
+                .timeout(Duration::from_secs(10))
+                .build()
+                .unwrap_or_default(),
+            webhook_url: webhook_url.to_string(),
+        }
+    }
+}
+
+fn field(name: &str, value: impl Into<String>, inline: bool) -> Value {
+    json!({ "name": name, "value": value.into(), "inline": inline })
+}
+
+fn direction_color(direction: Direction) -> u32 {
+    match direction {
+        Direction::Long => GREEN,
+        Direction::Short => RED,
+    }
+}
+
+/// Webhook body for `event`.
+pub fn embed(event: &TradeEvent) -> Value {
+    let (title, color, description, fields) = match event {
+        TradeEvent::Signal {
+            symbol,
+            scale,
+            signal,
+            metadata,
+        } => {
+            let mut fields = vec![
+                field("Entry", price(signal.entry_price), true),
+                field("Stop", price(signal.stop_loss), true),
+                field("Target", price(signal.take_profit), true),
+                field(
+                    "Confidence",
+                    format!("{:.1}%", signal.confidence * 100.0),
+                    true,
+                ),
+                field(
+                    "PDA",
+                    format!(
+                        "{} {} ({})",
+                        metadata.pda_type,
+                        label(metadata.pda_direction),
+                        label(metadata.pda_zone)
+                    ),
+                    true,
+                ),
+                field(
+                    "CISD",
+                    if signal.cisd_confirmed {
+                        "Confirmed"
+                    } else {
+                        "No"
+                    },
+                    true,
+                ),
+            ];
+            if !metadata.alignment.is_empty() {
+                let rows: Vec<String> = metadata
+                    .alignment
+                    .iter()
+                    .map(|a| format!("{:<4} {:<8} BOS {}", a.tf, a.trend, a.bos))
+                    .collect();
+                fields.push(field(
+                    "Alignment",
+                    format!("```\n{}\n```", rows.join("\n")),
+                    false,
+                ));
+            }
+            (
+                format!("Signal {} {} {}", symbol, scale, signal.direction),
+                direction_color(signal.direction),
+                signal.reason.clone(),
+                fields,
+            )
+        }
+        TradeEvent::Opened {
+            position: p,
+            confidence,
+        } => (
+            format!("Opened #{} {} {} {}", p.id, p.symbol, p.scale, p.direction),
+            direction_color(p.direction),
+            p.reason.clone(),
+            vec![
+                field("Entry", price(p.entry_price), true),
+                field("Stop", price(p.stop_loss), true),
+                field("Target", price(p.take_profit), true),
+                field("Size", format!("${:.2}", p.size_usd), true),
+                field("Confidence", format!("{:.1}%", confidence * 100.0), true),
+            ],
+        ),
+        TradeEvent::PartialTp {
+            position: p,
+            exit,
+            confidence,
+        } => (
+            format!("Partial TP #{} {} {}", p.id, p.symbol, p.scale),
+            GREEN,
+            format!("{} SD filled at {}", exit.level, price(exit.price)),
+            vec![
+                field("PnL", format!("${:+.2}", exit.pnl), true),
+                field("Remaining", format!("{}", p.remaining_size_btc), true),
+                field("Stop", price(p.stop_loss), true),
+                field("Confidence", format!("{:.1}%", confidence * 100.0), true),
+            ],
+        ),
+        TradeEvent::Closed {
+            position: p,
+            confidence,
+        } => (
+            format!(
+                "Closed #{} {} {} ({})",
+                p.id,
+                p.symbol,
+                p.scale,
+                p.close_reason
+                    .map_or_else(|| "unknown".to_string(), |r| r.to_string())
+            ),
+            if p.pnl > 0.0 { GREEN } else { RED },
+            format!(
+                "{} -> {}",
+                price(p.entry_price),
+                price(p.exit_price.unwrap_or(0.0))
+            ),
+            vec![
+                field("PnL", format!("${:+.2}", p.pnl), true),
+                field("Partials", format!("{}", p.partial_exits.len()), true),
+                field("Confidence", format!("{:.1}%", confidence * 100.0), true),
+            ],
+        ),
+        TradeEvent::Stuck { stuck, new_stop } => {
+            let p = &stuck.position;
+            let mut fields = vec![
+                field("Held", format!("{:.0}m", stuck.held_minutes), true),
+                field("Typical", format!("{:.0}m", stuck.typical_minutes), true),
+                field("Progress", format!("{:+.0}%", stuck.progress * 100.0), true),
+                field("Stop", price(p.stop_loss), true),
+            ];
+            if let Some(stop) = new_stop {
+                fields.push(field("New stop", price(*stop), true));
+            }
+            (
+                format!("Stuck #{} {} {} {}", p.id, p.symbol, p.scale, p.direction),
+                ORANGE,
+                format!("Pinned near entry {}", price(p.entry_price)),
+                fields,
+            )
+        }
+        TradeEvent::DailySummary(d) => {
+            let win_rate = if d.trades > 0 {
+                d.wins as f64 / d.trades as f64 * 100.0
+            } else {
+                0.0
+            };
+            (
+                format!("Daily summary {}", d.date),
+                BLUE,
+                format!("End of {}", d.session),
+                vec![
+                    field("Trades", format!("{}", d.trades), true),
+                    field("Win rate", format!("{:.0}%", win_rate), true),
+                    field("PnL", format!("${:+.2}", d.pnl), true),
+                    field("Balance", format!("${:.2}", d.balance), true),
+                    field("Open", format!("{}", d.open_positions), true),
+                ],
+            )
+        }
+        TradeEvent::Alert(text) => ("Alert".to_string(), RED, text.clone(), Vec::new()),
+    };
+    json!({
+        "embeds": [{
+            "title": title,
+            "description": description,
+            "color": color,
+            "fields": fields,
+            "timestamp": Utc::now().to_rfc3339(),
+        }]
+    })
+}
+
+#[async_trait]
+impl Notifier for DiscordNotifier {
+    fn name(&self) -> &str {
+        "discord"
+    }
+
+    async fn notify(&self, event: &TradeEvent) -> Result<()> {
+        let resp = self
+            .client
+            .post(&self.webhook_url)
+            .json(&embed(event))
+            .send()
+            .await?;
+        if !resp.status().is_success() {
+            let status = resp.status();
+            let body = resp.text().await.unwrap_or_default();
+            bail!("Discord webhook {}: {}", status, body);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Direction;
+    use crate::notifications::DailySummary;
+    use crate::strategies::signals::TradeSignal;
+    use crate::test_helpers::make_signal;
+    use crate::trading::trade_record::TradeMetadata;
+
+    #[test]
+    fn signal_embed_shows_pda_cisd_and_alignment() {
+        let signal = TradeSignal {
+            cisd_confirmed: true,
+            reason: "Bullish FVG retest".to_string(),
+            ..make_signal(Direction::Long, 50000.0, 49500.0, 51000.0)
+        };
+        let metadata: TradeMetadata = serde_json::from_value(json!({
+            "scale": "5m", "direction": "long", "confidence": 0.7,
+            "session": "london", "session_weight": 1.5, "cisd_confirmed": true,
+            "pda_type": "FVG", "pda_direction": "bullish", "pda_zone": "discount",
+            "alignment": [{"tf": "15m", "trend": "bullish", "bos": 2}],
+        }))
+        .unwrap();
+        let body = embed(&TradeEvent::Signal {
+            symbol: "BTC-USD".to_string(),
+            scale: "5m".to_string(),
+            signal,
+            metadata: Box::new(metadata),
+        });
+        let e = &body["embeds"][0];
+        assert_eq!(e["title"], "Signal BTC-USD 5m long");
+        assert_eq!(e["color"], GREEN);
+        assert_eq!(e["description"], "Bullish FVG retest");
+        let fields = e["fields"].as_array().unwrap();
+        let value = |name: &str| {
+            fields.iter().find(|f| f["name"] == name).unwrap()["value"]
+                .as_str()
+                .unwrap()
+                .to_string()
+        };
+        assert_eq!(value("PDA"), "FVG bullish (discount)");
+        assert_eq!(value("CISD"), "Confirmed");
+        assert!(value("Alignment").contains("15m  bullish  BOS 2"));
+
+        let summary = embed(&TradeEvent::DailySummary(DailySummary {
+            date: "2024-01-15".to_string(),
+            session: "ny_indices".to_string(),
+            trades: 4,
+            wins: 3,
+            pnl: 12.5,
+            balance: 212.5,
+            open_positions: 0,
+        }));
+        assert_eq!(summary["embeds"][0]["fields"][1]["value"], "75%");
+    }
+}
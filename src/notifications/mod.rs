@@ -0,0 +1,256 @@
+pub mod discord;
+pub mod telegram;
+
+use anyhow::Result;
+use async_trait::async_trait;
+
+use crate::config::Config;
+use crate::strategies::signals::TradeSignal;
+use crate::trading::paper_trader::{PartialExit, Position};
+use crate::trading::stuck_positions::StuckPosition;
+use crate::trading::trade_record::TradeMetadata;
+
+pub use discord::DiscordNotifier;
+pub use telegram::TelegramNotifier;
+
+/// The day so far, posted when a killzone ends.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DailySummary {
+    /// ET date
+    pub date: String,
+    /// Session that just ended
+    pub session: String,
+    /// Trades closed today
+    pub trades: usize,
+    pub wins: usize,
+    pub pnl: f64,
+    pub balance: f64,
+    pub open_positions: usize,
+}
+
+/// A step in a trade's life worth telling the operator about.
+#[derive(Debug, Clone)]
+pub enum TradeEvent {
+    /// Signal passed all filters (sent before the entry attempt)
+    Signal {
+        symbol: String,
+        scale: String,
+        signal: TradeSignal,
+        /// PDA, CISD and alignment context
+        metadata: Box<TradeMetadata>,
+    },
+    Opened {
+        position: Position,
+        confidence: f64,
+    },
+    PartialTp {
+        position: Position,
+        exit: PartialExit,
+        confidence: f64,
+    },
+    Closed {
+        position: Position,
+        confidence: f64,
+    },
+    /// Open far past the scale's typical hold with price pinned near entry
+    Stuck {
+        stuck: StuckPosition,
+        /// Tightened stop, when STUCK_TIGHTEN_STOP moved it
+        new_stop: Option<f64>,
+    },
+    DailySummary(DailySummary),
+    /// Needs the operator, e.g. a live order whose fill couldn't be confirmed
+    Alert(String),
+}
+
+impl TradeEvent {
+    /// Plain-text message body.
+    pub fn message(&self) -> String {
+        match self {
+            TradeEvent::Signal {
+                symbol,
+                scale,
+                signal,
+                ..
+            } => format!(
+                "SIGNAL {} {} {}\nEntry {} | SL {} | TP {}\nConfidence {:.1}%\n{}",
+                symbol,
+                scale,
+                signal.direction,
+                price(signal.entry_price),
+                price(signal.stop_loss),
+                price(signal.take_profit),
+                signal.confidence * 100.0,
+                signal.reason
+            ),
+            TradeEvent::Opened {
+                position: p,
+                confidence,
+            } => format!(
+                "OPENED #{} {} {} {}\nEntry {} | SL {} | TP {}\nSize ${:.2} | Confidence {:.1}%",
+                p.id,
+                p.symbol,
+                p.scale,
+                p.direction,
+                price(p.entry_price),
+                price(p.stop_loss),
+                price(p.take_profit),
+                p.size_usd,
+                confidence * 100.0
+            ),
+            TradeEvent::PartialTp {
+                position: p,
+                exit,
+                confidence,
+            } => format!(
+                "PARTIAL TP #{} {} {} ({} SD) @ {}\nPnL ${:+.2} | Remaining {}\nEntry {} | SL {} | TP {} | Confidence {:.1}%",
+                p.id,
+                p.symbol,
+                p.scale,
+                exit.level,
+                price(exit.price),
+                exit.pnl,
+                p.remaining_size_btc,
+                price(p.entry_price),
+                price(p.stop_loss),
+                price(p.take_profit),
+                confidence * 100.0
+            ),
+            TradeEvent::Closed {
+                position: p,
+                confidence,
+            } => format!(
+                "CLOSED #{} {} {} {} ({})\nPnL ${:+.2} | {} -> {}\nSL {} | TP {} | Confidence {:.1}%",
+                p.id,
+                p.symbol,
+                p.scale,
+                if p.pnl > 0.0 { "WIN" } else { "LOSS" },
+                p.close_reason
+                    .map_or_else(|| "unknown".to_string(), |r| r.to_string()),
+                p.pnl,
+                price(p.entry_price),
+                price(p.exit_price.unwrap_or(0.0)),
+                price(p.stop_loss),
+                price(p.take_profit),
+                confidence * 100.0
+            ),
+            TradeEvent::Stuck { stuck, new_stop } => {
+                let p = &stuck.position;
+                format!(
+                    "STUCK #{} {} {} {}\nHeld {:.0}m (typical {:.0}m) | Progress {:+.0}%\nEntry {} | SL {} | TP {}{}",
+                    p.id,
+                    p.symbol,
+                    p.scale,
+                    p.direction,
+                    stuck.held_minutes,
+                    stuck.typical_minutes,
+                    stuck.progress * 100.0,
+                    price(p.entry_price),
+                    price(p.stop_loss),
+                    price(p.take_profit),
+                    new_stop.map_or_else(String::new, |s| format!("\nStop tightened to {}", price(s)))
+                )
+            }
+            TradeEvent::DailySummary(d) => format!(
+                "DAILY SUMMARY {} (end of {})\nTrades {} | Wins {} | PnL ${:+.2}\nBalance ${:.2} | Open {}",
+                d.date, d.session, d.trades, d.wins, d.pnl, d.balance, d.open_positions
+            ),
+            TradeEvent::Alert(text) => format!("ALERT\n{}", text),
+        }
+    }
+}
+
+/// Enough decimals to tell levels apart on low-priced assets.
+fn price(x: f64) -> String {
+    let dp = if x >= 100.0 {
+        2
+    } else if x >= 1.0 {
+        4
+    } else {
+        6
+    };
+    format!("${:.*}", dp, x)
+}
+
+/// Delivers trade events to an external channel.
+#[async_trait]
+pub trait Notifier: Send + Sync {
+    /// Channel name for logs
+    fn name(&self) -> &str;
+    async fn notify(&self, event: &TradeEvent) -> Result<()>;
+}
+
+/// Notifiers enabled by `cfg` (none when nothing is configured).
+pub fn from_config(cfg: &Config) -> Vec<Box<dyn Notifier>> {
+    let mut out: Vec<Box<dyn Notifier>> = Vec::new();
+    if !cfg.telegram_bot_token.is_empty() && !cfg.telegram_chat_id.is_empty() {
+        out.push(Box::new(TelegramNotifier::new(
+            &cfg.telegram_bot_token,
+            &cfg.telegram_chat_id,
+        )));
+    }
+    if !cfg.discord_webhook_url.is_empty() {
+        out.push(Box::new(DiscordNotifier::new(&cfg.discord_webhook_url)));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{CloseReason, PositionStatus};
+    use crate::test_helpers::default_test_config;
+
+    #[test]
+    fn messages_carry_levels_and_confidence() {
+        let position: Position = serde_json::from_value(serde_json::json!({
+            "id": 7, "symbol": "DOGE-USD", "direction": "short",
+            "entry_price": 0.08124, "size_usd": 50.0, "size_btc": 615.0,
+            "stop_loss": 0.0825, "take_profit": 0.0790,
+            "entry_time": "2024-01-15T12:00:00Z", "reason": "test",
+            "scale": "5m", "status": "open",
+        }))
+        .unwrap();
+        let opened = TradeEvent::Opened {
+            position: position.clone(),
+            confidence: 0.72,
+        }
+        .message();
+        assert!(opened.starts_with("OPENED #7 DOGE-USD 5m short"));
+        assert!(opened.contains("Entry $0.081240 | SL $0.082500 | TP $0.079000"));
+        assert!(opened.contains("Confidence 72.0%"));
+
+        let mut closed = position.clone();
+        closed.status = PositionStatus::ClosedTp;
+        closed.close_reason = Some(CloseReason::TakeProfit);
+        closed.exit_price = Some(0.079);
+        closed.pnl = 1.3;
+        let msg = TradeEvent::Closed {
+            position: closed,
+            confidence: 0.72,
+        }
+        .message();
+        assert!(msg.contains("WIN"));
+        assert!(msg.contains("PnL $+1.30 | $0.081240 -> $0.079000"));
+
+        let stuck = TradeEvent::Stuck {
+            stuck: StuckPosition {
+                position: position.clone(),
+                held_minutes: 310.0,
+                typical_minutes: 100.0,
+                progress: -0.05,
+            },
+            new_stop: Some(0.08187),
+        }
+        .message();
+        assert!(stuck.starts_with("STUCK #7 DOGE-USD 5m short"));
+        assert!(stuck.contains("Held 310m (typical 100m) | Progress -5%"));
+        assert!(stuck.contains("Stop tightened to $0.081870"));
+
+        assert!(from_config(&default_test_config()).is_empty());
+        let mut cfg = default_test_config();
+        cfg.telegram_bot_token = "123:abc".to_string();
+        cfg.telegram_chat_id = "42".to_string();
+        assert_eq!(from_config(&cfg)[0].name(), "telegram");
+    }
+}
@@ -0,0 +1,58 @@
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+use reqwest::Client;
+use std::time::Duration;
+
+use super::{Notifier, TradeEvent};
+
+/// Sends each event as a plain-text message via the Bot API `sendMessage`.
+pub struct TelegramNotifier {
+    client: Client,
+    token: String,
+    chat_id: String,
+}
+
+impl TelegramNotifier {
+    pub fn new(token: &str, chat_id: &str) -> Self {
+        Self {
+            // A slow Telegram must not stall position checks for long
+            client: Client::builder()
+                .timeout(Duration::from_secs(10))
+                .build()
+                .unwrap_or_default(),
+            token: token.to_string(),
+            chat_id: chat_id.to_string(),
+        }
+    }
+
+    pub async fn send(&self, text: &str) -> Result<()> {
+        let url = format!("https://api.telegram.org/bot{}/sendMessage", self.token);
+        let resp = self
+            .client
+            .post(&url)
+            .json(&serde_json::json!({
+                "chat_id": self.chat_id,
+                "text": text,
+                "disable_web_page_preview": true,
+            }))
+            .send()
+            .await?;
+        if !resp.status().is_success() {
+            let status = resp.status();
+            let body = resp.text().await.unwrap_or_default();
+            bail!("Telegram sendMessage {}: {}", status, body);
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Notifier for TelegramNotifier {
+    fn name(&self) -> &str {
+        "telegram"
+    }
+
+    async fn notify(&self, event: &TradeEvent) -> Result<()> {
+        self.send(&event.message()).await
+    }
+}
@@ -0,0 +1,154 @@
+use anyhow::{Context, Result};
+use chrono::Utc;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::{filter_records, RefinerState, TradeQuery, TradeStore, TraderState};
+use crate::config::Config;
+use crate::trading::paper_trader::Position;
+use crate::trading::strategy_refiner::{Adjustment, SkipEntry};
+use crate::trading::trade_record::{self, TradeRecord};
+
+/// The original file layout in `log_dir`: `paper_trades.json`,
+/// `trade_records.json` and `refinements.json`.
+pub struct JsonStore {
+    trades_file: PathBuf,
+    records_file: PathBuf,
+    refinements_file: PathBuf,
+    initial_balance: f64,
+}
+
+impl JsonStore {
+    pub fn new(cfg: &Config) -> Self {
+        let dir = Path::new(&cfg.log_dir);
+        Self {
+            trades_file: dir.join("paper_trades.json"),
+            records_file: dir.join("trade_records.json"),
+            refinements_file: dir.join("refinements.json"),
+            initial_balance: cfg.initial_balance,
+        }
+    }
+}
+
+/// Write-then-rename so a crash never leaves a truncated file.
+fn write_atomic(path: &Path, value: &impl serde::Serialize) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, serde_json::to_string_pretty(value)?)?;
+    fs::rename(&tmp, path).with_context(|| format!("writing {}", path.display()))
+}
+
+fn read_json(path: &Path) -> Option<Value> {
+    let content = fs::read_to_string(path).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+impl TradeStore for JsonStore {
+    fn load_trader(&self) -> Result<Option<TraderState>> {
+        let Some(state) = read_json(&self.trades_file) else {
+            return Ok(None);
+        };
+        let mut out = TraderState {
+            balance: state["balance"].as_f64().unwrap_or(self.initial_balance),
+            trade_counter: state["trade_counter"].as_u64().unwrap_or(0),
+            daily_pnl: state["daily_pnl"].as_f64().unwrap_or(0.0),
+            daily_pnl_date: state["daily_pnl_date"].as_str().unwrap_or("").to_string(),
+            positions: serde_json::from_value::<Vec<Position>>(state["positions"].clone())
+                .unwrap_or_default(),
+            trade_history: serde_json::from_value::<Vec<Position>>(state["trade_history"].clone())
+                .unwrap_or_default(),
+            trade_records: HashMap::new(),
+        };
+
+        if let Ok(content) = fs::read_to_string(&self.records_file) {
+            match trade_record::load_records(&content) {
+                Ok((records, skipped)) => {
+                    if skipped > 0 {
+                        tracing::warn!(
+                            "Skipped {} unreadable trade records in {}",
+                            skipped,
+                            self.records_file.display()
+                        );
+                    }
+                    out.trade_records = records;
+                }
+                Err(e) => {
+                    tracing::warn!("Could not read {}: {}", self.records_file.display(), e)
+                }
+            }
+        }
+        Ok(Some(out))
+    }
+
+    fn save_trader(&self, state: &TraderState) -> Result<()> {
+        let json = serde_json::json!({
+            "balance": state.balance,
+            "trade_counter": state.trade_counter,
+            "daily_pnl": state.daily_pnl,
+            "daily_pnl_date": state.daily_pnl_date,
+            "positions": state.positions,
+            "trade_history": state.trade_history,
+        });
+        write_atomic(&self.trades_file, &json)?;
+        if !state.trade_records.is_empty() {
+            write_atomic(&self.records_file, &state.trade_records)?;
+        }
+        Ok(())
+    }
+
+    fn load_refiner(&self) -> Result<Option<RefinerState>> {
+        let Some(state) = read_json(&self.refinements_file) else {
+            return Ok(None);
+        };
+        let adjustment_history =
+            serde_json::from_value::<Vec<Adjustment>>(state["adjustment_history"].clone())
+                .unwrap_or_default();
+        let skip_combos = match serde_json::from_value::<HashMap<String, SkipEntry>>(
+            state["skip_combos"].clone(),
+        ) {
+            Ok(combos) => combos,
+            // Older files stored bare keys; start their expiry clock now
+            Err(_) => state["skip_combos"]
+                .as_array()
+                .map(|combos| {
+                    let now = Utc::now();
+                    combos
+                        .iter()
+                        .filter_map(|v| v.as_str())
+                        .map(|key| {
+                            let entry = SkipEntry {
+                                since: now,
+                                edge: 0.0,
+                                sample_size: None,
+                            };
+                            (key.to_string(), entry)
+                        })
+                        .collect()
+                })
+                .unwrap_or_default(),
+        };
+        Ok(Some(RefinerState {
+            adjustment_history,
+            skip_combos,
+        }))
+    }
+
+    fn save_refiner(&self, state: &RefinerState) -> Result<()> {
+        let json = serde_json::json!({
+            "adjustment_history": state.adjustment_history,
+            "skip_combos": state.skip_combos,
+        });
+        write_atomic(&self.refinements_file, &json)
+    }
+
+    fn query_records(&self, query: &TradeQuery) -> Result<Vec<TradeRecord>> {
+        Ok(self
+            .load_trader()?
+            .map(|state| filter_records(&state, query))
+            .unwrap_or_default())
+    }
+}
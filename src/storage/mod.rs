@@ -0,0 +1,242 @@
+pub mod json;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use std::collections::HashMap;
+
+use crate::config::{Config, StorageBackend};
+use crate::trading::paper_trader::Position;
+use crate::trading::strategy_refiner::{Adjustment, SkipEntry};
+use crate::trading::trade_record::TradeRecord;
+
+pub use json::JsonStore;
+#[cfg(feature = "sqlite")]
+pub use sqlite::SqliteStore;
+
+/// Everything a `PaperTrader` persists.
+#[derive(Debug, Clone, Default)]
+pub struct TraderState {
+    pub balance: f64,
+    pub trade_counter: u64,
+    pub daily_pnl: f64,
+    pub daily_pnl_date: String,
+    pub positions: Vec<Position>,
+    pub trade_history: Vec<Position>,
+    pub trade_records: HashMap<u64, TradeRecord>,
+}
+
+/// Everything a `StrategyRefiner` persists.
+#[derive(Debug, Clone, Default)]
+pub struct RefinerState {
+    pub adjustment_history: Vec<Adjustment>,
+    pub skip_combos: HashMap<String, SkipEntry>,
+}
+
+/// Filter for `TradeStore::query_records`; `None` fields match everything.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct TradeQuery {
+    pub scale: Option<String>,
+    pub symbol: Option<String>,
+    /// Entered at or after
+    pub since: Option<DateTime<Utc>>,
+    /// Only trades with an outcome
+    pub closed_only: bool,
+    /// Only trades whose signal carried this tag
+    pub tag: Option<String>,
+}
+
+impl TradeQuery {
+    /// `record` carries the queried tag, or no tag is queried.
+    pub fn has_tag(&self, record: &TradeRecord) -> bool {
+        self.tag
+            .as_ref()
+            .is_none_or(|t| record.metadata.tags.contains(t))
+    }
+
+    pub fn matches(&self, record: &TradeRecord, position: Option<&Position>) -> bool {
+        if self
+            .scale
+            .as_deref()
+            .is_some_and(|s| s != record.metadata.scale)
+        {
+            return false;
+        }
+        if self.closed_only && record.outcome.is_empty() {
+            return false;
+        }
+        if !self.has_tag(record) {
+            return false;
+        }
+        if let Some(symbol) = &self.symbol {
+            if position.map(|p| &p.symbol) != Some(symbol) {
+                return false;
+            }
+        }
+        if let Some(since) = self.since {
+            if position.and_then(entry_time).is_none_or(|t| t < since) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+pub(crate) fn entry_time(p: &Position) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(&p.entry_time)
+        .ok()
+        .map(|t| t.with_timezone(&Utc))
+}
+
+/// Persistence for trader and refiner state. Each save replaces the stored
+/// state as a whole, so a crash mid-save leaves the previous state intact.
+pub trait TradeStore: Send {
+    /// `None` when nothing has been stored yet.
+    fn load_trader(&self) -> Result<Option<TraderState>>;
+    fn save_trader(&self, state: &TraderState) -> Result<()>;
+    fn load_refiner(&self) -> Result<Option<RefinerState>>;
+    fn save_refiner(&self, state: &RefinerState) -> Result<()>;
+    /// Trade records matching `query`, oldest entry first.
+    fn query_records(&self, query: &TradeQuery) -> Result<Vec<TradeRecord>>;
+}
+
+/// The store selected by `cfg.storage_backend`, rooted at `cfg.log_dir`.
+pub fn open(cfg: &Config) -> Box<dyn TradeStore> {
+    match cfg.storage_backend {
+        StorageBackend::Json => Box::new(JsonStore::new(cfg)),
+        #[cfg(feature = "sqlite")]
+        StorageBackend::Sqlite => match SqliteStore::open(&format!("{}/trades.db", cfg.log_dir)) {
+            Ok(store) => Box::new(store),
+            Err(e) => {
+                tracing::error!("Could not open SQLite store, using JSON: {:#}", e);
+                Box::new(JsonStore::new(cfg))
+            }
+        },
+        #[cfg(not(feature = "sqlite"))]
+        StorageBackend::Sqlite => {
+            tracing::warn!("STORAGE_BACKEND=sqlite needs the `sqlite` feature; using JSON");
+            Box::new(JsonStore::new(cfg))
+        }
+    }
+}
+
+/// Records for `query` from in-memory state, oldest entry first.
+pub(crate) fn filter_records(state: &TraderState, query: &TradeQuery) -> Vec<TradeRecord> {
+    let positions: HashMap<u64, &Position> = state
+        .positions
+        .iter()
+        .chain(state.trade_history.iter())
+        .map(|p| (p.id, p))
+        .collect();
+    let mut out: Vec<(Option<DateTime<Utc>>, TradeRecord)> = state
+        .trade_records
+        .values()
+        .filter_map(|r| {
+            let pos = positions.get(&r.position_id).copied();
+            query
+                .matches(r, pos)
+                .then(|| (pos.and_then(entry_time), r.clone()))
+        })
+        .collect();
+    out.sort_by_key(|(t, r)| (*t, r.position_id));
+    out.into_iter().map(|(_, r)| r).collect()
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::test_helpers::default_test_config;
+    use chrono::Duration;
+
+    /// Position 1 closed 40 days ago, 2 closed 2 days ago, 3 still open;
+    /// all on 5m with a trade record each.
+    pub(crate) fn sample_state() -> TraderState {
+        let position = |id: u64, age: Duration, status: &str| -> Position {
+            serde_json::from_value(serde_json::json!({
+                "id": id, "symbol": "BTC-USD", "direction": "long",
+                "entry_price": 50000.0, "size_usd": 100.0, "size_btc": 0.002,
+                "stop_loss": 49500.0, "take_profit": 51000.0,
+                "entry_time": (Utc::now() - age).to_rfc3339(),
+                "reason": "test", "scale": "5m", "status": status,
+            }))
+            .unwrap()
+        };
+        let record = |id: u64, outcome: &str| -> TradeRecord {
+            serde_json::from_value(serde_json::json!({
+                "schema_version": 1, "position_id": id,
+                "metadata": {
+                    "scale": "5m", "direction": "long", "confidence": 0.7,
+                    "session": "london", "session_weight": 1.5, "cisd_confirmed": false,
+                },
+                "outcome": outcome,
+            }))
+            .unwrap()
+        };
+        let mut old = position(1, Duration::days(40), "closed_sl");
+        old.partial_exits
+            .push(crate::trading::paper_trader::PartialExit {
+                level: -1.0,
+                price: 50500.0,
+                size_btc: 0.001,
+                pnl: 0.5,
+                time: Utc::now().to_rfc3339(),
+                logged: true,
+                slippage: 0.0,
+            });
+        TraderState {
+            balance: 1010.0,
+            trade_counter: 3,
+            daily_pnl: 10.0,
+            daily_pnl_date: Utc::now().format("%Y-%m-%d").to_string(),
+            positions: vec![position(3, Duration::hours(1), "open")],
+            trade_history: vec![old, position(2, Duration::days(2), "closed_tp")],
+            trade_records: HashMap::from([
+                (1, record(1, "loss")),
+                (2, record(2, "win")),
+                (3, record(3, "")),
+            ]),
+        }
+    }
+
+    #[test]
+    fn json_store_round_trips_and_queries() {
+        let mut cfg = default_test_config();
+        let dir = std::env::temp_dir().join(format!("ict_json_store_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        cfg.log_dir = dir.to_string_lossy().to_string();
+        let store = open(&cfg);
+        assert!(store.load_trader().unwrap().is_none());
+
+        store.save_trader(&sample_state()).unwrap();
+        let loaded = store.load_trader().unwrap().unwrap();
+        assert_eq!(loaded.balance, 1010.0);
+        assert_eq!(loaded.trade_history[0].partial_exits.len(), 1);
+
+        let last_30_days = TradeQuery {
+            scale: Some("5m".to_string()),
+            since: Some(Utc::now() - Duration::days(30)),
+            ..Default::default()
+        };
+        let ids: Vec<u64> = store
+            .query_records(&last_30_days)
+            .unwrap()
+            .iter()
+            .map(|r| r.position_id)
+            .collect();
+        assert_eq!(ids, vec![2, 3]);
+        let closed = TradeQuery {
+            closed_only: true,
+            ..last_30_days
+        };
+        assert_eq!(store.query_records(&closed).unwrap().len(), 1);
+        let other = TradeQuery {
+            symbol: Some("ETH-USD".to_string()),
+            ..Default::default()
+        };
+        assert!(store.query_records(&other).unwrap().is_empty());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
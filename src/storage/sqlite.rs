@@ -0,0 +1,370 @@
+use anyhow::{Context, Result};
+use rusqlite::{params, Connection, OptionalExtension};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use super::{entry_time, RefinerState, TradeQuery, TradeStore, TraderState};
+use crate::trading::paper_trader::{PartialExit, Position};
+use crate::trading::trade_record::TradeRecord;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS account (
+    id INTEGER PRIMARY KEY CHECK (id = 0),
+    balance REAL NOT NULL,
+    trade_counter INTEGER NOT NULL,
+    daily_pnl REAL NOT NULL,
+    daily_pnl_date TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS positions (
+    id INTEGER PRIMARY KEY,
+    seq INTEGER NOT NULL,
+    symbol TEXT NOT NULL,
+    scale TEXT NOT NULL,
+    status TEXT NOT NULL,
+    open INTEGER NOT NULL,
+    entry_ts INTEGER,
+    pnl REAL NOT NULL,
+    data TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS positions_scale_entry ON positions (scale, entry_ts);
+CREATE TABLE IF NOT EXISTS partial_exits (
+    position_id INTEGER NOT NULL,
+    seq INTEGER NOT NULL,
+    level REAL NOT NULL,
+    price REAL NOT NULL,
+    size REAL NOT NULL,
+    pnl REAL NOT NULL,
+    time TEXT NOT NULL,
+    logged INTEGER NOT NULL,
+    slippage REAL NOT NULL DEFAULT 0,
+    PRIMARY KEY (position_id, seq)
+);
+CREATE TABLE IF NOT EXISTS trade_records (
+    id INTEGER PRIMARY KEY,
+    scale TEXT NOT NULL,
+    outcome TEXT NOT NULL,
+    pnl REAL NOT NULL,
+    data TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS adjustments (
+    seq INTEGER PRIMARY KEY,
+    timestamp TEXT NOT NULL,
+    parameter TEXT NOT NULL,
+    old_value REAL NOT NULL,
+    new_value REAL NOT NULL,
+    data TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS skip_combos (
+    combo TEXT PRIMARY KEY,
+    data TEXT NOT NULL
+);
+";
+
+/// SQLite store (`trades.db`). Each save runs in one transaction, so the
+/// trader's positions, partial exits and records always agree on disk.
+pub struct SqliteStore {
+    conn: Mutex<Connection>,
+}
+
+impl SqliteStore {
+    pub fn open(path: &str) -> Result<Self> {
+        if let Some(dir) = std::path::Path::new(path).parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let conn = Connection::open(path).with_context(|| format!("opening {}", path))?;
+        conn.execute_batch(SCHEMA)?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    fn conn(&self) -> std::sync::MutexGuard<'_, Connection> {
+        self.conn.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+fn load_positions(conn: &Connection, open: bool) -> Result<Vec<Position>> {
+    let mut exits: HashMap<u64, Vec<PartialExit>> = HashMap::new();
+    let mut stmt = conn.prepare(
+        "SELECT position_id, level, price, size, pnl, time, logged, slippage
+         FROM partial_exits ORDER BY position_id, seq",
+    )?;
+    let rows = stmt.query_map([], |r| {
+        Ok((
+            r.get::<_, i64>(0)? as u64,
+            PartialExit {
+                level: r.get(1)?,
+                price: r.get(2)?,
+                size_btc: r.get(3)?,
+                pnl: r.get(4)?,
+                time: r.get(5)?,
+                logged: r.get(6)?,
+                slippage: r.get(7)?,
+            },
+        ))
+    })?;
+    for row in rows {
+        let (id, exit) = row?;
+        exits.entry(id).or_default().push(exit);
+    }
+
+    let mut stmt = conn.prepare("SELECT data FROM positions WHERE open = ?1 ORDER BY seq")?;
+    let rows = stmt.query_map([open], |r| r.get::<_, String>(0))?;
+    let mut out = Vec::new();
+    for data in rows {
+        let mut p: Position = serde_json::from_str(&data?)?;
+        p.partial_exits = exits.remove(&p.id).unwrap_or_default();
+        out.push(p);
+    }
+    Ok(out)
+}
+
+fn insert_position(tx: &Connection, seq: usize, p: &Position, open: bool) -> Result<()> {
+    // Partial exits live in their own table
+    let mut stored = p.clone();
+    let exits = std::mem::take(&mut stored.partial_exits);
+    tx.execute(
+        "INSERT OR REPLACE INTO positions (id, seq, symbol, scale, status, open, entry_ts, pnl, data)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        params![
+            p.id as i64,
+            seq as i64,
+            p.symbol,
+            p.scale,
+            serde_json::to_value(p.status)?.as_str().unwrap_or_default(),
+            open,
+            entry_time(p).map(|t| t.timestamp()),
+            p.pnl,
+            serde_json::to_string(&stored)?,
+        ],
+    )?;
+    for (i, e) in exits.iter().enumerate() {
+        tx.execute(
+            "INSERT OR REPLACE INTO partial_exits
+             (position_id, seq, level, price, size, pnl, time, logged, slippage)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![
+                p.id as i64,
+                i as i64,
+                e.level,
+                e.price,
+                e.size_btc,
+                e.pnl,
+                e.time,
+                e.logged,
+                e.slippage
+            ],
+        )?;
+    }
+    Ok(())
+}
+
+impl TradeStore for SqliteStore {
+    fn load_trader(&self) -> Result<Option<TraderState>> {
+        let conn = self.conn();
+        let account = conn
+            .query_row(
+                "SELECT balance, trade_counter, daily_pnl, daily_pnl_date FROM account WHERE id = 0",
+                [],
+                |r| {
+                    Ok((
+                        r.get::<_, f64>(0)?,
+                        r.get::<_, i64>(1)?,
+                        r.get::<_, f64>(2)?,
+                        r.get::<_, String>(3)?,
+                    ))
+                },
+            )
+            .optional()?;
+        let Some((balance, trade_counter, daily_pnl, daily_pnl_date)) = account else {
+            return Ok(None);
+        };
+
+        let mut trade_records = HashMap::new();
+        let mut stmt = conn.prepare("SELECT id, data FROM trade_records")?;
+        let rows = stmt.query_map([], |r| {
+            Ok((r.get::<_, i64>(0)? as u64, r.get::<_, String>(1)?))
+        })?;
+        for row in rows {
+            let (id, data) = row?;
+            let mut record: TradeRecord = serde_json::from_str(&data)?;
+            record.migrate();
+            trade_records.insert(id, record);
+        }
+
+        Ok(Some(TraderState {
+            balance,
+            trade_counter: trade_counter as u64,
+            daily_pnl,
+            daily_pnl_date,
+            positions: load_positions(&conn, true)?,
+            trade_history: load_positions(&conn, false)?,
+            trade_records,
+        }))
+    }
+
+    fn save_trader(&self, state: &TraderState) -> Result<()> {
+        let conn = self.conn();
+        let tx = conn.unchecked_transaction()?;
+        tx.execute(
+            "INSERT OR REPLACE INTO account (id, balance, trade_counter, daily_pnl, daily_pnl_date)
+             VALUES (0, ?1, ?2, ?3, ?4)",
+            params![
+                state.balance,
+                state.trade_counter as i64,
+                state.daily_pnl,
+                state.daily_pnl_date
+            ],
+        )?;
+        tx.execute_batch(
+            "DELETE FROM positions; DELETE FROM partial_exits; DELETE FROM trade_records;",
+        )?;
+        for (seq, p) in state.positions.iter().enumerate() {
+            insert_position(&tx, seq, p, true)?;
+        }
+        for (seq, p) in state.trade_history.iter().enumerate() {
+            insert_position(&tx, seq, p, false)?;
+        }
+        for (id, r) in &state.trade_records {
+            tx.execute(
+                "INSERT INTO trade_records (id, scale, outcome, pnl, data) VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    *id as i64,
+                    r.metadata.scale,
+                    r.outcome,
+                    r.pnl,
+                    serde_json::to_string(r)?
+                ],
+            )?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn load_refiner(&self) -> Result<Option<RefinerState>> {
+        let conn = self.conn();
+        let mut state = RefinerState::default();
+        let mut stmt = conn.prepare("SELECT data FROM adjustments ORDER BY seq")?;
+        for data in stmt.query_map([], |r| r.get::<_, String>(0))? {
+            state.adjustment_history.push(serde_json::from_str(&data?)?);
+        }
+        let mut stmt = conn.prepare("SELECT combo, data FROM skip_combos")?;
+        let rows = stmt.query_map([], |r| Ok((r.get::<_, String>(0)?, r.get::<_, String>(1)?)))?;
+        for row in rows {
+            let (combo, data) = row?;
+            state
+                .skip_combos
+                .insert(combo, serde_json::from_str(&data)?);
+        }
+        if state.adjustment_history.is_empty() && state.skip_combos.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(state))
+    }
+
+    fn save_refiner(&self, state: &RefinerState) -> Result<()> {
+        let conn = self.conn();
+        let tx = conn.unchecked_transaction()?;
+        tx.execute_batch("DELETE FROM adjustments; DELETE FROM skip_combos;")?;
+        for (seq, a) in state.adjustment_history.iter().enumerate() {
+            tx.execute(
+                "INSERT INTO adjustments (seq, timestamp, parameter, old_value, new_value, data)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![
+                    seq as i64,
+                    a.timestamp,
+                    a.parameter,
+                    a.old_value,
+                    a.new_value,
+                    serde_json::to_string(a)?
+                ],
+            )?;
+        }
+        for (combo, entry) in &state.skip_combos {
+            tx.execute(
+                "INSERT INTO skip_combos (combo, data) VALUES (?1, ?2)",
+                params![combo, serde_json::to_string(entry)?],
+            )?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn query_records(&self, query: &TradeQuery) -> Result<Vec<TradeRecord>> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare(
+            "SELECT r.data FROM trade_records r LEFT JOIN positions p ON p.id = r.id
+             WHERE (?1 IS NULL OR r.scale = ?1)
+               AND (?2 IS NULL OR p.symbol = ?2)
+               AND (?3 IS NULL OR p.entry_ts >= ?3)
+               AND (?4 = 0 OR r.outcome != '')
+             ORDER BY p.entry_ts, r.id",
+        )?;
+        let rows = stmt.query_map(
+            params![
+                query.scale,
+                query.symbol,
+                query.since.map(|t| t.timestamp()),
+                query.closed_only
+            ],
+            |r| r.get::<_, String>(0),
+        )?;
+        let mut out = Vec::new();
+        for data in rows {
+            let mut record: TradeRecord = serde_json::from_str(&data?)?;
+            record.migrate();
+            if query.has_tag(&record) {
+                out.push(record);
+            }
+        }
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::tests::sample_state;
+    use chrono::{Duration, Utc};
+
+    #[test]
+    fn sqlite_round_trips_and_filters_by_scale_and_time() {
+        let dir = std::env::temp_dir().join(format!("ict_sqlite_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let store = SqliteStore::open(dir.join("trades.db").to_str().unwrap()).unwrap();
+        assert!(store.load_trader().unwrap().is_none());
+
+        let state = sample_state();
+        store.save_trader(&state).unwrap();
+        let loaded = store.load_trader().unwrap().unwrap();
+        assert_eq!(loaded.trade_counter, state.trade_counter);
+        assert_eq!(loaded.positions.len(), 1);
+        assert_eq!(loaded.trade_history.len(), 2);
+        assert_eq!(loaded.trade_history[0].partial_exits.len(), 1);
+        assert_eq!(loaded.trade_records.len(), 3);
+
+        let recent = store
+            .query_records(&TradeQuery {
+                scale: Some("5m".to_string()),
+                since: Some(Utc::now() - Duration::days(30)),
+                ..Default::default()
+            })
+            .unwrap();
+        let ids: Vec<u64> = recent.iter().map(|r| r.position_id).collect();
+        assert_eq!(ids, vec![2, 3]);
+
+        let mut refiner = RefinerState::default();
+        refiner.skip_combos.insert(
+            "1m_asia".to_string(),
+            crate::trading::strategy_refiner::SkipEntry {
+                since: Utc::now(),
+                edge: -0.2,
+                sample_size: Some(12),
+            },
+        );
+        store.save_refiner(&refiner).unwrap();
+        let loaded = store.load_refiner().unwrap().unwrap();
+        assert_eq!(loaded.skip_combos["1m_asia"].sample_size, Some(12));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -1,18 +1,33 @@
+use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
 use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
 
-use crate::config::Config;
+use crate::config::{AlignmentRule, Config, SignalRanking};
+use crate::core::anchors::{Anchor, AnchorCache};
 use crate::core::cisd::CisdDetector;
-use crate::core::liquidity::LiquidityDetector;
-use crate::core::pd_arrays::{Pda, PdArrayDetector};
-use crate::core::sessions::SessionManager;
+use crate::core::kelly::KellyResult;
+use crate::core::liquidity::{LiquidityDetector, RangeLiquidity};
+use crate::core::opening_gaps;
+use crate::core::ote::OteZone;
+use crate::core::pd_arrays::{void_fill, CacheStats, ImbalanceParams, Pda, PdaCache};
+use crate::core::pda_registry::{PdaRegistry, PdaState};
+use crate::core::power_of_three::PowerOfThree;
+use crate::core::sessions::{last_completed_range, SessionManager};
+use crate::core::smt;
 use crate::core::stddev_projections::StdDevProjector;
 use crate::core::stop_loss::StopLossEngine;
 use crate::core::structure::{DealingRange, MarketStructure};
-use crate::models::{CandleSeries, Direction, PdaType, Timeframe, Trend, Zone};
+use crate::core::sweeps::SweepDetector;
+use crate::core::volume_profile::session_profiles;
+use crate::models::{CandleSeries, Direction, PdaType, Precision, Timeframe, Trend, Zone};
 use crate::strategies::signals::TradeSignal;
 use crate::trading::trade_record::{AlignmentInfo, TpLevelInfo};
 
+/// Timeframe opening gaps are read from when available
+const GAP_TF: Timeframe = Timeframe::H1;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AlignmentState {
     pub timeframe: Timeframe,
@@ -42,6 +57,9 @@ pub struct HftSignal {
     pub tp_label: String,
     pub tp_levels: Vec<TpLevelInfo>,
     pub alignment: Vec<AlignmentInfo>,
+    /// Limit price inside the OTE zone, when price has yet to retrace to it
+    #[serde(default)]
+    pub limit_entry: Option<f64>,
 }
 
 impl HftSignal {
@@ -58,6 +76,9 @@ impl HftSignal {
             session_weight: self.session_weight,
             reason: self.reason.clone(),
             tp_levels: Some(self.tp_levels.clone()),
+            size_multiplier: 1.0,
+            atr_percentile: None,
+            tags: Vec::new(),
         }
     }
 }
@@ -67,57 +88,189 @@ pub struct HftScale {
     pub name: String,
     pub entry_tf: Timeframe,
     pub alignment_tfs: Vec<Timeframe>,
+    pub alignment_rules: Vec<AlignmentRule>,
     pub structure_tf: Timeframe,
     pub confirm_tf: Timeframe,
     pub weight: f64,
+    /// Tick size for signal prices (from `cfg.precision(&cfg.symbol)`)
+    pub precision: Precision,
 
-    pd_detector: PdArrayDetector,
+    /// Structure- and entry-TF PDAs, re-detected only when their series change
+    structure_pdas: PdaCache,
+    entry_pdas: PdaCache,
     cisd_detector: CisdDetector,
     stop_engine: StopLossEngine,
     sd_projector: StdDevProjector,
     liquidity_detector: LiquidityDetector,
     alignment_analyzers: HashMap<Timeframe, MarketStructure>,
     structure_analyzer: MarketStructure,
+    /// Entry-TF day opens (00:00/08:30/09:30 ET)
+    anchors: AnchorCache,
 
     pub last_alignment: Vec<AlignmentState>,
+    /// Day's Power of Three phase at the last evaluation
+    pub last_amd: Option<PowerOfThree>,
+    /// Session range (e.g. "Asia low") swept at the last Judas check
+    pub last_session_sweep: Option<String>,
     last_structure_pdas: Vec<Pda>,
+    /// Fresh/mitigated/invalidated state of the structure PDAs
+    pda_registry: PdaRegistry,
+    /// Correlated symbol and its structure-TF candles, for SMT divergence
+    smt_peer: Option<(String, CandleSeries)>,
+    /// Last evaluation, keyed by `eval_key` of its inputs
+    memo: Option<(u64, Option<HftSignal>)>,
 }
 
 impl HftScale {
     pub fn new(scale_key: &str, cfg: &Config) -> Self {
         let scale_cfg = &cfg.hft_scales[scale_key];
-        let alignment_analyzers = scale_cfg
-            .alignment_tfs
+        let alignment_rules = if scale_cfg.alignment_rules.is_empty() {
+            vec![AlignmentRule::Agree(scale_cfg.alignment_tfs.clone())]
+        } else {
+            scale_cfg.alignment_rules.clone()
+        };
+
+        // Track alignment_tfs plus any extra timeframes the rules reference
+        let mut alignment_tfs = scale_cfg.alignment_tfs.clone();
+        for tf in alignment_rules.iter().flat_map(|r| r.timeframes()) {
+            if !alignment_tfs.contains(&tf) {
+                alignment_tfs.push(tf);
+            }
+        }
+        let alignment_analyzers = alignment_tfs
             .iter()
             .map(|&tf| (tf, MarketStructure::new()))
             .collect();
+        let precision = cfg.precision(&cfg.symbol);
+        let imbalances = ImbalanceParams::from_config(cfg);
 
         Self {
             scale_key: scale_key.to_string(),
             name: scale_cfg.name.clone(),
             entry_tf: scale_cfg.entry_tf,
-            alignment_tfs: scale_cfg.alignment_tfs.clone(),
+            alignment_tfs,
+            alignment_rules,
             structure_tf: scale_cfg.structure_tf,
             confirm_tf: scale_cfg.confirm_tf,
             weight: scale_cfg.weight,
-            pd_detector: PdArrayDetector::new(),
+            precision,
+            structure_pdas: PdaCache::new().with_imbalances(imbalances),
+            entry_pdas: PdaCache::new().with_imbalances(imbalances),
             cisd_detector: CisdDetector::new(),
-            stop_engine: StopLossEngine::new(),
-            sd_projector: StdDevProjector::new(),
-            liquidity_detector: LiquidityDetector::new(),
+            stop_engine: StopLossEngine::with_lookback(cfg.swing_lookback)
+                .with_precision(precision),
+            sd_projector: StdDevProjector::new().with_precision(precision),
+            liquidity_detector: LiquidityDetector::new().with_precision(precision),
             alignment_analyzers,
             structure_analyzer: MarketStructure::new(),
+            anchors: AnchorCache::new(),
             last_alignment: Vec::new(),
+            last_amd: None,
+            last_session_sweep: None,
             last_structure_pdas: Vec::new(),
+            pda_registry: PdaRegistry::new(),
+            smt_peer: None,
+            memo: None,
         }
     }
 
+    /// Hits and misses of the structure- and entry-TF PDA caches.
+    pub fn pda_cache_stats(&self) -> CacheStats {
+        let mut stats = self.structure_pdas.stats;
+        stats.merge(self.entry_pdas.stats);
+        stats
+    }
+
+    /// Every timeframe this scale reads during evaluation.
+    pub fn required_timeframes(&self) -> Vec<Timeframe> {
+        let mut tfs = vec![self.entry_tf, self.structure_tf, self.confirm_tf];
+        for &tf in &self.alignment_tfs {
+            if !tfs.contains(&tf) {
+                tfs.push(tf);
+            }
+        }
+        tfs
+    }
+
+    /// Evaluate the scale, reusing the previous result when the candle
+    /// windows, reference price, session and detector params are unchanged.
     pub fn evaluate(
         &mut self,
         data: &HashMap<Timeframe, CandleSeries>,
         reference_price: Option<f64>,
         session: &SessionManager,
         cfg: &Config,
+    ) -> Option<HftSignal> {
+        let key = self.eval_key(data, reference_price, session, cfg);
+        if let Some((cached_key, signal)) = &self.memo {
+            if *cached_key == key {
+                return signal.clone();
+            }
+        }
+        let signal = self.evaluate_uncached(data, reference_price, session, cfg);
+        self.memo = Some((key, signal.clone()));
+        signal
+    }
+
+    /// Hash of everything `evaluate_uncached` reads: per timeframe the
+    /// window length, first timestamp and full last candle (it may still be
+    /// forming), plus reference price, session and detector params.
+    fn eval_key(
+        &self,
+        data: &HashMap<Timeframe, CandleSeries>,
+        reference_price: Option<f64>,
+        session: &SessionManager,
+        cfg: &Config,
+    ) -> u64 {
+        let mut h = DefaultHasher::new();
+        let mut tfs = self.required_timeframes();
+        if !tfs.contains(&GAP_TF) {
+            tfs.push(GAP_TF);
+        }
+        for tf in tfs {
+            tf.hash(&mut h);
+            if let Some(df) = data.get(&tf) {
+                df.len().hash(&mut h);
+                df.first().map(|c| c.timestamp).hash(&mut h);
+                if let Some(c) = df.last() {
+                    c.timestamp.hash(&mut h);
+                    for v in [c.open, c.high, c.low, c.close, c.volume] {
+                        v.to_bits().hash(&mut h);
+                    }
+                }
+            }
+        }
+        if let Some((peer, df)) = &self.smt_peer {
+            peer.hash(&mut h);
+            df.len().hash(&mut h);
+            df.last().map(|c| (c.timestamp, c.high.to_bits(), c.low.to_bits())).hash(&mut h);
+        }
+        reference_price.map(f64::to_bits).hash(&mut h);
+        session.current_session.hash(&mut h);
+        session.session_weight.to_bits().hash(&mut h);
+        session.silver_bullet_multiplier().to_bits().hash(&mut h);
+        session.quarter.hash(&mut h);
+        cfg.quarter_mode.as_str().hash(&mut h);
+        cfg.quarter_off_weight.to_bits().hash(&mut h);
+        cfg.fvg_min_gap_percent.to_bits().hash(&mut h);
+        cfg.ob_lookback.hash(&mut h);
+        cfg.breaker_lookback.hash(&mut h);
+        cfg.opening_gaps_keep.hash(&mut h);
+        cfg.smt_weight.to_bits().hash(&mut h);
+        cfg.smt_required.hash(&mut h);
+        cfg.exhaust_candles.hash(&mut h);
+        cfg.sweep_displacement_atr.to_bits().hash(&mut h);
+        cfg.judas_anchor.hash(&mut h);
+        cfg.pd_anchor.hash(&mut h);
+        h.finish()
+    }
+
+    fn evaluate_uncached(
+        &mut self,
+        data: &HashMap<Timeframe, CandleSeries>,
+        reference_price: Option<f64>,
+        session: &SessionManager,
+        cfg: &Config,
     ) -> Option<HftSignal> {
         let entry_df = data.get(&self.entry_tf)?;
         let struct_df = data.get(&self.structure_tf)?;
@@ -127,6 +280,11 @@ impl HftScale {
             return None;
         }
 
+        if session.quarter_multiplier(cfg).is_none() {
+            tracing::trace!("[EVAL] {} blocked in {}", self.name, session.quarter);
+            return None;
+        }
+
         // Step 1: Alignment gate
         let aligned_direction = match self.check_alignment(data) {
             Some(d) => d,
@@ -138,10 +296,7 @@ impl HftScale {
 
         // Exhaustion filter (TTrades Article 5): skip if 3+ consecutive
         // same-direction expansion candles on entry TF (move is spent)
-        let exhaust_count: usize = std::env::var("EXHAUST_CANDLES")
-            .ok()
-            .and_then(|s| s.parse().ok())
-            .unwrap_or(0); // 0 = disabled
+        let exhaust_count = cfg.exhaust_candles; // 0 = disabled
         if exhaust_count > 0 && entry_df.len() >= exhaust_count {
             let recent = entry_df.tail(exhaust_count);
             let all_same_dir = match aligned_direction {
@@ -172,10 +327,10 @@ impl HftScale {
         }
 
         // Step 2: Structure TF PDAs + Dealing Range
-        self.structure_analyzer.analyze(struct_df);
+        self.structure_analyzer.update(struct_df);
         let dr = self.structure_analyzer.get_dealing_range(Some(struct_df));
-        let structure_pdas = self
-            .pd_detector
+        let mut structure_pdas = self
+            .structure_pdas
             .detect_all(
                 struct_df,
                 self.structure_tf,
@@ -184,17 +339,36 @@ impl HftScale {
                 cfg.breaker_lookback,
             )
             .to_vec();
+        // NWOG/NDOG from hourly candles (a week of them), else the structure TF
+        if cfg.opening_gaps_keep > 0 {
+            let (gap_tf, gap_df) = match data.get(&GAP_TF) {
+                Some(df) => (GAP_TF, df),
+                None => (self.structure_tf, struct_df),
+            };
+            let eq = (struct_df.highs_max() + struct_df.lows_min()) / 2.0;
+            structure_pdas.extend(
+                opening_gaps::detect(gap_df, cfg.opening_gaps_keep)
+                    .iter()
+                    .map(|g| g.to_pda(eq, gap_tf)),
+            );
+        }
+        self.pda_registry.update(&structure_pdas, struct_df);
         self.last_structure_pdas = structure_pdas.clone();
         let _liquidity = self.structure_analyzer.get_liquidity_levels();
 
         // Step 3: Judas swing detection
-        if !self.detect_judas_swing(entry_df, aligned_direction, reference_price, &dr) {
+        self.anchors.update(entry_df);
+        let judas_ref = self.anchor_price(cfg.judas_anchor, reference_price);
+        let pd_ref = cfg
+            .pd_anchor
+            .and_then(|a| self.anchor_price(a, reference_price));
+        if !self.detect_judas_swing(cfg, entry_df, aligned_direction, judas_ref, pd_ref, &dr) {
             tracing::debug!("[EVAL] {} passed alignment ({:?}) but blocked at Judas swing", self.name, aligned_direction);
             return None;
         }
 
         // Step 4: PDA engagement
-        let engaged_pda = match self.check_pda_engagement(entry_df, &structure_pdas, aligned_direction) {
+        let engaged_pda = match self.check_pda_engagement(entry_df, &structure_pdas, aligned_direction, pd_ref) {
             Some(p) => p,
             None => {
                 tracing::debug!("[EVAL] {} passed Judas swing but blocked at PDA engagement", self.name);
@@ -208,8 +382,7 @@ impl HftScale {
             .filter(|p| p.pda_type == PdaType::BRK)
             .collect();
 
-        let mut entry_pd_detector = PdArrayDetector::new();
-        let entry_pdas = entry_pd_detector.detect_all(
+        let entry_pdas = self.entry_pdas.detect_all(
             entry_df,
             self.entry_tf,
             cfg.fvg_min_gap_percent,
@@ -232,8 +405,23 @@ impl HftScale {
         let cisd_confirmed = !cisds.is_empty();
         let base_confidence = if cisd_confirmed { 0.8 } else { 0.4 };
 
-        // Step 6: Build signal
-        Some(self.build_signal(
+        // Step 6: SMT divergence against the correlated symbol
+        let smt_note = match &self.smt_peer {
+            Some((peer, peer_df)) => {
+                let note = smt::detect(struct_df, peer_df)
+                    .filter(|d| Some(d.direction) == aligned_direction.to_direction())
+                    .map(|d| d.describe(peer));
+                if note.is_none() && cfg.smt_required {
+                    tracing::debug!("[EVAL] {} passed CISD but blocked at SMT", self.name);
+                    return None;
+                }
+                note
+            }
+            None => None,
+        };
+
+        // Step 7: Build signal
+        let mut signal = self.build_signal(
             entry_df,
             aligned_direction,
             engaged_pda,
@@ -242,7 +430,12 @@ impl HftScale {
             base_confidence,
             session,
             cfg,
-        ))
+        );
+        if let Some(note) = smt_note {
+            signal.confidence = round3((signal.confidence + cfg.smt_weight).min(1.0));
+            signal.reason.push_str(&format!(" | SMT: {}", note));
+        }
+        Some(signal)
     }
 
     pub fn check_alignment(
@@ -250,7 +443,7 @@ impl HftScale {
         data: &HashMap<Timeframe, CandleSeries>,
     ) -> Option<Trend> {
         self.last_alignment.clear();
-        let mut directions = Vec::new();
+        let mut trends = HashMap::new();
 
         for &tf in &self.alignment_tfs {
             let df = data.get(&tf)?;
@@ -259,7 +452,7 @@ impl HftScale {
             }
 
             let analyzer = self.alignment_analyzers.get_mut(&tf)?;
-            let trend = analyzer.analyze(df);
+            let trend = analyzer.update(df);
             let dr = analyzer.get_dealing_range(Some(df));
 
             self.last_alignment.push(AlignmentState {
@@ -270,65 +463,79 @@ impl HftScale {
                 bos_count: analyzer.bos_events.len(),
             });
 
-            if trend == Trend::Neutral {
-                return None;
-            }
-
-            directions.push(trend);
+            trends.insert(tf, trend);
         }
 
-        // All must agree
-        if directions.windows(2).all(|w| w[0] == w[1]) {
-            Some(directions[0])
-        } else {
-            None
+        evaluate_alignment(&self.alignment_rules, &trends)
+    }
+
+    /// Today's `anchor` price; the midnight open prefers the exchange's
+    /// `reference_price` over the entry-TF candles.
+    fn anchor_price(&self, anchor: Anchor, reference_price: Option<f64>) -> Option<f64> {
+        let cached = self.anchors.current().and_then(|d| d.get(anchor));
+        match anchor {
+            Anchor::Midnight => reference_price.or(cached),
+            _ => cached,
         }
     }
 
+    /// `ref_price` is the Power of Three open, `pd_price` the
+    /// premium/discount split (both default to the DR equilibrium).
     fn detect_judas_swing(
-        &self,
+        &mut self,
+        cfg: &Config,
         entry_df: &CandleSeries,
         direction: Trend,
         ref_price: Option<f64>,
+        pd_price: Option<f64>,
         dr: &DealingRange,
     ) -> bool {
-        if entry_df.is_empty() {
-            return false;
-        }
+        let current = match entry_df.last() {
+            Some(c) => c.close,
+            None => return false,
+        };
 
         let pivot = ref_price.unwrap_or(dr.equilibrium);
         if pivot == 0.0 {
             return false;
         }
 
-        // Use wider lookback window for better Judas swing detection
-        let recent = entry_df.tail(60);
-        let current = match recent.last() {
-            Some(c) => c.close,
-            None => return false,
-        };
+        // Classic Judas: the day's manipulation leg ran against `direction`
+        // and price is back through the open
+        let amd = PowerOfThree::today(entry_df, pivot);
+        let distributing = amd.distributing(direction);
+        self.last_amd = Some(amd);
+        self.last_session_sweep = None;
+        if distributing {
+            return true;
+        }
 
-        match direction {
-            Trend::Bullish => {
-                // Classic Judas: swept below pivot and reclaimed
-                let went_below = recent.any_low_below(pivot);
-                let came_back = current > pivot;
-                if went_below && came_back {
-                    return true;
-                }
-                // Fallback: price is in discount zone of dealing range (below equilibrium)
-                // and showing reversal — this is a valid ICT setup
-                current < dr.equilibrium && current > dr.low
-            }
-            Trend::Bearish => {
-                let went_above = recent.any_high_above(pivot);
-                let came_back = current < pivot;
-                if went_above && came_back {
-                    return true;
-                }
-                // Fallback: price is in premium zone (above equilibrium) and showing reversal
-                current > dr.equilibrium && current < dr.high
+        // Displacement sweep of the last Asia (then London) range against
+        // `direction`, with price still back inside it
+        let sweeps = SweepDetector::new(cfg.sweep_displacement_atr);
+        for (session, label) in [("asian", "Asia"), ("london", "London")] {
+            let Some(range) = last_completed_range(cfg, session, entry_df) else {
+                continue;
+            };
+            if let Some(sweep) = sweeps.session_sweep(entry_df, &range, direction) {
+                let side = match direction {
+                    Trend::Bullish => "low",
+                    _ => "high",
+                };
+                self.last_session_sweep = Some(format!(
+                    "{} {} ({:.1} ATR)",
+                    label, side, sweep.displacement
+                ));
+                return true;
             }
+        }
+
+        // Fallback: price is in the dealing range's discount (premium) zone
+        // and showing reversal — this is a valid ICT setup
+        let split = pd_price.unwrap_or(dr.equilibrium);
+        match direction {
+            Trend::Bullish => current < split && current > dr.low,
+            Trend::Bearish => current > split && current < dr.high,
             Trend::Neutral => false,
         }
     }
@@ -338,6 +545,7 @@ impl HftScale {
         entry_df: &CandleSeries,
         structure_pdas: &[Pda],
         direction: Trend,
+        pd_price: Option<f64>,
     ) -> Option<Pda> {
         if structure_pdas.is_empty() || entry_df.is_empty() {
             return None;
@@ -347,15 +555,21 @@ impl HftScale {
         let recent_low = recent.lows_min();
         let recent_high = recent.highs_max();
 
-        // First try strict zone matching (discount for bullish, premium for bearish)
+        // First try strict zone matching (discount for bullish, premium for
+        // bearish), against the PD anchor when one is set
+        let in_zone = |p: &Pda, zone: Zone| match pd_price {
+            Some(split) if zone == Zone::Discount => p.midpoint < split,
+            Some(split) => p.midpoint > split,
+            None => p.zone == zone,
+        };
         let strict_candidates: Vec<&Pda> = match direction {
             Trend::Bullish => structure_pdas
                 .iter()
-                .filter(|p| p.direction == Trend::Bullish && p.zone == Zone::Discount)
+                .filter(|p| p.direction == Trend::Bullish && in_zone(p, Zone::Discount))
                 .collect(),
             Trend::Bearish => structure_pdas
                 .iter()
-                .filter(|p| p.direction == Trend::Bearish && p.zone == Zone::Premium)
+                .filter(|p| p.direction == Trend::Bearish && in_zone(p, Zone::Premium))
                 .collect(),
             Trend::Neutral => structure_pdas
                 .iter()
@@ -382,10 +596,18 @@ impl HftScale {
             strict_candidates
         };
 
-        let mut sorted = candidates;
-        sorted.sort_by(|a, b| b.strength.partial_cmp(&a.strength).unwrap());
+        // Fresh arrays first, then mitigated; invalidated ones are spent
+        let mut sorted: Vec<(PdaState, &Pda)> = candidates
+            .into_iter()
+            .map(|p| (self.pda_registry.state(p), p))
+            .filter(|(state, _)| *state != PdaState::Invalidated)
+            .collect();
+        sorted.sort_by(|(sa, a), (sb, b)| {
+            sa.cmp(sb)
+                .then_with(|| b.strength.partial_cmp(&a.strength).unwrap())
+        });
 
-        for pda in sorted {
+        for (_, pda) in sorted {
             if recent_low <= pda.high && recent_high >= pda.low {
                 return Some(pda.clone());
             }
@@ -394,6 +616,7 @@ impl HftScale {
         None
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn build_signal(
         &mut self,
         entry_df: &CandleSeries,
@@ -403,7 +626,7 @@ impl HftScale {
         cisd: bool,
         confidence: f64,
         session: &SessionManager,
-        _cfg: &Config,
+        cfg: &Config,
     ) -> HftSignal {
         let current = entry_df.last().unwrap().close;
         let trade_dir = match direction {
@@ -470,23 +693,44 @@ impl HftScale {
                 });
             }
         }
-        if let Some(erl) = self.liquidity_detector.nearest_erl_target(&pools, current, trade_dir) {
-            let erl_dist = (erl.price - current).abs();
+
+        // IRL→ERL: entries at internal liquidity draw to external pools and
+        // entries at external liquidity draw back to internal FVGs/OBs
+        let entry_liquidity = RangeLiquidity::classify(pda.midpoint, dr);
+        let target = self.liquidity_detector.framework_target(
+            entry_liquidity,
+            &pools,
+            &self.last_structure_pdas,
+            dr,
+            current,
+            trade_dir,
+        );
+        if let Some(t) = &target {
+            let target_dist = (t.price - current).abs();
             let sd_dist = (take_profit - current).abs();
 
-            // Use ERL if it's a strong pool (2+ touches) and provides better R:R
-            if erl.touches >= 2 {
-                // If ERL is farther than SD TP, use it (more upside)
-                if erl_dist > sd_dist {
-                    take_profit = erl.price;
-                    tp_label = format!("ERL {}x touches ({:.0})", erl.touches, erl.price);
+            match t.class {
+                // Use ERL if it's a strong pool (2+ touches) and provides better R:R
+                RangeLiquidity::ERL if t.touches >= 2 => {
+                    // If ERL is farther than SD TP, use it (more upside)
+                    if target_dist > sd_dist {
+                        take_profit = t.price;
+                        tp_label = t.label.clone();
+                    }
+                    // If ERL is closer but still meaningful (>60% of SD dist), prefer it
+                    // as a more reliable target (liquidity actually rests there)
+                    else if target_dist > sd_dist * 0.6 && t.strength > 0.5 {
+                        take_profit = t.price;
+                        tp_label = format!("ERL {}x reliable ({:.0})", t.touches, t.price);
+                    }
                 }
-                // If ERL is closer but still meaningful (>60% of SD dist), prefer it
-                // as a more reliable target (liquidity actually rests there)
-                else if erl_dist > sd_dist * 0.6 && erl.strength > 0.5 {
-                    take_profit = erl.price;
-                    tp_label = format!("ERL {}x reliable ({:.0})", erl.touches, erl.price);
+                // Internal arrays are nearer draws: take one unless it gives
+                // up most of the projected move
+                RangeLiquidity::IRL if target_dist > sd_dist * 0.6 => {
+                    take_profit = t.price;
+                    tp_label = t.label.clone();
                 }
+                _ => {}
             }
         }
 
@@ -498,25 +742,50 @@ impl HftScale {
                 price: l.price,
                 pda_confluence: l.has_pda_confluence,
                 level: Some(l.level),
+                volume_confluence: false,
             })
             .collect();
 
         // If ERL target was used, replace TP4 (-4.5 SD) with the ERL price
         // so partial exits actually reach the liquidity pool
-        if let Some(erl) = self.liquidity_detector.nearest_erl_target(&pools, current, trade_dir) {
+        if let Some(erl) = target.filter(|t| t.class == RangeLiquidity::ERL) {
             if erl.touches >= 2 {
                 let erl_dist = (erl.price - current).abs();
                 // Replace the last TP level if ERL is farther
                 if let Some(tp4) = tp_levels.iter_mut().find(|l| l.level == Some(-4.5)) {
                     let tp4_dist = (tp4.price - current).abs();
                     if erl_dist > tp4_dist {
-                        tp4.price = round2(erl.price);
+                        tp4.price = self.precision.round_price(erl.price);
                         tp4.label = format!("ERL {}x ({:.0})", erl.touches, erl.price);
                     }
                 }
             }
         }
 
+        // Targets left inside an unfilled void extend to where it is filled
+        if let Some((fill, kind)) =
+            void_fill(&self.last_structure_pdas, entry_df, take_profit, trade_dir)
+        {
+            take_profit = fill;
+            tp_label = format!("{} -> {} fill", tp_label, kind);
+        }
+        for lvl in &mut tp_levels {
+            if let Some((fill, kind)) =
+                void_fill(&self.last_structure_pdas, entry_df, lvl.price, trade_dir)
+            {
+                lvl.price = self.precision.round_price(fill);
+                lvl.label = format!("{} -> {} fill", lvl.label, kind);
+            }
+        }
+
+        // Volume confluence against the previous and current session profiles
+        let profiles = session_profiles(entry_df, cfg, cfg.volume_profile_bins);
+        let profiles = &profiles[profiles.len().saturating_sub(2)..];
+        for lvl in &mut tp_levels {
+            lvl.volume_confluence = profiles.iter().any(|p| p.level_at(lvl.price));
+        }
+        let pda_at_volume = profiles.iter().any(|p| p.high_volume_in(pda.low, pda.high));
+
         // Protected swing SL
         self.stop_engine
             .find_protected_swings(entry_df, Some(&self.last_structure_pdas));
@@ -533,12 +802,58 @@ impl HftScale {
 
         // Silver Bullet boost (10-11 AM ET)
         adjusted *= session.silver_bullet_multiplier();
+        // Q1/Q4 discount under QuarterMode::Weight
+        adjusted *= session.quarter_multiplier(cfg).unwrap_or(1.0);
 
         let recent = entry_df.tail(30);
         let range_pct = (recent.highs_max() - recent.lows_min()) / current;
         if range_pct > 0.03 && !cisd {
             adjusted *= 0.5;
         }
+        if pda_at_volume {
+            adjusted += cfg.volume_confluence_bonus;
+        }
+
+        // A volume level between stop and entry backs the stop
+        let (sl_lo, sl_hi) = (sl_level.price.min(current), sl_level.price.max(current));
+        let sl_at_volume = profiles
+            .iter()
+            .flat_map(|p| p.levels())
+            .any(|l| l > sl_lo && l < sl_hi);
+        let volume_note = match (pda_at_volume, sl_at_volume) {
+            (true, true) => " | VOL: PDA at HVN, SL behind volume",
+            (true, false) => " | VOL: PDA at HVN",
+            (false, true) => " | VOL: SL behind volume",
+            (false, false) => "",
+        };
+
+        // OTE: the 62-79% retracement of the day's manipulation leg
+        let ote = self
+            .last_amd
+            .as_ref()
+            .and_then(|amd| OteZone::from_manipulation(entry_df, amd))
+            .filter(|z| z.direction == trade_dir);
+        let limit_entry = ote
+            .as_ref()
+            .and_then(|z| z.limit_for(current))
+            .map(|l| self.precision.round_price(l))
+            .filter(|&l| match trade_dir {
+                Direction::Long => sl_level.price < l && l < take_profit,
+                Direction::Short => take_profit < l && l < sl_level.price,
+            });
+        let ote_note = match (&ote, limit_entry) {
+            (Some(z), Some(l)) => format!(
+                " | OTE: {:.*}-{:.*}, limit @ {:.*}",
+                self.precision.price_decimals(),
+                z.low,
+                self.precision.price_decimals(),
+                z.high,
+                self.precision.price_decimals(),
+                l
+            ),
+            (Some(z), None) if z.contains(current) => " | OTE: in zone".to_string(),
+            _ => String::new(),
+        };
 
         let alignment_info: Vec<AlignmentInfo> = self
             .last_alignment
@@ -554,28 +869,36 @@ impl HftScale {
             self.alignment_tfs.iter().map(|tf| tf.to_string()).collect();
 
         let reason = format!(
-            "[{}] {} | Aligned: {} -> {} | PDA: {}({}) @ {:.2} | CISD: {} | SL: {} ({:.2}%) | TP: {} | SD: {:.2}",
+            "[{}] {} | Aligned: {} -> {} | PDA: {}({}) @ {:.*} | CISD: {} | SL: {} ({:.2}%) | TP: {} | SD: {:.*}",
             self.name,
             trade_dir.to_string().to_uppercase(),
             alignment_tfs_str.join("+"),
             direction,
             pda.pda_type,
             pda.direction,
+            self.precision.price_decimals(),
             pda.midpoint,
             if cisd { "YES" } else { "NO" },
             sl_level.mode,
             sl_level.risk_percent,
             tp_label,
+            self.precision.price_decimals(),
             sd_proj.range_size,
-        );
+        ) + volume_note
+            + &ote_note
+            + &self
+                .last_session_sweep
+                .as_ref()
+                .map(|s| format!(" | {} swept", s))
+                .unwrap_or_default();
 
         HftSignal {
             scale: self.scale_key.clone(),
             scale_name: self.name.clone(),
             direction: trade_dir,
-            entry_price: round2(current),
-            stop_loss: round2(sl_level.price),
-            take_profit: round2(take_profit),
+            entry_price: self.precision.round_price(current),
+            stop_loss: self.precision.round_price(sl_level.price),
+            take_profit: self.precision.round_price(take_profit),
             pda_engaged: pda,
             cisd_confirmed: cisd,
             confidence: round3(adjusted.min(1.0)),
@@ -588,6 +911,7 @@ impl HftScale {
             tp_label,
             tp_levels,
             alignment: alignment_info,
+            limit_entry,
         }
     }
 }
@@ -606,20 +930,74 @@ impl FractalEngine {
         Self { scales }
     }
 
+    /// PDA cache hits and misses across all scales.
+    pub fn pda_cache_stats(&self) -> CacheStats {
+        let mut stats = CacheStats::default();
+        for scale in self.scales.values() {
+            stats.merge(scale.pda_cache_stats());
+        }
+        stats
+    }
+
+    /// Correlated symbol's candles for SMT divergence; `None` turns it off.
+    pub fn set_smt_peer(&mut self, peer: Option<(&str, &HashMap<Timeframe, CandleSeries>)>) {
+        for scale in self.scales.values_mut() {
+            scale.smt_peer = peer.and_then(|(symbol, data)| {
+                let df = data.get(&scale.structure_tf)?;
+                Some((symbol.to_string(), df.clone()))
+            });
+        }
+    }
+
+    /// Signals that pass min-confidence, best first per `cfg.signal_ranking`.
+    /// `scale_stats` are the per-scale Kelly results used for EV ranking.
     pub fn evaluate_all(
         &mut self,
         data: &HashMap<Timeframe, CandleSeries>,
         reference_price: Option<f64>,
         session: &SessionManager,
         cfg: &Config,
+        scale_stats: &HashMap<String, KellyResult>,
     ) -> Vec<HftSignal> {
-        let mut raw_signals: Vec<HftSignal> = Vec::new();
+        let mut raw_signals = self.evaluate_with_confluence(data, reference_price, session, cfg);
 
-        for (_key, scale) in &mut self.scales {
-            if let Some(signal) = scale.evaluate(data, reference_price, session, cfg) {
-                raw_signals.push(signal);
-            }
-        }
+        // Filter by min confidence
+        raw_signals.retain(|s| s.confidence >= cfg.min_confidence(&s.scale, &s.session));
+
+        rank_signals(&mut raw_signals, cfg.signal_ranking, scale_stats);
+        raw_signals
+    }
+
+    /// Signal for one scale with cross-scale confluence applied, before the
+    /// min-confidence filter. Each scale is evaluated once (memoized).
+    pub fn evaluate_scale(
+        &mut self,
+        scale_key: &str,
+        data: &HashMap<Timeframe, CandleSeries>,
+        reference_price: Option<f64>,
+        session: &SessionManager,
+        cfg: &Config,
+    ) -> Option<HftSignal> {
+        self.evaluate_with_confluence(data, reference_price, session, cfg)
+            .into_iter()
+            .find(|s| s.scale == scale_key)
+    }
+
+    fn evaluate_with_confluence(
+        &mut self,
+        data: &HashMap<Timeframe, CandleSeries>,
+        reference_price: Option<f64>,
+        session: &SessionManager,
+        cfg: &Config,
+    ) -> Vec<HftSignal> {
+        // Scales own their detectors, so they evaluate in parallel; sorting
+        // by key keeps the output order stable across runs
+        let mut scales: Vec<(&String, &mut HftScale)> = self.scales.iter_mut().collect();
+        scales.sort_by(|a, b| a.0.cmp(b.0));
+        let mut raw_signals: Vec<HftSignal> = scales
+            .into_par_iter()
+            .filter_map(|(_, scale)| scale.evaluate(data, reference_price, session, cfg))
+            .collect();
 
         // Cross-scale confluence
         if raw_signals.len() > 1 {
@@ -638,15 +1016,6 @@ impl FractalEngine {
                 }
             }
         }
-
-        // Filter by min confidence
-        raw_signals.retain(|s| {
-            cfg.hft_scales
-                .get(&s.scale)
-                .map_or(false, |sc| s.confidence >= sc.min_confidence)
-        });
-
-        raw_signals.sort_by(|a, b| b.confidence.partial_cmp(&a.confidence).unwrap());
         raw_signals
     }
 
@@ -701,10 +1070,236 @@ pub struct AlignmentDetail {
     pub trend: String,
 }
 
-fn round2(x: f64) -> f64 {
-    (x * 100.0).round() / 100.0
+/// Evaluate alignment rules against per-timeframe trends.
+/// `Agree` rules set the direction; `NotOpposing` rules only veto it.
+pub fn evaluate_alignment(
+    rules: &[AlignmentRule],
+    trends: &HashMap<Timeframe, Trend>,
+) -> Option<Trend> {
+    let mut direction: Option<Trend> = None;
+
+    for rule in rules {
+        if let AlignmentRule::Agree(tfs) = rule {
+            for tf in tfs {
+                let trend = *trends.get(tf)?;
+                if trend == Trend::Neutral || direction.is_some_and(|d| d != trend) {
+                    return None;
+                }
+                direction = Some(trend);
+            }
+        }
+    }
+
+    let direction = direction?;
+    for rule in rules {
+        if let AlignmentRule::NotOpposing(tf) = rule {
+            let trend = *trends.get(tf)?;
+            if trend != Trend::Neutral && trend != direction {
+                return None;
+            }
+        }
+    }
+
+    Some(direction)
+}
+
+/// Sort best first.
+pub fn rank_signals(
+    signals: &mut [HftSignal],
+    ranking: SignalRanking,
+    scale_stats: &HashMap<String, KellyResult>,
+) {
+    match ranking {
+        SignalRanking::Confidence => signals.sort_by(|a, b| b.confidence.total_cmp(&a.confidence)),
+        SignalRanking::ExpectedValue => signals.sort_by(|a, b| {
+            expected_value(b, scale_stats.get(&b.scale))
+                .total_cmp(&expected_value(a, scale_stats.get(&a.scale)))
+        }),
+    }
+}
+
+/// Expected value in R: confidence x payoff - (1 - confidence). Payoff is
+/// the scale's historical Kelly payoff ratio, or the signal's own
+/// reward/risk until the scale has enough trades.
+pub fn expected_value(signal: &HftSignal, stats: Option<&KellyResult>) -> f64 {
+    let payoff = match stats {
+        Some(k) if !k.using_default => k.payoff_ratio,
+        _ => {
+            let risk = (signal.entry_price - signal.stop_loss).abs();
+            if risk > 0.0 {
+                (signal.take_profit - signal.entry_price).abs() / risk
+            } else {
+                0.0
+            }
+        }
+    };
+    signal.confidence * payoff - (1.0 - signal.confidence)
 }
 
 fn round3(x: f64) -> f64 {
     (x * 1000.0).round() / 1000.0
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trends(pairs: &[(Timeframe, Trend)]) -> HashMap<Timeframe, Trend> {
+        pairs.iter().cloned().collect()
+    }
+
+    #[test]
+    fn all_agree_rule() {
+        let rules = vec![AlignmentRule::Agree(vec![Timeframe::H1, Timeframe::H4])];
+        let t = trends(&[(Timeframe::H1, Trend::Bullish), (Timeframe::H4, Trend::Bullish)]);
+        assert_eq!(evaluate_alignment(&rules, &t), Some(Trend::Bullish));
+
+        let t = trends(&[(Timeframe::H1, Trend::Bullish), (Timeframe::H4, Trend::Bearish)]);
+        assert_eq!(evaluate_alignment(&rules, &t), None);
+
+        let t = trends(&[(Timeframe::H1, Trend::Neutral), (Timeframe::H4, Trend::Neutral)]);
+        assert_eq!(evaluate_alignment(&rules, &t), None);
+    }
+
+    #[test]
+    fn not_opposing_allows_neutral_but_vetoes_opposite() {
+        let rules = vec![
+            AlignmentRule::Agree(vec![Timeframe::H4, Timeframe::H1]),
+            AlignmentRule::NotOpposing(Timeframe::M15),
+        ];
+        let mut t = trends(&[
+            (Timeframe::H4, Trend::Bearish),
+            (Timeframe::H1, Trend::Bearish),
+            (Timeframe::M15, Trend::Neutral),
+        ]);
+        assert_eq!(evaluate_alignment(&rules, &t), Some(Trend::Bearish));
+
+        t.insert(Timeframe::M15, Trend::Bearish);
+        assert_eq!(evaluate_alignment(&rules, &t), Some(Trend::Bearish));
+
+        t.insert(Timeframe::M15, Trend::Bullish);
+        assert_eq!(evaluate_alignment(&rules, &t), None);
+    }
+
+    #[test]
+    fn missing_timeframe_or_no_agree_rule_blocks() {
+        let rules = vec![AlignmentRule::NotOpposing(Timeframe::M15)];
+        let t = trends(&[(Timeframe::M15, Trend::Bullish)]);
+        assert_eq!(evaluate_alignment(&rules, &t), None);
+
+        let rules = vec![AlignmentRule::Agree(vec![Timeframe::H1, Timeframe::D1])];
+        let t = trends(&[(Timeframe::H1, Trend::Bullish)]);
+        assert_eq!(evaluate_alignment(&rules, &t), None);
+    }
+
+    #[test]
+    fn parse_rule_list() {
+        let rules = AlignmentRule::parse_list("agree:4h,1h; not_opposing:15m").unwrap();
+        assert_eq!(
+            rules,
+            vec![
+                AlignmentRule::Agree(vec![Timeframe::H4, Timeframe::H1]),
+                AlignmentRule::NotOpposing(Timeframe::M15),
+            ]
+        );
+        assert_eq!(AlignmentRule::parse_list("").unwrap(), Vec::new());
+        assert!(AlignmentRule::parse_list("agree:7h").is_none());
+        assert!(AlignmentRule::parse_list("sometimes:1h").is_none());
+    }
+
+    #[test]
+    fn scale_tracks_rule_timeframes() {
+        let mut cfg = crate::test_helpers::default_test_config();
+        cfg.hft_scales.get_mut("5m").unwrap().alignment_rules = vec![
+            AlignmentRule::Agree(vec![Timeframe::H4, Timeframe::H1]),
+            AlignmentRule::NotOpposing(Timeframe::M5),
+        ];
+        let scale = HftScale::new("5m", &cfg);
+        assert!(scale.alignment_tfs.contains(&Timeframe::M5));
+        assert!(scale.alignment_analyzers.contains_key(&Timeframe::M5));
+    }
+
+    #[test]
+    fn eval_key_tracks_forming_candle_and_params() {
+        use crate::test_helpers::{default_test_config, make_bullish_trend};
+
+        let mut cfg = default_test_config();
+        let session = SessionManager::new(&cfg);
+        let scale = HftScale::new("5m", &cfg);
+        let data: HashMap<Timeframe, CandleSeries> = scale
+            .required_timeframes()
+            .into_iter()
+            .map(|tf| (tf, make_bullish_trend(30, 100.0)))
+            .collect();
+
+        let key = scale.eval_key(&data, Some(100.0), &session, &cfg);
+        assert_eq!(key, scale.eval_key(&data, Some(100.0), &session, &cfg));
+
+        // Same timestamp, new close on the still-forming candle
+        let mut moved = data.clone();
+        let series = moved.get_mut(&scale.entry_tf).unwrap();
+        series.last_mut().unwrap().close += 1.0;
+        assert_ne!(key, scale.eval_key(&moved, Some(100.0), &session, &cfg));
+
+        assert_ne!(key, scale.eval_key(&data, Some(101.0), &session, &cfg));
+        cfg.ob_lookback += 1;
+        assert_ne!(key, scale.eval_key(&data, Some(100.0), &session, &cfg));
+    }
+
+    #[test]
+    fn ev_ranking_prefers_better_payoff() {
+        let signal = |scale: &str, confidence: f64| HftSignal {
+            scale: scale.to_string(),
+            scale_name: scale.to_string(),
+            direction: Direction::Long,
+            entry_price: 100.0,
+            stop_loss: 99.0,
+            take_profit: 101.5,
+            pda_engaged: Pda {
+                pda_type: PdaType::FVG,
+                direction: Trend::Bullish,
+                zone: Zone::Discount,
+                high: 99.8,
+                low: 99.5,
+                midpoint: 99.65,
+                timestamp: chrono::Utc::now(),
+                timeframe: Timeframe::M5,
+                strength: 0.5,
+            },
+            cisd_confirmed: false,
+            confidence,
+            session: "london".to_string(),
+            session_weight: 1.0,
+            reason: String::new(),
+            cross_scale_confluence: 1,
+            stop_mode: String::new(),
+            stop_reason: String::new(),
+            tp_label: String::new(),
+            tp_levels: Vec::new(),
+            alignment: Vec::new(),
+            limit_entry: None,
+        };
+        let kelly = |payoff_ratio: f64| KellyResult {
+            full_kelly: 0.0,
+            applied_fraction: 0.02,
+            win_rate: 0.5,
+            loss_rate: 0.5,
+            payoff_ratio,
+            sample_size: 50,
+            using_default: false,
+            edge: 0.0,
+        };
+        let stats: HashMap<String, KellyResult> =
+            [("1m".to_string(), kelly(0.8)), ("15m".to_string(), kelly(2.0))].into();
+
+        let mut signals = vec![signal("1m", 0.71), signal("15m", 0.70)];
+        rank_signals(&mut signals, SignalRanking::Confidence, &stats);
+        assert_eq!(signals[0].scale, "1m");
+        rank_signals(&mut signals, SignalRanking::ExpectedValue, &stats);
+        assert_eq!(signals[0].scale, "15m");
+        assert!((expected_value(&signals[0], stats.get("15m")) - 1.1).abs() < 1e-9);
+
+        // No history: the signal's own 1.5R target stands in for payoff
+        assert!((expected_value(&signals[1], None) - (0.71 * 1.5 - 0.29)).abs() < 1e-9);
+    }
+}
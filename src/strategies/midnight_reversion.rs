@@ -0,0 +1,296 @@
+use chrono::{DateTime, TimeZone, Utc};
+use chrono_tz::US::Eastern;
+use std::collections::HashMap;
+
+use crate::config::Config;
+use crate::core::pd_arrays::{PdArrayDetector, Pda};
+use crate::core::sessions::SessionManager;
+use crate::core::structure::MarketStructure;
+use crate::models::{CandleSeries, Direction, Timeframe};
+use crate::strategies::fractal_engine::HftSignal;
+use crate::strategies::strategy::Strategy;
+use crate::trading::trade_record::{AlignmentInfo, TpLevelInfo};
+
+/// Candles since midnight needed before the day's range means anything.
+const MIN_CANDLES_SINCE_MIDNIGHT: usize = 3;
+/// Entry-TF candles back that may have tapped the PDA.
+const PDA_TAP_LOOKBACK: usize = 3;
+/// Stop sits this fraction of the extreme's distance from the open beyond it.
+const STOP_BUFFER: f64 = 0.1;
+
+/// ICT daily-range model around the midnight ET open.
+///
+/// With a bullish structure-TF bias, the move below the midnight open is
+/// treated as the day's discount leg: once price taps a bullish PDA below
+/// the open and displaces back up, go long targeting the open and then the
+/// day's high. Bearish bias mirrors this in premium above the open.
+pub struct MidnightReversion {
+    bias_analyzers: HashMap<String, MarketStructure>,
+    pd_detector: PdArrayDetector,
+    /// Minimum distance from the open, as a fraction of price
+    min_discount: f64,
+    min_rr: f64,
+}
+
+impl MidnightReversion {
+    pub fn new(cfg: &Config) -> Self {
+        Self {
+            bias_analyzers: HashMap::new(),
+            pd_detector: PdArrayDetector::new(),
+            min_discount: cfg.midnight_min_discount,
+            min_rr: cfg.midnight_min_rr,
+        }
+    }
+}
+
+/// 00:00 ET of the (ET) day containing `ts`.
+fn midnight_et(ts: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    let date = ts.with_timezone(&Eastern).date_naive();
+    Eastern
+        .from_local_datetime(&date.and_hms_opt(0, 0, 0)?)
+        .earliest()
+        .map(|t| t.with_timezone(&Utc))
+}
+
+impl Strategy for MidnightReversion {
+    fn name(&self) -> &'static str {
+        "midnight_reversion"
+    }
+
+    fn evaluate_scale(
+        &mut self,
+        scale_key: &str,
+        data: &HashMap<Timeframe, CandleSeries>,
+        midnight_open: Option<f64>,
+        session: &SessionManager,
+        cfg: &Config,
+    ) -> Option<HftSignal> {
+        let scale_cfg = cfg.hft_scales.get(scale_key)?;
+        let open = midnight_open.filter(|p| *p > 0.0)?;
+        let entry_df = data.get(&scale_cfg.entry_tf)?;
+        let struct_df = data.get(&scale_cfg.structure_tf)?;
+        let last = entry_df.last()?;
+        let today = entry_df.since(midnight_et(last.timestamp)?);
+        if today.len() < MIN_CANDLES_SINCE_MIDNIGHT || entry_df.len() < 2 {
+            return None;
+        }
+        let prev = entry_df.get(entry_df.len() - 2)?;
+        let current = last.close;
+
+        // Step 1: daily bias from structure-TF market structure
+        let bias = self
+            .bias_analyzers
+            .entry(scale_key.to_string())
+            .or_default()
+            .analyze(struct_df);
+        let direction = bias.to_direction()?;
+        let long = direction == Direction::Long;
+
+        // Step 2: price on the discount (long) / premium (short) side of the open
+        let offset = (current - open) / open;
+        let in_zone = if long {
+            offset <= -self.min_discount
+        } else {
+            offset >= self.min_discount
+        };
+        if !in_zone {
+            return None;
+        }
+
+        // Step 3: displacement back toward the open
+        let displaced = if long {
+            last.is_bullish() && last.close > prev.high
+        } else {
+            last.is_bearish() && last.close < prev.low
+        };
+        if !displaced {
+            return None;
+        }
+
+        // Step 4: recently tapped PDA on the same side of the open
+        let recent = entry_df.tail(PDA_TAP_LOOKBACK);
+        let pda: Pda = self
+            .pd_detector
+            .detect_all(
+                entry_df,
+                scale_cfg.entry_tf,
+                cfg.fvg_min_gap_percent,
+                cfg.ob_lookback,
+                cfg.breaker_lookback,
+            )
+            .iter()
+            .filter(|p| p.direction == bias)
+            .filter(|p| {
+                if long {
+                    p.high < open && recent.any_low_below(p.high)
+                } else {
+                    p.low > open && recent.any_high_above(p.low)
+                }
+            })
+            .max_by_key(|p| p.timestamp)?
+            .clone();
+
+        // Step 5: stop beyond the day's extreme, targets at the open then the
+        // opposite extreme (or a symmetric projection if the open is the high)
+        let (day_high, day_low) = (today.highs_max(), today.lows_min());
+        let (stop_loss, far_target, far_label) = if long {
+            let stop = day_low - (open - day_low) * STOP_BUFFER;
+            if day_high > open {
+                (stop, day_high, "Day high")
+            } else {
+                (stop, open + (open - day_low), "Range projection")
+            }
+        } else {
+            let stop = day_high + (day_high - open) * STOP_BUFFER;
+            if day_low < open {
+                (stop, day_low, "Day low")
+            } else {
+                (stop, open - (day_high - open), "Range projection")
+            }
+        };
+
+        let risk = (current - stop_loss).abs();
+        let reward = (far_target - current).abs();
+        if risk <= 0.0 || reward / risk < self.min_rr {
+            return None;
+        }
+
+        let precision = cfg.precision(&cfg.symbol);
+        let dp = precision.price_decimals();
+        let tp_levels = vec![
+            TpLevelInfo {
+                label: format!("Midnight open ({:.0})", open),
+                price: precision.round_price(open),
+                pda_confluence: false,
+                level: None,
+                volume_confluence: false,
+            },
+            TpLevelInfo {
+                label: format!("{} ({:.0})", far_label, far_target),
+                price: precision.round_price(far_target),
+                pda_confluence: false,
+                level: None,
+                volume_confluence: false,
+            },
+        ];
+
+        let confidence = (0.5 + 0.3 * pda.strength)
+            * scale_cfg.weight
+            * session.session_weight
+            * session.silver_bullet_multiplier();
+
+        let reason = format!(
+            "[{}] {} | Midnight reversion: bias {} on {}, {:+.2}% from open {:.*} | PDA: {}({}) @ {:.*} | SL: day extreme ({:.*}) | TP: open -> {} | R:R {:.1}",
+            scale_cfg.name,
+            direction.to_string().to_uppercase(),
+            bias,
+            scale_cfg.structure_tf,
+            offset * 100.0,
+            dp,
+            open,
+            pda.pda_type,
+            pda.direction,
+            dp,
+            pda.midpoint,
+            dp,
+            stop_loss,
+            far_label,
+            reward / risk,
+        );
+
+        Some(HftSignal {
+            scale: scale_key.to_string(),
+            scale_name: scale_cfg.name.clone(),
+            direction,
+            entry_price: precision.round_price(current),
+            stop_loss: precision.round_price(stop_loss),
+            take_profit: precision.round_price(far_target),
+            pda_engaged: pda,
+            cisd_confirmed: false,
+            confidence: round3(confidence.min(1.0)),
+            session: session.current_session.clone(),
+            session_weight: session.session_weight,
+            reason,
+            cross_scale_confluence: 1,
+            stop_mode: "midnight_extreme".to_string(),
+            stop_reason: format!(
+                "Beyond the day's {} since midnight ET",
+                if long { "low" } else { "high" }
+            ),
+            tp_label: format!("{} ({:.0})", far_label, far_target),
+            tp_levels,
+            alignment: vec![AlignmentInfo {
+                tf: scale_cfg.structure_tf.to_string(),
+                trend: bias.to_string(),
+                bos: 0,
+            }],
+            limit_entry: None,
+        })
+    }
+}
+
+fn round3(x: f64) -> f64 {
+    (x * 1000.0).round() / 1000.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::{default_test_config, make_candles};
+
+    /// Higher highs and higher lows: 6 candles up 2.0, 6 down 1.0, repeated.
+    fn rising_zigzag(waves: usize) -> CandleSeries {
+        let mut price = 90.0;
+        let mut bars = Vec::new();
+        for _ in 0..waves {
+            for step in [2.0, -1.0] {
+                for _ in 0..6 {
+                    let next: f64 = price + step;
+                    bars.push((price, price.max(next) + 0.2, price.min(next) - 0.2, next));
+                    price = next;
+                }
+            }
+        }
+        make_candles(&bars)
+    }
+
+    #[test]
+    fn long_after_discount_tap_below_midnight_open() {
+        let cfg = default_test_config();
+        let session = SessionManager::new(&cfg);
+        // 5m scale: entry M5, structure M15. Midnight open 100.
+        let entry = make_candles(&[
+            (100.0, 103.0, 99.8, 100.0),
+            (100.0, 100.1, 99.0, 99.1),
+            (99.1, 99.2, 98.0, 98.2),
+            (98.2, 98.6, 98.1, 98.5),
+            (98.5, 99.4, 98.5, 99.3),
+            (99.3, 99.5, 98.9, 99.0),  // bullish FVG 98.6 - 98.9
+            (99.0, 99.1, 98.7, 98.8),  // taps the FVG
+            (98.8, 99.3, 98.75, 99.2), // displaces above the prior high
+        ]);
+        let mut data = HashMap::new();
+        data.insert(Timeframe::M5, entry.clone());
+        data.insert(Timeframe::M15, rising_zigzag(6));
+
+        let mut strat = MidnightReversion::new(&cfg);
+        let sig = strat
+            .evaluate_scale("5m", &data, Some(100.0), &session, &cfg)
+            .expect("signal");
+        assert_eq!(sig.direction, Direction::Long);
+        assert_eq!(sig.entry_price, 99.2);
+        assert!(sig.stop_loss < 98.0);
+        assert_eq!(sig.tp_levels[0].price, 100.0);
+        assert_eq!(sig.take_profit, 103.0);
+        assert_eq!(sig.pda_engaged.low, 98.6);
+
+        // Above the open there is no discount to buy
+        assert!(strat
+            .evaluate_scale("5m", &data, Some(98.5), &session, &cfg)
+            .is_none());
+        // No midnight open, no setup
+        assert!(strat
+            .evaluate_scale("5m", &data, None, &session, &cfg)
+            .is_none());
+    }
+}
@@ -1,3 +1,6 @@
 pub mod fractal_engine;
+pub mod midnight_reversion;
 pub mod signals;
+pub mod silver_bullet;
+pub mod strategy;
 pub mod weekly_profiles;
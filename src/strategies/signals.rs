@@ -1,7 +1,11 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
+use crate::core::calendar::EconCalendar;
 use crate::core::pd_arrays::Pda;
+use crate::core::sessions::SessionManager;
 use crate::models::Direction;
+use crate::strategies::weekly_profiles::WeeklyBias;
 use crate::trading::trade_record::TpLevelInfo;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -18,4 +22,42 @@ pub struct TradeSignal {
     pub reason: String,
     #[serde(default)]
     pub tp_levels: Option<Vec<TpLevelInfo>>,
+    /// Scales the risked amount (e.g. a reduced-size retry); 1.0 = normal
+    #[serde(default = "default_size_multiplier")]
+    pub size_multiplier: f64,
+    /// Rank (0-1) of the entry timeframe's current ATR, for volatility sizing
+    #[serde(default)]
+    pub atr_percentile: Option<f64>,
+    /// Free-form labels (`silver_bullet`, `tgif`, ...) carried onto the
+    /// position and its trade record
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+fn default_size_multiplier() -> f64 {
+    1.0
+}
+
+/// A high-impact release this recent makes a signal `post_news`.
+const POST_NEWS_MINUTES: i64 = 120;
+
+/// Tags for a signal firing now: the AM silver bullet window, an active
+/// TGIF setup, or a recent high-impact release.
+pub fn context_tags(
+    session: &SessionManager,
+    weekly_bias: &WeeklyBias,
+    calendar: &EconCalendar,
+    now: DateTime<Utc>,
+) -> Vec<String> {
+    let mut tags = Vec::new();
+    if session.is_silver_bullet() {
+        tags.push("silver_bullet".to_string());
+    }
+    if weekly_bias.tgif_active {
+        tags.push("tgif".to_string());
+    }
+    if calendar.blackout(now, 0, POST_NEWS_MINUTES).is_some() {
+        tags.push("post_news".to_string());
+    }
+    tags
 }
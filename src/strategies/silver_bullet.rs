@@ -0,0 +1,231 @@
+use std::collections::HashMap;
+
+use crate::config::Config;
+use crate::core::pd_arrays::{PdArrayDetector, Pda};
+use crate::core::sessions::SessionManager;
+use crate::models::{CandleSeries, Direction, PdaType, Timeframe, Trend};
+use crate::strategies::fractal_engine::HftSignal;
+use crate::strategies::strategy::Strategy;
+use crate::trading::trade_record::TpLevelInfo;
+
+/// Entry-TF candles before the raid whose extremes are the liquidity pools.
+const RANGE_LOOKBACK: usize = 24;
+/// Entry-TF candles back in which the raid and its FVG may have formed.
+const RAID_LOOKBACK: usize = 8;
+/// Stop sits this fraction of the raid's depth beyond its extreme.
+const STOP_BUFFER: f64 = 0.1;
+
+/// ICT AM Silver Bullet (10:00-11:00 ET).
+///
+/// Inside the window, once price raids the liquidity under the recent
+/// range's low (or over its high) and displaces back in, leaving an FVG,
+/// enter as price retraces into that FVG. Stop beyond the raid's extreme,
+/// target the liquidity on the opposite side of the range.
+pub struct SilverBullet {
+    pd_detector: PdArrayDetector,
+    min_rr: f64,
+}
+
+impl SilverBullet {
+    pub fn new(cfg: &Config) -> Self {
+        Self {
+            pd_detector: PdArrayDetector::new(),
+            min_rr: cfg.silver_bullet_min_rr,
+        }
+    }
+}
+
+impl Strategy for SilverBullet {
+    fn name(&self) -> &'static str {
+        "silver_bullet"
+    }
+
+    fn evaluate_scale(
+        &mut self,
+        scale_key: &str,
+        data: &HashMap<Timeframe, CandleSeries>,
+        _midnight_open: Option<f64>,
+        session: &SessionManager,
+        cfg: &Config,
+    ) -> Option<HftSignal> {
+        // Step 1: only inside the window
+        if !session.is_silver_bullet() {
+            return None;
+        }
+        let scale_cfg = cfg.hft_scales.get(scale_key)?;
+        let entry_df = data.get(&scale_cfg.entry_tf)?;
+        let n = entry_df.len();
+        if n < RANGE_LOOKBACK + RAID_LOOKBACK {
+            return None;
+        }
+        let range = entry_df.slice(n - RAID_LOOKBACK - RANGE_LOOKBACK, n - RAID_LOOKBACK);
+        let recent = entry_df.slice(n - RAID_LOOKBACK, n);
+        let (ssl, bsl) = (range.lows_min(), range.highs_max());
+        let last = recent.last()?;
+        let current = last.close;
+
+        // Step 2: one side of the range raided, price back inside it
+        let direction = match (recent.any_low_below(ssl), recent.any_high_above(bsl)) {
+            (true, false) if current > ssl => Direction::Long,
+            (false, true) if current < bsl => Direction::Short,
+            _ => return None,
+        };
+        let long = direction == Direction::Long;
+        let raid = if long {
+            recent.get(recent.low_idx_min()?)?
+        } else {
+            recent.get(recent.high_idx_max()?)?
+        };
+
+        // Step 3: FVG left by the displacement after the raid, which the
+        // last candle trades back into without closing through
+        let formed_by = entry_df.get(n - 2)?.timestamp;
+        let trend = if long { Trend::Bullish } else { Trend::Bearish };
+        let fvg: Pda = self
+            .pd_detector
+            .detect_all(
+                entry_df,
+                scale_cfg.entry_tf,
+                cfg.fvg_min_gap_percent,
+                cfg.ob_lookback,
+                cfg.breaker_lookback,
+            )
+            .iter()
+            .filter(|p| p.pda_type == PdaType::FVG && p.direction == trend)
+            .filter(|p| p.timestamp > raid.timestamp && p.timestamp < formed_by)
+            .filter(|p| {
+                if long {
+                    last.low <= p.high && current >= p.low
+                } else {
+                    last.high >= p.low && current <= p.high
+                }
+            })
+            .max_by_key(|p| p.timestamp)?
+            .clone();
+
+        // Step 4: stop beyond the raid, target the opposite pool
+        let (stop_loss, target) = if long {
+            (raid.low - (ssl - raid.low) * STOP_BUFFER, bsl)
+        } else {
+            (raid.high + (raid.high - bsl) * STOP_BUFFER, ssl)
+        };
+        let risk = (current - stop_loss).abs();
+        let reward = (target - current).abs();
+        if risk <= 0.0 || reward / risk < self.min_rr {
+            return None;
+        }
+
+        let precision = cfg.precision(&cfg.symbol);
+        let dp = precision.price_decimals();
+        let (raided, pool) = if long {
+            ("Sell-side", "Buy-side")
+        } else {
+            ("Buy-side", "Sell-side")
+        };
+        let tp_label = format!("{} liquidity ({:.0})", pool, target);
+        let tp_levels = vec![TpLevelInfo {
+            label: tp_label.clone(),
+            price: precision.round_price(target),
+            pda_confluence: false,
+            level: None,
+            volume_confluence: false,
+        }];
+
+        let confidence = (0.5 + 0.3 * fvg.strength) * scale_cfg.weight * session.session_weight;
+
+        let reason = format!(
+            "[{}] {} | Silver bullet: {} liquidity raided @ {:.*} | FVG {:.*}-{:.*} | SL: raid extreme ({:.*}) | TP: {} | R:R {:.1}",
+            scale_cfg.name,
+            direction.to_string().to_uppercase(),
+            raided,
+            dp,
+            if long { raid.low } else { raid.high },
+            dp,
+            fvg.low,
+            dp,
+            fvg.high,
+            dp,
+            stop_loss,
+            tp_label,
+            reward / risk,
+        );
+
+        Some(HftSignal {
+            scale: scale_key.to_string(),
+            scale_name: scale_cfg.name.clone(),
+            direction,
+            entry_price: precision.round_price(current),
+            stop_loss: precision.round_price(stop_loss),
+            take_profit: precision.round_price(target),
+            pda_engaged: fvg,
+            cisd_confirmed: false,
+            confidence: round3(confidence.min(1.0)),
+            session: session.current_session.clone(),
+            session_weight: session.session_weight,
+            reason,
+            cross_scale_confluence: 1,
+            stop_mode: "raid_extreme".to_string(),
+            stop_reason: format!("Beyond the {} raid", raided.to_lowercase()),
+            tp_label,
+            tp_levels,
+            alignment: Vec::new(),
+            limit_entry: None,
+        })
+    }
+}
+
+fn round3(x: f64) -> f64 {
+    (x * 1000.0).round() / 1000.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::{default_test_config, make_candles};
+    use chrono::{DateTime, Utc};
+
+    #[test]
+    fn long_on_fvg_retrace_after_sell_side_raid_in_window() {
+        let cfg = default_test_config();
+        // Range 99-103, then a raid to 98.8 and a bullish FVG 99.3-99.8
+        let mut bars: Vec<(f64, f64, f64, f64)> = (0..RANGE_LOOKBACK)
+            .map(|i| match i {
+                5 => (100.5, 103.0, 100.0, 100.5),
+                12 => (100.5, 101.0, 99.0, 100.5),
+                _ => (100.5, 101.0, 100.0, 100.5),
+            })
+            .collect();
+        bars.extend([
+            (100.0, 100.2, 99.6, 99.7),
+            (99.7, 99.8, 98.8, 99.0), // raids 99.0
+            (99.0, 99.3, 98.9, 99.2),
+            (99.2, 100.2, 99.15, 100.1), // displacement
+            (100.1, 100.4, 99.8, 100.3),
+            (100.3, 100.35, 99.9, 100.0),
+            (100.0, 100.05, 99.6, 99.7),
+            (99.7, 99.9, 99.5, 99.85), // back into the FVG
+        ]);
+        let mut data = HashMap::new();
+        data.insert(Timeframe::M5, make_candles(&bars));
+
+        let at = |t: &str| DateTime::parse_from_rfc3339(t).unwrap().with_timezone(&Utc);
+        let mut session = SessionManager::new(&cfg);
+        // 15:15 UTC in January is 10:15 ET
+        session.update(&cfg, Some(at("2024-01-16T15:15:00Z")));
+        let mut strat = SilverBullet::new(&cfg);
+        let sig = strat
+            .evaluate_scale("5m", &data, None, &session, &cfg)
+            .expect("signal");
+        assert_eq!(sig.direction, Direction::Long);
+        assert_eq!(sig.entry_price, 99.85);
+        assert_eq!((sig.pda_engaged.low, sig.pda_engaged.high), (99.3, 99.8));
+        assert!(sig.stop_loss < 98.8);
+        assert_eq!(sig.take_profit, 103.0);
+
+        // 09:15 ET is before the window
+        session.update(&cfg, Some(at("2024-01-16T14:15:00Z")));
+        assert!(strat
+            .evaluate_scale("5m", &data, None, &session, &cfg)
+            .is_none());
+    }
+}
@@ -0,0 +1,296 @@
+use std::collections::HashMap;
+
+use crate::config::Config;
+use crate::core::pd_arrays::CacheStats;
+use crate::core::sessions::SessionManager;
+use crate::models::{CandleSeries, Timeframe};
+use crate::strategies::fractal_engine::{FractalEngine, HftSignal};
+use crate::strategies::midnight_reversion::MidnightReversion;
+use crate::strategies::silver_bullet::SilverBullet;
+
+/// A signal generator the backtester can drive scale by scale.
+///
+/// Each scale's timeframes come from `cfg.hft_scales`, so strategies share
+/// the same position slots, cooldowns and filters in the runner.
+pub trait Strategy: Send {
+    fn name(&self) -> &'static str;
+
+    /// Signal for one scale, before the min-confidence filter.
+    fn evaluate_scale(
+        &mut self,
+        scale_key: &str,
+        data: &HashMap<Timeframe, CandleSeries>,
+        midnight_open: Option<f64>,
+        session: &SessionManager,
+        cfg: &Config,
+    ) -> Option<HftSignal>;
+
+    /// PD array cache hits, for strategies that cache detection.
+    fn pda_cache_stats(&self) -> Option<CacheStats> {
+        None
+    }
+}
+
+impl Strategy for FractalEngine {
+    fn name(&self) -> &'static str {
+        "fractal"
+    }
+
+    fn evaluate_scale(
+        &mut self,
+        scale_key: &str,
+        data: &HashMap<Timeframe, CandleSeries>,
+        midnight_open: Option<f64>,
+        session: &SessionManager,
+        cfg: &Config,
+    ) -> Option<HftSignal> {
+        FractalEngine::evaluate_scale(self, scale_key, data, midnight_open, session, cfg)
+    }
+
+    fn pda_cache_stats(&self) -> Option<CacheStats> {
+        Some(FractalEngine::pda_cache_stats(self))
+    }
+}
+
+/// Names of the built-in strategies.
+pub const STRATEGY_NAMES: [&str; 3] = ["fractal", "midnight_reversion", "silver_bullet"];
+
+type Factory = Box<dyn Fn(&Config) -> Box<dyn Strategy> + Send + Sync>;
+
+/// Strategies by name: the built-ins plus any registered at startup.
+pub struct StrategyRegistry {
+    factories: Vec<(String, Factory)>,
+}
+
+impl StrategyRegistry {
+    /// `fractal`, `midnight_reversion` and `silver_bullet`.
+    pub fn builtin() -> Self {
+        let mut registry = Self {
+            factories: Vec::new(),
+        };
+        registry.register("fractal", |cfg| Box::new(FractalEngine::new(cfg)));
+        registry.register("midnight_reversion", |cfg| {
+            Box::new(MidnightReversion::new(cfg))
+        });
+        registry.register("silver_bullet", |cfg| Box::new(SilverBullet::new(cfg)));
+        registry
+    }
+
+    /// Add (or replace) the strategy built by `factory` under `name`.
+    pub fn register(
+        &mut self,
+        name: &str,
+        factory: impl Fn(&Config) -> Box<dyn Strategy> + Send + Sync + 'static,
+    ) {
+        let name = name.trim().to_lowercase();
+        self.factories.retain(|(n, _)| *n != name);
+        self.factories.push((name, Box::new(factory)));
+    }
+
+    pub fn names(&self) -> Vec<&str> {
+        self.factories.iter().map(|(n, _)| n.as_str()).collect()
+    }
+
+    /// Build a strategy by name (`midnight` is short for `midnight_reversion`).
+    pub fn build(&self, name: &str, cfg: &Config) -> Option<Box<dyn Strategy>> {
+        let name = match name.trim().to_lowercase().as_str() {
+            "midnight" => "midnight_reversion".to_string(),
+            other => other.to_string(),
+        };
+        self.factories
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, factory)| factory(cfg))
+    }
+
+    /// Build each of `names` in order; unknown names are logged and skipped.
+    pub fn build_all(&self, names: &[String], cfg: &Config) -> Vec<Box<dyn Strategy>> {
+        names
+            .iter()
+            .filter_map(|name| {
+                let strategy = self.build(name, cfg);
+                if strategy.is_none() {
+                    tracing::warn!("Unknown strategy '{}', skipped", name);
+                }
+                strategy
+            })
+            .collect()
+    }
+}
+
+/// Build a built-in strategy by name.
+pub fn from_name(name: &str, cfg: &Config) -> Option<Box<dyn Strategy>> {
+    StrategyRegistry::builtin().build(name, cfg)
+}
+
+/// Strategies to run: STRATEGIES, else just `default`.
+pub fn configured(cfg: &Config, default: &str) -> Vec<String> {
+    if cfg.strategies.is_empty() {
+        vec![default.to_string()]
+    } else {
+        cfg.strategies.clone()
+    }
+}
+
+/// First signal for `scale_key` and the strategy that gave it, trying
+/// `strategies` in STRATEGIES order.
+pub fn first_signal<'a>(
+    strategies: impl IntoIterator<Item = &'a mut dyn Strategy>,
+    scale_key: &str,
+    data: &HashMap<Timeframe, CandleSeries>,
+    midnight_open: Option<f64>,
+    session: &SessionManager,
+    cfg: &Config,
+) -> Option<(&'static str, HftSignal)> {
+    strategies.into_iter().find_map(|s| {
+        let signal = s.evaluate_scale(scale_key, data, midnight_open, session, cfg)?;
+        Some((s.name(), signal))
+    })
+}
+
+/// Parse `silver_bullet:0.5,midnight_reversion:0.75`; `None` unless every
+/// entry is `name:multiplier` with a non-negative multiplier.
+pub fn parse_risk_budgets(s: &str) -> Option<HashMap<String, f64>> {
+    s.split(',')
+        .map(str::trim)
+        .filter(|e| !e.is_empty())
+        .map(|e| {
+            let (name, mult) = e.split_once(':')?;
+            let mult: f64 = mult.trim().parse().ok()?;
+            (mult >= 0.0).then(|| (name.trim().to_lowercase(), mult))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::pd_arrays::Pda;
+    use crate::models::{Direction, PdaType, Trend, Zone};
+    use crate::strategies::fractal_engine::HftSignal;
+    use crate::test_helpers::default_test_config;
+
+    struct Never;
+
+    /// Always signals, tagging the reason with its name.
+    struct Always(&'static str);
+
+    impl Strategy for Always {
+        fn name(&self) -> &'static str {
+            self.0
+        }
+
+        fn evaluate_scale(
+            &mut self,
+            scale_key: &str,
+            _data: &HashMap<Timeframe, CandleSeries>,
+            _midnight_open: Option<f64>,
+            _session: &SessionManager,
+            _cfg: &Config,
+        ) -> Option<HftSignal> {
+            Some(HftSignal {
+                scale: scale_key.to_string(),
+                scale_name: scale_key.to_string(),
+                direction: Direction::Long,
+                entry_price: 100.0,
+                stop_loss: 99.0,
+                take_profit: 102.0,
+                pda_engaged: Pda {
+                    pda_type: PdaType::FVG,
+                    direction: Trend::Bullish,
+                    zone: Zone::Discount,
+                    high: 99.8,
+                    low: 99.5,
+                    midpoint: 99.65,
+                    timestamp: chrono::Utc::now(),
+                    timeframe: Timeframe::M5,
+                    strength: 0.5,
+                },
+                cisd_confirmed: false,
+                confidence: 0.7,
+                session: "london".to_string(),
+                session_weight: 1.0,
+                reason: self.0.to_string(),
+                cross_scale_confluence: 1,
+                stop_mode: String::new(),
+                stop_reason: String::new(),
+                tp_label: String::new(),
+                tp_levels: Vec::new(),
+                alignment: Vec::new(),
+                limit_entry: None,
+            })
+        }
+    }
+
+    impl Strategy for Never {
+        fn name(&self) -> &'static str {
+            "never"
+        }
+
+        fn evaluate_scale(
+            &mut self,
+            _scale_key: &str,
+            _data: &HashMap<Timeframe, CandleSeries>,
+            _midnight_open: Option<f64>,
+            _session: &SessionManager,
+            _cfg: &Config,
+        ) -> Option<HftSignal> {
+            None
+        }
+    }
+
+    #[test]
+    fn registry_builds_builtin_and_user_strategies_by_name() {
+        let mut cfg = default_test_config();
+        let mut registry = StrategyRegistry::builtin();
+        assert_eq!(registry.names(), STRATEGY_NAMES);
+        assert_eq!(
+            registry.build("Midnight", &cfg).unwrap().name(),
+            "midnight_reversion"
+        );
+
+        registry.register("never", |_| Box::new(Never));
+        let names = ["silver_bullet", "nope", "never"].map(String::from);
+        let built: Vec<&str> = registry
+            .build_all(&names, &cfg)
+            .iter()
+            .map(|s| s.name())
+            .collect();
+        assert_eq!(built, ["silver_bullet", "never"]);
+
+        assert_eq!(configured(&cfg, "fractal"), ["fractal"]);
+        cfg.strategies = vec!["silver_bullet".to_string()];
+        assert_eq!(configured(&cfg, "fractal"), ["silver_bullet"]);
+
+        let budgets = parse_risk_budgets("silver_bullet:0.5, Never:0").unwrap();
+        assert_eq!(budgets["silver_bullet"], 0.5);
+        assert_eq!(budgets["never"], 0.0);
+        assert!(parse_risk_budgets("silver_bullet:-1").is_none());
+        assert!(parse_risk_budgets("silver_bullet").is_none());
+        assert!(parse_risk_budgets("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn first_signal_follows_the_configured_order() {
+        let cfg = default_test_config();
+        let session = SessionManager::new(&cfg);
+        let data = HashMap::new();
+        let mut strategies: Vec<Box<dyn Strategy>> = vec![
+            Box::new(Never),
+            Box::new(Always("silver_bullet")),
+            Box::new(Always("fractal")),
+        ];
+        let pick = |strategies: &mut Vec<Box<dyn Strategy>>| {
+            let ordered = strategies
+                .iter_mut()
+                .map(|s| s.as_mut() as &mut dyn Strategy);
+            first_signal(ordered, "5m", &data, None, &session, &cfg).map(|(name, _)| name)
+        };
+        assert_eq!(pick(&mut strategies), Some("silver_bullet"));
+
+        strategies.swap(1, 2);
+        assert_eq!(pick(&mut strategies), Some("fractal"));
+        strategies.truncate(1);
+        assert_eq!(pick(&mut strategies), None);
+    }
+}
@@ -1,4 +1,3 @@
-use chrono::Datelike;
 use serde::{Deserialize, Serialize};
 
 use crate::config::Config;
@@ -20,16 +19,20 @@ pub struct WeeklyBias {
 pub struct WeeklyProfileClassifier {
     pd_detector: PdArrayDetector,
     structure: MarketStructure,
-    cisd: CisdDetector,
     pub current_bias: Option<WeeklyBias>,
 }
 
+impl Default for WeeklyProfileClassifier {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl WeeklyProfileClassifier {
     pub fn new() -> Self {
         Self {
             pd_detector: PdArrayDetector::new(),
             structure: MarketStructure::new(),
-            cisd: CisdDetector::new(),
             current_bias: None,
         }
     }
@@ -146,23 +149,15 @@ impl WeeklyProfileClassifier {
         bias
     }
 
+    /// Daily candles since the latest weekly bar opened (Monday 00:00 UTC).
     fn get_current_week(&self, daily_df: &CandleSeries) -> CandleSeries {
-        if daily_df.is_empty() {
+        let Some(week) = daily_df.resample_weekly().last().cloned() else {
             return CandleSeries::default();
-        }
-
-        let latest = daily_df.last().unwrap().timestamp;
-        let weekday = latest.weekday().num_days_from_monday(); // 0=Mon
-        let week_start = latest - chrono::Duration::days(weekday as i64);
-        let week_start_date = week_start.date_naive();
-
-        let candles: Vec<_> = daily_df
-            .iter()
-            .filter(|c| c.timestamp.date_naive() >= week_start_date)
-            .cloned()
-            .collect();
-
-        CandleSeries::new(candles)
+        };
+        let first = daily_df
+            .as_slice()
+            .partition_point(|c| c.timestamp < week.timestamp);
+        daily_df.slice(first, daily_df.len())
     }
 
     fn score_classic_expansion(
@@ -1,8 +1,17 @@
 use chrono::{DateTime, Duration, Utc};
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
-use crate::config::{Config, DayRatings, HftScaleConfig, SessionTime};
-use crate::models::{Candle, CandleSeries, Timeframe};
+use crate::config::{
+    Config, DayRatings, FillTiming, HedgePolicy, HftScaleConfig, QuarterMode, RiskLimits,
+    SessionTime, SignalRanking, StorageBackend, TpAllocMode, TpAllocation, ALL_WEEKDAYS,
+};
+use crate::core::anchors::Anchor;
+use crate::core::position_sizing::SizingMode;
+use crate::models::{Candle, CandleSeries, Direction, GapFill, Timeframe};
+use crate::strategies::signals::TradeSignal;
+use crate::trading::execution_model::ExecutionModelKind;
+use crate::trading::paper_trader::IntrabarOrdering;
 
 /// Create candles from (open, high, low, close) tuples with auto-incrementing 1m timestamps.
 pub fn make_candles(data: &[(f64, f64, f64, f64)]) -> CandleSeries {
@@ -74,6 +83,43 @@ pub fn make_bearish_trend(n: usize, start: f64) -> CandleSeries {
     CandleSeries::new(candles)
 }
 
+/// Same candles with every price multiplied by `factor`, e.g. a BTC-scale
+/// fixture at DOGE prices with `factor = 0.001`.
+pub fn scale_prices(series: &CandleSeries, factor: f64) -> CandleSeries {
+    let candles: Vec<Candle> = series
+        .iter()
+        .map(|c| Candle {
+            open: c.open * factor,
+            high: c.high * factor,
+            low: c.low * factor,
+            close: c.close * factor,
+            ..c.clone()
+        })
+        .collect();
+
+    CandleSeries::new(candles)
+}
+
+/// A London 5m signal with the given levels and 0.7 confidence.
+pub fn make_signal(direction: Direction, entry: f64, sl: f64, tp: f64) -> TradeSignal {
+    TradeSignal {
+        direction,
+        entry_price: entry,
+        stop_loss: sl,
+        take_profit: tp,
+        pda_engaged: None,
+        cisd_confirmed: false,
+        confidence: 0.7,
+        session: "london".to_string(),
+        session_weight: 1.5,
+        reason: "test signal 5m".to_string(),
+        tp_levels: None,
+        size_multiplier: 1.0,
+        atr_percentile: None,
+        tags: Vec::new(),
+    }
+}
+
 /// A Config suitable for testing — paper mode, no API keys needed, temp log dir.
 pub fn default_test_config() -> Config {
     let mut sessions = HashMap::new();
@@ -120,11 +166,15 @@ pub fn default_test_config() -> Config {
             name: "1m Scalp".to_string(),
             entry_tf: Timeframe::M1,
             alignment_tfs: vec![Timeframe::M5, Timeframe::M15, Timeframe::H1],
+            alignment_rules: Vec::new(),
             structure_tf: Timeframe::M5,
             confirm_tf: Timeframe::M1,
             scan_interval: 10,
             min_confidence: 0.5,
             weight: 0.7,
+            max_hold_minutes: None,
+            tp_alloc_conservative: None,
+            tp_alloc_aggressive: None,
         },
     );
     hft_scales.insert(
@@ -133,11 +183,15 @@ pub fn default_test_config() -> Config {
             name: "5m Intraday".to_string(),
             entry_tf: Timeframe::M5,
             alignment_tfs: vec![Timeframe::M15, Timeframe::H1, Timeframe::H4],
+            alignment_rules: Vec::new(),
             structure_tf: Timeframe::M15,
             confirm_tf: Timeframe::M5,
             scan_interval: 30,
             min_confidence: 0.45,
             weight: 0.85,
+            max_hold_minutes: None,
+            tp_alloc_conservative: None,
+            tp_alloc_aggressive: None,
         },
     );
     hft_scales.insert(
@@ -146,11 +200,15 @@ pub fn default_test_config() -> Config {
             name: "15m Swing".to_string(),
             entry_tf: Timeframe::M15,
             alignment_tfs: vec![Timeframe::H1, Timeframe::H4, Timeframe::D1],
+            alignment_rules: Vec::new(),
             structure_tf: Timeframe::H1,
             confirm_tf: Timeframe::M15,
             scan_interval: 60,
             min_confidence: 0.4,
             weight: 1.0,
+            max_hold_minutes: None,
+            tp_alloc_conservative: None,
+            tp_alloc_aggressive: None,
         },
     );
 
@@ -207,32 +265,167 @@ pub fn default_test_config() -> Config {
     Config {
         exchange: "coinbase".to_string(),
         symbol: "BTC-USD".to_string(),
+        symbols: vec!["BTC-USD".to_string()],
+        precision: HashMap::new(),
+        instruments: HashMap::new(),
+        funding_interval_hours: 1,
+        funding_rate: 0.0,
         coinbase_api_key: String::new(),
         coinbase_api_secret: String::new(),
+        use_websocket: false,
+        live_fill_timeout_secs: 30,
+        stop_limit_buffer: 0.005,
         paper_trade: true,
         initial_balance: 200.0,
         max_daily_loss: 0.03,
         max_open_positions: 3,
+        max_risk_pct: 0.02,
+        regime_low_confidence: 0.4,
+        regime_high_confidence: 0.7,
+        cautious_limits: RiskLimits {
+            max_open_positions: 3,
+            max_risk_pct: 0.02,
+        },
+        expansion_limits: RiskLimits {
+            max_open_positions: 3,
+            max_risk_pct: 0.02,
+        },
+        max_net_long_exposure: 0.0,
+        max_net_short_exposure: 0.0,
+        max_drawdown: 0.0,
+        drawdown_cool_off_hours: 24.0,
+        drawdown_flatten: false,
+        pyramid_max_adds: 0,
+        pyramid_size: 0.5,
+        hedge_policy: HedgePolicy::Allow,
+        sizing_mode: SizingMode::Kelly,
+        fixed_risk_pct: 0.01,
+        trail_activation_r: 0.0,
+        trail_distance_pct: 0.0,
+        trail_timeframe: None,
+        kill_switch_file: std::env::temp_dir()
+            .join("ict_bot_test_kill_switch")
+            .to_string_lossy()
+            .to_string(),
+        kill_switch: false,
+        kill_switch_flatten: false,
+        schedule_jitter_secs: 0.0,
+        telegram_bot_token: String::new(),
+        telegram_chat_id: String::new(),
+        discord_webhook_url: String::new(),
+        api_bind: String::new(),
+        api_token: String::new(),
         fee_rate: 0.0,
         slippage_rate: 0.0,
+        exit_slippage_rate: 0.0,
+        execution_model: ExecutionModelKind::Flat,
+        maker_fee_rate: 0.0,
+        impact_coefficient: 0.0,
+        partial_fill_pct: 0.0,
+        partial_fill_step: 0.0002,
+        tp_alloc_mode: TpAllocMode::Dynamic,
+        tp_alloc_conservative: TpAllocation::conservative(),
+        tp_alloc_aggressive: TpAllocation::aggressive(),
+        signal_ranking: SignalRanking::Confidence,
+        move_to_breakeven_after_tp1: false,
+        stuck_hold_multiple: 3.0,
+        stuck_progress_band: 0.2,
+        stuck_tighten_stop: false,
+        fill_timing: FillTiming::SignalClose,
+        fill_latency_secs: 0,
+        backtest_strategy: "fractal".to_string(),
+        strategies: Vec::new(),
+        strategy_risk: HashMap::new(),
+        backtest_tick_seconds: 15,
+        intrabar_ordering: IntrabarOrdering::SlFirst,
+        replay_bars: 50,
+        ab_bootstrap_runs: 5000,
+        mc_runs: 5000,
+        mc_ruin_pct: 50.0,
+        mc_seed: 1,
+        opt_min_confidence: Vec::new(),
+        opt_fvg_min_gap: Vec::new(),
+        opt_ob_lookback: Vec::new(),
+        opt_tp_alloc: Vec::new(),
+        opt_parallelism: None,
+        wf_train_days: 30,
+        wf_test_days: 7,
+        skip_scales: Vec::new(),
+        data_lookback: None,
+        gap_fill: GapFill::Off,
+        data_max_stale_bars: 3,
+        cooldown_minutes: None,
+        min_tp_multiple: 6.0,
+        max_leverage: 5.0,
+        max_hold_minutes: 180,
+        post_tp_stall_minutes: 120,
+        exhaust_candles: 0,
+        sweep_displacement_atr: 1.0,
+        judas_anchor: Anchor::Midnight,
+        pd_anchor: None,
+        swing_lookback: 1,
+        midnight_min_discount: 0.001,
+        midnight_min_rr: 1.5,
+        silver_bullet_min_rr: 2.0,
+        expectancy_window: 50,
         sessions,
         session_weights,
+        holidays: Vec::new(),
+        builtin_holidays: false,
+        holiday_half_day_weight: 0.5,
+        trading_days: ALL_WEEKDAYS.to_vec(),
+        blackout_dates: Vec::new(),
+        econ_calendar: String::new(),
+        news_blackout_before: 30,
+        news_blackout_after: 15,
+        news_tighten_stops: false,
+        news_currencies: vec!["USD".to_string()],
+        killzone_profiles: HashMap::new(),
+        quarter_mode: QuarterMode::Off,
+        quarter_off_weight: 0.8,
         hft_scales,
         cross_scale_confluence_bonus: 0.1,
+        volume_profile_bins: 40,
+        volume_confluence_bonus: 0.05,
+        smt_pairs: HashMap::new(),
+        smt_weight: 0.1,
+        smt_required: false,
+        ote_entries: false,
+        ote_expiry_minutes: 60,
         day_ratings,
         min_day_rating: 3.0,
         fvg_min_gap_percent: 0.0005,
         ob_lookback: 20,
         breaker_lookback: 30,
+        vi_min_gap_percent: 0.0002,
+        void_min_candles: 3,
+        void_min_move_percent: 0.005,
+        opening_gaps_keep: 5,
         tgif_retrace_min: 0.20,
         tgif_retrace_max: 0.30,
         analysis_interval: 3600,
         min_sample_per_bucket: 10,
         adjustment_step: 0.02,
+        skip_expiry_days: 14.0,
+        skip_retry_size: 0.5,
+        storage_backend: StorageBackend::Json,
         log_dir: std::env::temp_dir()
             .join("ict_bot_test")
             .to_string_lossy()
             .to_string(),
         log_level: "ERROR".to_string(),
+        account: "default".to_string(),
     }
 }
+
+/// `default_test_config` with a fresh log dir of its own, so persisted
+/// state never leaks between tests.
+pub fn unique_test_config() -> Config {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+    let n = COUNTER.fetch_add(1, Ordering::SeqCst);
+    let dir = std::env::temp_dir().join(format!("ict_bot_test_{}_{}", std::process::id(), n));
+    let _ = std::fs::remove_dir_all(&dir);
+    let mut cfg = default_test_config();
+    cfg.log_dir = dir.to_string_lossy().to_string();
+    cfg
+}
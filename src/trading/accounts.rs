@@ -0,0 +1,96 @@
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+use crate::config::Config;
+use crate::trading::paper_trader::PaperTrader;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountSummary {
+    pub account: String,
+    pub log_dir: String,
+    pub initial_balance: f64,
+    pub balance: f64,
+    pub realized_pnl: f64,
+    pub open_positions: usize,
+    pub total_trades: usize,
+}
+
+/// Summarize an account from its persisted state directory.
+pub fn account_summary(cfg: &Config) -> AccountSummary {
+    let trader = PaperTrader::new(cfg);
+    let snap = trader.snapshot();
+    AccountSummary {
+        account: cfg.account.clone(),
+        log_dir: cfg.log_dir.clone(),
+        initial_balance: cfg.initial_balance,
+        balance: snap.balance,
+        realized_pnl: snap.realized_pnl,
+        open_positions: snap.open_positions.len(),
+        total_trades: snap.total_trades,
+    }
+}
+
+/// Sum of all accounts, labelled "TOTAL".
+pub fn aggregate(summaries: &[AccountSummary]) -> AccountSummary {
+    AccountSummary {
+        account: "TOTAL".to_string(),
+        log_dir: String::new(),
+        initial_balance: summaries.iter().map(|s| s.initial_balance).sum(),
+        balance: round2(summaries.iter().map(|s| s.balance).sum()),
+        realized_pnl: round2(summaries.iter().map(|s| s.realized_pnl).sum()),
+        open_positions: summaries.iter().map(|s| s.open_positions).sum(),
+        total_trades: summaries.iter().map(|s| s.total_trades).sum(),
+    }
+}
+
+pub fn log_overview(accounts: &[Config]) {
+    let summaries: Vec<AccountSummary> = accounts.iter().map(account_summary).collect();
+    info!("--- Accounts ---");
+    for s in summaries.iter().chain(std::iter::once(&aggregate(&summaries))) {
+        info!(
+            "  {:>14}: ${:.2} (PnL ${:+.2}) | {} trades | {} open",
+            s.account, s.balance, s.realized_pnl, s.total_trades, s.open_positions
+        );
+    }
+}
+
+fn round2(x: f64) -> f64 {
+    (x * 100.0).round() / 100.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Direction;
+    use crate::test_helpers::{default_test_config, make_signal};
+
+    #[test]
+    fn accounts_have_isolated_state() {
+        let mut base = default_test_config();
+        base.log_dir = std::env::temp_dir()
+            .join(format!("ict_bot_accounts_{}", std::process::id()))
+            .to_string_lossy()
+            .to_string();
+        let _ = std::fs::remove_dir_all(&base.log_dir);
+
+        let a = base.for_account("alpha");
+        let b = base.for_account("beta");
+        assert_ne!(a.log_dir, b.log_dir);
+        assert_eq!(a.account, "alpha");
+
+        let mut trader = PaperTrader::new(&a);
+        let signal = make_signal(Direction::Long, 50000.0, 49500.0, 51000.0);
+        trader.open_position(&signal, "5m", None);
+
+        let sa = account_summary(&a);
+        let sb = account_summary(&b);
+        assert_eq!(sa.open_positions, 1);
+        assert_eq!(sb.open_positions, 0);
+
+        let total = aggregate(&[sa, sb]);
+        assert_eq!(total.open_positions, 1);
+        assert!((total.initial_balance - 2.0 * base.initial_balance).abs() < 1e-9);
+
+        let _ = std::fs::remove_dir_all(&base.log_dir);
+    }
+}
@@ -0,0 +1,127 @@
+use anyhow::{anyhow, Result};
+use plotters::prelude::*;
+use std::path::{Path, PathBuf};
+
+use crate::core::pd_arrays::Pda;
+use crate::models::{CandleSeries, Direction};
+use crate::trading::paper_trader::Position;
+
+/// Candles shown before the entry bar.
+const WINDOW: usize = 80;
+const SIZE: (u32, u32) = (900, 500);
+
+/// Default chart location for a position: `{log_dir}/charts/position_{id}.png`.
+pub fn chart_path(log_dir: &str, position_id: u64) -> PathBuf {
+    Path::new(log_dir)
+        .join("charts")
+        .join(format!("position_{}.png", position_id))
+}
+
+/// Render the entry-TF window with PDA zone, SL, TP levels and entry marked.
+/// Text-free so no system fonts are required.
+pub fn render_position(
+    path: &Path,
+    candles: &CandleSeries,
+    pos: &Position,
+    pda: Option<&Pda>,
+) -> Result<()> {
+    let window = candles.tail(WINDOW);
+    if window.is_empty() {
+        return Err(anyhow!("no candles to render"));
+    }
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    let tps: Vec<f64> = if pos.tp_targets.is_empty() {
+        vec![pos.take_profit]
+    } else {
+        pos.tp_targets.iter().map(|t| t.price).collect()
+    };
+
+    let mut lo = window.lows_min().min(pos.stop_loss);
+    let mut hi = window.highs_max().max(pos.stop_loss);
+    for &p in tps.iter().chain([pos.entry_price].iter()) {
+        lo = lo.min(p);
+        hi = hi.max(p);
+    }
+    if let Some(p) = pda {
+        lo = lo.min(p.low);
+        hi = hi.max(p.high);
+    }
+    let pad = (hi - lo).max(1e-9) * 0.03;
+    let n = window.len() as f64 + 3.0;
+
+    let root = BitMapBackend::new(path, SIZE).into_drawing_area();
+    root.fill(&WHITE).map_err(|e| anyhow!("{}", e))?;
+    let mut chart = ChartBuilder::on(&root)
+        .margin(10)
+        .build_cartesian_2d(-1.0..n, (lo - pad)..(hi + pad))
+        .map_err(|e| anyhow!("{}", e))?;
+
+    if let Some(p) = pda {
+        let color = match p.direction {
+            crate::models::Trend::Bearish => RED.mix(0.15),
+            _ => GREEN.mix(0.15),
+        };
+        chart
+            .draw_series(std::iter::once(Rectangle::new(
+                [(-1.0, p.low), (n, p.high)],
+                color.filled(),
+            )))
+            .map_err(|e| anyhow!("{}", e))?;
+    }
+
+    chart
+        .draw_series(window.iter().enumerate().map(|(i, c)| {
+            CandleStick::new(i as f64, c.open, c.high, c.low, c.close, GREEN.filled(), RED.filled(), 5)
+        }))
+        .map_err(|e| anyhow!("{}", e))?;
+
+    let hline = |y: f64, style: ShapeStyle| PathElement::new(vec![(-1.0, y), (n, y)], style);
+    chart
+        .draw_series(std::iter::once(hline(pos.stop_loss, RED.stroke_width(2))))
+        .map_err(|e| anyhow!("{}", e))?;
+    chart
+        .draw_series(tps.iter().map(|&tp| hline(tp, GREEN.stroke_width(1))))
+        .map_err(|e| anyhow!("{}", e))?;
+    chart
+        .draw_series(std::iter::once(hline(pos.entry_price, BLUE.stroke_width(1))))
+        .map_err(|e| anyhow!("{}", e))?;
+
+    let entry_x = window.len() as f64 - 1.0;
+    let marker = match pos.direction {
+        Direction::Long => BLUE.filled(),
+        Direction::Short => MAGENTA.filled(),
+    };
+    chart
+        .draw_series(std::iter::once(Circle::new((entry_x, pos.entry_price), 6, marker)))
+        .map_err(|e| anyhow!("{}", e))?;
+
+    root.present().map_err(|e| anyhow!("{}", e))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Direction;
+    use crate::test_helpers::{default_test_config, make_bullish_trend, make_signal};
+    use crate::trading::paper_trader::PaperTrader;
+
+    #[test]
+    fn renders_png() {
+        let cfg = default_test_config();
+        let candles = make_bullish_trend(50, 100.0);
+        let entry = candles.last().unwrap().close;
+        let mut trader = PaperTrader::new_fresh(&cfg);
+        let signal = make_signal(Direction::Long, entry, entry - 5.0, entry + 10.0);
+        let pos = trader.open_position(&signal, "5m", None).unwrap().clone();
+
+        let dir = std::env::temp_dir().join(format!("ict_bot_chart_{}", std::process::id()));
+        let path = chart_path(&dir.to_string_lossy(), pos.id);
+        render_position(&path, &candles, &pos, None).unwrap();
+        assert!(std::fs::metadata(&path).unwrap().len() > 0);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
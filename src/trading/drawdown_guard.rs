@@ -0,0 +1,119 @@
+use chrono::{DateTime, Duration, Utc};
+
+use crate::config::Config;
+
+/// State change reported by `DrawdownGuard::update`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BreakerEvent {
+    /// Drawdown from the peak reached the limit; entries are halted
+    Tripped { drawdown_pct: f64, peak: f64 },
+    /// Cool-off over; the peak restarts from the current equity
+    Reset { equity: f64 },
+}
+
+/// Multi-day circuit breaker: halts new entries once equity falls
+/// `max_drawdown` below its peak, for `cool_off` from the trip.
+#[derive(Debug, Clone)]
+pub struct DrawdownGuard {
+    /// Fraction of the peak; 0 disables the guard
+    max_drawdown: f64,
+    cool_off: Duration,
+    peak: f64,
+    tripped_at: Option<DateTime<Utc>>,
+}
+
+impl DrawdownGuard {
+    pub fn new(cfg: &Config, peak: f64) -> Self {
+        Self {
+            max_drawdown: cfg.max_drawdown,
+            cool_off: Duration::minutes((cfg.drawdown_cool_off_hours * 60.0) as i64),
+            peak,
+            tripped_at: None,
+        }
+    }
+
+    /// Track `equity` at `now`, tripping or resetting the breaker.
+    pub fn update(&mut self, equity: f64, now: DateTime<Utc>) -> Option<BreakerEvent> {
+        if let Some(at) = self.tripped_at {
+            if now < at + self.cool_off {
+                return None;
+            }
+            self.tripped_at = None;
+            self.peak = equity;
+            return Some(BreakerEvent::Reset { equity });
+        }
+
+        self.peak = self.peak.max(equity);
+        if self.max_drawdown <= 0.0 || self.peak <= 0.0 {
+            return None;
+        }
+        let drawdown = (self.peak - equity) / self.peak;
+        if drawdown < self.max_drawdown {
+            return None;
+        }
+        self.tripped_at = Some(now);
+        Some(BreakerEvent::Tripped {
+            drawdown_pct: drawdown * 100.0,
+            peak: self.peak,
+        })
+    }
+
+    pub fn is_tripped(&self) -> bool {
+        self.tripped_at.is_some()
+    }
+
+    /// When the breaker tripped, while tripped.
+    pub fn tripped_at(&self) -> Option<DateTime<Utc>> {
+        self.tripped_at
+    }
+
+    /// Resume a trip from a checkpoint; the cool-off still runs from `at`.
+    pub fn restore_trip(&mut self, at: DateTime<Utc>) {
+        self.tripped_at = Some(at);
+    }
+
+    /// When entries resume, while tripped.
+    pub fn resumes_at(&self) -> Option<DateTime<Utc>> {
+        self.tripped_at.map(|at| at + self.cool_off)
+    }
+
+    pub fn peak(&self) -> f64 {
+        self.peak
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::default_test_config;
+    use chrono::TimeZone;
+
+    #[test]
+    fn trips_below_peak_and_resets_after_cool_off() {
+        let mut cfg = default_test_config();
+        cfg.max_drawdown = 0.1;
+        cfg.drawdown_cool_off_hours = 24.0;
+        let mut guard = DrawdownGuard::new(&cfg, 1000.0);
+        let t0 = Utc.with_ymd_and_hms(2024, 1, 15, 12, 0, 0).unwrap();
+
+        assert_eq!(guard.update(1200.0, t0), None);
+        assert_eq!(guard.update(1090.0, t0), None);
+        assert!(!guard.is_tripped());
+
+        // 10% below the 1200 peak
+        let event = guard.update(1080.0, t0 + Duration::days(2)).unwrap();
+        assert!(matches!(event, BreakerEvent::Tripped { peak, .. } if peak == 1200.0));
+        assert!(guard.is_tripped());
+        assert_eq!(guard.resumes_at(), Some(t0 + Duration::days(3)));
+
+        // Still halted during the cool-off, even after a recovery
+        assert_eq!(guard.update(1150.0, t0 + Duration::hours(60)), None);
+        assert!(guard.is_tripped());
+        assert_eq!(
+            guard.update(1050.0, t0 + Duration::days(3)),
+            Some(BreakerEvent::Reset { equity: 1050.0 })
+        );
+        assert_eq!(guard.peak(), 1050.0);
+        assert_eq!(guard.update(1000.0, t0 + Duration::days(4)), None);
+    }
+}
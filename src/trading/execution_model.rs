@@ -0,0 +1,316 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::Arc;
+
+use crate::config::Config;
+
+/// Most price levels one order is split across; the last absorbs the rest
+const MAX_FILL_LEVELS: usize = 20;
+
+/// Which execution model prices simulated fills (env EXECUTION_MODEL).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecutionModelKind {
+    /// One fee rate and flat slippage for every order
+    #[default]
+    Flat,
+    /// Resting orders pay the maker fee without slippage
+    MakerTaker,
+}
+
+impl ExecutionModelKind {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "flat" => Some(ExecutionModelKind::Flat),
+            "maker_taker" => Some(ExecutionModelKind::MakerTaker),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ExecutionModelKind::Flat => "flat",
+            ExecutionModelKind::MakerTaker => "maker_taker",
+        }
+    }
+}
+
+/// Whether an order rested on the book or crossed the spread.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Liquidity {
+    /// Resting limit: limit entries and take-profit exits
+    Maker,
+    /// Market order: entries at signal, stops and forced exits
+    Taker,
+}
+
+/// An order about to be filled by the paper trader.
+#[derive(Debug, Clone, Copy)]
+pub struct Order {
+    /// Opens (true) or reduces (false) a position
+    pub entry: bool,
+    pub liquidity: Liquidity,
+    /// Base units
+    pub size: f64,
+    /// Volume of the bar the order fills in, when known
+    pub bar_volume: Option<f64>,
+}
+
+/// Cost rates an order fills at, as fractions of its notional.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FillCosts {
+    pub fee_rate: f64,
+    /// Adverse price move of the fill
+    pub slippage_rate: f64,
+}
+
+/// Prices simulated fills; injected into `PaperTrader`.
+pub trait ExecutionModel: fmt::Debug + Send + Sync {
+    fn costs(&self, order: &Order) -> FillCosts;
+
+    /// `(size, extra adverse move)` of each price level `order` fills at,
+    /// on top of `costs`. One level at the quoted price by default.
+    fn fill_levels(&self, order: &Order) -> Vec<(f64, f64)> {
+        vec![(order.size, 0.0)]
+    }
+}
+
+/// `FEE_RATE` on every order, `SLIPPAGE_RATE` on entries and
+/// `EXIT_SLIPPAGE_RATE` on exits.
+#[derive(Debug, Clone)]
+pub struct FlatExecution {
+    pub fee_rate: f64,
+    pub slippage_rate: f64,
+    pub exit_slippage_rate: f64,
+}
+
+impl ExecutionModel for FlatExecution {
+    fn costs(&self, order: &Order) -> FillCosts {
+        FillCosts {
+            fee_rate: self.fee_rate,
+            slippage_rate: if order.entry {
+                self.slippage_rate
+            } else {
+                self.exit_slippage_rate
+            },
+        }
+    }
+}
+
+/// Maker orders pay `maker_fee_rate` and fill at their price; taker
+/// orders pay `FEE_RATE` plus flat slippage.
+#[derive(Debug, Clone)]
+pub struct MakerTakerExecution {
+    pub maker_fee_rate: f64,
+    pub taker: FlatExecution,
+}
+
+impl ExecutionModel for MakerTakerExecution {
+    fn costs(&self, order: &Order) -> FillCosts {
+        match order.liquidity {
+            Liquidity::Maker => FillCosts {
+                fee_rate: self.maker_fee_rate,
+                slippage_rate: 0.0,
+            },
+            Liquidity::Taker => self.taker.costs(order),
+        }
+    }
+}
+
+/// Adds square-root market impact to taker orders:
+/// `coefficient * sqrt(size / bar volume)` on top of the inner model.
+#[derive(Debug)]
+pub struct SqrtImpact {
+    pub inner: Box<dyn ExecutionModel>,
+    pub coefficient: f64,
+}
+
+impl ExecutionModel for SqrtImpact {
+    fn costs(&self, order: &Order) -> FillCosts {
+        let mut costs = self.inner.costs(order);
+        if order.liquidity == Liquidity::Taker {
+            if let Some(volume) = order.bar_volume.filter(|v| *v > 0.0) {
+                costs.slippage_rate += self.coefficient * (order.size / volume).sqrt();
+            }
+        }
+        costs
+    }
+
+    fn fill_levels(&self, order: &Order) -> Vec<(f64, f64)> {
+        self.inner.fill_levels(order)
+    }
+}
+
+/// Splits taker entries larger than `max_share` of the bar's volume into
+/// levels of that size, each `step` further from the quoted price.
+#[derive(Debug)]
+pub struct PartialFills {
+    pub inner: Box<dyn ExecutionModel>,
+    pub max_share: f64,
+    pub step: f64,
+}
+
+impl ExecutionModel for PartialFills {
+    fn costs(&self, order: &Order) -> FillCosts {
+        self.inner.costs(order)
+    }
+
+    fn fill_levels(&self, order: &Order) -> Vec<(f64, f64)> {
+        let level_size = order.bar_volume.map_or(0.0, |v| v * self.max_share);
+        if !order.entry || order.liquidity != Liquidity::Taker || level_size <= 0.0 {
+            return self.inner.fill_levels(order);
+        }
+        // Full levels, then whatever is left on the last one
+        let n = ((order.size / level_size - 1e-9).ceil() as usize).clamp(1, MAX_FILL_LEVELS);
+        (0..n)
+            .map(|i| {
+                let size = if i + 1 == n {
+                    order.size - level_size * i as f64
+                } else {
+                    level_size
+                };
+                (size, self.step * i as f64)
+            })
+            .collect()
+    }
+}
+
+/// The model `cfg` selects (EXECUTION_MODEL, MAKER_FEE_RATE,
+/// IMPACT_COEFFICIENT, PARTIAL_FILL_PCT).
+pub fn from_config(cfg: &Config) -> Arc<dyn ExecutionModel> {
+    let flat = FlatExecution {
+        fee_rate: cfg.fee_rate,
+        slippage_rate: cfg.slippage_rate,
+        exit_slippage_rate: cfg.exit_slippage_rate,
+    };
+    let mut model: Box<dyn ExecutionModel> = match cfg.execution_model {
+        ExecutionModelKind::Flat => Box::new(flat),
+        ExecutionModelKind::MakerTaker => Box::new(MakerTakerExecution {
+            maker_fee_rate: cfg.maker_fee_rate,
+            taker: flat,
+        }),
+    };
+    if cfg.impact_coefficient > 0.0 {
+        model = Box::new(SqrtImpact {
+            inner: model,
+            coefficient: cfg.impact_coefficient,
+        });
+    }
+    if cfg.partial_fill_pct > 0.0 {
+        model = Box::new(PartialFills {
+            inner: model,
+            max_share: cfg.partial_fill_pct,
+            step: cfg.partial_fill_step,
+        });
+    }
+    Arc::from(model)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::default_test_config;
+
+    #[test]
+    fn maker_taker_and_impact_costs() {
+        let mut cfg = default_test_config();
+        cfg.fee_rate = 0.006;
+        cfg.maker_fee_rate = 0.004;
+        cfg.slippage_rate = 0.0005;
+        cfg.exit_slippage_rate = 0.001;
+        cfg.execution_model = ExecutionModelKind::MakerTaker;
+        cfg.impact_coefficient = 0.1;
+        let model = from_config(&cfg);
+        let order = |entry, liquidity, size| Order {
+            entry,
+            liquidity,
+            size,
+            bar_volume: Some(100.0),
+        };
+
+        let maker = model.costs(&order(true, Liquidity::Maker, 25.0));
+        assert_eq!(
+            maker,
+            FillCosts {
+                fee_rate: 0.004,
+                slippage_rate: 0.0
+            }
+        );
+        // 25% of the bar: 0.1 * sqrt(0.25) = 5% impact on top of slippage
+        let taker = model.costs(&order(true, Liquidity::Taker, 25.0));
+        assert_eq!(taker.fee_rate, 0.006);
+        assert!((taker.slippage_rate - 0.0505).abs() < 1e-12);
+        let exit = model.costs(&order(false, Liquidity::Taker, 1.0));
+        assert!((exit.slippage_rate - 0.011).abs() < 1e-12);
+
+        // Without volume the impact is unknown and left out
+        let blind = model.costs(&Order {
+            bar_volume: None,
+            ..order(true, Liquidity::Taker, 25.0)
+        });
+        assert_eq!(blind.slippage_rate, 0.0005);
+
+        cfg.execution_model = ExecutionModelKind::Flat;
+        cfg.impact_coefficient = 0.0;
+        let flat = from_config(&cfg).costs(&order(true, Liquidity::Maker, 25.0));
+        assert_eq!(
+            flat,
+            FillCosts {
+                fee_rate: 0.006,
+                slippage_rate: 0.0005
+            }
+        );
+    }
+
+    #[test]
+    fn partial_fills_split_taker_entries_into_levels() {
+        let model = PartialFills {
+            inner: Box::new(FlatExecution {
+                fee_rate: 0.006,
+                slippage_rate: 0.0005,
+                exit_slippage_rate: 0.001,
+            }),
+            max_share: 0.1,
+            step: 0.001,
+        };
+        let order = |entry, liquidity, size| Order {
+            entry,
+            liquidity,
+            size,
+            bar_volume: Some(10.0),
+        };
+
+        // 2.5 against 1.0 per level: two full levels, the rest on the third
+        let levels = model.fill_levels(&order(true, Liquidity::Taker, 2.5));
+        assert_eq!(levels.len(), 3);
+        assert_eq!(&levels[..2], &[(1.0, 0.0), (1.0, 0.001)]);
+        assert!((levels[2].0 - 0.5).abs() < 1e-12);
+        assert!((levels[2].1 - 0.002).abs() < 1e-12);
+
+        // An exact multiple does not leave an empty last level
+        assert_eq!(
+            model.fill_levels(&order(true, Liquidity::Taker, 2.0)).len(),
+            2
+        );
+
+        // Clamped at MAX_FILL_LEVELS; the last level absorbs the rest
+        let levels = model.fill_levels(&order(true, Liquidity::Taker, 50.0));
+        assert_eq!(levels.len(), MAX_FILL_LEVELS);
+        let last = levels[MAX_FILL_LEVELS - 1];
+        assert!((last.0 - (50.0 - (MAX_FILL_LEVELS - 1) as f64)).abs() < 1e-9);
+        let total: f64 = levels.iter().map(|(size, _)| size).sum();
+        assert!((total - 50.0).abs() < 1e-9);
+
+        // Exits, maker orders and unknown volume fill at one level
+        for o in [
+            order(false, Liquidity::Taker, 2.5),
+            order(true, Liquidity::Maker, 2.5),
+            Order {
+                bar_volume: None,
+                ..order(true, Liquidity::Taker, 2.5)
+            },
+        ] {
+            assert_eq!(model.fill_levels(&o), vec![(2.5, 0.0)]);
+        }
+    }
+}
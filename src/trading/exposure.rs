@@ -0,0 +1,96 @@
+use crate::config::Config;
+use crate::models::{Direction, PositionStatus};
+use crate::trading::paper_trader::Position;
+
+/// Notional (USD at entry price, remaining size) of working positions by
+/// side, summed across symbols and scales: crypto majors move together,
+/// so a long on each is one larger long.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Exposure {
+    pub long_usd: f64,
+    pub short_usd: f64,
+}
+
+impl Exposure {
+    /// Open positions and resting limits (they fill without asking again).
+    pub fn of<'a>(positions: impl IntoIterator<Item = &'a Position>) -> Self {
+        let mut exposure = Self::default();
+        for p in positions
+            .into_iter()
+            .filter(|p| matches!(p.status, PositionStatus::Open | PositionStatus::Pending))
+        {
+            exposure.add(p.direction, p.remaining_size_btc * p.entry_price);
+        }
+        exposure
+    }
+
+    fn add(&mut self, direction: Direction, notional: f64) {
+        match direction {
+            Direction::Long => self.long_usd += notional,
+            Direction::Short => self.short_usd += notional,
+        }
+    }
+
+    /// Positive = net long.
+    pub fn net(&self) -> f64 {
+        self.long_usd - self.short_usd
+    }
+}
+
+/// Caps on net directional exposure, as multiples of balance; 0 = no cap.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ExposureLimits {
+    pub max_net_long: f64,
+    pub max_net_short: f64,
+}
+
+impl ExposureLimits {
+    pub fn from_config(cfg: &Config) -> Self {
+        Self {
+            max_net_long: cfg.max_net_long_exposure,
+            max_net_short: cfg.max_net_short_exposure,
+        }
+    }
+
+    /// Whether adding `size_usd` in `direction` to `current` keeps the net
+    /// within the cap on that side. Entries that reduce the net always pass.
+    pub fn allows(
+        &self,
+        current: &Exposure,
+        direction: Direction,
+        size_usd: f64,
+        balance: f64,
+    ) -> bool {
+        let mut after = *current;
+        after.add(direction, size_usd);
+        let (net, cap) = match direction {
+            Direction::Long => (after.net(), self.max_net_long),
+            Direction::Short => (-after.net(), self.max_net_short),
+        };
+        cap <= 0.0 || net <= cap * balance
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::default_test_config;
+
+    #[test]
+    fn net_exposure_caps_each_side() {
+        let mut cfg = default_test_config();
+        cfg.max_net_long_exposure = 2.0;
+        let limits = ExposureLimits::from_config(&cfg);
+        let current = Exposure {
+            long_usd: 1500.0,
+            short_usd: 200.0,
+        };
+        assert_eq!(current.net(), 1300.0);
+
+        // Balance 1000: net long may reach 2000
+        assert!(limits.allows(&current, Direction::Long, 700.0, 1000.0));
+        assert!(!limits.allows(&current, Direction::Long, 701.0, 1000.0));
+        // Shorts reduce the net and the short side is uncapped
+        assert!(limits.allows(&current, Direction::Short, 5000.0, 1000.0));
+    }
+}
@@ -0,0 +1,244 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+use crate::config::Config;
+use crate::models::{CloseReason, Direction};
+use crate::strategies::signals::TradeSignal;
+use crate::trading::paper_trader::{PartialExit, Position, StopAdjustReason};
+use crate::trading::strategy_refiner::Adjustment;
+
+/// A trade lifecycle step or refiner change, as one journal line.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum JournalEvent {
+    /// Signal that passed every filter
+    Signal {
+        symbol: String,
+        scale: String,
+        direction: Direction,
+        entry_price: f64,
+        stop_loss: f64,
+        take_profit: f64,
+        confidence: f64,
+        session: String,
+    },
+    Entry {
+        position_id: u64,
+        symbol: String,
+        scale: String,
+        direction: Direction,
+        entry_price: f64,
+        stop_loss: f64,
+        take_profit: f64,
+        size_usd: f64,
+    },
+    Partial {
+        position_id: u64,
+        /// SD level of the target
+        level: f64,
+        price: f64,
+        size_btc: f64,
+        pnl: f64,
+    },
+    /// Stop moved by the trail, news, stuck or manual rules
+    Trail {
+        position_id: u64,
+        old_stop: f64,
+        new_stop: f64,
+        reason: StopAdjustReason,
+    },
+    Close {
+        position_id: u64,
+        symbol: String,
+        scale: String,
+        direction: Direction,
+        entry_price: f64,
+        exit_price: Option<f64>,
+        pnl: f64,
+        r_multiple: Option<f64>,
+        close_reason: Option<CloseReason>,
+    },
+    /// Parameter changed by the strategy refiner
+    Adjustment {
+        parameter: String,
+        old_value: f64,
+        new_value: f64,
+        reason: String,
+    },
+}
+
+impl JournalEvent {
+    pub fn signal(symbol: &str, scale: &str, signal: &TradeSignal) -> Self {
+        JournalEvent::Signal {
+            symbol: symbol.to_string(),
+            scale: scale.to_string(),
+            direction: signal.direction,
+            entry_price: signal.entry_price,
+            stop_loss: signal.stop_loss,
+            take_profit: signal.take_profit,
+            confidence: signal.confidence,
+            session: signal.session.clone(),
+        }
+    }
+
+    pub fn entry(pos: &Position) -> Self {
+        JournalEvent::Entry {
+            position_id: pos.id,
+            symbol: pos.symbol.clone(),
+            scale: pos.scale.clone(),
+            direction: pos.direction,
+            entry_price: pos.entry_price,
+            stop_loss: pos.stop_loss,
+            take_profit: pos.take_profit,
+            size_usd: pos.size_usd,
+        }
+    }
+
+    pub fn partial(position_id: u64, exit: &PartialExit) -> Self {
+        JournalEvent::Partial {
+            position_id,
+            level: exit.level,
+            price: exit.price,
+            size_btc: exit.size_btc,
+            pnl: exit.pnl,
+        }
+    }
+
+    pub fn trail(position_id: u64, old_stop: f64, new_stop: f64, reason: StopAdjustReason) -> Self {
+        JournalEvent::Trail {
+            position_id,
+            old_stop,
+            new_stop,
+            reason,
+        }
+    }
+
+    pub fn close(pos: &Position) -> Self {
+        JournalEvent::Close {
+            position_id: pos.id,
+            symbol: pos.symbol.clone(),
+            scale: pos.scale.clone(),
+            direction: pos.direction,
+            entry_price: pos.entry_price,
+            exit_price: pos.exit_price,
+            pnl: pos.pnl,
+            r_multiple: pos.r_multiple,
+            close_reason: pos.close_reason,
+        }
+    }
+
+    pub fn adjustment(adj: &Adjustment) -> Self {
+        JournalEvent::Adjustment {
+            parameter: adj.parameter.clone(),
+            old_value: adj.old_value,
+            new_value: adj.new_value,
+            reason: adj.reason.clone(),
+        }
+    }
+}
+
+/// One line of the journal file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JournalEntry {
+    pub time: DateTime<Utc>,
+    #[serde(flatten)]
+    pub event: JournalEvent,
+}
+
+/// Append-only NDJSON event journal (`{log_dir}/events.ndjson` for the bot).
+/// Write failures are logged and never interrupt trading.
+#[derive(Debug, Clone, Default)]
+pub struct Journal {
+    path: Option<PathBuf>,
+}
+
+impl Journal {
+    pub fn new(cfg: &Config) -> Self {
+        Self::at(format!("{}/events.ndjson", cfg.log_dir))
+    }
+
+    pub fn at(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: Some(path.into()),
+        }
+    }
+
+    /// A journal that records nothing (optimizer and walk-forward runs).
+    pub fn disabled() -> Self {
+        Self::default()
+    }
+
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    pub fn record(&self, time: DateTime<Utc>, event: JournalEvent) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        if let Err(e) = append(path, &JournalEntry { time, event }) {
+            warn!("Failed to write event journal: {:#}", e);
+        }
+    }
+
+    /// Every readable entry, oldest first.
+    pub fn load(path: &Path) -> Vec<JournalEntry> {
+        fs::read_to_string(path)
+            .unwrap_or_default()
+            .lines()
+            .filter_map(|l| serde_json::from_str(l).ok())
+            .collect()
+    }
+}
+
+fn append(path: &Path, entry: &JournalEntry) -> anyhow::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", serde_json::to_string(entry)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn appends_tagged_events_one_per_line() {
+        let dir = std::env::temp_dir().join(format!("ict_journal_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let path = dir.join("events.ndjson");
+        let journal = Journal::at(&path);
+        let t = Utc::now();
+
+        journal.record(
+            t,
+            JournalEvent::trail(7, 99.0, 99.5, StopAdjustReason::Trail),
+        );
+        let adj = Adjustment {
+            parameter: "min_confidence".to_string(),
+            old_value: 0.5,
+            new_value: 0.52,
+            reason: "low edge".to_string(),
+            edge: -0.1,
+            sample_size: 20,
+            timestamp: String::new(),
+        };
+        journal.record(t, JournalEvent::adjustment(&adj));
+        Journal::disabled().record(t, JournalEvent::adjustment(&adj));
+
+        let raw = fs::read_to_string(&path).unwrap();
+        let first: serde_json::Value = serde_json::from_str(raw.lines().next().unwrap()).unwrap();
+        assert_eq!(first["event"], "trail");
+        assert_eq!(first["reason"], "trail");
+        let entries = Journal::load(&path);
+        assert_eq!(entries.len(), 2);
+        assert!(matches!(entries[1].event, JournalEvent::Adjustment { .. }));
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,68 @@
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use crate::config::Config;
+
+/// Operational kill switch. Engaged when the sentinel file exists,
+/// KILL_SWITCH=true is set at startup, or `engage()` is called on any clone.
+#[derive(Debug, Clone)]
+pub struct KillSwitch {
+    file: PathBuf,
+    flag: Arc<AtomicBool>,
+    pub flatten: bool,
+}
+
+impl KillSwitch {
+    pub fn new(cfg: &Config) -> Self {
+        Self {
+            file: PathBuf::from(&cfg.kill_switch_file),
+            flag: Arc::new(AtomicBool::new(cfg.kill_switch)),
+            flatten: cfg.kill_switch_flatten,
+        }
+    }
+
+    pub fn file(&self) -> &Path {
+        &self.file
+    }
+
+    pub fn engage(&self) {
+        self.flag.store(true, Ordering::SeqCst);
+    }
+
+    pub fn release(&self) {
+        self.flag.store(false, Ordering::SeqCst);
+    }
+
+    pub fn is_engaged(&self) -> bool {
+        self.flag.load(Ordering::SeqCst) || self.file.exists()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::default_test_config;
+
+    #[test]
+    fn file_and_flag_engage() {
+        let mut cfg = default_test_config();
+        let path = std::env::temp_dir().join(format!("ict_bot_kill_{}", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        cfg.kill_switch_file = path.to_string_lossy().to_string();
+
+        let ks = KillSwitch::new(&cfg);
+        assert!(!ks.is_engaged());
+
+        std::fs::write(&path, "").unwrap();
+        assert!(ks.is_engaged());
+        std::fs::remove_file(&path).unwrap();
+        assert!(!ks.is_engaged());
+
+        let handle = ks.clone();
+        handle.engage();
+        assert!(ks.is_engaged());
+        handle.release();
+        assert!(!ks.is_engaged());
+    }
+}
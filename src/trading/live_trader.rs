@@ -0,0 +1,1103 @@
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::fs;
+use std::time::{Duration, Instant};
+use tracing::{error, info, warn};
+
+use crate::config::Config;
+use crate::exchange::{
+    CoinbaseClient, OrderApi, OrderRejected, OrderSide, OrderState, OrderStatus,
+};
+use crate::models::{CloseReason, Direction, PositionStatus};
+use crate::strategies::signals::TradeSignal;
+use crate::trading::paper_trader::{PaperTrader, Position, StopAdjustReason};
+use crate::trading::trade_record::TradeMetadata;
+
+const FILL_POLL_INTERVAL: Duration = Duration::from_millis(500);
+/// Reads of a cancelled order's final state before it counts as unknown
+const SETTLE_ATTEMPTS: usize = 3;
+
+/// Why a market order gave no usable fill.
+#[derive(Debug, thiserror::Error)]
+enum FillError {
+    /// The venue confirmed nothing filled
+    #[error("{0:#}")]
+    Unfilled(anyhow::Error),
+    /// It may have filled; `order_id` is known when the venue accepted it
+    #[error("{err:#}")]
+    Unknown {
+        order_id: Option<String>,
+        err: anyhow::Error,
+    },
+}
+
+/// Real order execution on top of the `PaperTrader` ledger.
+///
+/// Mirrors the ledger's open / check / close / update_stop calls: the ledger
+/// still decides sizing, partial TPs and exits, and every change is mirrored
+/// with market orders plus a resting stop per position. Fills are polled and
+/// the ledger is reconciled to actual prices and sizes.
+pub struct LiveTrader {
+    apis: HashMap<String, Box<dyn OrderApi>>,
+    /// Position id -> resting stop order id
+    stops: HashMap<u64, String>,
+    orders_file: String,
+    /// Position id -> entry order whose fill is not yet confirmed
+    pending: HashMap<u64, String>,
+    pending_file: String,
+    fill_timeout: Duration,
+    /// Problems the operator must look at, since the last `take_alerts`
+    alerts: Vec<String>,
+}
+
+impl LiveTrader {
+    pub fn new(cfg: &Config, apis: HashMap<String, Box<dyn OrderApi>>) -> Self {
+        let orders_file = format!("{}/live_orders.json", cfg.log_dir);
+        let pending_file = format!("{}/live_pending.json", cfg.log_dir);
+        let load = |path: &str| -> HashMap<u64, String> {
+            fs::read_to_string(path)
+                .ok()
+                .and_then(|s| serde_json::from_str(&s).ok())
+                .unwrap_or_default()
+        };
+        Self {
+            apis,
+            stops: load(&orders_file),
+            orders_file,
+            pending: load(&pending_file),
+            pending_file,
+            fill_timeout: Duration::from_secs(cfg.live_fill_timeout_secs),
+            alerts: Vec::new(),
+        }
+    }
+
+    /// Coinbase Advanced Trade execution for every configured symbol.
+    pub fn coinbase(cfg: &Config) -> Self {
+        let apis = cfg
+            .symbols
+            .iter()
+            .map(|s| {
+                let api: Box<dyn OrderApi> = Box::new(CoinbaseClient::new(&cfg.for_symbol(s)));
+                (s.clone(), api)
+            })
+            .collect();
+        Self::new(cfg, apis)
+    }
+
+    /// Stop order currently protecting a position.
+    pub fn stop_order(&self, id: u64) -> Option<&str> {
+        self.stops.get(&id).map(String::as_str)
+    }
+
+    /// Entry order of a position whose fill is still unconfirmed.
+    pub fn pending_entry(&self, id: u64) -> Option<&str> {
+        self.pending.get(&id).map(String::as_str)
+    }
+
+    /// Alerts raised since the last call, for the notifiers.
+    pub fn take_alerts(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.alerts)
+    }
+
+    /// Open via the ledger, send a market entry, reconcile the fill and rest a stop.
+    ///
+    /// The ledger position is only dropped when the venue confirms nothing
+    /// filled. If the order's fate is unknown it stays in the ledger, the
+    /// order is re-read on every `check_positions` and an alert is raised.
+    pub async fn open_position(
+        &mut self,
+        ledger: &mut PaperTrader,
+        symbol: &str,
+        signal: &TradeSignal,
+        scale: &str,
+        metadata: Option<TradeMetadata>,
+    ) -> Result<Option<Position>> {
+        let Some(pos) = ledger
+            .open_position_for(symbol, signal, scale, metadata)
+            .cloned()
+        else {
+            return Ok(None);
+        };
+
+        let fill = match self
+            .market_fill(symbol, OrderSide::entry(pos.direction), pos.size_btc)
+            .await
+        {
+            Ok(f) => f,
+            Err(FillError::Unfilled(e)) => {
+                ledger.discard_position(pos.id);
+                return Err(e.context(format!("entry for #{} failed", pos.id)));
+            }
+            Err(FillError::Unknown { order_id, err }) => {
+                let follow_up = match &order_id {
+                    Some(o) => format!("re-checking order {}", o),
+                    None => "no order id, reconcile by hand".to_string(),
+                };
+                self.alert(format!(
+                    "LIVE #{} {} entry state unknown, kept in the ledger without a stop ({}): {:#}",
+                    pos.id, symbol, follow_up, err
+                ));
+                if let Some(order_id) = order_id {
+                    self.pending.insert(pos.id, order_id);
+                    self.save();
+                }
+                return Ok(ledger.position(pos.id).cloned());
+            }
+        };
+        self.protect_entry(ledger, symbol, &pos, &fill).await?;
+        Ok(ledger.position(pos.id).cloned())
+    }
+
+    /// Reconcile an entry fill into the ledger and rest its stop,
+    /// flattening if the stop can't be placed.
+    async fn protect_entry(
+        &mut self,
+        ledger: &mut PaperTrader,
+        symbol: &str,
+        pos: &Position,
+        fill: &OrderStatus,
+    ) -> Result<()> {
+        ledger.reconcile_entry(pos.id, fill.avg_price, fill.filled_size);
+        info!(
+            "LIVE #{} filled {:.8} {} @ ${:.2} (signal ${:.2})",
+            pos.id, fill.filled_size, symbol, fill.avg_price, pos.signal_price
+        );
+
+        if let Err(e) = self
+            .place_stop(
+                symbol,
+                pos.id,
+                pos.direction,
+                fill.filled_size,
+                pos.stop_loss,
+            )
+            .await
+        {
+            // Never leave an unprotected position on the venue
+            warn!("LIVE #{} stop placement failed ({}), flattening", pos.id, e);
+            self.flatten(ledger, symbol, pos.id, CloseReason::DisasterStop)
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Sync exchange stop fills, then run the ledger's exit logic at `price`
+    /// and mirror any reductions (partial TPs, time exits) with market orders.
+    pub async fn check_positions(
+        &mut self,
+        ledger: &mut PaperTrader,
+        symbol: &str,
+        price: f64,
+    ) -> Result<Vec<Position>> {
+        let mut closed = Vec::new();
+
+        // 0. Entries whose fill was never confirmed
+        let pending: Vec<(u64, String)> = ledger
+            .open_positions_for(symbol)
+            .filter_map(|p| self.pending.get(&p.id).map(|o| (p.id, o.clone())))
+            .collect();
+        for (id, order_id) in pending {
+            let status = match self.api(symbol)?.order_status(&order_id).await {
+                Ok(status) => status,
+                Err(e) => {
+                    warn!("LIVE #{} entry {} still unknown: {:#}", id, order_id, e);
+                    continue;
+                }
+            };
+            match status.state {
+                OrderState::Filled | OrderState::Cancelled | OrderState::Failed
+                    if status.filled_size > 0.0 =>
+                {
+                    self.forget_pending(id);
+                    let Some(pos) = ledger.position(id).cloned() else {
+                        continue;
+                    };
+                    warn!("LIVE #{} entry {} settled after all", id, order_id);
+                    self.protect_entry(ledger, symbol, &pos, &status).await?;
+                }
+                OrderState::Cancelled | OrderState::Failed => {
+                    self.forget_pending(id);
+                    ledger.discard_position(id);
+                    self.alert(format!(
+                        "LIVE #{} entry {} never filled, dropped from the ledger",
+                        id, order_id
+                    ));
+                }
+                OrderState::Filled | OrderState::Open => {}
+            }
+        }
+
+        // 1. Stops filled on the venue
+        let open: Vec<(u64, Direction, f64, f64)> = ledger
+            .open_positions_for(symbol)
+            .map(|p| (p.id, p.direction, p.remaining_size_btc, p.stop_loss))
+            .collect();
+        for &(id, direction, remaining, stop_loss) in &open {
+            let Some(order_id) = self.stops.get(&id).cloned() else {
+                continue;
+            };
+            let status = self.api(symbol)?.order_status(&order_id).await?;
+            match status.state {
+                OrderState::Filled => {
+                    self.forget_stop(id);
+                    let reason = ledger
+                        .position(id)
+                        .map_or(CloseReason::StopLoss, |p| p.stop_reason());
+                    if let Some(pos) = ledger.close_position_at(id, status.avg_price, reason) {
+                        info!("LIVE #{} stop filled @ ${:.2}", id, status.avg_price);
+                        closed.push(pos);
+                    }
+                }
+                OrderState::Cancelled | OrderState::Failed => {
+                    warn!(
+                        "LIVE #{} stop {} no longer resting, re-placing",
+                        id, order_id
+                    );
+                    self.forget_stop(id);
+                    self.place_stop(symbol, id, direction, remaining, stop_loss)
+                        .await?;
+                }
+                OrderState::Open => {}
+            }
+        }
+
+        // 2. Ledger exits at the current price
+        let before: HashMap<u64, f64> = ledger
+            .open_positions_for(symbol)
+            .map(|p| (p.id, p.remaining_size_btc))
+            .collect();
+        closed.extend(ledger.check_symbol_positions(symbol, price));
+
+        // 3. Mirror reductions on the venue; one failure doesn't stop the rest
+        let mut ids: Vec<u64> = before.keys().copied().collect();
+        ids.sort_unstable();
+        for id in ids {
+            let Some(pos) = ledger.position(id).cloned() else {
+                continue;
+            };
+            if self.pending.contains_key(&id) {
+                self.alert(format!(
+                    "LIVE #{} ledger exit before its entry was confirmed, not mirrored",
+                    id
+                ));
+                continue;
+            }
+            match self
+                .mirror_reduce(ledger, symbol, &pos, before[&id], price)
+                .await
+            {
+                Ok(stopped) => closed.extend(stopped),
+                Err(e) => self.alert(format!("LIVE #{} {} reduce failed: {:#}", id, symbol, e)),
+            }
+        }
+
+        Ok(closed)
+    }
+
+    /// Send the market reduce the ledger booked for `pos` since it held
+    /// `before`, then re-rest the stop for what the venue still holds.
+    /// Returns the position if its stop filled first.
+    async fn mirror_reduce(
+        &mut self,
+        ledger: &mut PaperTrader,
+        symbol: &str,
+        pos: &Position,
+        before: f64,
+        price: f64,
+    ) -> Result<Option<Position>> {
+        let id = pos.id;
+        let still_open = pos.status == PositionStatus::Open;
+        let after = if still_open {
+            pos.remaining_size_btc
+        } else {
+            0.0
+        };
+        let reduce = before - after;
+        if reduce <= 0.0 {
+            return Ok(None);
+        }
+
+        if let Some(stop_fill) = self.cancel_stop(symbol, id).await? {
+            // Stop filled before we could cancel: venue is already flat
+            self.forget_stop(id);
+            if !still_open {
+                return Ok(None);
+            }
+            return Ok(ledger.close_position_at(id, stop_fill.avg_price, pos.stop_reason()));
+        }
+        let booked_price = if still_open {
+            pos.partial_exits.last().map(|pe| pe.price).unwrap_or(price)
+        } else {
+            pos.exit_price.unwrap_or(price)
+        };
+        let held = match self
+            .market_fill(symbol, OrderSide::exit(pos.direction), reduce)
+            .await
+        {
+            Ok(fill) if fill.filled_size < reduce => {
+                // Part of the reduce never happened: the venue still holds it
+                ledger.adjust_pnl(id, exit_pnl_delta(pos.direction, booked_price, &fill));
+                if still_open {
+                    ledger.restore_partial_exit(id, reduce - fill.filled_size);
+                }
+                before - fill.filled_size
+            }
+            Ok(fill) => {
+                ledger.adjust_pnl(id, exit_pnl_delta(pos.direction, booked_price, &fill));
+                after
+            }
+            Err(e) => {
+                if still_open && matches!(e, FillError::Unfilled(_)) {
+                    ledger.restore_partial_exit(id, reduce);
+                }
+                // Whatever happened, keep the pre-reduce size protected
+                self.restore_stop(symbol, id, pos.direction, before, pos.stop_loss)
+                    .await;
+                return Err(e.into());
+            }
+        };
+
+        if held > 0.0 && !still_open {
+            self.alert(format!(
+                "LIVE #{} closed in the ledger but {:.8} {} is still open on the venue",
+                id, held, symbol
+            ));
+        }
+        if held > 0.0 {
+            self.restore_stop(symbol, id, pos.direction, held, pos.stop_loss)
+                .await;
+        }
+        Ok(None)
+    }
+
+    /// Move a stop in the ledger and replace the resting stop order.
+    ///
+    /// The new stop rests before the old one is cancelled, so the position
+    /// is never left without one.
+    pub async fn update_stop(
+        &mut self,
+        ledger: &mut PaperTrader,
+        symbol: &str,
+        id: u64,
+        price: f64,
+        reason: StopAdjustReason,
+    ) -> Result<Option<f64>> {
+        let Some(old) = ledger.update_stop(id, price, reason) else {
+            return Ok(None);
+        };
+        let Some(pos) = ledger.position(id).cloned() else {
+            return Ok(Some(old));
+        };
+        let new_order = self
+            .submit_stop(symbol, pos.direction, pos.remaining_size_btc, price)
+            .await?;
+        match self.cancel_stop(symbol, id).await {
+            Ok(None) => {}
+            Ok(Some(_)) => {
+                // Old stop already filled, so the venue is flat; the next
+                // check closes the position
+                self.cancel_or_alert(symbol, id, &new_order).await;
+                return Ok(Some(old));
+            }
+            Err(e) => {
+                // The old stop may still rest: keep it, drop the new one
+                self.cancel_or_alert(symbol, id, &new_order).await;
+                return Err(e.context(format!("replacing stop of #{}", id)));
+            }
+        }
+        self.stops.insert(id, new_order);
+        self.save();
+        Ok(Some(old))
+    }
+
+    /// Flatten every open position of `symbol` at market (kill switch).
+    pub async fn close_all(
+        &mut self,
+        ledger: &mut PaperTrader,
+        symbol: &str,
+    ) -> Result<Vec<Position>> {
+        let ids: Vec<u64> = ledger.open_positions_for(symbol).map(|p| p.id).collect();
+        let mut closed = Vec::new();
+        for id in ids {
+            if let Some(pos) = self
+                .flatten(ledger, symbol, id, CloseReason::Manual)
+                .await?
+            {
+                closed.push(pos);
+            }
+        }
+        Ok(closed)
+    }
+
+    /// Flatten one open position at market (manual close).
+    pub async fn close_position(
+        &mut self,
+        ledger: &mut PaperTrader,
+        symbol: &str,
+        id: u64,
+    ) -> Result<Option<Position>> {
+        self.flatten(ledger, symbol, id, CloseReason::Manual).await
+    }
+
+    /// Close a position at market. A partial fill only books what filled;
+    /// the rest stays open in the ledger behind a fresh stop.
+    async fn flatten(
+        &mut self,
+        ledger: &mut PaperTrader,
+        symbol: &str,
+        id: u64,
+        reason: CloseReason,
+    ) -> Result<Option<Position>> {
+        let Some(pos) = ledger.position(id).cloned() else {
+            return Ok(None);
+        };
+        if let Some(stop_fill) = self.cancel_stop(symbol, id).await? {
+            self.forget_stop(id);
+            return Ok(ledger.close_position_at(id, stop_fill.avg_price, pos.stop_reason()));
+        }
+        let remaining = pos.remaining_size_btc;
+        let fill = match self
+            .market_fill(symbol, OrderSide::exit(pos.direction), remaining)
+            .await
+        {
+            Ok(fill) => fill,
+            Err(e) => {
+                self.restore_stop(symbol, id, pos.direction, remaining, pos.stop_loss)
+                    .await;
+                return Err(e.into());
+            }
+        };
+        if fill.filled_size < remaining {
+            ledger.reduce_position_at(id, fill.filled_size, fill.avg_price);
+            let rest = ledger.position(id).map_or(0.0, |p| p.remaining_size_btc);
+            self.alert(format!(
+                "LIVE #{} close filled {:.8} of {:.8} {}, the rest stays open",
+                id, fill.filled_size, remaining, symbol
+            ));
+            if rest > 0.0 {
+                self.restore_stop(symbol, id, pos.direction, rest, pos.stop_loss)
+                    .await;
+            }
+            return Ok(None);
+        }
+        Ok(ledger.close_position_at(id, fill.avg_price, reason))
+    }
+
+    fn api(&mut self, symbol: &str) -> Result<&mut Box<dyn OrderApi>> {
+        self.apis
+            .get_mut(symbol)
+            .with_context(|| format!("no order API for {}", symbol))
+    }
+
+    async fn market_fill(
+        &mut self,
+        symbol: &str,
+        side: OrderSide,
+        size: f64,
+    ) -> Result<OrderStatus, FillError> {
+        let timeout = self.fill_timeout;
+        let api = self.api(symbol).map_err(FillError::Unfilled)?;
+        let order_id = match api.market_order(side, size).await {
+            Ok(order_id) => order_id,
+            Err(e) if e.is::<OrderRejected>() => return Err(FillError::Unfilled(e)),
+            // Sent, but the answer was lost: it may still fill
+            Err(err) => {
+                return Err(FillError::Unknown {
+                    order_id: None,
+                    err,
+                })
+            }
+        };
+        match wait_fill(api.as_mut(), &order_id, timeout).await {
+            Ok(status) => Ok(status),
+            Err(e) => settle_unfilled(api.as_mut(), &order_id, e).await,
+        }
+    }
+
+    async fn submit_stop(
+        &mut self,
+        symbol: &str,
+        direction: Direction,
+        size: f64,
+        stop_price: f64,
+    ) -> Result<String> {
+        self.api(symbol)?
+            .stop_order(OrderSide::exit(direction), size, stop_price)
+            .await
+    }
+
+    async fn place_stop(
+        &mut self,
+        symbol: &str,
+        id: u64,
+        direction: Direction,
+        size: f64,
+        stop_price: f64,
+    ) -> Result<()> {
+        let order_id = self
+            .submit_stop(symbol, direction, size, stop_price)
+            .await?;
+        self.stops.insert(id, order_id);
+        self.save();
+        Ok(())
+    }
+
+    /// Re-rest a stop after a failed or partial exit, alerting if even that fails.
+    async fn restore_stop(
+        &mut self,
+        symbol: &str,
+        id: u64,
+        direction: Direction,
+        size: f64,
+        stop_price: f64,
+    ) {
+        if let Err(e) = self
+            .place_stop(symbol, id, direction, size, stop_price)
+            .await
+        {
+            self.alert(format!(
+                "LIVE #{} {} has no stop on {:.8}: {:#}",
+                id, symbol, size, e
+            ));
+        }
+    }
+
+    /// Cancel a stop we no longer want, alerting if it may still rest.
+    async fn cancel_or_alert(&mut self, symbol: &str, id: u64, order_id: &str) {
+        let cancelled = match self.api(symbol) {
+            Ok(api) => api.cancel_order(order_id).await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = cancelled {
+            self.alert(format!(
+                "LIVE #{} extra stop {} may still rest: {:#}",
+                id, order_id, e
+            ));
+        }
+    }
+
+    /// Cancel a position's resting stop. If it had already filled, the stop
+    /// stays tracked and its fill is returned instead.
+    async fn cancel_stop(&mut self, symbol: &str, id: u64) -> Result<Option<OrderStatus>> {
+        let Some(order_id) = self.stops.get(&id).cloned() else {
+            return Ok(None);
+        };
+        let api = self.api(symbol)?;
+        api.cancel_order(&order_id).await?;
+        let status = api.order_status(&order_id).await?;
+        if status.state == OrderState::Filled {
+            return Ok(Some(status));
+        }
+        self.forget_stop(id);
+        Ok(None)
+    }
+
+    fn forget_stop(&mut self, id: u64) {
+        if self.stops.remove(&id).is_some() {
+            self.save();
+        }
+    }
+
+    fn forget_pending(&mut self, id: u64) {
+        if self.pending.remove(&id).is_some() {
+            self.save();
+        }
+    }
+
+    fn alert(&mut self, message: String) {
+        error!("{}", message);
+        self.alerts.push(message);
+    }
+
+    fn save(&self) {
+        if let Some(parent) = std::path::Path::new(&self.orders_file).parent() {
+            let _ = fs::create_dir_all(parent);
+        }
+        for (path, orders) in [
+            (&self.orders_file, &self.stops),
+            (&self.pending_file, &self.pending),
+        ] {
+            if let Ok(json) = serde_json::to_string_pretty(orders) {
+                let _ = fs::write(path, json);
+            }
+        }
+    }
+}
+
+/// Poll an order until it fills, failing on cancel/reject or timeout.
+async fn wait_fill(
+    api: &mut dyn OrderApi,
+    order_id: &str,
+    timeout: Duration,
+) -> Result<OrderStatus> {
+    let started = Instant::now();
+    loop {
+        let status = api.order_status(order_id).await?;
+        match status.state {
+            OrderState::Filled | OrderState::Cancelled | OrderState::Failed
+                if status.filled_size > 0.0 =>
+            {
+                return Ok(status)
+            }
+            OrderState::Cancelled | OrderState::Failed => {
+                anyhow::bail!("order {} {:?} without fill", order_id, status.state)
+            }
+            OrderState::Filled | OrderState::Open => {}
+        }
+        if started.elapsed() >= timeout {
+            anyhow::bail!("order {} not filled after {:?}", order_id, timeout);
+        }
+        tokio::time::sleep(FILL_POLL_INTERVAL).await;
+    }
+}
+
+/// Cancel an order `wait_fill` gave up on and re-read it, so a late or
+/// partial fill is still returned. `Unfilled` only once the venue shows it
+/// closed with nothing filled; otherwise its state is unknown.
+async fn settle_unfilled(
+    api: &mut dyn OrderApi,
+    order_id: &str,
+    err: anyhow::Error,
+) -> Result<OrderStatus, FillError> {
+    if let Err(e) = api.cancel_order(order_id).await {
+        warn!("Cancel of unfilled order {} failed: {:#}", order_id, e);
+    }
+    let mut last = String::new();
+    for attempt in 0..SETTLE_ATTEMPTS {
+        if attempt > 0 {
+            tokio::time::sleep(FILL_POLL_INTERVAL).await;
+        }
+        match api.order_status(order_id).await {
+            Ok(status) if status.filled_size > 0.0 && status.state != OrderState::Open => {
+                warn!(
+                    "Order {} settled {:?} with {:.8} filled ({:#})",
+                    order_id, status.state, status.filled_size, err
+                );
+                return Ok(status);
+            }
+            Ok(status) if matches!(status.state, OrderState::Cancelled | OrderState::Failed) => {
+                return Err(FillError::Unfilled(err));
+            }
+            Ok(status) => last = format!("still {:?}", status.state),
+            Err(e) => last = format!("{:#}", e),
+        }
+    }
+    Err(FillError::Unknown {
+        order_id: Some(order_id.to_string()),
+        err: err.context(format!("order {} state unknown ({})", order_id, last)),
+    })
+}
+
+/// PnL difference between the actual exit fill and the price the ledger booked.
+fn exit_pnl_delta(direction: Direction, booked_price: f64, fill: &OrderStatus) -> f64 {
+    if fill.avg_price <= 0.0 {
+        return 0.0;
+    }
+    let diff = match direction {
+        Direction::Long => fill.avg_price - booked_price,
+        Direction::Short => booked_price - fill.avg_price,
+    };
+    diff * fill.filled_size
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::{make_signal, unique_test_config};
+    use crate::trading::trade_record::TpLevelInfo;
+    use async_trait::async_trait;
+    use std::sync::{Arc, Mutex};
+
+    /// Venue stub: market orders fill instantly at `fill_price`, stops rest.
+    #[derive(Default)]
+    struct Venue {
+        fill_price: f64,
+        reject: bool,
+        /// Market orders are placed but the reply is lost
+        lost: bool,
+        /// Order status reads fail
+        status_down: bool,
+        stop_reject: bool,
+        /// Market orders rest open with only this much filled
+        partial: Option<f64>,
+        next_id: u64,
+        orders: HashMap<String, OrderStatus>,
+        market_sizes: Vec<(OrderSide, f64)>,
+        stops: Vec<(String, f64, f64)>,
+    }
+
+    struct MockApi(Arc<Mutex<Venue>>);
+
+    #[async_trait]
+    impl OrderApi for MockApi {
+        async fn market_order(&mut self, side: OrderSide, base_size: f64) -> Result<String> {
+            let mut v = self.0.lock().unwrap();
+            if v.reject {
+                return Err(OrderRejected("insufficient funds".into()).into());
+            }
+            v.next_id += 1;
+            let id = format!("m{}", v.next_id);
+            let status = OrderStatus {
+                state: if v.partial.is_some() {
+                    OrderState::Open
+                } else {
+                    OrderState::Filled
+                },
+                filled_size: v.partial.unwrap_or(base_size),
+                avg_price: v.fill_price,
+            };
+            v.orders.insert(id.clone(), status);
+            v.market_sizes.push((side, base_size));
+            if v.lost {
+                anyhow::bail!("request timed out");
+            }
+            Ok(id)
+        }
+
+        async fn limit_order(&mut self, side: OrderSide, base_size: f64, _: f64) -> Result<String> {
+            self.market_order(side, base_size).await
+        }
+
+        async fn stop_order(
+            &mut self,
+            _: OrderSide,
+            base_size: f64,
+            stop_price: f64,
+        ) -> Result<String> {
+            let mut v = self.0.lock().unwrap();
+            if v.stop_reject {
+                return Err(OrderRejected("stop price too close".into()).into());
+            }
+            v.next_id += 1;
+            let id = format!("s{}", v.next_id);
+            let status = OrderStatus {
+                state: OrderState::Open,
+                filled_size: 0.0,
+                avg_price: 0.0,
+            };
+            v.orders.insert(id.clone(), status);
+            v.stops.push((id.clone(), base_size, stop_price));
+            Ok(id)
+        }
+
+        async fn cancel_order(&mut self, order_id: &str) -> Result<()> {
+            let mut v = self.0.lock().unwrap();
+            if let Some(o) = v.orders.get_mut(order_id) {
+                if o.state == OrderState::Open {
+                    o.state = OrderState::Cancelled;
+                }
+            }
+            Ok(())
+        }
+
+        async fn order_status(&mut self, order_id: &str) -> Result<OrderStatus> {
+            let v = self.0.lock().unwrap();
+            if v.status_down {
+                anyhow::bail!("503 service unavailable");
+            }
+            v.orders.get(order_id).cloned().context("unknown order")
+        }
+    }
+
+    fn setup(fill_price: f64) -> (LiveTrader, PaperTrader, Arc<Mutex<Venue>>) {
+        // Own state dir so persisted stop ids never leak between tests
+        let cfg = unique_test_config();
+        let venue = Arc::new(Mutex::new(Venue {
+            fill_price,
+            ..Default::default()
+        }));
+        let api: Box<dyn OrderApi> = Box::new(MockApi(venue.clone()));
+        let live = LiveTrader::new(&cfg, HashMap::from([("BTC-USD".to_string(), api)]));
+        (live, PaperTrader::new_fresh(&cfg), venue)
+    }
+
+    fn long_signal() -> TradeSignal {
+        make_signal(Direction::Long, 50000.0, 49500.0, 51000.0)
+    }
+
+    /// Long with TP1 at 50500 and TP2 at 51000.
+    fn two_tp_signal() -> TradeSignal {
+        let mut signal = long_signal();
+        signal.tp_levels = Some(vec![
+            TpLevelInfo {
+                label: "TP1".into(),
+                price: 50500.0,
+                pda_confluence: false,
+                level: Some(-1.0),
+                volume_confluence: false,
+            },
+            TpLevelInfo {
+                label: "TP2".into(),
+                price: 51000.0,
+                pda_confluence: false,
+                level: Some(-2.0),
+                volume_confluence: false,
+            },
+        ]);
+        signal
+    }
+
+    #[tokio::test]
+    async fn entry_fill_is_reconciled_and_protected_by_stop() {
+        let (mut live, mut ledger, venue) = setup(50010.0);
+        let pos = live
+            .open_position(&mut ledger, "BTC-USD", &long_signal(), "5m", None)
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(pos.entry_price, 50010.0);
+        assert!(pos.entry_improvement_bps() < 0.0);
+        let v = venue.lock().unwrap();
+        assert_eq!(v.market_sizes[0].0, OrderSide::Buy);
+        assert_eq!(v.stops.len(), 1);
+        assert_eq!(v.stops[0].2, 49500.0);
+        assert_eq!(live.stop_order(pos.id), Some(v.stops[0].0.as_str()));
+    }
+
+    #[tokio::test]
+    async fn rejected_entry_is_discarded_from_ledger() {
+        let (mut live, mut ledger, venue) = setup(50000.0);
+        venue.lock().unwrap().reject = true;
+        let before = ledger.balance();
+
+        let res = live
+            .open_position(&mut ledger, "BTC-USD", &long_signal(), "5m", None)
+            .await;
+        assert!(res.is_err());
+        assert_eq!(ledger.open_positions().count(), 0);
+        assert!((ledger.balance() - before).abs() < 1e-9);
+    }
+
+    #[tokio::test]
+    async fn entry_open_at_timeout_is_cancelled_and_partial_fill_protected() {
+        let (mut live, mut ledger, venue) = setup(50000.0);
+        live.fill_timeout = Duration::ZERO;
+        venue.lock().unwrap().partial = Some(0.001);
+
+        let pos = live
+            .open_position(&mut ledger, "BTC-USD", &long_signal(), "5m", None)
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(pos.size_btc, 0.001);
+        assert_eq!(ledger.open_positions().count(), 1);
+        let v = venue.lock().unwrap();
+        assert_eq!(v.orders["m1"].state, OrderState::Cancelled);
+        assert_eq!(v.stops.len(), 1);
+        assert_eq!(v.stops[0].1, 0.001);
+    }
+
+    #[tokio::test]
+    async fn venue_stop_fill_closes_at_fill_price() {
+        let (mut live, mut ledger, venue) = setup(50000.0);
+        let pos = live
+            .open_position(&mut ledger, "BTC-USD", &long_signal(), "5m", None)
+            .await
+            .unwrap()
+            .unwrap();
+        {
+            let mut v = venue.lock().unwrap();
+            let stop_id = v.stops[0].0.clone();
+            let o = v.orders.get_mut(&stop_id).unwrap();
+            o.state = OrderState::Filled;
+            o.filled_size = pos.size_btc;
+            o.avg_price = 49480.0;
+        }
+
+        let closed = live
+            .check_positions(&mut ledger, "BTC-USD", 49490.0)
+            .await
+            .unwrap();
+        assert_eq!(closed.len(), 1);
+        assert_eq!(closed[0].exit_price, Some(49480.0));
+        assert!(live.stop_order(pos.id).is_none());
+    }
+
+    #[tokio::test]
+    async fn partial_tp_reduces_on_venue_and_resizes_stop() {
+        let (mut live, mut ledger, venue) = setup(50000.0);
+        let pos = live
+            .open_position(&mut ledger, "BTC-USD", &two_tp_signal(), "5m", None)
+            .await
+            .unwrap()
+            .unwrap();
+
+        venue.lock().unwrap().fill_price = 50600.0;
+        let closed = live
+            .check_positions(&mut ledger, "BTC-USD", 50600.0)
+            .await
+            .unwrap();
+        assert!(closed.is_empty());
+
+        let remaining = ledger.position(pos.id).unwrap().remaining_size_btc;
+        let v = venue.lock().unwrap();
+        let (side, reduced) = v.market_sizes[1];
+        assert_eq!(side, OrderSide::Sell);
+        assert!((reduced + remaining - pos.size_btc).abs() < 1e-8);
+        // Old stop cancelled, new one sized to the remainder
+        assert_eq!(v.stops.len(), 2);
+        assert!((v.stops[1].1 - remaining).abs() < 1e-12);
+        assert_eq!(v.orders[&v.stops[0].0].state, OrderState::Cancelled);
+    }
+
+    #[tokio::test]
+    async fn entry_in_unknown_state_is_kept_and_settled_later() {
+        let (mut live, mut ledger, venue) = setup(50000.0);
+        live.fill_timeout = Duration::ZERO;
+        venue.lock().unwrap().status_down = true;
+
+        let pos = live
+            .open_position(&mut ledger, "BTC-USD", &long_signal(), "5m", None)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(ledger.open_positions().count(), 1);
+        assert_eq!(live.pending_entry(pos.id), Some("m1"));
+        assert_eq!(live.take_alerts().len(), 1);
+        assert!(venue.lock().unwrap().stops.is_empty());
+
+        // The venue answers again: the fill is reconciled and protected
+        venue.lock().unwrap().status_down = false;
+        live.check_positions(&mut ledger, "BTC-USD", 50000.0)
+            .await
+            .unwrap();
+        assert_eq!(live.pending_entry(pos.id), None);
+        assert!(live.stop_order(pos.id).is_some());
+    }
+
+    #[tokio::test]
+    async fn lost_entry_reply_is_kept_not_discarded() {
+        let (mut live, mut ledger, venue) = setup(50000.0);
+        venue.lock().unwrap().lost = true;
+
+        let pos = live
+            .open_position(&mut ledger, "BTC-USD", &long_signal(), "5m", None)
+            .await
+            .unwrap();
+        assert!(pos.is_some());
+        assert_eq!(ledger.open_positions().count(), 1);
+        assert_eq!(live.take_alerts().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn failed_reduce_keeps_the_full_size_protected() {
+        let (mut live, mut ledger, venue) = setup(50000.0);
+        let pos = live
+            .open_position(&mut ledger, "BTC-USD", &two_tp_signal(), "5m", None)
+            .await
+            .unwrap()
+            .unwrap();
+        let balance = ledger.balance();
+
+        venue.lock().unwrap().reject = true;
+        live.check_positions(&mut ledger, "BTC-USD", 50600.0)
+            .await
+            .unwrap();
+
+        // Nothing reduced on the venue, so the ledger books nothing either
+        let p = ledger.position(pos.id).unwrap();
+        assert_eq!(p.remaining_size_btc, pos.size_btc);
+        assert!((ledger.balance() - balance).abs() < 0.01);
+        let v = venue.lock().unwrap();
+        assert_eq!(v.stops.len(), 2);
+        assert_eq!(v.stops[1].1, pos.size_btc);
+        assert_eq!(live.stop_order(pos.id), Some(v.stops[1].0.as_str()));
+        assert_eq!(live.take_alerts().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn partial_reduce_fill_resizes_ledger_and_stop_to_the_venue() {
+        let (mut live, mut ledger, venue) = setup(50000.0);
+        let pos = live
+            .open_position(&mut ledger, "BTC-USD", &two_tp_signal(), "5m", None)
+            .await
+            .unwrap()
+            .unwrap();
+
+        live.fill_timeout = Duration::ZERO;
+        let filled = 0.0001;
+        {
+            let mut v = venue.lock().unwrap();
+            v.fill_price = 50600.0;
+            v.partial = Some(filled);
+        }
+        live.check_positions(&mut ledger, "BTC-USD", 50600.0)
+            .await
+            .unwrap();
+
+        let held = pos.size_btc - filled;
+        let p = ledger.position(pos.id).unwrap();
+        assert!((p.remaining_size_btc - held).abs() < 1e-8);
+        assert!((p.partial_exits[0].size_btc - filled).abs() < 1e-8);
+        let v = venue.lock().unwrap();
+        assert!((v.stops.last().unwrap().1 - held).abs() < 1e-8);
+    }
+
+    #[tokio::test]
+    async fn partial_close_books_the_fill_and_protects_the_rest() {
+        let (mut live, mut ledger, venue) = setup(50000.0);
+        let pos = live
+            .open_position(&mut ledger, "BTC-USD", &long_signal(), "5m", None)
+            .await
+            .unwrap()
+            .unwrap();
+
+        live.fill_timeout = Duration::ZERO;
+        venue.lock().unwrap().partial = Some(0.0001);
+        let closed = live
+            .close_position(&mut ledger, "BTC-USD", pos.id)
+            .await
+            .unwrap();
+        assert!(closed.is_none());
+
+        let rest = ledger.position(pos.id).unwrap().remaining_size_btc;
+        assert!((rest - (pos.size_btc - 0.0001)).abs() < 1e-8);
+        let v = venue.lock().unwrap();
+        assert_eq!(live.stop_order(pos.id), Some(v.stops[1].0.as_str()));
+        assert!((v.stops[1].1 - rest).abs() < 1e-12);
+    }
+
+    #[tokio::test]
+    async fn failed_stop_move_keeps_the_old_stop_resting() {
+        let (mut live, mut ledger, venue) = setup(50000.0);
+        let pos = live
+            .open_position(&mut ledger, "BTC-USD", &long_signal(), "5m", None)
+            .await
+            .unwrap()
+            .unwrap();
+        let old_stop = live.stop_order(pos.id).unwrap().to_string();
+
+        venue.lock().unwrap().stop_reject = true;
+        let res = live
+            .update_stop(
+                &mut ledger,
+                "BTC-USD",
+                pos.id,
+                49800.0,
+                StopAdjustReason::Trail,
+            )
+            .await;
+        assert!(res.is_err());
+        assert_eq!(live.stop_order(pos.id), Some(old_stop.as_str()));
+        assert_eq!(
+            venue.lock().unwrap().orders[&old_stop].state,
+            OrderState::Open
+        );
+
+        venue.lock().unwrap().stop_reject = false;
+        live.update_stop(
+            &mut ledger,
+            "BTC-USD",
+            pos.id,
+            49800.0,
+            StopAdjustReason::Trail,
+        )
+        .await
+        .unwrap();
+        let v = venue.lock().unwrap();
+        assert_eq!(v.orders[&old_stop].state, OrderState::Cancelled);
+        assert_eq!(live.stop_order(pos.id), Some(v.stops[1].0.as_str()));
+    }
+}
@@ -1,4 +1,19 @@
+#[cfg(feature = "charts")]
+pub mod chart;
+pub mod accounts;
+pub mod drawdown_guard;
+pub mod execution_model;
+pub mod exposure;
+pub mod journal;
+pub mod kill_switch;
+pub mod live_trader;
 pub mod paper_trader;
+pub mod risk_regime;
+pub mod runtime_state;
+pub mod scheduler;
+pub mod shutdown_report;
 pub mod strategy_refiner;
+pub mod stuck_positions;
 pub mod trade_analyzer;
 pub mod trade_record;
+pub mod trailing;
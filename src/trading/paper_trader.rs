@@ -1,30 +1,44 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::fs;
-use std::path::Path;
+use std::sync::Arc;
 
-use crate::config::Config;
+use crate::backtesting::intrabar::ohlc_waypoints;
+use crate::config::{Config, HedgePolicy, TpAllocation};
+use crate::core::expectancy::ExpectancyForecast;
 use crate::core::kelly::{HasPnl, KellyCriterion, KellyResult};
-use crate::models::{Direction, PositionStatus};
+use crate::core::position_sizing::{SizingMode, VolRegime};
+use crate::core::r_multiple::RStats;
+use crate::models::{Candle, CloseReason, Direction, PositionStatus, Precision};
+use crate::storage::{self, TradeQuery, TradeStore, TraderState};
 use crate::strategies::signals::TradeSignal;
-use crate::trading::trade_record::{TradeMetadata, TradeRecord};
-
-/// Partial TP allocation — conservative (non-CISD)
-const TP_ALLOC_CONSERVATIVE: &[(f64, f64)] = &[
-    (-1.0, 0.60),
-    (-2.0, 0.20),
-    (-4.0, 0.10),
-    (-4.5, 0.10),
-];
-
-/// Partial TP allocation — aggressive (CISD confirmed, let runners run)
-const TP_ALLOC_AGGRESSIVE: &[(f64, f64)] = &[
-    (-1.0, 0.10),
-    (-2.0, 0.15),
-    (-4.0, 0.30),
-    (-4.5, 0.45),
-];
+use crate::trading::drawdown_guard::{BreakerEvent, DrawdownGuard};
+use crate::trading::execution_model::{self, ExecutionModel, FillCosts, Liquidity, Order};
+use crate::trading::exposure::{Exposure, ExposureLimits};
+use crate::trading::risk_regime::RiskRegime;
+use crate::trading::trade_analyzer::{BucketStats, TradeAnalyzer};
+use crate::trading::trade_record::{TradeMetadata, TradeRecord, SCHEMA_VERSION};
+
+/// How a candle that spans both SL and TP is resolved when only OHLC is known.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IntrabarOrdering {
+    /// Assume the adverse extreme trades first (worst case).
+    #[default]
+    SlFirst,
+    /// Follow the O -> L -> H -> C / O -> H -> L -> C path.
+    OhlcPath,
+}
+
+impl IntrabarOrdering {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "sl_first" => Some(IntrabarOrdering::SlFirst),
+            "ohlc" | "ohlc_path" => Some(IntrabarOrdering::OhlcPath),
+            _ => None,
+        }
+    }
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TpTarget {
@@ -44,16 +58,49 @@ pub struct PartialExit {
     pub time: String,
     #[serde(default)]
     pub logged: bool,
+    /// Cost of exit slippage on this fill (USD)
+    #[serde(default)]
+    pub slippage: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StopAdjustReason {
+    Trail,
+    BreakEven,
+    Manual,
+    /// Tightened after the position was flagged as stuck
+    Stuck,
+    /// Tightened ahead of a high-impact news release
+    News,
+}
+
+/// One change to a position's stop.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StopAdjustment {
+    pub time: String,
+    pub old_price: f64,
+    pub new_price: f64,
+    pub reason: StopAdjustReason,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Position {
     pub id: u64,
+    /// Product traded (e.g. BTC-USD)
+    #[serde(default)]
+    pub symbol: String,
     pub direction: Direction,
     pub entry_price: f64,
+    /// Price the signal asked for (before slippage / order model)
+    #[serde(default)]
+    pub signal_price: f64,
     pub size_usd: f64,
     pub size_btc: f64,
     pub stop_loss: f64,
+    /// Stop at entry, before any trailing
+    #[serde(default)]
+    pub initial_stop_loss: f64,
     pub take_profit: f64,
     pub entry_time: String,
     pub reason: String,
@@ -63,9 +110,14 @@ pub struct Position {
     pub kelly_fraction: f64,
     pub status: PositionStatus,
     #[serde(default)]
+    pub close_reason: Option<CloseReason>,
+    #[serde(default)]
     pub exit_price: Option<f64>,
     #[serde(default)]
     pub exit_time: Option<String>,
+    /// Cost of exit slippage on the final (non-partial) exit (USD)
+    #[serde(default)]
+    pub exit_slippage: f64,
     #[serde(default)]
     pub pnl: f64,
     #[serde(default)]
@@ -74,35 +126,225 @@ pub struct Position {
     pub tp_targets: Vec<TpTarget>,
     #[serde(default)]
     pub partial_exits: Vec<PartialExit>,
+    #[serde(default)]
+    pub stop_history: Vec<StopAdjustment>,
+    /// Perpetual funding paid while open (USD, negative = received)
+    #[serde(default)]
+    pub funding: f64,
+    /// Limit entries: cancelled if still pending at this time
+    #[serde(default)]
+    pub expires: Option<String>,
+    /// Pyramid tranche: id of the position it adds to
+    #[serde(default)]
+    pub parent_id: Option<u64>,
+    /// Dollar risk taken at entry (entry to initial stop, full size); set at close
+    #[serde(default)]
+    pub initial_risk_usd: f64,
+    /// Realized PnL in units of `initial_risk_usd`; set at close
+    #[serde(default)]
+    pub r_multiple: Option<f64>,
+    /// Worst price checked while open (max adverse excursion)
+    #[serde(default)]
+    pub mae_price: Option<f64>,
+    /// Best price checked while open (max favorable excursion)
+    #[serde(default)]
+    pub mfe_price: Option<f64>,
+    /// Killzone of the signal
+    #[serde(default)]
+    pub session: String,
+    /// Volatility regime of the entry timeframe at entry
+    #[serde(default)]
+    pub vol_regime: Option<VolRegime>,
+    /// Price levels of an entry too large for one; `entry_price` is
+    /// their volume-weighted average. Empty for single-price fills.
+    #[serde(default)]
+    pub entry_fills: Vec<EntryFill>,
+    /// Fee and slippage charged to the balance at entry
+    #[serde(default)]
+    pub entry_costs: f64,
+    /// Tags of the signal (`TradeSignal::tags`)
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+/// One price level of a partially filled entry.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct EntryFill {
+    pub price: f64,
+    pub size_btc: f64,
+}
+
+impl Position {
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t == tag)
+    }
+
+    /// Realized entry vs signal price in basis points; positive = price improvement.
+    pub fn entry_improvement_bps(&self) -> f64 {
+        if self.signal_price <= 0.0 {
+            return 0.0;
+        }
+        let diff = match self.direction {
+            Direction::Long => self.signal_price - self.entry_price,
+            Direction::Short => self.entry_price - self.signal_price,
+        };
+        diff / self.signal_price * 10_000.0
+    }
+
+    /// Record the entry risk and the R the closed trade made.
+    fn record_r(&mut self) {
+        let stop = if self.initial_stop_loss > 0.0 {
+            self.initial_stop_loss
+        } else {
+            self.stop_loss
+        };
+        self.initial_risk_usd = round2((self.entry_price - stop).abs() * self.size_btc);
+        self.r_multiple = (self.initial_risk_usd > 0.0).then(|| self.pnl / self.initial_risk_usd);
+    }
+
+    /// Widen the adverse and favorable extremes to include `price`.
+    fn track_excursion(&mut self, price: f64) {
+        let mae = self.mae_price.unwrap_or(price);
+        let mfe = self.mfe_price.unwrap_or(price);
+        let (mae, mfe) = match self.direction {
+            Direction::Long => (mae.min(price), mfe.max(price)),
+            Direction::Short => (mae.max(price), mfe.min(price)),
+        };
+        self.mae_price = Some(mae);
+        self.mfe_price = Some(mfe);
+    }
+
+    /// (MAE, MFE) as distances from entry in units of the initial stop
+    /// distance, both >= 0. `None` until the position has been checked.
+    pub fn excursion_r(&self) -> Option<(f64, f64)> {
+        let stop = if self.initial_stop_loss > 0.0 {
+            self.initial_stop_loss
+        } else {
+            self.stop_loss
+        };
+        let risk = (self.entry_price - stop).abs();
+        if risk == 0.0 {
+            return None;
+        }
+        let gain = |price: f64| match self.direction {
+            Direction::Long => (price - self.entry_price) / risk,
+            Direction::Short => (self.entry_price - price) / risk,
+        };
+        Some((
+            (-gain(self.mae_price?)).max(0.0),
+            gain(self.mfe_price?).max(0.0),
+        ))
+    }
+
+    /// Whether `price` has traded through a pending limit entry.
+    fn limit_reached(&self, price: f64) -> bool {
+        match self.direction {
+            Direction::Long => price <= self.entry_price,
+            Direction::Short => price >= self.entry_price,
+        }
+    }
+
+    fn expired_at(&self, now: DateTime<Utc>) -> bool {
+        self.expires
+            .as_deref()
+            .and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+            .is_some_and(|t| now >= t)
+    }
+
+    /// Reason for a stop-out: break-even or trailing if the stop was moved
+    /// in the trade's favour.
+    pub fn stop_reason(&self) -> CloseReason {
+        if self
+            .stop_history
+            .last()
+            .is_some_and(|a| a.reason == StopAdjustReason::BreakEven && a.new_price == self.stop_loss)
+        {
+            return CloseReason::BreakEven;
+        }
+        let trailed = self.initial_stop_loss > 0.0
+            && match self.direction {
+                Direction::Long => self.stop_loss > self.initial_stop_loss,
+                Direction::Short => self.stop_loss < self.initial_stop_loss,
+            };
+        if trailed {
+            CloseReason::TrailingStop
+        } else {
+            CloseReason::StopLoss
+        }
+    }
 }
 
 impl HasPnl for Position {
     fn pnl(&self) -> f64 {
         self.pnl
     }
-    fn reason(&self) -> &str {
-        &self.reason
+    fn scale(&self) -> &str {
+        &self.scale
+    }
+    fn session(&self) -> &str {
+        &self.session
+    }
+    fn vol_regime(&self) -> Option<VolRegime> {
+        self.vol_regime
     }
 }
 
 pub struct PaperTrader {
-    pub balance: f64,
-    pub positions: Vec<Position>,
-    pub trade_history: Vec<Position>,
-    pub trade_counter: u64,
-    pub daily_pnl: f64,
-    pub daily_pnl_date: String,
-    pub kelly: KellyCriterion,
-    pub last_kelly_result: Option<KellyResult>,
-    pub trade_records: HashMap<u64, TradeRecord>,
-    trades_file: String,
-    records_file: String,
+    balance: f64,
+    positions: Vec<Position>,
+    trade_history: Vec<Position>,
+    trade_counter: u64,
+    daily_pnl: f64,
+    daily_pnl_date: String,
+    kelly: KellyCriterion,
+    last_kelly_result: Option<KellyResult>,
+    trade_records: HashMap<u64, TradeRecord>,
+    /// Limit entries filled or cancelled since last taken (not persisted)
+    limit_updates: Vec<Position>,
+    /// Last checked price per symbol, for mark-to-market equity (not persisted)
+    marks: HashMap<String, f64>,
+    /// Where state is persisted; `None` for backtests
+    store: Option<Box<dyn TradeStore>>,
     /// When set, used instead of Utc::now() for timestamps (backtesting)
     pub sim_time: Option<DateTime<Utc>>,
-    /// Trading fees as fraction (e.g., 0.001 = 0.1%)
-    fee_rate: f64,
-    /// Slippage as fraction (e.g., 0.0005 = 0.05%)
-    slippage_rate: f64,
+    /// Volume of the bar fills happen in (backtesting), for size-dependent impact
+    pub bar_volume: Option<f64>,
+    /// Fee and slippage of each simulated fill
+    execution: Arc<dyn ExecutionModel>,
+    /// Partial-TP tables (`cfg.tp_allocation`) by scale, killzone and
+    /// CISD; `""` for scales and sessions without an entry
+    tp_allocs: HashMap<(String, String, bool), TpAllocation>,
+    breakeven_after_tp1: bool,
+    /// Per-scale max hold (minutes) overriding `max_hold_minutes`
+    scale_max_hold: HashMap<String, i64>,
+    /// Max hold (minutes) of scales without their own; 0 = none
+    max_hold_minutes: i64,
+    /// Minutes after the last partial TP before the remainder is closed
+    post_tp_stall_minutes: i64,
+    /// Notional cap as a multiple of the balance
+    max_leverage: f64,
+    /// Symbol used by `open_position` when none is given
+    symbol: String,
+    /// Per-symbol lot size overrides (`cfg.precision`)
+    precision: HashMap<String, Precision>,
+    /// Weekly-profile regime the next entry is sized and capped under
+    regime: RiskRegime,
+    /// Per-trade risk cap of `regime`
+    max_risk_pct: f64,
+    /// Killzone risk caps replacing `max_risk_pct` (`cfg.killzone_profiles`)
+    killzone_risk: HashMap<String, f64>,
+    /// Net long/short notional caps across all positions
+    exposure_limits: ExposureLimits,
+    /// Halts entries after a drawdown from peak balance
+    drawdown_guard: DrawdownGuard,
+    /// Tranches one position may add; 0 = no pyramiding
+    pyramid_max_adds: usize,
+    /// Risk multiplier of an added tranche
+    pyramid_size: f64,
+    hedge_policy: HedgePolicy,
+    sizing_mode: SizingMode,
+    /// Balance fraction of the fixed sizing modes
+    fixed_risk_pct: f64,
 }
 
 impl PaperTrader {
@@ -117,13 +359,33 @@ impl PaperTrader {
             kelly: KellyCriterion::new(),
             last_kelly_result: None,
             trade_records: HashMap::new(),
-            trades_file: format!("{}/paper_trades.json", cfg.log_dir),
-            records_file: format!("{}/trade_records.json", cfg.log_dir),
+            limit_updates: Vec::new(),
+            marks: HashMap::new(),
+            store: Some(storage::open(cfg)),
             sim_time: None,
-            fee_rate: cfg.fee_rate,
-            slippage_rate: cfg.slippage_rate,
+            bar_volume: None,
+            execution: execution_model::from_config(cfg),
+            tp_allocs: tp_allocs(cfg),
+            breakeven_after_tp1: cfg.move_to_breakeven_after_tp1,
+            scale_max_hold: scale_max_hold(cfg),
+            max_hold_minutes: cfg.max_hold_minutes,
+            post_tp_stall_minutes: cfg.post_tp_stall_minutes,
+            max_leverage: cfg.max_leverage,
+            symbol: cfg.symbol.clone(),
+            precision: cfg.precision.clone(),
+            regime: RiskRegime::Normal,
+            max_risk_pct: cfg.max_risk_pct,
+            killzone_risk: killzone_risk(cfg),
+            exposure_limits: ExposureLimits::from_config(cfg),
+            drawdown_guard: DrawdownGuard::new(cfg, cfg.initial_balance),
+            pyramid_max_adds: cfg.pyramid_max_adds,
+            pyramid_size: cfg.pyramid_size,
+            hedge_policy: cfg.hedge_policy,
+            sizing_mode: cfg.sizing_mode,
+            fixed_risk_pct: cfg.fixed_risk_pct,
         };
         trader.load_state(cfg);
+        trader.drawdown_guard = DrawdownGuard::new(cfg, trader.peak_balance());
         trader
     }
 
@@ -139,26 +401,240 @@ impl PaperTrader {
             kelly: KellyCriterion::new(),
             last_kelly_result: None,
             trade_records: HashMap::new(),
-            trades_file: String::new(),
-            records_file: String::new(),
+            limit_updates: Vec::new(),
+            marks: HashMap::new(),
+            store: None,
             sim_time: None,
-            fee_rate: cfg.fee_rate,
-            slippage_rate: cfg.slippage_rate,
+            bar_volume: None,
+            execution: execution_model::from_config(cfg),
+            tp_allocs: tp_allocs(cfg),
+            breakeven_after_tp1: cfg.move_to_breakeven_after_tp1,
+            scale_max_hold: scale_max_hold(cfg),
+            max_hold_minutes: cfg.max_hold_minutes,
+            post_tp_stall_minutes: cfg.post_tp_stall_minutes,
+            max_leverage: cfg.max_leverage,
+            symbol: cfg.symbol.clone(),
+            precision: cfg.precision.clone(),
+            regime: RiskRegime::Normal,
+            max_risk_pct: cfg.max_risk_pct,
+            killzone_risk: killzone_risk(cfg),
+            exposure_limits: ExposureLimits::from_config(cfg),
+            drawdown_guard: DrawdownGuard::new(cfg, cfg.initial_balance),
+            pyramid_max_adds: cfg.pyramid_max_adds,
+            pyramid_size: cfg.pyramid_size,
+            hedge_policy: cfg.hedge_policy,
+            sizing_mode: cfg.sizing_mode,
+            fixed_risk_pct: cfg.fixed_risk_pct,
         }
     }
 
+    fn precision_for(&self, symbol: &str) -> Precision {
+        Precision::resolve(&self.precision, symbol)
+    }
+
     /// Get the current time (sim_time for backtesting, Utc::now() for live)
     fn now(&self) -> DateTime<Utc> {
         self.sim_time.unwrap_or_else(Utc::now)
     }
 
+    /// Positions that are still open.
+    pub fn open_positions(&self) -> impl Iterator<Item = &Position> {
+        self.positions
+            .iter()
+            .filter(|p| p.status == PositionStatus::Open)
+    }
+
+    /// Limit entries still waiting to fill.
+    pub fn pending_positions(&self) -> impl Iterator<Item = &Position> {
+        self.positions
+            .iter()
+            .filter(|p| p.status == PositionStatus::Pending)
+    }
+
+    /// No open positions and no resting limits.
+    pub fn is_flat(&self) -> bool {
+        !self
+            .positions
+            .iter()
+            .any(|p| matches!(p.status, PositionStatus::Open | PositionStatus::Pending))
+    }
+
+    /// Open positions for one symbol.
+    pub fn open_positions_for<'a>(&'a self, symbol: &'a str) -> impl Iterator<Item = &'a Position> {
+        self.open_positions().filter(move |p| p.symbol == symbol)
+    }
+
+    /// Open position count per symbol.
+    pub fn open_by_symbol(&self) -> HashMap<String, usize> {
+        let mut counts = HashMap::new();
+        for p in self.open_positions() {
+            *counts.entry(p.symbol.clone()).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    /// Look up a position (open or closed) by id.
+    pub fn position(&self, id: u64) -> Option<&Position> {
+        self.positions
+            .iter()
+            .find(|p| p.id == id)
+            .or_else(|| self.trade_history.iter().find(|p| p.id == id))
+    }
+
+    /// Realized balance (open positions not marked).
+    pub fn balance(&self) -> f64 {
+        self.balance
+    }
+
+    /// Every tracked position: open, pending, and closed ones not yet moved
+    /// to history.
+    pub fn positions(&self) -> &[Position] {
+        &self.positions
+    }
+
+    /// Closed trades, oldest first.
+    pub fn history(&self) -> &[Position] {
+        &self.trade_history
+    }
+
+    /// Trade records by position id.
+    pub fn records(&self) -> &HashMap<u64, TradeRecord> {
+        &self.trade_records
+    }
+
+    /// Kelly sizing used for the most recent entry.
+    pub fn last_kelly_result(&self) -> Option<&KellyResult> {
+        self.last_kelly_result.as_ref()
+    }
+
+    /// Add an already closed trade and its record (report fixtures).
+    #[cfg(test)]
+    pub(crate) fn push_closed(&mut self, pos: Position, record: TradeRecord) {
+        self.trade_records.insert(pos.id, record);
+        self.trade_history.push(pos);
+    }
+
+    /// Closed trades matching `filter`, oldest first.
+    pub fn history_filtered(&self, filter: impl Fn(&Position) -> bool) -> Vec<&Position> {
+        self.trade_history.iter().filter(|p| filter(p)).collect()
+    }
+
+    /// Edge of the closed trades carrying each tag.
+    pub fn tag_stats(&self, min_sample: usize) -> Vec<BucketStats> {
+        let records: Vec<TradeRecord> = self.trade_records.values().cloned().collect();
+        TradeAnalyzer::new(min_sample).tag_stats(&records)
+    }
+
+    /// Cheap read-only view of account state (no Kelly recalculation).
+    pub fn snapshot(&self) -> TraderSnapshot {
+        TraderSnapshot {
+            balance: round2(self.balance),
+            daily_pnl: round2(self.daily_pnl),
+            open_positions: self.open_positions().cloned().collect(),
+            total_trades: self.trade_history.len(),
+            realized_pnl: round2(self.trade_history.iter().map(|t| t.pnl).sum()),
+        }
+    }
+
+    /// Move the stop of an open position, recording the change in its
+    /// `stop_history`. Returns the previous stop.
+    pub fn update_stop(&mut self, id: u64, price: f64, reason: StopAdjustReason) -> Option<f64> {
+        let now_str = self.now().to_rfc3339();
+        let pos = self
+            .positions
+            .iter_mut()
+            .find(|p| p.id == id && p.status == PositionStatus::Open)?;
+        let old = pos.stop_loss;
+        if price != old {
+            pos.stop_history.push(StopAdjustment {
+                time: now_str,
+                old_price: old,
+                new_price: price,
+                reason,
+            });
+            pos.stop_loss = price;
+        }
+        self.save_state();
+        Some(old)
+    }
+
+    /// Limit entries filled (now `Open`) or `Cancelled` since the last call.
+    pub fn take_limit_updates(&mut self) -> Vec<Position> {
+        std::mem::take(&mut self.limit_updates)
+    }
+
+    /// Partial exits not yet reported, marking them as logged.
+    pub fn take_unlogged_partials(&mut self) -> Vec<(u64, PartialExit)> {
+        let mut out = Vec::new();
+        for pos in &mut self.positions {
+            for pe in &mut pos.partial_exits {
+                if !pe.logged {
+                    pe.logged = true;
+                    out.push((pos.id, pe.clone()));
+                }
+            }
+        }
+        out
+    }
+
+    /// Pick up partial-TP tables changed in `cfg` (e.g. by the refiner).
+    pub fn set_tp_allocations(&mut self, cfg: &Config) {
+        self.tp_allocs = tp_allocs(cfg);
+    }
+
+    /// Apply the limits of `regime` to subsequent entries.
+    pub fn set_regime(&mut self, regime: RiskRegime, cfg: &Config) {
+        self.regime = regime;
+        self.max_risk_pct = regime.limits(cfg).max_risk_pct;
+    }
+
+    pub fn regime(&self) -> RiskRegime {
+        self.regime
+    }
+
+    /// Highest balance reached, replaying closed trades back from the
+    /// current balance.
+    fn peak_balance(&self) -> f64 {
+        let mut balance = self.balance;
+        let mut peak = balance;
+        for p in self.trade_history.iter().rev() {
+            balance -= p.pnl;
+            peak = peak.max(balance);
+        }
+        peak
+    }
+
+    /// Advance the drawdown circuit breaker with the current equity, so
+    /// open losses count as well as realized ones.
+    pub fn update_drawdown_guard(&mut self) -> Option<BreakerEvent> {
+        let now = self.now();
+        let equity = self.equity();
+        self.drawdown_guard.update(equity, now)
+    }
+
+    pub fn drawdown_guard(&self) -> &DrawdownGuard {
+        &self.drawdown_guard
+    }
+
+    /// Keep a breaker trip from before a restart (see `RuntimeState`).
+    pub fn restore_drawdown_trip(&mut self, tripped_at: DateTime<Utc>) {
+        self.drawdown_guard.restore_trip(tripped_at);
+    }
+
     pub fn can_open_position(&self, cfg: &Config) -> bool {
+        if self.drawdown_guard.is_tripped() {
+            return false;
+        }
+
+        // Resting limits count: they fill without asking again. Pyramid
+        // tranches ride on their parent's slot.
         let open_count = self
             .positions
             .iter()
-            .filter(|p| p.status == PositionStatus::Open)
+            .filter(|p| p.parent_id.is_none())
+            .filter(|p| matches!(p.status, PositionStatus::Open | PositionStatus::Pending))
             .count();
-        if open_count >= cfg.max_open_positions {
+        if open_count >= self.regime.limits(cfg).max_open_positions {
             return false;
         }
 
@@ -173,65 +649,186 @@ impl PaperTrader {
         true
     }
 
+    /// Open position `signal` would pyramid into: same symbol and
+    /// direction, already in profit at the signal's entry, CISD confirmed
+    /// and with adds to spare.
+    fn pyramid_parent(&self, symbol: &str, signal: &TradeSignal) -> Option<u64> {
+        if self.pyramid_max_adds == 0 || !signal.cisd_confirmed {
+            return None;
+        }
+        self.open_positions()
+            .filter(|p| p.parent_id.is_none() && p.symbol == symbol)
+            .filter(|p| p.direction == signal.direction)
+            .filter(|p| match p.direction {
+                Direction::Long => signal.entry_price > p.entry_price,
+                Direction::Short => signal.entry_price < p.entry_price,
+            })
+            .find(|p| self.tranches(p.id).count() < self.pyramid_max_adds)
+            .map(|p| p.id)
+    }
+
+    /// An open or resting position on `symbol` points the other way.
+    fn is_hedge(&self, symbol: &str, direction: Direction) -> bool {
+        self.positions.iter().any(|p| {
+            p.symbol == symbol
+                && p.direction != direction
+                && matches!(p.status, PositionStatus::Open | PositionStatus::Pending)
+        })
+    }
+
+    /// Tranches added to position `id`, open or closed.
+    pub fn tranches(&self, id: u64) -> impl Iterator<Item = &Position> {
+        self.positions
+            .iter()
+            .chain(&self.trade_history)
+            .filter(move |p| p.parent_id == Some(id) && p.status != PositionStatus::Cancelled)
+    }
+
     pub fn open_position(
         &mut self,
         signal: &TradeSignal,
         scale: &str,
         metadata: Option<TradeMetadata>,
+    ) -> Option<&Position> {
+        let symbol = self.symbol.clone();
+        self.open_position_for(&symbol, signal, scale, metadata)
+    }
+
+    pub fn open_position_for(
+        &mut self,
+        symbol: &str,
+        signal: &TradeSignal,
+        scale: &str,
+        metadata: Option<TradeMetadata>,
+    ) -> Option<&Position> {
+        self.enter(symbol, signal, scale, metadata, None)
+    }
+
+    /// Rest a limit entry at `signal.entry_price`, sized now. `check_positions`
+    /// fills it once price trades through the limit and cancels it if still
+    /// pending at `expires`.
+    pub fn place_limit_for(
+        &mut self,
+        symbol: &str,
+        signal: &TradeSignal,
+        scale: &str,
+        metadata: Option<TradeMetadata>,
+        expires: DateTime<Utc>,
+    ) -> Option<&Position> {
+        self.enter(symbol, signal, scale, metadata, Some(expires))
+    }
+
+    fn enter(
+        &mut self,
+        symbol: &str,
+        signal: &TradeSignal,
+        scale: &str,
+        metadata: Option<TradeMetadata>,
+        expires: Option<DateTime<Utc>>,
     ) -> Option<&Position> {
         let sl_distance = (signal.entry_price - signal.stop_loss).abs();
         if sl_distance == 0.0 {
             return None;
         }
+        if self.hedge_policy == HedgePolicy::Block && self.is_hedge(symbol, signal.direction) {
+            tracing::info!(
+                "Skipping {} {}: opposite position open and HEDGE_POLICY=block",
+                symbol,
+                signal.direction
+            );
+            return None;
+        }
 
-        // Kelly position sizing
-        let (risk_amount, kelly_result) =
-            self.kelly
-                .get_risk_amount(self.balance, &self.trade_history, Some(scale));
+        // Kelly, fixed or volatility-scaled sizing
+        let vol_regime = signal.atr_percentile.map(VolRegime::from_percentile);
+        let kelly_result = self.kelly.calculate_for_context(
+            &self.trade_history,
+            scale,
+            &signal.session,
+            vol_regime,
+        );
         self.last_kelly_result = Some(kelly_result.clone());
-
-        // Hard cap: max risk per trade (configurable via MAX_RISK_PCT env)
-        let risk_pct: f64 = std::env::var("MAX_RISK_PCT")
-            .ok()
-            .and_then(|s| s.parse().ok())
-            .unwrap_or(0.02);
-        let max_risk = self.balance * risk_pct;
-        let capped_risk = risk_amount.min(max_risk);
+        let fraction = self.sizing_mode.risk_fraction(
+            kelly_result.applied_fraction,
+            self.fixed_risk_pct,
+            signal.atr_percentile,
+        );
+        let risk_amount = round2(self.balance * fraction);
+
+        // Hard cap: max risk per trade for the killzone, else the active regime
+        let max_risk_pct = self
+            .killzone_risk
+            .get(&signal.session)
+            .copied()
+            .unwrap_or(self.max_risk_pct);
+        let max_risk = self.balance * max_risk_pct;
+        let mut capped_risk = risk_amount.min(max_risk) * signal.size_multiplier.max(0.0);
+
+        // Adding to a winner: a smaller tranche under the running position
+        let parent_id = self.pyramid_parent(symbol, signal);
+        if parent_id.is_some() {
+            capped_risk *= self.pyramid_size;
+        }
 
         let mut size_btc = capped_risk / sl_distance;
         let mut size_usd = size_btc * signal.entry_price;
 
-        // Leverage cap (configurable via MAX_LEVERAGE env, default 5x)
-        let max_leverage: f64 = std::env::var("MAX_LEVERAGE")
-            .ok()
-            .and_then(|s| s.parse().ok())
-            .unwrap_or(5.0);
-        let max_position_usd = self.balance * max_leverage;
+        // Leverage cap (MAX_LEVERAGE, default 5x)
+        let max_position_usd = self.balance * self.max_leverage;
         if size_usd > max_position_usd {
             size_usd = max_position_usd;
             size_btc = size_usd / signal.entry_price;
         }
 
-        // Apply entry fee + slippage
-        let entry_fee = size_usd * self.fee_rate;
-        let slippage_cost = size_usd * self.slippage_rate;
-        self.balance -= entry_fee + slippage_cost;
+        // Venue lot size; below one lot there is nothing to trade
+        let precision = self.precision_for(symbol);
+        size_btc = precision.round_size(size_btc);
+        if size_btc <= 0.0 {
+            return None;
+        }
+        size_usd = size_btc * signal.entry_price;
+
+        // Net directional exposure across every open position
+        let exposure = Exposure::of(&self.positions);
+        if !self
+            .exposure_limits
+            .allows(&exposure, signal.direction, size_usd, self.balance)
+        {
+            tracing::info!(
+                "Skipping {} {} ${:.2}: net exposure ${:+.2} at its cap",
+                symbol,
+                signal.direction,
+                size_usd,
+                exposure.net()
+            );
+            return None;
+        }
 
-        // Adjust entry price for slippage (adverse direction)
-        let entry_price = match signal.direction {
-            Direction::Long => signal.entry_price * (1.0 + self.slippage_rate),
-            Direction::Short => signal.entry_price * (1.0 - self.slippage_rate),
+        // Limits pay entry costs when they fill
+        let (entry_price, entry_fills, entry_costs) = match expires {
+            Some(_) => (signal.entry_price, Vec::new(), 0.0),
+            None => self.charge_entry(
+                signal.direction,
+                signal.entry_price,
+                size_usd,
+                Liquidity::Taker,
+            ),
         };
 
         self.trade_counter += 1;
         let id = self.trade_counter;
 
         // Build TP targets from SD levels — dynamic allocation based on CISD
-        let tp_alloc = if signal.cisd_confirmed {
-            TP_ALLOC_AGGRESSIVE
-        } else {
-            TP_ALLOC_CONSERVATIVE
+        let cisd = signal.cisd_confirmed;
+        let table = |scale: &str, session: &str| {
+            self.tp_allocs
+                .get(&(scale.to_string(), session.to_string(), cisd))
         };
+        let tp_alloc = table(scale, &signal.session)
+            .or_else(|| table(scale, ""))
+            .or_else(|| table("", &signal.session))
+            .or_else(|| table("", ""))
+            .map_or(&[][..], |a| &a.0[..]);
         let mut tp_targets = Vec::new();
         if let Some(ref tp_levels) = signal.tp_levels {
             let tp_map: HashMap<i64, f64> = tp_levels
@@ -246,7 +843,7 @@ impl PaperTrader {
                         level,
                         price,
                         pct,
-                        size_btc: round8(size_btc * pct),
+                        size_btc: precision.round_size(size_btc * pct),
                         hit: false,
                     });
                 }
@@ -255,38 +852,68 @@ impl PaperTrader {
 
         let pos = Position {
             id,
+            symbol: symbol.to_string(),
             direction: signal.direction,
             entry_price,
+            signal_price: signal.entry_price,
             size_usd: round2(size_usd),
-            size_btc: round8(size_btc),
+            size_btc,
             stop_loss: signal.stop_loss,
+            initial_stop_loss: signal.stop_loss,
             take_profit: signal.take_profit,
             entry_time: self.now().to_rfc3339(),
             reason: signal.reason.clone(),
             scale: scale.to_string(),
             kelly_fraction: kelly_result.applied_fraction,
-            status: PositionStatus::Open,
+            status: if expires.is_some() {
+                PositionStatus::Pending
+            } else {
+                PositionStatus::Open
+            },
+            close_reason: None,
             exit_price: None,
             exit_time: None,
+            exit_slippage: 0.0,
             pnl: 0.0,
-            remaining_size_btc: round8(size_btc),
+            remaining_size_btc: size_btc,
             tp_targets,
             partial_exits: Vec::new(),
+            stop_history: Vec::new(),
+            funding: 0.0,
+            expires: expires.map(|t| t.to_rfc3339()),
+            parent_id,
+            initial_risk_usd: 0.0,
+            r_multiple: None,
+            mae_price: None,
+            mfe_price: None,
+            session: signal.session.clone(),
+            vol_regime,
+            entry_fills,
+            entry_costs,
+            tags: signal.tags.clone(),
         };
 
+        let entry_improvement_bps = pos.entry_improvement_bps();
         self.positions.push(pos);
 
         // Trade record
         if let Some(mut md) = metadata {
             md.kelly_fraction = kelly_result.applied_fraction;
+            md.risk_regime = self.regime.to_string();
+            md.sizing_mode = self.sizing_mode.to_string();
+            md.tags = signal.tags.clone();
             self.trade_records.insert(
                 id,
                 TradeRecord {
+                    schema_version: SCHEMA_VERSION,
                     position_id: id,
                     metadata: md,
                     outcome: String::new(),
                     pnl: 0.0,
                     hold_duration_seconds: 0.0,
+                    entry_improvement_bps,
+                    close_reason: None,
+                    extra: Default::default(),
                 },
             );
         }
@@ -295,29 +922,195 @@ impl PaperTrader {
         self.positions.last()
     }
 
+    /// Use `model` to price fills instead of the configured one.
+    pub fn with_execution_model(mut self, model: Arc<dyn ExecutionModel>) -> Self {
+        self.execution = model;
+        self
+    }
+
+    /// Costs of filling `size` base units now.
+    fn fill_costs(&self, entry: bool, liquidity: Liquidity, size: f64) -> FillCosts {
+        self.execution.costs(&Order {
+            entry,
+            liquidity,
+            size,
+            bar_volume: self.bar_volume,
+        })
+    }
+
+    /// Deduct entry fee and slippage on `size_usd`; returns the entry
+    /// price after slippage (adverse direction), the levels the order
+    /// walked when there were several, and the amount deducted.
+    fn charge_entry(
+        &mut self,
+        direction: Direction,
+        price: f64,
+        size_usd: f64,
+        liquidity: Liquidity,
+    ) -> (f64, Vec<EntryFill>, f64) {
+        let order = Order {
+            entry: true,
+            liquidity,
+            size: size_usd / price,
+            bar_volume: self.bar_volume,
+        };
+        let costs = self.execution.costs(&order);
+        let levels = self.execution.fill_levels(&order);
+        let adverse = |rate: f64| match direction {
+            Direction::Long => price * (1.0 + rate),
+            Direction::Short => price * (1.0 - rate),
+        };
+
+        // Volume-weighted extra move of the levels beyond the first
+        let filled: f64 = levels.iter().map(|(size, _)| size).sum();
+        let extra = if filled > 0.0 {
+            levels.iter().map(|(size, step)| size * step).sum::<f64>() / filled
+        } else {
+            0.0
+        };
+        let slippage_rate = costs.slippage_rate + extra;
+        let fills = if levels.len() > 1 {
+            levels
+                .iter()
+                .map(|&(size, step)| EntryFill {
+                    price: adverse(costs.slippage_rate + step),
+                    size_btc: size,
+                })
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        let charged = size_usd * (costs.fee_rate + slippage_rate);
+        self.balance -= charged;
+        (adverse(slippage_rate), fills, charged)
+    }
+
+    /// Fill the pending limit at `pos_idx` at its limit price.
+    fn fill_limit(&mut self, pos_idx: usize) {
+        let (direction, limit, size_usd) = {
+            let pos = &self.positions[pos_idx];
+            (pos.direction, pos.entry_price, pos.size_usd)
+        };
+        let (entry_price, _, entry_costs) =
+            self.charge_entry(direction, limit, size_usd, Liquidity::Maker);
+        let now_str = self.now().to_rfc3339();
+        let pos = &mut self.positions[pos_idx];
+        pos.entry_price = entry_price;
+        pos.entry_costs = entry_costs;
+        pos.entry_time = now_str;
+        pos.status = PositionStatus::Open;
+        let improvement = pos.entry_improvement_bps();
+        let id = pos.id;
+        if let Some(record) = self.trade_records.get_mut(&id) {
+            record.entry_improvement_bps = improvement;
+        }
+        self.limit_updates.push(self.positions[pos_idx].clone());
+    }
+
+    /// Drop the pending limit at `pos_idx` unfilled.
+    fn cancel_limit(&mut self, pos_idx: usize) {
+        let mut pos = self.positions.remove(pos_idx);
+        pos.status = PositionStatus::Cancelled;
+        self.trade_records.remove(&pos.id);
+        self.limit_updates.push(pos);
+    }
+
     pub fn check_positions(&mut self, current_price: f64) -> Vec<Position> {
+        self.check_positions_where(None, None, current_price, false)
+    }
+
+    /// Check only the positions of `symbol` against its price.
+    pub fn check_symbol_positions(&mut self, symbol: &str, current_price: f64) -> Vec<Position> {
+        self.check_positions_where(Some(symbol), None, current_price, false)
+    }
+
+    /// Check positions against a whole candle: open, both extremes in the
+    /// order given by `ordering`, then close. Levels touched by an extreme
+    /// fill at the level itself rather than at the extreme.
+    pub fn check_positions_with_candle(
+        &mut self,
+        candle: &Candle,
+        ordering: IntrabarOrdering,
+    ) -> Vec<Position> {
+        let mut closed = self.check_positions_where(None, None, candle.open, false);
+        match ordering {
+            IntrabarOrdering::SlFirst => {
+                let passes = [
+                    (Direction::Long, candle.low),
+                    (Direction::Short, candle.high),
+                    (Direction::Long, candle.high),
+                    (Direction::Short, candle.low),
+                ];
+                for (direction, price) in passes {
+                    closed.extend(self.check_positions_where(None, Some(direction), price, true));
+                }
+            }
+            IntrabarOrdering::OhlcPath => {
+                let path = ohlc_waypoints(candle);
+                for &price in &path[1..3] {
+                    closed.extend(self.check_positions_where(None, None, price, true));
+                }
+            }
+        }
+        closed.extend(self.check_positions_where(None, None, candle.close, false));
+        closed
+    }
+
+    /// `touch`: `current_price` is a candle extreme, so TP fills happen at
+    /// the target price instead of at `current_price`.
+    fn check_positions_where(
+        &mut self,
+        symbol: Option<&str>,
+        direction: Option<Direction>,
+        current_price: f64,
+        touch: bool,
+    ) -> Vec<Position> {
         let mut closed = Vec::new();
         let mut changed = false;
 
+        let now = self.now();
         let mut i = 0;
         while i < self.positions.len() {
-            if self.positions[i].status != PositionStatus::Open {
+            if symbol.is_some_and(|s| self.positions[i].symbol != s)
+                || direction.is_some_and(|d| self.positions[i].direction != d)
+            {
                 i += 1;
                 continue;
             }
 
-            // Time-based exit: if position open > MAX_HOLD_MINUTES without any TP hit, close at market
-            let max_hold: i64 = std::env::var("MAX_HOLD_MINUTES")
-                .ok()
-                .and_then(|s| s.parse().ok())
-                .unwrap_or(180); // default 3 hours
+            // Pending limits fill once price trades through them (and are
+            // then checked as open at the same price) or expire unfilled
+            if self.positions[i].status == PositionStatus::Pending {
+                if self.positions[i].limit_reached(current_price) {
+                    self.fill_limit(i);
+                    changed = true;
+                } else if self.positions[i].expired_at(now) {
+                    self.cancel_limit(i);
+                    changed = true;
+                    continue;
+                }
+            }
+            if self.positions[i].status != PositionStatus::Open {
+                i += 1;
+                continue;
+            }
+            self.positions[i].track_excursion(current_price);
+            self.mark(&self.positions[i].symbol.clone(), current_price);
+
+            // Time-based exit: if position open > the scale's max hold (else
+            // MAX_HOLD_MINUTES) without any TP hit, close at market
+            let max_hold: i64 = match self.scale_max_hold.get(&self.positions[i].scale) {
+                Some(&m) => m,
+                None => self.max_hold_minutes,
+            };
             if max_hold > 0 {
                 let no_tp_hit = self.positions[i].tp_targets.iter().all(|t| !t.hit);
                 if no_tp_hit {
                     if let Ok(entry_dt) = chrono::DateTime::parse_from_rfc3339(&self.positions[i].entry_time) {
                         let elapsed = (self.now() - entry_dt.with_timezone(&chrono::Utc)).num_minutes();
                         if elapsed >= max_hold {
-                            self.close_position(i, current_price, PositionStatus::ClosedSl);
+                            self.close_position(i, current_price, CloseReason::Expiry);
                             closed.push(self.positions[i].clone());
                             changed = true;
                             i += 1;
@@ -328,10 +1121,7 @@ impl PaperTrader {
             }
 
             // Post-TP stall exit: if some TPs hit but remaining stall, close remainder
-            let post_tp_stall: i64 = std::env::var("POST_TP_STALL_MINUTES")
-                .ok()
-                .and_then(|s| s.parse().ok())
-                .unwrap_or(120); // default 2 hours after last TP hit
+            let post_tp_stall = self.post_tp_stall_minutes;
             if post_tp_stall > 0 {
                 let tps_hit = self.positions[i].tp_targets.iter().filter(|t| t.hit).count();
                 let total_tps = self.positions[i].tp_targets.len();
@@ -340,7 +1130,7 @@ impl PaperTrader {
                         if let Ok(last_tp_dt) = chrono::DateTime::parse_from_rfc3339(&last_exit.time) {
                             let since_last_tp = (self.now() - last_tp_dt.with_timezone(&chrono::Utc)).num_minutes();
                             if since_last_tp >= post_tp_stall {
-                                self.close_position(i, current_price, PositionStatus::ClosedTp);
+                                self.close_position(i, current_price, CloseReason::Stall);
                                 closed.push(self.positions[i].clone());
                                 changed = true;
                                 i += 1;
@@ -359,7 +1149,8 @@ impl PaperTrader {
 
             if hit_sl {
                 // Exit at stop loss price (simulating stop order fill)
-                self.close_position(i, self.positions[i].stop_loss, PositionStatus::ClosedSl);
+                let reason = self.positions[i].stop_reason();
+                self.close_position(i, self.positions[i].stop_loss, reason);
                 closed.push(self.positions[i].clone());
                 changed = true;
                 i += 1;
@@ -369,6 +1160,7 @@ impl PaperTrader {
             // Check partial TP targets
             if !self.positions[i].tp_targets.is_empty() {
                 let mut any_hit = false;
+                let mut fill_price = current_price;
                 for t_idx in 0..self.positions[i].tp_targets.len() {
                     if self.positions[i].tp_targets[t_idx].hit {
                         continue;
@@ -382,7 +1174,10 @@ impl PaperTrader {
                         }
                     };
                     if hit {
-                        self.partial_close(i, t_idx, current_price);
+                        if touch {
+                            fill_price = self.positions[i].tp_targets[t_idx].price;
+                        }
+                        self.partial_close(i, t_idx, fill_price);
                         any_hit = true;
                         changed = true;
                     }
@@ -393,9 +1188,9 @@ impl PaperTrader {
                     let all_hit = self.positions[i].tp_targets.iter().all(|t| t.hit);
                     if all_hit {
                         if self.positions[i].remaining_size_btc > 0.0 {
-                            self.close_position(i, current_price, PositionStatus::ClosedTp);
+                            self.close_position(i, fill_price, CloseReason::TakeProfit);
                         } else {
-                            self.finalize_position(i, PositionStatus::ClosedTp);
+                            self.finalize_position(i, CloseReason::TakeProfit);
                         }
                         closed.push(self.positions[i].clone());
                     }
@@ -407,7 +1202,12 @@ impl PaperTrader {
                     Direction::Short => current_price <= self.positions[i].take_profit,
                 };
                 if hit_tp {
-                    self.close_position(i, current_price, PositionStatus::ClosedTp);
+                    let fill_price = if touch {
+                        self.positions[i].take_profit
+                    } else {
+                        current_price
+                    };
+                    self.close_position(i, fill_price, CloseReason::TakeProfit);
                     closed.push(self.positions[i].clone());
                     changed = true;
                 }
@@ -423,26 +1223,309 @@ impl PaperTrader {
         closed
     }
 
-    fn partial_close(&mut self, pos_idx: usize, target_idx: usize, exit_price: f64) {
-        let now_str = self.now().to_rfc3339();
-        let fee_rate = self.fee_rate;
-        let pos = &mut self.positions[pos_idx];
-        let close_size = pos.tp_targets[target_idx]
-            .size_btc
-            .min(pos.remaining_size_btc);
-        if close_size <= 0.0 {
-            return;
+    /// Close every open position at market (kill switch / manual flatten).
+    pub fn close_all(&mut self, current_price: f64) -> Vec<Position> {
+        self.close_all_where(None, current_price)
+    }
+
+    /// Close every open position of `symbol` at market.
+    pub fn close_symbol(&mut self, symbol: &str, current_price: f64) -> Vec<Position> {
+        self.close_all_where(Some(symbol), current_price)
+    }
+
+    /// Cancel the resting limit entries of `symbol` (all symbols if `None`).
+    pub fn cancel_limits(&mut self, symbol: Option<&str>) -> Vec<Position> {
+        let ids = self.cancel_limits_where(symbol);
+        if ids.is_empty() {
+            return Vec::new();
         }
+        self.save_state();
+        // Reported here, not by the next take_limit_updates
+        let (cancelled, rest) = std::mem::take(&mut self.limit_updates)
+            .into_iter()
+            .partition(|p| ids.contains(&p.id));
+        self.limit_updates = rest;
+        cancelled
+    }
 
-        let pnl = match pos.direction {
-            Direction::Long => (exit_price - pos.entry_price) * close_size,
-            Direction::Short => (pos.entry_price - exit_price) * close_size,
+    /// Ids of the cancelled limits; each is also queued in `limit_updates`.
+    fn cancel_limits_where(&mut self, symbol: Option<&str>) -> Vec<u64> {
+        let mut cancelled = Vec::new();
+        let mut i = 0;
+        while i < self.positions.len() {
+            if self.positions[i].status == PositionStatus::Pending
+                && symbol.is_none_or(|s| self.positions[i].symbol == s)
+            {
+                cancelled.push(self.positions[i].id);
+                self.cancel_limit(i);
+            } else {
+                i += 1;
+            }
+        }
+        cancelled
+    }
+
+    fn close_all_where(&mut self, symbol: Option<&str>, current_price: f64) -> Vec<Position> {
+        let mut closed = Vec::new();
+        let cancelled = !self.cancel_limits_where(symbol).is_empty();
+        for i in 0..self.positions.len() {
+            if self.positions[i].status == PositionStatus::Open
+                && symbol.is_none_or(|s| self.positions[i].symbol == s)
+            {
+                self.close_position(i, current_price, CloseReason::Manual);
+                closed.push(self.positions[i].clone());
+            }
+        }
+        if cancelled || !closed.is_empty() {
+            self.save_state();
+        }
+        closed
+    }
+
+    /// Close one open position at market (manual close).
+    pub fn close_by_id(&mut self, id: u64, current_price: f64) -> Option<Position> {
+        let idx = self
+            .positions
+            .iter()
+            .position(|p| p.id == id && p.status == PositionStatus::Open)?;
+        self.close_position(idx, current_price, CloseReason::Manual);
+        self.save_state();
+        Some(self.positions[idx].clone())
+    }
+
+    /// Close an open position at an explicit price (e.g. an exchange stop
+    /// fill). The price is a real fill, so no slippage is modeled.
+    pub fn close_position_at(
+        &mut self,
+        id: u64,
+        exit_price: f64,
+        reason: CloseReason,
+    ) -> Option<Position> {
+        let idx = self
+            .positions
+            .iter()
+            .position(|p| p.id == id && p.status == PositionStatus::Open)?;
+        self.close_position_with(idx, exit_price, reason, false);
+        self.save_state();
+        Some(self.positions[idx].clone())
+    }
+
+    /// Drop an open position whose entry never filled, refunding entry costs.
+    pub fn discard_position(&mut self, id: u64) -> Option<Position> {
+        let idx = self
+            .positions
+            .iter()
+            .position(|p| p.id == id && p.status == PositionStatus::Open)?;
+        let pos = self.positions.remove(idx);
+        self.balance += pos.entry_costs;
+        self.trade_records.remove(&id);
+        self.save_state();
+        Some(pos)
+    }
+
+    /// Replace the simulated entry with the actual fill (price and size).
+    pub fn reconcile_entry(&mut self, id: u64, fill_price: f64, filled_size: f64) -> bool {
+        let Some(precision) = self.position(id).map(|p| self.precision_for(&p.symbol)) else {
+            return false;
+        };
+        let Some(pos) = self
+            .positions
+            .iter_mut()
+            .find(|p| p.id == id && p.status == PositionStatus::Open)
+        else {
+            return false;
+        };
+        if pos.size_btc > 0.0 && filled_size > 0.0 {
+            let ratio = filled_size / pos.size_btc;
+            for t in &mut pos.tp_targets {
+                t.size_btc = precision.round_size(t.size_btc * ratio);
+            }
+            pos.size_btc = precision.round_size(filled_size);
+            pos.remaining_size_btc = pos.size_btc;
+        }
+        if fill_price > 0.0 {
+            pos.entry_price = fill_price;
+        }
+        pos.size_usd = round2(pos.size_btc * pos.entry_price);
+        let improvement = pos.entry_improvement_bps();
+        if let Some(record) = self.trade_records.get_mut(&id) {
+            record.entry_improvement_bps = improvement;
+        }
+        self.save_state();
+        true
+    }
+
+    /// Put `size` of the last partial exit back on an open position (the
+    /// venue didn't fill that much of the reduce), reversing its share of
+    /// the booked PnL.
+    pub fn restore_partial_exit(&mut self, id: u64, size: f64) -> bool {
+        let Some(precision) = self.position(id).map(|p| self.precision_for(&p.symbol)) else {
+            return false;
+        };
+        let Some(pos) = self
+            .positions
+            .iter_mut()
+            .find(|p| p.id == id && p.status == PositionStatus::Open)
+        else {
+            return false;
+        };
+        let Some(exit) = pos.partial_exits.last_mut() else {
+            return false;
+        };
+        let size = size.min(exit.size_btc);
+        if size <= 0.0 {
+            return false;
+        }
+        let reversed = round2(exit.pnl * size / exit.size_btc);
+        exit.size_btc = precision.round_size(exit.size_btc - size);
+        exit.pnl = round2(exit.pnl - reversed);
+        pos.remaining_size_btc = precision.round_size(pos.remaining_size_btc + size);
+        pos.pnl = round2(pos.pnl - reversed);
+        self.balance -= reversed;
+        self.daily_pnl -= reversed;
+        self.save_state();
+        true
+    }
+
+    /// Book `size` of an open position closed at a real fill `exit_price`
+    /// (a close the venue only partly filled); the rest stays open. The
+    /// fill is recorded as a logged partial exit at level 0.
+    pub fn reduce_position_at(
+        &mut self,
+        id: u64,
+        size: f64,
+        exit_price: f64,
+    ) -> Option<PartialExit> {
+        self.roll_daily_pnl();
+        let now_str = self.now().to_rfc3339();
+        let precision = self.precision_for(&self.position(id)?.symbol);
+        let fee_rate = self.fill_costs(false, Liquidity::Taker, size).fee_rate;
+        let pos = self
+            .positions
+            .iter_mut()
+            .find(|p| p.id == id && p.status == PositionStatus::Open)?;
+        let size = size.min(pos.remaining_size_btc);
+        if size <= 0.0 {
+            return None;
+        }
+        let pnl = match pos.direction {
+            Direction::Long => (exit_price - pos.entry_price) * size,
+            Direction::Short => (pos.entry_price - exit_price) * size,
+        };
+        let pnl = round2(pnl - size * exit_price * fee_rate);
+
+        pos.remaining_size_btc = precision.round_size(pos.remaining_size_btc - size);
+        pos.pnl = round2(pos.pnl + pnl);
+        self.balance += pnl;
+        self.daily_pnl += pnl;
+        let exit = PartialExit {
+            level: 0.0,
+            price: exit_price,
+            size_btc: size,
+            pnl,
+            time: now_str,
+            logged: true,
+            slippage: 0.0,
+        };
+        pos.partial_exits.push(exit.clone());
+        self.save_state();
+        Some(exit)
+    }
+
+    /// Start a fresh daily PnL on the first booking of a new (UTC) day.
+    fn roll_daily_pnl(&mut self) {
+        let today = self.now().format("%Y-%m-%d").to_string();
+        if self.daily_pnl_date != today {
+            self.daily_pnl = 0.0;
+            self.daily_pnl_date = today;
+        }
+    }
+
+    /// Settle one perpetual funding payment on the open positions of
+    /// `symbol` at `mark_price`. Longs pay a positive rate, shorts receive
+    /// it. Returns the net amount paid.
+    pub fn apply_funding(&mut self, symbol: &str, rate: f64, mark_price: f64) -> f64 {
+        self.roll_daily_pnl();
+        let mut paid = 0.0;
+        for pos in self
+            .positions
+            .iter_mut()
+            .filter(|p| p.status == PositionStatus::Open && p.symbol == symbol)
+        {
+            let notional = pos.remaining_size_btc * mark_price;
+            let payment = match pos.direction {
+                Direction::Long => notional * rate,
+                Direction::Short => -notional * rate,
+            };
+            pos.funding += payment;
+            pos.pnl -= payment;
+            paid += payment;
+        }
+        if paid != 0.0 {
+            self.balance -= paid;
+            self.daily_pnl -= paid;
+            self.save_state();
+        }
+        paid
+    }
+
+    /// Book a realized PnL correction (actual exit fill vs simulated price).
+    pub fn adjust_pnl(&mut self, id: u64, delta: f64) {
+        if delta == 0.0 {
+            return;
+        }
+        self.roll_daily_pnl();
+        for pos in self
+            .positions
+            .iter_mut()
+            .chain(self.trade_history.iter_mut())
+            .filter(|p| p.id == id)
+        {
+            pos.pnl = round2(pos.pnl + delta);
+        }
+        if let Some(record) = self.trade_records.get_mut(&id) {
+            record.pnl = round2(record.pnl + delta);
+            if !record.outcome.is_empty() {
+                record.outcome = if record.pnl > 0.0 { "win" } else { "loss" }.to_string();
+            }
+        }
+        self.balance += delta;
+        self.daily_pnl += delta;
+        self.save_state();
+    }
+
+    fn partial_close(&mut self, pos_idx: usize, target_idx: usize, exit_price: f64) {
+        self.roll_daily_pnl();
+        let now_str = self.now().to_rfc3339();
+        let breakeven_after_tp1 = self.breakeven_after_tp1;
+        let precision = self.precision_for(&self.positions[pos_idx].symbol);
+        let close_size = {
+            let pos = &self.positions[pos_idx];
+            pos.tp_targets[target_idx]
+                .size_btc
+                .min(pos.remaining_size_btc)
+        };
+        if close_size <= 0.0 {
+            return;
+        }
+        // Take-profits rest on the book
+        let FillCosts {
+            fee_rate,
+            slippage_rate: exit_slippage_rate,
+        } = self.fill_costs(false, Liquidity::Maker, close_size);
+        let pos = &mut self.positions[pos_idx];
+
+        let (exit_price, slippage) =
+            slipped_exit(pos.direction, exit_price, close_size, exit_slippage_rate);
+        let pnl = match pos.direction {
+            Direction::Long => (exit_price - pos.entry_price) * close_size,
+            Direction::Short => (pos.entry_price - exit_price) * close_size,
         };
         // Deduct exit fee
         let exit_fee = close_size * exit_price * fee_rate;
         let pnl = round2(pnl - exit_fee);
 
-        pos.remaining_size_btc = round8(pos.remaining_size_btc - close_size);
+        pos.remaining_size_btc = precision.round_size(pos.remaining_size_btc - close_size);
         pos.pnl = round2(pos.pnl + pnl);
         self.balance += pnl;
         self.daily_pnl += pnl;
@@ -453,18 +1536,41 @@ impl PaperTrader {
             price: exit_price,
             size_btc: close_size,
             pnl,
-            time: now_str,
+            time: now_str.clone(),
             logged: false,
+            slippage,
         });
 
+        // Break-even: cover round-trip fees on the remainder
+        if breakeven_after_tp1 && pos.partial_exits.len() == 1 && pos.remaining_size_btc > 0.0 {
+            let breakeven = match pos.direction {
+                Direction::Long => pos.entry_price * (1.0 + 2.0 * fee_rate),
+                Direction::Short => pos.entry_price * (1.0 - 2.0 * fee_rate),
+            };
+            let tighter = match pos.direction {
+                Direction::Long => breakeven > pos.stop_loss,
+                Direction::Short => breakeven < pos.stop_loss,
+            };
+            if tighter {
+                pos.stop_history.push(StopAdjustment {
+                    time: now_str,
+                    old_price: pos.stop_loss,
+                    new_price: breakeven,
+                    reason: StopAdjustReason::BreakEven,
+                });
+                pos.stop_loss = breakeven;
+            }
+        }
     }
 
-    fn finalize_position(&mut self, pos_idx: usize, status: PositionStatus) {
+    fn finalize_position(&mut self, pos_idx: usize, reason: CloseReason) {
         let now_str = self.now().to_rfc3339();
         let pos = &mut self.positions[pos_idx];
         pos.exit_price = pos.partial_exits.last().map(|pe| pe.price);
         pos.exit_time = Some(now_str);
-        pos.status = status;
+        pos.status = reason.status();
+        pos.close_reason = Some(reason);
+        pos.record_r();
 
         let closed_pos = pos.clone();
         self.trade_history.push(closed_pos);
@@ -472,15 +1578,36 @@ impl PaperTrader {
         self.update_trade_record(pos_idx);
     }
 
-    fn close_position(&mut self, pos_idx: usize, exit_price: f64, status: PositionStatus) {
+    /// Close the remainder at `exit_price` less exit slippage.
+    fn close_position(&mut self, pos_idx: usize, exit_price: f64, reason: CloseReason) {
+        self.close_position_with(pos_idx, exit_price, reason, true);
+    }
+
+    /// Close the remainder as a market order; `slipped` is false when
+    /// `exit_price` is already a real fill.
+    fn close_position_with(
+        &mut self,
+        pos_idx: usize,
+        exit_price: f64,
+        reason: CloseReason,
+        slipped: bool,
+    ) {
+        self.roll_daily_pnl();
         let now_str = self.now().to_rfc3339();
-        let fee_rate = self.fee_rate;
-        let pos = &mut self.positions[pos_idx];
-        let close_size = if pos.remaining_size_btc > 0.0 {
-            pos.remaining_size_btc
-        } else {
-            pos.size_btc
+        let close_size = {
+            let pos = &self.positions[pos_idx];
+            if pos.remaining_size_btc > 0.0 {
+                pos.remaining_size_btc
+            } else {
+                pos.size_btc
+            }
         };
+        let costs = self.fill_costs(false, Liquidity::Taker, close_size);
+        let fee_rate = costs.fee_rate;
+        let slippage_rate = if slipped { costs.slippage_rate } else { 0.0 };
+        let pos = &mut self.positions[pos_idx];
+        let (exit_price, slippage) =
+            slipped_exit(pos.direction, exit_price, close_size, slippage_rate);
 
         let pnl = match pos.direction {
             Direction::Long => (exit_price - pos.entry_price) * close_size,
@@ -492,9 +1619,12 @@ impl PaperTrader {
 
         pos.exit_price = Some(exit_price);
         pos.exit_time = Some(now_str);
-        pos.status = status;
+        pos.exit_slippage = slippage;
+        pos.status = reason.status();
+        pos.close_reason = Some(reason);
         pos.pnl = round2(pos.pnl + pnl);
         pos.remaining_size_btc = 0.0;
+        pos.record_r();
 
         self.balance += pnl;
         self.daily_pnl += pnl;
@@ -514,6 +1644,7 @@ impl PaperTrader {
                 "loss".to_string()
             };
             record.pnl = pos.pnl;
+            record.close_reason = pos.close_reason;
 
             if let (Ok(entry_dt), Some(exit_time)) = (
                 DateTime::parse_from_rfc3339(&pos.entry_time),
@@ -527,8 +1658,40 @@ impl PaperTrader {
         }
     }
 
+    /// Record `price` as the latest mark for `symbol`.
+    pub fn mark(&mut self, symbol: &str, price: f64) {
+        match self.marks.get_mut(symbol) {
+            Some(m) => *m = price,
+            None => {
+                self.marks.insert(symbol.to_string(), price);
+            }
+        }
+    }
+
+    /// PnL of the open remainder of every position at its symbol's last mark.
+    pub fn unrealized_pnl(&self) -> f64 {
+        self.positions
+            .iter()
+            .filter(|p| p.status == PositionStatus::Open)
+            .filter_map(|p| {
+                let mark = self.marks.get(&p.symbol)?;
+                let pnl = p.remaining_size_btc * (mark - p.entry_price);
+                Some(match p.direction {
+                    Direction::Long => pnl,
+                    Direction::Short => -pnl,
+                })
+            })
+            .sum()
+    }
+
+    /// Mark-to-market equity: balance plus unrealized PnL.
+    pub fn equity(&self) -> f64 {
+        self.balance + self.unrealized_pnl()
+    }
+
     pub fn get_stats(&mut self) -> TradingStats {
         let kelly = self.kelly.calculate(&self.trade_history, None);
+        let unrealized = self.unrealized_pnl();
         let open_count = self
             .positions
             .iter()
@@ -539,6 +1702,8 @@ impl PaperTrader {
             return TradingStats {
                 total_trades: 0,
                 balance: self.balance,
+                unrealized_pnl: round2(unrealized),
+                equity: round2(self.balance + unrealized),
                 win_rate: 0.0,
                 total_pnl: 0.0,
                 avg_win: 0.0,
@@ -553,6 +1718,7 @@ impl PaperTrader {
                 kelly_sample: kelly.sample_size,
                 kelly_win_rate: kelly.win_rate,
                 kelly_payoff: kelly.payoff_ratio,
+                r: None,
             };
         }
 
@@ -562,6 +1728,8 @@ impl PaperTrader {
         TradingStats {
             total_trades: self.trade_history.len(),
             balance: round2(self.balance),
+            unrealized_pnl: round2(unrealized),
+            equity: round2(self.balance + unrealized),
             win_rate: round1(wins.len() as f64 / self.trade_history.len() as f64 * 100.0),
             total_pnl: round2(self.trade_history.iter().map(|t| t.pnl).sum()),
             avg_win: if wins.is_empty() {
@@ -594,9 +1762,24 @@ impl PaperTrader {
             kelly_sample: kelly.sample_size,
             kelly_win_rate: kelly.win_rate,
             kelly_payoff: kelly.payoff_ratio,
+            r: RStats::from_multiples(self.trade_history.iter().filter_map(|t| t.r_multiple)),
         }
     }
 
+    /// Forward expectancy over the last `window` closed trades, optionally for one scale.
+    pub fn expectancy_forecast(&self, scale: Option<&str>, window: usize) -> Option<ExpectancyForecast> {
+        let trades: Vec<(DateTime<Utc>, f64)> = self
+            .trade_history
+            .iter()
+            .filter(|p| scale.is_none_or(|s| p.scale == s))
+            .filter_map(|p| {
+                let exit = DateTime::parse_from_rfc3339(p.exit_time.as_deref()?).ok()?;
+                Some((exit.with_timezone(&Utc), p.pnl))
+            })
+            .collect();
+        ExpectancyForecast::from_trades(&trades, window)
+    }
+
     pub fn get_kelly_by_scale(&mut self) -> HashMap<String, KellyResult> {
         let mut results = HashMap::new();
         for scale in &["1m", "5m", "15m"] {
@@ -606,67 +1789,122 @@ impl PaperTrader {
         results
     }
 
-    fn save_state(&self) {
-        let _ = fs::create_dir_all(Path::new(&self.trades_file).parent().unwrap_or(Path::new("logs")));
-
-        let state = serde_json::json!({
-            "balance": self.balance,
-            "trade_counter": self.trade_counter,
-            "daily_pnl": self.daily_pnl,
-            "daily_pnl_date": self.daily_pnl_date,
-            "positions": self.positions,
-            "trade_history": self.trade_history,
-        });
+    /// Trade records matching `query` from the store, oldest entry first
+    /// (in-memory records for a trader without one).
+    pub fn query_records(&self, query: &TradeQuery) -> anyhow::Result<Vec<TradeRecord>> {
+        match &self.store {
+            Some(store) => store.query_records(query),
+            None => Ok(storage::filter_records(&self.state(), query)),
+        }
+    }
 
-        if let Ok(json) = serde_json::to_string_pretty(&state) {
-            let _ = fs::write(&self.trades_file, json);
+    fn state(&self) -> TraderState {
+        TraderState {
+            balance: self.balance,
+            trade_counter: self.trade_counter,
+            daily_pnl: self.daily_pnl,
+            daily_pnl_date: self.daily_pnl_date.clone(),
+            positions: self.positions.clone(),
+            trade_history: self.trade_history.clone(),
+            trade_records: self.trade_records.clone(),
         }
+    }
 
-        if !self.trade_records.is_empty() {
-            if let Ok(json) = serde_json::to_string_pretty(&self.trade_records) {
-                let _ = fs::write(&self.records_file, json);
-            }
+    fn save_state(&self) {
+        let Some(store) = &self.store else {
+            return;
+        };
+        if let Err(e) = store.save_trader(&self.state()) {
+            tracing::warn!("Failed to save trader state: {:#}", e);
         }
     }
 
     fn load_state(&mut self, cfg: &Config) {
-        if let Ok(content) = fs::read_to_string(&self.trades_file) {
-            if let Ok(state) = serde_json::from_str::<serde_json::Value>(&content) {
-                self.balance = state["balance"].as_f64().unwrap_or(cfg.initial_balance);
-                self.trade_counter = state["trade_counter"].as_u64().unwrap_or(0);
-                self.daily_pnl = state["daily_pnl"].as_f64().unwrap_or(0.0);
-                self.daily_pnl_date = state["daily_pnl_date"]
-                    .as_str()
-                    .unwrap_or("")
-                    .to_string();
-
-                if let Ok(positions) =
-                    serde_json::from_value::<Vec<Position>>(state["positions"].clone())
-                {
-                    self.positions = positions;
-                }
-                if let Ok(history) =
-                    serde_json::from_value::<Vec<Position>>(state["trade_history"].clone())
-                {
-                    self.trade_history = history;
-                }
+        let Some(store) = &self.store else {
+            return;
+        };
+        match store.load_trader() {
+            Ok(Some(state)) => {
+                self.balance = state.balance;
+                self.trade_counter = state.trade_counter;
+                self.daily_pnl = state.daily_pnl;
+                self.daily_pnl_date = state.daily_pnl_date;
+                self.positions = state.positions;
+                self.trade_history = state.trade_history;
+                self.trade_records = state.trade_records;
             }
+            Ok(None) => {}
+            Err(e) => tracing::warn!("Could not load trader state: {:#}", e),
         }
 
-        if let Ok(content) = fs::read_to_string(&self.records_file) {
-            if let Ok(records) =
-                serde_json::from_str::<HashMap<u64, TradeRecord>>(&content)
-            {
-                self.trade_records = records;
+        // State files from before multi-symbol support
+        for p in self.positions.iter_mut().chain(self.trade_history.iter_mut()) {
+            if p.symbol.is_empty() {
+                p.symbol = cfg.symbol.clone();
+            }
+        }
+
+        // Trades from before scales and sessions were stored on positions:
+        // from the trade record, else a scale key named in the reason
+        for p in self
+            .positions
+            .iter_mut()
+            .chain(self.trade_history.iter_mut())
+        {
+            let metadata = self.trade_records.get(&p.id).map(|r| &r.metadata);
+            if p.scale.is_empty() {
+                p.scale = metadata.map(|m| m.scale.clone()).unwrap_or_else(|| {
+                    p.reason
+                        .split(|c: char| !c.is_ascii_alphanumeric())
+                        .find(|w| cfg.hft_scales.contains_key(*w))
+                        .unwrap_or_default()
+                        .to_string()
+                });
+            }
+            if p.session.is_empty() {
+                p.session = metadata.map(|m| m.session.clone()).unwrap_or_default();
+            }
+        }
+
+        // Closes from before close reasons were tracked: best guess from status
+        for p in self.trade_history.iter_mut() {
+            if p.close_reason.is_none() {
+                p.close_reason = match p.status {
+                    PositionStatus::ClosedTp => Some(CloseReason::TakeProfit),
+                    PositionStatus::ClosedSl => Some(CloseReason::StopLoss),
+                    PositionStatus::ClosedManual => Some(CloseReason::Manual),
+                    PositionStatus::ClosedExpired => Some(CloseReason::Expiry),
+                    PositionStatus::Pending | PositionStatus::Open | PositionStatus::Cancelled => {
+                        None
+                    }
+                };
+            }
+            if let Some(record) = self.trade_records.get_mut(&p.id) {
+                if record.close_reason.is_none() && !record.outcome.is_empty() {
+                    record.close_reason = p.close_reason;
+                }
             }
         }
     }
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TraderSnapshot {
+    pub balance: f64,
+    pub daily_pnl: f64,
+    pub open_positions: Vec<Position>,
+    pub total_trades: usize,
+    pub realized_pnl: f64,
+}
+
 #[derive(Debug, Clone)]
 pub struct TradingStats {
     pub total_trades: usize,
     pub balance: f64,
+    /// Open positions at their last checked price
+    pub unrealized_pnl: f64,
+    /// Balance plus unrealized PnL
+    pub equity: f64,
     pub win_rate: f64,
     pub total_pnl: f64,
     pub avg_win: f64,
@@ -681,52 +1919,101 @@ pub struct TradingStats {
     pub kelly_sample: usize,
     pub kelly_win_rate: f64,
     pub kelly_payoff: f64,
+    /// Closed trades in R; `None` before any trade with a known risk
+    pub r: Option<RStats>,
 }
 
 fn round1(x: f64) -> f64 {
     (x * 10.0).round() / 10.0
 }
+fn scale_max_hold(cfg: &Config) -> HashMap<String, i64> {
+    cfg.hft_scales
+        .iter()
+        .filter_map(|(key, s)| s.max_hold_minutes.map(|m| (key.clone(), m)))
+        .collect()
+}
+
+fn tp_allocs(cfg: &Config) -> HashMap<(String, String, bool), TpAllocation> {
+    let mut scales: Vec<&str> = cfg.hft_scales.keys().map(String::as_str).collect();
+    scales.push("");
+    let mut sessions: Vec<&str> = cfg
+        .killzone_profiles
+        .iter()
+        .filter(|(_, k)| k.tp_alloc_mode.is_some())
+        .map(|(name, _)| name.as_str())
+        .collect();
+    sessions.push("");
+    scales
+        .into_iter()
+        .flat_map(|scale| sessions.iter().map(move |&session| (scale, session)))
+        .flat_map(|(scale, session)| [false, true].map(|cisd| (scale, session, cisd)))
+        .map(|(scale, session, cisd)| {
+            (
+                (scale.to_string(), session.to_string(), cisd),
+                cfg.tp_allocation(scale, session, cisd).clone(),
+            )
+        })
+        .collect()
+}
+
+fn killzone_risk(cfg: &Config) -> HashMap<String, f64> {
+    cfg.killzone_profiles
+        .iter()
+        .filter_map(|(name, k)| k.max_risk_pct.map(|r| (name.clone(), r)))
+        .collect()
+}
+
+/// Exit fill after adverse slippage (longs sell lower, shorts buy back
+/// higher) and its cost in USD.
+fn slipped_exit(direction: Direction, price: f64, size: f64, rate: f64) -> (f64, f64) {
+    let fill = match direction {
+        Direction::Long => price * (1.0 - rate),
+        Direction::Short => price * (1.0 + rate),
+    };
+    (fill, (price - fill).abs() * size)
+}
+
 fn round2(x: f64) -> f64 {
     (x * 100.0).round() / 100.0
 }
-fn round8(x: f64) -> f64 {
-    (x * 1e8).round() / 1e8
-}
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::test_helpers::default_test_config;
-
-    fn test_config() -> Config {
-        let mut cfg = default_test_config();
-        // Use a unique temp dir for each test to avoid state leaking
-        cfg.log_dir = std::env::temp_dir()
-            .join(format!("ict_bot_test_{}", std::process::id()))
-            .to_string_lossy()
-            .to_string();
-        cfg
-    }
-
-    fn make_signal(direction: Direction, entry: f64, sl: f64, tp: f64) -> TradeSignal {
-        TradeSignal {
-            direction,
-            entry_price: entry,
-            stop_loss: sl,
-            take_profit: tp,
-            pda_engaged: None,
-            cisd_confirmed: false,
-            confidence: 0.7,
-            session: "london".to_string(),
-            session_weight: 1.5,
-            reason: "test signal 5m".to_string(),
-            tp_levels: None,
-        }
+    use crate::backtesting::report::PyramidStats;
+    use crate::test_helpers::{make_signal, unique_test_config};
+    use crate::trading::trade_record::TpLevelInfo;
+    use std::fs;
+
+    #[test]
+    fn low_priced_asset_sizes_to_lot_increments() {
+        let mut cfg = unique_test_config();
+        cfg.initial_balance = 1000.0;
+        let mut trader = PaperTrader::new(&cfg);
+        // DOGE-USD: 0.00001 tick, 0.1 lot
+        let signal = make_signal(Direction::Long, 0.08123, 0.07987, 0.08511);
+        let pos = trader
+            .open_position_for("DOGE-USD", &signal, "5m", None)
+            .unwrap()
+            .clone();
+        let lots = pos.size_btc / 0.1;
+        assert!((lots - lots.round()).abs() < 1e-9, "size {}", pos.size_btc);
+        assert!(pos.size_btc > 1000.0);
+        assert!((pos.size_usd - pos.size_btc * 0.08123).abs() < 0.01);
+
+        // Risk too small for a single lot: no position
+        let mut tiny = unique_test_config();
+        tiny.initial_balance = 0.001;
+        tiny.precision = Precision::parse_list("DOGE-USD:0.00001:1000").unwrap();
+        let mut trader = PaperTrader::new(&tiny);
+        assert!(trader
+            .open_position_for("DOGE-USD", &signal, "5m", None)
+            .is_none());
     }
 
     #[test]
     fn open_position_creates_correctly() {
-        let cfg = test_config();
+        let cfg = unique_test_config();
         let mut trader = PaperTrader::new(&cfg);
         let signal = make_signal(Direction::Long, 50000.0, 49500.0, 51000.0);
         let pos = trader.open_position(&signal, "5m", None);
@@ -741,7 +2028,7 @@ mod tests {
 
     #[test]
     fn check_positions_sl_hit_long() {
-        let cfg = test_config();
+        let cfg = unique_test_config();
         let mut trader = PaperTrader::new(&cfg);
         let signal = make_signal(Direction::Long, 50000.0, 49500.0, 51000.0);
         trader.open_position(&signal, "5m", None);
@@ -752,9 +2039,138 @@ mod tests {
         assert!(closed[0].pnl < 0.0);
     }
 
+    #[test]
+    fn r_multiple_recorded_at_close() {
+        let cfg = unique_test_config();
+        let mut trader = PaperTrader::new(&cfg);
+        let signal = make_signal(Direction::Long, 50000.0, 49500.0, 51000.0);
+        let size = trader.open_position(&signal, "5m", None).unwrap().size_btc;
+
+        let closed = trader.check_positions(49500.0);
+        let pos = &closed[0];
+        assert!((pos.initial_risk_usd - round2(500.0 * size)).abs() < 1e-9);
+        let r = pos.r_multiple.unwrap();
+        assert!((r - pos.pnl / pos.initial_risk_usd).abs() < 1e-9);
+        assert!((r + 1.0).abs() < 0.01, "full stop-out is -1R, got {}", r);
+
+        let stats = trader.get_stats().r.unwrap();
+        assert_eq!(stats.sample_size, 1);
+        assert_eq!(stats.expectancy_r, r);
+    }
+
+    #[test]
+    fn excursions_track_worst_and_best_checked_prices() {
+        let cfg = unique_test_config();
+        let mut trader = PaperTrader::new(&cfg);
+        let signal = make_signal(Direction::Short, 50000.0, 50500.0, 48000.0);
+        let id = trader.open_position(&signal, "5m", None).unwrap().id;
+
+        for price in [49600.0, 50200.0, 49900.0] {
+            assert!(trader.check_positions(price).is_empty());
+        }
+        let pos = trader.position(id).unwrap();
+        assert_eq!(pos.mae_price, Some(50200.0));
+        assert_eq!(pos.mfe_price, Some(49600.0));
+        let (mae, mfe) = pos.excursion_r().unwrap();
+        assert!((mae - 0.4).abs() < 1e-9 && (mfe - 0.8).abs() < 1e-9);
+
+        let closed = trader.check_positions(50600.0);
+        assert_eq!(closed[0].mae_price, Some(50600.0));
+        assert_eq!(closed[0].mfe_price, Some(49600.0));
+    }
+
+    #[test]
+    fn equity_marks_open_positions_to_last_checked_price() {
+        let cfg = unique_test_config();
+        let mut trader = PaperTrader::new(&cfg);
+        let signal = make_signal(Direction::Short, 50000.0, 50500.0, 48000.0);
+        let pos = trader.open_position(&signal, "5m", None).unwrap();
+        let size = pos.remaining_size_btc;
+        let balance = trader.balance;
+        assert_eq!(trader.unrealized_pnl(), 0.0);
+
+        assert!(trader.check_positions(49800.0).is_empty());
+        let expected = size * 200.0;
+        assert!((trader.unrealized_pnl() - expected).abs() < 1e-9);
+        assert!((trader.equity() - (balance + expected)).abs() < 1e-9);
+
+        let stats = trader.get_stats();
+        assert_eq!(stats.equity, round2(balance + expected));
+    }
+
+    #[test]
+    fn open_losses_trip_the_drawdown_guard() {
+        let mut cfg = unique_test_config();
+        cfg.max_drawdown = 0.05;
+        let mut trader = PaperTrader::new(&cfg);
+        let signal = make_signal(Direction::Long, 50000.0, 49500.0, 51000.0);
+        let size = trader.open_position(&signal, "5m", None).unwrap().size_btc;
+        assert_eq!(trader.update_drawdown_guard(), None);
+
+        // Unrealized loss past 5% of the balance, nothing realized yet
+        let balance = trader.balance();
+        trader.mark(&cfg.symbol, 50000.0 - balance * 0.06 / size);
+        let event = trader.update_drawdown_guard();
+        assert!(matches!(event, Some(BreakerEvent::Tripped { .. })));
+        assert_eq!(trader.balance(), balance);
+        assert!(!trader.can_open_position(&cfg));
+    }
+
+    #[test]
+    fn cisd_signals_pyramid_into_a_winner() {
+        let mut cfg = unique_test_config();
+        cfg.pyramid_max_adds = 1;
+        let mut trader = PaperTrader::new(&cfg);
+        let mut signal = make_signal(Direction::Long, 50000.0, 49500.0, 51000.0);
+        let parent = trader.open_position(&signal, "15m", None).unwrap().clone();
+
+        // In profit with CISD: a half-risk tranche with its own stop
+        signal.cisd_confirmed = true;
+        signal.entry_price = 50400.0;
+        signal.stop_loss = 49900.0;
+        let add = trader.open_position(&signal, "5m", None).unwrap().clone();
+        assert_eq!(add.parent_id, Some(parent.id));
+        assert_eq!(add.stop_loss, 49900.0);
+        assert!((add.size_btc - parent.size_btc / 2.0).abs() < 1e-6);
+        assert!(trader.can_open_position(&cfg));
+
+        // Adds used up: the next one is a position of its own
+        let third = trader.open_position(&signal, "1m", None).unwrap().clone();
+        assert_eq!(third.parent_id, None);
+
+        trader.check_positions(51000.0);
+        let stats = PyramidStats::from_history(&trader.trade_history).unwrap();
+        assert_eq!((stats.positions, stats.adds), (1, 1));
+        let closed_parent = trader.trade_history.iter().find(|p| p.id == parent.id);
+        let parent_pnl = closed_parent.unwrap().pnl;
+        assert!((stats.total_pnl - (parent_pnl + stats.add_pnl)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn hedge_policy_blocks_or_tracks_opposite_entries() {
+        let mut cfg = unique_test_config();
+        let long = make_signal(Direction::Long, 50000.0, 49500.0, 51000.0);
+        let short = make_signal(Direction::Short, 50000.0, 50500.0, 49000.0);
+
+        cfg.hedge_policy = HedgePolicy::Block;
+        let mut trader = PaperTrader::new(&cfg);
+        trader.open_position(&long, "15m", None).unwrap();
+        assert!(trader.open_position(&short, "1m", None).is_none());
+        assert!(trader.open_position(&long, "5m", None).is_some());
+
+        cfg.hedge_policy = HedgePolicy::Allow;
+        let mut trader = PaperTrader::new(&cfg);
+        let long_id = trader.open_position(&long, "15m", None).unwrap().id;
+        let short_id = trader.open_position(&short, "1m", None).unwrap().id;
+        // Each leg's stop moves by id only
+        trader.update_stop(long_id, 49800.0, StopAdjustReason::Trail);
+        assert_eq!(trader.position(long_id).unwrap().stop_loss, 49800.0);
+        assert_eq!(trader.position(short_id).unwrap().stop_loss, 50500.0);
+    }
+
     #[test]
     fn check_positions_tp_hit_long() {
-        let cfg = test_config();
+        let cfg = unique_test_config();
         let mut trader = PaperTrader::new(&cfg);
         let signal = make_signal(Direction::Long, 50000.0, 49500.0, 51000.0);
         trader.open_position(&signal, "5m", None);
@@ -767,7 +2183,7 @@ mod tests {
 
     #[test]
     fn check_positions_sl_hit_short() {
-        let cfg = test_config();
+        let cfg = unique_test_config();
         let mut trader = PaperTrader::new(&cfg);
         let signal = make_signal(Direction::Short, 50000.0, 50500.0, 49000.0);
         trader.open_position(&signal, "5m", None);
@@ -779,7 +2195,7 @@ mod tests {
 
     #[test]
     fn can_open_position_respects_max() {
-        let cfg = test_config();
+        let cfg = unique_test_config();
         let mut trader = PaperTrader::new(&cfg);
         // Open max_open_positions (3)
         for _ in 0..3 {
@@ -791,7 +2207,7 @@ mod tests {
 
     #[test]
     fn balance_updates_on_close() {
-        let cfg = test_config();
+        let cfg = unique_test_config();
         let mut trader = PaperTrader::new(&cfg);
         let initial_balance = trader.balance;
         let signal = make_signal(Direction::Long, 50000.0, 49500.0, 51000.0);
@@ -803,4 +2219,462 @@ mod tests {
         // Balance should have increased
         assert!(trader.balance > initial_balance);
     }
+
+    #[test]
+    fn legacy_state_gets_close_reasons_on_load() {
+        let cfg = unique_test_config();
+        let md: TradeMetadata = serde_json::from_value(serde_json::json!({
+            "scale": "5m", "direction": "long", "confidence": 0.7,
+            "session": "london", "session_weight": 1.5, "cisd_confirmed": false,
+        }))
+        .unwrap();
+        let mut trader = PaperTrader::new(&cfg);
+        let signal = make_signal(Direction::Long, 50000.0, 49500.0, 51000.0);
+        let id = trader.open_position(&signal, "5m", Some(md)).unwrap().id;
+        trader.check_positions(49400.0);
+
+        // Strip what older builds did not write
+        let strip = |path: &str, keys: &[&str]| {
+            let mut v: serde_json::Value =
+                serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap();
+            let mut stack = vec![&mut v];
+            while let Some(node) = stack.pop() {
+                match node {
+                    serde_json::Value::Object(obj) => {
+                        for k in keys {
+                            obj.remove(*k);
+                        }
+                        stack.extend(obj.values_mut());
+                    }
+                    serde_json::Value::Array(arr) => stack.extend(arr.iter_mut()),
+                    _ => {}
+                }
+            }
+            fs::write(path, v.to_string()).unwrap();
+        };
+        strip(&format!("{}/paper_trades.json", cfg.log_dir), &["close_reason"]);
+        strip(
+            &format!("{}/trade_records.json", cfg.log_dir),
+            &["close_reason", "schema_version"],
+        );
+
+        let reloaded = PaperTrader::new(&cfg);
+        assert_eq!(
+            reloaded.trade_history[0].close_reason,
+            Some(CloseReason::StopLoss)
+        );
+        let record = &reloaded.trade_records[&id];
+        assert_eq!(record.close_reason, Some(CloseReason::StopLoss));
+        assert_eq!(record.schema_version, SCHEMA_VERSION);
+    }
+
+    #[test]
+    fn per_scale_max_hold_expires_positions() {
+        let mut cfg = unique_test_config();
+        cfg.hft_scales.get_mut("5m").unwrap().max_hold_minutes = Some(30);
+        let mut trader = PaperTrader::new(&cfg);
+        let t0 = DateTime::parse_from_rfc3339("2024-01-15T12:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        trader.sim_time = Some(t0);
+        let signal = make_signal(Direction::Long, 50000.0, 49500.0, 51000.0);
+        let short_lived = trader.open_position(&signal, "5m", None).unwrap().id;
+        trader.open_position(&signal, "15m", None);
+
+        trader.sim_time = Some(t0 + chrono::Duration::minutes(31));
+        let closed = trader.check_positions(50100.0);
+        assert_eq!(closed.len(), 1);
+        assert_eq!(closed[0].id, short_lived);
+        assert_eq!(closed[0].status, PositionStatus::ClosedExpired);
+        assert_eq!(closed[0].close_reason, Some(CloseReason::Expiry));
+        assert_eq!(trader.open_positions().count(), 1);
+    }
+
+    #[test]
+    fn limit_entries_fill_on_retrace_or_expire() {
+        let mut cfg = unique_test_config();
+        cfg.fee_rate = 0.001;
+        let mut trader = PaperTrader::new_fresh(&cfg);
+        let t0 = DateTime::parse_from_rfc3339("2024-01-15T12:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        trader.sim_time = Some(t0);
+        let signal = make_signal(Direction::Long, 49800.0, 49500.0, 51000.0);
+        let expires = t0 + chrono::Duration::minutes(60);
+        let balance = trader.balance;
+        let id = trader
+            .place_limit_for(&cfg.symbol, &signal, "5m", None, expires)
+            .unwrap()
+            .id;
+        assert_eq!(trader.position(id).unwrap().status, PositionStatus::Pending);
+        assert!(!trader.is_flat());
+        assert_eq!(trader.balance, balance);
+
+        // Not retraced yet
+        assert!(trader.check_positions(50000.0).is_empty());
+        assert!(trader.take_limit_updates().is_empty());
+        assert_eq!(trader.open_positions().count(), 0);
+
+        // Traded through the limit: fills at the limit, costs paid now
+        trader.sim_time = Some(t0 + chrono::Duration::minutes(10));
+        assert!(trader.check_positions(49750.0).is_empty());
+        let filled = trader.take_limit_updates();
+        assert_eq!(filled.len(), 1);
+        assert_eq!(filled[0].status, PositionStatus::Open);
+        assert_eq!(filled[0].signal_price, 49800.0);
+        assert_eq!(filled[0].entry_time, trader.now().to_rfc3339());
+        assert!(trader.balance < balance);
+
+        // Never retraced before expiry
+        let id = trader
+            .place_limit_for(&cfg.symbol, &signal, "15m", None, expires)
+            .unwrap()
+            .id;
+        trader.sim_time = Some(expires);
+        trader.check_positions(50000.0);
+        let cancelled = trader.take_limit_updates();
+        assert_eq!(cancelled.len(), 1);
+        assert_eq!(cancelled[0].status, PositionStatus::Cancelled);
+        assert!(trader.position(id).is_none());
+        assert_eq!(trader.open_positions().count(), 1);
+
+        // Cancelled on demand (kill switch): reported once, not queued
+        let expires = expires + chrono::Duration::hours(1);
+        let id = trader
+            .place_limit_for(&cfg.symbol, &signal, "15m", None, expires)
+            .unwrap()
+            .id;
+        let cancelled = trader.cancel_limits(None);
+        assert_eq!(cancelled.len(), 1);
+        assert_eq!(cancelled[0].id, id);
+        assert!(trader.take_limit_updates().is_empty());
+        assert!(trader.cancel_limits(Some(&cfg.symbol)).is_empty());
+        assert_eq!(trader.open_positions().count(), 1);
+    }
+
+    #[test]
+    fn read_api_and_update_stop() {
+        let cfg = unique_test_config();
+        let mut trader = PaperTrader::new(&cfg);
+        let signal = make_signal(Direction::Long, 50000.0, 49500.0, 51000.0);
+        let id = trader.open_position(&signal, "5m", None).unwrap().id;
+
+        assert_eq!(trader.open_positions().count(), 1);
+        assert_eq!(trader.position(id).unwrap().stop_loss, 49500.0);
+
+        assert_eq!(
+            trader.update_stop(id, 49800.0, StopAdjustReason::Trail),
+            Some(49500.0)
+        );
+        assert_eq!(trader.position(id).unwrap().stop_loss, 49800.0);
+        assert_eq!(trader.update_stop(id + 1, 1.0, StopAdjustReason::Manual), None);
+
+        // Every move is kept for auditing; a no-op move is not recorded
+        trader.update_stop(id, 49800.0, StopAdjustReason::Trail);
+        let history = &trader.position(id).unwrap().stop_history;
+        assert_eq!(history.len(), 1);
+        assert_eq!(
+            (history[0].old_price, history[0].new_price),
+            (49500.0, 49800.0)
+        );
+        assert_eq!(history[0].reason, StopAdjustReason::Trail);
+
+        // Persisted with the position
+        let reloaded = PaperTrader::new(&cfg);
+        assert_eq!(reloaded.position(id).unwrap().stop_history.len(), 1);
+
+        // Tightened stop now triggers earlier
+        let closed = trader.check_positions(49790.0);
+        assert_eq!(closed.len(), 1);
+        assert_eq!(trader.open_positions().count(), 0);
+        assert_eq!(trader.update_stop(id, 49900.0, StopAdjustReason::Trail), None);
+
+        let snap = trader.snapshot();
+        assert_eq!(snap.total_trades, 1);
+        assert!(snap.open_positions.is_empty());
+        assert!(snap.realized_pnl < 0.0);
+    }
+
+    #[test]
+    fn exit_slippage_is_adverse_and_recorded_per_exit() {
+        let run = |rate: f64| {
+            let mut cfg = unique_test_config();
+            cfg.exit_slippage_rate = rate;
+            let mut trader = PaperTrader::new(&cfg);
+            let mut signal = make_signal(Direction::Short, 50000.0, 50500.0, 49000.0);
+            signal.tp_levels = Some(vec![
+                TpLevelInfo { label: "TP1".into(), price: 49500.0, pda_confluence: false, level: Some(-1.0), volume_confluence: false },
+                TpLevelInfo { label: "TP2".into(), price: 49000.0, pda_confluence: false, level: Some(-2.0), volume_confluence: false },
+            ]);
+            trader.open_position(&signal, "5m", None);
+            trader.check_positions(49400.0);
+            trader.check_positions(50600.0).remove(0)
+        };
+        let clean = run(0.0);
+        let slipped = run(0.001);
+
+        // Shorts buy back higher: both the partial and the stop fill worse
+        let partial = &slipped.partial_exits[0];
+        assert!((partial.price - 49400.0 * 1.001).abs() < 1e-6);
+        assert!((partial.slippage - 49.4 * partial.size_btc).abs() < 1e-6);
+        assert!((slipped.exit_price.unwrap() - 50500.0 * 1.001).abs() < 1e-6);
+        assert!(slipped.exit_slippage > 0.0);
+        assert_eq!(clean.exit_slippage, 0.0);
+
+        let cost = partial.slippage + slipped.exit_slippage;
+        assert!((clean.pnl - slipped.pnl - cost).abs() < 0.02);
+    }
+
+    #[test]
+    fn unlogged_partials_reported_once() {
+        let cfg = unique_test_config();
+        let mut trader = PaperTrader::new(&cfg);
+        let mut signal = make_signal(Direction::Long, 50000.0, 49500.0, 51000.0);
+        signal.tp_levels = Some(vec![
+            TpLevelInfo { label: "TP1".into(), price: 50500.0, pda_confluence: false, level: Some(-1.0), volume_confluence: false },
+            TpLevelInfo { label: "TP2".into(), price: 51000.0, pda_confluence: false, level: Some(-2.0), volume_confluence: false },
+        ]);
+        trader.open_position(&signal, "5m", None);
+        trader.check_positions(50600.0);
+
+        let partials = trader.take_unlogged_partials();
+        assert_eq!(partials.len(), 1);
+        assert!(trader.take_unlogged_partials().is_empty());
+    }
+
+    #[test]
+    fn close_all_flattens_open_positions() {
+        let cfg = unique_test_config();
+        let mut trader = PaperTrader::new(&cfg);
+        trader.open_position(&make_signal(Direction::Long, 50000.0, 49500.0, 51000.0), "5m", None);
+        trader.open_position(&make_signal(Direction::Short, 50000.0, 50500.0, 49000.0), "1m", None);
+
+        let closed = trader.close_all(50000.0);
+        assert_eq!(closed.len(), 2);
+        assert!(closed.iter().all(|p| p.status == PositionStatus::ClosedManual));
+        assert_eq!(trader.open_positions().count(), 0);
+        assert_eq!(trader.trade_history.len(), 2);
+        assert!(trader.close_all(50000.0).is_empty());
+    }
+
+    #[test]
+    fn positions_are_checked_per_symbol() {
+        let cfg = unique_test_config();
+        let mut trader = PaperTrader::new(&cfg);
+        let btc = make_signal(Direction::Long, 50000.0, 49500.0, 51000.0);
+        let eth = make_signal(Direction::Long, 3000.0, 2950.0, 3100.0);
+        trader.open_position(&btc, "5m", None);
+        trader.open_position_for("ETH-USD", &eth, "5m", None);
+
+        assert_eq!(trader.open_positions().next().unwrap().symbol, "BTC-USD");
+        assert_eq!(trader.open_by_symbol()["ETH-USD"], 1);
+
+        // ETH stop is hit at 2900; the BTC position must not see that price
+        let closed = trader.check_symbol_positions("ETH-USD", 2900.0);
+        assert_eq!(closed.len(), 1);
+        assert_eq!(closed[0].symbol, "ETH-USD");
+        assert_eq!(trader.open_positions_for("BTC-USD").count(), 1);
+        assert!(trader.close_symbol("ETH-USD", 2900.0).is_empty());
+    }
+
+    #[test]
+    fn funding_is_paid_by_longs_and_received_by_shorts() {
+        let cfg = unique_test_config();
+        let mut trader = PaperTrader::new_fresh(&cfg);
+        let long = make_signal(Direction::Long, 50000.0, 49000.0, 52000.0);
+        let short = make_signal(Direction::Short, 3000.0, 3100.0, 2800.0);
+        let long_size = trader.open_position(&long, "5m", None).unwrap().size_btc;
+        let short_size = trader
+            .open_position_for("ETH-USD", &short, "5m", None)
+            .unwrap()
+            .size_btc;
+        let balance = trader.balance;
+
+        let paid = trader.apply_funding("BTC-USD", 0.0001, 50000.0);
+        assert!((paid - long_size * 5.0).abs() < 1e-9);
+        let received = trader.apply_funding("ETH-USD", 0.0001, 3000.0);
+        assert!((received + short_size * 0.3).abs() < 1e-9);
+        assert!((trader.balance - (balance - paid - received)).abs() < 1e-9);
+
+        let closed = trader.close_all(50000.0);
+        let btc = closed.iter().find(|p| p.symbol == "BTC-USD").unwrap();
+        assert!((btc.funding - paid).abs() < 1e-9);
+        assert!((btc.pnl + paid).abs() < 0.01);
+    }
+
+    #[test]
+    fn candle_spanning_sl_and_tp_follows_ordering() {
+        // Down-closing candle: the OHLC path rallies through TP before the low
+        let bar = &crate::test_helpers::make_candles(&[(50000.0, 51200.0, 49400.0, 49800.0)])[0];
+        let signal = make_signal(Direction::Long, 50000.0, 49500.0, 51000.0);
+
+        let mut trader = PaperTrader::new(&unique_test_config());
+        trader.open_position(&signal, "5m", None);
+        let closed = trader.check_positions_with_candle(bar, IntrabarOrdering::SlFirst);
+        assert_eq!(closed.len(), 1);
+        assert_eq!(closed[0].status, PositionStatus::ClosedSl);
+
+        let mut trader = PaperTrader::new(&unique_test_config());
+        trader.open_position(&signal, "5m", None);
+        let closed = trader.check_positions_with_candle(bar, IntrabarOrdering::OhlcPath);
+        assert_eq!(closed.len(), 1);
+        assert_eq!(closed[0].status, PositionStatus::ClosedTp);
+        // Filled at the target, not at the candle high
+        assert!(closed[0].exit_price.unwrap() <= 51000.0);
+
+        assert_eq!(IntrabarOrdering::parse("OHLC"), Some(IntrabarOrdering::OhlcPath));
+        assert_eq!(IntrabarOrdering::parse("worst"), None);
+    }
+
+    #[test]
+    fn close_reasons_are_tagged_and_aggregated() {
+        use crate::trading::trade_analyzer::TradeAnalyzer;
+
+        let md: TradeMetadata = serde_json::from_value(serde_json::json!({
+            "scale": "5m", "direction": "long", "confidence": 0.7,
+            "session": "london", "session_weight": 1.5, "cisd_confirmed": false,
+        }))
+        .unwrap();
+        let mut trader = PaperTrader::new(&unique_test_config());
+        let signal = make_signal(Direction::Long, 50000.0, 49500.0, 51000.0);
+        let trailed = trader.open_position(&signal, "5m", Some(md.clone())).unwrap().id;
+        let fixed = trader.open_position(&signal, "5m", Some(md.clone())).unwrap().id;
+        trader.update_stop(trailed, 49800.0, StopAdjustReason::Trail);
+
+        let closed = trader.check_positions(49700.0);
+        assert_eq!(closed.len(), 1);
+        assert_eq!(closed[0].id, trailed);
+        assert_eq!(closed[0].close_reason, Some(CloseReason::TrailingStop));
+        assert_eq!(closed[0].status, PositionStatus::ClosedSl);
+
+        let closed = trader.check_positions(49400.0);
+        assert_eq!(closed[0].id, fixed);
+        assert_eq!(closed[0].close_reason, Some(CloseReason::StopLoss));
+
+        trader.open_position(&signal, "5m", Some(md));
+        let closed = trader.close_all(50000.0);
+        assert_eq!(closed[0].close_reason, Some(CloseReason::Manual));
+        assert_eq!(closed[0].status, PositionStatus::ClosedManual);
+
+        let records: Vec<TradeRecord> = trader.trade_records.values().cloned().collect();
+        let mix = TradeAnalyzer::exit_mix(&records);
+        assert_eq!(mix.len(), 3);
+        let trailing = mix.iter().find(|m| m.reason == "trailing_stop").unwrap();
+        assert_eq!(trailing.losses, 1);
+        assert!((trailing.pct_of_losers - 100.0 / 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn tags_follow_signals_into_history_queries_and_stats() {
+        let md: TradeMetadata = serde_json::from_value(serde_json::json!({
+            "scale": "5m", "direction": "long", "confidence": 0.7,
+            "session": "ny_forex", "session_weight": 1.5, "cisd_confirmed": false,
+        }))
+        .unwrap();
+        let mut trader = PaperTrader::new_fresh(&unique_test_config());
+        let mut signal = make_signal(Direction::Long, 50000.0, 49500.0, 51000.0);
+        signal.tags = vec!["silver_bullet".to_string(), "post_news".to_string()];
+        let tagged = trader.open_position(&signal, "5m", Some(md.clone()));
+        let tagged = tagged.unwrap().id;
+        trader.check_positions(49400.0);
+        signal.tags.clear();
+        trader.open_position(&signal, "5m", Some(md));
+        trader.check_positions(51100.0);
+
+        let sb = trader.history_filtered(|p| p.has_tag("silver_bullet"));
+        assert_eq!(sb.len(), 1);
+        assert_eq!(sb[0].id, tagged);
+        let query = TradeQuery {
+            tag: Some("post_news".to_string()),
+            ..Default::default()
+        };
+        let records = trader.query_records(&query).unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].metadata.tags, ["silver_bullet", "post_news"]);
+
+        let stats = trader.tag_stats(1);
+        let values: Vec<&str> = stats.iter().map(|b| b.value.as_str()).collect();
+        assert_eq!(values, ["post_news", "silver_bullet"]);
+        assert!(stats.iter().all(|b| b.total == 1 && b.losses == 1));
+    }
+
+    #[test]
+    fn stop_moves_to_breakeven_after_first_partial() {
+        let mut cfg = unique_test_config();
+        cfg.move_to_breakeven_after_tp1 = true;
+        let mut trader = PaperTrader::new(&cfg);
+        let mut signal = make_signal(Direction::Long, 50000.0, 49500.0, 51000.0);
+        signal.tp_levels = Some(vec![
+            TpLevelInfo { label: "TP1".into(), price: 50500.0, pda_confluence: false, level: Some(-1.0), volume_confluence: false },
+            TpLevelInfo { label: "TP2".into(), price: 51000.0, pda_confluence: false, level: Some(-2.0), volume_confluence: false },
+        ]);
+        let id = trader.open_position(&signal, "5m", None).unwrap().id;
+        assert!(trader.check_positions(50600.0).is_empty());
+
+        let pos = trader.position(id).unwrap();
+        assert_eq!(pos.stop_loss, pos.entry_price);
+        assert_eq!(pos.stop_history.len(), 1);
+        assert_eq!(pos.stop_history[0].old_price, 49500.0);
+        assert_eq!(pos.stop_history[0].reason, StopAdjustReason::BreakEven);
+
+        let closed = trader.check_positions(49990.0);
+        assert_eq!(closed.len(), 1);
+        assert_eq!(closed[0].close_reason, Some(CloseReason::BreakEven));
+        assert!(closed[0].pnl > 0.0);
+    }
+
+    #[test]
+    fn entry_improvement_tracks_slippage_vs_signal() {
+        let mut cfg = unique_test_config();
+        cfg.slippage_rate = 0.0005;
+        let mut trader = PaperTrader::new(&cfg);
+        let long = make_signal(Direction::Long, 50000.0, 49500.0, 51000.0);
+        let short = make_signal(Direction::Short, 50000.0, 50500.0, 49000.0);
+        let long_pos = trader.open_position(&long, "5m", None).unwrap().clone();
+        let short_pos = trader.open_position(&short, "5m", None).unwrap().clone();
+
+        // Market entries slip against the trade by the configured 5 bps:
+        // longs fill above the signal, shorts below, both a negative improvement
+        assert_eq!(long_pos.entry_price, 50025.0);
+        assert!((long_pos.entry_improvement_bps() + 5.0).abs() < 1e-6);
+        assert_eq!(short_pos.entry_price, 49975.0);
+        assert!((short_pos.entry_improvement_bps() + 5.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn large_entries_fill_across_levels_at_their_vwap() {
+        let mut cfg = unique_test_config();
+        cfg.initial_balance = 10_000.0;
+        cfg.sizing_mode = SizingMode::Fixed;
+        cfg.partial_fill_pct = 0.5;
+        cfg.partial_fill_step = 0.001;
+        let mut trader = PaperTrader::new(&cfg);
+        let signal = make_signal(Direction::Long, 50000.0, 49500.0, 51000.0);
+
+        // 0.2 BTC against a 0.1 BTC bar: four levels of 0.05
+        trader.bar_volume = Some(0.1);
+        let balance = trader.balance;
+        let pos = trader.open_position(&signal, "5m", None).unwrap().clone();
+        assert_eq!(pos.size_btc, 0.2);
+        assert_eq!(pos.entry_fills.len(), 4);
+        let fills = &pos.entry_fills;
+        assert!(fills.iter().all(|f| (f.size_btc - 0.05).abs() < 1e-12));
+        let vwap = fills.iter().map(|f| f.price * f.size_btc).sum::<f64>() / 0.2;
+        assert!((pos.entry_price - vwap).abs() < 1e-6);
+        let expected = 50000.0 * (1.0 + cfg.slippage_rate + 0.0015);
+        assert!((pos.entry_price - expected).abs() < 1e-6);
+        assert!((balance - trader.balance - pos.entry_costs).abs() < 1e-9);
+
+        // An entry that never filled refunds exactly what it was charged,
+        // level walk included, even once the bar has moved on
+        trader.bar_volume = None;
+        trader.discard_position(pos.id).unwrap();
+        assert!((trader.balance - balance).abs() < 1e-9);
+
+        // A bar deep enough for the whole order fills at one price
+        trader.bar_volume = Some(10.0);
+        let pos = trader.open_position(&signal, "5m", None).unwrap();
+        assert!(pos.entry_fills.is_empty());
+        assert!((pos.entry_price - 50000.0 * (1.0 + cfg.slippage_rate)).abs() < 1e-6);
+    }
 }
@@ -0,0 +1,123 @@
+use std::fmt;
+
+use crate::config::{Config, RiskLimits};
+use crate::models::WeeklyProfile;
+use crate::strategies::weekly_profiles::WeeklyBias;
+
+/// Risk posture derived from the weekly profile and its confidence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RiskRegime {
+    /// Undetermined profile or low confidence
+    Cautious,
+    #[default]
+    Normal,
+    /// High-confidence Classic Expansion
+    Expansion,
+}
+
+impl RiskRegime {
+    pub fn classify(bias: &WeeklyBias, cfg: &Config) -> Self {
+        if bias.profile == WeeklyProfile::Undetermined
+            || bias.confidence < cfg.regime_low_confidence
+        {
+            RiskRegime::Cautious
+        } else if bias.profile == WeeklyProfile::ClassicExpansion
+            && bias.confidence >= cfg.regime_high_confidence
+        {
+            RiskRegime::Expansion
+        } else {
+            RiskRegime::Normal
+        }
+    }
+
+    pub fn limits(self, cfg: &Config) -> RiskLimits {
+        match self {
+            RiskRegime::Cautious => cfg.cautious_limits,
+            RiskRegime::Normal => RiskLimits {
+                max_open_positions: cfg.max_open_positions,
+                max_risk_pct: cfg.max_risk_pct,
+            },
+            RiskRegime::Expansion => cfg.expansion_limits,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RiskRegime::Cautious => "cautious",
+            RiskRegime::Normal => "normal",
+            RiskRegime::Expansion => "expansion",
+        }
+    }
+}
+
+impl fmt::Display for RiskRegime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Direction, DrawOnLiquidity, Trend};
+    use crate::test_helpers::{default_test_config, make_signal};
+    use crate::trading::paper_trader::PaperTrader;
+
+    fn bias(profile: WeeklyProfile, confidence: f64) -> WeeklyBias {
+        WeeklyBias {
+            profile,
+            direction: Trend::Bullish,
+            confidence,
+            draw_on_liquidity: DrawOnLiquidity::Bsl,
+            tgif_active: false,
+            notes: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn regime_limits_cap_positions_and_risk() {
+        let mut cfg = default_test_config();
+        cfg.cautious_limits = RiskLimits {
+            max_open_positions: 1,
+            max_risk_pct: 0.01,
+        };
+        cfg.expansion_limits = RiskLimits {
+            max_open_positions: 5,
+            max_risk_pct: 0.03,
+        };
+
+        let classify = |p, c| RiskRegime::classify(&bias(p, c), &cfg);
+        assert_eq!(
+            classify(WeeklyProfile::Undetermined, 0.9),
+            RiskRegime::Cautious
+        );
+        assert_eq!(
+            classify(WeeklyProfile::MidweekReversal, 0.3),
+            RiskRegime::Cautious
+        );
+        assert_eq!(
+            classify(WeeklyProfile::MidweekReversal, 0.8),
+            RiskRegime::Normal
+        );
+        assert_eq!(
+            classify(WeeklyProfile::ClassicExpansion, 0.6),
+            RiskRegime::Normal
+        );
+        assert_eq!(
+            classify(WeeklyProfile::ClassicExpansion, 0.7),
+            RiskRegime::Expansion
+        );
+
+        let signal = make_signal(Direction::Long, 50000.0, 49000.0, 52000.0);
+        let mut trader = PaperTrader::new_fresh(&cfg);
+        trader.set_regime(RiskRegime::Cautious, &cfg);
+        let pos = trader.open_position(&signal, "5m", None).unwrap();
+        // Kelly default risk exceeds 1% of 200, so the regime cap binds
+        assert!((pos.size_btc * 1000.0 - 2.0).abs() < 1e-6);
+        assert!(!trader.can_open_position(&cfg));
+
+        trader.set_regime(RiskRegime::Expansion, &cfg);
+        assert!(trader.can_open_position(&cfg));
+        assert_eq!(trader.regime().to_string(), "expansion");
+    }
+}
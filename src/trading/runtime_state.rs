@@ -0,0 +1,192 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Datelike, Utc};
+use chrono_tz::US::Eastern;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::models::PositionStatus;
+use crate::strategies::weekly_profiles::WeeklyBias;
+use crate::trading::paper_trader::PaperTrader;
+
+/// Bot-level state for one symbol that the ledger doesn't hold.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SymbolRuntime {
+    /// Scale -> id of the position occupying it
+    pub scale_positions: BTreeMap<String, u64>,
+    /// Scale -> end of its post-close cooldown
+    pub scale_cooldown: BTreeMap<String, DateTime<Utc>>,
+    pub weekly_bias: Option<WeeklyBias>,
+}
+
+/// Scale slots, cooldowns and weekly bias per symbol, plus any drawdown
+/// breaker trip, checkpointed to `runtime_state.json` so a restart can't
+/// double-enter a scale or cut a cool-off short.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RuntimeState {
+    pub saved: Option<DateTime<Utc>>,
+    pub symbols: BTreeMap<String, SymbolRuntime>,
+    /// When the drawdown breaker tripped, so a restart keeps the cool-off
+    #[serde(default)]
+    pub breaker_tripped_at: Option<DateTime<Utc>>,
+}
+
+/// What `RuntimeState::validate` changed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Validation {
+    /// Slots whose position is gone, closed, or doesn't match the slot
+    pub stale_slots: usize,
+    /// Open positions that had no slot and were given one
+    pub restored_slots: usize,
+    pub expired_cooldowns: usize,
+    /// Biases from an earlier (ET) week
+    pub stale_biases: usize,
+}
+
+impl RuntimeState {
+    pub fn path(dir: &str) -> PathBuf {
+        Path::new(dir).join("runtime_state.json")
+    }
+
+    /// `None` if there is no checkpoint or it can't be read.
+    pub fn load(dir: &str) -> Option<Self> {
+        let path = Self::path(dir);
+        let content = fs::read_to_string(&path).ok()?;
+        match serde_json::from_str(&content) {
+            Ok(state) => Some(state),
+            Err(e) => {
+                tracing::warn!("Ignoring unreadable {}: {}", path.display(), e);
+                None
+            }
+        }
+    }
+
+    /// Write-then-rename, like the trade store.
+    pub fn save(&self, dir: &str) -> Result<()> {
+        fs::create_dir_all(dir).with_context(|| format!("creating {}", dir))?;
+        let path = Self::path(dir);
+        let tmp = path.with_extension("json.tmp");
+        fs::write(&tmp, serde_json::to_string_pretty(self)?)?;
+        fs::rename(&tmp, &path).with_context(|| format!("writing {}", path.display()))
+    }
+
+    /// Reconcile with the reloaded ledger: keep only slots held by a matching
+    /// open position or resting limit, give each of those its scale slot, and drop
+    /// expired cooldowns and biases from a previous week.
+    pub fn validate(&mut self, trader: &PaperTrader, now: DateTime<Utc>) -> Validation {
+        let mut v = Validation::default();
+        for (symbol, st) in &mut self.symbols {
+            let before = st.scale_positions.len();
+            st.scale_positions.retain(|scale, id| {
+                trader.position(*id).is_some_and(|p| {
+                    matches!(p.status, PositionStatus::Open | PositionStatus::Pending)
+                        && &p.symbol == symbol
+                        && &p.scale == scale
+                })
+            });
+            v.stale_slots += before - st.scale_positions.len();
+
+            let before = st.scale_cooldown.len();
+            st.scale_cooldown.retain(|_, until| *until > now);
+            v.expired_cooldowns += before - st.scale_cooldown.len();
+        }
+
+        for p in trader.open_positions().chain(trader.pending_positions()) {
+            let slots = &mut self
+                .symbols
+                .entry(p.symbol.clone())
+                .or_default()
+                .scale_positions;
+            if !slots.contains_key(&p.scale) {
+                slots.insert(p.scale.clone(), p.id);
+                v.restored_slots += 1;
+            }
+        }
+
+        let week = |t: DateTime<Utc>| t.with_timezone(&Eastern).iso_week();
+        let current_week = self.saved.is_some_and(|saved| week(saved) == week(now));
+        for st in self.symbols.values_mut() {
+            if !current_week && st.weekly_bias.take().is_some() {
+                v.stale_biases += 1;
+            }
+        }
+        v
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Direction;
+    use crate::test_helpers::{default_test_config, make_signal};
+    use chrono::Duration;
+
+    #[test]
+    fn round_trips_and_validates_against_the_ledger() {
+        let mut cfg = default_test_config();
+        let dir = std::env::temp_dir().join(format!("ict_runtime_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        cfg.log_dir = dir.to_string_lossy().to_string();
+
+        let mut trader = PaperTrader::new_fresh(&cfg);
+        let signal = make_signal(Direction::Long, 50000.0, 49500.0, 51000.0);
+        let id_5m = trader.open_position(&signal, "5m", None).unwrap().id;
+        let id_15m = trader.open_position(&signal, "15m", None).unwrap().id;
+
+        let now = Utc::now();
+        let bias = serde_json::from_value(serde_json::json!({
+            "profile": "classic_expansion", "direction": "bullish", "confidence": 0.6,
+            "draw_on_liquidity": "BSL", "tgif_active": false, "notes": [],
+        }))
+        .unwrap();
+        let state = RuntimeState {
+            saved: Some(now),
+            symbols: BTreeMap::from([(
+                cfg.symbol.clone(),
+                SymbolRuntime {
+                    // 1m points at a position that doesn't exist any more
+                    scale_positions: BTreeMap::from([
+                        ("5m".to_string(), id_5m),
+                        ("1m".to_string(), 99),
+                    ]),
+                    scale_cooldown: BTreeMap::from([
+                        ("1m".to_string(), now + Duration::minutes(5)),
+                        ("15m".to_string(), now - Duration::minutes(5)),
+                    ]),
+                    weekly_bias: Some(bias),
+                },
+            )]),
+            breaker_tripped_at: Some(now),
+        };
+        state.save(&cfg.log_dir).unwrap();
+        let mut loaded = RuntimeState::load(&cfg.log_dir).unwrap();
+        assert_eq!(loaded.breaker_tripped_at, Some(now));
+
+        let v = loaded.validate(&trader, now);
+        assert_eq!(
+            v,
+            Validation {
+                stale_slots: 1,
+                restored_slots: 1,
+                expired_cooldowns: 1,
+                stale_biases: 0,
+            }
+        );
+        let st = &loaded.symbols[&cfg.symbol];
+        assert_eq!(
+            st.scale_positions,
+            BTreeMap::from([("5m".to_string(), id_5m), ("15m".to_string(), id_15m)])
+        );
+        assert_eq!(st.scale_cooldown.len(), 1);
+
+        // A week later the bias no longer applies
+        assert_eq!(
+            loaded
+                .validate(&trader, now + Duration::days(7))
+                .stale_biases,
+            1
+        );
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,183 @@
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+use crate::config::fnv1a;
+
+/// How far past its due time a run may start before it counts as late
+/// (the bot loop ticks once a second).
+const LATE_TOLERANCE: Duration = Duration::from_secs(2);
+
+/// Run counts and timing for one scheduled task.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TaskStats {
+    pub runs: u64,
+    /// Runs that started more than `LATE_TOLERANCE` after they were due
+    pub late: u64,
+    /// Whole intervals skipped because a previous run or tick overran
+    pub missed: u64,
+    pub max_lateness: Duration,
+    pub last_latency: Duration,
+    pub max_latency: Duration,
+    total_latency: Duration,
+    timed: u32,
+}
+
+impl TaskStats {
+    pub fn avg_latency(&self) -> Duration {
+        if self.timed == 0 {
+            return Duration::ZERO;
+        }
+        self.total_latency / self.timed
+    }
+}
+
+#[derive(Debug)]
+struct Task {
+    interval: Duration,
+    next_due: Instant,
+    stats: TaskStats,
+}
+
+/// Fixed-interval task schedule with a deterministic per-task offset.
+///
+/// Each task name hashes to an offset in `[0, jitter)`, so the 1m, 5m and
+/// 15m scans of every symbol (and the data refresh) are spread out instead of
+/// all falling due on the same second, and the spread is the same on every
+/// restart.
+#[derive(Debug)]
+pub struct Scheduler {
+    jitter: Duration,
+    tasks: BTreeMap<String, Task>,
+}
+
+impl Scheduler {
+    pub fn new(jitter_secs: f64) -> Self {
+        Self {
+            jitter: Duration::from_secs_f64(jitter_secs.max(0.0)),
+            tasks: BTreeMap::new(),
+        }
+    }
+
+    /// Offset added to every due time of `name`.
+    pub fn offset(&self, name: &str) -> Duration {
+        let millis = self.jitter.as_millis() as u64;
+        if millis == 0 {
+            return Duration::ZERO;
+        }
+        Duration::from_millis(fnv1a(name.as_bytes()) % millis)
+    }
+
+    /// Register `name` (first due one interval plus its offset after `now`),
+    /// or update its interval if it changed.
+    pub fn schedule(&mut self, name: &str, interval: Duration, now: Instant) {
+        let offset = self.offset(name);
+        let task = self.tasks.entry(name.to_string()).or_insert_with(|| Task {
+            interval,
+            next_due: now + interval + offset,
+            stats: TaskStats::default(),
+        });
+        task.interval = interval;
+    }
+
+    /// True if `name` is due at `now`. Advances it to its next slot and
+    /// records lateness and skipped intervals.
+    pub fn poll(&mut self, name: &str, now: Instant) -> bool {
+        let Some(task) = self.tasks.get_mut(name) else {
+            return false;
+        };
+        if now < task.next_due {
+            return false;
+        }
+        let lateness = now - task.next_due;
+        let missed = if task.interval.is_zero() {
+            0
+        } else {
+            (lateness.as_nanos() / task.interval.as_nanos()) as u64
+        };
+        // Stay on the original phase so offsets don't drift into each other
+        task.next_due += task.interval * (missed as u32 + 1);
+        if task.next_due <= now {
+            task.next_due = now + task.interval;
+        }
+        let stats = &mut task.stats;
+        stats.runs += 1;
+        stats.missed += missed;
+        if lateness > LATE_TOLERANCE {
+            stats.late += 1;
+            if missed > 0 {
+                tracing::warn!(
+                    "{} ran {:.1}s late, {} run(s) skipped",
+                    name,
+                    lateness.as_secs_f64(),
+                    missed
+                );
+            } else {
+                tracing::debug!("{} ran {:.1}s late", name, lateness.as_secs_f64());
+            }
+        }
+        stats.max_lateness = stats.max_lateness.max(lateness);
+        true
+    }
+
+    /// Record how long the run started by the last `poll` took.
+    pub fn finished(&mut self, name: &str, latency: Duration) {
+        if let Some(task) = self.tasks.get_mut(name) {
+            let stats = &mut task.stats;
+            stats.last_latency = latency;
+            stats.max_latency = stats.max_latency.max(latency);
+            stats.total_latency += latency;
+            stats.timed += 1;
+        }
+    }
+
+    pub fn stats(&self, name: &str) -> Option<&TaskStats> {
+        self.tasks.get(name).map(|t| &t.stats)
+    }
+
+    /// Every task's stats, by name.
+    pub fn all_stats(&self) -> impl Iterator<Item = (&str, &TaskStats)> {
+        self.tasks.iter().map(|(k, t)| (k.as_str(), &t.stats))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spreads_tasks_and_counts_late_and_missed_runs() {
+        let sched = Scheduler::new(5.0);
+        let a = sched.offset("BTC-USD/1m");
+        let b = sched.offset("BTC-USD/5m");
+        assert_ne!(a, b);
+        assert!(a < Duration::from_secs(5) && b < Duration::from_secs(5));
+        assert_eq!(a, Scheduler::new(5.0).offset("BTC-USD/1m"));
+        assert_eq!(Scheduler::new(0.0).offset("BTC-USD/1m"), Duration::ZERO);
+
+        let mut sched = Scheduler::new(5.0);
+        let t0 = Instant::now();
+        let every = Duration::from_secs(60);
+        sched.schedule("scan", every, t0);
+        let due = t0 + every + sched.offset("scan");
+
+        assert!(!sched.poll("scan", due - Duration::from_millis(1)));
+        assert!(sched.poll("scan", due));
+        assert!(!sched.poll("scan", due + Duration::from_secs(1)));
+        sched.finished("scan", Duration::from_millis(300));
+
+        // A 150s stall: one late run, one slot missed, phase kept
+        assert!(sched.poll("scan", due + Duration::from_secs(150)));
+        assert!(!sched.poll("scan", due + Duration::from_secs(179)));
+        assert!(sched.poll("scan", due + Duration::from_secs(180)));
+        sched.finished("scan", Duration::from_millis(100));
+
+        let stats = sched.stats("scan").unwrap();
+        assert_eq!(stats.runs, 3);
+        assert_eq!(stats.late, 1);
+        assert_eq!(stats.missed, 1);
+        assert_eq!(stats.max_lateness, Duration::from_secs(90));
+        assert_eq!(stats.max_latency, Duration::from_millis(300));
+        assert_eq!(stats.avg_latency(), Duration::from_millis(200));
+        assert!(!sched.poll("unknown", due));
+    }
+}
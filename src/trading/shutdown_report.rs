@@ -0,0 +1,134 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+use crate::trading::paper_trader::Position;
+
+/// Final state of one account, written on shutdown or panic so post-mortems
+/// and restart checks don't depend on scrollback.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShutdownReport {
+    pub time: DateTime<Utc>,
+    /// `shutdown`, or `panic: <message>`
+    pub reason: String,
+    pub account: String,
+    pub version: String,
+    /// `Config::config_hash` of the effective config; differs when a restart
+    /// picks up new settings
+    pub config_revision: String,
+    pub balance: f64,
+    pub daily_pnl: f64,
+    pub open_positions: Vec<Position>,
+    /// Last M1 close per symbol
+    pub last_prices: BTreeMap<String, f64>,
+    /// `SYMBOL/scale` -> cooldown end
+    pub cooldowns: BTreeMap<String, DateTime<Utc>>,
+    pub kill_switch_active: bool,
+}
+
+impl ShutdownReport {
+    /// Write to `dir/shutdown_<account>_<UTC timestamp>.json`.
+    pub fn write(&self, dir: &str) -> Result<PathBuf> {
+        fs::create_dir_all(dir).with_context(|| format!("creating {}", dir))?;
+        let path = Path::new(dir).join(format!(
+            "shutdown_{}_{}.json",
+            self.account,
+            self.time.format("%Y%m%dT%H%M%SZ")
+        ));
+        let json = serde_json::to_string_pretty(self)?;
+        fs::write(&path, json).with_context(|| format!("writing {}", path.display()))?;
+        Ok(path)
+    }
+}
+
+/// Latest snapshot per account, kept for the panic hook.
+fn latest() -> &'static Mutex<HashMap<String, (String, ShutdownReport)>> {
+    static LATEST: OnceLock<Mutex<HashMap<String, (String, ShutdownReport)>>> = OnceLock::new();
+    LATEST.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Remember `report` as the account's latest state, to be written to `dir`
+/// if the process panics.
+pub fn record(dir: &str, report: ShutdownReport) {
+    if let Ok(mut map) = latest().lock() {
+        map.insert(report.account.clone(), (dir.to_string(), report));
+    }
+}
+
+/// Chain a panic hook that writes every recorded snapshot before the
+/// default handler runs.
+pub fn install_panic_hook() {
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let msg = info
+            .payload()
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| info.payload().downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "unknown".to_string());
+        let location = info
+            .location()
+            .map(|l| format!(" at {}:{}", l.file(), l.line()))
+            .unwrap_or_default();
+        // try_lock: a panic while the registry is held must not deadlock
+        if let Ok(map) = latest().try_lock() {
+            for (dir, report) in map.values() {
+                let mut report = report.clone();
+                report.time = Utc::now();
+                report.reason = format!("panic: {}{}", msg, location);
+                match report.write(dir) {
+                    Ok(path) => eprintln!("Crash report written to {}", path.display()),
+                    Err(e) => eprintln!("Failed to write crash report: {:#}", e),
+                }
+            }
+        }
+        previous(info);
+    }));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::default_test_config;
+
+    #[test]
+    fn writes_report_with_stable_config_revision() {
+        let mut cfg = default_test_config();
+        let rev = cfg.config_hash();
+        assert_eq!(rev, cfg.clone().config_hash());
+        cfg.fvg_min_gap_percent += 0.01;
+        assert_ne!(rev, cfg.config_hash());
+
+        let dir = std::env::temp_dir().join(format!("ict_shutdown_{}", std::process::id()));
+        let report = ShutdownReport {
+            time: Utc::now(),
+            reason: "shutdown".to_string(),
+            account: "main".to_string(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            config_revision: rev.clone(),
+            balance: 1000.0,
+            daily_pnl: -12.5,
+            open_positions: Vec::new(),
+            last_prices: BTreeMap::from([("BTC-USD".to_string(), 50_000.0)]),
+            cooldowns: BTreeMap::from([("BTC-USD/5m".to_string(), Utc::now())]),
+            kill_switch_active: false,
+        };
+        let path = report.write(dir.to_str().unwrap()).unwrap();
+        assert!(path
+            .file_name()
+            .unwrap()
+            .to_string_lossy()
+            .starts_with("shutdown_main_"));
+
+        let loaded: ShutdownReport =
+            serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(loaded.config_revision, rev);
+        assert_eq!(loaded.last_prices["BTC-USD"], 50_000.0);
+        assert_eq!(loaded.cooldowns.len(), 1);
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -1,9 +1,10 @@
-use chrono::Utc;
+use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
-use std::fs;
+use std::collections::HashMap;
 
 use crate::config::Config;
+use crate::storage::{self, RefinerState, TradeStore};
+use crate::trading::paper_trader::Position;
 use crate::trading::trade_analyzer::{BucketStats, TradeAnalyzer};
 use crate::trading::trade_record::TradeRecord;
 
@@ -40,19 +41,43 @@ impl Adjustment {
     }
 }
 
+/// A scale+session combo the refiner stopped trading.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkipEntry {
+    /// When the skip started (or was re-armed after a failed retry)
+    pub since: DateTime<Utc>,
+    pub edge: f64,
+    /// Bucket size when skipped; growth means retry trades have come in.
+    /// `None` for combos migrated from bare keys until the next refine.
+    #[serde(default)]
+    pub sample_size: Option<usize>,
+}
+
 // Hard floor/ceiling for each adjustable parameter
 const MIN_CONFIDENCE_FLOOR: f64 = 0.3;
 const MIN_CONFIDENCE_CEILING: f64 = 0.8;
 const SESSION_WEIGHT_FLOOR: f64 = 0.1;
 const SESSION_WEIGHT_CEILING: f64 = 2.0;
+/// Recent closed trades per scale that TP hit rates are measured over
+const TP_HIT_WINDOW: usize = 50;
+/// Hit-rate gap between two SD levels before allocation moves
+const TP_HIT_GAP: f64 = 0.2;
+/// Smallest share a level keeps
+const TP_SHARE_FLOOR: f64 = 0.05;
 
 pub struct StrategyRefiner {
     pub adjustment_step: f64,
     pub min_sample: usize,
     pub analyzer: TradeAnalyzer,
     pub adjustment_history: Vec<Adjustment>,
-    pub skip_combos: HashSet<String>,
-    refinements_file: String,
+    pub skip_combos: HashMap<String, SkipEntry>,
+    /// Simulated clock for backtests; `None` = wall clock
+    pub sim_time: Option<DateTime<Utc>>,
+    /// After this long a skipped combo is retried at reduced size
+    skip_expiry: Option<Duration>,
+    retry_size: f64,
+    /// Where state is persisted; `None` for in-memory refiners
+    store: Option<Box<dyn TradeStore>>,
 }
 
 impl StrategyRefiner {
@@ -62,13 +87,31 @@ impl StrategyRefiner {
             min_sample: cfg.min_sample_per_bucket,
             analyzer: TradeAnalyzer::new(cfg.min_sample_per_bucket),
             adjustment_history: Vec::new(),
-            skip_combos: HashSet::new(),
-            refinements_file: format!("{}/refinements.json", cfg.log_dir),
+            skip_combos: HashMap::new(),
+            sim_time: None,
+            skip_expiry: skip_expiry(cfg),
+            retry_size: cfg.skip_retry_size,
+            store: Some(storage::open(cfg)),
         };
         refiner.load_state();
         refiner
     }
 
+    /// Refiner that neither loads nor persists state (walk-forward / what-if runs).
+    pub fn in_memory(cfg: &Config) -> Self {
+        Self {
+            adjustment_step: cfg.adjustment_step,
+            min_sample: cfg.min_sample_per_bucket,
+            analyzer: TradeAnalyzer::new(cfg.min_sample_per_bucket),
+            adjustment_history: Vec::new(),
+            skip_combos: HashMap::new(),
+            sim_time: None,
+            skip_expiry: skip_expiry(cfg),
+            retry_size: cfg.skip_retry_size,
+            store: None,
+        }
+    }
+
     pub fn refine(
         &mut self,
         records: &[TradeRecord],
@@ -79,10 +122,11 @@ impl StrategyRefiner {
 
         adjustments.extend(self.adjust_min_confidence(&analysis, cfg));
         adjustments.extend(self.adjust_session_weights(&analysis, cfg));
-        self.update_skip_list(&analysis);
-        adjustments.extend(self.flag_stop_modes(&analysis));
+        let skips_changed = self.update_skip_list(&analysis);
+        adjustments.extend(self.flag_negative_edge(&analysis, "stop_mode", "stop mode"));
+        adjustments.extend(self.flag_negative_edge(&analysis, "tag", "tag"));
 
-        if !adjustments.is_empty() {
+        if !adjustments.is_empty() || skips_changed {
             self.adjustment_history.extend(adjustments.clone());
             self.save_state();
         }
@@ -90,14 +134,122 @@ impl StrategyRefiner {
         adjustments
     }
 
+    /// Move partial-TP allocation, per scale, from the SD level its recent
+    /// trades reach least often to the one they reach most often. Both
+    /// tables of a scale change, becoming its overrides.
+    pub fn refine_tp_allocation(
+        &mut self,
+        history: &[Position],
+        cfg: &mut Config,
+    ) -> Vec<Adjustment> {
+        let mut adjustments = Vec::new();
+        let mut scales: Vec<String> = cfg.hft_scales.keys().cloned().collect();
+        scales.sort();
+        for scale in scales {
+            let trades: Vec<&Position> = history
+                .iter()
+                .rev()
+                .filter(|p| p.scale == scale && !p.tp_targets.is_empty())
+                .take(TP_HIT_WINDOW)
+                .collect();
+            if trades.len() < self.min_sample {
+                continue;
+            }
+            let rates = tp_hit_rates(&trades);
+            let by_rate = |a: &&(f64, f64), b: &&(f64, f64)| a.1.total_cmp(&b.1);
+            let (Some(&(to, best)), Some(&(from, worst))) =
+                (rates.iter().max_by(by_rate), rates.iter().min_by(by_rate))
+            else {
+                continue;
+            };
+            if best - worst < TP_HIT_GAP {
+                continue;
+            }
+
+            for aggressive in [false, true] {
+                let scale_cfg = &cfg.hft_scales[&scale];
+                let (table, mut alloc) = if aggressive {
+                    let a = scale_cfg.tp_alloc_aggressive.as_ref();
+                    ("aggressive", a.unwrap_or(&cfg.tp_alloc_aggressive).clone())
+                } else {
+                    let a = scale_cfg.tp_alloc_conservative.as_ref();
+                    (
+                        "conservative",
+                        a.unwrap_or(&cfg.tp_alloc_conservative).clone(),
+                    )
+                };
+                let old = alloc.share(from).unwrap_or(0.0);
+                let moved = alloc.shift(from, to, self.adjustment_step, TP_SHARE_FLOOR);
+                if moved <= 0.0 {
+                    continue;
+                }
+                for level in &mut alloc.0 {
+                    level.1 = round4(level.1);
+                }
+                let new = alloc.share(from).unwrap_or(0.0);
+                let scale_cfg = cfg.hft_scales.get_mut(&scale).expect("scale listed above");
+                if aggressive {
+                    scale_cfg.tp_alloc_aggressive = Some(alloc);
+                } else {
+                    scale_cfg.tp_alloc_conservative = Some(alloc);
+                }
+                adjustments.push(Adjustment::new(
+                    format!("HFT_SCALES.{}.tp_alloc_{}[{}]", scale, table, from),
+                    old,
+                    new,
+                    format!(
+                        "scale {} SD {} hit {:.0}% vs SD {} {:.0}%, {:.2} moved to SD {}",
+                        scale,
+                        to,
+                        best * 100.0,
+                        from,
+                        worst * 100.0,
+                        moved,
+                        to
+                    ),
+                    best - worst,
+                    trades.len(),
+                ));
+            }
+        }
+
+        if !adjustments.is_empty() {
+            self.adjustment_history.extend(adjustments.clone());
+            self.save_state();
+        }
+        adjustments
+    }
+
+    fn now(&self) -> DateTime<Utc> {
+        self.sim_time.unwrap_or_else(Utc::now)
+    }
+
+    /// Skip has run its course; the combo trades again at reduced size.
+    pub fn is_expired(&self, entry: &SkipEntry) -> bool {
+        self.skip_expiry
+            .is_some_and(|expiry| self.now() - entry.since >= expiry)
+    }
+
+    /// Combo is skipped and its skip has not yet expired.
     pub fn should_skip(&self, scale: &str, session: &str) -> bool {
-        self.skip_combos.contains(&format!("{}_{}", scale, session))
+        self.skip_combos
+            .get(&format!("{}_{}", scale, session))
+            .is_some_and(|e| !self.is_expired(e))
+    }
+
+    /// Risk multiplier for a new trade: reduced while an expired skip is
+    /// being retried, 1.0 otherwise.
+    pub fn size_multiplier(&self, scale: &str, session: &str) -> f64 {
+        match self.skip_combos.get(&format!("{}_{}", scale, session)) {
+            Some(e) if self.is_expired(e) => self.retry_size,
+            _ => 1.0,
+        }
     }
 
     pub fn reset(&mut self) {
         self.adjustment_history.clear();
         self.skip_combos.clear();
-        let _ = fs::remove_file(&self.refinements_file);
+        self.save_state();
     }
 
     fn adjust_min_confidence(
@@ -193,43 +345,76 @@ impl StrategyRefiner {
         adjustments
     }
 
+    /// Returns whether the skip list changed.
     fn update_skip_list(
         &mut self,
         analysis: &std::collections::HashMap<String, std::collections::HashMap<String, BucketStats>>,
-    ) {
+    ) -> bool {
         let combo_stats = match analysis.get("scale_session") {
             Some(s) => s,
-            None => return,
+            None => return false,
         };
 
+        let now = self.now();
+        let mut changed = false;
         for (combo_key, bucket) in combo_stats {
+            // Migrated combos: retry trades count from the current bucket
+            if let Some(e) = self.skip_combos.get_mut(combo_key) {
+                if e.sample_size.is_none() {
+                    e.sample_size = Some(bucket.total);
+                    changed = true;
+                }
+            }
             if bucket.total >= 20 && bucket.edge < -0.15 {
-                self.skip_combos.insert(combo_key.clone());
-            } else if self.skip_combos.contains(combo_key) && bucket.edge >= 0.0 {
+                // Skip new offenders; re-arm a retried combo only once retry
+                // trades have come in and the edge is still bad
+                let arm = match self.skip_combos.get(combo_key) {
+                    Some(e) => self.is_expired(e) && Some(bucket.total) > e.sample_size,
+                    None => true,
+                };
+                if arm {
+                    self.skip_combos.insert(
+                        combo_key.clone(),
+                        SkipEntry {
+                            since: now,
+                            edge: bucket.edge,
+                            sample_size: Some(bucket.total),
+                        },
+                    );
+                    changed = true;
+                }
+            } else if self.skip_combos.contains_key(combo_key) && bucket.edge >= 0.0 {
                 self.skip_combos.remove(combo_key);
+                changed = true;
             }
         }
+        changed
     }
 
-    fn flag_stop_modes(
+    /// Warn about `dimension` buckets (stop modes, tags) with a clearly
+    /// negative edge; nothing is changed.
+    fn flag_negative_edge(
         &self,
         analysis: &std::collections::HashMap<String, std::collections::HashMap<String, BucketStats>>,
+        dimension: &str,
+        label: &str,
     ) -> Vec<Adjustment> {
         let mut adjustments = Vec::new();
-        let stop_stats = match analysis.get("stop_mode") {
+        let stats = match analysis.get(dimension) {
             Some(s) => s,
             None => return adjustments,
         };
 
-        for (mode, bucket) in stop_stats {
+        for (value, bucket) in stats {
             if bucket.sample_sufficient && bucket.edge < -0.1 {
                 adjustments.push(Adjustment::new(
-                    format!("WARNING:stop_mode.{}", mode),
+                    format!("WARNING:{}.{}", dimension, value),
                     0.0,
                     0.0,
                     format!(
-                        "stop mode '{}' has negative edge={:+.4} (n={}, wr={:.1}%)",
-                        mode,
+                        "{} '{}' has negative edge={:+.4} (n={}, wr={:.1}%)",
+                        label,
+                        value,
                         bucket.edge,
                         bucket.total,
                         bucket.win_rate * 100.0
@@ -244,38 +429,191 @@ impl StrategyRefiner {
     }
 
     fn save_state(&self) {
-        let state = serde_json::json!({
-            "adjustment_history": self.adjustment_history,
-            "skip_combos": self.skip_combos.iter().collect::<Vec<_>>(),
-        });
-
-        if let Some(parent) = std::path::Path::new(&self.refinements_file).parent() {
-            let _ = fs::create_dir_all(parent);
-        }
-        if let Ok(json) = serde_json::to_string_pretty(&state) {
-            let _ = fs::write(&self.refinements_file, json);
+        let Some(store) = &self.store else {
+            return;
+        };
+        let state = RefinerState {
+            adjustment_history: self.adjustment_history.clone(),
+            skip_combos: self.skip_combos.clone(),
+        };
+        if let Err(e) = store.save_refiner(&state) {
+            tracing::warn!("Failed to save refiner state: {:#}", e);
         }
     }
 
     fn load_state(&mut self) {
-        if let Ok(content) = fs::read_to_string(&self.refinements_file) {
-            if let Ok(state) = serde_json::from_str::<serde_json::Value>(&content) {
-                if let Ok(history) = serde_json::from_value::<Vec<Adjustment>>(
-                    state["adjustment_history"].clone(),
-                ) {
-                    self.adjustment_history = history;
-                }
-                if let Some(combos) = state["skip_combos"].as_array() {
-                    self.skip_combos = combos
-                        .iter()
-                        .filter_map(|v| v.as_str().map(|s| s.to_string()))
-                        .collect();
-                }
+        let Some(store) = &self.store else {
+            return;
+        };
+        match store.load_refiner() {
+            Ok(Some(state)) => {
+                self.adjustment_history = state.adjustment_history;
+                self.skip_combos = state.skip_combos;
             }
+            Ok(None) => {}
+            Err(e) => tracing::warn!("Could not load refiner state: {:#}", e),
         }
     }
 }
 
+fn skip_expiry(cfg: &Config) -> Option<Duration> {
+    (cfg.skip_expiry_days > 0.0)
+        .then(|| Duration::seconds((cfg.skip_expiry_days * 86_400.0) as i64))
+}
+
+/// Share of `trades` that reached each SD level they targeted, by level.
+fn tp_hit_rates(trades: &[&Position]) -> Vec<(f64, f64)> {
+    let mut counts: Vec<(f64, usize, usize)> = Vec::new();
+    for t in trades.iter().flat_map(|p| &p.tp_targets) {
+        let i = match counts.iter().position(|c| c.0 == t.level) {
+            Some(i) => i,
+            None => {
+                counts.push((t.level, 0, 0));
+                counts.len() - 1
+            }
+        };
+        counts[i].1 += usize::from(t.hit);
+        counts[i].2 += 1;
+    }
+    counts
+        .into_iter()
+        .map(|(level, hits, n)| (level, hits as f64 / n as f64))
+        .collect()
+}
+
 fn round4(x: f64) -> f64 {
     (x * 10000.0).round() / 10000.0
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::TpAllocation;
+    use crate::test_helpers::default_test_config;
+    use chrono::TimeZone;
+
+    fn record(id: u64, pnl: f64) -> TradeRecord {
+        serde_json::from_value(serde_json::json!({
+            "position_id": id,
+            "metadata": {
+                "scale": "5m", "direction": "long", "confidence": 0.6,
+                "session": "london", "session_weight": 1.0, "cisd_confirmed": false,
+            },
+            "outcome": if pnl > 0.0 { "win" } else { "loss" },
+            "pnl": pnl,
+        }))
+        .unwrap()
+    }
+
+    /// Closed 5m trade that reached the SD levels in `hits`.
+    fn tp_trade(id: u64, hits: &[f64]) -> Position {
+        let targets: Vec<_> = [-1.0, -2.0, -4.0, -4.5]
+            .iter()
+            .map(|&level| {
+                serde_json::json!({
+                    "level": level, "price": 0.0, "pct": 0.25, "size_btc": 0.0,
+                    "hit": hits.contains(&level),
+                })
+            })
+            .collect();
+        serde_json::from_value(serde_json::json!({
+            "id": id, "direction": "long", "entry_price": 100.0, "size_usd": 100.0,
+            "size_btc": 1.0, "stop_loss": 99.0, "take_profit": 104.5,
+            "entry_time": "2024-01-01T00:00:00Z", "reason": "", "scale": "5m",
+            "status": "closed_tp", "tp_targets": targets,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn tp_allocation_follows_hit_levels() {
+        let mut cfg = default_test_config();
+        assert!(TpAllocation::parse("-1:0.5,-2:0.6").is_none());
+        cfg.tp_alloc_aggressive = TpAllocation::parse("-1:0.25,-2:0.25,-4:0.25,-4.5:0.25").unwrap();
+        let mut refiner = StrategyRefiner::in_memory(&cfg);
+
+        let history: Vec<Position> = (0..30)
+            .map(|i| tp_trade(i, if i % 2 == 0 { &[-1.0, -2.0] } else { &[-1.0] }))
+            .collect();
+        let adjustments = refiner.refine_tp_allocation(&history, &mut cfg);
+        assert_eq!(adjustments.len(), 2);
+        assert_eq!(
+            adjustments[0].parameter,
+            "HFT_SCALES.5m.tp_alloc_conservative[-4]"
+        );
+
+        // Moved from SD -4 (never hit) to SD -1 (always hit), 5m only
+        let step = cfg.adjustment_step;
+        let scale = &cfg.hft_scales["5m"];
+        let conservative = scale.tp_alloc_conservative.as_ref().unwrap();
+        assert!((conservative.share(-1.0).unwrap() - (0.6 + step)).abs() < 1e-9);
+        assert!((conservative.share(-4.0).unwrap() - (0.1 - step)).abs() < 1e-9);
+        assert!(conservative.is_valid());
+        let aggressive = scale.tp_alloc_aggressive.as_ref().unwrap();
+        assert!((aggressive.share(-1.0).unwrap() - (0.25 + step)).abs() < 1e-9);
+        assert!(cfg.hft_scales["1m"].tp_alloc_conservative.is_none());
+        assert_eq!(cfg.tp_allocation("5m", "", false), conservative);
+    }
+
+    #[test]
+    fn skipped_combo_is_retried_after_expiry() {
+        let mut cfg = default_test_config();
+        let mut refiner = StrategyRefiner::in_memory(&cfg);
+        let t0 = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        refiner.sim_time = Some(t0);
+
+        let mut records: Vec<TradeRecord> = (0..20).map(|i| record(i, -10.0)).collect();
+        refiner.refine(&records, &mut cfg);
+        assert!(refiner.should_skip("5m", "london"));
+        assert_eq!(refiner.size_multiplier("5m", "london"), 1.0);
+
+        // Expired: trade again at reduced size; no new samples keeps it so
+        refiner.sim_time = Some(t0 + Duration::days(15));
+        assert!(!refiner.should_skip("5m", "london"));
+        assert_eq!(refiner.size_multiplier("5m", "london"), 0.5);
+        refiner.refine(&records, &mut cfg);
+        assert!(!refiner.should_skip("5m", "london"));
+
+        // Retry trades lose too: skipped again from now
+        records.extend((20..25).map(|i| record(i, -10.0)));
+        refiner.refine(&records, &mut cfg);
+        assert!(refiner.should_skip("5m", "london"));
+        assert_eq!(refiner.skip_combos["5m_london"].sample_size, Some(25));
+
+        // Edge recovers: combo cleared
+        records.extend((25..60).map(|i| record(i, 30.0)));
+        refiner.refine(&records, &mut cfg);
+        assert!(refiner.skip_combos.is_empty());
+        assert_eq!(refiner.size_multiplier("5m", "london"), 1.0);
+    }
+
+    #[test]
+    fn bare_key_skip_from_old_file_is_retried_after_expiry() {
+        let mut cfg = default_test_config();
+        let dir = std::env::temp_dir().join(format!("ict_refiner_legacy_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join("refinements.json"),
+            r#"{"adjustment_history": [], "skip_combos": ["5m_london"]}"#,
+        )
+        .unwrap();
+        cfg.log_dir = dir.to_string_lossy().to_string();
+
+        let mut refiner = StrategyRefiner::new(&cfg);
+        assert_eq!(refiner.skip_combos["5m_london"].sample_size, None);
+
+        // Expired with the old losing bucket: retried, not re-armed
+        refiner.sim_time = Some(Utc::now() + Duration::days(15));
+        let mut records: Vec<TradeRecord> = (0..20).map(|i| record(i, -10.0)).collect();
+        refiner.refine(&records, &mut cfg);
+        assert!(!refiner.should_skip("5m", "london"));
+        assert_eq!(refiner.skip_combos["5m_london"].sample_size, Some(20));
+
+        // Retry trades lose too: skipped again
+        records.extend((20..25).map(|i| record(i, -10.0)));
+        refiner.refine(&records, &mut cfg);
+        assert!(refiner.should_skip("5m", "london"));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
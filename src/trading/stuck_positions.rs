@@ -0,0 +1,171 @@
+use chrono::{DateTime, Utc};
+use std::collections::HashSet;
+
+use crate::config::Config;
+use crate::models::Direction;
+use crate::storage;
+use crate::trading::paper_trader::{PaperTrader, Position};
+
+/// Closed trades on a scale needed before their median hold is trusted
+const MIN_SAMPLES: usize = 5;
+/// Most recent closed trades the typical hold is taken from
+const RECENT_TRADES: usize = 50;
+/// Typical hold without enough history, in entry-timeframe bars
+const FALLBACK_BARS: u64 = 20;
+
+/// An open position held well past its scale's typical hold with price
+/// still pinned near entry.
+#[derive(Debug, Clone)]
+pub struct StuckPosition {
+    pub position: Position,
+    pub held_minutes: f64,
+    pub typical_minutes: f64,
+    /// See [`progress`]
+    pub progress: f64,
+}
+
+/// How far price has travelled toward the target (positive, 1.0 = TP) or
+/// the stop (negative, -1.0 = SL).
+pub fn progress(pos: &Position, price: f64) -> f64 {
+    let moved = match pos.direction {
+        Direction::Long => price - pos.entry_price,
+        Direction::Short => pos.entry_price - price,
+    };
+    let toward = if moved >= 0.0 {
+        (pos.take_profit - pos.entry_price).abs()
+    } else {
+        (pos.entry_price - pos.stop_loss).abs()
+    };
+    if toward > 0.0 {
+        moved / toward
+    } else {
+        0.0
+    }
+}
+
+/// Median hold (minutes) of the scale's recent closed trades, else
+/// `FALLBACK_BARS` of its entry timeframe.
+pub fn typical_hold_minutes(trader: &PaperTrader, scale: &str, cfg: &Config) -> Option<f64> {
+    let mut holds: Vec<f64> = trader
+        .history()
+        .iter()
+        .rev()
+        .filter(|p| p.scale == scale)
+        .filter_map(|p| {
+            let entry = storage::entry_time(p)?;
+            let exit = DateTime::parse_from_rfc3339(p.exit_time.as_deref()?).ok()?;
+            Some((exit.with_timezone(&Utc) - entry).num_seconds() as f64 / 60.0)
+        })
+        .take(RECENT_TRADES)
+        .collect();
+    if holds.len() >= MIN_SAMPLES {
+        holds.sort_by(f64::total_cmp);
+        return Some(holds[holds.len() / 2]);
+    }
+    let scale_cfg = cfg.hft_scales.get(scale)?;
+    Some((scale_cfg.entry_tf.as_seconds() * FALLBACK_BARS) as f64 / 60.0)
+}
+
+/// Stop moved halfway to entry, if that is tighter and still on the far
+/// side of `price`.
+pub fn tightened_stop(pos: &Position, price: f64) -> Option<f64> {
+    let stop = pos.stop_loss + (pos.entry_price - pos.stop_loss) / 2.0;
+    let valid = match pos.direction {
+        Direction::Long => stop > pos.stop_loss && stop < price,
+        Direction::Short => stop < pos.stop_loss && stop > price,
+    };
+    valid.then_some(stop)
+}
+
+/// Flags each stuck position once (STUCK_HOLD_MULTIPLE, STUCK_PROGRESS_BAND).
+#[derive(Debug, Default)]
+pub struct StuckDetector {
+    alerted: HashSet<u64>,
+}
+
+impl StuckDetector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Newly stuck positions of `symbol` at `price`.
+    pub fn check(
+        &mut self,
+        trader: &PaperTrader,
+        cfg: &Config,
+        symbol: &str,
+        price: f64,
+        now: DateTime<Utc>,
+    ) -> Vec<StuckPosition> {
+        self.alerted
+            .retain(|id| trader.open_positions().any(|p| p.id == *id));
+        if cfg.stuck_hold_multiple <= 0.0 {
+            return Vec::new();
+        }
+        let mut stuck = Vec::new();
+        for pos in trader.open_positions_for(symbol) {
+            if self.alerted.contains(&pos.id) {
+                continue;
+            }
+            let Some(entry) = storage::entry_time(pos) else {
+                continue;
+            };
+            let Some(typical) = typical_hold_minutes(trader, &pos.scale, cfg) else {
+                continue;
+            };
+            let held = (now - entry).num_seconds() as f64 / 60.0;
+            let progress = progress(pos, price);
+            if held >= typical * cfg.stuck_hold_multiple
+                && progress.abs() <= cfg.stuck_progress_band
+            {
+                self.alerted.insert(pos.id);
+                stuck.push(StuckPosition {
+                    position: pos.clone(),
+                    held_minutes: held,
+                    typical_minutes: typical,
+                    progress,
+                });
+            }
+        }
+        stuck
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::{default_test_config, make_signal};
+    use chrono::Duration;
+
+    #[test]
+    fn flags_positions_pinned_near_entry_long_past_typical_hold() {
+        let cfg = default_test_config();
+        let mut trader = PaperTrader::new_fresh(&cfg);
+        let start = DateTime::parse_from_rfc3339("2024-01-16T14:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        trader.sim_time = Some(start);
+        let signal = make_signal(Direction::Long, 50000.0, 49000.0, 52000.0);
+        let pos = trader.open_position(&signal, "5m", None).unwrap().clone();
+
+        // No history: 20 bars of 5m = 100 minutes typical
+        assert_eq!(typical_hold_minutes(&trader, "5m", &cfg), Some(100.0));
+        assert!((progress(&pos, 51000.0) - 0.5).abs() < 1e-9);
+        assert!((progress(&pos, 49500.0) + 0.5).abs() < 1e-9);
+
+        let mut detector = StuckDetector::new();
+        let early = start + Duration::minutes(200);
+        assert!(detector.check(&trader, &cfg, "BTC-USD", 50100.0, early).is_empty());
+        let late = start + Duration::minutes(301);
+        // Moving away from entry isn't stuck
+        assert!(detector.check(&trader, &cfg, "BTC-USD", 51000.0, late).is_empty());
+        let stuck = detector.check(&trader, &cfg, "BTC-USD", 50100.0, late);
+        assert_eq!(stuck.len(), 1);
+        assert_eq!(stuck[0].position.id, pos.id);
+        // Alerted once
+        assert!(detector.check(&trader, &cfg, "BTC-USD", 50100.0, late).is_empty());
+
+        assert_eq!(tightened_stop(&pos, 50100.0), Some(49500.0));
+        assert_eq!(tightened_stop(&pos, 49400.0), None);
+    }
+}
@@ -1,22 +1,35 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-use crate::trading::trade_record::TradeRecord;
+use crate::trading::trade_record::{label, TradeRecord};
 
 const DIMENSIONS: &[&str] = &[
     "scale",
+    "direction",
     "session",
     "day_of_week",
+    "market_regime",
     "cisd_status",
     "stop_mode",
     "pda_type",
+    "pda_zone",
     "confidence_bucket",
     "cross_scale_confluence",
     "weekly_profile",
+    "weekly_direction",
+    "risk_regime",
+    "sizing_mode",
     "tp_label",
     "scale_session",
+    "close_reason",
+    "tag",
+    "strategy",
 ];
 
+/// Setup dimensions the attribution table breaks down, so losing setups
+/// can be switched off.
+pub const ATTRIBUTION_DIMENSIONS: &[&str] = &["pda_type", "stop_mode", "cisd_status"];
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BucketStats {
     pub dimension: String,
@@ -32,6 +45,25 @@ pub struct BucketStats {
     pub sample_sufficient: bool,
 }
 
+/// How winners and losers split across exit causes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExitMix {
+    pub reason: String,
+    pub wins: usize,
+    pub losses: usize,
+    pub total_pnl: f64,
+    /// Share of all winning trades that exited this way (%)
+    pub pct_of_winners: f64,
+    /// Share of all losing trades that exited this way (%)
+    pub pct_of_losers: f64,
+}
+
+fn close_reason_key(record: &TradeRecord) -> String {
+    record
+        .close_reason
+        .map_or_else(|| "unknown".to_string(), |r| r.to_string())
+}
+
 pub struct TradeAnalyzer {
     pub min_sample: usize,
 }
@@ -60,6 +92,94 @@ impl TradeAnalyzer {
         results
     }
 
+    /// Exit-cause breakdown, e.g. what % of losers were trailing-stop exits.
+    /// Sorted by trade count, most common first.
+    pub fn exit_mix(records: &[TradeRecord]) -> Vec<ExitMix> {
+        let closed: Vec<&TradeRecord> = records
+            .iter()
+            .filter(|r| r.outcome == "win" || r.outcome == "loss")
+            .collect();
+        let total_wins = closed.iter().filter(|r| r.outcome == "win").count();
+        let total_losses = closed.len() - total_wins;
+
+        let mut by_reason: HashMap<String, ExitMix> = HashMap::new();
+        for r in closed {
+            let key = close_reason_key(r);
+            let mix = by_reason.entry(key.clone()).or_insert_with(|| ExitMix {
+                reason: key,
+                wins: 0,
+                losses: 0,
+                total_pnl: 0.0,
+                pct_of_winners: 0.0,
+                pct_of_losers: 0.0,
+            });
+            if r.outcome == "win" {
+                mix.wins += 1;
+            } else {
+                mix.losses += 1;
+            }
+            mix.total_pnl += r.pnl;
+        }
+
+        let mut out: Vec<ExitMix> = by_reason.into_values().collect();
+        for mix in &mut out {
+            if total_wins > 0 {
+                mix.pct_of_winners = mix.wins as f64 / total_wins as f64 * 100.0;
+            }
+            if total_losses > 0 {
+                mix.pct_of_losers = mix.losses as f64 / total_losses as f64 * 100.0;
+            }
+        }
+        out.sort_by(|a, b| {
+            (b.wins + b.losses)
+                .cmp(&(a.wins + a.losses))
+                .then_with(|| a.reason.cmp(&b.reason))
+        });
+        out
+    }
+
+    /// Edge per PDA type, stop mode and CISD status: grouped by dimension
+    /// in `ATTRIBUTION_DIMENSIONS` order, worst edge first within each.
+    pub fn attribution(&self, records: &[TradeRecord]) -> Vec<BucketStats> {
+        let closed: Vec<&TradeRecord> = records
+            .iter()
+            .filter(|r| r.outcome == "win" || r.outcome == "loss")
+            .collect();
+        let mut out = Vec::new();
+        for &dim in ATTRIBUTION_DIMENSIONS {
+            out.extend(self.ranked(&closed, dim));
+        }
+        out
+    }
+
+    /// Edge per tag; a trade with several tags counts in each. Worst first.
+    pub fn tag_stats(&self, records: &[TradeRecord]) -> Vec<BucketStats> {
+        self.dimension_stats(records, "tag")
+    }
+
+    /// Edge per value of one dimension (e.g. `strategy`), worst first.
+    pub fn dimension_stats(&self, records: &[TradeRecord], dimension: &str) -> Vec<BucketStats> {
+        let closed: Vec<&TradeRecord> = records
+            .iter()
+            .filter(|r| r.outcome == "win" || r.outcome == "loss")
+            .collect();
+        self.ranked(&closed, dimension)
+    }
+
+    /// Buckets of one dimension, worst edge first.
+    fn ranked(&self, records: &[&TradeRecord], dimension: &str) -> Vec<BucketStats> {
+        let mut buckets: Vec<BucketStats> = self
+            .analyze_dimension(records, dimension)
+            .into_values()
+            .collect();
+        buckets.sort_by(|a, b| {
+            a.edge
+                .total_cmp(&b.edge)
+                .then_with(|| a.value.cmp(&b.value))
+        });
+        buckets
+    }
+
     pub fn get_negative_edge_buckets(
         &self,
         analysis: &HashMap<String, HashMap<String, BucketStats>>,
@@ -96,7 +216,7 @@ impl TradeAnalyzer {
         let mut buckets: HashMap<String, Vec<&TradeRecord>> = HashMap::new();
 
         for r in records {
-            if let Some(key) = self.extract_key(r, dimension) {
+            for key in self.extract_keys(r, dimension) {
                 buckets.entry(key).or_default().push(r);
             }
         }
@@ -111,12 +231,22 @@ impl TradeAnalyzer {
         results
     }
 
+    /// Bucket keys of `record`: one per dimension, but one per tag for `tag`.
+    fn extract_keys(&self, record: &TradeRecord, dimension: &str) -> Vec<String> {
+        match dimension {
+            "tag" => record.metadata.tags.clone(),
+            _ => self.extract_key(record, dimension).into_iter().collect(),
+        }
+    }
+
     fn extract_key(&self, record: &TradeRecord, dimension: &str) -> Option<String> {
         let m = &record.metadata;
         match dimension {
             "scale" => Some(m.scale.clone()),
+            "direction" => Some(m.direction.to_string()),
             "session" => Some(m.session.clone()),
             "day_of_week" => Some(m.day_of_week.clone()),
+            "market_regime" => Some(label(m.market_regime)),
             "cisd_status" => Some(if m.cisd_confirmed {
                 "confirmed".to_string()
             } else {
@@ -132,6 +262,7 @@ impl TradeAnalyzer {
             } else {
                 m.pda_type.clone()
             }),
+            "pda_zone" => Some(label(m.pda_zone)),
             "confidence_bucket" => Some(if m.confidence >= 0.8 {
                 "high_0.8+".to_string()
             } else if m.confidence >= 0.6 {
@@ -147,12 +278,29 @@ impl TradeAnalyzer {
             } else {
                 m.weekly_profile.clone()
             }),
+            "weekly_direction" => Some(label(m.weekly_direction)),
+            "risk_regime" => Some(if m.risk_regime.is_empty() {
+                "unknown".to_string()
+            } else {
+                m.risk_regime.clone()
+            }),
+            "sizing_mode" => Some(if m.sizing_mode.is_empty() {
+                "unknown".to_string()
+            } else {
+                m.sizing_mode.clone()
+            }),
             "tp_label" => Some(if m.tp_label.is_empty() {
                 "unknown".to_string()
             } else {
                 m.tp_label.clone()
             }),
             "scale_session" => Some(format!("{}_{}", m.scale, m.session)),
+            "close_reason" => Some(close_reason_key(record)),
+            "strategy" => Some(if m.strategy.is_empty() {
+                "unknown".to_string()
+            } else {
+                m.strategy.clone()
+            }),
             _ => None,
         }
     }
@@ -233,3 +381,50 @@ impl TradeAnalyzer {
 fn round4(x: f64) -> f64 {
     (x * 10000.0).round() / 10000.0
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(pda_type: &str, stop_mode: &str, cisd: bool, pnl: f64) -> TradeRecord {
+        serde_json::from_value(serde_json::json!({
+            "position_id": 1,
+            "outcome": if pnl > 0.0 { "win" } else { "loss" },
+            "pnl": pnl,
+            "metadata": {
+                "scale": "5m", "direction": "long", "confidence": 0.6,
+                "session": "london", "session_weight": 1.5, "cisd_confirmed": cisd,
+                "pda_type": pda_type, "stop_mode": stop_mode
+            }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn attribution_ranks_setups_worst_edge_first_per_dimension() {
+        let records = vec![
+            record("FVG", "swing", true, 10.0),
+            record("FVG", "swing", true, -4.0),
+            record("OB", "atr", false, -5.0),
+            record("OB", "swing", false, -3.0),
+            record("", "", true, 6.0),
+        ];
+        let table = TradeAnalyzer::new(2).attribution(&records);
+        let mut dims: Vec<&str> = table.iter().map(|b| b.dimension.as_str()).collect();
+        dims.dedup();
+        assert_eq!(dims, ATTRIBUTION_DIMENSIONS);
+        assert_eq!(table.len(), 3 + 3 + 2);
+
+        let ob = &table[0];
+        assert_eq!((ob.value.as_str(), ob.total, ob.edge), ("OB", 2, -4.0));
+        assert!(ob.sample_sufficient);
+        assert_eq!(table[2].value, "none");
+        assert!(!table[2].sample_sufficient);
+
+        let cisd: Vec<(&str, f64)> = table[6..]
+            .iter()
+            .map(|b| (b.value.as_str(), b.total_pnl))
+            .collect();
+        assert_eq!(cisd, [("unconfirmed", -8.0), ("confirmed", 12.0)]);
+    }
+}
@@ -1,19 +1,36 @@
-use serde::{Deserialize, Serialize};
+use serde::de::{DeserializeOwned, Error};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::HashMap;
+
+use crate::core::pd_arrays::Pda;
+use crate::core::sessions::MarketRegime;
+use crate::core::structure::SwingPoint;
+use crate::models::{Candle, CloseReason, Direction, Timeframe, Trend, Zone};
+use crate::trading::paper_trader::{PartialExit, Position, StopAdjustment, TpTarget};
+
+/// On-disk schema of `TradeRecord`. Additive fields only need a serde
+/// default; bump this when old records need a step in `migrate`.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Fields this build does not know (written by a newer one), kept so
+/// rewriting the file does not drop them.
+pub type ExtraFields = serde_json::Map<String, serde_json::Value>;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TradeMetadata {
     pub scale: String,
-    pub direction: String,
+    #[serde(deserialize_with = "direction")]
+    pub direction: Direction,
     pub confidence: f64,
     pub session: String,
     pub session_weight: f64,
     pub cisd_confirmed: bool,
     #[serde(default)]
     pub pda_type: String,
-    #[serde(default)]
-    pub pda_direction: String,
-    #[serde(default)]
-    pub pda_zone: String,
+    #[serde(default, deserialize_with = "lenient")]
+    pub pda_direction: Option<Trend>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub pda_zone: Option<Zone>,
     #[serde(default)]
     pub pda_strength: f64,
     #[serde(default)]
@@ -28,20 +45,61 @@ pub struct TradeMetadata {
     pub alignment: Vec<AlignmentInfo>,
     #[serde(default)]
     pub weekly_profile: String,
-    #[serde(default)]
-    pub weekly_direction: String,
+    #[serde(default, deserialize_with = "lenient")]
+    pub weekly_direction: Option<Trend>,
     #[serde(default)]
     pub weekly_confidence: f64,
     #[serde(default)]
     pub day_of_week: String,
+    /// Weekday, weekend or holiday when the signal fired
+    #[serde(default, deserialize_with = "lenient")]
+    pub market_regime: Option<MarketRegime>,
     #[serde(default)]
     pub kelly_fraction: f64,
+    /// Weekly-profile risk regime the trade was sized under
+    #[serde(default)]
+    pub risk_regime: String,
+    /// Sizing mode the trade was sized with
+    #[serde(default)]
+    pub sizing_mode: String,
+    /// Tags of the signal (`TradeSignal::tags`)
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Strategy that produced the signal
+    #[serde(default)]
+    pub strategy: String,
+    #[serde(flatten)]
+    pub extra: ExtraFields,
 }
 
 fn default_one() -> usize {
     1
 }
 
+/// Enum fields were free-form strings before they were typed: any casing
+/// parses, and empty or unrecognised values read as `None`.
+fn lenient<'de, D, T>(d: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: DeserializeOwned,
+{
+    let s = Option::<String>::deserialize(d)?.unwrap_or_default();
+    let s = s.trim().to_lowercase();
+    if s.is_empty() {
+        return Ok(None);
+    }
+    Ok(serde_json::from_value(serde_json::Value::String(s)).ok())
+}
+
+/// Display value of an optional enum field, `unknown` when unset.
+pub fn label<T: std::fmt::Display>(value: Option<T>) -> String {
+    value.map_or_else(|| "unknown".to_string(), |v| v.to_string())
+}
+
+fn direction<'de, D: Deserializer<'de>>(d: D) -> Result<Direction, D::Error> {
+    lenient(d)?.ok_or_else(|| D::Error::custom("expected long or short"))
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TpLevelInfo {
     pub label: String,
@@ -50,6 +108,9 @@ pub struct TpLevelInfo {
     pub pda_confluence: bool,
     #[serde(default)]
     pub level: Option<f64>,
+    /// A session volume-profile level sits at this price
+    #[serde(default)]
+    pub volume_confluence: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -62,6 +123,9 @@ pub struct AlignmentInfo {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TradeRecord {
+    /// 0 = written before records were versioned
+    #[serde(default)]
+    pub schema_version: u32,
     pub position_id: u64,
     pub metadata: TradeMetadata,
     #[serde(default)]
@@ -70,4 +134,173 @@ pub struct TradeRecord {
     pub pnl: f64,
     #[serde(default)]
     pub hold_duration_seconds: f64,
+    /// Entry fill vs signal price in bps (positive = improvement, negative = slippage)
+    #[serde(default)]
+    pub entry_improvement_bps: f64,
+    #[serde(default)]
+    pub close_reason: Option<CloseReason>,
+    #[serde(flatten)]
+    pub extra: ExtraFields,
+}
+
+impl TradeRecord {
+    /// Bring a record written by an older build up to `SCHEMA_VERSION`.
+    /// Records from a newer build keep their version and unknown fields.
+    pub fn migrate(&mut self) {
+        if self.schema_version < 1 {
+            // v0 wrote free-form casing (direction is normalised on parse),
+            // and 0 for unset confluence
+            self.outcome = self.outcome.trim().to_lowercase();
+            self.metadata.cross_scale_confluence = self.metadata.cross_scale_confluence.max(1);
+        }
+        self.schema_version = self.schema_version.max(SCHEMA_VERSION);
+    }
+}
+
+/// A closed trade with the entry-timeframe candles around it and the PDAs
+/// and swings visible at entry, for review in an external chart tool.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TradeReplay {
+    pub position_id: u64,
+    pub symbol: String,
+    pub scale: String,
+    pub timeframe: Timeframe,
+    pub direction: Direction,
+    pub entry_time: String,
+    pub entry_price: f64,
+    pub exit_time: Option<String>,
+    pub exit_price: Option<f64>,
+    pub initial_stop_loss: f64,
+    /// Stop at exit, after any trailing
+    pub stop_loss: f64,
+    pub take_profit: f64,
+    pub tp_targets: Vec<TpTarget>,
+    pub partial_exits: Vec<PartialExit>,
+    pub stop_history: Vec<StopAdjustment>,
+    pub close_reason: Option<CloseReason>,
+    pub pnl: f64,
+    pub r_multiple: Option<f64>,
+    pub candles: Vec<Candle>,
+    pub pdas: Vec<Pda>,
+    pub swing_highs: Vec<SwingPoint>,
+    pub swing_lows: Vec<SwingPoint>,
+}
+
+impl TradeReplay {
+    /// Levels and exits of `pos` over `candles`; PDAs and swings start empty.
+    pub fn new(pos: &Position, timeframe: Timeframe, candles: Vec<Candle>) -> Self {
+        Self {
+            position_id: pos.id,
+            symbol: pos.symbol.clone(),
+            scale: pos.scale.clone(),
+            timeframe,
+            direction: pos.direction,
+            entry_time: pos.entry_time.clone(),
+            entry_price: pos.entry_price,
+            exit_time: pos.exit_time.clone(),
+            exit_price: pos.exit_price,
+            initial_stop_loss: pos.initial_stop_loss,
+            stop_loss: pos.stop_loss,
+            take_profit: pos.take_profit,
+            tp_targets: pos.tp_targets.clone(),
+            partial_exits: pos.partial_exits.clone(),
+            stop_history: pos.stop_history.clone(),
+            close_reason: pos.close_reason,
+            pnl: pos.pnl,
+            r_multiple: pos.r_multiple,
+            candles,
+            pdas: Vec::new(),
+            swing_highs: Vec::new(),
+            swing_lows: Vec::new(),
+        }
+    }
+}
+
+/// Parse a `trade_records.json` map and migrate every record. Entries that
+/// fail to parse are skipped and counted rather than losing the whole file.
+pub fn load_records(content: &str) -> serde_json::Result<(HashMap<u64, TradeRecord>, usize)> {
+    let raw: HashMap<u64, serde_json::Value> = serde_json::from_str(content)?;
+    let mut records = HashMap::with_capacity(raw.len());
+    let mut skipped = 0;
+    for (id, value) in raw {
+        match serde_json::from_value::<TradeRecord>(value) {
+            Ok(mut record) => {
+                record.migrate();
+                records.insert(id, record);
+            }
+            Err(_) => skipped += 1,
+        }
+    }
+    Ok((records, skipped))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `trade_records.json` as written before versioning and close reasons.
+    const V0_RECORDS: &str = r#"{
+        "7": {
+            "position_id": 7,
+            "metadata": {
+                "scale": "5m", "direction": "Long", "confidence": 0.72,
+                "session": "london", "session_weight": 1.5, "cisd_confirmed": true,
+                "pda_type": "FVG", "pda_direction": " Bullish", "pda_zone": "",
+                "weekly_direction": "sideways", "cross_scale_confluence": 0
+            },
+            "outcome": "WIN",
+            "pnl": 41.5,
+            "hold_duration_seconds": 900.0
+        },
+        "8": { "position_id": "not-a-number" }
+    }"#;
+
+    #[test]
+    fn loads_and_migrates_v0_records() {
+        let (records, skipped) = load_records(V0_RECORDS).unwrap();
+        assert_eq!(skipped, 1);
+        let r = &records[&7];
+        assert_eq!(r.schema_version, SCHEMA_VERSION);
+        assert_eq!(r.outcome, "win");
+        assert_eq!(r.metadata.direction, Direction::Long);
+        assert_eq!(r.metadata.pda_direction, Some(Trend::Bullish));
+        assert_eq!(r.metadata.pda_zone, None);
+        assert_eq!(r.metadata.weekly_direction, None);
+        assert_eq!(r.metadata.cross_scale_confluence, 1);
+        assert_eq!(r.close_reason, None);
+        assert!(r.metadata.tp_levels.is_empty());
+        assert!(r.extra.is_empty());
+
+        assert!(load_records("[]").is_err());
+    }
+
+    #[test]
+    fn newer_records_keep_version_and_unknown_fields() {
+        let json = r#"{
+            "3": {
+                "schema_version": 9,
+                "position_id": 3,
+                "metadata": {
+                    "scale": "1m", "direction": "short", "confidence": 0.6,
+                    "session": "ny_forex", "session_weight": 1.2, "cisd_confirmed": false,
+                    "setup_grade": "A+"
+                },
+                "outcome": "loss",
+                "pnl": -12.0,
+                "close_reason": "trailing_stop",
+                "mae_bps": 35.0
+            }
+        }"#;
+        let (records, skipped) = load_records(json).unwrap();
+        assert_eq!(skipped, 0);
+        let r = &records[&3];
+        assert_eq!(r.schema_version, 9);
+        assert_eq!(r.close_reason, Some(CloseReason::TrailingStop));
+
+        // Round-trips without losing what this build does not understand
+        let out = serde_json::to_value(r).unwrap();
+        assert_eq!(out["mae_bps"], 35.0);
+        assert_eq!(out["metadata"]["setup_grade"], "A+");
+        assert_eq!(out["schema_version"], 9);
+    }
 }
@@ -0,0 +1,203 @@
+use std::collections::HashMap;
+
+use crate::config::Config;
+use crate::core::stop_loss::StopLossEngine;
+use crate::models::{CandleSeries, Direction, PositionStatus, Precision, Timeframe};
+use crate::trading::paper_trader::{PaperTrader, Position};
+
+/// Stop move for one position, found by [`TrailingStops::check`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrailUpdate {
+    pub position_id: u64,
+    pub price: f64,
+    pub reason: String,
+}
+
+/// Trail progress of one position.
+#[derive(Debug, Clone, Copy)]
+struct TrailState {
+    activated: bool,
+    /// Most favourable price seen since tracking began
+    best_price: f64,
+}
+
+/// Trails each open position on its own state, keyed by position id
+/// (TRAIL_ACTIVATION_R, TRAIL_DISTANCE_PCT, TRAIL_TF).
+#[derive(Debug, Clone)]
+pub struct TrailingStops {
+    activation_r: f64,
+    distance_pct: f64,
+    timeframe: Option<Timeframe>,
+    swing_lookback: usize,
+    states: HashMap<u64, TrailState>,
+}
+
+impl TrailingStops {
+    pub fn new(cfg: &Config) -> Self {
+        Self {
+            activation_r: cfg.trail_activation_r,
+            distance_pct: cfg.trail_distance_pct,
+            timeframe: cfg.trail_timeframe,
+            swing_lookback: cfg.swing_lookback,
+            states: HashMap::new(),
+        }
+    }
+
+    /// Timeframe whose protected swings a position of `scale` trails behind.
+    pub fn timeframe_for(&self, scale: &str) -> Timeframe {
+        if let Some(tf) = self.timeframe {
+            return tf;
+        }
+        match scale {
+            "1m" => Timeframe::M1,
+            "15m" => Timeframe::M15,
+            _ => Timeframe::M5,
+        }
+    }
+
+    /// Stop moves for `symbol`'s open positions at `price`. Positions no
+    /// longer open are forgotten.
+    pub fn check(
+        &mut self,
+        trader: &PaperTrader,
+        symbol: &str,
+        price: f64,
+        data: &HashMap<Timeframe, CandleSeries>,
+        precision: Precision,
+    ) -> Vec<TrailUpdate> {
+        self.states.retain(|id, _| {
+            trader
+                .position(*id)
+                .is_some_and(|p| p.status == PositionStatus::Open)
+        });
+        trader
+            .open_positions_for(symbol)
+            .filter_map(|pos| self.trail(pos, price, data, precision))
+            .collect()
+    }
+
+    fn trail(
+        &mut self,
+        pos: &Position,
+        price: f64,
+        data: &HashMap<Timeframe, CandleSeries>,
+        precision: Precision,
+    ) -> Option<TrailUpdate> {
+        let state = self.states.entry(pos.id).or_insert(TrailState {
+            activated: false,
+            best_price: pos.entry_price,
+        });
+        state.best_price = match pos.direction {
+            Direction::Long => state.best_price.max(price),
+            Direction::Short => state.best_price.min(price),
+        };
+        if !state.activated {
+            let initial = if pos.initial_stop_loss > 0.0 {
+                pos.initial_stop_loss
+            } else {
+                pos.stop_loss
+            };
+            let moved = match pos.direction {
+                Direction::Long => state.best_price - pos.entry_price,
+                Direction::Short => pos.entry_price - state.best_price,
+            };
+            state.activated = moved >= self.activation_r * (pos.entry_price - initial).abs();
+            if !state.activated {
+                return None;
+            }
+        }
+        let best_price = state.best_price;
+
+        let mut candidates: Vec<(f64, String)> = Vec::new();
+        if let Some(candles) = data.get(&self.timeframe_for(&pos.scale)) {
+            let mut engine =
+                StopLossEngine::with_lookback(self.swing_lookback).with_precision(precision);
+            if let Some(level) =
+                engine.get_trailing_stop(pos.direction, pos.stop_loss, candles, None)
+            {
+                candidates.push((level.price, level.reason));
+            }
+        }
+        if self.distance_pct > 0.0 {
+            let stop = match pos.direction {
+                Direction::Long => best_price * (1.0 - self.distance_pct),
+                Direction::Short => best_price * (1.0 + self.distance_pct),
+            };
+            candidates.push((
+                precision.round_price(stop),
+                format!(
+                    "Trailing stop: {:.2}% behind best price {:.*}",
+                    self.distance_pct * 100.0,
+                    precision.price_decimals(),
+                    best_price
+                ),
+            ));
+        }
+
+        // Tightest level that improves the stop and stays beyond price
+        let valid = |stop: f64| match pos.direction {
+            Direction::Long => stop > pos.stop_loss && stop < price,
+            Direction::Short => stop < pos.stop_loss && stop > price,
+        };
+        let (stop, reason) = candidates
+            .into_iter()
+            .filter(|(stop, _)| valid(*stop))
+            .max_by(|a, b| match pos.direction {
+                Direction::Long => a.0.total_cmp(&b.0),
+                Direction::Short => b.0.total_cmp(&a.0),
+            })?;
+        Some(TrailUpdate {
+            position_id: pos.id,
+            price: stop,
+            reason,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::{default_test_config, make_signal};
+    use crate::trading::paper_trader::StopAdjustReason;
+
+    #[test]
+    fn positions_sharing_a_stop_trail_independently() {
+        let mut cfg = default_test_config();
+        cfg.trail_activation_r = 1.0;
+        cfg.trail_distance_pct = 0.01;
+        let mut trader = PaperTrader::new_fresh(&cfg);
+        let signal = |entry: f64| make_signal(Direction::Long, entry, 49000.0, 60000.0);
+        let early = trader
+            .open_position(&signal(50000.0), "5m", None)
+            .unwrap()
+            .id;
+        let late = trader
+            .open_position(&signal(51000.0), "15m", None)
+            .unwrap()
+            .id;
+        let precision = cfg.precision("BTC-USD");
+        let data = HashMap::new();
+
+        let mut trailing = TrailingStops::new(&cfg);
+        assert_eq!(trailing.timeframe_for("15m"), Timeframe::M15);
+        // 1R up on the first (1000 risk), not yet on the second (2000 risk)
+        let updates = trailing.check(&trader, "BTC-USD", 51000.0, &data, precision);
+        assert_eq!(updates.len(), 1);
+        assert_eq!(updates[0].position_id, early);
+        assert_eq!(updates[0].price, 50490.0);
+        trader.update_stop(early, updates[0].price, StopAdjustReason::Trail);
+
+        // The second activates once it is 1R up too
+        let updates = trailing.check(&trader, "BTC-USD", 53000.0, &data, precision);
+        let ids: Vec<u64> = updates.iter().map(|u| u.position_id).collect();
+        assert_eq!(ids, vec![early, late]);
+        for u in &updates {
+            assert_eq!(u.price, 52470.0);
+            trader.update_stop(u.position_id, u.price, StopAdjustReason::Trail);
+        }
+        // A pullback never loosens a stop
+        assert!(trailing
+            .check(&trader, "BTC-USD", 52600.0, &data, precision)
+            .is_empty());
+    }
+}
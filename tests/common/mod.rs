@@ -1,5 +1,8 @@
+#![allow(dead_code)]
+
 use chrono::{DateTime, Duration, Utc};
-use ict_trading_bot::models::{Candle, CandleSeries};
+use ict_trading_bot::models::{Candle, CandleSeries, Direction};
+use ict_trading_bot::strategies::signals::TradeSignal;
 
 /// Create candles from (open, high, low, close) tuples with auto-incrementing 1m timestamps.
 pub fn make_candles(data: &[(f64, f64, f64, f64)]) -> CandleSeries {
@@ -70,3 +73,23 @@ pub fn make_bearish_trend(n: usize, start: f64) -> CandleSeries {
 
     CandleSeries::new(candles)
 }
+
+/// A London 5m signal with the given levels and 0.7 confidence.
+pub fn make_signal(direction: Direction, entry: f64, sl: f64, tp: f64) -> TradeSignal {
+    TradeSignal {
+        direction,
+        entry_price: entry,
+        stop_loss: sl,
+        take_profit: tp,
+        pda_engaged: None,
+        cisd_confirmed: false,
+        confidence: 0.7,
+        session: "london".to_string(),
+        session_weight: 1.5,
+        reason: "test signal 5m".to_string(),
+        tp_levels: None,
+        size_multiplier: 1.0,
+        atr_percentile: None,
+        tags: Vec::new(),
+    }
+}
@@ -5,11 +5,13 @@ use async_trait::async_trait;
 use chrono::{DateTime, Duration, Utc};
 use std::collections::HashMap;
 
+use ict_trading_bot::bot::BotHarness;
 use ict_trading_bot::config::Config;
 use ict_trading_bot::core::sessions::SessionManager;
-use ict_trading_bot::exchange::Exchange;
+use ict_trading_bot::exchange::{Exchange, HistoricalExchange};
 use ict_trading_bot::models::{Candle, CandleSeries, Direction, PositionStatus, Timeframe};
 use ict_trading_bot::strategies::fractal_engine::FractalEngine;
+use ict_trading_bot::strategies::signals::TradeSignal;
 use ict_trading_bot::strategies::weekly_profiles::WeeklyProfileClassifier;
 use ict_trading_bot::trading::paper_trader::PaperTrader;
 
@@ -169,26 +171,24 @@ async fn full_pipeline_without_exchange() {
         Some(40000.0), // midnight open reference
         &session,
         &cfg,
+        &HashMap::new(), // no Kelly history yet
     );
     // Note: signals may or may not be generated depending on market conditions
     // This test validates the pipeline runs without panics
 
     // 5. Open a position via PaperTrader (manually, to test the trader)
     let mut trader = PaperTrader::new(&cfg);
-    let initial_balance = trader.balance;
-
-    let signal = ict_trading_bot::strategies::signals::TradeSignal {
-        direction: Direction::Long,
-        entry_price: current_price,
-        stop_loss: current_price - 500.0,
-        take_profit: current_price + 1000.0,
-        pda_engaged: None,
-        cisd_confirmed: false,
-        confidence: 0.7,
+    let initial_balance = trader.balance();
+
+    let signal = TradeSignal {
         session: session.current_session.clone(),
         session_weight: session.session_weight,
-        reason: "Integration test signal".to_string(),
-        tp_levels: None,
+        ..common::make_signal(
+            Direction::Long,
+            current_price,
+            current_price - 500.0,
+            current_price + 1000.0,
+        )
     };
 
     let pos = trader.open_position(&signal, "5m", None);
@@ -205,9 +205,9 @@ async fn full_pipeline_without_exchange() {
 
     // 7. Assert: balance increased, position closed
     assert!(
-        trader.balance > initial_balance,
+        trader.balance() > initial_balance,
         "Balance should increase after TP hit: {} vs {}",
-        trader.balance,
+        trader.balance(),
         initial_balance
     );
     assert!(
@@ -219,3 +219,57 @@ async fn full_pipeline_without_exchange() {
     // The fractal engine alignment check exercised: MarketStructure, PdArrayDetector,
     // CisdDetector, StdDevProjector, StopLossEngine across multiple timeframes
 }
+
+#[tokio::test]
+async fn bot_harness_stops_out_and_cools_down_the_scale() {
+    let mut cfg = test_config();
+    cfg.log_dir = format!("{}_harness", cfg.log_dir);
+    let _ = std::fs::remove_dir_all(&cfg.log_dir);
+    cfg.max_daily_loss = 0.001;
+
+    // Flat at 100 for an hour, then a flush through the stop two minutes in
+    let start = DateTime::parse_from_rfc3339("2024-01-17T15:00:00Z")
+        .unwrap()
+        .with_timezone(&Utc);
+    let m1: Vec<Candle> = (-60..10)
+        .map(|i| {
+            let close = if i >= 2 { 97.0 } else { 100.0 };
+            Candle {
+                timestamp: start + Duration::minutes(i),
+                open: 100.0,
+                high: 100.2,
+                low: close - 0.2,
+                close,
+                volume: 100.0,
+            }
+        })
+        .collect();
+    let mut market = HistoricalExchange::new();
+    market.load(Timeframe::M1, m1);
+
+    let mut harness =
+        BotHarness::new(cfg, vec![("BTC-USD".to_string(), market)], start).await;
+    let signal = TradeSignal {
+        session: "ny_indices".to_string(),
+        ..common::make_signal(Direction::Long, 100.0, 98.0, 104.0)
+    };
+    let id = harness.open_position("BTC-USD", "5m", &signal).unwrap();
+
+    harness.run_for(Duration::minutes(1), Duration::seconds(5)).await;
+    assert_eq!(harness.trader().position(id).unwrap().status, PositionStatus::Open);
+
+    harness.run_for(Duration::minutes(2), Duration::seconds(5)).await;
+    let pos = harness.trader().position(id).unwrap();
+    assert_eq!(pos.status, PositionStatus::ClosedSl);
+    assert_eq!(harness.scale_position("BTC-USD", "5m"), None);
+    let closed_at = DateTime::parse_from_rfc3339(pos.exit_time.as_deref().unwrap())
+        .unwrap()
+        .with_timezone(&Utc);
+    let cooldown = harness.cooldown_until("BTC-USD", "5m").unwrap();
+    assert!(cooldown > harness.now() && cooldown <= closed_at + Duration::hours(1));
+
+    // The stop-out breaches the daily loss limit
+    let cfg = harness.config().read().await.clone();
+    assert!(!harness.trader().can_open_position(&cfg));
+    harness.shutdown().await;
+}